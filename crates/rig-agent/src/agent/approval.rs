@@ -0,0 +1,431 @@
+//! Tool calls held for a decision made outside the run.
+//!
+//! A hook that returns [`ToolCallAction::Defer`] from
+//! [`on_tool_call`](crate::agent::AgentHook::on_tool_call) holds that call
+//! instead of running it. The other calls of the same batch still resolve as
+//! usual, then the run ends with
+//! [`PromptError::ApprovalRequired`], carrying [`PendingApprovals`]: the
+//! deferred calls plus everything needed to continue the run. Record a
+//! decision for each call and hand it back to
+//! [`AgentRunner::resume_approvals`](crate::agent::AgentRunner::resume_approvals):
+//!
+//! ```rust,no_run
+//! use rig_agent::agent::{Agent, AgentRunner, ApprovalDecision};
+//! use rig_agent::completion::PromptError;
+//!
+//! # async fn example(agent: Agent) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut pending = match agent.runner("Refund order 1042").run().await {
+//!     Ok(response) => return Ok(println!("{}", response.output)),
+//!     Err(PromptError::ApprovalRequired(pending)) => *pending,
+//!     Err(err) => return Err(err.into()),
+//! };
+//!
+//! // `PendingApprovals` is serializable, so it can be stored while a person
+//! // reviews the calls, possibly across several requests.
+//! let ids: Vec<String> = pending
+//!     .requests()
+//!     .iter()
+//!     .map(|request| request.internal_call_id.clone())
+//!     .collect();
+//! for id in ids {
+//!     pending.decide(&id, ApprovalDecision::approve());
+//! }
+//!
+//! let response = AgentRunner::resume_approvals(&agent, pending).run().await?;
+//! println!("{}", response.output);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A decision stands in for the `on_tool_call` hooks of its call, so the hook
+//! that deferred it is not consulted again; `on_tool_result` hooks still run.
+//! Calls left undecided are denied. Results of the calls that resolved before
+//! the pause are committed with the decided ones, without being surfaced as
+//! stream items again. Like a [`RunCheckpoint`](crate::agent::run::store::RunCheckpoint),
+//! the pending state carries the run's conversation so far but not per-run
+//! overrides or the hook scratchpad.
+
+use std::collections::HashMap;
+
+use rig_core::message::{ToolCall, UserContent};
+use serde::{Deserialize, Serialize};
+
+use super::{AgentRun, PendingToolCall, ToolCallAction};
+use crate::completion::{CompletionError, Message, PromptError};
+
+/// Reason fed back to the model for a deferred call that was never decided.
+const NOT_APPROVED: &str = "tool call was not approved";
+
+/// A decision on a deferred tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ApprovalDecision {
+    /// Run the tool with the arguments it was deferred with.
+    Approve,
+    /// Do not run the tool; return this feedback to the model.
+    Deny(String),
+    /// Run the tool with these arguments instead.
+    Edit(serde_json::Value),
+}
+
+impl ApprovalDecision {
+    /// Creates a decision that runs the tool as requested.
+    pub fn approve() -> Self {
+        Self::Approve
+    }
+
+    /// Creates a decision that skips the tool and returns `reason` to the model.
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Self::Deny(reason.into())
+    }
+
+    /// Creates a decision that runs the tool with replacement arguments.
+    pub fn edit(args: impl Into<serde_json::Value>) -> Self {
+        Self::Edit(args.into())
+    }
+}
+
+/// One tool call awaiting a decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// The tool call as the model emitted it.
+    pub tool_call: ToolCall,
+    /// Arguments the tool runs with once approved: the model's, or a rewrite
+    /// applied by a hook before the deferring one.
+    pub args: serde_json::Value,
+    /// Rig-generated identifier of the call, matching the `internal_call_id`
+    /// of its stream items. Decisions are keyed by it.
+    pub internal_call_id: String,
+    /// Why the hook deferred the call.
+    pub reason: String,
+    /// The decision recorded so far, if any.
+    pub decision: Option<ApprovalDecision>,
+    /// Position of the call within its tool batch.
+    index: usize,
+}
+
+impl ApprovalRequest {
+    pub(crate) fn new(
+        index: usize,
+        tool_call: ToolCall,
+        args: serde_json::Value,
+        internal_call_id: String,
+        reason: String,
+    ) -> Self {
+        Self {
+            tool_call,
+            args,
+            internal_call_id,
+            reason,
+            decision: None,
+            index,
+        }
+    }
+
+    /// The tool-call action the recorded decision resolves to.
+    fn action(&self) -> ToolCallAction {
+        match &self.decision {
+            Some(ApprovalDecision::Approve) if self.args == self.tool_call.function.arguments => {
+                ToolCallAction::Run
+            }
+            Some(ApprovalDecision::Approve) => ToolCallAction::Rewrite(self.args.clone()),
+            Some(ApprovalDecision::Deny(reason)) => ToolCallAction::Skip(reason.clone()),
+            Some(ApprovalDecision::Edit(args)) => ToolCallAction::Rewrite(args.clone()),
+            None => ToolCallAction::Skip(NOT_APPROVED.to_string()),
+        }
+    }
+}
+
+/// A run paused on deferred tool calls, returned in
+/// [`PromptError::ApprovalRequired`].
+///
+/// See the [module docs](self) for the approval flow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApprovals {
+    requests: Vec<ApprovalRequest>,
+    /// Results of the batch's other calls, by position; `None` for a
+    /// deferred call.
+    settled: Vec<Option<UserContent>>,
+    prompt: Message,
+    conversation_id: Option<String>,
+    appends_to_memory: bool,
+    run: AgentRun,
+}
+
+impl PendingApprovals {
+    pub(crate) fn new(
+        mut requests: Vec<ApprovalRequest>,
+        settled: Vec<Option<UserContent>>,
+        prompt: Message,
+        conversation_id: Option<String>,
+        appends_to_memory: bool,
+        run: AgentRun,
+    ) -> Self {
+        // Concurrent batches settle out of order.
+        requests.sort_by_key(|request| request.index);
+        Self {
+            requests,
+            settled,
+            prompt,
+            conversation_id,
+            appends_to_memory,
+            run,
+        }
+    }
+
+    /// The deferred tool calls, in call order.
+    pub fn requests(&self) -> &[ApprovalRequest] {
+        &self.requests
+    }
+
+    /// Record `decision` for the call with `internal_call_id`, replacing any
+    /// earlier decision. Returns `false` when no such call is pending.
+    pub fn decide(&mut self, internal_call_id: &str, decision: ApprovalDecision) -> bool {
+        match self
+            .requests
+            .iter_mut()
+            .find(|request| request.internal_call_id == internal_call_id)
+        {
+            Some(request) => {
+                request.decision = Some(decision);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether every deferred call has a decision.
+    pub fn is_decided(&self) -> bool {
+        self.requests
+            .iter()
+            .all(|request| request.decision.is_some())
+    }
+
+    /// The paused run.
+    pub fn run(&self) -> &AgentRun {
+        &self.run
+    }
+
+    /// The conversation the run belongs to, if any.
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
+    pub(crate) fn prompt(&self) -> &Message {
+        &self.prompt
+    }
+
+    pub(crate) fn appends_to_memory(&self) -> bool {
+        self.appends_to_memory
+    }
+
+    /// Fold the decisions into the paused batch: calls that already settled
+    /// become preresolved, and each deferred call is keyed to the action its
+    /// decision resolves to in `actions`.
+    pub(crate) fn resolve_calls(
+        self,
+        calls: Vec<PendingToolCall>,
+        actions: &mut HashMap<String, ToolCallAction>,
+    ) -> Result<Vec<PendingToolCall>, PromptError> {
+        if calls.len() != self.settled.len() {
+            return Err(stale_approvals());
+        }
+        let mut requests = self.requests.into_iter();
+        let mut resolved = Vec::with_capacity(calls.len());
+        for (index, (mut call, settled)) in calls.into_iter().zip(self.settled).enumerate() {
+            match settled {
+                Some(result) => call.preresolved_result = Some(result),
+                None => {
+                    let request = requests
+                        .find(|request| request.index == index)
+                        .filter(|request| request.tool_call.id == call.tool_call.id)
+                        .ok_or_else(stale_approvals)?;
+                    actions.insert(request.internal_call_id.clone(), request.action());
+                    call.internal_call_id = Some(request.internal_call_id);
+                }
+            }
+            resolved.push(call);
+        }
+        Ok(resolved)
+    }
+}
+
+fn stale_approvals() -> PromptError {
+    PromptError::CompletionError(CompletionError::ResponseError(
+        "pending approvals do not match the run's pending tool calls".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use futures::StreamExt;
+    use rig_core::message::{AssistantContent, ToolResult, ToolResultContent, UserContent};
+    use serde_json::json;
+
+    use super::{ApprovalDecision, NOT_APPROVED, PendingApprovals};
+    use crate::{
+        agent::{
+            AgentBuilder, AgentHook, AgentRunner, HookContext, MultiTurnStreamItem, ToolCall,
+            ToolCallAction,
+        },
+        completion::{Message, PromptError, Usage},
+        test_utils::{
+            MockAddTool, MockCompletionModel, MockStreamEvent, MockSubtractTool, MockTurn,
+        },
+    };
+
+    /// Defers every `subtract` call, counting each consultation.
+    #[derive(Clone, Default)]
+    struct DeferSubtract(Arc<AtomicUsize>);
+
+    impl AgentHook for DeferSubtract {
+        async fn on_tool_call(&self, _ctx: &HookContext, event: ToolCall<'_>) -> ToolCallAction {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            if event.tool_name == "subtract" {
+                ToolCallAction::defer("subtractions need sign-off")
+            } else {
+                ToolCallAction::run()
+            }
+        }
+    }
+
+    fn tool_result_json(messages: &[Message]) -> Vec<serde_json::Value> {
+        messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::User { content } => Some(content.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|content| match content {
+                UserContent::ToolResult(result) => Some(result_json(result)),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn result_json(result: &ToolResult) -> Vec<serde_json::Value> {
+        result
+            .content
+            .iter()
+            .map(|content| match content {
+                ToolResultContent::Text(text) => json!(text.text),
+                ToolResultContent::Json { value } => value.clone(),
+                other => json!(format!("{other:?}")),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn deferred_calls_pause_the_run_until_decided() {
+        let hook = DeferSubtract::default();
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([
+            MockTurn::from_contents([
+                AssistantContent::tool_call("tc1", "add", json!({"x": 2, "y": 3})),
+                AssistantContent::tool_call("tc2", "subtract", json!({"x": 10, "y": 4})),
+            ]),
+            MockTurn::text("done"),
+        ]))
+        .tool(MockAddTool)
+        .tool(MockSubtractTool)
+        .add_hook(hook.clone())
+        .build();
+
+        let error = agent
+            .runner("do the maths")
+            .max_turns(3)
+            .run()
+            .await
+            .expect_err("the run pauses on the deferred call");
+        let PromptError::ApprovalRequired(pending) = error else {
+            panic!("expected ApprovalRequired, got {error:?}");
+        };
+        assert_eq!(pending.requests().len(), 1);
+        let request = &pending.requests()[0];
+        assert_eq!(request.tool_call.function.name, "subtract");
+        assert_eq!(request.reason, "subtractions need sign-off");
+        assert!(!pending.is_decided());
+
+        // The pending state survives a trip through storage.
+        let mut pending: PendingApprovals =
+            serde_json::from_str(&serde_json::to_string(&pending).unwrap()).unwrap();
+        let id = pending.requests()[0].internal_call_id.clone();
+        assert!(!pending.decide("unknown", ApprovalDecision::approve()));
+        assert!(pending.decide(&id, ApprovalDecision::edit(json!({"x": 10, "y": 1}))));
+        assert!(pending.is_decided());
+
+        let response = AgentRunner::resume_approvals(&agent, pending)
+            .run()
+            .await
+            .expect("the resumed run completes");
+        assert_eq!(response.output, "done");
+        // `add` ran once before the pause; the edited subtraction ran on resume.
+        assert_eq!(
+            tool_result_json(response.messages.as_deref().unwrap()),
+            vec![json!(5), json!(9)]
+        );
+        // The decision replaced the hook for the deferred call.
+        assert_eq!(hook.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn denied_and_undecided_calls_are_skipped_when_streaming() {
+        let turns = || {
+            [
+                vec![
+                    MockStreamEvent::tool_call("tc1", "subtract", json!({"x": 1, "y": 1})),
+                    MockStreamEvent::tool_call("tc2", "subtract", json!({"x": 2, "y": 2})),
+                    MockStreamEvent::final_response(Usage::new()),
+                ],
+                vec![
+                    MockStreamEvent::text("done"),
+                    MockStreamEvent::final_response(Usage::new()),
+                ],
+            ]
+        };
+        let agent = AgentBuilder::new(MockCompletionModel::from_stream_turns(turns()))
+            .tool(MockSubtractTool)
+            .add_hook(DeferSubtract::default())
+            .build();
+
+        let mut stream = agent.runner("do the maths").max_turns(3).stream().await;
+        let mut pending = None;
+        while let Some(item) = stream.next().await {
+            if let Err(err) = item {
+                match crate::agent::prompt_request::streaming::streaming_error_into_prompt(err) {
+                    PromptError::ApprovalRequired(approvals) => pending = Some(*approvals),
+                    other => panic!("unexpected error: {other:?}"),
+                }
+            }
+        }
+        let mut pending = pending.expect("the stream pauses on the deferred calls");
+        assert_eq!(pending.requests().len(), 2);
+        let first = pending.requests()[0].internal_call_id.clone();
+        pending.decide(&first, ApprovalDecision::deny("not today"));
+
+        let mut stream = AgentRunner::resume_approvals(&agent, pending)
+            .stream()
+            .await;
+        let mut results = Vec::new();
+        let mut output = None;
+        while let Some(item) = stream.next().await {
+            match item.expect("the resumed stream succeeds") {
+                MultiTurnStreamItem::ToolExecutionCommitted { .. } => {
+                    panic!("no deferred call was approved")
+                }
+                MultiTurnStreamItem::StreamUserItem(
+                    crate::streaming::StreamedUserContent::ToolResult { tool_result, .. },
+                ) => results.extend(result_json(&tool_result)),
+                MultiTurnStreamItem::FinalResponse(response) => output = Some(response.output),
+                _ => {}
+            }
+        }
+        assert_eq!(results, vec![json!("not today"), json!(NOT_APPROVED)]);
+        assert_eq!(output.as_deref(), Some("done"));
+    }
+}
@@ -0,0 +1,452 @@
+//! A built-in hook that stops agent runs once a token or cost budget is spent.
+//!
+//! [`BudgetGuard`] meters the [`Usage`] of every completed model turn and
+//! refuses the next model call once its budget is exhausted. The run then
+//! fails with [`PromptError::BudgetExceeded`](crate::completion::PromptError::BudgetExceeded),
+//! so callers can tell budget exhaustion apart from other cancellations.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use rig_agent::agent::BudgetGuard;
+//!
+//! // At most 200k tokens per conversation...
+//! let per_conversation = BudgetGuard::tokens(200_000).per_conversation();
+//! // ...and 2M tokens per hour across every run sharing this guard.
+//! let hourly = BudgetGuard::tokens(2_000_000).per_window(Duration::from_secs(3600));
+//! ```
+//!
+//! The guard checks the budget *before* each model call, so the call that
+//! crosses the limit completes and only later calls are refused. Clones share
+//! their ledger: attach clones of one guard to several agents to give them a
+//! common budget.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rig_core::{completion::Usage, pricing::ModelPricing};
+use web_time::Instant;
+
+use super::hook::{
+    AgentHook, CompletionCall, CompletionCallAction, HookContext, ModelTurnAction,
+    ModelTurnFinished,
+};
+
+/// What a [`BudgetGuard`] measures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetLimit {
+    /// Tokens, counted as [`Usage::total_tokens`], or input plus output tokens
+    /// when the provider reports no total.
+    Tokens(u64),
+    /// Cost of the usage at the given rates, in the rates' currency.
+    Cost {
+        /// Maximum spend.
+        max: f64,
+        /// Rates the usage is priced at.
+        pricing: ModelPricing,
+    },
+}
+
+impl BudgetLimit {
+    fn max(&self) -> f64 {
+        match self {
+            Self::Tokens(max) => *max as f64,
+            Self::Cost { max, .. } => *max,
+        }
+    }
+
+    fn measure(&self, usage: &Usage) -> f64 {
+        match self {
+            Self::Tokens(_) => {
+                let total = if usage.total_tokens > 0 {
+                    usage.total_tokens
+                } else {
+                    usage.input_tokens + usage.output_tokens
+                };
+                total as f64
+            }
+            Self::Cost { pricing, .. } => pricing.cost(usage).total(),
+        }
+    }
+}
+
+/// Which usage a [`BudgetGuard`] counts against its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    /// Every run the guard (or a clone of it) is attached to, for its whole
+    /// lifetime.
+    Total,
+    /// Each run separately.
+    Run,
+    /// Each conversation id separately. Runs without a conversation id are
+    /// metered per run.
+    Conversation,
+    /// Every run the guard is attached to, over a sliding time window.
+    Window(Duration),
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Total => f.write_str("in total"),
+            Self::Run => f.write_str("for this run"),
+            Self::Conversation => f.write_str("for this conversation"),
+            Self::Window(window) => write!(f, "in the last {window:?}"),
+        }
+    }
+}
+
+/// Why a [`BudgetGuard`] refused a model call, carried by
+/// [`PromptError::BudgetExceeded`](crate::completion::PromptError::BudgetExceeded).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    /// The limit that was reached.
+    pub limit: BudgetLimit,
+    /// The scope the limit applies to.
+    pub scope: BudgetScope,
+    /// Tokens or cost already spent in that scope.
+    pub spent: f64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.limit {
+            BudgetLimit::Tokens(_) => "token",
+            BudgetLimit::Cost { .. } => "cost",
+        };
+        write!(
+            f,
+            "{unit} budget of {} exhausted {}: {} spent",
+            self.limit.max(),
+            self.scope,
+            self.spent
+        )
+    }
+}
+
+/// Usage counted by the current run, kept in the run's [`Scratchpad`](super::Scratchpad).
+#[derive(Debug, Clone, Copy, Default)]
+struct RunSpend(f64);
+
+#[derive(Debug, Default)]
+struct Ledger {
+    total: f64,
+    conversations: HashMap<String, f64>,
+    window: VecDeque<(Instant, f64)>,
+}
+
+/// An [`AgentHook`] that refuses model calls once a token or cost budget is
+/// exhausted.
+///
+/// Usage is metered from [`on_model_turn_finished`](AgentHook::on_model_turn_finished),
+/// which both the blocking and the streaming surface fire for every completed
+/// model call, rather than from the blocking-only
+/// [`on_completion_response`](AgentHook::on_completion_response). The budget is
+/// checked in [`on_completion_call`](AgentHook::on_completion_call). Register
+/// the guard before hooks that retry or stop model turns: those short-circuit
+/// the hooks after them, which would leave the retried turn unmetered.
+#[derive(Debug, Clone)]
+pub struct BudgetGuard {
+    limit: BudgetLimit,
+    scope: BudgetScope,
+    ledger: Arc<Mutex<Ledger>>,
+}
+
+impl BudgetGuard {
+    /// A guard with a token budget shared by every run it is attached to.
+    pub fn tokens(max: u64) -> Self {
+        Self::new(BudgetLimit::Tokens(max))
+    }
+
+    /// A guard with a cost budget shared by every run it is attached to, with
+    /// usage priced at `pricing`.
+    pub fn cost(max: f64, pricing: ModelPricing) -> Self {
+        Self::new(BudgetLimit::Cost { max, pricing })
+    }
+
+    /// A guard enforcing `limit` across every run it is attached to.
+    pub fn new(limit: BudgetLimit) -> Self {
+        Self {
+            limit,
+            scope: BudgetScope::Total,
+            ledger: Arc::default(),
+        }
+    }
+
+    /// Apply the budget to each run separately.
+    pub fn per_run(self) -> Self {
+        self.scope(BudgetScope::Run)
+    }
+
+    /// Apply the budget to each conversation id separately.
+    pub fn per_conversation(self) -> Self {
+        self.scope(BudgetScope::Conversation)
+    }
+
+    /// Apply the budget to usage within a sliding `window`, across runs.
+    pub fn per_window(self, window: Duration) -> Self {
+        self.scope(BudgetScope::Window(window))
+    }
+
+    /// Set the scope the budget applies to. Defaults to [`BudgetScope::Total`].
+    pub fn scope(mut self, scope: BudgetScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// The limit this guard enforces.
+    pub fn limit(&self) -> BudgetLimit {
+        self.limit
+    }
+
+    /// Tokens or cost spent so far in the scope `ctx` belongs to.
+    pub fn spent(&self, ctx: &HookContext) -> f64 {
+        let mut ledger = self
+            .ledger
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        match (self.scope, ctx.conversation_id()) {
+            (BudgetScope::Total, _) => ledger.total,
+            (BudgetScope::Conversation, Some(id)) => {
+                ledger.conversations.get(id).copied().unwrap_or_default()
+            }
+            (BudgetScope::Run | BudgetScope::Conversation, _) => {
+                ctx.scratchpad().get::<RunSpend>().unwrap_or_default().0
+            }
+            (BudgetScope::Window(window), _) => {
+                let now = Instant::now();
+                while ledger
+                    .window
+                    .front()
+                    .is_some_and(|(at, _)| now.duration_since(*at) >= window)
+                {
+                    ledger.window.pop_front();
+                }
+                ledger.window.iter().map(|(_, spent)| spent).sum()
+            }
+        }
+    }
+
+    fn record(&self, ctx: &HookContext, usage: &Usage) {
+        let spent = self.limit.measure(usage);
+        let mut ledger = self
+            .ledger
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        match (self.scope, ctx.conversation_id()) {
+            (BudgetScope::Total, _) => ledger.total += spent,
+            (BudgetScope::Conversation, Some(id)) => {
+                *ledger.conversations.entry(id.to_owned()).or_default() += spent;
+            }
+            (BudgetScope::Run | BudgetScope::Conversation, _) => {
+                ctx.scratchpad().update(|run: &mut RunSpend| run.0 += spent);
+            }
+            (BudgetScope::Window(_), _) => ledger.window.push_back((Instant::now(), spent)),
+        }
+    }
+}
+
+impl AgentHook for BudgetGuard {
+    async fn on_completion_call(
+        &self,
+        ctx: &HookContext,
+        _event: CompletionCall<'_>,
+    ) -> CompletionCallAction {
+        let spent = self.spent(ctx);
+        if spent < self.limit.max() {
+            return CompletionCallAction::Continue;
+        }
+
+        let exceeded = BudgetExceeded {
+            limit: self.limit,
+            scope: self.scope,
+            spent,
+        };
+        let reason = exceeded.to_string();
+        ctx.scratchpad().insert(exceeded);
+        CompletionCallAction::Stop(reason)
+    }
+
+    async fn on_model_turn_finished(
+        &self,
+        ctx: &HookContext,
+        event: ModelTurnFinished<'_>,
+    ) -> ModelTurnAction {
+        self.record(ctx, &event.usage);
+        ModelTurnAction::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use super::{BudgetGuard, BudgetLimit, BudgetScope};
+    use crate::{
+        agent::{AgentBuilder, MultiTurnStreamItem, StreamingError},
+        completion::{PromptError, Usage},
+        test_utils::{MockAddTool, MockCompletionModel, MockStreamEvent, MockTurn},
+    };
+
+    fn usage(input: u64, output: u64) -> Usage {
+        Usage {
+            input_tokens: input,
+            output_tokens: output,
+            ..Usage::new()
+        }
+    }
+
+    /// A tool turn spending 18 tokens, then a text turn spending 30.
+    fn tool_then_text_model() -> MockCompletionModel {
+        MockCompletionModel::from_turns([
+            MockTurn::tool_call("tc1", "add", serde_json::json!({"x": 2, "y": 3}))
+                .with_usage(usage(7, 11)),
+            MockTurn::text("the answer is 5").with_usage(usage(13, 17)),
+        ])
+    }
+
+    /// Text turns spending 30 tokens each.
+    fn text_model() -> MockCompletionModel {
+        MockCompletionModel::from_turns(
+            std::iter::repeat_with(|| MockTurn::text("hi").with_usage(usage(13, 17))).take(3),
+        )
+    }
+
+    #[tokio::test]
+    async fn exhausted_run_budget_refuses_the_next_call() {
+        let agent = AgentBuilder::new(tool_then_text_model())
+            .tool(MockAddTool)
+            .add_hook(BudgetGuard::tokens(10).per_run())
+            .build();
+
+        let error = agent
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .run()
+            .await
+            .expect_err("the second call is over budget");
+
+        let PromptError::BudgetExceeded {
+            chat_history,
+            exceeded,
+        } = error
+        else {
+            panic!("expected BudgetExceeded, got {error:?}");
+        };
+        assert_eq!(exceeded.limit, BudgetLimit::Tokens(10));
+        assert_eq!(exceeded.scope, BudgetScope::Run);
+        assert_eq!(exceeded.spent, 18.0);
+        // The prompt, the tool call and its result.
+        assert_eq!(chat_history.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn total_budget_is_shared_across_runs() {
+        let guard = BudgetGuard::tokens(40);
+        let agent = AgentBuilder::new(tool_then_text_model())
+            .tool(MockAddTool)
+            .add_hook(guard.clone())
+            .build();
+
+        // Checked before each call, so the run that crosses the limit finishes.
+        agent
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .run()
+            .await
+            .expect("the first run starts under budget");
+
+        let error = agent
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .run()
+            .await
+            .expect_err("the second run starts over budget");
+        assert!(
+            matches!(&error, PromptError::BudgetExceeded { exceeded, .. } if exceeded.spent == 48.0),
+            "{error:?}"
+        );
+        assert_eq!(
+            error.to_string(),
+            "BudgetExceeded: token budget of 40 exhausted in total: 48 spent"
+        );
+    }
+
+    #[tokio::test]
+    async fn conversation_budgets_are_kept_per_conversation_id() {
+        let agent = AgentBuilder::new(text_model())
+            .add_hook(BudgetGuard::tokens(20).per_conversation())
+            .build();
+
+        agent.runner("hello").conversation("a").run().await.unwrap();
+        let error = agent
+            .runner("hello again")
+            .conversation("a")
+            .run()
+            .await
+            .expect_err("conversation a is over budget");
+        assert!(matches!(error, PromptError::BudgetExceeded { .. }));
+
+        agent.runner("hello").conversation("b").run().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn window_budgets_forget_usage_older_than_the_window() {
+        let expired = AgentBuilder::new(text_model())
+            .add_hook(BudgetGuard::tokens(20).per_window(Duration::ZERO))
+            .build();
+        expired.runner("hello").run().await.unwrap();
+        expired.runner("hello").run().await.unwrap();
+
+        let hourly = AgentBuilder::new(text_model())
+            .add_hook(BudgetGuard::tokens(20).per_window(Duration::from_secs(3600)))
+            .build();
+        hourly.runner("hello").run().await.unwrap();
+        assert!(matches!(
+            hourly.runner("hello").run().await,
+            Err(PromptError::BudgetExceeded { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn streamed_runs_are_metered_and_refused() {
+        let model = MockCompletionModel::from_stream_turns([
+            vec![
+                MockStreamEvent::tool_call("tc1", "add", serde_json::json!({"x": 2, "y": 3})),
+                MockStreamEvent::final_response(usage(7, 11)),
+            ],
+            vec![
+                MockStreamEvent::text("5"),
+                MockStreamEvent::final_response(usage(13, 17)),
+            ],
+        ]);
+        let mut stream = AgentBuilder::new(model)
+            .tool(MockAddTool)
+            .add_hook(BudgetGuard::tokens(10).per_run())
+            .build()
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .stream()
+            .await;
+
+        let mut error = None;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(MultiTurnStreamItem::FinalResponse(_)) => panic!("run should not finish"),
+                Ok(_) => {}
+                Err(err) => error = Some(err),
+            }
+        }
+
+        let Some(StreamingError::Prompt(error)) = error else {
+            panic!("expected a prompt error");
+        };
+        assert!(matches!(*error, PromptError::BudgetExceeded { .. }));
+    }
+}
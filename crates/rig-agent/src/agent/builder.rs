@@ -5,6 +5,7 @@ use schemars::{JsonSchema, Schema, schema_for};
 use rig_core::{
     memory::ConversationMemory,
     message::ToolChoice,
+    pricing::ModelPricing,
     vector_store::{VectorSearchRequest, VectorStoreIndexDyn},
 };
 
@@ -12,7 +13,7 @@ use crate::{
     agent::hook::{AgentHook, CompletionCall, CompletionCallAction, HookContext, RequestPatch},
     completion::{CompletionModel, Document},
     tool::{
-        DynamicTool, PortableDynamicTool, Tool, ToolSet,
+        DynamicTool, PortableDynamicTool, Tool, ToolExecutionPolicy, ToolSet,
         server::{ToolServer, ToolServerHandle},
     },
 };
@@ -231,6 +232,24 @@ impl<ToolState> AgentBuilder<ToolState> {
         self
     }
 
+    /// Validate the arguments of every tool call against the tool's parameter
+    /// schema before it is dispatched.
+    ///
+    /// Defaults to `false`, leaving argument checking to each tool's own
+    /// deserialization. When enabled, a call whose arguments violate the schema
+    /// (wrong types, missing required fields, values outside an `enum` or
+    /// range) is never executed; it goes through invalid tool-call recovery
+    /// like a call to an unknown tool, with the violations and their JSON
+    /// paths on
+    /// [`InvalidToolCallContext::argument_errors`](crate::agent::InvalidToolCallContext::argument_errors).
+    /// Without a hook that resolves it, the run fails with
+    /// [`PromptError::InvalidToolArguments`](crate::completion::PromptError::InvalidToolArguments).
+    /// See [`tool::schema`](crate::tool::schema) for the supported keywords.
+    pub fn validate_tool_arguments(mut self, enabled: bool) -> Self {
+        self.config.validate_tool_arguments = enabled;
+        self
+    }
+
     /// Set the output schema for structured output. When set, providers that support
     /// native structured outputs will constrain the model's response to match this schema.
     pub fn output_schema<T>(mut self) -> Self
@@ -280,6 +299,21 @@ impl<ToolState> AgentBuilder<ToolState> {
         self
     }
 
+    /// Price every run's token usage at these rates, attaching the result to
+    /// [`PromptResponse::cost`](crate::agent::PromptResponse::cost) and
+    /// recording its total as `gen_ai.usage.cost` on the run's `invoke_agent`
+    /// span.
+    ///
+    /// The whole run is priced at one set of rates, so a hook that routes
+    /// turns to differently priced models needs to price
+    /// [`PromptResponse::completion_calls`](crate::agent::PromptResponse::completion_calls)
+    /// itself. Look the rates up in a [`PricingTable`](rig_core::pricing::PricingTable)
+    /// with [`PricingTable::get`](rig_core::pricing::PricingTable::get).
+    pub fn pricing(mut self, pricing: ModelPricing) -> Self {
+        self.config.pricing = Some(pricing);
+        self
+    }
+
     /// Attach a default hook to the agent. Each call appends to the agent's hook
     /// stack; hooks run for every prompt request (unless more are added per
     /// request) in registration order. How their results compose is
@@ -425,6 +459,14 @@ forward_into_tool_builder! {
         index: impl VectorStoreIndexDyn + Send + Sync + 'static,
         toolset: ToolSet
     );
+
+    /// Set the timeout and retry policy for every tool without a policy of
+    /// its own. Transitions the builder to the `WithBuilderTools` state.
+    default_tool_policy(policy: ToolExecutionPolicy);
+
+    /// Set the timeout and retry policy for the tool named `tool_name`.
+    /// Transitions the builder to the `WithBuilderTools` state.
+    tool_policy(tool_name: impl Into<String>, policy: ToolExecutionPolicy);
 }
 
 impl AgentBuilder<WithToolServerHandle> {
@@ -486,6 +528,18 @@ impl AgentBuilder<WithBuilderTools> {
         self.map_server(|server| server.retrieved_tools(sample, index, toolset))
     }
 
+    /// Set the timeout and retry policy for every tool without a policy of
+    /// its own. See [`ToolServer::default_tool_policy`].
+    pub fn default_tool_policy(self, policy: ToolExecutionPolicy) -> Self {
+        self.map_server(|server| server.default_tool_policy(policy))
+    }
+
+    /// Set the timeout and retry policy for the tool named `tool_name`. See
+    /// [`ToolServer::tool_policy`].
+    pub fn tool_policy(self, tool_name: impl Into<String>, policy: ToolExecutionPolicy) -> Self {
+        self.map_server(|server| server.tool_policy(tool_name, policy))
+    }
+
     /// Build the agent with the configured tools.
     ///
     /// A new `ToolServer` will be created containing all tools added via
@@ -13,8 +13,11 @@ use crate::{
     streaming::{StreamingChat, StreamingPrompt},
     tool::server::{ToolRegistrySnapshot, ToolServerError, ToolServerHandle},
 };
-use rig_core::{message::ToolChoice, wasm_compat::WasmCompatSend};
-use std::{collections::BTreeSet, sync::Arc};
+use rig_core::{message::ToolChoice, pricing::ModelPricing, wasm_compat::WasmCompatSend};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 use super::UNKNOWN_AGENT_NAME;
 
@@ -29,6 +32,9 @@ pub(crate) struct PreparedCompletionRequest {
     pub(crate) tool_snapshot: Arc<ToolRegistrySnapshot>,
     pub(crate) executable_tool_names: BTreeSet<String>,
     pub(crate) allowed_tool_names: BTreeSet<String>,
+    /// Parameter schemas of the executable tools, keyed by name, when the
+    /// agent validates tool arguments; empty otherwise.
+    pub(crate) tool_schemas: BTreeMap<String, serde_json::Value>,
     /// When Tool output mode is active, the name of the synthetic output tool
     /// advertised to the model (allowed but not executable). See #1928.
     pub(crate) output_tool_name: Option<String>,
@@ -257,8 +263,9 @@ pub(crate) async fn build_prepared_completion_request(
     // Apply a per-turn request patch (the merged patch from every `CompletionCall`
     // hook): each set field replaces the agent's configured value for this turn,
     // unset fields inherit it, `additional_params` is shallow-merged, and
-    // `extra_context`/`history` are applied below. This is per-turn only — it
-    // never mutates the agent's baseline.
+    // `extra_context`/`history` are applied below; a patched `prompt` replaces
+    // the turn's prompt. This is per-turn only — it never mutates the agent's
+    // baseline or the run's history.
     let preamble = request_patch
         .and_then(|o| o.preamble.as_deref())
         .or(preamble);
@@ -283,6 +290,9 @@ pub(crate) async fn build_prepared_completion_request(
         (base, patch) => patch.or(base).cloned(),
     };
     let active_tools = request_patch.and_then(|o| o.active_tools.as_deref());
+    let prompt = request_patch
+        .and_then(|o| o.prompt.clone())
+        .unwrap_or(prompt);
 
     // Retrieved tools keep their existing query-selection behavior: prefer the
     // current prompt's RAG text, then the latest matching history message.
@@ -345,6 +355,15 @@ pub(crate) async fn build_prepared_completion_request(
     // synthetic output tool is appended.
     let executable_tool_names: BTreeSet<String> =
         tooldefs.iter().map(|tool| tool.name.clone()).collect();
+    let tool_schemas: BTreeMap<String, serde_json::Value> = if runner.config.validate_tool_arguments
+    {
+        tooldefs
+            .iter()
+            .map(|tool| (tool.name.clone(), tool.parameters.clone()))
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
 
     // Resolve the effective output mode (#1928). Once the run has committed to a
     // Tool-mode output tool on an earlier turn (signaled by `committed_output_
@@ -537,6 +556,7 @@ pub(crate) async fn build_prepared_completion_request(
         tool_snapshot: Arc::new(tool_snapshot),
         executable_tool_names,
         allowed_tool_names,
+        tool_schemas,
         output_tool_name,
         // The post-patch binding from above — the one `.max_tokens_opt(..)`
         // put on the request.
@@ -625,6 +645,11 @@ pub(crate) struct AgentConfig {
     pub(crate) memory: Option<Arc<dyn rig_core::memory::ConversationMemory>>,
     /// Optional conversation id used when none is set per-request.
     pub(crate) conversation_id: Option<String>,
+    /// Optional token rates used to price each run's usage.
+    pub(crate) pricing: Option<ModelPricing>,
+    /// Whether tool-call arguments are validated against the tool's parameter
+    /// schema before dispatch.
+    pub(crate) validate_tool_arguments: bool,
 }
 
 impl AgentConfig {
@@ -647,6 +672,8 @@ impl AgentConfig {
             output_mode: OutputMode::default(),
             memory: None,
             conversation_id: None,
+            pricing: None,
+            validate_tool_arguments: false,
         }
     }
 }
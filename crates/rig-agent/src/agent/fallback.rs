@@ -0,0 +1,506 @@
+//! Provider failover for agent models.
+//!
+//! A [`FallbackModel`] is itself a [`CompletionModel`] that tries an ordered
+//! chain of [`ModelHandle`]s. Each request goes to the primary model first;
+//! when an attempt fails with one of the configured [`FallbackCondition`]s the
+//! same request is sent to the next model, and the last model's error is
+//! returned once the chain is exhausted. Errors outside the configured
+//! conditions — an invalid request, a schema error — are returned at once:
+//! another provider would reject them too.
+//!
+//! Streaming fails over only before the first streamed event. Once a provider
+//! has yielded anything the stream belongs to it, and later errors reach the
+//! caller unchanged.
+//!
+//! The model that served a call is reported as its label — the
+//! [`ModelHandle::label`], or `#<position>` for an unlabeled handle — through
+//! the response identity (`served_by` on
+//! [`CompletionCall`](crate::agent::CompletionCall) and the hook events'
+//! [`ResponseIdentity`](crate::agent::ResponseIdentity)) and as
+//! `rig.model.served_by` on the agent's `chat` span. Every failover emits a
+//! warning event naming the model that failed.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rig_agent::{
+//!     agent::{AgentBuilder, FallbackModel, ModelHandle},
+//!     prelude::*,
+//! };
+//! use rig_core::providers::{anthropic, openai};
+//! use rig_reqwest::prelude::*;
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let openai = openai::Client::from_env()?;
+//! let anthropic = anthropic::Client::from_env()?;
+//!
+//! let model = FallbackModel::new(ModelHandle::named(
+//!     "openai",
+//!     openai.completion_model(openai::GPT_5_2),
+//! ))
+//! .fallback(ModelHandle::named(
+//!     "anthropic",
+//!     anthropic.completion_model(anthropic::completion::CLAUDE_SONNET_4_6),
+//! ))
+//! .attempt_timeout(Duration::from_secs(60));
+//!
+//! let agent = AgentBuilder::new(model).preamble("You are helpful.").build();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{error::Error as StdError, time::Duration};
+
+use rig_core::{
+    completion::{
+        CompletionError, CompletionModel, CompletionRequest, CompletionResponse,
+        ProviderCapabilities,
+    },
+    http_client,
+    streaming::StreamingCompletionResponse,
+};
+
+use super::ModelHandle;
+
+/// A class of model failure that moves a [`FallbackModel`] to its next model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FallbackCondition {
+    /// An HTTP 5xx response.
+    ServerError,
+    /// An HTTP 429 response.
+    RateLimited,
+    /// A request or gateway timeout (HTTP 408 or 504), a transport timeout,
+    /// or an attempt exceeding [`FallbackModel::attempt_timeout`].
+    Timeout,
+    /// The provider reported it is overloaded: HTTP 503 or 529, or an error
+    /// naming an overload (Anthropic's `overloaded_error`).
+    Overloaded,
+}
+
+impl FallbackCondition {
+    /// Every condition; the default for a new [`FallbackModel`].
+    pub const ALL: [Self; 4] = [
+        Self::ServerError,
+        Self::RateLimited,
+        Self::Timeout,
+        Self::Overloaded,
+    ];
+
+    /// Whether `error` belongs to this class.
+    pub fn matches(self, error: &CompletionError) -> bool {
+        let status = error
+            .provider_response_status()
+            .map(|status| status.as_u16());
+        match self {
+            Self::ServerError => status.is_some_and(|status| (500..600).contains(&status)),
+            Self::RateLimited => status == Some(429),
+            Self::Timeout => matches!(status, Some(408 | 504)) || is_timeout(error),
+            Self::Overloaded => {
+                matches!(status, Some(503 | 529)) || error_chain_mentions(error, "overloaded")
+            }
+        }
+    }
+}
+
+/// A [`CompletionModel`] that fails over along an ordered chain of models.
+///
+/// See the [module docs](self) for the failover rules and how the serving
+/// model is reported. The chain's [capabilities](CompletionModel::capabilities)
+/// are those every model in it shares, so a request shaped for the chain is
+/// valid on whichever model serves it.
+#[derive(Debug, Clone)]
+pub struct FallbackModel {
+    models: Vec<ModelHandle>,
+    conditions: Vec<FallbackCondition>,
+    attempt_timeout: Option<Duration>,
+}
+
+impl FallbackModel {
+    /// A chain that starts at `primary` and fails over on every
+    /// [`FallbackCondition`].
+    pub fn new(primary: ModelHandle) -> Self {
+        Self {
+            models: vec![primary],
+            conditions: FallbackCondition::ALL.to_vec(),
+            attempt_timeout: None,
+        }
+    }
+
+    /// Append `model` to the end of the chain.
+    pub fn fallback(mut self, model: ModelHandle) -> Self {
+        self.models.push(model);
+        self
+    }
+
+    /// Replace the conditions that trigger a failover.
+    pub fn fallback_on(mut self, conditions: impl IntoIterator<Item = FallbackCondition>) -> Self {
+        self.conditions = conditions.into_iter().collect();
+        self
+    }
+
+    /// Bound each attempt — for a stream, until its first event — to
+    /// `timeout`. An attempt that overruns fails as a
+    /// [`FallbackCondition::Timeout`].
+    pub fn attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// The chain's models, primary first.
+    pub fn models(&self) -> &[ModelHandle] {
+        &self.models
+    }
+
+    fn should_fall_back(&self, error: &CompletionError) -> bool {
+        self.conditions
+            .iter()
+            .any(|condition| condition.matches(error))
+    }
+
+    /// Run one attempt under the configured attempt timeout.
+    async fn attempt<T>(
+        &self,
+        attempt: impl Future<Output = Result<T, CompletionError>>,
+    ) -> Result<T, CompletionError> {
+        match self.attempt_timeout {
+            Some(timeout) => rig_core::wasm_compat::timeout(timeout, attempt)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(CompletionError::HttpError(http_client::Error::instance(
+                        std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("model attempt timed out after {}ms", timeout.as_millis()),
+                        ),
+                    )))
+                }),
+            None => attempt.await,
+        }
+    }
+
+    /// Decide what to do with a failed attempt on the model at `index`:
+    /// `true` moves on to the next model.
+    fn fail_over(&self, index: usize, label: &str, error: &CompletionError) -> bool {
+        let fail_over = index + 1 < self.models.len() && self.should_fall_back(error);
+        if fail_over {
+            tracing::warn!(
+                target: "rig",
+                model = label,
+                error = %error,
+                "model failed; falling back to the next model"
+            );
+        }
+        fail_over
+    }
+}
+
+/// The label a model is reported under: its handle label, or its position.
+fn served_label(index: usize, model: &ModelHandle) -> String {
+    model
+        .label()
+        .map_or_else(|| format!("#{index}"), str::to_owned)
+}
+
+fn record_served_by(label: &str) {
+    tracing::Span::current().record("rig.model.served_by", label);
+}
+
+/// Whether any error in `error`'s source chain is a timeout.
+fn is_timeout(error: &CompletionError) -> bool {
+    let mut source: Option<&(dyn StdError + 'static)> = Some(error);
+    while let Some(current) = source {
+        if current
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::TimedOut)
+        {
+            return true;
+        }
+        source = current.source();
+    }
+    error_chain_mentions(error, "timed out")
+}
+
+/// Whether the message of any error in `error`'s source chain contains
+/// `needle`, ignoring ASCII case.
+fn error_chain_mentions(error: &CompletionError, needle: &str) -> bool {
+    let mut source: Option<&(dyn StdError + 'static)> = Some(error);
+    while let Some(current) = source {
+        if current.to_string().to_ascii_lowercase().contains(needle) {
+            return true;
+        }
+        source = current.source();
+    }
+    false
+}
+
+impl CompletionModel for FallbackModel {
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse, CompletionError> {
+        let mut models = self.models.iter().enumerate();
+        loop {
+            let Some((index, model)) = models.next() else {
+                return Err(CompletionError::ProviderError(
+                    "fallback model chain has no models".to_string(),
+                ));
+            };
+            let label = served_label(index, model);
+            match self.attempt(model.completion(request.clone())).await {
+                Ok(mut response) => {
+                    let served_by = response.served_by.get_or_insert(label);
+                    record_served_by(served_by);
+                    return Ok(response);
+                }
+                Err(error) if self.fail_over(index, &label, &error) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse, CompletionError> {
+        let mut models = self.models.iter().enumerate();
+        loop {
+            let Some((index, model)) = models.next() else {
+                return Err(CompletionError::ProviderError(
+                    "fallback model chain has no models".to_string(),
+                ));
+            };
+            let label = served_label(index, model);
+            let attempt = self.attempt(async {
+                let mut stream = model.stream(request.clone()).await?;
+                stream.wait_for_first_event().await?;
+                Ok(stream)
+            });
+            match attempt.await {
+                Ok(stream) => {
+                    let stream = match stream.served_by() {
+                        Some(_) => stream,
+                        None => stream.with_served_by(label),
+                    };
+                    record_served_by(stream.served_by().unwrap_or_default());
+                    return Ok(stream);
+                }
+                Err(error) if self.fail_over(index, &label, &error) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        let composes = self
+            .models
+            .iter()
+            .all(|model| model.capabilities().composes_native_output_with_tools);
+        ProviderCapabilities::new().with_native_output_tool_composition(composes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use rig_core::{
+        completion::{AssistantContent, Message},
+        test_utils::{MockCompletionModel, MockStreamEvent, MockTurn},
+    };
+
+    use super::*;
+    use crate::agent::AgentBuilder;
+    use crate::completion::Prompt;
+
+    fn request() -> CompletionRequest {
+        CompletionRequest {
+            model: None,
+            preamble: None,
+            chat_history: vec![Message::user("hello")],
+            documents: Vec::new(),
+            tools: Vec::new(),
+            temperature: None,
+            max_tokens: None,
+            tool_choice: None,
+            additional_params: None,
+            output_schema: None,
+            record_telemetry_content: false,
+        }
+    }
+
+    fn unavailable() -> MockTurn {
+        MockTurn::provider_response_error(
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            "unavailable",
+            "req_1",
+        )
+    }
+
+    fn text_of(response: &CompletionResponse) -> Option<&str> {
+        match response.choice.first() {
+            Some(AssistantContent::Text(text)) => Some(&text.text),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn conditions_classify_provider_failures() {
+        let status = |status| CompletionError::from_http_response(status, "failed");
+        let unavailable = status(http::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(FallbackCondition::ServerError.matches(&unavailable));
+        assert!(FallbackCondition::Overloaded.matches(&unavailable));
+        assert!(!FallbackCondition::RateLimited.matches(&unavailable));
+        assert!(
+            FallbackCondition::RateLimited.matches(&status(http::StatusCode::TOO_MANY_REQUESTS))
+        );
+        assert!(FallbackCondition::Timeout.matches(&status(http::StatusCode::GATEWAY_TIMEOUT)));
+        assert!(
+            FallbackCondition::Overloaded.matches(&CompletionError::ProviderError(
+                "overloaded_error: Overloaded".to_string()
+            ))
+        );
+
+        let bad_request = status(http::StatusCode::BAD_REQUEST);
+        assert!(
+            FallbackCondition::ALL
+                .iter()
+                .all(|condition| !condition.matches(&bad_request))
+        );
+    }
+
+    #[tokio::test]
+    async fn completion_fails_over_and_reports_the_serving_model() {
+        let primary = MockCompletionModel::new([unavailable()]);
+        let secondary = MockCompletionModel::new([MockTurn::text("from secondary")]);
+        let model = FallbackModel::new(ModelHandle::named("primary", primary.clone()))
+            .fallback(ModelHandle::new(secondary.clone()));
+
+        let response = model.completion(request()).await.expect("secondary serves");
+        assert_eq!(text_of(&response), Some("from secondary"));
+        assert_eq!(response.identity().served_by.as_deref(), Some("#1"));
+        assert_eq!(primary.request_count(), 1);
+        assert_eq!(secondary.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn unmatched_errors_and_an_exhausted_chain_surface_the_error() {
+        let primary = MockCompletionModel::new([MockTurn::request_error("bad request")]);
+        let secondary = MockCompletionModel::new([MockTurn::text("unused")]);
+        let model = FallbackModel::new(ModelHandle::new(primary))
+            .fallback(ModelHandle::new(secondary.clone()));
+        assert!(model.completion(request()).await.is_err());
+        assert_eq!(
+            secondary.request_count(),
+            0,
+            "a request error is not retried"
+        );
+
+        let model = FallbackModel::new(ModelHandle::new(MockCompletionModel::new([unavailable()])))
+            .fallback(ModelHandle::new(MockCompletionModel::new([unavailable()])));
+        let error = model
+            .completion(request())
+            .await
+            .expect_err("every model fails");
+        assert_eq!(
+            error.provider_response_status(),
+            Some(http::StatusCode::SERVICE_UNAVAILABLE)
+        );
+
+        let only_rate_limits =
+            FallbackModel::new(ModelHandle::new(MockCompletionModel::new([unavailable()])))
+                .fallback(ModelHandle::new(secondary.clone()))
+                .fallback_on([FallbackCondition::RateLimited]);
+        assert!(only_rate_limits.completion(request()).await.is_err());
+        assert_eq!(secondary.request_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn stream_fails_over_only_before_the_first_event() {
+        let primary = MockCompletionModel::from_stream_turns([vec![MockStreamEvent::error(
+            "overloaded_error: Overloaded",
+        )]]);
+        let secondary = MockCompletionModel::from_stream_turns([vec![
+            MockStreamEvent::text("streamed"),
+            MockStreamEvent::final_response_with_total_tokens(1),
+        ]]);
+        let model = FallbackModel::new(ModelHandle::new(primary))
+            .fallback(ModelHandle::named("secondary", secondary));
+
+        let mut stream = model.stream(request()).await.expect("secondary streams");
+        while stream.next().await.is_some() {}
+        assert!(matches!(
+            stream.choice.as_slice(),
+            [AssistantContent::Text(text)] if text.text == "streamed"
+        ));
+        assert_eq!(stream.identity().served_by.as_deref(), Some("secondary"));
+
+        let primary = MockCompletionModel::from_stream_turns([vec![
+            MockStreamEvent::text("partial"),
+            MockStreamEvent::error("overloaded_error: Overloaded"),
+        ]]);
+        let secondary =
+            MockCompletionModel::from_stream_turns([vec![MockStreamEvent::text("unused")]]);
+        let model = FallbackModel::new(ModelHandle::new(primary))
+            .fallback(ModelHandle::new(secondary.clone()));
+        let mut stream = model.stream(request()).await.expect("primary streams");
+        let mut errors = 0;
+        while let Some(item) = stream.next().await {
+            errors += usize::from(item.is_err());
+        }
+        assert_eq!(errors, 1, "a mid-stream error reaches the caller");
+        assert_eq!(secondary.request_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn attempts_that_overrun_the_timeout_fail_over() {
+        struct Hanging;
+
+        impl CompletionModel for Hanging {
+            async fn completion(
+                &self,
+                _request: CompletionRequest,
+            ) -> Result<CompletionResponse, CompletionError> {
+                futures::future::pending().await
+            }
+
+            async fn stream(
+                &self,
+                _request: CompletionRequest,
+            ) -> Result<StreamingCompletionResponse, CompletionError> {
+                futures::future::pending().await
+            }
+        }
+
+        let model = FallbackModel::new(ModelHandle::new(Hanging))
+            .fallback(ModelHandle::new(MockCompletionModel::text("in time")))
+            .attempt_timeout(Duration::from_millis(10));
+        let response = model.completion(request()).await.expect("fallback serves");
+        assert_eq!(text_of(&response), Some("in time"));
+    }
+
+    #[tokio::test]
+    async fn agent_completion_calls_record_the_serving_model() {
+        let model = FallbackModel::new(ModelHandle::named(
+            "primary",
+            MockCompletionModel::new([unavailable()]),
+        ))
+        .fallback(ModelHandle::named(
+            "secondary",
+            MockCompletionModel::text("done"),
+        ));
+
+        let response = AgentBuilder::new(model)
+            .build()
+            .prompt("hello")
+            .extended_details()
+            .await
+            .expect("the fallback serves the turn");
+        assert_eq!(response.output, "done");
+        assert_eq!(
+            response
+                .completion_calls
+                .iter()
+                .map(|call| call.served_by.as_deref())
+                .collect::<Vec<_>>(),
+            [Some("secondary")]
+        );
+    }
+}
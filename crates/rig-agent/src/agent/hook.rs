@@ -208,7 +208,7 @@ use crate::{
     agent::model::ModelHandle,
     completion::{Document, ResponseIdentity, Usage},
     json_utils,
-    tool::{ToolContext, ToolOutput, ToolResult},
+    tool::{ArgumentViolation, ToolContext, ToolOutput, ToolResult, schema},
 };
 
 /// Opaque process-scoped identifier for one agent run.
@@ -385,6 +385,7 @@ pub struct HookContext {
     turn: AtomicUsize,
     is_streaming: bool,
     agent_name: Option<String>,
+    conversation_id: Option<String>,
     scratchpad: Scratchpad,
     tool_call_rewrite_frames: ToolCallRewriteFrames,
 }
@@ -396,11 +397,17 @@ impl HookContext {
             turn: AtomicUsize::new(0),
             is_streaming,
             agent_name,
+            conversation_id: None,
             scratchpad: Scratchpad::default(),
             tool_call_rewrite_frames: ToolCallRewriteFrames::default(),
         }
     }
 
+    pub(crate) fn with_conversation_id(mut self, conversation_id: Option<String>) -> Self {
+        self.conversation_id = conversation_id;
+        self
+    }
+
     pub(crate) fn set_turn(&self, turn: usize) {
         self.turn.store(turn, Ordering::Relaxed);
     }
@@ -425,6 +432,11 @@ impl HookContext {
         self.agent_name.as_deref()
     }
 
+    /// Conversation id the run belongs to, when one is configured.
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
     /// Shared run scratchpad.
     pub fn scratchpad(&self) -> &Scratchpad {
         &self.scratchpad
@@ -462,6 +474,21 @@ pub struct InvalidToolCallContext {
     pub chat_history: Vec<Message>,
     /// Whether the call came from the streaming path.
     pub is_streaming: bool,
+    /// Where the arguments violate the tool's parameter schema, when the
+    /// agent validates tool arguments (see
+    /// [`AgentBuilder::validate_tool_arguments`](crate::agent::AgentBuilder::validate_tool_arguments)).
+    /// Empty when the call was rejected for its tool name.
+    pub argument_errors: Vec<ArgumentViolation>,
+}
+
+impl InvalidToolCallContext {
+    /// Model-readable feedback listing [`Self::argument_errors`], suitable for
+    /// [`InvalidToolCallAction::retry`]; `None` when the call was rejected for
+    /// its tool name rather than its arguments.
+    pub fn argument_feedback(&self) -> Option<String> {
+        (!self.argument_errors.is_empty())
+            .then(|| schema::violation_feedback(&self.tool_name, &self.argument_errors))
+    }
 }
 
 /// Completion-call event.
@@ -829,8 +856,8 @@ pub enum StepEventKind {
 /// - JSON-object `additional_params` values are shallow-merged, with later
 ///   top-level keys winning; a later non-object value replaces an earlier value.
 /// - `active_tools` allow-lists are intersected.
-/// - Scalar fields and `history` use last-writer-wins semantics, with a warning
-///   when multiple hooks set the same field.
+/// - Scalar fields, `prompt` and `history` use last-writer-wins semantics, with
+///   a warning when multiple hooks set the same field.
 ///
 /// The merged patch does not mutate the agent's configured baseline and is not
 /// carried into subsequent turns.
@@ -852,6 +879,9 @@ pub struct RequestPatch {
     pub extra_context: Vec<Document>,
     /// Conversation history to use instead of the current history for this turn.
     pub history: Option<Vec<Message>>,
+    /// Prompt to send instead of the current prompt for this turn. Dynamic
+    /// tool retrieval queries it too; the run's history keeps the original.
+    pub prompt: Option<Message>,
 }
 
 fn merge_last_wins<T>(earlier: Option<T>, later: Option<T>, field: &str) -> Option<T> {
@@ -940,6 +970,12 @@ impl RequestPatch {
         self
     }
 
+    /// Replaces the prompt sent for this turn.
+    pub fn prompt(mut self, value: impl Into<Message>) -> Self {
+        self.prompt = Some(value.into());
+        self
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.preamble.is_none()
             && self.temperature.is_none()
@@ -949,6 +985,7 @@ impl RequestPatch {
             && self.additional_params.is_none()
             && self.extra_context.is_empty()
             && self.history.is_none()
+            && self.prompt.is_none()
     }
 
     pub(crate) fn merge(mut self, later: Self) -> Self {
@@ -964,6 +1001,7 @@ impl RequestPatch {
         self.max_tokens = merge_last_wins(self.max_tokens, later.max_tokens, "max_tokens");
         self.tool_choice = merge_last_wins(self.tool_choice, later.tool_choice, "tool_choice");
         self.history = merge_last_wins(self.history, later.history, "history");
+        self.prompt = merge_last_wins(self.prompt, later.prompt, "prompt");
         self.active_tools = match (self.active_tools.take(), later.active_tools) {
             (Some(earlier), Some(later)) => {
                 let later: std::collections::BTreeSet<_> = later.iter().collect();
@@ -1012,6 +1050,9 @@ impl ModelSelectionAction {
 }
 
 /// Action for completion-call hooks.
+// One action is produced per model turn, so the size spread is irrelevant;
+// boxing `Patch` would only make every hook's constructor noisier.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum CompletionCallAction {
     /// Send the baseline request.
@@ -1050,6 +1091,10 @@ pub enum ToolCallAction {
     Skip(String),
     /// Stop the run.
     Stop(String),
+    /// Hold the call for a decision made outside the run. The run ends with
+    /// [`PromptError::ApprovalRequired`](crate::completion::PromptError::ApprovalRequired);
+    /// see [`approval`](crate::agent::approval).
+    Defer(String),
 }
 
 impl ToolCallAction {
@@ -1079,6 +1124,12 @@ impl ToolCallAction {
     pub fn stop(reason: impl Into<String>) -> Self {
         Self::Stop(reason.into())
     }
+
+    /// Creates an action that holds the tool call until it is approved,
+    /// denied or edited through [`PendingApprovals`](crate::agent::PendingApprovals).
+    pub fn defer(reason: impl Into<String>) -> Self {
+        Self::Defer(reason.into())
+    }
 }
 
 /// Action for post-tool hooks.
@@ -1274,9 +1325,10 @@ pub trait AgentHook: WasmCompatSend + WasmCompatSync {
 
     /// Runs before a valid tool call is executed.
     ///
-    /// The hook may rewrite the current arguments, skip execution, or stop the
-    /// run. Rewrites in a [`HookStack`] are passed to subsequent hooks. The
-    /// default action executes with the current arguments.
+    /// The hook may rewrite the current arguments, skip execution, defer the
+    /// call for approval, or stop the run. Rewrites in a [`HookStack`] are
+    /// passed to subsequent hooks. The default action executes with the
+    /// current arguments.
     fn on_tool_call(
         &self,
         _ctx: &HookContext,
@@ -2288,6 +2340,7 @@ mod migrated_tests {
             tool_choice: None,
             chat_history: vec![],
             is_streaming: false,
+            argument_errors: Vec::new(),
         }
     }
 
@@ -101,10 +101,14 @@
 //! # Ok(())
 //! # }
 //! ```
+pub mod approval;
+pub mod budget;
 mod builder;
 mod completion;
+pub mod fallback;
 pub mod hook;
 pub mod model;
+pub mod moderation;
 pub(crate) mod prompt_request;
 pub mod run;
 pub mod runner;
@@ -114,8 +118,11 @@ mod tool;
 /// configured name.
 pub(crate) const UNKNOWN_AGENT_NAME: &str = "Unnamed Agent";
 
+pub use approval::{ApprovalDecision, ApprovalRequest, PendingApprovals};
+pub use budget::{BudgetExceeded, BudgetGuard, BudgetLimit, BudgetScope};
 pub use builder::{AgentBuilder, NoToolConfig, WithBuilderTools, WithToolServerHandle};
 pub use completion::Agent;
+pub use fallback::{FallbackCondition, FallbackModel};
 pub use hook::CompletionCall as CompletionCallEvent;
 pub use hook::{
     AgentHook, CompletionCallAction, CompletionResponse as CompletionResponseEvent, HookContext,
@@ -125,6 +132,7 @@ pub use hook::{
     ToolCallAction, ToolCallDelta, ToolResultAction, ToolResultEvent,
 };
 pub use model::ModelHandle;
+pub use moderation::{FlaggedPrompt, FlaggedResponse, ModerationGuard};
 pub use prompt_request::streaming::{
     MultiTurnStreamItem, RUN_EVENTS_CAPACITY, RunEvents, StreamingError, StreamingPromptRequest,
     StreamingResult, stream_to_stdout,
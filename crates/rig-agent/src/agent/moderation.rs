@@ -0,0 +1,507 @@
+//! A built-in hook that screens prompts and responses with a moderation model.
+//!
+//! [`ModerationGuard`] sends the text of every turn's prompt to a
+//! [`ModerationModel`] before the model call, and the text of every completed
+//! model turn after it. What happens to flagged text is set separately for
+//! each side:
+//!
+//! ```
+//! use rig_agent::agent::{FlaggedPrompt, FlaggedResponse, ModerationGuard};
+//! # use rig_core::moderation::{ModerationError, ModerationModel, ModerationResponse};
+//! # struct Moderator;
+//! # impl ModerationModel for Moderator {
+//! #     async fn moderate(&self, _: Vec<String>) -> Result<ModerationResponse, ModerationError> {
+//! #         Ok(ModerationResponse::new(Vec::new(), "probe"))
+//! #     }
+//! # }
+//! # let moderation_model = Moderator;
+//!
+//! // With a provider client: `client.moderation_model("omni-moderation-latest")`.
+//! let guard = ModerationGuard::new(moderation_model)
+//!     .prompts(FlaggedPrompt::replace("[removed by moderation]"))
+//!     .responses(FlaggedResponse::regenerate(
+//!         "That answer was flagged by moderation. Answer again without that content.",
+//!     ))
+//!     .threshold(0.5);
+//! ```
+//!
+//! Blocking ends the run with
+//! [`PromptError::PromptCancelled`](crate::completion::PromptError::PromptCancelled),
+//! its reason naming the flagged categories. A failed moderation request also
+//! blocks: the guard fails closed rather than letting unchecked text through.
+
+use std::{collections::BTreeSet, fmt};
+
+use rig_core::{
+    message::{AssistantContent, Message, UserContent},
+    moderation::{ModerationModel, ModerationResponse},
+};
+
+use super::hook::{
+    AgentHook, CompletionCall, CompletionCallAction, HookContext, ModelTurnAction,
+    ModelTurnFinished, RequestPatch,
+};
+
+/// What a [`ModerationGuard`] does with a flagged prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlaggedPrompt {
+    /// Stop the run before the model call.
+    Block,
+    /// Send this text instead of the flagged prompt. The replacement is also
+    /// sent in place of the prompt on the run's later turns, while the run's
+    /// own history keeps the original.
+    Replace(String),
+    /// Do not moderate prompts.
+    Allow,
+}
+
+impl FlaggedPrompt {
+    /// Send `text` instead of a flagged prompt.
+    pub fn replace(text: impl Into<String>) -> Self {
+        Self::Replace(text.into())
+    }
+}
+
+/// What a [`ModerationGuard`] does with a flagged model response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlaggedResponse {
+    /// Stop the run.
+    Block,
+    /// Reject the turn and ask the model to answer again, with this text as
+    /// the user feedback. Retries count against the run's turn budget, and a
+    /// flagged turn carrying tool calls, which cannot be retried, is blocked.
+    Regenerate(String),
+    /// Do not moderate responses.
+    Allow,
+}
+
+impl FlaggedResponse {
+    /// Retry a flagged turn with `feedback` as the user feedback.
+    pub fn regenerate(feedback: impl Into<String>) -> Self {
+        Self::Regenerate(feedback.into())
+    }
+}
+
+/// Which side of the exchange a [`ModerationGuard`] flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screened {
+    Prompt,
+    Response,
+}
+
+impl fmt::Display for Screened {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prompt => f.write_str("prompt"),
+            Self::Response => f.write_str("response"),
+        }
+    }
+}
+
+/// Prompts replaced earlier in the current run, kept in the run's
+/// [`Scratchpad`](super::Scratchpad) so later turns send the replacement in
+/// their history too.
+#[derive(Debug, Clone, Default)]
+struct ReplacedPrompts(Vec<(Message, Message)>);
+
+/// An [`AgentHook`] that screens prompts and model responses with a
+/// [`ModerationModel`].
+///
+/// Prompts are checked in [`on_completion_call`](AgentHook::on_completion_call)
+/// and responses in [`on_model_turn_finished`](AgentHook::on_model_turn_finished),
+/// which both the blocking and the streaming surface fire. Only text is
+/// screened: tool results, images and reasoning pass through, and so does the
+/// history a run starts from. On the streaming surface a flagged turn's text
+/// deltas have already been emitted when the guard rejects it; consumers see
+/// the rejection as
+/// [`MultiTurnStreamItem::ModelTurnRetried`](crate::agent::MultiTurnStreamItem::ModelTurnRetried)
+/// or as the run's error.
+#[derive(Debug, Clone)]
+pub struct ModerationGuard<M> {
+    model: M,
+    prompts: FlaggedPrompt,
+    responses: FlaggedResponse,
+    threshold: Option<f64>,
+}
+
+impl<M> ModerationGuard<M>
+where
+    M: ModerationModel,
+{
+    /// A guard that blocks flagged prompts and flagged responses.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            prompts: FlaggedPrompt::Block,
+            responses: FlaggedResponse::Block,
+            threshold: None,
+        }
+    }
+
+    /// Set what happens to flagged prompts. Defaults to
+    /// [`FlaggedPrompt::Block`].
+    pub fn prompts(mut self, action: FlaggedPrompt) -> Self {
+        self.prompts = action;
+        self
+    }
+
+    /// Set what happens to flagged responses. Defaults to
+    /// [`FlaggedResponse::Block`].
+    pub fn responses(mut self, action: FlaggedResponse) -> Self {
+        self.responses = action;
+        self
+    }
+
+    /// Also treat any category scoring at least `threshold` as flagged, on top
+    /// of the categories the provider flags itself.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Moderate `texts` and return the names of the flagged categories, or
+    /// the reason to block when moderation itself failed.
+    async fn flagged_categories(
+        &self,
+        texts: Vec<String>,
+        screened: Screened,
+    ) -> Result<BTreeSet<String>, String> {
+        if texts.is_empty() {
+            return Ok(BTreeSet::new());
+        }
+        let response = self
+            .model
+            .moderate(texts)
+            .await
+            .map_err(|error| format!("moderation of the {screened} failed: {error}"))?;
+        Ok(self.categories_of(&response))
+    }
+
+    fn categories_of(&self, response: &ModerationResponse) -> BTreeSet<String> {
+        let mut flagged = BTreeSet::new();
+        for result in &response.results {
+            flagged.extend(result.flagged_categories().map(str::to_owned));
+            if let Some(threshold) = self.threshold {
+                flagged.extend(
+                    result
+                        .categories_scoring_at_least(threshold)
+                        .map(str::to_owned),
+                );
+            }
+            // A provider can flag an input without breaking out why.
+            if result.flagged && flagged.is_empty() {
+                flagged.insert("unspecified".to_owned());
+            }
+        }
+        flagged
+    }
+}
+
+fn blocked_reason(screened: Screened, categories: &BTreeSet<String>) -> String {
+    let categories: Vec<&str> = categories.iter().map(String::as_str).collect();
+    format!(
+        "moderation flagged the {screened}: {}",
+        categories.join(", ")
+    )
+}
+
+fn prompt_texts(prompt: &Message) -> Vec<String> {
+    match prompt {
+        Message::User { content } => content
+            .iter()
+            .filter_map(|content| match content {
+                UserContent::Text(text) => Some(text.text.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn response_texts(content: &[AssistantContent]) -> Vec<String> {
+    content
+        .iter()
+        .filter_map(|content| match content {
+            AssistantContent::Text(text) => Some(text.text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+impl<M> AgentHook for ModerationGuard<M>
+where
+    M: ModerationModel,
+{
+    async fn on_completion_call(
+        &self,
+        ctx: &HookContext,
+        event: CompletionCall<'_>,
+    ) -> CompletionCallAction {
+        let replacement = match &self.prompts {
+            FlaggedPrompt::Allow => return CompletionCallAction::Continue,
+            FlaggedPrompt::Block => None,
+            FlaggedPrompt::Replace(text) => Some(text),
+        };
+
+        let mut patch = RequestPatch::new();
+        let replaced = ctx
+            .scratchpad()
+            .get::<ReplacedPrompts>()
+            .unwrap_or_default();
+        if !replaced.0.is_empty() {
+            patch = patch.history(event.history.iter().map(|message| {
+                replaced
+                    .0
+                    .iter()
+                    .find(|(original, _)| original == message)
+                    .map_or_else(|| message.clone(), |(_, replacement)| replacement.clone())
+            }));
+        }
+
+        let categories = match self
+            .flagged_categories(prompt_texts(event.prompt), Screened::Prompt)
+            .await
+        {
+            Ok(categories) => categories,
+            Err(reason) => return CompletionCallAction::Stop(reason),
+        };
+        if !categories.is_empty() {
+            let Some(replacement) = replacement else {
+                return CompletionCallAction::Stop(blocked_reason(Screened::Prompt, &categories));
+            };
+            let replacement = Message::user(replacement.clone());
+            ctx.scratchpad().update(|replaced: &mut ReplacedPrompts| {
+                replaced.0.push((event.prompt.clone(), replacement.clone()));
+            });
+            patch = patch.prompt(replacement);
+        }
+
+        if patch.is_empty() {
+            CompletionCallAction::Continue
+        } else {
+            CompletionCallAction::Patch(patch)
+        }
+    }
+
+    async fn on_model_turn_finished(
+        &self,
+        _ctx: &HookContext,
+        event: ModelTurnFinished<'_>,
+    ) -> ModelTurnAction {
+        if self.responses == FlaggedResponse::Allow {
+            return ModelTurnAction::Continue;
+        }
+
+        let categories = match self
+            .flagged_categories(response_texts(event.content), Screened::Response)
+            .await
+        {
+            Ok(categories) => categories,
+            Err(reason) => return ModelTurnAction::Stop(reason),
+        };
+        if categories.is_empty() {
+            return ModelTurnAction::Continue;
+        }
+
+        let has_tool_call = event
+            .content
+            .iter()
+            .any(|content| matches!(content, AssistantContent::ToolCall(_)));
+        match &self.responses {
+            FlaggedResponse::Regenerate(feedback) if !has_tool_call => {
+                ModelTurnAction::retry_with_feedback(feedback.clone())
+            }
+            _ => ModelTurnAction::Stop(blocked_reason(Screened::Response, &categories)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    use rig_core::moderation::{
+        ModerationCategory, ModerationError, ModerationModel, ModerationResponse, ModerationResult,
+    };
+
+    use super::{FlaggedPrompt, FlaggedResponse, ModerationGuard};
+    use crate::{
+        agent::AgentBuilder,
+        completion::PromptError,
+        test_utils::{MockAddTool, MockCompletionModel, MockTurn},
+    };
+
+    /// Flags any input containing `"forbidden"` under `violence`, scoring
+    /// every input 0.4 under `hate`, and records what it was asked.
+    #[derive(Clone, Default)]
+    struct KeywordModerator {
+        seen: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl KeywordModerator {
+        fn seen(&self) -> Vec<String> {
+            self.seen.lock().expect("lock").clone()
+        }
+    }
+
+    impl ModerationModel for KeywordModerator {
+        async fn moderate(
+            &self,
+            inputs: Vec<String>,
+        ) -> Result<ModerationResponse, ModerationError> {
+            if self.fail {
+                return Err(ModerationError::ResponseError("unavailable".to_owned()));
+            }
+            self.seen.lock().expect("lock").extend(inputs.clone());
+            let results = inputs
+                .iter()
+                .map(|input| {
+                    ModerationResult::from_categories(BTreeMap::from([
+                        (
+                            "violence".to_owned(),
+                            ModerationCategory {
+                                flagged: input.contains("forbidden"),
+                                score: 0.9,
+                            },
+                        ),
+                        (
+                            "hate".to_owned(),
+                            ModerationCategory {
+                                flagged: false,
+                                score: 0.4,
+                            },
+                        ),
+                    ]))
+                })
+                .collect();
+            Ok(ModerationResponse::new(results, "probe"))
+        }
+    }
+
+    fn cancel_reason(error: PromptError) -> String {
+        let PromptError::PromptCancelled { reason, .. } = error else {
+            panic!("expected PromptCancelled, got {error:?}");
+        };
+        reason
+    }
+
+    #[tokio::test]
+    async fn flagged_prompts_are_blocked_before_the_model_call() {
+        let model = MockCompletionModel::from_turns([MockTurn::text("unreachable")]);
+        let agent = AgentBuilder::new(model.clone())
+            .add_hook(ModerationGuard::new(KeywordModerator::default()))
+            .build();
+
+        let error = agent
+            .runner("something forbidden")
+            .run()
+            .await
+            .expect_err("the prompt is flagged");
+        assert_eq!(
+            cancel_reason(error),
+            "moderation flagged the prompt: violence"
+        );
+        assert!(model.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replaced_prompts_stay_replaced_on_later_turns() {
+        let model = MockCompletionModel::from_turns([
+            MockTurn::tool_call("tc1", "add", serde_json::json!({"x": 2, "y": 3})),
+            MockTurn::text("5"),
+        ]);
+        let agent = AgentBuilder::new(model.clone())
+            .tool(MockAddTool)
+            .add_hook(
+                ModerationGuard::new(KeywordModerator::default())
+                    .prompts(FlaggedPrompt::replace("add 2 and 3")),
+            )
+            .build();
+
+        let response = agent
+            .runner("forbidden: add 2 and 3")
+            .max_turns(3)
+            .run()
+            .await
+            .expect("the replacement is sent");
+        assert_eq!(response.output, "5");
+
+        let requests = model.requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            let sent = serde_json::to_string(&request.chat_history).expect("serialize");
+            assert!(!sent.contains("forbidden"), "{sent}");
+            assert!(sent.contains("add 2 and 3"), "{sent}");
+        }
+    }
+
+    #[tokio::test]
+    async fn flagged_responses_are_regenerated_or_blocked() {
+        let moderator = KeywordModerator::default();
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([
+            MockTurn::text("a forbidden answer"),
+            MockTurn::text("a fine answer"),
+        ]))
+        .add_hook(
+            ModerationGuard::new(moderator.clone())
+                .responses(FlaggedResponse::regenerate("answer again")),
+        )
+        .build();
+        let response = agent
+            .runner("hello")
+            .max_turns(3)
+            .run()
+            .await
+            .expect("the second answer passes");
+        assert_eq!(response.output, "a fine answer");
+        assert_eq!(
+            moderator.seen(),
+            [
+                "hello",
+                "a forbidden answer",
+                "answer again",
+                "a fine answer"
+            ]
+        );
+
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([MockTurn::text(
+            "a forbidden answer",
+        )]))
+        .add_hook(ModerationGuard::new(KeywordModerator::default()))
+        .build();
+        let error = agent.runner("hello").run().await.expect_err("blocked");
+        assert_eq!(
+            cancel_reason(error),
+            "moderation flagged the response: violence"
+        );
+    }
+
+    #[tokio::test]
+    async fn threshold_flags_high_scoring_categories_and_failures_block() {
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([MockTurn::text("hi")]))
+            .add_hook(
+                ModerationGuard::new(KeywordModerator::default())
+                    .responses(FlaggedResponse::Allow)
+                    .threshold(0.3),
+            )
+            .build();
+        let error = agent.runner("hello").run().await.expect_err("hate > 0.3");
+        assert_eq!(
+            cancel_reason(error),
+            "moderation flagged the prompt: hate, violence"
+        );
+
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([MockTurn::text("hi")]))
+            .add_hook(ModerationGuard::new(KeywordModerator {
+                fail: true,
+                ..KeywordModerator::default()
+            }))
+            .build();
+        let error = agent.runner("hello").run().await.expect_err("fails closed");
+        assert!(cancel_reason(error).starts_with("moderation of the prompt failed"),);
+    }
+}
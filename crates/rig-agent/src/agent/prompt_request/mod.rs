@@ -6,6 +6,7 @@ use rig_core::{
     message::{
         AssistantContent, ProviderCallId, ToolCallId, ToolResultContent, UserContent, non_empty,
     },
+    pricing::Cost,
     wasm_compat::{WasmBoxedFuture, WasmCompatSend},
 };
 
@@ -349,6 +350,10 @@ pub struct CompletionCall {
     /// for. `None` means the provider did not report one, never an error.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub provider_request_id: Option<String>,
+    /// Label of the model that served this call when the agent's model routes
+    /// among several, e.g. a [`FallbackModel`](crate::agent::FallbackModel).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
     /// Why the model stopped generating on this call, when the provider
     /// reported it. `None` means the provider reported no reason.
     ///
@@ -390,6 +395,7 @@ impl CompletionCall {
             message_id: None,
             response_id: None,
             provider_request_id: None,
+            served_by: None,
             finish_reason: None,
             raw: serde_json::Value::Null,
         }
@@ -406,6 +412,7 @@ impl CompletionCall {
         self.message_id = identity.message_id;
         self.response_id = identity.response_id;
         self.provider_request_id = identity.provider_request_id;
+        self.served_by = identity.served_by;
         self
     }
 
@@ -425,6 +432,7 @@ impl CompletionCall {
             message_id: self.message_id.clone(),
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: self.served_by.clone(),
         }
     }
 }
@@ -473,6 +481,10 @@ pub struct PromptResponse {
     /// Where [`output`](Self::output) is the concatenated text, this preserves
     /// the individual content parts (text, reasoning, images, …).
     pub content: Vec<AssistantContent>,
+    /// Cost of [`usage`](Self::usage), when the agent was built with
+    /// [`AgentBuilder::pricing`](crate::agent::AgentBuilder::pricing).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<Cost>,
     /// Number of synthetic output-tool calls in the turn that finalized this
     /// response. Kept crate-private because it is runner bookkeeping rather
     /// than provider-facing response content.
@@ -495,6 +507,7 @@ impl PromptResponse {
             usage,
             completion_calls: Vec::new(),
             messages: None,
+            cost: None,
             output_tool_calls: 0,
         }
     }
@@ -521,6 +534,12 @@ impl PromptResponse {
         self
     }
 
+    /// Attach the cost of this run's usage.
+    pub fn with_cost(mut self, cost: Cost) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
     pub(crate) fn with_output_tool_calls(mut self, count: usize) -> Self {
         self.output_tool_calls = count;
         self
@@ -540,6 +559,11 @@ impl PromptResponse {
         self.usage
     }
 
+    /// Cost of the run's usage, if the agent prices it.
+    pub fn cost(&self) -> Option<Cost> {
+        self.cost
+    }
+
     /// The run's accumulated message history, if tracked.
     pub fn messages(&self) -> Option<&[Message]> {
         self.messages.as_deref()
@@ -2210,6 +2234,127 @@ mod tests {
         }));
     }
 
+    /// Retries a schema-violating call with the validator's feedback,
+    /// recording every context it resolves.
+    #[derive(Clone, Default)]
+    struct RetryWithArgumentFeedbackHook {
+        contexts: Arc<Mutex<Vec<InvalidToolCallContext>>>,
+    }
+
+    impl AgentHook for RetryWithArgumentFeedbackHook {
+        async fn on_invalid_tool_call(
+            &self,
+            _ctx: &HookContext,
+            event: &InvalidToolCallContext,
+        ) -> Option<InvalidToolCallAction> {
+            self.contexts
+                .lock()
+                .expect("invalid tool context records mutex was poisoned")
+                .push(event.clone());
+            event.argument_feedback().map(InvalidToolCallAction::retry)
+        }
+    }
+
+    #[tokio::test]
+    async fn schema_violating_tool_arguments_are_retried_before_dispatch() {
+        let add_calls = Arc::new(AtomicU32::new(0));
+        let model = MockCompletionModel::new([
+            MockTurn::tool_call("tool_call_1", "add", json!({"x": "2"})),
+            MockTurn::tool_call("tool_call_2", "add", json!({"x": 2, "y": 3})),
+            MockTurn::text("done"),
+        ]);
+        let recorded = model.clone();
+        let hook = RetryWithArgumentFeedbackHook::default();
+        let agent = AgentBuilder::new(model)
+            .tool(CountingAddTool {
+                calls: add_calls.clone(),
+            })
+            .validate_tool_arguments(true)
+            .build();
+
+        let response = agent
+            .prompt("add")
+            .add_hook(hook.clone())
+            .max_invalid_tool_call_retries(1)
+            .max_turns(4)
+            .await
+            .expect("the corrected call should run");
+
+        assert_eq!(response, "done");
+        assert_eq!(
+            add_calls.load(Ordering::SeqCst),
+            1,
+            "only the valid call runs"
+        );
+        let contexts = hook
+            .contexts
+            .lock()
+            .expect("invalid tool context records mutex was poisoned")
+            .clone();
+        let [context] = contexts.as_slice() else {
+            panic!("expected one invalid call, got {contexts:?}");
+        };
+        assert_eq!(
+            context
+                .argument_errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "$.y: required property is missing",
+                r#"$.x: expected number, got string "2""#,
+            ]
+        );
+        let retry_history = &recorded.requests()[1].chat_history;
+        assert!(retry_history.iter().any(|message| {
+            matches!(
+                message,
+                Message::User { content }
+                    if content.iter().any(|content| matches!(
+                        content,
+                        UserContent::ToolResult(result)
+                            if result.content.iter().any(|content| matches!(
+                                content,
+                                rig_core::message::ToolResultContent::Text(text)
+                                    if text.text.contains(r#"$.x: expected number, got string "2""#)
+                            ))
+                    ))
+            )
+        }));
+    }
+
+    #[tokio::test]
+    async fn unresolved_schema_violations_fail_the_run() {
+        let add_calls = Arc::new(AtomicU32::new(0));
+        let agent = AgentBuilder::new(MockCompletionModel::new([MockTurn::tool_call(
+            "tool_call_1",
+            "add",
+            json!({"x": 2, "y": [3]}),
+        )]))
+        .tool(CountingAddTool {
+            calls: add_calls.clone(),
+        })
+        .validate_tool_arguments(true)
+        .build();
+
+        let err = agent
+            .prompt("add")
+            .max_turns(2)
+            .await
+            .expect_err("no hook resolves the call");
+
+        let PromptError::InvalidToolArguments {
+            tool_name, errors, ..
+        } = err
+        else {
+            panic!("expected InvalidToolArguments, got {err:?}");
+        };
+        assert_eq!(tool_name, "add");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "$.y");
+        assert_eq!(add_calls.load(Ordering::SeqCst), 0);
+    }
+
     #[tokio::test]
     async fn invalid_tool_call_hook_retries_mixed_non_streaming_turn_without_executing_valid_call()
     {
@@ -3317,6 +3462,7 @@ mod tests {
                 message_id: Some("msg_1".into()),
                 response_id: Some("resp_1".into()),
                 provider_request_id: Some("req_1".into()),
+                served_by: Some("primary".into()),
             },
         );
         let json = serde_json::to_string(&call).expect("serialize");
@@ -5,6 +5,7 @@ use rig_core::{
 };
 
 use crate::{
+    agent::approval::{ApprovalRequest, PendingApprovals},
     agent::completion::{PreparedCompletionRequest, build_prepared_completion_request},
     agent::hook::{
         AgentHook, HookContext, HookStack, InvalidToolCallAction, ModelSelection,
@@ -17,9 +18,9 @@ use crate::{
         streamed::{StreamedResolution, StreamedTurnAssembler, StreamedTurnEvent},
     },
     agent::runner::{
-        AgentRunner, CompletionCallOutcome, ModelTurnDecision, ToolExecution, append_run_messages,
-        build_chat_span, new_execute_tool_span, observe_action, resolve_completion_call,
-        resolve_model_turn_action, run_single_tool,
+        AgentRunner, CompletionCallOutcome, ModelTurnDecision, ToolCallResolution, ToolExecution,
+        append_run_messages, build_chat_span, new_execute_tool_span, observe_action,
+        resolve_completion_call, resolve_model_turn_action, run_single_tool,
     },
     streaming::{StreamedAssistantContent, StreamedUserContent, ToolCallDeltaContent},
     tool::{ToolContext, server::ToolRegistrySnapshot},
@@ -31,7 +32,7 @@ use tracing_futures::Instrument;
 
 use super::{CompletionCall, PromptResponse, forward_prompt_setters};
 use crate::{
-    agent::{Agent, model::ModelHandle},
+    agent::{Agent, BudgetExceeded, model::ModelHandle},
     completion::{CompletionError, PromptError},
 };
 use rig_core::message::{Message, Text};
@@ -445,7 +446,7 @@ pub(crate) fn store_error_usage(runner: &AgentRunner, run: &AgentRun) {
 /// a [`TurnSource`]. The streaming surface forwards the yielded [`DriveItem`]s;
 /// the blocking surface folds them to `Done`.
 pub(crate) fn drive_agent<S>(
-    runner: AgentRunner,
+    mut runner: AgentRunner,
     mut source: S,
     mut run: AgentRun,
     agent_span: tracing::Span,
@@ -460,7 +461,8 @@ where
         // Run-scoped hook context: minted once, shared by every hook event on
         // both surfaces. `is_streaming` records which surface is driving; the
         // per-turn index is advanced on each `CallModel` step below.
-        let hook_ctx = HookContext::new(is_streaming, runner.config.name.clone());
+        let hook_ctx = HookContext::new(is_streaming, runner.config.name.clone())
+            .with_conversation_id(runner.config.conversation_id.clone());
         // Set only after a model turn commits successfully and consumed by its
         // immediately following CallTools step. This keeps the sans-IO run state
         // serializable while pinning execution to the definitions sent that turn.
@@ -471,6 +473,10 @@ where
         // is invoked, so a completion-call stop, selection stop, or preparation
         // failure leaves it unchanged while a provider error still counts.
         let mut previous_model: Option<ModelHandle> = None;
+        // Recorded in each checkpoint so a resumed run appends to the same
+        // memory this one would have.
+        runner.appends_to_memory = memory_handle.is_some();
+        runner.save_checkpoint(&run).await;
 
         // Drive one medium-specific step stream: forward its items, and on the
         // first error store error usage, surface it, and end the run. A macro
@@ -523,7 +529,13 @@ where
                         match resolve_completion_call(&runner.config.hooks, &hook_ctx, &prompt, &history, turn).await {
                             CompletionCallOutcome::Terminate(reason) => {
                                 store_error_usage(&runner, &run);
-                                yield Err(StreamingError::Prompt(Box::new(run.cancel_error(reason))));
+                                // A `BudgetGuard` leaves its verdict in the
+                                // scratchpad so the stop surfaces as a distinct error.
+                                let error = match hook_ctx.scratchpad().remove::<BudgetExceeded>() {
+                                    Some(exceeded) => PromptError::budget_exceeded(run.full_history(), exceeded),
+                                    None => run.cancel_error(reason),
+                                };
+                                yield Err(StreamingError::Prompt(Box::new(error)));
                                 break 'outer;
                             }
                             CompletionCallOutcome::Proceed(request_patch) => request_patch,
@@ -611,15 +623,42 @@ where
                         prompt,
                     ));
                     pending_tool_snapshot = Some(turn_tool_snapshot);
+                    runner.save_checkpoint(&run).await;
                 }
                 AgentRunStep::CallTools { calls } => {
-                    let Some(tool_snapshot) = pending_tool_snapshot.take() else {
-                        store_error_usage(&runner, &run);
-                        yield Err(StreamingError::Completion(CompletionError::ResponseError(
-                            "agent requested tool execution without a prepared registry snapshot"
-                                .to_string(),
-                        )));
-                        break 'outer;
+                    let tool_snapshot = match pending_tool_snapshot.take() {
+                        Some(tool_snapshot) => tool_snapshot,
+                        // A resumed run lost the snapshot pinned by the turn
+                        // that requested these calls; pin the registry as it
+                        // stands now.
+                        None if runner.is_resumed() => match runner.resumed_tool_snapshot(&run).await {
+                            Ok(tool_snapshot) => tool_snapshot,
+                            Err(err) => {
+                                store_error_usage(&runner, &run);
+                                yield Err(err.into());
+                                break 'outer;
+                            }
+                        },
+                        None => {
+                            store_error_usage(&runner, &run);
+                            yield Err(StreamingError::Completion(CompletionError::ResponseError(
+                                "agent requested tool execution without a prepared registry snapshot"
+                                    .to_string(),
+                            )));
+                            break 'outer;
+                        }
+                    };
+                    // The batch a paused run stopped on: apply its decisions.
+                    let calls = match runner.approvals.take() {
+                        Some(approvals) => match approvals.resolve_calls(calls, &mut runner.approved_actions) {
+                            Ok(calls) => calls,
+                            Err(err) => {
+                                store_error_usage(&runner, &run);
+                                yield Err(Box::new(err).into());
+                                break 'outer;
+                            }
+                        },
+                        None => calls,
                     };
                     drive_step!('outer, source.run_tool_calls(
                         &runner,
@@ -628,8 +667,16 @@ where
                         calls,
                         tool_snapshot,
                     ));
+                    runner.save_checkpoint(&run).await;
                 }
-                AgentRunStep::Done(response) => {
+                AgentRunStep::Done(mut response) => {
+                    if let Some(pricing) = &runner.config.pricing {
+                        let cost = pricing.cost(&response.usage);
+                        if created_agent_span {
+                            agent_span.record("gen_ai.usage.cost", cost.total());
+                        }
+                        response = response.with_cost(cost);
+                    }
                     // Run-completion marker, unifying the blocking and streaming
                     // drivers' run-finished logs into one shared event.
                     tracing::info!(
@@ -643,6 +690,7 @@ where
                         response.messages.as_deref().unwrap_or_default(),
                     )
                     .await;
+                    runner.delete_checkpoint().await;
                     // Build the final item only when the surface forwards it
                     // (streaming). The blocking fold discards it, so its source
                     // returns `None` and the extra full-response clone is skipped.
@@ -721,6 +769,12 @@ where
         internal_call_id: String,
         surface: ToolSurface,
     }
+    // A settled call, or one a hook deferred for approval (boxed: the
+    // request carries the whole tool call).
+    enum CollectedToolCall {
+        Settled(CollectedToolResult),
+        Deferred(Box<ApprovalRequest>),
+    }
 
     Box::pin(async_stream::stream! {
         let full_history_for_errors = run.full_history();
@@ -764,6 +818,7 @@ where
         // no successful result is surfaced or committed.
         let mut collected: Vec<Option<CollectedToolResult>> =
             (0..call_count).map(|_| None).collect();
+        let mut deferred: Vec<ApprovalRequest> = Vec::new();
         let mut first_error: Option<(usize, PromptError)> = None;
 
         {
@@ -784,11 +839,11 @@ where
                         if let Some(result) = preresolved_result {
                             return (
                                 index,
-                                Some(Ok(CollectedToolResult {
+                                Some(Ok(CollectedToolCall::Settled(CollectedToolResult {
                                     content: result,
                                     internal_call_id,
                                     surface: ToolSurface::Preresolved,
-                                })),
+                                }))),
                             );
                         }
                         // `None` marks a dropped (never-started) sibling.
@@ -804,17 +859,28 @@ where
                             full_history_for_errors,
                         )
                         .await;
-                        let mapped = outcome.map(|o| {
-                            let surface = match o.execution {
-                                ToolExecution::Executed(effective) => {
-                                    ToolSurface::Executed(effective)
-                                }
-                                ToolExecution::Skipped => ToolSurface::Skipped,
-                            };
-                            CollectedToolResult {
-                                content: o.content,
-                                internal_call_id,
-                                surface,
+                        let mapped = outcome.map(|resolution| match resolution {
+                            ToolCallResolution::Settled(o) => {
+                                let surface = match o.execution {
+                                    ToolExecution::Executed(effective) => {
+                                        ToolSurface::Executed(effective)
+                                    }
+                                    ToolExecution::Skipped => ToolSurface::Skipped,
+                                };
+                                CollectedToolCall::Settled(CollectedToolResult {
+                                    content: o.content,
+                                    internal_call_id,
+                                    surface,
+                                })
+                            }
+                            ToolCallResolution::Deferred { reason, args } => {
+                                CollectedToolCall::Deferred(Box::new(ApprovalRequest::new(
+                                    index,
+                                    tool_call,
+                                    args,
+                                    internal_call_id,
+                                    reason,
+                                )))
                             }
                         });
                         (index, Some(mapped))
@@ -831,11 +897,12 @@ where
                     None => continue,
                 };
                 match result {
-                    Ok(collected_result) => {
+                    Ok(CollectedToolCall::Settled(collected_result)) => {
                         if let Some(slot) = collected.get_mut(index) {
                             *slot = Some(collected_result);
                         }
                     }
+                    Ok(CollectedToolCall::Deferred(request)) => deferred.push(*request),
                     Err(err) => {
                         // Fail-fast: stop starting new siblings; keep draining
                         // in-flight ones so the lowest call-index terminator wins.
@@ -855,6 +922,27 @@ where
             return;
         }
 
+        // A deferral pauses the batch: nothing is surfaced or committed, and
+        // the settled results wait in the pending approvals for the decisions.
+        if !deferred.is_empty() {
+            let settled = collected
+                .into_iter()
+                .map(|slot| slot.map(|collected_result| collected_result.content))
+                .collect();
+            let pending = PendingApprovals::new(
+                deferred,
+                settled,
+                runner.prompt.clone(),
+                runner.config.conversation_id.clone(),
+                runner.appends_to_memory,
+                run.clone(),
+            );
+            yield Err(StreamingError::Prompt(Box::new(PromptError::ApprovalRequired(
+                Box::new(pending),
+            ))));
+            return;
+        }
+
         // Success: prepare each call's stream items and results in call order,
         // commit the results, then surface the buffered items. An executed call
         // surfaces `ToolExecutionCommitted`
@@ -1029,7 +1117,8 @@ impl TurnSource for StreamingTurnSource {
             let mut assembler = StreamedTurnAssembler::new(
                 prepared.executable_tool_names.clone(),
                 prepared.allowed_tool_names.clone(),
-            );
+            )
+            .with_tool_schemas(prepared.tool_schemas.clone());
             let mut completion_call_emitted = false;
             let mut turn_abandoned = false;
             let mut provider_final_seen = false;
@@ -1561,14 +1650,26 @@ impl AgentRunner {
     /// hook handling with the blocking [`run`](AgentRunner::run) via
     /// `drive_agent`, so the two behave identically apart from the streamed
     /// delta events.
-    pub async fn stream(self) -> StreamingResult {
+    pub async fn stream(mut self) -> StreamingResult {
+        let resumed = self.load_checkpoint().await;
         let (agent_span, created_agent_span) = self.open_agent_span();
 
-        let (history_override, memory_handle) = match self.resolve_history_and_memory().await {
+        let resolved = match resumed {
+            Ok(Some(resumed)) => Ok(resumed),
+            Ok(None) => self
+                .resolve_history_and_memory()
+                .await
+                .map(|(history_override, memory_handle)| {
+                    (self.build_run(history_override), memory_handle)
+                })
+                .map_err(StreamingError::from),
+            Err(err) => Err(StreamingError::Prompt(Box::new(err))),
+        };
+        let (run, memory_handle) = match resolved {
             Ok(resolved) => resolved,
             Err(err) => {
                 let stream = async_stream::stream! {
-                    yield Err(StreamingError::from(err));
+                    yield Err(err);
                 };
                 // Instrument under the agent span like the success path so
                 // a load failure stays tied to invoke_agent.
@@ -1576,7 +1677,6 @@ impl AgentRunner {
             }
         };
 
-        let run = self.build_run(history_override);
         let source = StreamingTurnSource::new(
             &self.config.hooks,
             self.agent_name_or_default().to_string(),
@@ -4153,6 +4253,86 @@ mod migrated_tests {
         assert_eq!(recorded.request_count(), 2);
     }
 
+    #[tokio::test]
+    async fn schema_violating_streamed_tool_arguments_are_retried_before_dispatch() {
+        #[derive(Clone, Default)]
+        struct RetryWithArgumentFeedback {
+            contexts: Arc<Mutex<Vec<InvalidToolCallContext>>>,
+        }
+
+        impl AgentHook for RetryWithArgumentFeedback {
+            async fn on_invalid_tool_call(
+                &self,
+                _ctx: &HookContext,
+                event: &InvalidToolCallContext,
+            ) -> Option<InvalidToolCallAction> {
+                self.contexts
+                    .lock()
+                    .expect("invalid tool context records mutex was poisoned")
+                    .push(event.clone());
+                event.argument_feedback().map(InvalidToolCallAction::retry)
+            }
+        }
+
+        let add_calls = Arc::new(AtomicU32::new(0));
+        let model = MockCompletionModel::from_stream_turns([
+            vec![
+                MockStreamEvent::tool_call("tool_call_1", "add", serde_json::json!({"x": 2})),
+                MockStreamEvent::final_response_with_total_tokens(4),
+            ],
+            vec![
+                MockStreamEvent::text("done"),
+                MockStreamEvent::final_response_with_total_tokens(6),
+            ],
+        ]);
+        let recorded = model.clone();
+        let hook = RetryWithArgumentFeedback::default();
+        let agent = AgentBuilder::new(model)
+            .tool(CountingAddTool {
+                calls: add_calls.clone(),
+            })
+            .validate_tool_arguments(true)
+            .build();
+
+        let mut stream = agent
+            .stream_prompt("use the tool")
+            .add_hook(hook.clone())
+            .max_turns(3)
+            .max_invalid_tool_call_retries(1)
+            .await;
+        let mut final_response_text = None;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(MultiTurnStreamItem::FinalResponse(response)) => {
+                    final_response_text = Some(response.output().to_string());
+                }
+                Ok(_) => {}
+                Err(err) => panic!("unexpected streaming error: {err:?}"),
+            }
+        }
+
+        assert_eq!(final_response_text.as_deref(), Some("done"));
+        assert_eq!(add_calls.load(Ordering::SeqCst), 0);
+        let contexts = hook
+            .contexts
+            .lock()
+            .expect("invalid tool context records mutex was poisoned")
+            .clone();
+        let [context] = contexts.as_slice() else {
+            panic!("expected one invalid call, got {contexts:?}");
+        };
+        assert!(context.is_streaming);
+        assert_eq!(context.argument_errors.len(), 1);
+        assert_eq!(context.argument_errors[0].path, "$.y");
+        let retry_history = &recorded.requests()[1].chat_history;
+        let retry_history =
+            serde_json::to_string(retry_history).expect("history serializes to JSON");
+        assert!(
+            retry_history.contains("$.y: required property is missing"),
+            "the retry feedback reaches the model: {retry_history}"
+        );
+    }
+
     #[tokio::test]
     async fn invalid_tool_call_context_uses_completed_streaming_tool_call_provider_id() {
         let invalid_hook = RecordingInvalidToolCallHook::default();
@@ -65,6 +65,7 @@
 //! ```
 
 pub mod output_mode;
+pub mod store;
 pub mod streamed;
 
 pub use output_mode::OutputMode;
@@ -88,6 +89,7 @@ use crate::{
     },
     completion::{Message, PromptError, Usage},
     json_utils,
+    tool::{ArgumentViolation, schema::validate_arguments},
 };
 
 pub use streamed::{
@@ -114,11 +116,43 @@ fn unknown_tool_call_error(
     }
 }
 
+/// Schema violations in a call to `tool_name`; empty when `tool_schemas`
+/// holds no schema for the tool, i.e. its arguments are not validated.
+pub(crate) fn argument_violations(
+    tool_schemas: &BTreeMap<String, serde_json::Value>,
+    tool_name: &str,
+    args: &serde_json::Value,
+) -> Vec<ArgumentViolation> {
+    tool_schemas
+        .get(tool_name)
+        .map(|schema| validate_arguments(schema, args))
+        .unwrap_or_default()
+}
+
+/// Whether `tool_call` must go through invalid tool-call resolution: its
+/// tool is not allowed this turn, or its arguments violate the tool's schema.
+fn is_invalid_call(
+    tool_call: &ToolCall,
+    allowed_tool_names: &BTreeSet<String>,
+    tool_schemas: &BTreeMap<String, serde_json::Value>,
+) -> bool {
+    !allowed_tool_names.contains(&tool_call.function.name)
+        || !argument_violations(
+            tool_schemas,
+            &tool_call.function.name,
+            &tool_call.function.arguments,
+        )
+        .is_empty()
+}
+
 #[derive(Clone, Copy)]
 struct InvalidToolCallDiagnostic<'a> {
     tool_call: &'a ToolCall,
     executable_tool_names: &'a BTreeSet<String>,
     allowed_tool_names: &'a BTreeSet<String>,
+    tool_schemas: &'a BTreeMap<String, serde_json::Value>,
+    /// Schema violations of the call; empty when it was rejected by name.
+    argument_errors: &'a [ArgumentViolation],
     history: &'a [Message],
 }
 
@@ -132,8 +166,22 @@ impl InvalidToolCallDiagnostic<'_> {
         )
     }
 
-    fn unknown_current(&self) -> PromptError {
-        self.unknown(self.tool_call.function.name.clone())
+    fn invalid_arguments(&self, tool_name: String, errors: Vec<ArgumentViolation>) -> PromptError {
+        PromptError::InvalidToolArguments {
+            tool_name,
+            errors,
+            chat_history: Box::new(self.history.to_vec()),
+        }
+    }
+
+    /// The error that fails the run when the current call stays unresolved.
+    fn unresolved(&self) -> PromptError {
+        let tool_name = self.tool_call.function.name.clone();
+        if self.argument_errors.is_empty() {
+            self.unknown(tool_name)
+        } else {
+            self.invalid_arguments(tool_name, self.argument_errors.to_vec())
+        }
     }
 
     fn cancelled(&self, reason: String) -> PromptError {
@@ -205,6 +253,10 @@ pub struct ModelTurn {
     pub response_id: Option<String>,
     /// The provider's transport request id for this attempt, when reported.
     pub provider_request_id: Option<String>,
+    /// Label of the model that served this attempt when the agent's model
+    /// routes among several; see [`CompletionCall::served_by`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
     /// The assistant content returned by the model.
     pub choice: Vec<AssistantContent>,
     /// Token usage reported by the provider for this completion request.
@@ -213,6 +265,12 @@ pub struct ModelTurn {
     pub executable_tool_names: BTreeSet<String>,
     /// Tools allowed by the active [`ToolChoice`] for this turn.
     pub allowed_tool_names: BTreeSet<String>,
+    /// Parameter schemas of the tools whose arguments are validated before
+    /// dispatch, keyed by tool name. A call whose arguments violate its
+    /// tool's schema goes through invalid tool-call resolution. Empty unless
+    /// the agent validates tool arguments.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_schemas: BTreeMap<String, serde_json::Value>,
     /// Why the model stopped generating on this turn, when the provider
     /// reported it. Carried so the blocking surface records the same terminal
     /// reason the streamed surface does (rig#2322).
@@ -243,10 +301,12 @@ impl ModelTurn {
             message_id,
             response_id: None,
             provider_request_id: None,
+            served_by: None,
             choice,
             usage,
             executable_tool_names,
             allowed_tool_names,
+            tool_schemas: BTreeMap::new(),
             finish_reason: None,
             raw: serde_json::Value::Null,
         }
@@ -263,6 +323,19 @@ impl ModelTurn {
         self
     }
 
+    /// Attach the label of the model that served this attempt.
+    pub fn with_served_by(mut self, served_by: Option<String>) -> Self {
+        self.served_by = served_by;
+        self
+    }
+
+    /// Validate tool-call arguments against these parameter schemas, keyed
+    /// by tool name.
+    pub fn with_tool_schemas(mut self, tool_schemas: BTreeMap<String, serde_json::Value>) -> Self {
+        self.tool_schemas = tool_schemas;
+        self
+    }
+
     /// Attach the terminal finish reason this attempt reported.
     pub fn with_finish_reason(mut self, finish_reason: Option<FinishReason>) -> Self {
         self.finish_reason = finish_reason;
@@ -281,6 +354,9 @@ impl ModelTurn {
 ///
 /// Deliberately exhaustive: a driver must handle every outcome, so adding a
 /// variant is a breaking change by design.
+// One outcome is produced per model turn, so the size spread is irrelevant;
+// boxing `NeedsResolution` would only make every driver's match noisier.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ModelTurnOutcome {
     /// The turn was accepted. Unless `response_hook_suppressed` is set, the
@@ -317,6 +393,9 @@ struct ResolvingState {
     next_index: usize,
     executable_tool_names: BTreeSet<String>,
     allowed_tool_names: BTreeSet<String>,
+    /// Parameter schemas of the tools whose arguments are validated.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tool_schemas: BTreeMap<String, serde_json::Value>,
     /// Synthetic tool results for skipped tool calls, keyed by the call's
     /// position in `items` — never by the tool-call id, which is empty for
     /// every call on id-less wires (older ollama daemons) and would collide
@@ -329,13 +408,16 @@ struct ResolvingState {
 }
 
 /// The invalid tool call resolution is currently parked on, if any: the item
-/// at `next_index` when it is a tool call outside the allowed set.
+/// at `next_index` when it is a tool call outside the allowed set or with
+/// arguments violating its schema.
 fn pending_invalid_call(resolving: &ResolvingState) -> Option<&ToolCall> {
     match resolving.items.get(resolving.next_index) {
         Some(AssistantContent::ToolCall(tool_call))
-            if !resolving
-                .allowed_tool_names
-                .contains(&tool_call.function.name) =>
+            if is_invalid_call(
+                tool_call,
+                &resolving.allowed_tool_names,
+                &resolving.tool_schemas,
+            ) =>
         {
             Some(tool_call)
         }
@@ -343,6 +425,25 @@ fn pending_invalid_call(resolving: &ResolvingState) -> Option<&ToolCall> {
     }
 }
 
+/// Schema violations of a parked invalid call; empty when its tool name is
+/// what made it invalid.
+fn resolving_argument_errors(
+    resolving: &ResolvingState,
+    tool_call: &ToolCall,
+) -> Vec<ArgumentViolation> {
+    if !resolving
+        .allowed_tool_names
+        .contains(&tool_call.function.name)
+    {
+        return Vec::new();
+    }
+    argument_violations(
+        &resolving.tool_schemas,
+        &tool_call.function.name,
+        &tool_call.function.arguments,
+    )
+}
+
 fn has_tool_calls(items: &[AssistantContent]) -> bool {
     items
         .iter()
@@ -713,6 +814,7 @@ impl AgentRun {
             tool_choice: self.tool_choice.clone(),
             chat_history: self.diagnostic_history(resolving),
             is_streaming: false,
+            argument_errors: resolving_argument_errors(resolving, tool_call),
         })
     }
 
@@ -1012,6 +1114,7 @@ impl AgentRun {
                 message_id: turn.message_id.clone(),
                 response_id: turn.response_id,
                 provider_request_id: turn.provider_request_id,
+                served_by: turn.served_by,
             },
             turn.finish_reason,
             turn.raw,
@@ -1027,6 +1130,7 @@ impl AgentRun {
             next_index: 0,
             executable_tool_names: turn.executable_tool_names,
             allowed_tool_names: turn.allowed_tool_names,
+            tool_schemas: turn.tool_schemas,
             skipped: BTreeMap::new(),
             recovered: false,
             any_skipped: false,
@@ -1130,26 +1234,38 @@ impl AgentRun {
         diagnostic: InvalidToolCallDiagnostic<'_>,
     ) -> Result<ValidatedInvalidToolCallAction, PromptError> {
         let result = match action {
-            InvalidToolCallAction::Fail => Err(diagnostic.unknown_current()),
+            InvalidToolCallAction::Fail => Err(diagnostic.unresolved()),
             InvalidToolCallAction::Retry { feedback } => {
                 if self.invalid_tool_call_retries >= self.max_invalid_tool_call_retries {
-                    Err(diagnostic.unknown_current())
+                    Err(diagnostic.unresolved())
                 } else {
                     self.invalid_tool_call_retries += 1;
                     Ok(ValidatedInvalidToolCallAction::Retry { feedback })
                 }
             }
             InvalidToolCallAction::Repair { tool_name } => {
-                if diagnostic.allowed_tool_names.contains(&tool_name) {
-                    Ok(ValidatedInvalidToolCallAction::Repair { tool_name })
-                } else {
+                if !diagnostic.allowed_tool_names.contains(&tool_name) {
                     Err(diagnostic.unknown(tool_name))
+                } else {
+                    // Renaming keeps the arguments, which must suit the
+                    // repaired tool; otherwise the call would be parked on
+                    // again and re-resolved forever.
+                    let errors = argument_violations(
+                        diagnostic.tool_schemas,
+                        &tool_name,
+                        &diagnostic.tool_call.function.arguments,
+                    );
+                    if errors.is_empty() {
+                        Ok(ValidatedInvalidToolCallAction::Repair { tool_name })
+                    } else {
+                        Err(diagnostic.invalid_arguments(tool_name, errors))
+                    }
                 }
             }
             InvalidToolCallAction::Stop { reason } => Err(diagnostic.cancelled(reason)),
             InvalidToolCallAction::Skip { reason } => {
                 if matches!(self.tool_choice, Some(ToolChoice::None)) {
-                    Err(diagnostic.unknown_current())
+                    Err(diagnostic.unresolved())
                 } else {
                     Ok(ValidatedInvalidToolCallAction::Skip { reason })
                 }
@@ -1192,12 +1308,15 @@ impl AgentRun {
         };
 
         let diagnostic_history = self.diagnostic_history(&resolving);
+        let argument_errors = resolving_argument_errors(&resolving, &tool_call);
         let action = self.validate_invalid_tool_call_action(
             action,
             InvalidToolCallDiagnostic {
                 tool_call: &tool_call,
                 executable_tool_names: &resolving.executable_tool_names,
                 allowed_tool_names: &resolving.allowed_tool_names,
+                tool_schemas: &resolving.tool_schemas,
+                argument_errors: &argument_errors,
                 history: &diagnostic_history,
             },
         )?;
@@ -1358,9 +1477,11 @@ impl AgentRun {
         while let Some(item) = resolving.items.get(resolving.next_index) {
             match item {
                 AssistantContent::ToolCall(tool_call)
-                    if !resolving
-                        .allowed_tool_names
-                        .contains(&tool_call.function.name) =>
+                    if is_invalid_call(
+                        tool_call,
+                        &resolving.allowed_tool_names,
+                        &resolving.tool_schemas,
+                    ) =>
                 {
                     break;
                 }
@@ -1471,6 +1592,7 @@ impl AgentRun {
             chat_history: self
                 .streamed_diagnostic_history(partial, Some(invalid.tool_call.clone())),
             is_streaming: true,
+            argument_errors: invalid.argument_errors.clone(),
         }
     }
 
@@ -1501,6 +1623,8 @@ impl AgentRun {
                 tool_call: &invalid.tool_call,
                 executable_tool_names: &invalid.executable_tool_names,
                 allowed_tool_names: &invalid.allowed_tool_names,
+                tool_schemas: &invalid.tool_schemas,
+                argument_errors: &invalid.argument_errors,
                 history: &diagnostic_history,
             },
         )?;
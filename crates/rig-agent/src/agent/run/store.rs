@@ -0,0 +1,603 @@
+//! Durable checkpoints for [`AgentRunner`](crate::agent::AgentRunner)
+//! executions.
+//!
+//! A runner configured with
+//! [`checkpoint`](crate::agent::AgentRunner::checkpoint) writes a
+//! [`RunCheckpoint`] into its [`RunStore`] when the run starts and again after
+//! every completed step — each model turn and each tool batch. A worker that
+//! crashes, or a run ended early by an error or a hook stop, leaves its last
+//! checkpoint behind; [`AgentRunner::resume`](crate::agent::AgentRunner::resume)
+//! rebuilds the loop from the agent (hooks, tools, memory) and continues from
+//! it. A run that completes deletes its checkpoint.
+//!
+//! ```rust,no_run
+//! use rig_agent::agent::{Agent, AgentRunner, run::store::FileRunStore};
+//!
+//! # async fn example(agent: Agent) -> Result<(), Box<dyn std::error::Error>> {
+//! let store = FileRunStore::new("./runs");
+//! let response = match agent
+//!     .runner("Plan the migration")
+//!     .max_turns(8)
+//!     .checkpoint(store.clone(), "job-42")
+//!     .run()
+//!     .await
+//! {
+//!     Ok(response) => response,
+//!     // Later, possibly in another process:
+//!     Err(_) => AgentRunner::resume(&agent, store, "job-42").run().await?,
+//! };
+//! println!("{}", response.output);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The stored [`AgentRun`] carries the conversation and provider responses
+//! accumulated so far; see the [run module docs](super) for its sensitivity
+//! and versioning caveats. Run-scoped hook state — the
+//! [`HookContext::scratchpad`](crate::agent::HookContext::scratchpad) — is not
+//! checkpointed and starts empty on resume.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use rig_core::wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync};
+use serde::{Deserialize, Serialize};
+
+use super::AgentRun;
+use crate::completion::Message;
+
+/// Boxed error source for run store backend failures.
+#[cfg(not(target_family = "wasm"))]
+pub type RunStoreBackendError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Boxed error source for run store backend failures.
+#[cfg(target_family = "wasm")]
+pub type RunStoreBackendError = Box<dyn std::error::Error + 'static>;
+
+/// Errors produced by a [`RunStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum RunStoreError {
+    /// The backing store failed to save, load, or delete a checkpoint.
+    #[error("Run store backend error: {0}")]
+    Backend(RunStoreBackendError),
+
+    /// A checkpoint could not be encoded or decoded.
+    #[error("Run checkpoint serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// No checkpoint is stored under the requested run id.
+    #[error("No checkpoint stored for run `{0}`")]
+    NotFound(String),
+
+    /// The run id cannot be used as a key by this store.
+    #[error("Invalid run id `{0}`")]
+    InvalidRunId(String),
+}
+
+impl RunStoreError {
+    /// Wrap an arbitrary error from a backend implementation.
+    pub fn backend<E>(source: E) -> Self
+    where
+        E: Into<RunStoreBackendError>,
+    {
+        Self::Backend(source.into())
+    }
+}
+
+/// Everything needed to continue an agent run: the sans-IO [`AgentRun`] plus
+/// the runner state that is not part of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    /// The id the run is checkpointed under.
+    pub run_id: String,
+    /// The prompt the run was started with.
+    pub prompt: Message,
+    /// The conversation id the run was started with, if any.
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    /// Whether the run appends its messages to conversation memory when it
+    /// completes. False when memory was bypassed by explicit history.
+    #[serde(default)]
+    pub appends_to_memory: bool,
+    /// The run state after the last completed step.
+    pub run: AgentRun,
+}
+
+/// A durable backend for [`RunCheckpoint`]s, keyed by run id.
+///
+/// `save` replaces any checkpoint already stored under the same id. It runs
+/// inline after every step of the run, so keep it cheap.
+pub trait RunStore: WasmCompatSend + WasmCompatSync {
+    /// Store `checkpoint` under its run id, replacing any previous one.
+    fn save<'a>(
+        &'a self,
+        checkpoint: &'a RunCheckpoint,
+    ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>>;
+
+    /// Load the checkpoint stored under `run_id`, or `None` if there is none.
+    fn load<'a>(
+        &'a self,
+        run_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<RunCheckpoint>, RunStoreError>>;
+
+    /// Remove the checkpoint stored under `run_id`. Removing a missing
+    /// checkpoint is not an error.
+    fn delete<'a>(&'a self, run_id: &'a str) -> WasmBoxedFuture<'a, Result<(), RunStoreError>>;
+}
+
+macro_rules! forward_run_store {
+    ($($ptr:ident)+) => {$(
+        impl<S> RunStore for $ptr<S>
+        where
+            S: RunStore + ?Sized,
+        {
+            fn save<'a>(
+                &'a self,
+                checkpoint: &'a RunCheckpoint,
+            ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+                (**self).save(checkpoint)
+            }
+
+            fn load<'a>(
+                &'a self,
+                run_id: &'a str,
+            ) -> WasmBoxedFuture<'a, Result<Option<RunCheckpoint>, RunStoreError>> {
+                (**self).load(run_id)
+            }
+
+            fn delete<'a>(
+                &'a self,
+                run_id: &'a str,
+            ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+                (**self).delete(run_id)
+            }
+        }
+    )+};
+}
+
+forward_run_store!(Arc Box);
+
+/// A process-local [`RunStore`]. Checkpoints do not survive a restart; use it
+/// in tests or to suspend runs within one process.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRunStore {
+    inner: Arc<Mutex<HashMap<String, RunCheckpoint>>>,
+}
+
+impl InMemoryRunStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ids of the runs currently checkpointed, in no particular order.
+    pub fn run_ids(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, RunCheckpoint>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl RunStore for InMemoryRunStore {
+    fn save<'a>(
+        &'a self,
+        checkpoint: &'a RunCheckpoint,
+    ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            self.lock()
+                .insert(checkpoint.run_id.clone(), checkpoint.clone());
+            Ok(())
+        })
+    }
+
+    fn load<'a>(
+        &'a self,
+        run_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<RunCheckpoint>, RunStoreError>> {
+        Box::pin(async move { Ok(self.lock().get(run_id).cloned()) })
+    }
+
+    fn delete<'a>(&'a self, run_id: &'a str) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            self.lock().remove(run_id);
+            Ok(())
+        })
+    }
+}
+
+/// A [`RunStore`] that keeps one JSON file per run in a directory.
+///
+/// Files are named `<run_id>.json` and replaced atomically (write to a
+/// temporary file, then rename), so a crash mid-save leaves the previous
+/// checkpoint intact. Run ids are restricted to ASCII letters, digits, `-`,
+/// `_` and `.`, and may not start with `.`. File IO is blocking and runs
+/// inline on the calling task.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug, Clone)]
+pub struct FileRunStore {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl FileRunStore {
+    /// Store checkpoints in `dir`, created on the first save.
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory checkpoints are stored in.
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    fn path(&self, run_id: &str) -> Result<std::path::PathBuf, RunStoreError> {
+        let valid = !run_id.is_empty()
+            && !run_id.starts_with('.')
+            && run_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(RunStoreError::InvalidRunId(run_id.to_string()));
+        }
+        Ok(self.dir.join(format!("{run_id}.json")))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl RunStore for FileRunStore {
+    fn save<'a>(
+        &'a self,
+        checkpoint: &'a RunCheckpoint,
+    ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            let path = self.path(&checkpoint.run_id)?;
+            let contents = serde_json::to_vec(checkpoint)?;
+            let temp = path.with_extension("json.tmp");
+            std::fs::create_dir_all(&self.dir).map_err(RunStoreError::backend)?;
+            std::fs::write(&temp, contents).map_err(RunStoreError::backend)?;
+            std::fs::rename(&temp, &path).map_err(RunStoreError::backend)
+        })
+    }
+
+    fn load<'a>(
+        &'a self,
+        run_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<RunCheckpoint>, RunStoreError>> {
+        Box::pin(async move {
+            let path = self.path(run_id)?;
+            match std::fs::read(&path) {
+                Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(RunStoreError::backend(error)),
+            }
+        })
+    }
+
+    fn delete<'a>(&'a self, run_id: &'a str) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            let path = self.path(run_id)?;
+            match std::fs::remove_file(&path) {
+                Ok(()) => Ok(()),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(error) => Err(RunStoreError::backend(error)),
+            }
+        })
+    }
+}
+
+/// The store and id a runner checkpoints into.
+#[derive(Clone)]
+pub(crate) struct RunCheckpointing {
+    pub(crate) store: Arc<dyn RunStore>,
+    pub(crate) run_id: String,
+    /// Continue the stored run instead of starting a fresh one.
+    pub(crate) resume: bool,
+}
+
+impl RunCheckpointing {
+    /// Persist the run after a completed step. A failed save is logged rather
+    /// than failing the run, matching conversation-memory appends.
+    pub(crate) async fn save(
+        &self,
+        prompt: &Message,
+        conversation_id: Option<&str>,
+        appends_to_memory: bool,
+        run: &AgentRun,
+    ) {
+        let checkpoint = RunCheckpoint {
+            run_id: self.run_id.clone(),
+            prompt: prompt.clone(),
+            conversation_id: conversation_id.map(str::to_owned),
+            appends_to_memory,
+            run: run.clone(),
+        };
+        if let Err(err) = self.store.save(&checkpoint).await {
+            tracing::warn!(
+                error = %err,
+                run_id = %self.run_id,
+                "run checkpoint save failed; continuing without it"
+            );
+        }
+    }
+
+    /// Drop the checkpoint of a run that completed.
+    pub(crate) async fn delete(&self) {
+        if let Err(err) = self.store.delete(&self.run_id).await {
+            tracing::warn!(
+                error = %err,
+                run_id = %self.run_id,
+                "run checkpoint delete failed after the run completed"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use futures::StreamExt;
+    use rig_core::memory::{ConversationMemory, InMemoryConversationMemory};
+
+    use super::{FileRunStore, InMemoryRunStore, RunCheckpoint, RunStore, RunStoreError};
+    use crate::{
+        agent::{AgentBuilder, AgentHook, AgentRun, AgentRunner, BudgetGuard, HookContext},
+        completion::{Message, PromptError, Usage},
+        test_utils::{MockAddTool, MockCompletionModel, MockStreamEvent, MockTurn},
+    };
+
+    fn usage(input: u64, output: u64) -> Usage {
+        Usage {
+            input_tokens: input,
+            output_tokens: output,
+            ..Usage::new()
+        }
+    }
+
+    /// A tool turn, then a text turn. Each turn is served once.
+    fn tool_then_text_model() -> MockCompletionModel {
+        MockCompletionModel::from_turns([
+            MockTurn::tool_call("tc1", "add", serde_json::json!({"x": 2, "y": 3}))
+                .with_usage(usage(7, 11)),
+            MockTurn::text("the answer is 5").with_usage(usage(13, 17)),
+        ])
+    }
+
+    #[derive(Clone, Default)]
+    struct CountToolCalls(Arc<AtomicUsize>);
+
+    impl AgentHook for CountToolCalls {
+        async fn on_tool_call(
+            &self,
+            _ctx: &HookContext,
+            _event: crate::agent::ToolCall<'_>,
+        ) -> crate::agent::ToolCallAction {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            crate::agent::ToolCallAction::Run
+        }
+    }
+
+    #[tokio::test]
+    async fn stopped_runs_resume_from_their_last_checkpoint() {
+        let store = InMemoryRunStore::new();
+        let tool_calls = CountToolCalls::default();
+        let agent = AgentBuilder::new(tool_then_text_model())
+            .tool(MockAddTool)
+            .add_hook(tool_calls.clone())
+            .build();
+
+        // The budget stops the run before its second model call, after the
+        // tool batch has been checkpointed.
+        let error = agent
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .add_hook(BudgetGuard::tokens(10).per_run())
+            .checkpoint(store.clone(), "job-1")
+            .run()
+            .await
+            .expect_err("the budget stops the run");
+        assert!(matches!(error, PromptError::BudgetExceeded { .. }));
+
+        let checkpoint = store
+            .load("job-1")
+            .await
+            .unwrap()
+            .expect("the stopped run left a checkpoint");
+        assert_eq!(checkpoint.prompt, Message::user("add 2 and 3"));
+        // The prompt, the tool call and its result.
+        assert_eq!(checkpoint.run.messages().len(), 3);
+
+        let response = AgentRunner::resume(&agent, store.clone(), "job-1")
+            .run()
+            .await
+            .expect("the resumed run completes");
+        assert_eq!(response.output, "the answer is 5");
+        assert_eq!(response.usage.input_tokens, 20);
+        assert_eq!(tool_calls.0.load(Ordering::SeqCst), 1);
+        assert!(
+            store.run_ids().is_empty(),
+            "completed runs drop their checkpoint"
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_runs_execute_checkpointed_tool_calls() {
+        let store = InMemoryRunStore::new();
+
+        // Simulate a worker that died after the model asked for a tool: the
+        // checkpoint holds the turn, but the tool never ran.
+        let mut run = AgentRun::new("add 2 and 3").max_turns(3);
+        let crate::agent::AgentRunStep::CallModel { .. } = run.next_step().unwrap() else {
+            panic!("a fresh run calls the model");
+        };
+        run.model_response(crate::agent::ModelTurn::new(
+            None,
+            vec![rig_core::message::AssistantContent::tool_call(
+                "tc1",
+                "add",
+                serde_json::json!({"x": 2, "y": 3}),
+            )],
+            usage(7, 11),
+            ["add".to_string()].into(),
+            ["add".to_string()].into(),
+        ))
+        .unwrap();
+        store
+            .save(&RunCheckpoint {
+                run_id: "job-2".into(),
+                prompt: Message::user("add 2 and 3"),
+                conversation_id: None,
+                appends_to_memory: false,
+                run,
+            })
+            .await
+            .unwrap();
+
+        // The scripted tool turn is never requested: only the text turn is.
+        let model = MockCompletionModel::from_stream_turns([vec![
+            MockStreamEvent::text("the answer is 5"),
+            MockStreamEvent::final_response(usage(13, 17)),
+        ]]);
+        let agent = AgentBuilder::new(model).tool(MockAddTool).build();
+        let mut stream = AgentRunner::resume(&agent, store.clone(), "job-2")
+            .stream()
+            .await;
+        let mut tool_results = 0;
+        let mut output = None;
+        while let Some(item) = stream.next().await {
+            match item.expect("the resumed stream succeeds") {
+                crate::agent::MultiTurnStreamItem::StreamUserItem(_) => tool_results += 1,
+                crate::agent::MultiTurnStreamItem::FinalResponse(response) => {
+                    output = Some(response.output);
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(tool_results, 1);
+        assert_eq!(output.as_deref(), Some("the answer is 5"));
+        assert!(store.run_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resumed_runs_append_to_conversation_memory() {
+        let store = InMemoryRunStore::new();
+        let memory = InMemoryConversationMemory::new();
+        let agent = AgentBuilder::new(tool_then_text_model())
+            .tool(MockAddTool)
+            .memory(memory.clone())
+            .build();
+
+        agent
+            .runner("add 2 and 3")
+            .conversation("thread-1")
+            .max_turns(3)
+            .add_hook(BudgetGuard::tokens(10).per_run())
+            .checkpoint(store.clone(), "job-3")
+            .run()
+            .await
+            .expect_err("the budget stops the run");
+        assert!(memory.load("thread-1").await.unwrap().is_empty());
+
+        AgentRunner::resume(&agent, store, "job-3")
+            .run()
+            .await
+            .expect("the resumed run completes");
+        // The prompt, the tool call, its result and the answer.
+        assert_eq!(memory.load("thread-1").await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn resuming_an_unknown_run_fails() {
+        let agent = AgentBuilder::new(MockCompletionModel::from_turns([])).build();
+        let error = AgentRunner::resume(&agent, InMemoryRunStore::new(), "missing")
+            .run()
+            .await
+            .expect_err("nothing to resume");
+        assert!(
+            matches!(error, PromptError::RunStoreError(RunStoreError::NotFound(ref id)) if id == "missing"),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_store_round_trips_checkpoints() {
+        let dir = std::env::temp_dir().join(format!("rig-run-store-{}", std::process::id()));
+        let store = FileRunStore::new(&dir);
+        let checkpoint = RunCheckpoint {
+            run_id: "job-4".into(),
+            prompt: Message::user("hi"),
+            conversation_id: Some("thread-1".into()),
+            appends_to_memory: true,
+            run: AgentRun::new("hi"),
+        };
+
+        store.save(&checkpoint).await.unwrap();
+        let loaded = store.load("job-4").await.unwrap().unwrap();
+        assert_eq!(loaded.conversation_id.as_deref(), Some("thread-1"));
+        assert!(loaded.appends_to_memory);
+
+        store.delete("job-4").await.unwrap();
+        assert!(store.load("job-4").await.unwrap().is_none());
+        store.delete("job-4").await.unwrap();
+
+        assert!(matches!(
+            store.load("../escape").await,
+            Err(RunStoreError::InvalidRunId(_))
+        ));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn streamed_runs_checkpoint_too() {
+        let store = InMemoryRunStore::new();
+        let model = MockCompletionModel::from_stream_turns([
+            vec![
+                MockStreamEvent::tool_call("tc1", "add", serde_json::json!({"x": 2, "y": 3})),
+                MockStreamEvent::final_response(usage(7, 11)),
+            ],
+            vec![
+                MockStreamEvent::text("the answer is 5"),
+                MockStreamEvent::final_response(usage(13, 17)),
+            ],
+        ]);
+        let agent = AgentBuilder::new(model).tool(MockAddTool).build();
+
+        let mut stream = agent
+            .runner("add 2 and 3")
+            .max_turns(3)
+            .add_hook(BudgetGuard::tokens(10).per_run())
+            .checkpoint(store.clone(), "job-5")
+            .stream()
+            .await;
+        while let Some(item) = stream.next().await {
+            if item.is_err() {
+                break;
+            }
+        }
+        assert_eq!(store.run_ids(), vec!["job-5".to_string()]);
+
+        let mut stream = AgentRunner::resume(&agent, store.clone(), "job-5")
+            .stream()
+            .await;
+        let mut finished = false;
+        while let Some(item) = stream.next().await {
+            if let crate::agent::MultiTurnStreamItem::FinalResponse(response) =
+                item.expect("the resumed stream succeeds")
+            {
+                assert_eq!(response.output, "the answer is 5");
+                finished = true;
+            }
+        }
+        assert!(finished);
+        assert!(store.run_ids().is_empty());
+    }
+}
@@ -33,7 +33,7 @@
 //! internally; hand-driven runs can use it to stream any
 //! [`AgentRun`](super::AgentRun).
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 
@@ -47,6 +47,7 @@ use crate::{
     completion::{CompletionError, Message, Usage},
     json_utils,
     streaming::{StreamedAssistantContent, ToolCallDeltaContent},
+    tool::ArgumentViolation,
 };
 
 /// Assemble assistant content in canonical replay order: reasoning blocks,
@@ -148,6 +149,14 @@ pub struct StreamedInvalidToolCall {
     pub executable_tool_names: BTreeSet<String>,
     /// Tools allowed by the active tool choice for this turn.
     pub allowed_tool_names: BTreeSet<String>,
+    /// Where the arguments violate the tool's parameter schema; empty when
+    /// the call was rejected for its tool name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub argument_errors: Vec<ArgumentViolation>,
+    /// Parameter schemas validated this turn, so a repaired call can be
+    /// checked against its new tool's schema.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tool_schemas: BTreeMap<String, serde_json::Value>,
 }
 
 /// Snapshot of a streamed turn at the moment an invalid tool call appeared.
@@ -382,7 +391,8 @@ fn reasoning_from_part(
 }
 
 enum PendingInvalid {
-    /// A complete tool call with a disallowed name.
+    /// A complete tool call with a disallowed name or schema-violating
+    /// arguments.
     FullCall {
         tool_call: Box<ToolCall>,
         internal_call_id: String,
@@ -396,6 +406,7 @@ enum PendingInvalid {
 pub struct StreamedTurnAssembler {
     executable_tool_names: BTreeSet<String>,
     allowed_tool_names: BTreeSet<String>,
+    tool_schemas: BTreeMap<String, serde_json::Value>,
     text: String,
     saw_text: bool,
     reasoning_parts: Vec<ReasoningPart>,
@@ -445,6 +456,7 @@ impl StreamedTurnAssembler {
         Self {
             executable_tool_names,
             allowed_tool_names,
+            tool_schemas: BTreeMap::new(),
             text: String::new(),
             saw_text: false,
             reasoning_parts: Vec::new(),
@@ -456,6 +468,14 @@ impl StreamedTurnAssembler {
         }
     }
 
+    /// Validate complete tool calls' arguments against these parameter
+    /// schemas, keyed by tool name, surfacing violating calls as
+    /// [`StreamedTurnEvent::InvalidToolCall`].
+    pub fn with_tool_schemas(mut self, tool_schemas: BTreeMap<String, serde_json::Value>) -> Self {
+        self.tool_schemas = tool_schemas;
+        self
+    }
+
     /// Replayed assistant blocks excluded from assembly so far this turn.
     /// Zero on well-formed provider streams; non-zero means transcript
     /// content was lost (one warning summarizes the count at
@@ -668,13 +688,26 @@ impl StreamedTurnAssembler {
                 tool_call,
                 internal_call_id,
             } => {
-                if !self.allowed_tool_names.contains(&tool_call.function.name) {
+                let argument_errors = if self.allowed_tool_names.contains(&tool_call.function.name)
+                {
+                    super::argument_violations(
+                        &self.tool_schemas,
+                        &tool_call.function.name,
+                        &tool_call.function.arguments,
+                    )
+                } else {
+                    Vec::new()
+                };
+                if !self.allowed_tool_names.contains(&tool_call.function.name)
+                    || !argument_errors.is_empty()
+                {
                     return Ok(self.surface_invalid_call(
                         tool_call.clone(),
                         internal_call_id.clone(),
                         Some(json_utils::serialize_json_value(
                             &tool_call.function.arguments,
                         )),
+                        argument_errors,
                         PendingInvalid::FullCall {
                             tool_call: Box::new(tool_call.clone()),
                             internal_call_id: internal_call_id.clone(),
@@ -705,6 +738,7 @@ impl StreamedTurnAssembler {
                                 tool_call,
                                 internal_call_id.clone(),
                                 Some(buffered_args),
+                                Vec::new(),
                                 PendingInvalid::NameDelta {
                                     internal_call_id: internal_call_id.clone(),
                                 },
@@ -879,6 +913,7 @@ impl StreamedTurnAssembler {
         tool_call: ToolCall,
         internal_call_id: String,
         args: Option<String>,
+        argument_errors: Vec<ArgumentViolation>,
         pending: PendingInvalid,
     ) -> Vec<StreamedTurnEvent> {
         let invalid = StreamedInvalidToolCall {
@@ -887,6 +922,8 @@ impl StreamedTurnAssembler {
             args,
             executable_tool_names: self.executable_tool_names.clone(),
             allowed_tool_names: self.allowed_tool_names.clone(),
+            argument_errors,
+            tool_schemas: self.tool_schemas.clone(),
         };
         self.pending_invalid = Some(pending);
         vec![StreamedTurnEvent::InvalidToolCall(Box::new(invalid))]
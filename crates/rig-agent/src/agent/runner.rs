@@ -24,15 +24,19 @@
 //! # }
 //! ```
 
-use std::sync::{
-    Arc, Mutex,
-    atomic::{AtomicU64, Ordering},
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 use futures::StreamExt;
 use tracing::{Instrument, info_span, span::Id};
 
 use super::{
+    approval::PendingApprovals,
     completion::{Agent, AgentConfig, PreparedCompletionRequest},
     hook::{
         AgentHook, CompletionCall, CompletionCallAction,
@@ -49,7 +53,10 @@ use super::{
         },
         tool_result_output,
     },
-    run::{AgentRun, DEFAULT_OUTPUT_RETRIES, ModelTurn, ModelTurnOutcome, PendingToolCall},
+    run::{
+        AgentRun, DEFAULT_OUTPUT_RETRIES, ModelTurn, ModelTurnOutcome, PendingToolCall,
+        store::{RunCheckpointing, RunStore, RunStoreError},
+    },
 };
 use rig_core::{
     memory::ConversationMemory,
@@ -90,14 +97,16 @@ macro_rules! build_chat_span {
             $runner.config.record_telemetry_content,
         );
         // The core macro is the single source of the completion-parent
-        // contract (marker + required fields); only the agent-specific field
-        // is declared here.
+        // contract (marker + required fields); only the agent-specific fields
+        // are declared here. `rig.model.served_by` is recorded by models that
+        // route between providers, such as `FallbackModel`.
         $crate::core::telemetry::completion_parent_span!(
             target: "rig::agent_chat",
             name: $name,
             operation: $operation,
             system_instructions: system_instructions.as_deref(),
             gen_ai.agent.name = $runner.agent_name_or_default(),
+            rig.model.served_by = tracing::field::Empty,
         )
     }};
 }
@@ -167,6 +176,17 @@ pub struct AgentRunner {
     pub(crate) unhandled_invalid_tool_call_policy: UnhandledInvalidToolCallPolicy,
     pub(crate) concurrency: usize,
     pub(crate) error_usage: Option<Arc<Mutex<Usage>>>,
+    /// Where the run is checkpointed after every step, if anywhere.
+    pub(crate) checkpointing: Option<RunCheckpointing>,
+    /// Decided approvals a [`resume_approvals`](Self::resume_approvals)d
+    /// runner continues; taken by the first tool batch.
+    pub(crate) approvals: Option<Box<PendingApprovals>>,
+    /// Tool-call actions recorded by approval decisions, by internal call id.
+    /// They stand in for the `ToolCall` hook chain of their calls.
+    pub(crate) approved_actions: HashMap<String, ToolCallAction>,
+    /// Whether the completed run appends to conversation memory; set by the
+    /// driver and recorded in checkpoints and pending approvals.
+    pub(crate) appends_to_memory: bool,
 }
 
 /// The `(history_override, memory_handle)` pair resolved for one run by
@@ -176,6 +196,10 @@ pub(crate) type HistoryAndMemory = (
     Option<(Arc<dyn ConversationMemory>, String)>,
 );
 
+/// The `(run, memory_handle)` pair restored by
+/// [`AgentRunner::load_checkpoint`].
+pub(crate) type ResumedRun = (AgentRun, Option<(Arc<dyn ConversationMemory>, String)>);
+
 impl AgentRunner {
     /// Build a runner from an agent, seeding it with the agent's default hook
     /// stack. Prefer [`Agent::runner`].
@@ -193,9 +217,50 @@ impl AgentRunner {
             unhandled_invalid_tool_call_policy: UnhandledInvalidToolCallPolicy::Fail,
             concurrency: 1,
             error_usage: None,
+            checkpointing: None,
+            approvals: None,
+            approved_actions: HashMap::new(),
+            appends_to_memory: false,
         }
     }
 
+    /// Build a runner that continues the run checkpointed in `store` under
+    /// `run_id`, instead of starting a new one.
+    ///
+    /// The loop is rebuilt from `agent` — its model, hooks, tools and memory —
+    /// and the stored [`AgentRun`] supplies the prompt, history and progress.
+    /// The resumed run keeps checkpointing under the same id. Per-run
+    /// overrides made on the original runner (temperature, preamble, ...) are
+    /// not stored; set them again on the returned runner if needed. A
+    /// checkpoint that is missing fails the run with
+    /// [`PromptError::RunStoreError`].
+    ///
+    /// See [`run::store`](crate::agent::run::store) for what is checkpointed.
+    pub fn resume<S>(agent: &Agent, store: S, run_id: impl Into<String>) -> Self
+    where
+        S: RunStore + 'static,
+    {
+        let mut runner = Self::from_agent(agent, Message::user(""));
+        runner.checkpointing = Some(RunCheckpointing {
+            store: Arc::new(store),
+            run_id: run_id.into(),
+            resume: true,
+        });
+        runner
+    }
+
+    /// Build a runner that continues a run paused on deferred tool calls,
+    /// applying the decisions recorded on `approvals`.
+    ///
+    /// As with [`resume`](Self::resume), the loop is rebuilt from `agent` and
+    /// per-run overrides are not carried over. See
+    /// [`approval`](crate::agent::approval) for how decisions are applied.
+    pub fn resume_approvals(agent: &Agent, approvals: PendingApprovals) -> Self {
+        let mut runner = Self::from_agent(agent, approvals.prompt().clone());
+        runner.approvals = Some(Box::new(approvals));
+        runner
+    }
+
     /// Append a hook to the stack (on top of any the agent already carries).
     /// Hooks run in registration order; how their results compose is
     /// event-dependent (model selections and `ToolCall`/`ToolResult` rewrites
@@ -435,6 +500,105 @@ impl AgentRunner {
         self
     }
 
+    /// Checkpoint this run into `store` under `run_id` when it starts and
+    /// after every completed step, so it can be continued with
+    /// [`resume`](Self::resume) if it does not complete. The checkpoint is
+    /// deleted once the run completes.
+    pub fn checkpoint<S>(mut self, store: S, run_id: impl Into<String>) -> Self
+    where
+        S: RunStore + 'static,
+    {
+        self.checkpointing = Some(RunCheckpointing {
+            store: Arc::new(store),
+            run_id: run_id.into(),
+            resume: false,
+        });
+        self
+    }
+
+    /// Load the run a [`resume`](Self::resume)d or
+    /// [`resume_approvals`](Self::resume_approvals)d runner continues,
+    /// adopting its prompt and conversation id. `None` for a fresh run.
+    pub(crate) async fn load_checkpoint(&mut self) -> Result<Option<ResumedRun>, PromptError> {
+        let (run, conversation_id, appends_to_memory) = match &self.approvals {
+            Some(approvals) => (
+                approvals.run().clone(),
+                approvals.conversation_id().map(str::to_owned),
+                approvals.appends_to_memory(),
+            ),
+            None => {
+                let Some(checkpointing) = self.checkpointing.as_ref().filter(|c| c.resume) else {
+                    return Ok(None);
+                };
+                let checkpoint = checkpointing
+                    .store
+                    .load(&checkpointing.run_id)
+                    .await?
+                    .ok_or_else(|| RunStoreError::NotFound(checkpointing.run_id.clone()))?;
+                self.prompt = checkpoint.prompt;
+                (
+                    checkpoint.run,
+                    checkpoint.conversation_id,
+                    checkpoint.appends_to_memory,
+                )
+            }
+        };
+
+        if conversation_id.is_some() {
+            self.config.conversation_id = conversation_id;
+        }
+        // History already lives in the run; memory is only re-attached so the
+        // completed run appends to it.
+        let memory_handle = match (&self.config.memory, &self.config.conversation_id) {
+            (Some(memory), Some(id)) if appends_to_memory => Some((memory.clone(), id.clone())),
+            _ => None,
+        };
+        Ok(Some((run, memory_handle)))
+    }
+
+    /// Whether this runner continues a checkpointed or paused run.
+    pub(crate) fn is_resumed(&self) -> bool {
+        self.approvals.is_some() || self.checkpointing.as_ref().is_some_and(|c| c.resume)
+    }
+
+    pub(crate) async fn save_checkpoint(&self, run: &AgentRun) {
+        if let Some(checkpointing) = &self.checkpointing {
+            checkpointing
+                .save(
+                    &self.prompt,
+                    self.config.conversation_id.as_deref(),
+                    self.appends_to_memory,
+                    run,
+                )
+                .await;
+        }
+    }
+
+    pub(crate) async fn delete_checkpoint(&self) {
+        if let Some(checkpointing) = &self.checkpointing {
+            checkpointing.delete().await;
+        }
+    }
+
+    /// Snapshot the tool registry for tool calls a resumed run inherited from
+    /// its checkpoint, resolving dynamic tools against the latest message
+    /// with retrievable text — the query the original turn would have used.
+    pub(crate) async fn resumed_tool_snapshot(
+        &self,
+        run: &AgentRun,
+    ) -> Result<Arc<ToolRegistrySnapshot>, CompletionError> {
+        let retrieval_query = run
+            .full_history()
+            .iter()
+            .rev()
+            .find_map(|message| message.rag_text());
+        self.tool_server_handle
+            .snapshot_tool_defs(retrieval_query)
+            .await
+            .map(Arc::new)
+            .map_err(|_| CompletionError::RequestError("Failed to get tool definitions".into()))
+    }
+
     pub(crate) fn agent_name_or_default(&self) -> &str {
         self.config.name.as_deref().unwrap_or(UNKNOWN_AGENT_NAME)
     }
@@ -514,6 +678,7 @@ pub(crate) fn acquire_agent_span(
             gen_ai.usage.cache_creation.input_tokens = tracing::field::Empty,
             gen_ai.usage.tool_use_prompt_tokens = tracing::field::Empty,
             gen_ai.usage.reasoning_tokens = tracing::field::Empty,
+            gen_ai.usage.cost = tracing::field::Empty,
         );
         (span, true)
     } else {
@@ -522,6 +687,8 @@ pub(crate) fn acquire_agent_span(
 }
 
 /// Outcome of firing the `CompletionCall` hook for a turn.
+// Produced once per model turn, like `CompletionCallAction`.
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum CompletionCallOutcome {
     /// Proceed, optionally applying a per-turn request patch (the merged patch
     /// from every hook that contributed one).
@@ -589,6 +756,17 @@ pub(crate) enum ToolExecution {
     Skipped,
 }
 
+/// How [`run_single_tool`] resolved a call: settled with a result, or held
+/// by a [`ToolCallAction::Defer`] hook for approval.
+pub(crate) enum ToolCallResolution {
+    Settled(ToolCallOutcome),
+    /// Nothing ran; `args` are the arguments the call would run with.
+    Deferred {
+        reason: String,
+        args: serde_json::Value,
+    },
+}
+
 /// Outcome of [`run_single_tool`]: the tool-result content plus whether the
 /// tool's body ran (and the effective call) or a hook skipped it.
 pub(crate) struct ToolCallOutcome {
@@ -607,7 +785,8 @@ pub(crate) struct ToolCallOutcome {
 /// message content through [`tool_result_output`] without reparsing text.
 /// Records `gen_ai.tool.*` on the current span;
 /// `error_history` builds a cancellation error if a hook terminates the run.
-/// Returns whether the tool body executed via [`ToolCallOutcome::execution`].
+/// Returns whether the tool body executed via [`ToolCallOutcome::execution`],
+/// or [`ToolCallResolution::Deferred`] when a hook deferred the call.
 pub(crate) async fn run_single_tool(
     runner: &AgentRunner,
     ctx: &HookContext,
@@ -615,7 +794,7 @@ pub(crate) async fn run_single_tool(
     tool_call: &ToolCall,
     internal_call_id: &str,
     error_history: &[Message],
-) -> Result<ToolCallOutcome, PromptError> {
+) -> Result<ToolCallResolution, PromptError> {
     let hooks = &runner.config.hooks;
     let tool_context = &runner.tool_context;
     let record_content = runner.config.record_telemetry_content;
@@ -637,17 +816,23 @@ pub(crate) async fn run_single_tool(
     // rewrite into `salvaged_rewrite` so it is *not* lost — the rewritten args
     // must still be reported on the skipped `ToolResult` and in tracing rather
     // than leaking the model's original args (see [`HookStack::resolve_tool_call`]).
-    let (action, salvaged_rewrite) = hooks
-        .resolve_tool_call(
-            ctx,
-            ToolCallEvent {
-                tool_name,
-                tool_call_id: Some(tool_call.id.as_str()),
-                internal_call_id,
-                args: &args,
-            },
-        )
-        .await;
+    // An approval decision replaces the chain that deferred the call.
+    let (action, salvaged_rewrite) = match runner.approved_actions.get(internal_call_id) {
+        Some(action) => (action.clone(), None),
+        None => {
+            hooks
+                .resolve_tool_call(
+                    ctx,
+                    ToolCallEvent {
+                        tool_name,
+                        tool_call_id: Some(tool_call.id.as_str()),
+                        internal_call_id,
+                        args: &args,
+                    },
+                )
+                .await
+        }
+    };
 
     // Apply a salvaged rewrite (short-circuit path only) so `args` — what the
     // `ToolResult` reports — and the span reflect the effective arguments.
@@ -675,6 +860,13 @@ pub(crate) async fn run_single_tool(
                 reason,
             ));
         }
+        ToolCallAction::Defer(reason) => {
+            tracing::info!(tool_name = tool_name, reason = reason, "Tool call deferred");
+            return Ok(ToolCallResolution::Deferred {
+                reason,
+                args: salvaged_rewrite.unwrap_or_else(|| tool_call.function.arguments.clone()),
+            });
+        }
         ToolCallAction::Skip(reason) => {
             tracing::info!(tool_name = tool_name, reason = reason, "Tool call rejected");
             // Synthetic rejection: `Skipped` outcome, message delivered verbatim.
@@ -712,7 +904,9 @@ pub(crate) async fn run_single_tool(
             let ToolDispatch {
                 result: exec,
                 context: dispatch_context,
+                attempts,
             } = tool_snapshot.dispatch(tool_name, &args, tool_context).await;
+            tool_span.record("gen_ai.tool.call.attempts", attempts);
             (
                 exec,
                 ToolExecution::Executed(Box::new(effective_tool_call)),
@@ -750,7 +944,7 @@ pub(crate) async fn run_single_tool(
             if record_content {
                 tool_span.record("gen_ai.tool.call.result", replacement.render());
             }
-            Ok(ToolCallOutcome {
+            Ok(ToolCallResolution::Settled(ToolCallOutcome {
                 content: tool_result_output(
                     tool_call.id.clone(),
                     tool_call.provider.clone(),
@@ -758,7 +952,7 @@ pub(crate) async fn run_single_tool(
                     replacement,
                 ),
                 execution,
-            })
+            }))
         }
         ToolResultAction::Keep => {
             if record_content {
@@ -770,7 +964,10 @@ pub(crate) async fn run_single_tool(
                 tool_call.function.name.clone(),
                 exec.output().clone(),
             );
-            Ok(ToolCallOutcome { content, execution })
+            Ok(ToolCallResolution::Settled(ToolCallOutcome {
+                content,
+                execution,
+            }))
         }
     }
 }
@@ -797,6 +994,7 @@ pub(crate) fn new_execute_tool_span() -> tracing::Span {
         gen_ai.tool.call.arguments = tracing::field::Empty,
         gen_ai.tool.call.result = tracing::field::Empty,
         gen_ai.tool.call.outcome = tracing::field::Empty,
+        gen_ai.tool.call.attempts = tracing::field::Empty,
         gen_ai.tool.error.type = tracing::field::Empty
     )
 }
@@ -902,10 +1100,12 @@ impl TurnSource for UnaryTurnSource {
                     prepared.executable_tool_names,
                     prepared.allowed_tool_names,
                 )
+                .with_tool_schemas(prepared.tool_schemas)
                 .with_identity(
                     resp.response_id.clone(),
                     resp.provider_request_id.clone(),
                 )
+                .with_served_by(resp.served_by.clone())
                 .with_finish_reason(attempt_finish_reason.clone())
                 // This attempt's captured raw payload (an `Arc` clone), so the
                 // run record carries the same payload the hooks observe below.
@@ -1123,10 +1323,16 @@ impl AgentRunner {
     /// Drive the agent loop to completion, returning the aggregated
     /// [`PromptResponse`]. Hooks fire at every observable point; the first hook
     /// to terminate cancels the run.
-    pub async fn run(self) -> Result<PromptResponse, PromptError> {
+    pub async fn run(mut self) -> Result<PromptResponse, PromptError> {
+        let resumed = self.load_checkpoint().await;
         let (agent_span, created_agent_span) = self.open_agent_span();
-        let (history_override, memory_handle) = self.resolve_history_and_memory().await?;
-        let run = self.build_run(history_override);
+        let (run, memory_handle) = match resumed? {
+            Some(resumed) => resumed,
+            None => {
+                let (history_override, memory_handle) = self.resolve_history_and_memory().await?;
+                (self.build_run(history_override), memory_handle)
+            }
+        };
 
         // Fold the shared engine to its final response. The blocking surface
         // uses a unary model transport and ignores the intermediate items the
@@ -4212,6 +4418,7 @@ mod migrated_tests {
         use crate::streaming::StreamingCompletionResponse;
         use crate::test_utils::{MockAddTool, MockCompletionModel, MockStreamEvent, MockTurn};
         use crate::tool::{ToolContext, ToolExecutionError};
+        use rig_core::pricing::ModelPricing;
         use rig_core::telemetry::{CompletionOperation, CompletionSpanBuilder};
 
         use super::{BoundedResponseRetry, StopCompletedModelTurn, TestRetryMode};
@@ -4520,7 +4727,7 @@ mod migrated_tests {
         /// Cross-crate tripwire: the chat span built by `build_chat_span!`
         /// must statically declare rig-core's full completion-parent contract
         /// (marker + every required field) plus the agent-specific
-        /// `gen_ai.agent.name`. `Span::record` silently no-ops on undeclared
+        /// `gen_ai.agent.name` and `rig.model.served_by`. `Span::record` silently no-ops on undeclared
         /// fields, so a missing field here would lose that telemetry on every
         /// adopted completion with no error.
         #[test]
@@ -4544,7 +4751,11 @@ mod migrated_tests {
                 let expected: HashSet<&str> = COMPLETION_PARENT_REQUIRED_FIELDS
                     .iter()
                     .copied()
-                    .chain([COMPLETION_PARENT_MARKER_FIELD, "gen_ai.agent.name"])
+                    .chain([
+                        COMPLETION_PARENT_MARKER_FIELD,
+                        "gen_ai.agent.name",
+                        "rig.model.served_by",
+                    ])
                     .collect();
                 assert_eq!(declared, expected);
                 // Duplicate field names collapse in a `HashSet`, so also pin
@@ -4710,6 +4921,56 @@ mod migrated_tests {
             assert_eq!(streaming_completion, &["stopped streaming response"]);
         }
 
+        #[tokio::test]
+        async fn priced_runs_attach_cost_to_the_response_and_agent_span() {
+            let _isolation = crate::test_utils::scoped_tracing_subscriber_guard().await;
+            let captured = Captured::default();
+            let subscriber = Registry::default().with(CaptureLayer {
+                captured: captured.clone(),
+            });
+            let _default = tracing::subscriber::set_default(subscriber);
+
+            warm_blocking_callsites().await;
+            tracing::callsite::rebuild_interest_cache();
+            captured.clear();
+
+            // One unit per input token and two per output token.
+            let agent = AgentBuilder::new(tool_then_text_model())
+                .tool(MockAddTool)
+                .pricing(ModelPricing::new(1_000_000.0, 2_000_000.0))
+                .build();
+            let response = agent
+                .runner("add 2 and 3")
+                .max_turns(3)
+                .run()
+                .await
+                .expect("blocking run should succeed");
+
+            let cost = response.cost().expect("a priced agent attaches a cost");
+            assert_eq!(cost.input, (7 + 13) as f64);
+            assert_eq!(cost.output, (2 * (11 + 17)) as f64);
+
+            let spans = captured.snapshot();
+            let agent_span = spans
+                .iter()
+                .find(|s| s.name == "invoke_agent")
+                .expect("blocking run should create an invoke_agent span");
+            assert_eq!(
+                agent_span.string_fields.get("gen_ai.usage.cost"),
+                Some(&vec!["76.0".to_string()]),
+            );
+
+            let unpriced = AgentBuilder::new(tool_then_text_model())
+                .tool(MockAddTool)
+                .build()
+                .runner("add 2 and 3")
+                .max_turns(3)
+                .run()
+                .await
+                .expect("blocking run should succeed");
+            assert!(unpriced.cost().is_none());
+        }
+
         #[tokio::test]
         async fn run_records_usage_and_chains_chat_spans_on_a_created_agent_span() {
             let _isolation = crate::test_utils::scoped_tracing_subscriber_guard().await;
@@ -5121,6 +5382,38 @@ mod migrated_tests {
         })
     }
 
+    /// A tool call that overruns its policy timeout is abandoned and reaches
+    /// the model as a timeout result; the run continues to the next turn.
+    #[tokio::test]
+    async fn tool_policy_timeout_is_fed_back_to_the_model() {
+        let tool = crate::test_utils::MockControlledTool::new(
+            Arc::new(Notify::new()),
+            Arc::new(Notify::new()),
+        );
+        let response = AgentBuilder::new(MockCompletionModel::from_turns([
+            MockTurn::tool_call("tc1", "controlled", json!({})),
+            MockTurn::text("gave up"),
+        ]))
+        .tool(tool)
+        .tool_policy(
+            "controlled",
+            crate::tool::ToolExecutionPolicy::new().timeout(std::time::Duration::from_millis(20)),
+        )
+        .build()
+        .runner("call the slow tool")
+        .max_turns(3)
+        .run()
+        .await
+        .expect("a timed-out tool call is not a run failure");
+
+        assert_eq!(response.output, "gave up");
+        let messages = response.messages.expect("messages");
+        assert!(
+            tool_result_text_in_history(&messages, "tool `controlled` did not finish within 20ms"),
+            "the timeout must be the tool result the model sees: {messages:?}"
+        );
+    }
+
     /// Even with `run()` executing tools concurrently, the tool-result order —
     /// and so the whole message history — matches the sequential streaming
     /// driver. (`run()` runs tools with `buffer_unordered` but writes each result
@@ -3,6 +3,7 @@
 use serde::de::DeserializeOwned;
 use thiserror::Error;
 
+use crate::agent::{BudgetExceeded, PendingApprovals, run::store::RunStoreError};
 use rig_core::{
     memory::MemoryError,
     wasm_compat::{WasmCompatSend, WasmCompatSync},
@@ -21,6 +22,11 @@ pub enum PromptError {
     #[error("MemoryError: {0}")]
     MemoryError(#[from] MemoryError),
 
+    /// A run checkpoint could not be loaded for
+    /// [`AgentRunner::resume`](crate::agent::AgentRunner::resume).
+    #[error("RunStoreError: {0}")]
+    RunStoreError(#[from] RunStoreError),
+
     /// The run exhausted its total model-call budget.
     #[error("MaxTurnsError: reached max turns limit: {max_turns}")]
     MaxTurnsError {
@@ -41,6 +47,22 @@ pub enum PromptError {
         reason: String,
     },
 
+    /// A [`BudgetGuard`](crate::agent::BudgetGuard) refused a model call
+    /// because its token or cost budget was exhausted.
+    #[error("BudgetExceeded: {exceeded}")]
+    BudgetExceeded {
+        /// Canonical history available when the call was refused.
+        chat_history: Box<Vec<Message>>,
+        /// The exhausted budget.
+        exceeded: BudgetExceeded,
+    },
+
+    /// A hook deferred one or more tool calls for approval. Record decisions
+    /// on the [`PendingApprovals`] and continue with
+    /// [`AgentRunner::resume_approvals`](crate::agent::AgentRunner::resume_approvals).
+    #[error("ApprovalRequired: {} tool call(s) awaiting approval", .0.requests().len())]
+    ApprovalRequired(Box<PendingApprovals>),
+
     /// The model attempted to call a tool unavailable for the current turn.
     #[error(
         "UnknownToolCall: model attempted to call unknown or disallowed tool `{tool_name}`. Available tools: {available_tools:?}. Allowed tools for this turn: {allowed_tools:?}"
@@ -55,6 +77,22 @@ pub enum PromptError {
         /// Canonical history available at failure.
         chat_history: Box<Vec<Message>>,
     },
+
+    /// The model called a tool with arguments that violate its parameter
+    /// schema, and invalid tool-call recovery did not resolve the call. Only
+    /// raised when the agent validates tool arguments.
+    #[error(
+        "InvalidToolArguments: arguments for tool `{tool_name}` do not match its schema: {}",
+        crate::tool::schema::join_violations(errors)
+    )]
+    InvalidToolArguments {
+        /// Tool name emitted by the model.
+        tool_name: String,
+        /// Every schema violation found in the arguments.
+        errors: Vec<crate::tool::ArgumentViolation>,
+        /// Canonical history available at failure.
+        chat_history: Box<Vec<Message>>,
+    },
 }
 
 /// Forwards the `provider_response_*` accessor trio through the variant that
@@ -120,6 +158,16 @@ impl PromptError {
             reason: reason.into(),
         }
     }
+
+    pub(crate) fn budget_exceeded(
+        chat_history: impl IntoIterator<Item = Message>,
+        exceeded: BudgetExceeded,
+    ) -> Self {
+        Self::BudgetExceeded {
+            chat_history: Box::new(chat_history.into_iter().collect()),
+            exceeded,
+        }
+    }
 }
 
 /// Errors returned by typed structured prompting.
@@ -76,7 +76,7 @@ pub mod test_utils;
 pub mod tool;
 
 pub use agent::{
-    Agent, AgentBuilder, AgentHook, AgentRun, AgentRunner, HookContext, ModelHandle,
+    Agent, AgentBuilder, AgentHook, AgentRun, AgentRunner, FallbackModel, HookContext, ModelHandle,
     ModelSelection, ModelSelectionAction,
 };
 pub use extractor::ExtractionResponse;
@@ -96,6 +96,7 @@ const _: fn() = || {
     assert_send_sync_static::<Agent>();
     assert_send_sync_static::<AgentRunner>();
     assert_send_sync_static::<ModelHandle>();
+    assert_send_sync_static::<FallbackModel>();
     assert_send_sync_static::<agent::MultiTurnStreamItem>();
     assert_send_sync_static::<agent::RunEvents>();
     assert_send_sync_static::<agent::PromptResponse>();
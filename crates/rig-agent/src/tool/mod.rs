@@ -123,12 +123,16 @@ use rig_core::{
 
 use crate::completion::{self, ToolDefinition};
 
+pub mod policy;
+pub mod schema;
 pub mod server;
 
+pub use policy::{ToolExecutionPolicy, ToolRetry};
 pub use rig_core::tool::{
     IntoToolOutput, PortableDynamicTool, ToolErrorKind, ToolExecutionError, ToolOutput, ToolResult,
 };
 pub use rig_core::tool::{MissingToolContext, ToolContext};
+pub use schema::ArgumentViolation;
 
 /// A typed LLM tool.
 ///
@@ -557,6 +561,8 @@ impl ToolRegistration {
 pub(crate) struct ToolDispatch {
     pub(crate) result: ToolResult,
     pub(crate) context: ToolContext,
+    /// How many times the tool was executed; zero when it was not found.
+    pub(crate) attempts: usize,
 }
 
 impl ToolDispatch {
@@ -574,27 +580,28 @@ impl ToolDispatch {
 /// Every surface enters here with its caller-owned context. The helper clones
 /// inbound values exactly once, clears prior result metadata, and returns the
 /// per-dispatch context so callers can expose its metadata without publishing
-/// mutations the tool made to its local inbound snapshot.
+/// mutations the tool made to its local inbound snapshot. `policy` bounds and
+/// retries the execution; each attempt gets its own dispatch context.
 pub(crate) async fn dispatch_tool(
     name: &str,
     args: String,
     tool: Option<RegisteredTool>,
     context: &ToolContext,
+    policy: &ToolExecutionPolicy,
 ) -> ToolDispatch {
-    let mut dispatch_context = context.for_dispatch();
-    let result = match tool {
+    match tool {
         Some(tool) => {
             tracing::debug!(target: "rig", tool_name = name, "calling tool with args:\n{args}");
-            tool.execute(args, &mut dispatch_context).await
+            policy.dispatch(name, &args, &tool, context).await
         }
-        None => ToolResult::failed(
-            ToolExecutionError::not_found(format!("no tool named `{name}` is registered"))
-                .with_model_feedback(format!("tool `{name}` not found")),
-        ),
-    };
-    ToolDispatch {
-        result,
-        context: dispatch_context,
+        None => ToolDispatch {
+            result: ToolResult::failed(
+                ToolExecutionError::not_found(format!("no tool named `{name}` is registered"))
+                    .with_model_feedback(format!("tool `{name}` not found")),
+            ),
+            context: context.for_dispatch(),
+            attempts: 0,
+        },
     }
 }
 
@@ -737,7 +744,14 @@ impl ToolSet {
     ) -> ToolResult {
         context.clear_dispatch_result();
         let tool = self.get(name).cloned();
-        let dispatch = dispatch_tool(name, args.into(), tool, context).await;
+        let dispatch = dispatch_tool(
+            name,
+            args.into(),
+            tool,
+            context,
+            &ToolExecutionPolicy::default(),
+        )
+        .await;
         dispatch.publish_to(context)
     }
 
@@ -0,0 +1,354 @@
+//! Execution deadlines and retries for registered tools.
+//!
+//! A [`ToolExecutionPolicy`] bounds each attempt of a tool call with a
+//! timeout and re-dispatches attempts that fail with a retryable
+//! [`ToolExecutionError`]. Policies are configured on a
+//! [`ToolServer`](super::server::ToolServer) — or through the matching
+//! [`AgentBuilder`](crate::agent::AgentBuilder) methods — as a default for
+//! every tool plus per-tool overrides:
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use rig_agent::tool::{ToolExecutionPolicy, ToolRetry, server::ToolServer};
+//!
+//! let server = ToolServer::new()
+//!     .default_tool_policy(ToolExecutionPolicy::new().timeout(Duration::from_secs(30)))
+//!     .tool_policy(
+//!         "search",
+//!         ToolExecutionPolicy::new()
+//!             .timeout(Duration::from_secs(10))
+//!             .retry(ToolRetry::new(2)),
+//!     );
+//! ```
+//!
+//! A timed-out attempt is dropped and yields a [`ToolExecutionError::timeout`]
+//! result, which is retryable by default. The last attempt's result is what
+//! the model sees; the number of attempts is recorded on the `execute_tool`
+//! span as `gen_ai.tool.call.attempts`, and each retry emits an event on it.
+
+use std::{collections::HashMap, time::Duration};
+
+use super::{RegisteredTool, ToolContext, ToolDispatch, ToolExecutionError, ToolResult};
+
+/// How many times, and how far apart, a retryable tool failure is re-dispatched.
+///
+/// A failure is retryable when its [`ToolExecutionError::retryable`] is
+/// `Some(true)` — the default for timeouts, rate limiting and network errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRetry {
+    max_retries: usize,
+    initial_delay: Duration,
+    factor: f64,
+    max_delay: Duration,
+}
+
+impl ToolRetry {
+    /// Retry up to `max_retries` times, waiting 250ms before the first retry
+    /// and doubling the delay up to 10s.
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            initial_delay: Duration::from_millis(250),
+            factor: 2.0,
+            max_delay: Duration::from_secs(10),
+        }
+    }
+
+    /// Wait `initial_delay` before the first retry, multiplying the delay by
+    /// `factor` for each later one, capped at `max_delay`.
+    pub fn backoff(mut self, initial_delay: Duration, factor: f64, max_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.factor = factor;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The maximum number of retries after the first attempt.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    /// Delay before the retry numbered `retry` (starting at 1).
+    fn delay(&self, retry: usize) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let delay = self.initial_delay.as_secs_f64() * self.factor.max(1.0).powi(exponent);
+        Duration::try_from_secs_f64(delay)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Timeout and retry settings for executing a tool.
+///
+/// Unset fields of a per-tool policy fall back to the server's default
+/// policy. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolExecutionPolicy {
+    timeout: Option<Duration>,
+    retry: Option<ToolRetry>,
+}
+
+impl ToolExecutionPolicy {
+    /// A policy with no timeout and no retries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bound each attempt to `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry attempts that fail with a retryable error.
+    pub fn retry(mut self, retry: ToolRetry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// The configured per-attempt timeout, if any.
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The configured retry policy, if any.
+    pub fn get_retry(&self) -> Option<&ToolRetry> {
+        self.retry.as_ref()
+    }
+
+    /// This policy with unset fields taken from `fallback`.
+    fn or(&self, fallback: &Self) -> Self {
+        Self {
+            timeout: self.timeout.or(fallback.timeout),
+            retry: self.retry.clone().or_else(|| fallback.retry.clone()),
+        }
+    }
+
+    /// Run `tool` under this policy. Each attempt gets a fresh dispatch
+    /// context; the last attempt's result and context are returned.
+    pub(crate) async fn dispatch(
+        &self,
+        name: &str,
+        args: &str,
+        tool: &RegisteredTool,
+        context: &ToolContext,
+    ) -> ToolDispatch {
+        let mut attempts = 1;
+        loop {
+            let mut dispatch_context = context.for_dispatch();
+            let execution = tool.execute(args.to_string(), &mut dispatch_context);
+            let result = match self.timeout {
+                Some(timeout) => rig_core::wasm_compat::timeout(timeout, execution)
+                    .await
+                    .unwrap_or_else(|_| {
+                        let message = format!(
+                            "tool `{name}` did not finish within {}ms",
+                            timeout.as_millis()
+                        );
+                        ToolResult::failed(
+                            ToolExecutionError::timeout(message.clone())
+                                .with_model_feedback(message),
+                        )
+                    }),
+                None => execution.await,
+            };
+
+            let retry = self.retry.as_ref().filter(|retry| {
+                attempts <= retry.max_retries
+                    && result.error().and_then(ToolExecutionError::retryable) == Some(true)
+            });
+            let Some(retry) = retry else {
+                return ToolDispatch {
+                    result,
+                    context: dispatch_context,
+                    attempts,
+                };
+            };
+
+            let delay = retry.delay(attempts);
+            tracing::info!(
+                target: "rig",
+                tool_name = name,
+                attempt = attempts,
+                error.type = result.error().map(|error| error.kind().as_str()),
+                delay_ms = delay.as_millis() as u64,
+                "retrying tool call after retryable failure"
+            );
+            rig_core::wasm_compat::sleep(delay).await;
+            attempts += 1;
+        }
+    }
+}
+
+/// A server's default policy plus its per-tool overrides.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolPolicies {
+    default: ToolExecutionPolicy,
+    per_tool: HashMap<String, ToolExecutionPolicy>,
+}
+
+impl ToolPolicies {
+    pub(crate) fn set_default(&mut self, policy: ToolExecutionPolicy) {
+        self.default = policy;
+    }
+
+    pub(crate) fn set(&mut self, tool_name: impl Into<String>, policy: ToolExecutionPolicy) {
+        self.per_tool.insert(tool_name.into(), policy);
+    }
+
+    /// The effective policy for `tool_name`.
+    pub(crate) fn resolve(&self, tool_name: &str) -> ToolExecutionPolicy {
+        match self.per_tool.get(tool_name) {
+            Some(policy) => policy.or(&self.default),
+            None => self.default.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::tool::{DynamicTool, ToolErrorKind, ToolOutput, ToolSet, server::ToolServer};
+
+    /// Fails with `error` for the first `failures` calls, then succeeds.
+    fn flaky_tool(
+        name: &str,
+        failures: usize,
+        error: fn() -> ToolExecutionError,
+    ) -> (DynamicTool, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let tool = DynamicTool::new(
+            name,
+            "fails a few times",
+            json!({"type": "object", "properties": {}}),
+            move |_context, _args| {
+                let counter = counter.clone();
+                Box::pin(async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        Err(error())
+                    } else {
+                        Ok(ToolOutput::text("ok"))
+                    }
+                })
+            },
+        );
+        (tool, calls)
+    }
+
+    fn quick_retry(max_retries: usize) -> ToolRetry {
+        ToolRetry::new(max_retries).backoff(Duration::from_millis(1), 2.0, Duration::from_millis(5))
+    }
+
+    #[test]
+    fn per_tool_policies_fall_back_to_the_default() {
+        let mut policies = ToolPolicies::default();
+        policies.set_default(
+            ToolExecutionPolicy::new()
+                .timeout(Duration::from_secs(30))
+                .retry(ToolRetry::new(1)),
+        );
+        policies.set(
+            "search",
+            ToolExecutionPolicy::new().timeout(Duration::from_secs(5)),
+        );
+
+        let search = policies.resolve("search");
+        assert_eq!(search.get_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(search.get_retry().map(ToolRetry::max_retries), Some(1));
+        assert_eq!(
+            policies.resolve("other").get_timeout(),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let retry =
+            ToolRetry::new(5).backoff(Duration::from_millis(100), 3.0, Duration::from_millis(500));
+        assert_eq!(retry.delay(1), Duration::from_millis(100));
+        assert_eq!(retry.delay(2), Duration::from_millis(300));
+        assert_eq!(retry.delay(3), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn retryable_failures_are_redispatched_up_to_the_limit() {
+        let (tool, calls) = flaky_tool("flaky", 2, || ToolExecutionError::network("reset"));
+        let handle = ToolServer::new()
+            .dynamic_tool(tool)
+            .tool_policy("flaky", ToolExecutionPolicy::new().retry(quick_retry(2)))
+            .run();
+
+        let dispatch = handle
+            .snapshot()
+            .dispatch("flaky", "{}", &ToolContext::new())
+            .await;
+        assert!(
+            dispatch.result.error().is_none(),
+            "the third attempt succeeds"
+        );
+        assert_eq!(dispatch.attempts, 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn non_retryable_failures_and_exhausted_retries_surface() {
+        let (tool, calls) = flaky_tool("strict", 5, || ToolExecutionError::invalid_args("bad"));
+        let policy = ToolExecutionPolicy::new().retry(quick_retry(3));
+        let dispatch = policy
+            .dispatch(
+                "strict",
+                "{}",
+                &RegisteredTool::Static(Arc::new(tool)),
+                &ToolContext::new(),
+            )
+            .await;
+        assert!(dispatch.result.is_error_kind(ToolErrorKind::InvalidArgs));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (tool, calls) = flaky_tool("flaky", 5, || ToolExecutionError::rate_limited("slow"));
+        let dispatch = policy
+            .dispatch(
+                "flaky",
+                "{}",
+                &RegisteredTool::Static(Arc::new(tool)),
+                &ToolContext::new(),
+            )
+            .await;
+        assert!(dispatch.result.is_error_kind(ToolErrorKind::RateLimited));
+        assert_eq!(dispatch.attempts, 4);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn attempts_that_overrun_the_timeout_fail_as_timeouts() {
+        let slow = DynamicTool::new(
+            "slow",
+            "never finishes in time",
+            json!({"type": "object", "properties": {}}),
+            |_context, _args| {
+                Box::pin(async {
+                    rig_core::wasm_compat::sleep(Duration::from_secs(5)).await;
+                    Ok(ToolOutput::text("late"))
+                })
+            },
+        );
+        let mut toolset = ToolSet::default();
+        toolset.add_dynamic_tool(slow);
+        let handle = ToolServer::new()
+            .default_tool_policy(ToolExecutionPolicy::new().timeout(Duration::from_millis(10)))
+            .run();
+        handle.append_toolset(toolset);
+
+        let result = handle.execute("slow", "{}", &mut ToolContext::new()).await;
+        assert!(result.is_error_kind(ToolErrorKind::Timeout), "{result:?}");
+    }
+}
@@ -0,0 +1,580 @@
+//! Validation of model-emitted tool arguments against a tool's advertised
+//! JSON Schema.
+//!
+//! Tools publish their parameters as a JSON Schema through
+//! [`ToolDefinition::parameters`](rig_core::completion::ToolDefinition::parameters).
+//! [`validate_arguments`] checks a call's arguments against that schema before
+//! dispatch and reports every violation with the JSON path of the offending
+//! value, in a form the model can act on:
+//!
+//! ```
+//! use rig_agent::tool::schema::validate_arguments;
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "city": { "type": "string" },
+//!         "unit": { "enum": ["celsius", "fahrenheit"] }
+//!     },
+//!     "required": ["city"]
+//! });
+//!
+//! let violations = validate_arguments(&schema, &json!({ "unit": "kelvin" }));
+//! let messages: Vec<String> = violations.iter().map(ToString::to_string).collect();
+//! assert_eq!(
+//!     messages,
+//!     [
+//!         "$.city: required property is missing",
+//!         r#"$.unit: expected one of ["celsius","fahrenheit"], got "kelvin""#,
+//!     ]
+//! );
+//! ```
+//!
+//! Agents opt in with
+//! [`AgentBuilder::validate_tool_arguments`](crate::agent::AgentBuilder::validate_tool_arguments),
+//! which routes violating calls through
+//! [`AgentHook::on_invalid_tool_call`](crate::agent::AgentHook::on_invalid_tool_call)
+//! with the violations on
+//! [`InvalidToolCallContext::argument_errors`](crate::agent::InvalidToolCallContext::argument_errors).
+//!
+//! The validator covers the keywords tool schemas use in practice: `type`
+//! (including `nullable`), `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `prefixItems`, `minItems`/`maxItems`,
+//! `minimum`/`maximum`, `exclusiveMinimum`/`exclusiveMaximum`,
+//! `minLength`/`maxLength`, `allOf`/`anyOf`/`oneOf` and local `$ref`s such as
+//! the `#/$defs/...` references `schemars` emits. Other keywords (`pattern`,
+//! `format`, ...) are not checked, and `oneOf` accepts a value matching any
+//! branch rather than exactly one, so validation never rejects arguments the
+//! tool itself could accept.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// `$ref` chains deeper than this are treated as unresolvable rather than
+/// followed, so a self-referencing schema cannot recurse without bound.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Longest rendering of an offending value quoted back in a violation message.
+const MAX_VALUE_PREVIEW: usize = 60;
+
+/// One way a tool call's arguments fail the tool's JSON Schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArgumentViolation {
+    /// JSONPath of the offending value, e.g. `$.location.unit` or `$.items[2]`.
+    /// A missing required property is reported at the path it should occupy.
+    pub path: String,
+    /// What is wrong with the value at `path`.
+    pub message: String,
+}
+
+impl fmt::Display for ArgumentViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate `args` against `schema`, returning every violation found; empty
+/// when the arguments conform. See the [module docs](self) for the supported
+/// keywords.
+pub fn validate_arguments(schema: &Value, args: &Value) -> Vec<ArgumentViolation> {
+    let mut validator = Validator {
+        root: schema,
+        violations: Vec::new(),
+    };
+    validator.validate(schema, args, &mut String::from("$"), 0);
+    validator.violations
+}
+
+/// `violations` on one line, separated by semicolons.
+pub(crate) fn join_violations(violations: &[ArgumentViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Model-readable feedback listing `violations` for a call to `tool_name`.
+pub(crate) fn violation_feedback(tool_name: &str, violations: &[ArgumentViolation]) -> String {
+    let mut feedback =
+        format!("The arguments for tool `{tool_name}` do not match its parameter schema:");
+    for violation in violations {
+        feedback.push_str("\n- ");
+        feedback.push_str(&violation.to_string());
+    }
+    feedback.push_str("\nCall the tool again with corrected arguments.");
+    feedback
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    violations: Vec<ArgumentViolation>,
+}
+
+impl<'a> Validator<'a> {
+    fn violation(&mut self, path: &str, message: impl Into<String>) {
+        self.violations.push(ArgumentViolation {
+            path: path.to_owned(),
+            message: message.into(),
+        });
+    }
+
+    /// Whether `value` satisfies `schema`, without recording violations.
+    fn accepts(&self, schema: &'a Value, value: &Value, depth: usize) -> bool {
+        let mut probe = Validator {
+            root: self.root,
+            violations: Vec::new(),
+        };
+        probe.validate(schema, value, &mut String::from("$"), depth);
+        probe.violations.is_empty()
+    }
+
+    fn validate(&mut self, schema: &'a Value, value: &Value, path: &mut String, depth: usize) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.violation(path, "no value is allowed here");
+                return;
+            }
+            Value::Object(schema) => schema,
+            // Not a schema; nothing to check against.
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(target) if depth < MAX_REF_DEPTH => {
+                    self.validate(target, value, path, depth + 1);
+                }
+                // An unresolvable reference cannot be checked; accept rather
+                // than reject arguments the tool may well handle.
+                _ => {}
+            }
+        }
+
+        if !self.check_type(schema, value, path) {
+            // Every later keyword would only restate the type mismatch.
+            return;
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.iter().any(|candidate| json_eq(candidate, value))
+        {
+            self.violation(
+                path,
+                format!(
+                    "expected one of {}, got {}",
+                    preview(&Value::Array(allowed.clone())),
+                    preview(value)
+                ),
+            );
+        }
+        if let Some(expected) = schema.get("const")
+            && !json_eq(expected, value)
+        {
+            self.violation(
+                path,
+                format!("expected {}, got {}", preview(expected), preview(value)),
+            );
+        }
+
+        if let Some(branches) = schema.get("allOf").and_then(Value::as_array) {
+            for branch in branches {
+                self.validate(branch, value, path, depth);
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(branches) = schema.get(keyword).and_then(Value::as_array)
+                && !branches
+                    .iter()
+                    .any(|branch| self.accepts(branch, value, depth))
+            {
+                self.violation(
+                    path,
+                    format!(
+                        "does not match any of the {} allowed schemas",
+                        branches.len()
+                    ),
+                );
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, depth),
+            Value::Array(items) => self.check_array(schema, items, path, depth),
+            Value::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    self.check_range(schema, number, path);
+                }
+            }
+            Value::String(string) => self.check_length(schema, string, path),
+            Value::Null | Value::Bool(_) => {}
+        }
+    }
+
+    /// Resolve a local `#/...` JSON-pointer reference against the root schema.
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+
+    /// Check `type` (and OpenAPI's `nullable`); `false` when it failed.
+    fn check_type(&mut self, schema: &Map<String, Value>, value: &Value, path: &str) -> bool {
+        let expected: Vec<&str> = match schema.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => return true,
+        };
+        let nullable = schema.get("nullable").and_then(Value::as_bool) == Some(true);
+        if (nullable && value.is_null()) || expected.iter().any(|name| is_type(value, name)) {
+            return true;
+        }
+
+        let mut expected = expected.join(" or ");
+        if nullable {
+            expected.push_str(" or null");
+        }
+        self.violation(
+            path,
+            format!(
+                "expected {expected}, got {} {}",
+                type_name(value),
+                preview(value)
+            ),
+        );
+        false
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &mut String,
+        depth: usize,
+    ) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    let len = push_key(path, name);
+                    self.violation(path, "required property is missing");
+                    path.truncate(len);
+                }
+            }
+        }
+
+        let additional = schema.get("additionalProperties");
+        for (name, property) in object {
+            let len = push_key(path, name);
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property_schema) => self.validate(property_schema, property, path, depth),
+                None => match additional {
+                    Some(Value::Bool(false)) => {
+                        let mut allowed: Vec<&str> = properties
+                            .map(|properties| properties.keys().map(String::as_str).collect())
+                            .unwrap_or_default();
+                        allowed.sort_unstable();
+                        let allowed = allowed.join(", ");
+                        self.violation(
+                            path,
+                            format!("unexpected property (allowed properties: {allowed})"),
+                        );
+                    }
+                    Some(additional @ Value::Object(_)) => {
+                        self.validate(additional, property, path, depth);
+                    }
+                    _ => {}
+                },
+            }
+            path.truncate(len);
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        items: &[Value],
+        path: &mut String,
+        depth: usize,
+    ) {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && count < min
+        {
+            self.violation(
+                path,
+                format!("must have at least {min} item(s), got {count}"),
+            );
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && count > max
+        {
+            self.violation(
+                path,
+                format!("must have at most {max} item(s), got {count}"),
+            );
+        }
+
+        // Draft 2020-12 `prefixItems` (or the older array form of `items`)
+        // constrains leading positions; `items` then covers the rest.
+        let (prefix, rest): (&[Value], Option<&Value>) =
+            match (schema.get("prefixItems"), schema.get("items")) {
+                (Some(Value::Array(prefix)), rest) => (prefix, rest),
+                (None, Some(Value::Array(prefix))) => (prefix, schema.get("additionalItems")),
+                (_, rest) => (&[], rest),
+            };
+        for (index, item) in items.iter().enumerate() {
+            let item_schema = prefix.get(index).or(rest);
+            if let Some(item_schema) = item_schema {
+                let len = path.len();
+                path.push_str(&format!("[{index}]"));
+                self.validate(item_schema, item, path, depth);
+                path.truncate(len);
+            }
+        }
+    }
+
+    fn check_range(&mut self, schema: &Map<String, Value>, number: f64, path: &str) {
+        let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum")
+            && number < min
+        {
+            self.violation(path, format!("must be >= {min}, got {number}"));
+        }
+        if let Some(max) = bound("maximum")
+            && number > max
+        {
+            self.violation(path, format!("must be <= {max}, got {number}"));
+        }
+        if let Some(min) = bound("exclusiveMinimum")
+            && number <= min
+        {
+            self.violation(path, format!("must be > {min}, got {number}"));
+        }
+        if let Some(max) = bound("exclusiveMaximum")
+            && number >= max
+        {
+            self.violation(path, format!("must be < {max}, got {number}"));
+        }
+    }
+
+    fn check_length(&mut self, schema: &Map<String, Value>, string: &str, path: &str) {
+        let length = string.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && length < min
+        {
+            self.violation(
+                path,
+                format!("must be at least {min} character(s) long, got {length}"),
+            );
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && length > max
+        {
+            self.violation(
+                path,
+                format!("must be at most {max} character(s) long, got {length}"),
+            );
+        }
+    }
+}
+
+/// Append an object key to a JSONPath, returning the length to truncate back
+/// to. Identifier-like keys use dot notation, anything else bracket notation.
+fn push_key(path: &mut String, key: &str) -> usize {
+    let len = path.len();
+    let identifier = key
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if identifier {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push('[');
+        path.push_str(&Value::String(key.to_owned()).to_string());
+        path.push(']');
+    }
+    len
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|float| float.fract() == 0.0)
+            }
+            _ => false,
+        },
+        // An unknown type name cannot be checked; accept.
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// JSON equality that treats numerically equal numbers as equal (`1 == 1.0`).
+fn json_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| json_eq(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| right.get(key).is_some_and(|r| json_eq(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+/// Compact JSON rendering of `value`, shortened for long values.
+fn preview(value: &Value) -> String {
+    let rendered = value.to_string();
+    if rendered.chars().count() <= MAX_VALUE_PREVIEW {
+        return rendered;
+    }
+    let mut shortened: String = rendered.chars().take(MAX_VALUE_PREVIEW).collect();
+    shortened.push('…');
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Sorted, since object properties are visited in map order.
+    fn messages(schema: &Value, args: &Value) -> Vec<String> {
+        let mut messages: Vec<String> = validate_arguments(schema, args)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        messages.sort();
+        messages
+    }
+
+    #[test]
+    fn reports_types_ranges_and_lengths_at_their_paths() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer", "minimum": 1, "maximum": 10 },
+                "ratio": { "type": "number", "exclusiveMaximum": 1 },
+                "name": { "type": "string", "minLength": 2 },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "maxItems": 2
+                },
+                "weird key": { "type": "boolean" }
+            },
+            "additionalProperties": false
+        });
+
+        assert_eq!(
+            messages(
+                &schema,
+                &json!({
+                    "count": 0,
+                    "ratio": 1.0,
+                    "name": "x",
+                    "tags": ["a", 2, "c"],
+                    "weird key": "yes",
+                    "extra": true
+                })
+            ),
+            [
+                "$.count: must be >= 1, got 0",
+                "$.extra: unexpected property (allowed properties: count, name, ratio, tags, weird key)",
+                "$.name: must be at least 2 character(s) long, got 1",
+                "$.ratio: must be < 1, got 1",
+                "$.tags: must have at most 2 item(s), got 3",
+                "$.tags[1]: expected string, got integer 2",
+                r#"$["weird key"]: expected boolean, got string "yes""#,
+            ]
+        );
+        assert!(validate_arguments(&schema, &json!({ "count": 3.0, "tags": [] })).is_empty());
+    }
+
+    #[test]
+    fn follows_local_refs_and_combinators() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "location": { "$ref": "#/$defs/Location" },
+                "when": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                "mode": { "type": ["string", "null"], "const": "fast" }
+            },
+            "required": ["location"],
+            "$defs": {
+                "Location": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"]
+                }
+            }
+        });
+
+        assert_eq!(
+            messages(
+                &schema,
+                &json!({ "location": {}, "when": 5, "mode": "slow" })
+            ),
+            [
+                "$.location.city: required property is missing",
+                r#"$.mode: expected "fast", got "slow""#,
+                "$.when: does not match any of the 2 allowed schemas",
+            ]
+        );
+        assert_eq!(
+            messages(&schema, &json!("not an object")),
+            [r#"$: expected object, got string "not an object""#]
+        );
+        assert!(
+            validate_arguments(
+                &schema,
+                &json!({ "location": { "city": "Oslo" }, "when": null })
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn self_referencing_and_unknown_schemas_do_not_reject() {
+        let recursive = json!({ "$ref": "#" });
+        assert!(validate_arguments(&recursive, &json!({ "a": 1 })).is_empty());
+        assert!(validate_arguments(&json!({ "$ref": "#/$defs/Missing" }), &json!(1)).is_empty());
+        assert!(
+            validate_arguments(&json!({ "type": "object" }), &json!({ "any": [1] })).is_empty()
+        );
+    }
+}
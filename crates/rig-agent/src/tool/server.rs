@@ -11,7 +11,7 @@ use crate::{
     completion::{CompletionError, ToolDefinition},
     tool::{
         DynamicTool, PortableDynamicTool, RegisteredTool, Tool, ToolContext, ToolDispatch,
-        ToolResult, ToolSet, dispatch_tool,
+        ToolExecutionPolicy, ToolResult, ToolSet, dispatch_tool, policy::ToolPolicies,
     },
 };
 use rig_core::vector_store::{
@@ -29,21 +29,28 @@ use rig_core::vector_store::{
 /// [`definitions`](Self::definitions) / [`names`](Self::names) or
 /// [`execute`](Self::execute) against it without touching the live registry.
 ///
+/// The server's [`ToolExecutionPolicy`]s are pinned alongside the tools.
+///
 /// Cloning shares the pinned tool handles (they are `Arc`s) and copies the
 /// definitions.
 #[derive(Clone)]
 pub struct ToolRegistrySnapshot {
     definitions: Vec<ToolDefinition>,
     tools: IndexMap<String, RegisteredTool>,
+    policies: Arc<ToolPolicies>,
 }
 
 impl ToolRegistrySnapshot {
-    fn new(tools: IndexMap<String, RegisteredTool>) -> Self {
+    fn new(tools: IndexMap<String, RegisteredTool>, policies: Arc<ToolPolicies>) -> Self {
         let definitions = tools
             .iter()
             .map(|(name, tool)| tool.definition_with_name(name.clone()))
             .collect();
-        Self { definitions, tools }
+        Self {
+            definitions,
+            tools,
+            policies,
+        }
     }
 
     /// Provider-facing definitions in the same order as their pinned handles.
@@ -104,7 +111,8 @@ impl ToolRegistrySnapshot {
         context: &ToolContext,
     ) -> ToolDispatch {
         let tool = self.tools.get(tool_name).cloned();
-        dispatch_tool(tool_name, args.to_string(), tool, context).await
+        let policy = self.policies.resolve(tool_name);
+        dispatch_tool(tool_name, args.to_string(), tool, context, &policy).await
     }
 }
 
@@ -118,6 +126,8 @@ struct ToolServerState {
     /// A normal registration clears the token, preventing a stale handler
     /// refresh from replacing or removing the newer tool.
     managed_generations: HashMap<String, ManagedToolToken>,
+    /// Timeout and retry policies, shared with every registry snapshot.
+    policies: Arc<ToolPolicies>,
 }
 
 impl ToolServerState {
@@ -150,6 +160,7 @@ pub use rig_core::tool::{ManagedToolSink, ManagedToolToken};
 pub struct ToolServer {
     retrieval_indexes: Vec<(usize, Arc<dyn VectorStoreIndexDyn + Send + Sync>)>,
     toolset: ToolSet,
+    policies: ToolPolicies,
 }
 
 impl Default for ToolServer {
@@ -163,6 +174,7 @@ impl ToolServer {
         Self {
             retrieval_indexes: Vec::new(),
             toolset: ToolSet::default(),
+            policies: ToolPolicies::default(),
         }
     }
 
@@ -211,12 +223,33 @@ impl ToolServer {
         self
     }
 
+    /// Set the timeout and retry policy for every tool without a
+    /// [`tool_policy`](Self::tool_policy) of its own. See
+    /// [`ToolExecutionPolicy`].
+    pub fn default_tool_policy(mut self, policy: ToolExecutionPolicy) -> Self {
+        self.policies.set_default(policy);
+        self
+    }
+
+    /// Set the timeout and retry policy for the tool named `tool_name`.
+    /// Fields it leaves unset fall back to the
+    /// [`default_tool_policy`](Self::default_tool_policy).
+    pub fn tool_policy(
+        mut self,
+        tool_name: impl Into<String>,
+        policy: ToolExecutionPolicy,
+    ) -> Self {
+        self.policies.set(tool_name, policy);
+        self
+    }
+
     /// Consume the builder and return a shared [`ToolServerHandle`].
     pub fn run(self) -> ToolServerHandle {
         ToolServerHandle(Arc::new(RwLock::new(ToolServerState {
             retrieval_indexes: self.retrieval_indexes,
             toolset: self.toolset,
             managed_generations: HashMap::new(),
+            policies: Arc::new(self.policies),
         })))
     }
 }
@@ -443,8 +476,13 @@ impl ToolServerHandle {
         args: &str,
         context: &ToolContext,
     ) -> ToolDispatch {
-        let tool = self.with_registry(|state| state.toolset.get(tool_name).cloned());
-        dispatch_tool(tool_name, args.to_string(), tool, context).await
+        let (tool, policy) = self.with_registry(|state| {
+            (
+                state.toolset.get(tool_name).cloned(),
+                state.policies.resolve(tool_name),
+            )
+        });
+        dispatch_tool(tool_name, args.to_string(), tool, context, &policy).await
     }
 
     /// The registry as it stands, synchronously: every always-exposed
@@ -456,8 +494,13 @@ impl ToolServerHandle {
     /// For the retrieval-aware view that also selects dynamic tools for a
     /// prompt, use the async [`get_tool_defs`](Self::get_tool_defs).
     pub fn snapshot(&self) -> ToolRegistrySnapshot {
-        let tools = self.with_registry(|state| snapshot_registered_tools(state, &[]));
-        ToolRegistrySnapshot::new(tools)
+        let (tools, policies) = self.with_registry(|state| {
+            (
+                snapshot_registered_tools(state, &[]),
+                state.policies.clone(),
+            )
+        });
+        ToolRegistrySnapshot::new(tools, policies)
     }
 
     /// Provider definitions of the registry as it stands — the definitions of
@@ -545,9 +588,14 @@ impl ToolServerHandle {
             Vec::new()
         };
 
-        let tools = self.with_registry(|state| snapshot_registered_tools(state, &dynamic_tool_ids));
+        let (tools, policies) = self.with_registry(|state| {
+            (
+                snapshot_registered_tools(state, &dynamic_tool_ids),
+                state.policies.clone(),
+            )
+        });
 
-        Ok(ToolRegistrySnapshot::new(tools))
+        Ok(ToolRegistrySnapshot::new(tools, policies))
     }
 }
 
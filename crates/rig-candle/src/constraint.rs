@@ -0,0 +1,1534 @@
+//! JSON Schema constrained decoding.
+//!
+//! A schema compiles into a small grammar that a byte-level pushdown matcher
+//! walks. Before each sampling step the matcher runs over a trie of the
+//! vocabulary's token bytes, and every token that cannot continue a valid
+//! document is masked to negative infinity, so the sampler can only pick
+//! tokens that keep the output inside the schema.
+//!
+//! The mask enforces structure: types, `const`/`enum`, `anyOf`/`oneOf`, local
+//! `$ref`s, required and allowed properties, array items and lengths, string
+//! lengths, and non-negative numbers. Value assertions that cannot be decided
+//! byte by byte — other numeric bounds, `multipleOf`, `uniqueItems`, `format`
+//! — are left to the caller's own validation. Keywords that constrain
+//! structure in ways the matcher cannot follow (`pattern`, `not`, `if`,
+//! `patternProperties`, ...) are rejected rather than silently ignored.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use candle_core::{D, DType, Tensor};
+use rig_core::completion::{CompletionRequest, ToolDefinition};
+use serde_json::{Map, Value};
+use tokenizers::{DecoderWrapper, Tokenizer};
+
+use crate::CandleError;
+use crate::loader::LoadedModel;
+
+type NodeId = usize;
+
+const ANY: NodeId = 0;
+const ANY_OBJECT: NodeId = 1;
+const ANY_ARRAY: NodeId = 2;
+const STRING: NodeId = 3;
+const NUMBER: NodeId = 4;
+const INTEGER: NodeId = 5;
+const BOOLEAN: NodeId = 6;
+const NULL: NodeId = 7;
+
+/// Deepest nesting the matcher follows; bounds recursive schemas.
+const MAX_DEPTH: usize = 64;
+/// Longest whitespace run between tokens. Greedy decoding of a small model
+/// can otherwise loop on newlines forever inside an open object.
+const MAX_WHITESPACE: u8 = 24;
+/// Longest number literal, sign and exponent included.
+const MAX_NUMBER_LENGTH: u8 = 32;
+
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "not",
+    "if",
+    "pattern",
+    "patternProperties",
+    "propertyNames",
+    "dependentRequired",
+    "dependentSchemas",
+    "contains",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+];
+
+#[derive(Debug)]
+enum Node {
+    Alternatives(Vec<NodeId>),
+    /// A fixed value, serialized compactly.
+    Literal(Box<[u8]>),
+    String {
+        min_length: usize,
+        max_length: Option<usize>,
+    },
+    Number {
+        integer: bool,
+        unsigned: bool,
+    },
+    Object {
+        properties: Vec<(Box<[u8]>, NodeId)>,
+        /// Indices into `properties`.
+        required: Vec<usize>,
+        additional: Option<NodeId>,
+    },
+    Array {
+        prefix: Vec<NodeId>,
+        items: Option<NodeId>,
+        min_items: usize,
+        max_items: Option<usize>,
+    },
+}
+
+/// A compiled JSON Schema.
+#[derive(Debug)]
+pub(crate) struct Grammar {
+    nodes: Vec<Node>,
+    root: NodeId,
+}
+
+fn unsupported(reason: impl std::fmt::Display) -> CandleError {
+    CandleError::UnsupportedFeature(format!("output_schema: {reason}"))
+}
+
+fn base_nodes() -> Vec<Node> {
+    vec![
+        Node::Alternatives(vec![ANY_OBJECT, ANY_ARRAY, STRING, NUMBER, BOOLEAN, NULL]),
+        Node::Object {
+            properties: Vec::new(),
+            required: Vec::new(),
+            additional: Some(ANY),
+        },
+        Node::Array {
+            prefix: Vec::new(),
+            items: Some(ANY),
+            min_items: 0,
+            max_items: None,
+        },
+        Node::String {
+            min_length: 0,
+            max_length: None,
+        },
+        Node::Number {
+            integer: false,
+            unsigned: false,
+        },
+        Node::Number {
+            integer: true,
+            unsigned: false,
+        },
+        Node::Alternatives(Vec::new()),
+        Node::Literal(Box::from(&b"null"[..])),
+    ]
+}
+
+struct Compiler<'a> {
+    root: &'a Value,
+    nodes: Vec<Node>,
+    refs: HashMap<&'a str, NodeId>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(root: &'a Value) -> Self {
+        let mut compiler = Self {
+            root,
+            nodes: base_nodes(),
+            refs: HashMap::new(),
+        };
+        let true_literal = compiler.push(Node::Literal(Box::from(&b"true"[..])));
+        let false_literal = compiler.push(Node::Literal(Box::from(&b"false"[..])));
+        if let Some(boolean) = compiler.nodes.get_mut(BOOLEAN) {
+            *boolean = Node::Alternatives(vec![true_literal, false_literal]);
+        }
+        compiler
+    }
+
+    fn push(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn literal(&mut self, value: &Value) -> Result<NodeId, CandleError> {
+        let bytes = serde_json::to_vec(value).map_err(unsupported)?;
+        Ok(self.push(Node::Literal(bytes.into_boxed_slice())))
+    }
+
+    fn compile(&mut self, schema: &'a Value) -> Result<NodeId, CandleError> {
+        let object = match schema {
+            Value::Bool(true) => return Ok(ANY),
+            Value::Bool(false) => return Err(unsupported("a `false` schema admits no value")),
+            Value::Object(object) => object,
+            _ => return Err(unsupported("a schema must be an object or a boolean")),
+        };
+        if let Some(keyword) = UNSUPPORTED_KEYWORDS
+            .iter()
+            .find(|keyword| object.contains_key(**keyword))
+        {
+            return Err(unsupported(format_args!(
+                "`{keyword}` cannot be enforced while decoding"
+            )));
+        }
+        if let Some(reference) = object.get("$ref") {
+            return self.reference(reference);
+        }
+        if let Some(value) = object.get("const") {
+            return self.literal(value);
+        }
+        if let Some(values) = object.get("enum") {
+            let values = values
+                .as_array()
+                .filter(|values| !values.is_empty())
+                .ok_or_else(|| unsupported("`enum` must be a non-empty array"))?;
+            let branches = values
+                .iter()
+                .map(|value| self.literal(value))
+                .collect::<Result<_, _>>()?;
+            return Ok(self.push(Node::Alternatives(branches)));
+        }
+        if let Some(all_of) = object.get("allOf") {
+            return match all_of.as_array().map(Vec::as_slice) {
+                Some([only]) => self.compile(only),
+                _ => Err(unsupported("`allOf` is supported with a single subschema")),
+            };
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(branches) = object.get(keyword) {
+                let branches = branches
+                    .as_array()
+                    .filter(|branches| !branches.is_empty())
+                    .ok_or_else(|| {
+                        unsupported(format_args!("`{keyword}` must be a non-empty array"))
+                    })?;
+                let branches = branches
+                    .iter()
+                    .map(|branch| self.compile(branch))
+                    .collect::<Result<_, _>>()?;
+                return Ok(self.push(Node::Alternatives(branches)));
+            }
+        }
+
+        let kinds: Vec<&str> = match object.get("type") {
+            Some(Value::String(kind)) => vec![kind.as_str()],
+            Some(Value::Array(kinds)) => kinds
+                .iter()
+                .map(|kind| {
+                    kind.as_str()
+                        .ok_or_else(|| unsupported("`type` entries must be strings"))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(unsupported("`type` must be a string or an array")),
+            None if ["properties", "required", "additionalProperties"]
+                .iter()
+                .any(|keyword| object.contains_key(*keyword)) =>
+            {
+                vec!["object"]
+            }
+            None if ["items", "prefixItems", "minItems", "maxItems"]
+                .iter()
+                .any(|keyword| object.contains_key(*keyword)) =>
+            {
+                vec!["array"]
+            }
+            None => return Ok(ANY),
+        };
+        let mut branches = kinds
+            .into_iter()
+            .map(|kind| self.typed(kind, object))
+            .collect::<Result<Vec<_>, _>>()?;
+        if object.get("nullable") == Some(&Value::Bool(true)) && !branches.contains(&NULL) {
+            branches.push(NULL);
+        }
+        Ok(match branches.as_slice() {
+            [only] => *only,
+            _ => self.push(Node::Alternatives(branches)),
+        })
+    }
+
+    fn reference(&mut self, reference: &'a Value) -> Result<NodeId, CandleError> {
+        let pointer = reference
+            .as_str()
+            .and_then(|reference| reference.strip_prefix('#'))
+            .ok_or_else(|| unsupported("only local `$ref`s are supported"))?;
+        if let Some(node) = self.refs.get(pointer) {
+            return Ok(*node);
+        }
+        let target = self
+            .root
+            .pointer(pointer)
+            .ok_or_else(|| unsupported(format_args!("`$ref` `#{pointer}` does not resolve")))?;
+        // Registered before compiling the target so recursive definitions
+        // resolve to this indirection instead of recursing forever.
+        let node = self.push(Node::Alternatives(Vec::new()));
+        self.refs.insert(pointer, node);
+        let resolved = self.compile(target)?;
+        if let Some(slot) = self.nodes.get_mut(node) {
+            *slot = Node::Alternatives(vec![resolved]);
+        }
+        Ok(node)
+    }
+
+    fn typed(&mut self, kind: &str, object: &'a Map<String, Value>) -> Result<NodeId, CandleError> {
+        let usize_keyword = |keyword: &str| {
+            object
+                .get(keyword)
+                .and_then(Value::as_u64)
+                .and_then(|value| usize::try_from(value).ok())
+        };
+        match kind {
+            "null" => Ok(NULL),
+            "boolean" => Ok(BOOLEAN),
+            "string" => {
+                let min_length = usize_keyword("minLength").unwrap_or(0);
+                let max_length = usize_keyword("maxLength");
+                Ok(if min_length == 0 && max_length.is_none() {
+                    STRING
+                } else {
+                    self.push(Node::String {
+                        min_length,
+                        max_length,
+                    })
+                })
+            }
+            "number" | "integer" => {
+                let unsigned = ["minimum", "exclusiveMinimum"].iter().any(|keyword| {
+                    object
+                        .get(*keyword)
+                        .and_then(Value::as_f64)
+                        .is_some_and(|minimum| minimum >= 0.0)
+                });
+                let integer = kind == "integer";
+                Ok(match (integer, unsigned) {
+                    (false, false) => NUMBER,
+                    (true, false) => INTEGER,
+                    _ => self.push(Node::Number { integer, unsigned }),
+                })
+            }
+            "object" => self.object(object),
+            "array" => self.array(object, usize_keyword("minItems"), usize_keyword("maxItems")),
+            other => Err(unsupported(format_args!("unknown type `{other}`"))),
+        }
+    }
+
+    fn object(&mut self, object: &'a Map<String, Value>) -> Result<NodeId, CandleError> {
+        let mut properties = Vec::new();
+        if let Some(declared) = object.get("properties") {
+            let declared = declared
+                .as_object()
+                .ok_or_else(|| unsupported("`properties` must be an object"))?;
+            for (name, schema) in declared {
+                properties.push((Box::from(name.as_bytes()), self.compile(schema)?));
+            }
+        }
+        // An object that declares its properties is closed unless it opts back
+        // in: allowing arbitrary extra keys is valid but lets a small model
+        // wander off into keys the caller never asked for.
+        let additional = match object.get("additionalProperties") {
+            None if properties.is_empty() => Some(ANY),
+            None | Some(Value::Bool(false)) => None,
+            Some(Value::Bool(true)) => Some(ANY),
+            Some(schema) => Some(self.compile(schema)?),
+        };
+        let mut required = Vec::new();
+        for name in object
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let name = name
+                .as_str()
+                .ok_or_else(|| unsupported("`required` entries must be strings"))?;
+            let index = match properties
+                .iter()
+                .position(|(declared, _)| **declared == *name.as_bytes())
+            {
+                Some(index) => index,
+                None => {
+                    properties.push((Box::from(name.as_bytes()), additional.unwrap_or(ANY)));
+                    properties.len() - 1
+                }
+            };
+            if !required.contains(&index) {
+                required.push(index);
+            }
+        }
+        Ok(self.push(Node::Object {
+            properties,
+            required,
+            additional,
+        }))
+    }
+
+    fn array(
+        &mut self,
+        object: &'a Map<String, Value>,
+        min_items: Option<usize>,
+        max_items: Option<usize>,
+    ) -> Result<NodeId, CandleError> {
+        let mut prefix = Vec::new();
+        let tuple = match (object.get("prefixItems"), object.get("items")) {
+            (Some(prefix_items), _) => Some(prefix_items),
+            // Draft 7 tuples spell `prefixItems` as an array-valued `items`.
+            (None, Some(items @ Value::Array(_))) => Some(items),
+            _ => None,
+        };
+        if let Some(tuple) = tuple {
+            for item in tuple
+                .as_array()
+                .ok_or_else(|| unsupported("`prefixItems` must be an array"))?
+            {
+                prefix.push(self.compile(item)?);
+            }
+        }
+        let items = match object.get("items") {
+            Some(Value::Array(_)) => match object.get("additionalItems") {
+                None | Some(Value::Bool(true)) => Some(ANY),
+                Some(Value::Bool(false)) => None,
+                Some(schema) => Some(self.compile(schema)?),
+            },
+            None | Some(Value::Bool(true)) => Some(ANY),
+            Some(Value::Bool(false)) => None,
+            Some(schema) => Some(self.compile(schema)?),
+        };
+        Ok(self.push(Node::Array {
+            prefix,
+            items,
+            min_items: min_items.unwrap_or(0),
+            max_items,
+        }))
+    }
+}
+
+impl Grammar {
+    /// Compile `schema`, resolving `$ref`s against the schema itself.
+    pub(crate) fn from_schema(schema: &Value) -> Result<Self, CandleError> {
+        let mut compiler = Compiler::new(schema);
+        let root = compiler.compile(schema)?;
+        Ok(Self {
+            nodes: compiler.nodes,
+            root,
+        })
+    }
+
+    /// The Qwen3 tool-call body, `{"name": ..., "arguments": ...}`, with the
+    /// arguments held to the named tool's parameter schema. A tool whose
+    /// schema cannot be compiled keeps JSON-object arguments, the guarantee
+    /// tool calls had before constrained decoding.
+    pub(crate) fn tool_call_envelope(tools: &[&ToolDefinition]) -> Self {
+        let mut compiler = Compiler::new(&Value::Null);
+        let root = compiler.tool_call_envelopes(tools);
+        Self {
+            nodes: compiler.nodes,
+            root,
+        }
+    }
+
+    /// Mistral's tool-call body: a non-empty array of the same envelopes.
+    pub(crate) fn tool_call_batch(tools: &[&ToolDefinition]) -> Self {
+        let mut compiler = Compiler::new(&Value::Null);
+        let envelope = compiler.tool_call_envelopes(tools);
+        let root = compiler.push(Node::Array {
+            prefix: Vec::new(),
+            items: Some(envelope),
+            min_items: 1,
+            max_items: None,
+        });
+        Self {
+            nodes: compiler.nodes,
+            root,
+        }
+    }
+}
+
+impl<'a> Compiler<'a> {
+    /// One `{"name": ..., "arguments": ...}` envelope per tool, or their
+    /// alternatives.
+    fn tool_call_envelopes(&mut self, tools: &[&'a ToolDefinition]) -> NodeId {
+        let mut envelopes = Vec::new();
+        for tool in tools {
+            self.root = &tool.parameters;
+            self.refs.clear();
+            let checkpoint = self.nodes.len();
+            let arguments = self.compile(&tool.parameters).unwrap_or_else(|_| {
+                self.nodes.truncate(checkpoint);
+                ANY_OBJECT
+            });
+            let name = self
+                .literal(&Value::String(tool.name.clone()))
+                .unwrap_or(STRING);
+            envelopes.push(self.push(Node::Object {
+                properties: vec![
+                    (Box::from(&b"name"[..]), name),
+                    (Box::from(&b"arguments"[..]), arguments),
+                ],
+                required: vec![0, 1],
+                additional: None,
+            }));
+        }
+        match envelopes.as_slice() {
+            [only] => *only,
+            _ => self.push(Node::Alternatives(envelopes)),
+        }
+    }
+}
+
+impl Grammar {
+    fn advance(&self, mut stack: Stack, byte: u8, out: &mut Vec<Stack>) {
+        let Some(frame) = stack.frames.pop() else {
+            // Nothing may follow a complete document.
+            return;
+        };
+        let whitespace = matches!(byte, b' ' | b'\t' | b'\n' | b'\r');
+        if whitespace && frame.skips_whitespace() {
+            if stack.whitespace < MAX_WHITESPACE {
+                stack.whitespace += 1;
+                stack.frames.push(frame);
+                push_unique(out, stack);
+            }
+            return;
+        }
+        if !whitespace {
+            stack.whitespace = 0;
+        }
+        match frame {
+            Frame::Value(node) => self.start(stack, node, byte, 0, out),
+            Frame::Literal { node, matched } => {
+                let Some(Node::Literal(bytes)) = self.nodes.get(node) else {
+                    return;
+                };
+                if bytes.get(matched) == Some(&byte) {
+                    let matched = matched + 1;
+                    if matched < bytes.len() {
+                        stack.frames.push(Frame::Literal { node, matched });
+                    }
+                    push_unique(out, stack);
+                }
+            }
+            Frame::String {
+                node,
+                chars,
+                escape,
+            } => self.string(stack, node, chars, escape, byte, out),
+            Frame::Number {
+                integer,
+                phase,
+                length,
+            } => match phase.next(byte, integer) {
+                Some(phase) if length < MAX_NUMBER_LENGTH => {
+                    stack.frames.push(Frame::Number {
+                        integer,
+                        phase,
+                        length: length + 1,
+                    });
+                    push_unique(out, stack);
+                }
+                Some(_) => {}
+                // The number ended with the previous byte; this one belongs to
+                // whatever encloses it.
+                None if phase.is_terminal() && !stack.frames.is_empty() => {
+                    self.advance(stack, byte, out)
+                }
+                None => {}
+            },
+            Frame::Object {
+                node,
+                phase,
+                seen,
+                key,
+            } => self.object(stack, node, phase, seen, key, byte, out),
+            Frame::Array { node, phase, count } => self.array(stack, node, phase, count, byte, out),
+        }
+    }
+
+    /// Begin a value of `node` whose first byte is `byte`.
+    fn start(&self, mut stack: Stack, node: NodeId, byte: u8, depth: usize, out: &mut Vec<Stack>) {
+        if depth > MAX_DEPTH || stack.frames.len() >= MAX_DEPTH {
+            return;
+        }
+        let frame = match self.nodes.get(node) {
+            Some(Node::Alternatives(branches)) => {
+                for branch in branches {
+                    self.start(stack.clone(), *branch, byte, depth + 1, out);
+                }
+                return;
+            }
+            Some(Node::Literal(bytes)) => {
+                if bytes.first() != Some(&byte) {
+                    return;
+                }
+                if bytes.len() == 1 {
+                    push_unique(out, stack);
+                    return;
+                }
+                Frame::Literal { node, matched: 1 }
+            }
+            Some(Node::String { .. }) if byte == b'"' => Frame::String {
+                node,
+                chars: 0,
+                escape: Escape::None,
+            },
+            Some(Node::Number { integer, unsigned }) => {
+                let phase = match byte {
+                    b'-' if !unsigned => NumberPhase::Sign,
+                    b'0' => NumberPhase::Zero,
+                    b'1'..=b'9' => NumberPhase::Integer,
+                    _ => return,
+                };
+                Frame::Number {
+                    integer: *integer,
+                    phase,
+                    length: 1,
+                }
+            }
+            Some(Node::Object { .. }) if byte == b'{' => Frame::Object {
+                node,
+                phase: ObjectPhase::Open,
+                seen: Vec::new(),
+                key: Vec::new(),
+            },
+            Some(Node::Array { .. }) if byte == b'[' => Frame::Array {
+                node,
+                phase: ArrayPhase::Open,
+                count: 0,
+            },
+            _ => return,
+        };
+        stack.frames.push(frame);
+        push_unique(out, stack);
+    }
+
+    fn string(
+        &self,
+        mut stack: Stack,
+        node: NodeId,
+        chars: usize,
+        escape: Escape,
+        byte: u8,
+        out: &mut Vec<Stack>,
+    ) {
+        let (min_length, max_length) = match self.nodes.get(node) {
+            Some(Node::String {
+                min_length,
+                max_length,
+            }) => (*min_length, *max_length),
+            _ => (0, None),
+        };
+        let (chars, escape) = match escape {
+            Escape::None => match byte {
+                b'"' => {
+                    if chars >= min_length {
+                        push_unique(out, stack);
+                    }
+                    return;
+                }
+                b'\\' => (chars, Escape::Backslash),
+                0x00..=0x1f => return,
+                // UTF-8 continuation bytes extend the current character.
+                0x80..=0xbf => (chars, Escape::None),
+                _ => (chars + 1, Escape::None),
+            },
+            Escape::Backslash => match byte {
+                b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => (chars + 1, Escape::None),
+                b'u' => (chars, Escape::Unicode(4)),
+                _ => return,
+            },
+            Escape::Unicode(remaining) => {
+                if !byte.is_ascii_hexdigit() {
+                    return;
+                }
+                if remaining <= 1 {
+                    (chars + 1, Escape::None)
+                } else {
+                    (chars, Escape::Unicode(remaining - 1))
+                }
+            }
+        };
+        if max_length.is_some_and(|max_length| chars > max_length) {
+            return;
+        }
+        stack.frames.push(Frame::String {
+            node,
+            chars,
+            escape,
+        });
+        push_unique(out, stack);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn object(
+        &self,
+        mut stack: Stack,
+        node: NodeId,
+        mut phase: ObjectPhase,
+        mut seen: Vec<usize>,
+        mut key: Vec<u8>,
+        byte: u8,
+        out: &mut Vec<Stack>,
+    ) {
+        let Some(Node::Object {
+            properties,
+            required,
+            additional,
+        }) = self.nodes.get(node)
+        else {
+            return;
+        };
+        let can_add = additional.is_some() || properties.len() > seen.len();
+        let can_close = required.iter().all(|index| seen.contains(index));
+        match phase {
+            ObjectPhase::Open | ObjectPhase::Key => match byte {
+                b'"' if can_add => {
+                    phase = ObjectPhase::InKey {
+                        escape: Escape::None,
+                        escaped: false,
+                    };
+                    key.clear();
+                }
+                b'}' if phase == ObjectPhase::Open && can_close => {
+                    push_unique(out, stack);
+                    return;
+                }
+                _ => return,
+            },
+            ObjectPhase::InKey { escape, escaped } => match escape {
+                Escape::None => match byte {
+                    b'"' => {
+                        // An escaped key cannot be compared with the declared
+                        // names byte for byte, so it only counts as additional.
+                        let declared = (!escaped)
+                            .then(|| properties.iter().position(|(name, _)| **name == *key))
+                            .flatten();
+                        let value = match declared {
+                            Some(index) if seen.contains(&index) => return,
+                            Some(index) => {
+                                seen.push(index);
+                                match properties.get(index) {
+                                    Some((_, value)) => *value,
+                                    None => return,
+                                }
+                            }
+                            None => match additional {
+                                Some(value) => *value,
+                                None => return,
+                            },
+                        };
+                        key.clear();
+                        phase = ObjectPhase::Colon(value);
+                    }
+                    b'\\' if additional.is_some() => {
+                        phase = ObjectPhase::InKey {
+                            escape: Escape::Backslash,
+                            escaped: true,
+                        };
+                    }
+                    0x00..=0x1f | b'\\' => return,
+                    _ => {
+                        key.push(byte);
+                        let viable = additional.is_some()
+                            || properties.iter().enumerate().any(|(index, (name, _))| {
+                                !seen.contains(&index) && name.starts_with(&key)
+                            });
+                        if !viable {
+                            return;
+                        }
+                    }
+                },
+                Escape::Backslash => match byte {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {
+                        phase = ObjectPhase::InKey {
+                            escape: Escape::None,
+                            escaped,
+                        };
+                    }
+                    b'u' => {
+                        phase = ObjectPhase::InKey {
+                            escape: Escape::Unicode(4),
+                            escaped,
+                        };
+                    }
+                    _ => return,
+                },
+                Escape::Unicode(remaining) => {
+                    if !byte.is_ascii_hexdigit() {
+                        return;
+                    }
+                    phase = ObjectPhase::InKey {
+                        escape: if remaining <= 1 {
+                            Escape::None
+                        } else {
+                            Escape::Unicode(remaining - 1)
+                        },
+                        escaped,
+                    };
+                }
+            },
+            ObjectPhase::Colon(value) => {
+                if byte != b':' {
+                    return;
+                }
+                stack.frames.push(Frame::Object {
+                    node,
+                    phase: ObjectPhase::Next,
+                    seen,
+                    key,
+                });
+                stack.frames.push(Frame::Value(value));
+                push_unique(out, stack);
+                return;
+            }
+            ObjectPhase::Next => match byte {
+                b',' if can_add => phase = ObjectPhase::Key,
+                b'}' if can_close => {
+                    push_unique(out, stack);
+                    return;
+                }
+                _ => return,
+            },
+        }
+        stack.frames.push(Frame::Object {
+            node,
+            phase,
+            seen,
+            key,
+        });
+        push_unique(out, stack);
+    }
+
+    fn array(
+        &self,
+        mut stack: Stack,
+        node: NodeId,
+        phase: ArrayPhase,
+        count: usize,
+        byte: u8,
+        out: &mut Vec<Stack>,
+    ) {
+        let Some(Node::Array {
+            prefix,
+            items,
+            min_items,
+            max_items,
+        }) = self.nodes.get(node)
+        else {
+            return;
+        };
+        let item = prefix.get(count).copied().or(*items);
+        let can_add = item.is_some() && max_items.is_none_or(|max_items| count < max_items);
+        let can_close = count >= *min_items;
+        match (phase, byte) {
+            (ArrayPhase::Open | ArrayPhase::Next, b']') => {
+                if can_close {
+                    push_unique(out, stack);
+                }
+            }
+            (ArrayPhase::Next, b',') => {
+                if can_add {
+                    stack.frames.push(Frame::Array {
+                        node,
+                        phase: ArrayPhase::Item,
+                        count,
+                    });
+                    push_unique(out, stack);
+                }
+            }
+            (ArrayPhase::Open | ArrayPhase::Item, _) => {
+                if let (true, Some(item)) = (can_add, item) {
+                    stack.frames.push(Frame::Array {
+                        node,
+                        phase: ArrayPhase::Next,
+                        count: count + 1,
+                    });
+                    self.start(stack, item, byte, 0, out);
+                }
+            }
+            (ArrayPhase::Next, _) => {}
+        }
+    }
+}
+
+fn push_unique(out: &mut Vec<Stack>, stack: Stack) {
+    if !out.contains(&stack) {
+        out.push(stack);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Backslash,
+    Unicode(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberPhase {
+    Sign,
+    Zero,
+    Integer,
+    Point,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigits,
+}
+
+impl NumberPhase {
+    fn next(self, byte: u8, integer: bool) -> Option<Self> {
+        match (self, byte) {
+            (Self::Sign, b'0') => Some(Self::Zero),
+            (Self::Sign, b'1'..=b'9') | (Self::Integer, b'0'..=b'9') => Some(Self::Integer),
+            (Self::Zero | Self::Integer, b'.') if !integer => Some(Self::Point),
+            (Self::Point | Self::Fraction, b'0'..=b'9') => Some(Self::Fraction),
+            (Self::Zero | Self::Integer | Self::Fraction, b'e' | b'E') if !integer => {
+                Some(Self::Exponent)
+            }
+            (Self::Exponent, b'+' | b'-') => Some(Self::ExponentSign),
+            (Self::Exponent | Self::ExponentSign | Self::ExponentDigits, b'0'..=b'9') => {
+                Some(Self::ExponentDigits)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            Self::Zero | Self::Integer | Self::Fraction | Self::ExponentDigits
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectPhase {
+    /// After `{`: a key or `}`.
+    Open,
+    /// After `,`: a key.
+    Key,
+    InKey {
+        escape: Escape,
+        escaped: bool,
+    },
+    /// After a key: `:`, then a value of this node.
+    Colon(NodeId),
+    /// After a value: `,` or `}`.
+    Next,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayPhase {
+    /// After `[`: an item or `]`.
+    Open,
+    /// After `,`: an item.
+    Item,
+    /// After an item: `,` or `]`.
+    Next,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Frame {
+    /// A value of this node, not yet begun.
+    Value(NodeId),
+    Literal {
+        node: NodeId,
+        matched: usize,
+    },
+    String {
+        node: NodeId,
+        chars: usize,
+        escape: Escape,
+    },
+    Number {
+        integer: bool,
+        phase: NumberPhase,
+        length: u8,
+    },
+    Object {
+        node: NodeId,
+        phase: ObjectPhase,
+        /// Declared properties already present, as indices.
+        seen: Vec<usize>,
+        /// The key being read.
+        key: Vec<u8>,
+    },
+    Array {
+        node: NodeId,
+        phase: ArrayPhase,
+        count: usize,
+    },
+}
+
+impl Frame {
+    fn skips_whitespace(&self) -> bool {
+        match self {
+            Self::Value(_) | Self::Array { .. } => true,
+            Self::Object { phase, .. } => !matches!(phase, ObjectPhase::InKey { .. }),
+            Self::Literal { .. } | Self::String { .. } | Self::Number { .. } => false,
+        }
+    }
+}
+
+/// One parse of the bytes so far. Alternatives in the schema can leave several
+/// parses alive at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stack {
+    frames: Vec<Frame>,
+    whitespace: u8,
+}
+
+impl Stack {
+    /// Whether the document could end here.
+    fn is_complete(&self) -> bool {
+        match self.frames.as_slice() {
+            [] => true,
+            [Frame::Number { phase, .. }] => phase.is_terminal(),
+            _ => false,
+        }
+    }
+}
+
+/// Incremental matcher for one JSON document against a [`Grammar`].
+#[derive(Debug, Clone)]
+pub(crate) struct JsonMatcher {
+    grammar: Arc<Grammar>,
+    stacks: Vec<Stack>,
+}
+
+impl JsonMatcher {
+    pub(crate) fn new(grammar: Arc<Grammar>) -> Self {
+        let stacks = vec![Stack {
+            frames: vec![Frame::Value(grammar.root)],
+            whitespace: 0,
+        }];
+        Self { grammar, stacks }
+    }
+
+    fn step(&self, stacks: &[Stack], byte: u8) -> Vec<Stack> {
+        let mut out = Vec::new();
+        for stack in stacks {
+            self.grammar.advance(stack.clone(), byte, &mut out);
+        }
+        out
+    }
+
+    /// Feed `bytes`. When `release` is set the matcher stops at the first
+    /// complete document and the count of bytes it consumed is returned, so the
+    /// caller can hand the rest back to free text. `None` means the bytes do not
+    /// continue any valid document; the matcher is then left unchanged.
+    pub(crate) fn consume(&mut self, bytes: &[u8], release: bool) -> Option<usize> {
+        let mut stacks = self.stacks.clone();
+        for (index, byte) in bytes.iter().enumerate() {
+            if release && stacks.iter().any(|stack| stack.frames.is_empty()) {
+                self.stacks = stacks;
+                return Some(index);
+            }
+            stacks = self.step(&stacks, *byte);
+            if stacks.is_empty() {
+                return None;
+            }
+        }
+        self.stacks = stacks;
+        Some(bytes.len())
+    }
+
+    /// Whether the bytes so far form a complete document.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.stacks.iter().any(Stack::is_complete)
+    }
+
+    /// Every token whose bytes continue a valid document. With `release`, a
+    /// token may also run past the document's end into free text.
+    pub(crate) fn allowed_tokens(&self, vocabulary: &TokenVocabulary, release: bool) -> Vec<u32> {
+        let mut allowed = Vec::new();
+        let mut pending = vec![(0_usize, self.stacks.clone())];
+        while let Some((node, stacks)) = pending.pop() {
+            let Some(trie) = vocabulary.trie.get(node) else {
+                continue;
+            };
+            for (byte, child) in &trie.children {
+                let child = *child as usize;
+                let next = self.step(&stacks, *byte);
+                if release && next.iter().any(|stack| stack.frames.is_empty()) {
+                    vocabulary.collect_subtree(child, &mut allowed);
+                } else if !next.is_empty() {
+                    if let Some(child_node) = vocabulary.trie.get(child) {
+                        allowed.extend_from_slice(&child_node.tokens);
+                    }
+                    pending.push((child, next));
+                }
+            }
+        }
+        allowed
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: Vec<(u8, u32)>,
+    tokens: Vec<u32>,
+}
+
+/// The bytes every non-special token decodes to, indexed by token ID and as a
+/// byte trie, built once per loaded model on its first constrained request.
+#[derive(Debug)]
+pub(crate) struct TokenVocabulary {
+    trie: Vec<TrieNode>,
+    bytes: Vec<Option<Box<[u8]>>>,
+}
+
+impl TokenVocabulary {
+    pub(crate) fn new(tokenizer: &Tokenizer) -> Self {
+        let added = tokenizer.get_added_tokens_decoder();
+        let byte_level = matches!(tokenizer.get_decoder(), Some(DecoderWrapper::ByteLevel(_)));
+        let byte_level_table = byte_level_table();
+        let vocab = tokenizer.get_vocab(true);
+        let size = vocab.values().max().map_or(0, |max| *max as usize + 1);
+        let mut bytes: Vec<Option<Box<[u8]>>> = vec![None; size];
+        for (piece, id) in vocab {
+            let decoded = match added.get(&id) {
+                Some(token) if token.special => None,
+                // Added tokens are stored verbatim, never byte-level encoded.
+                Some(token) => Some(token.content.as_bytes().to_vec()),
+                None => piece_bytes(&piece, byte_level.then_some(&byte_level_table)),
+            };
+            if let (Some(slot), Some(decoded)) = (bytes.get_mut(id as usize), decoded)
+                && !decoded.is_empty()
+            {
+                *slot = Some(decoded.into_boxed_slice());
+            }
+        }
+
+        let mut trie = vec![TrieNode::default()];
+        for (id, token) in bytes.iter().enumerate() {
+            let Some(token) = token else {
+                continue;
+            };
+            let mut node = 0_usize;
+            for byte in token.iter() {
+                let existing = trie
+                    .get(node)
+                    .and_then(|trie_node| {
+                        trie_node
+                            .children
+                            .iter()
+                            .find(|(child_byte, _)| child_byte == byte)
+                    })
+                    .map(|(_, child)| *child as usize);
+                node = match existing {
+                    Some(child) => child,
+                    None => {
+                        let child = trie.len();
+                        trie.push(TrieNode::default());
+                        if let Some(parent) = trie.get_mut(node) {
+                            parent.children.push((*byte, child as u32));
+                        }
+                        child
+                    }
+                };
+            }
+            if let Some(trie_node) = trie.get_mut(node) {
+                trie_node.tokens.push(id as u32);
+            }
+        }
+        Self { trie, bytes }
+    }
+
+    pub(crate) fn token_bytes(&self, token: u32) -> Option<&[u8]> {
+        self.bytes.get(token as usize)?.as_deref()
+    }
+
+    fn collect_subtree(&self, node: usize, allowed: &mut Vec<u32>) {
+        let mut pending = vec![node];
+        while let Some(node) = pending.pop() {
+            if let Some(trie_node) = self.trie.get(node) {
+                allowed.extend_from_slice(&trie_node.tokens);
+                pending.extend(trie_node.children.iter().map(|(_, child)| *child as usize));
+            }
+        }
+    }
+}
+
+/// GPT-2's byte-level alphabet: printable Latin-1 bytes stand for themselves,
+/// the rest are shifted past U+00FF in byte order.
+fn byte_level_table() -> HashMap<char, u8> {
+    let printable = |byte: u8| matches!(byte, b'!'..=b'~' | 0xa1..=0xac | 0xae..=0xff);
+    let mut shifted = 0_u32;
+    (0_u8..=255)
+        .filter_map(|byte| {
+            if printable(byte) {
+                Some((char::from(byte), byte))
+            } else {
+                let character = char::from_u32(256 + shifted)?;
+                shifted += 1;
+                Some((character, byte))
+            }
+        })
+        .collect()
+}
+
+fn piece_bytes(piece: &str, byte_level: Option<&HashMap<char, u8>>) -> Option<Vec<u8>> {
+    // SentencePiece byte fallback spells raw bytes as `<0xHH>`.
+    if let Some(hex) = piece
+        .strip_prefix("<0x")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .filter(|hex| hex.len() == 2)
+    {
+        return u8::from_str_radix(hex, 16).ok().map(|byte| vec![byte]);
+    }
+    match byte_level {
+        Some(table) => piece
+            .chars()
+            .map(|character| table.get(&character).copied())
+            .collect(),
+        None => Some(piece.replace('\u{2581}', " ").into_bytes()),
+    }
+}
+
+#[derive(Debug)]
+enum ConstraintState {
+    /// Unconstrained text, watched for the start of a tool call. `tail` keeps
+    /// enough recent bytes to spot a marker split across tokens.
+    Free { tail: Vec<u8> },
+    /// The answer, held to the request's `output_schema`. Until a byte is
+    /// generated the model may open a tool call instead.
+    Output { matcher: JsonMatcher, started: bool },
+    /// The JSON body of a tool call.
+    ToolCall(JsonMatcher),
+}
+
+#[derive(Debug)]
+struct ToolCalls {
+    envelope: Arc<Grammar>,
+    /// The text that opens a tool call's JSON body.
+    marker: &'static str,
+    /// The tool-call opening marker, when the tokenizer has it as one token.
+    start_token: Option<u32>,
+}
+
+/// Per-request token masking for `output_schema` and Qwen3 or Mistral
+/// tool-call arguments.
+#[derive(Debug)]
+pub(crate) struct DecodingConstraint<'a> {
+    vocabulary: &'a TokenVocabulary,
+    stop_tokens: &'a HashSet<u32>,
+    tool_calls: Option<ToolCalls>,
+    state: ConstraintState,
+}
+
+impl<'a> DecodingConstraint<'a> {
+    /// The constraint a request needs, or `None` when decoding is free.
+    pub(crate) fn for_request(
+        request: &CompletionRequest,
+        loaded: &'a LoadedModel,
+    ) -> Result<Option<Self>, CandleError> {
+        let protocol = loaded.profile.definition.protocol;
+        let tools = crate::protocol::constrained_tools(request, protocol)?;
+        let output = request
+            .output_schema
+            .as_ref()
+            .map(|schema| Grammar::from_schema(schema.as_value()))
+            .transpose()?;
+        if output.is_none() && tools.is_empty() {
+            return Ok(None);
+        }
+        let tool_calls = crate::protocol::tool_call_syntax(protocol)
+            .filter(|_| !tools.is_empty())
+            .map(|syntax| ToolCalls {
+                envelope: Arc::new(if syntax.batched {
+                    Grammar::tool_call_batch(&tools)
+                } else {
+                    Grammar::tool_call_envelope(&tools)
+                }),
+                marker: syntax.marker,
+                start_token: loaded.tokenizer.token_to_id(syntax.marker),
+            });
+        let state = match output {
+            Some(grammar) => ConstraintState::Output {
+                matcher: JsonMatcher::new(Arc::new(grammar)),
+                started: false,
+            },
+            None => ConstraintState::Free { tail: Vec::new() },
+        };
+        Ok(Some(Self {
+            vocabulary: loaded
+                .vocabulary
+                .get_or_init(|| TokenVocabulary::new(&loaded.tokenizer)),
+            stop_tokens: &loaded.profile.stop_tokens,
+            tool_calls,
+            state,
+        }))
+    }
+
+    /// Mask every token the constraint forbids to negative infinity.
+    pub(crate) fn mask(&self, logits: &Tensor) -> Result<Tensor, CandleError> {
+        let allowed = match &self.state {
+            ConstraintState::Free { .. } => return Ok(logits.clone()),
+            ConstraintState::Output { matcher, started } => {
+                let mut allowed = matcher.allowed_tokens(self.vocabulary, false);
+                if matcher.is_complete() {
+                    allowed.extend(self.stop_tokens);
+                }
+                if !started {
+                    allowed.extend(
+                        self.tool_calls
+                            .as_ref()
+                            .and_then(|tool_calls| tool_calls.start_token),
+                    );
+                }
+                allowed
+            }
+            ConstraintState::ToolCall(matcher) => matcher.allowed_tokens(self.vocabulary, true),
+        };
+        let inference = |error: candle_core::Error| CandleError::Inference(error.to_string());
+        let size = logits.dim(D::Minus1).map_err(inference)?;
+        let mut mask = vec![f32::NEG_INFINITY; size];
+        let mut any_allowed = false;
+        for token in allowed {
+            if let Some(slot) = mask.get_mut(token as usize) {
+                *slot = 0.0;
+                any_allowed = true;
+            }
+        }
+        if !any_allowed {
+            return Err(CandleError::Inference(
+                "constrained decoding found no token that continues a schema-valid output"
+                    .to_string(),
+            ));
+        }
+        let mask = Tensor::from_vec(mask, size, logits.device()).map_err(inference)?;
+        logits
+            .to_dtype(DType::F32)
+            .and_then(|logits| logits.broadcast_add(&mask))
+            .map_err(inference)
+    }
+
+    /// Advance past a sampled, non-stop token.
+    pub(crate) fn accept(&mut self, token: u32) -> Result<(), CandleError> {
+        let bytes = self.vocabulary.token_bytes(token).unwrap_or_default();
+        match &mut self.state {
+            ConstraintState::Free { tail } => {
+                let Some(tool_calls) = &self.tool_calls else {
+                    return Ok(());
+                };
+                // A special-token marker decodes to no bytes, so it is
+                // recognized by ID rather than in the text.
+                if tool_calls.start_token == Some(token) {
+                    self.state = ConstraintState::ToolCall(JsonMatcher::new(Arc::clone(
+                        &tool_calls.envelope,
+                    )));
+                    return Ok(());
+                }
+                tail.extend_from_slice(bytes);
+                let marker = tool_calls.marker.as_bytes();
+                match tail
+                    .windows(marker.len())
+                    .position(|window| window == marker)
+                {
+                    Some(position) => {
+                        let rest = tail.get(position + marker.len()..).unwrap_or_default();
+                        let mut matcher = JsonMatcher::new(Arc::clone(&tool_calls.envelope));
+                        // A call whose first bytes already left the envelope
+                        // cannot be rescued; leave it to the parser to reject.
+                        self.state = match matcher.consume(rest, true) {
+                            Some(consumed) if consumed == rest.len() => {
+                                ConstraintState::ToolCall(matcher)
+                            }
+                            _ => ConstraintState::Free { tail: Vec::new() },
+                        };
+                    }
+                    None => {
+                        let keep = marker.len().saturating_sub(1);
+                        let excess = tail.len().saturating_sub(keep);
+                        tail.drain(..excess);
+                    }
+                }
+            }
+            ConstraintState::Output { matcher, started } => {
+                if !*started
+                    && let Some(tool_calls) = &self.tool_calls
+                    && tool_calls.start_token == Some(token)
+                {
+                    self.state = ConstraintState::ToolCall(JsonMatcher::new(Arc::clone(
+                        &tool_calls.envelope,
+                    )));
+                    return Ok(());
+                }
+                *started = true;
+                if matcher.consume(bytes, false).is_none() {
+                    return Err(CandleError::Inference(
+                        "a sampled token left the output schema".to_string(),
+                    ));
+                }
+            }
+            ConstraintState::ToolCall(matcher) => {
+                let consumed = matcher.consume(bytes, true).ok_or_else(|| {
+                    CandleError::Inference("a sampled token left the tool-call schema".to_string())
+                })?;
+                if matcher.is_complete() {
+                    self.state = ConstraintState::Free {
+                        tail: bytes.get(consumed..).unwrap_or_default().to_vec(),
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::indexing_slicing, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn matches(schema: Value, document: &str) -> bool {
+        let grammar = Grammar::from_schema(&schema).expect("supported schema");
+        let mut matcher = JsonMatcher::new(Arc::new(grammar));
+        matcher.consume(document.as_bytes(), false).is_some() && matcher.is_complete()
+    }
+
+    #[test]
+    fn matcher_accepts_exactly_the_documents_the_schema_allows() {
+        let person = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "maxLength": 5},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "maxItems": 2},
+                "nickname": {"type": ["string", "null"]},
+            },
+            "required": ["name", "age"],
+        });
+        for accepted in [
+            r#"{"name":"Ada","age":36}"#,
+            "{ \"age\": 0,\n  \"name\": \"\\u00e9\", \"tags\": [\"b\", \"a\"] }",
+            r#"{"name":"Ada","age":36,"nickname":null}"#,
+        ] {
+            assert!(matches(person.clone(), accepted), "{accepted}");
+        }
+        for rejected in [
+            r#"{"name":"Ada"}"#,
+            r#"{"name":"Ada","age":-1}"#,
+            r#"{"name":"Ada","age":3.5}"#,
+            r#"{"name":"Adalbert","age":1}"#,
+            r#"{"name":"Ada","age":1,"extra":true}"#,
+            r#"{"name":"Ada","age":1,"age":2}"#,
+            r#"{"name":"Ada","age":1,"tags":["c"]}"#,
+            r#"{"name":"Ada","age":1,"tags":["a","b","a"]}"#,
+            r#"{"name":"Ada","age":01}"#,
+        ] {
+            assert!(!matches(person.clone(), rejected), "{rejected}");
+        }
+    }
+
+    #[test]
+    fn references_alternatives_and_top_level_numbers_are_followed() {
+        let tree = serde_json::json!({
+            "$ref": "#/$defs/node",
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "value": {"anyOf": [{"type": "number"}, {"const": "none"}]},
+                        "children": {"type": "array", "items": {"$ref": "#/$defs/node"}},
+                    },
+                    "required": ["value"],
+                },
+            },
+        });
+        assert!(matches(
+            tree.clone(),
+            r#"{"value":1e3,"children":[{"value":"none"},{"value":-0.5,"children":[]}]}"#
+        ));
+        assert!(!matches(tree, r#"{"value":"some"}"#));
+
+        assert!(matches(serde_json::json!({"type": "number"}), "-12.5E+3"));
+        assert!(!matches(serde_json::json!({"type": "number"}), "-"));
+        assert!(matches(
+            serde_json::json!({}),
+            r#"[1,{"any":[true,null]},"x"]"#
+        ));
+
+        assert!(matches!(
+            Grammar::from_schema(&serde_json::json!({"type": "string", "pattern": "^a"})),
+            Err(CandleError::UnsupportedFeature(reason)) if reason.contains("pattern")
+        ));
+        assert!(Grammar::from_schema(&serde_json::json!({"$ref": "other.json"})).is_err());
+    }
+
+    #[test]
+    fn tool_envelope_holds_each_tool_to_its_own_arguments() {
+        let tools = [
+            ToolDefinition {
+                name: "add".to_string(),
+                description: "Add.".to_string(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": {"x": {"type": "integer"}},
+                    "required": ["x"],
+                }),
+            },
+            ToolDefinition {
+                name: "echo".to_string(),
+                description: "Echo.".to_string(),
+                parameters: serde_json::json!({"type": "object", "pattern": "unsupported"}),
+            },
+        ];
+        let grammar = Arc::new(Grammar::tool_call_envelope(
+            &tools.iter().collect::<Vec<_>>(),
+        ));
+        let accepts = |document: &str| {
+            let mut matcher = JsonMatcher::new(Arc::clone(&grammar));
+            matcher.consume(document.as_bytes(), false).is_some() && matcher.is_complete()
+        };
+        assert!(accepts(r#"{"name": "add", "arguments": {"x": 2}}"#));
+        assert!(accepts(r#"{"arguments": {"x": 2}, "name": "add"}"#));
+        assert!(!accepts(r#"{"name": "add", "arguments": {"x": "2"}}"#));
+        assert!(!accepts(r#"{"name": "sub", "arguments": {"x": 2}}"#));
+        // A schema the matcher cannot follow still gets JSON-object arguments.
+        assert!(accepts(
+            r#"{"name": "echo", "arguments": {"anything": [1]}}"#
+        ));
+        assert!(!accepts(r#"{"name": "echo", "arguments": [1]}"#));
+
+        let mut matcher = JsonMatcher::new(Arc::clone(&grammar));
+        assert_eq!(
+            matcher.consume(br#"{"name":"add","arguments":{"x":1}}"#, true),
+            Some(34)
+        );
+        let mut matcher = JsonMatcher::new(grammar);
+        assert_eq!(
+            matcher.consume(
+                b"{\"name\":\"add\",\"arguments\":{\"x\":1}}\n</tool_call>",
+                true
+            ),
+            Some(34)
+        );
+        assert!(matcher.is_complete());
+
+        let batch = Arc::new(Grammar::tool_call_batch(&tools.iter().collect::<Vec<_>>()));
+        let accepts_batch = |document: &str| {
+            let mut matcher = JsonMatcher::new(Arc::clone(&batch));
+            matcher.consume(document.as_bytes(), false).is_some() && matcher.is_complete()
+        };
+        assert!(accepts_batch(
+            r#"[{"name": "add", "arguments": {"x": 2}}, {"name": "echo", "arguments": {}}]"#
+        ));
+        assert!(!accepts_batch("[]"));
+        assert!(!accepts_batch(r#"{"name": "add", "arguments": {"x": 2}}"#));
+        assert!(!accepts_batch(
+            r#"[{"name": "add", "arguments": {"x": "2"}}]"#
+        ));
+    }
+
+    #[test]
+    fn byte_level_pieces_decode_to_their_raw_bytes() {
+        let table = byte_level_table();
+        assert_eq!(table.len(), 256);
+        assert_eq!(piece_bytes("Ġ{\"", Some(&table)), Some(b" {\"".to_vec()));
+        assert_eq!(piece_bytes("Ċ", Some(&table)), Some(b"\n".to_vec()));
+        assert_eq!(piece_bytes("<0x0A>", None), Some(b"\n".to_vec()));
+        assert_eq!(piece_bytes("\u{2581}name", None), Some(b" name".to_vec()));
+    }
+}
@@ -0,0 +1,687 @@
+//! Local BERT-family sentence embeddings behind Rig's `EmbeddingModel`.
+//!
+//! Encoder checkpoints are loaded from the same caller-supplied [`ModelData`]
+//! buffers as completion models. Inputs are tokenized, truncated to the
+//! configured sequence length, padded per batch with an attention mask, run
+//! through the encoder, pooled, and optionally L2-normalized.
+
+use std::sync::Arc;
+
+use candle_core::{D, DType, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig};
+use candle_transformers::models::nomic_bert::{
+    Config as NomicBertConfig, NomicBertModel, mean_pooling,
+};
+use rig_core::completion::Usage;
+use rig_core::embeddings::{Embedding, EmbeddingError, EmbeddingModel, EmbeddingResponse};
+use rig_core::telemetry::{ModalityOperation, instrument_modality};
+use rig_core::wasm_compat::WasmCompatSend;
+use serde::Deserialize;
+use tokenizers::{Tokenizer, TruncationParams};
+
+use crate::artifacts::{ModelData, require_nonempty};
+#[cfg(not(target_family = "wasm"))]
+use crate::runtime::{CancelOnDrop, acquire_concurrency};
+use crate::runtime::{CancellationSignal, RuntimeDevice, check_cancellation};
+use crate::types::{CandleError, PROVIDER_NAME};
+
+const DEFAULT_BATCH_SIZE: usize = 32;
+const DEFAULT_MAX_SEQUENCE_LENGTH: usize = 512;
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 1;
+const MAX_DOCUMENTS: usize = 1024;
+
+/// How per-token encoder states are reduced to one sentence vector.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pooling {
+    /// Average of the non-padding token states, as used by sentence-transformers
+    /// checkpoints such as all-MiniLM and by nomic-embed.
+    #[default]
+    Mean,
+    /// The state of the first (`[CLS]`) token, as used by bge.
+    Cls,
+}
+
+/// A cheaply cloneable, CPU-only Candle sentence-embedding model.
+///
+/// Supports BERT (`model_type: "bert"`, e.g. all-MiniLM and bge) and NomicBERT
+/// (`model_type: "nomic_bert"`) safetensors checkpoints. Task prefixes such as
+/// nomic's `search_document: ` are part of the input text and are not added
+/// here.
+#[derive(Clone)]
+pub struct CandleEmbeddingModel {
+    state: Arc<LoadedEmbeddingModel>,
+}
+
+/// Builder for loading a [`CandleEmbeddingModel`] and choosing its pooling.
+pub struct CandleEmbeddingModelBuilder {
+    data: ModelData,
+    pooling: Pooling,
+    normalize: bool,
+    batch_size: usize,
+    max_sequence_length: usize,
+    max_concurrent_requests: usize,
+}
+
+struct LoadedEmbeddingModel {
+    encoder: Encoder,
+    tokenizer: Tokenizer,
+    runtime: RuntimeDevice,
+    pad_token_id: u32,
+    ndims: usize,
+    pooling: Pooling,
+    normalize: bool,
+    batch_size: usize,
+    #[cfg(not(target_family = "wasm"))]
+    concurrency: Arc<tokio::sync::Semaphore>,
+}
+
+enum Encoder {
+    Bert(BertModel),
+    NomicBert(NomicBertModel),
+}
+
+enum EncoderConfig {
+    Bert(BertConfig),
+    NomicBert(NomicBertConfig),
+}
+
+#[derive(Deserialize)]
+struct EncoderIdentity {
+    model_type: Option<String>,
+    #[serde(default)]
+    architectures: Vec<String>,
+}
+
+impl CandleEmbeddingModel {
+    /// Loads an encoder from config, tokenizer, and one unsharded safetensors buffer.
+    pub fn from_safetensors(data: ModelData) -> Result<Self, CandleError> {
+        Self::builder(data).build()
+    }
+
+    /// Asynchronously loads an encoder outside the async executor.
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn from_safetensors_async(data: ModelData) -> Result<Self, CandleError> {
+        Self::builder(data).build_async().await
+    }
+
+    /// Starts a byte-backed embedding model builder.
+    pub fn builder(data: ModelData) -> CandleEmbeddingModelBuilder {
+        CandleEmbeddingModelBuilder {
+            data,
+            pooling: Pooling::default(),
+            normalize: true,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_sequence_length: DEFAULT_MAX_SEQUENCE_LENGTH,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+        }
+    }
+
+    /// Returns the pooling strategy applied to encoder output.
+    pub fn pooling(&self) -> Pooling {
+        self.state.pooling
+    }
+
+    fn model_type(&self) -> &'static str {
+        match self.state.encoder {
+            Encoder::Bert(_) => "bert",
+            Encoder::NomicBert(_) => "nomic_bert",
+        }
+    }
+
+    async fn infer_embeddings(
+        &self,
+        texts: Vec<String>,
+    ) -> Result<(Vec<Embedding>, u64), CandleError> {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let cancellation = CancellationSignal::default();
+            let mut cancel_on_drop = CancelOnDrop::new(cancellation.clone());
+            let permit = acquire_concurrency(Arc::clone(&self.state.concurrency)).await?;
+            let loaded = Arc::clone(&self.state);
+            let result = tokio::task::spawn_blocking(move || {
+                let result = loaded
+                    .runtime
+                    .device()
+                    .with_context(|| embed(&loaded, texts, &cancellation));
+                drop(permit);
+                result
+            })
+            .await
+            .map_err(|error| CandleError::BlockingTaskJoin(error.to_string()));
+            cancel_on_drop.disarm();
+            result?
+        }
+
+        #[cfg(target_family = "wasm")]
+        {
+            embed(&self.state, texts, &CancellationSignal)
+        }
+    }
+}
+
+impl CandleEmbeddingModelBuilder {
+    /// Sets the pooling strategy. Use the one the checkpoint was trained with.
+    pub fn pooling(mut self, pooling: Pooling) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    /// Enables or disables L2 normalization of pooled vectors. Enabled by default.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Sets how many documents are encoded in one forward pass.
+    ///
+    /// Each batch is padded to its longest member, so larger batches trade
+    /// memory for throughput. The default is 32.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the token length inputs are truncated to.
+    ///
+    /// The default is 512; the checkpoint's position limit always applies.
+    pub fn max_sequence_length(mut self, max_sequence_length: usize) -> Self {
+        self.max_sequence_length = max_sequence_length;
+        self
+    }
+
+    /// Sets the maximum number of native embedding requests admitted concurrently.
+    ///
+    /// The default is one to avoid CPU oversubscription. WASM inference is
+    /// synchronous and does not use this limit.
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    /// Validates all artifacts and loads encoder tensors onto the CPU.
+    pub fn build(self) -> Result<CandleEmbeddingModel, CandleError> {
+        if self.max_concurrent_requests == 0 {
+            return Err(CandleError::InvalidConcurrencyLimit);
+        }
+        if self.batch_size == 0 {
+            return Err(CandleError::Configuration(
+                "embedding batch size must be at least one".to_string(),
+            ));
+        }
+        if self.max_sequence_length == 0 {
+            return Err(CandleError::Configuration(
+                "embedding max sequence length must be at least one".to_string(),
+            ));
+        }
+        Ok(CandleEmbeddingModel {
+            state: Arc::new(load_embedding_model(self)?),
+        })
+    }
+
+    /// Validates and loads encoder artifacts on Tokio's blocking thread pool.
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn build_async(self) -> Result<CandleEmbeddingModel, CandleError> {
+        tokio::task::spawn_blocking(move || self.build())
+            .await
+            .map_err(|error| CandleError::BlockingTaskJoin(error.to_string()))?
+    }
+}
+
+impl EncoderConfig {
+    fn parse(config_bytes: &[u8]) -> Result<Self, CandleError> {
+        let identity: EncoderIdentity = serde_json::from_slice(config_bytes)
+            .map_err(|error| CandleError::Configuration(error.to_string()))?;
+        let is_bert_architecture = identity
+            .architectures
+            .iter()
+            .any(|architecture| architecture.starts_with("Bert"));
+        let bert = || {
+            serde_json::from_slice(config_bytes)
+                .map(Self::Bert)
+                .map_err(|error| CandleError::Configuration(error.to_string()))
+        };
+        match identity.model_type.as_deref() {
+            Some("bert") => bert(),
+            None if is_bert_architecture => bert(),
+            Some("nomic_bert") => serde_json::from_slice(config_bytes)
+                .map(Self::NomicBert)
+                .map_err(|error| CandleError::Configuration(error.to_string())),
+            other => Err(CandleError::UnsupportedModelFamily(format!(
+                "embedding checkpoints must be BERT or NomicBERT encoders, found model_type {other:?}"
+            ))),
+        }
+    }
+
+    fn hidden_size(&self) -> usize {
+        match self {
+            Self::Bert(config) => config.hidden_size,
+            Self::NomicBert(config) => config.n_embd,
+        }
+    }
+
+    fn vocab_size(&self) -> usize {
+        match self {
+            Self::Bert(config) => config.vocab_size,
+            Self::NomicBert(config) => config.vocab_size,
+        }
+    }
+
+    fn max_positions(&self) -> usize {
+        match self {
+            Self::Bert(config) => config.max_position_embeddings,
+            Self::NomicBert(config) => config.n_positions,
+        }
+    }
+
+    fn pad_token_id(&self) -> Result<u32, CandleError> {
+        match self {
+            Self::Bert(config) => u32::try_from(config.pad_token_id).map_err(|_| {
+                CandleError::InvalidConfigurationValue {
+                    field: "pad_token_id",
+                    reason: "does not fit in a token id".to_string(),
+                }
+            }),
+            // NomicBERT configs carry no pad id; its BERT vocabulary pads with 0.
+            Self::NomicBert(_) => Ok(0),
+        }
+    }
+
+    fn load(&self, builder: VarBuilder) -> Result<Encoder, CandleError> {
+        match self {
+            Self::Bert(config) => BertModel::load(builder, config).map(Encoder::Bert),
+            Self::NomicBert(config) => {
+                NomicBertModel::load(builder, config).map(Encoder::NomicBert)
+            }
+        }
+        .map_err(|error| CandleError::ModelLoading(error.to_string()))
+    }
+}
+
+fn load_embedding_model(
+    options: CandleEmbeddingModelBuilder,
+) -> Result<LoadedEmbeddingModel, CandleError> {
+    let data = options.data;
+    require_nonempty(&data.config, "config")?;
+    require_nonempty(&data.tokenizer, "tokenizer")?;
+    require_nonempty(&data.weights, "weights")?;
+
+    let config = EncoderConfig::parse(&data.config)?;
+    if config.hidden_size() == 0 {
+        return Err(CandleError::InvalidConfigurationValue {
+            field: "hidden_size",
+            reason: "must be nonzero".to_string(),
+        });
+    }
+    let pad_token_id = config.pad_token_id()?;
+
+    let mut tokenizer = Tokenizer::from_bytes(&data.tokenizer)
+        .map_err(|error| CandleError::TokenizerLoading(error.to_string()))?;
+    let tokenizer_vocab = tokenizer.get_vocab_size(true);
+    if tokenizer_vocab > config.vocab_size() {
+        return Err(CandleError::InvalidConfigurationValue {
+            field: "vocab_size",
+            reason: format!(
+                "tokenizer has {tokenizer_vocab} tokens but the encoder embeds only {}",
+                config.vocab_size()
+            ),
+        });
+    }
+    // Batches are padded here, not by the tokenizer, so a checkpoint's own
+    // padding settings cannot pad every input to a fixed width.
+    tokenizer
+        .with_padding(None)
+        .with_truncation(Some(TruncationParams {
+            max_length: options.max_sequence_length.min(config.max_positions()),
+            ..TruncationParams::default()
+        }))
+        .map_err(|error| CandleError::TokenizerLoading(error.to_string()))?;
+
+    let runtime = RuntimeDevice::cpu();
+    let load = || {
+        let builder = VarBuilder::from_buffered_safetensors(
+            data.weights,
+            runtime.cache_dtype(),
+            runtime.device(),
+        )
+        .map_err(|error| CandleError::InvalidCheckpoint(error.to_string()))?;
+        config.load(builder)
+    };
+    #[cfg(not(target_family = "wasm"))]
+    let encoder = runtime.device().with_context(load)?;
+    #[cfg(target_family = "wasm")]
+    let encoder = load()?;
+
+    Ok(LoadedEmbeddingModel {
+        encoder,
+        tokenizer,
+        runtime,
+        pad_token_id,
+        ndims: config.hidden_size(),
+        pooling: options.pooling,
+        normalize: options.normalize,
+        batch_size: options.batch_size,
+        #[cfg(not(target_family = "wasm"))]
+        concurrency: Arc::new(tokio::sync::Semaphore::new(options.max_concurrent_requests)),
+    })
+}
+
+/// Embed every text in batches, returning the embeddings in input order and
+/// the number of non-padding tokens encoded.
+fn embed(
+    loaded: &LoadedEmbeddingModel,
+    texts: Vec<String>,
+    cancellation: &CancellationSignal,
+) -> Result<(Vec<Embedding>, u64), CandleError> {
+    let mut vectors = Vec::with_capacity(texts.len());
+    let mut input_tokens = 0_u64;
+    for batch in texts.chunks(loaded.batch_size) {
+        check_cancellation(cancellation)?;
+        let (batch_vectors, batch_tokens) = embed_batch(loaded, batch)?;
+        vectors.extend(batch_vectors);
+        input_tokens = input_tokens.saturating_add(batch_tokens);
+    }
+    if vectors.len() != texts.len() {
+        return Err(CandleError::Inference(format!(
+            "encoder produced {} embeddings for {} documents",
+            vectors.len(),
+            texts.len()
+        )));
+    }
+    let embeddings = texts
+        .into_iter()
+        .zip(vectors)
+        .map(|(document, vec)| Embedding { document, vec })
+        .collect();
+    Ok((embeddings, input_tokens))
+}
+
+fn embed_batch(
+    loaded: &LoadedEmbeddingModel,
+    texts: &[String],
+) -> Result<(Vec<Vec<f64>>, u64), CandleError> {
+    let encodings = loaded
+        .tokenizer
+        .encode_batch(texts.iter().map(String::as_str).collect::<Vec<_>>(), true)
+        .map_err(|error| CandleError::TokenizerEncoding(error.to_string()))?;
+    // An input that tokenizes to nothing still gets one (masked) position so
+    // the batch tensor is never empty.
+    let width = encodings
+        .iter()
+        .map(|encoding| encoding.get_ids().len())
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let mut input_ids = Vec::with_capacity(texts.len() * width);
+    let mut type_ids = Vec::with_capacity(texts.len() * width);
+    let mut attention_mask = Vec::with_capacity(texts.len() * width);
+    let mut input_tokens = 0_u64;
+    for encoding in &encodings {
+        let length = encoding.get_ids().len();
+        let row_end = input_ids.len() + width;
+        input_ids.extend_from_slice(encoding.get_ids());
+        input_ids.resize(row_end, loaded.pad_token_id);
+        type_ids.extend_from_slice(encoding.get_type_ids());
+        type_ids.resize(row_end, 0);
+        attention_mask.resize(attention_mask.len() + length, 1_u32);
+        attention_mask.resize(row_end, 0);
+        input_tokens = input_tokens.saturating_add(length as u64);
+    }
+
+    let device = loaded.runtime.device();
+    let shape = (encodings.len(), width);
+    let input_ids = Tensor::from_vec(input_ids, shape, device).map_err(inference)?;
+    let type_ids = Tensor::from_vec(type_ids, shape, device).map_err(inference)?;
+    let attention_mask = Tensor::from_vec(attention_mask, shape, device).map_err(inference)?;
+    let hidden = match &loaded.encoder {
+        Encoder::Bert(model) => model.forward(&input_ids, &type_ids, Some(&attention_mask)),
+        Encoder::NomicBert(model) => {
+            model.forward(&input_ids, Some(&type_ids), Some(&attention_mask))
+        }
+    }
+    .map_err(inference)?;
+    let pooled = match loaded.pooling {
+        Pooling::Mean => mean_pooling(&hidden, &attention_mask),
+        Pooling::Cls => hidden.narrow(1, 0, 1).and_then(|first| first.squeeze(1)),
+    }
+    .map_err(inference)?;
+    let pooled = if loaded.normalize {
+        l2_normalize(&pooled).map_err(inference)?
+    } else {
+        pooled
+    };
+    let vectors = pooled
+        .to_dtype(DType::F64)
+        .and_then(|pooled| pooled.to_vec2::<f64>())
+        .map_err(inference)?;
+    Ok((vectors, input_tokens))
+}
+
+/// Scale each row to unit length, leaving an all-zero row at zero rather than NaN.
+fn l2_normalize(x: &Tensor) -> candle_core::Result<Tensor> {
+    let norm = x
+        .sqr()?
+        .sum_keepdim(D::Minus1)?
+        .sqrt()?
+        .clamp(f32::EPSILON, f32::MAX)?;
+    x.broadcast_div(&norm)
+}
+
+fn inference(error: candle_core::Error) -> CandleError {
+    CandleError::Inference(error.to_string())
+}
+
+impl EmbeddingModel for CandleEmbeddingModel {
+    fn max_documents(&self) -> usize {
+        MAX_DOCUMENTS
+    }
+
+    fn ndims(&self) -> usize {
+        self.state.ndims
+    }
+
+    async fn embed_texts_response(
+        &self,
+        texts: impl IntoIterator<Item = String> + WasmCompatSend,
+    ) -> Result<EmbeddingResponse, EmbeddingError> {
+        let texts: Vec<String> = texts.into_iter().collect();
+        instrument_modality(
+            PROVIDER_NAME,
+            self.model_type(),
+            ModalityOperation::Embeddings,
+            async {
+                let (embeddings, input_tokens) = self.infer_embeddings(texts).await?;
+                // Inference is in-process: there is no provider payload or
+                // request id, so `raw` stays `Null`.
+                Ok(EmbeddingResponse::new(embeddings, PROVIDER_NAME)
+                    .with_model(self.model_type())
+                    .with_usage(Usage {
+                        input_tokens,
+                        total_tokens: input_tokens,
+                        ..Usage::new()
+                    }))
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::indexing_slicing, clippy::panic)]
+mod tests {
+    use candle_core::Device;
+    use candle_nn::VarMap;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+    use super::*;
+
+    const TINY_BERT_CONFIG: &[u8] = br#"{
+        "model_type": "bert",
+        "vocab_size": 8,
+        "hidden_size": 4,
+        "num_hidden_layers": 1,
+        "num_attention_heads": 1,
+        "intermediate_size": 8,
+        "hidden_act": "gelu",
+        "hidden_dropout_prob": 0.0,
+        "max_position_embeddings": 16,
+        "type_vocab_size": 2,
+        "initializer_range": 0.02,
+        "layer_norm_eps": 0.000001,
+        "pad_token_id": 0
+    }"#;
+
+    fn tiny_tokenizer() -> Vec<u8> {
+        let vocab = [
+            ("[PAD]", 0),
+            ("[UNK]", 1),
+            ("red", 2),
+            ("green", 3),
+            ("blue", 4),
+            ("apple", 5),
+            ("sky", 6),
+            ("grass", 7),
+        ]
+        .into_iter()
+        .map(|(token, id)| (token.to_string(), id))
+        .collect();
+        let model = WordLevel::builder()
+            .vocab(vocab)
+            .unk_token("[UNK]".to_string())
+            .build()
+            .expect("word-level model");
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer
+            .to_string(false)
+            .expect("tokenizer json")
+            .into_bytes()
+    }
+
+    /// A randomly initialized tiny BERT checkpoint with every tensor Candle loads.
+    fn tiny_bert_weights() -> Vec<u8> {
+        let config: BertConfig = serde_json::from_slice(TINY_BERT_CONFIG).expect("config");
+        let varmap = VarMap::new();
+        let builder = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+        BertModel::load(builder, &config).expect("initialize tiny bert");
+        let tensors = varmap.data().lock().expect("varmap lock");
+        safetensors::tensor::serialize(
+            tensors
+                .iter()
+                .map(|(name, var)| (name.clone(), var.as_tensor().clone())),
+            None,
+        )
+        .expect("serialize tiny bert")
+    }
+
+    fn tiny_bert_data() -> ModelData {
+        ModelData {
+            config: TINY_BERT_CONFIG.to_vec(),
+            tokenizer: tiny_tokenizer(),
+            weights: tiny_bert_weights(),
+        }
+    }
+
+    fn norm(vector: &[f64]) -> f64 {
+        vector.iter().map(|value| value * value).sum::<f64>().sqrt()
+    }
+
+    fn texts() -> Vec<String> {
+        ["red apple", "blue sky", "green grass grass", "sky"]
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn embeds_in_order_with_normalized_vectors_independent_of_batching() {
+        let data = tiny_bert_data();
+        let batched = CandleEmbeddingModel::builder(ModelData {
+            config: data.config.clone(),
+            tokenizer: data.tokenizer.clone(),
+            weights: data.weights.clone(),
+        })
+        .batch_size(3)
+        .build()
+        .expect("batched model");
+        let single = CandleEmbeddingModel::builder(data)
+            .batch_size(1)
+            .build()
+            .expect("single-document model");
+        assert_eq!(batched.ndims(), 4);
+        assert_eq!(batched.pooling(), Pooling::Mean);
+
+        let response = batched
+            .embed_texts_response(texts())
+            .await
+            .expect("batched embeddings");
+        assert_eq!(response.provider, PROVIDER_NAME);
+        assert_eq!(response.model.as_deref(), Some("bert"));
+        assert_eq!(response.usage.input_tokens, 8);
+        let documents: Vec<_> = response
+            .embeddings
+            .iter()
+            .map(|embedding| embedding.document.as_str())
+            .collect();
+        assert_eq!(
+            documents,
+            ["red apple", "blue sky", "green grass grass", "sky"]
+        );
+
+        let unbatched = single.embed_texts(texts()).await.expect("unbatched");
+        for (batched, unbatched) in response.embeddings.iter().zip(&unbatched) {
+            assert_eq!(batched.vec.len(), 4);
+            assert!((norm(&batched.vec) - 1.0).abs() < 1e-5);
+            // Padding is masked out, so batch composition never changes a vector.
+            for (left, right) in batched.vec.iter().zip(&unbatched.vec) {
+                assert!((left - right).abs() < 1e-4, "{left} != {right}");
+            }
+        }
+        assert_ne!(response.embeddings[0].vec, response.embeddings[1].vec);
+    }
+
+    #[tokio::test]
+    async fn cls_pooling_without_normalization_reads_the_first_token_state() {
+        let model = CandleEmbeddingModel::builder(tiny_bert_data())
+            .pooling(Pooling::Cls)
+            .normalize(false)
+            .build()
+            .expect("cls model");
+        let embeddings = model
+            .embed_texts(["sky".to_string(), "sky grass".to_string()])
+            .await
+            .expect("embeddings");
+        assert_eq!(model.pooling(), Pooling::Cls);
+        assert!(embeddings.iter().all(|embedding| embedding.vec.len() == 4));
+        assert!((norm(&embeddings[0].vec) - 1.0).abs() > 1e-3);
+    }
+
+    #[test]
+    fn rejects_invalid_settings_and_non_encoder_checkpoints() {
+        assert!(matches!(
+            CandleEmbeddingModel::builder(tiny_bert_data())
+                .batch_size(0)
+                .build(),
+            Err(CandleError::Configuration(_))
+        ));
+        assert!(matches!(
+            CandleEmbeddingModel::builder(tiny_bert_data())
+                .max_concurrent_requests(0)
+                .build(),
+            Err(CandleError::InvalidConcurrencyLimit)
+        ));
+        let mut data = tiny_bert_data();
+        data.config = br#"{"model_type": "llama", "architectures": ["LlamaForCausalLM"]}"#.to_vec();
+        assert!(matches!(
+            CandleEmbeddingModel::from_safetensors(data),
+            Err(CandleError::UnsupportedModelFamily(_))
+        ));
+        let mut data = tiny_bert_data();
+        data.weights = b"not safetensors".to_vec();
+        assert!(matches!(
+            CandleEmbeddingModel::from_safetensors(data),
+            Err(CandleError::InvalidCheckpoint(_))
+        ));
+    }
+}
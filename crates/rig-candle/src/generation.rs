@@ -229,7 +229,9 @@ pub(crate) fn next_cache_position(
 use candle_core::Tensor;
 use candle_transformers::generation::LogitsProcessor;
 use candle_transformers::models::llama::{Cache, Llama};
+use candle_transformers::models::quantized_gemma3::ModelWeights as QuantizedGemma3;
 use candle_transformers::models::quantized_llama::ModelWeights as QuantizedLlama;
+use candle_transformers::models::quantized_phi3::ModelWeights as QuantizedPhi3;
 use candle_transformers::models::quantized_qwen3::ModelWeights as QuantizedQwen3;
 use candle_transformers::utils::apply_repeat_penalty;
 use rig_core::completion::{AssistantContent, CompletionResponse};
@@ -241,8 +243,9 @@ use tokenizers::{
 };
 use web_time::{Duration, Instant};
 
+use crate::constraint::DecodingConstraint;
 use crate::loader::{LoadedModel, LoadedWeights};
-use crate::profile::ModelFamily;
+use crate::prefix_cache::KvState;
 use crate::runtime::{CancellationSignal, check_cancellation};
 use crate::types::{CandleCompletionResponse, FinishReason};
 
@@ -268,16 +271,20 @@ pub(crate) struct IncrementalTextDecoder<'a> {
     token_ids: Vec<u32>,
     text: String,
     flushed: bool,
+    skip_special_tokens: bool,
 }
 
 impl<'a> IncrementalTextDecoder<'a> {
-    pub(crate) fn new(tokenizer: &'a Tokenizer) -> Self {
+    /// `skip_special_tokens` is false for protocols whose generated control
+    /// tokens, such as Mistral's `[TOOL_CALLS]`, carry meaning for the parser.
+    pub(crate) fn new(tokenizer: &'a Tokenizer, skip_special_tokens: bool) -> Self {
         Self {
             tokenizer,
-            stream: tokenizer.decode_stream(true),
+            stream: tokenizer.decode_stream(skip_special_tokens),
             token_ids: Vec::new(),
             text: String::new(),
             flushed: false,
+            skip_special_tokens,
         }
     }
 
@@ -300,7 +307,7 @@ impl<'a> IncrementalTextDecoder<'a> {
         self.flushed = true;
         let fully_decoded = self
             .tokenizer
-            .decode(&self.token_ids, true)
+            .decode(&self.token_ids, self.skip_special_tokens)
             .map_err(|error| CandleError::TokenizerDecoding(error.to_string()))?;
         let suffix = fully_decoded.strip_prefix(&self.text).ok_or_else(|| {
             CandleError::TokenizerDecoding(
@@ -329,7 +336,12 @@ struct GenerationSession<'a> {
     weights: SessionWeights<'a>,
     logits: Tensor,
     processor: LogitsProcessor,
+    constraint: Option<DecodingConstraint<'a>>,
     prompt_tokens: usize,
+    cached_prompt_tokens: usize,
+    /// Tokens whose keys and values are in `weights`; a sampled token is
+    /// only forwarded if generation continues past it.
+    forwarded_tokens: usize,
     max_tokens: usize,
     effective_max_tokens: u64,
     all_tokens: Vec<u32>,
@@ -345,9 +357,65 @@ enum SessionWeights<'a> {
     Safetensors { model: &'a Llama, cache: Cache },
     QuantizedLlama(QuantizedLlama),
     QuantizedQwen3(QuantizedQwen3),
+    QuantizedPhi3(QuantizedPhi3),
+    QuantizedGemma3(QuantizedGemma3),
 }
 
-impl SessionWeights<'_> {
+impl<'a> SessionWeights<'a> {
+    fn fresh(loaded: &'a LoadedModel) -> Result<Self, CandleError> {
+        Ok(match &loaded.model {
+            LoadedWeights::Safetensors { model, config } => SessionWeights::Safetensors {
+                model,
+                cache: Cache::new(
+                    true,
+                    loaded.runtime.cache_dtype(),
+                    config,
+                    loaded.runtime.device(),
+                )
+                .map_err(|error| CandleError::Inference(error.to_string()))?,
+            },
+            LoadedWeights::QuantizedLlama(model) => SessionWeights::QuantizedLlama(model.clone()),
+            LoadedWeights::QuantizedQwen3(model) => SessionWeights::QuantizedQwen3(model.clone()),
+            // The loaded weights are never forwarded, so the clone's KV
+            // cache is still unallocated and owned by this session alone.
+            LoadedWeights::QuantizedPhi3(model) => SessionWeights::QuantizedPhi3(model.clone()),
+            LoadedWeights::QuantizedGemma3(model) => SessionWeights::QuantizedGemma3(model.clone()),
+        })
+    }
+
+    fn resume(loaded: &'a LoadedModel, state: KvState) -> Result<Self, CandleError> {
+        match (&loaded.model, state) {
+            (LoadedWeights::Safetensors { model, .. }, KvState::Safetensors(cache)) => {
+                Ok(SessionWeights::Safetensors { model, cache })
+            }
+            (LoadedWeights::QuantizedLlama(_), KvState::QuantizedLlama(model)) => {
+                Ok(SessionWeights::QuantizedLlama(model))
+            }
+            (LoadedWeights::QuantizedQwen3(_), KvState::QuantizedQwen3(model)) => {
+                Ok(SessionWeights::QuantizedQwen3(model))
+            }
+            (LoadedWeights::QuantizedGemma3(_), KvState::QuantizedGemma3(model)) => {
+                Ok(SessionWeights::QuantizedGemma3(model))
+            }
+            _ => Err(CandleError::Inference(
+                "cached KV state does not belong to the loaded backend".to_string(),
+            )),
+        }
+    }
+
+    /// Shareable KV state, or `None` for a backend whose cache is written in
+    /// place: Candle's Phi-3 preallocates its cache and updates it through
+    /// shared storage, so a clone would be overwritten by later decoding.
+    fn snapshot(&self) -> Option<KvState> {
+        match self {
+            Self::Safetensors { cache, .. } => Some(KvState::Safetensors(cache.clone())),
+            Self::QuantizedLlama(model) => Some(KvState::QuantizedLlama(model.clone())),
+            Self::QuantizedQwen3(model) => Some(KvState::QuantizedQwen3(model.clone())),
+            Self::QuantizedPhi3(_) => None,
+            Self::QuantizedGemma3(model) => Some(KvState::QuantizedGemma3(model.clone())),
+        }
+    }
+
     fn forward(&mut self, input: &Tensor, position: usize) -> Result<Tensor, CandleError> {
         match self {
             Self::Safetensors { model, cache } => model
@@ -359,9 +427,33 @@ impl SessionWeights<'_> {
             Self::QuantizedQwen3(model) => model
                 .forward(input, position)
                 .and_then(|tensor| tensor.squeeze(0)),
+            Self::QuantizedPhi3(model) => model
+                .forward(input, position)
+                .and_then(|tensor| tensor.squeeze(0)),
+            Self::QuantizedGemma3(model) => Self::forward_gemma3(model, input, position),
         }
         .map_err(|error| CandleError::Inference(error.to_string()))
     }
+
+    /// Candle's Gemma 3 masks every cached position when several tokens are
+    /// forwarded after an offset, so a resumed prefill feeds them one by one.
+    fn forward_gemma3(
+        model: &mut QuantizedGemma3,
+        input: &Tensor,
+        position: usize,
+    ) -> candle_core::Result<Tensor> {
+        let len = input.dim(1)?;
+        if position == 0 || len == 1 {
+            return model.forward(input, position)?.squeeze(0);
+        }
+        let mut logits = None;
+        for offset in 0..len {
+            logits = Some(model.forward(&input.narrow(1, offset, 1)?, position + offset)?);
+        }
+        logits
+            .ok_or_else(|| candle_core::Error::Msg("empty Gemma 3 input".to_string()))?
+            .squeeze(0)
+    }
 }
 
 impl<'a> GenerationSession<'a> {
@@ -373,6 +465,7 @@ impl<'a> GenerationSession<'a> {
         let prompt = crate::protocol::render_prompt(request, loaded.profile.definition.protocol)?;
         let generation =
             effective_generation(request, &loaded.generation, loaded.profile.vocab_size)?;
+        let constraint = DecodingConstraint::for_request(request, loaded)?;
         let encoding = loaded
             .tokenizer
             .encode(prompt, false)
@@ -396,31 +489,50 @@ impl<'a> GenerationSession<'a> {
 
         check_cancellation(cancellation)?;
         let started = Instant::now();
-        let device = loaded.runtime.device();
-        let input = Tensor::new(prompt_ids, device)
-            .and_then(|tensor| tensor.unsqueeze(0))
-            .map_err(|error| CandleError::Inference(error.to_string()))?;
-        let mut weights = match &loaded.model {
-            LoadedWeights::Safetensors { model, config } => SessionWeights::Safetensors {
-                model,
-                cache: Cache::new(true, loaded.runtime.cache_dtype(), config, device)
-                    .map_err(|error| CandleError::Inference(error.to_string()))?,
-            },
-            LoadedWeights::QuantizedLlama(model) => SessionWeights::QuantizedLlama(model.clone()),
-            LoadedWeights::QuantizedQwen3(model) => SessionWeights::QuantizedQwen3(model.clone()),
+        // Resume from the longest retained prefix and prefill only the rest.
+        // A snapshot covering the whole prompt already holds its logits.
+        let (weights, cached_prompt_tokens, logits) = match loaded.prefix_cache.lookup(prompt_ids) {
+            Some(hit) => {
+                let mut weights = SessionWeights::resume(loaded, hit.state)?;
+                let suffix = prompt_ids.get(hit.cached_tokens..).unwrap_or_default();
+                let logits = if suffix.is_empty() {
+                    hit.logits
+                } else {
+                    check_cancellation(cancellation)?;
+                    weights.forward(&token_tensor(suffix, loaded)?, hit.cached_tokens)?
+                };
+                (weights, hit.cached_tokens, logits)
+            }
+            None => {
+                let mut weights = SessionWeights::fresh(loaded)?;
+                check_cancellation(cancellation)?;
+                let logits = weights.forward(&token_tensor(prompt_ids, loaded)?, 0)?;
+                (weights, 0, logits)
+            }
         };
-        check_cancellation(cancellation)?;
-        let logits = weights.forward(&input, 0)?;
+        if cached_prompt_tokens < prompt_ids.len()
+            && let Some(state) = weights.snapshot()
+        {
+            loaded
+                .prefix_cache
+                .insert(prompt_ids, state, logits.clone());
+        }
         let prefill_duration = started.elapsed();
         let processor = LogitsProcessor::from_sampling(generation.seed, sampling(&generation));
 
         Ok(Self {
             loaded,
             processor,
-            decoder: IncrementalTextDecoder::new(&loaded.tokenizer),
+            constraint,
+            decoder: IncrementalTextDecoder::new(
+                &loaded.tokenizer,
+                !crate::protocol::decodes_control_tokens(loaded.profile.definition.protocol),
+            ),
             weights,
             logits,
             prompt_tokens: prompt_ids.len(),
+            cached_prompt_tokens,
+            forwarded_tokens: prompt_ids.len(),
             max_tokens,
             effective_max_tokens,
             all_tokens: prompt_ids.to_vec(),
@@ -442,15 +554,20 @@ impl<'a> GenerationSession<'a> {
             return self.finish();
         }
 
+        // `self.logits` stays the model's raw output so a prefix snapshot
+        // taken at the end of generation is valid for any later request.
+        let mut logits = self.logits.clone();
         if self.generation.repeat_penalty != 1.0 && self.generation.repeat_last_n > 0 {
             let recent = recent_tokens(&self.all_tokens, self.generation.repeat_last_n);
-            self.logits =
-                apply_repeat_penalty(&self.logits, self.generation.repeat_penalty, recent)
-                    .map_err(|error| CandleError::Inference(error.to_string()))?;
+            logits = apply_repeat_penalty(&logits, self.generation.repeat_penalty, recent)
+                .map_err(|error| CandleError::Inference(error.to_string()))?;
+        }
+        if let Some(constraint) = &self.constraint {
+            logits = constraint.mask(&logits)?;
         }
         let token = self
             .processor
-            .sample(&self.logits)
+            .sample(&logits)
             .map_err(|error| CandleError::Inference(error.to_string()))?;
         self.generated_tokens = self.generated_tokens.checked_add(1).ok_or_else(|| {
             CandleError::Inference("generated token count overflowed usize".to_string())
@@ -464,6 +581,9 @@ impl<'a> GenerationSession<'a> {
             return Ok(GenerationStep::Token(None));
         }
 
+        if let Some(constraint) = &mut self.constraint {
+            constraint.accept(token)?;
+        }
         self.all_tokens.push(token);
         let fragment = self.decoder.push(token)?;
 
@@ -473,10 +593,9 @@ impl<'a> GenerationSession<'a> {
             check_cancellation(self.cancellation)?;
             let generated_index = self.generated_tokens.saturating_sub(1);
             let position = next_cache_position(self.prompt_tokens, generated_index)?;
-            let next = Tensor::new(&[token], self.loaded.runtime.device())
-                .and_then(|tensor| tensor.unsqueeze(0))
-                .map_err(|error| CandleError::Inference(error.to_string()))?;
+            let next = token_tensor(&[token], self.loaded)?;
             self.logits = self.weights.forward(&next, position)?;
+            self.forwarded_tokens = self.forwarded_tokens.saturating_add(1);
         }
 
         Ok(GenerationStep::Token(fragment))
@@ -496,6 +615,18 @@ impl<'a> GenerationSession<'a> {
         let generated_tokens = u64::try_from(self.generated_tokens).map_err(|_| {
             CandleError::Inference("generated token count does not fit in u64".to_string())
         })?;
+        let cached_prompt_tokens = u64::try_from(self.cached_prompt_tokens).map_err(|_| {
+            CandleError::Inference("cached prompt token count does not fit in u64".to_string())
+        })?;
+        if self.forwarded_tokens > self.prompt_tokens
+            && let Some(forwarded) = self.all_tokens.get(..self.forwarded_tokens)
+            && let Some(state) = self.weights.snapshot()
+        {
+            // The next turn's prompt usually repeats this one plus the reply.
+            self.loaded
+                .prefix_cache
+                .insert(forwarded, state, self.logits.clone());
+        }
         let generation_duration = self
             .started
             .elapsed()
@@ -508,6 +639,7 @@ impl<'a> GenerationSession<'a> {
         Ok(GenerationStep::Finished(CandleCompletionResponse {
             text: self.decoder.text().to_string(),
             prompt_tokens,
+            cached_prompt_tokens,
             generated_tokens,
             requested_max_tokens: self.generation.max_tokens,
             effective_max_tokens: self.effective_max_tokens,
@@ -524,6 +656,12 @@ impl<'a> GenerationSession<'a> {
     }
 }
 
+fn token_tensor(tokens: &[u32], loaded: &LoadedModel) -> Result<Tensor, CandleError> {
+    Tensor::new(tokens, loaded.runtime.device())
+        .and_then(|tensor| tensor.unsqueeze(0))
+        .map_err(|error| CandleError::Inference(error.to_string()))
+}
+
 fn duration_millis(duration: Duration) -> u64 {
     u64::try_from(duration.as_millis()).map_or(u64::MAX, |value| value)
 }
@@ -608,15 +746,15 @@ pub(crate) fn stream_generate(
     cancellation: &CancellationSignal,
     mut emit: impl FnMut(RawStreamingChoice<CandleCompletionResponse>) -> Result<(), CandleError>,
 ) -> Result<CandleCompletionResponse, CandleError> {
-    if loaded.profile.definition.protocol != ModelFamily::Qwen3 {
+    if crate::protocol::streams_incrementally(request, loaded.profile.definition.protocol) {
         return generate(loaded, request, cancellation, |fragment| {
             emit(RawStreamingChoice::Message(fragment))
         });
     }
 
-    // Qwen tool syntax can straddle arbitrary token boundaries. Buffer one
-    // model turn so control markup is never leaked as assistant text; complete
-    // tool calls are still delivered through Rig's streaming agent driver.
+    // Tool syntax can straddle arbitrary token boundaries. Buffer one model
+    // turn so control markup is never leaked as assistant text; complete tool
+    // calls are still delivered through Rig's streaming agent driver.
     let mut response = generate(loaded, request, cancellation, |_| Ok(()))?;
     let parsed = crate::protocol::parse_assistant(
         &response.text,
@@ -667,7 +805,7 @@ pub(crate) fn stream_generate(
             }
             AssistantContent::Image(_) => {
                 return Err(CandleError::Inference(
-                    "text-only Qwen output parser produced image content".to_string(),
+                    "text-only tool-call output parser produced image content".to_string(),
                 ));
             }
         }
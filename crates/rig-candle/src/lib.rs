@@ -1,9 +1,12 @@
 #![doc = include_str!("../README.md")]
 
 mod artifacts;
+mod constraint;
+mod embedding;
 mod generation;
 mod loader;
 mod model;
+mod prefix_cache;
 mod profile;
 mod protocol;
 mod runtime;
@@ -11,6 +14,7 @@ mod types;
 mod validation;
 
 pub use artifacts::{GgufModelData, ModelArtifacts, ModelData};
+pub use embedding::{CandleEmbeddingModel, CandleEmbeddingModelBuilder, Pooling};
 pub use generation::GenerationConfig;
 pub use model::{CandleModel, CandleModelBuilder, LlamaModel, stream_from_events};
 pub use profile::{ConversationProtocol, ModelArchitecture, ModelFamily, Quantization};
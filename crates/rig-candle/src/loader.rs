@@ -3,12 +3,15 @@
 use std::collections::HashSet;
 #[cfg(not(target_family = "wasm"))]
 use std::sync::Arc;
+use std::sync::OnceLock;
 
 use candle_core::Device;
 use candle_core::quantized::gguf_file;
 use candle_nn::VarBuilder;
 use candle_transformers::models::llama::{Config, Llama, LlamaConfig};
+use candle_transformers::models::quantized_gemma3::ModelWeights as QuantizedGemma3;
 use candle_transformers::models::quantized_llama::ModelWeights as QuantizedLlama;
+use candle_transformers::models::quantized_phi3::ModelWeights as QuantizedPhi3;
 use candle_transformers::models::quantized_qwen3::ModelWeights as QuantizedQwen3;
 use tokenizers::Tokenizer;
 
@@ -16,20 +19,23 @@ use crate::CandleError;
 #[cfg(test)]
 use crate::artifacts::ModelData;
 use crate::artifacts::{GgufModelData, ModelArtifacts, require_nonempty};
+use crate::constraint::TokenVocabulary;
 use crate::generation::GenerationConfig;
-#[cfg(target_family = "wasm")]
-use crate::profile::ModelArchitecture;
+use crate::prefix_cache::PrefixCache;
 use crate::profile::{
-    ArtifactFormat, LoaderBackend, ModelFamily, ValidatedProfile, definition_for,
+    ArtifactFormat, END_OF_TEXT, LoaderBackend, ModelFamily, ValidatedProfile, definition_for,
     validate_identity, validate_tokenizer_requirements,
 };
 use crate::runtime::RuntimeDevice;
 #[cfg(all(test, not(target_family = "wasm")))]
 use crate::runtime::TestControl;
 use crate::validation::{
-    ModelIdentity, Qwen3Config, detect_model_family, metadata_usize, resolve_stop_tokens,
-    validate_checkpoint, validate_family_config, validate_gguf_metadata, validate_gguf_tensors,
-    validate_model_config, validate_qwen3_config, validate_qwen3_gguf_metadata,
+    Gemma3Config, ModelIdentity, Phi3Config, Qwen3Config, declared_model_family,
+    detect_model_family, metadata_usize, resolve_stop_tokens, validate_checkpoint,
+    validate_family_config, validate_gemma3_config, validate_gemma3_gguf_metadata,
+    validate_gemma3_gguf_tensors, validate_gguf_metadata, validate_gguf_tensors,
+    validate_model_config, validate_phi3_config, validate_phi3_gguf_metadata,
+    validate_phi3_gguf_tensors, validate_qwen3_config, validate_qwen3_gguf_metadata,
     validate_qwen3_gguf_tensors, validate_tokenizer,
 };
 
@@ -39,6 +45,10 @@ pub(crate) struct LoadedModel {
     pub(crate) tokenizer: Tokenizer,
     pub(crate) profile: ValidatedProfile,
     pub(crate) generation: GenerationConfig,
+    /// Token bytes for constrained decoding, built on first use.
+    pub(crate) vocabulary: OnceLock<TokenVocabulary>,
+    /// KV state retained for requests that share a prompt prefix.
+    pub(crate) prefix_cache: PrefixCache,
     #[cfg(not(target_family = "wasm"))]
     pub(crate) concurrency: Arc<tokio::sync::Semaphore>,
     #[cfg(all(test, not(target_family = "wasm")))]
@@ -49,6 +59,8 @@ pub(crate) enum LoadedWeights {
     Safetensors { model: Llama, config: Config },
     QuantizedLlama(QuantizedLlama),
     QuantizedQwen3(QuantizedQwen3),
+    QuantizedPhi3(QuantizedPhi3),
+    QuantizedGemma3(QuantizedGemma3),
 }
 
 struct PreparedModel {
@@ -56,6 +68,8 @@ struct PreparedModel {
     tokenizer: Tokenizer,
     llama_config: Option<Config>,
     qwen3_config: Option<Qwen3Config>,
+    phi3_config: Option<Phi3Config>,
+    gemma3_config: Option<Gemma3Config>,
 }
 
 pub(crate) fn load_model_with_family(
@@ -63,6 +77,7 @@ pub(crate) fn load_model_with_family(
     selected_family: Option<ModelFamily>,
     generation: GenerationConfig,
     _max_concurrent_requests: usize,
+    prefix_cache_tokens: usize,
 ) -> Result<LoadedModel, CandleError> {
     let data = match artifacts {
         ModelArtifacts::Safetensors(data) => data,
@@ -76,6 +91,7 @@ pub(crate) fn load_model_with_family(
                 selected_family,
                 generation,
                 _max_concurrent_requests,
+                prefix_cache_tokens,
             );
         }
     };
@@ -115,6 +131,8 @@ pub(crate) fn load_model_with_family(
         tokenizer: prepared.tokenizer,
         profile: prepared.profile,
         generation,
+        vocabulary: OnceLock::new(),
+        prefix_cache: PrefixCache::new(prefix_cache_tokens),
         #[cfg(not(target_family = "wasm"))]
         concurrency: Arc::new(tokio::sync::Semaphore::new(_max_concurrent_requests)),
         #[cfg(all(test, not(target_family = "wasm")))]
@@ -127,6 +145,7 @@ pub(crate) fn load_gguf_model(
     selected_family: Option<ModelFamily>,
     generation: GenerationConfig,
     _max_concurrent_requests: usize,
+    prefix_cache_tokens: usize,
 ) -> Result<LoadedModel, CandleError> {
     require_nonempty(data.config, "config")?;
     require_nonempty(data.tokenizer, "tokenizer")?;
@@ -138,11 +157,11 @@ pub(crate) fn load_gguf_model(
         ArtifactFormat::Gguf,
     )?;
     #[cfg(target_family = "wasm")]
-    if prepared.profile.definition.architecture == ModelArchitecture::Qwen3 {
-        return Err(CandleError::UnsupportedModelFamily(
-            "the validated Qwen3-4B profile is native-only because its runtime memory exceeds wasm32 linear-memory capacity; use SmolLM2 for WASM"
-                .to_string(),
-        ));
+    if prepared.profile.definition.native_only {
+        return Err(CandleError::UnsupportedModelFamily(format!(
+            "the validated {} profile is native-only because its runtime memory exceeds wasm32 linear-memory capacity; use SmolLM2 for WASM",
+            prepared.profile.definition.name
+        )));
     }
     let runtime = RuntimeDevice::cpu();
     let load = || load_gguf(data.weights, &prepared, runtime.device());
@@ -156,6 +175,8 @@ pub(crate) fn load_gguf_model(
         tokenizer: prepared.tokenizer,
         profile: prepared.profile,
         generation,
+        vocabulary: OnceLock::new(),
+        prefix_cache: PrefixCache::new(prefix_cache_tokens),
         #[cfg(not(target_family = "wasm"))]
         concurrency: Arc::new(tokio::sync::Semaphore::new(_max_concurrent_requests)),
         #[cfg(all(test, not(target_family = "wasm")))]
@@ -173,57 +194,31 @@ fn prepare_model(
         .map_err(|error| CandleError::Configuration(error.to_string()))?;
     let tokenizer = Tokenizer::from_bytes(tokenizer_bytes)
         .map_err(|error| CandleError::TokenizerLoading(error.to_string()))?;
-    let is_qwen3 = identity.model_type.as_deref() == Some("qwen3")
-        || identity
-            .architectures
-            .iter()
-            .any(|architecture| architecture == "Qwen3ForCausalLM");
-    if is_qwen3 {
-        let config: Qwen3Config = serde_json::from_slice(config_bytes)
-            .map_err(|error| CandleError::Configuration(error.to_string()))?;
-        let detected_family = ModelFamily::Qwen3;
-        if let Some(selected) = selected_family
-            && selected != detected_family
-        {
-            return Err(CandleError::ModelFamilyMismatch {
-                selected,
-                detected: detected_family,
-            });
+    match declared_model_family(&identity) {
+        Some(ModelFamily::Qwen3) => {
+            prepare_qwen3(config_bytes, tokenizer, selected_family, artifact_format)
         }
-        let definition = definition_for(detected_family, artifact_format)?;
-        validate_qwen3_config(&config, definition)?;
-        validate_identity(
-            definition,
-            Some(config.model_type.as_str()),
-            &config.architectures,
-        )?;
-        validate_tokenizer_requirements(
-            definition,
-            &tokenizer,
-            config.vocab_size,
-            Some(config.bos_token_id),
-            &[config.eos_token_id],
-        )?;
-        let mut stop_tokens = HashSet::new();
-        stop_tokens.insert(config.eos_token_id);
-        return Ok(PreparedModel {
-            profile: ValidatedProfile::new(
-                definition,
-                config.vocab_size,
-                config.max_position_embeddings,
-                stop_tokens,
-            )?,
+        Some(ModelFamily::Phi3) => {
+            prepare_phi3(config_bytes, tokenizer, selected_family, artifact_format)
+        }
+        Some(ModelFamily::Gemma3) => {
+            prepare_gemma3(config_bytes, tokenizer, selected_family, artifact_format)
+        }
+        declared => prepare_llama(
+            config_bytes,
+            &identity,
             tokenizer,
-            llama_config: None,
-            qwen3_config: Some(config),
-        });
+            declared,
+            selected_family,
+            artifact_format,
+        ),
     }
+}
 
-    let llama_config: LlamaConfig = serde_json::from_slice(config_bytes)
-        .map_err(|error| CandleError::Configuration(error.to_string()))?;
-    let config = llama_config.into_config(false);
-    validate_model_config(&config)?;
-    let detected_family = detect_model_family(&tokenizer)?;
+fn require_selected_family(
+    selected_family: Option<ModelFamily>,
+    detected_family: ModelFamily,
+) -> Result<(), CandleError> {
     if let Some(selected) = selected_family
         && selected != detected_family
     {
@@ -232,6 +227,150 @@ fn prepare_model(
             detected: detected_family,
         });
     }
+    Ok(())
+}
+
+fn prepare_qwen3(
+    config_bytes: &[u8],
+    tokenizer: Tokenizer,
+    selected_family: Option<ModelFamily>,
+    artifact_format: ArtifactFormat,
+) -> Result<PreparedModel, CandleError> {
+    let config: Qwen3Config = serde_json::from_slice(config_bytes)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    let detected_family = ModelFamily::Qwen3;
+    require_selected_family(selected_family, detected_family)?;
+    let definition = definition_for(detected_family, artifact_format)?;
+    validate_qwen3_config(&config, definition)?;
+    validate_identity(
+        definition,
+        Some(config.model_type.as_str()),
+        &config.architectures,
+    )?;
+    validate_tokenizer_requirements(
+        definition,
+        &tokenizer,
+        config.vocab_size,
+        Some(config.bos_token_id),
+        &[config.eos_token_id],
+    )?;
+    let mut stop_tokens = HashSet::new();
+    stop_tokens.insert(config.eos_token_id);
+    Ok(PreparedModel {
+        profile: ValidatedProfile::new(
+            definition,
+            config.vocab_size,
+            config.max_position_embeddings,
+            stop_tokens,
+        )?,
+        tokenizer,
+        llama_config: None,
+        qwen3_config: Some(config),
+        phi3_config: None,
+        gemma3_config: None,
+    })
+}
+
+fn prepare_phi3(
+    config_bytes: &[u8],
+    tokenizer: Tokenizer,
+    selected_family: Option<ModelFamily>,
+    artifact_format: ArtifactFormat,
+) -> Result<PreparedModel, CandleError> {
+    let config: Phi3Config = serde_json::from_slice(config_bytes)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    let detected_family = ModelFamily::Phi3;
+    require_selected_family(selected_family, detected_family)?;
+    let definition = definition_for(detected_family, artifact_format)?;
+    validate_phi3_config(&config, definition)?;
+    // config.json names the sequence end, not the `<|end|>` turn end that
+    // the profile stops on, so it is checked against its own token.
+    validate_tokenizer_requirements(
+        definition,
+        &tokenizer,
+        config.vocab_size,
+        Some(config.bos_token_id),
+        &[],
+    )?;
+    if tokenizer.token_to_id(END_OF_TEXT) != Some(config.eos_token_id) {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "eos_token_id",
+            reason: format!("configured EOS ID does not match '{END_OF_TEXT}'"),
+        });
+    }
+    let end_id =
+        tokenizer
+            .token_to_id(definition.end_token)
+            .ok_or(CandleError::MissingSpecialToken {
+                token: definition.end_token,
+            })?;
+    Ok(PreparedModel {
+        profile: ValidatedProfile::new(
+            definition,
+            config.vocab_size,
+            config.max_position_embeddings,
+            HashSet::from([config.eos_token_id, end_id]),
+        )?,
+        tokenizer,
+        llama_config: None,
+        qwen3_config: None,
+        phi3_config: Some(config),
+        gemma3_config: None,
+    })
+}
+
+fn prepare_gemma3(
+    config_bytes: &[u8],
+    tokenizer: Tokenizer,
+    selected_family: Option<ModelFamily>,
+    artifact_format: ArtifactFormat,
+) -> Result<PreparedModel, CandleError> {
+    let config: Gemma3Config = serde_json::from_slice(config_bytes)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    let detected_family = ModelFamily::Gemma3;
+    require_selected_family(selected_family, detected_family)?;
+    let definition = definition_for(detected_family, artifact_format)?;
+    validate_gemma3_config(&config, definition)?;
+    let eos_tokens = config.eos_token_ids();
+    validate_tokenizer_requirements(
+        definition,
+        &tokenizer,
+        config.vocab_size,
+        Some(config.bos_token_id),
+        &eos_tokens,
+    )?;
+    Ok(PreparedModel {
+        profile: ValidatedProfile::new(
+            definition,
+            config.vocab_size,
+            config.max_position_embeddings,
+            eos_tokens.into_iter().collect(),
+        )?,
+        tokenizer,
+        llama_config: None,
+        qwen3_config: None,
+        phi3_config: None,
+        gemma3_config: Some(config),
+    })
+}
+
+fn prepare_llama(
+    config_bytes: &[u8],
+    identity: &ModelIdentity,
+    tokenizer: Tokenizer,
+    declared_family: Option<ModelFamily>,
+    selected_family: Option<ModelFamily>,
+    artifact_format: ArtifactFormat,
+) -> Result<PreparedModel, CandleError> {
+    let llama_config: LlamaConfig = serde_json::from_slice(config_bytes)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    let config = llama_config.into_config(false);
+    validate_model_config(&config)?;
+    let detected_family = match declared_family {
+        Some(family) => family,
+        None => detect_model_family(&tokenizer)?,
+    };
+    require_selected_family(selected_family, detected_family)?;
     let definition = definition_for(detected_family, artifact_format)?;
     validate_identity(
         definition,
@@ -251,6 +390,8 @@ fn prepare_model(
         tokenizer,
         llama_config: Some(config),
         qwen3_config: None,
+        phi3_config: None,
+        gemma3_config: None,
     })
 }
 
@@ -265,6 +406,7 @@ pub(crate) fn load_model(
         None,
         generation,
         max_concurrent_requests,
+        crate::model::DEFAULT_PREFIX_CACHE_TOKENS,
     )
 }
 
@@ -277,7 +419,13 @@ fn load_gguf(
     let content = gguf_file::Content::read(&mut reader)
         .map_err(|error| CandleError::InvalidQuantizedCheckpoint(error.to_string()))?;
     let definition = prepared.profile.definition;
-    let expected_architecture = definition.config_identity.model_type;
+    let requirements = definition.gguf.as_ref().ok_or_else(|| {
+        CandleError::UnsupportedModelFamily(format!(
+            "{} does not support GGUF artifacts",
+            definition.name
+        ))
+    })?;
+    let expected_architecture = requirements.architecture;
     match content.metadata.get("general.architecture") {
         Some(gguf_file::Value::String(architecture)) if architecture == expected_architecture => {}
         Some(value) => {
@@ -291,12 +439,6 @@ fn load_gguf(
             ));
         }
     }
-    let requirements = definition.gguf.as_ref().ok_or_else(|| {
-        CandleError::UnsupportedModelFamily(format!(
-            "{} does not support GGUF artifacts",
-            definition.name
-        ))
-    })?;
     match content.metadata.get("general.file_type") {
         // Q4_K_M legitimately uses auxiliary F32/Q5/Q6/Q8 encodings for
         // selected tensors; per-tensor validation below enforces that mix.
@@ -346,6 +488,30 @@ fn load_gguf(
                 .map(LoadedWeights::QuantizedQwen3)
                 .map_err(|error| CandleError::ModelLoading(error.to_string()))
         }
+        LoaderBackend::Phi3Gguf => {
+            let config = prepared.phi3_config.as_ref().ok_or_else(|| {
+                CandleError::Configuration(
+                    "prepared Phi-3 GGUF omitted its Phi-3 configuration".to_string(),
+                )
+            })?;
+            validate_phi3_gguf_metadata(&content, config, &prepared.tokenizer, definition)?;
+            validate_phi3_gguf_tensors(&content, config, definition)?;
+            QuantizedPhi3::from_gguf(false, content, &mut reader, device)
+                .map(LoadedWeights::QuantizedPhi3)
+                .map_err(|error| CandleError::ModelLoading(error.to_string()))
+        }
+        LoaderBackend::Gemma3Gguf => {
+            let config = prepared.gemma3_config.as_ref().ok_or_else(|| {
+                CandleError::Configuration(
+                    "prepared Gemma 3 GGUF omitted its Gemma 3 configuration".to_string(),
+                )
+            })?;
+            validate_gemma3_gguf_metadata(&content, config, &prepared.tokenizer, definition)?;
+            validate_gemma3_gguf_tensors(&content, config, definition)?;
+            QuantizedGemma3::from_gguf(content, &mut reader, device)
+                .map(LoadedWeights::QuantizedGemma3)
+                .map_err(|error| CandleError::ModelLoading(error.to_string()))
+        }
         LoaderBackend::LlamaSafetensors => Err(CandleError::UnsupportedModelFamily(
             "a safetensors profile cannot be loaded from GGUF artifacts".to_string(),
         )),
@@ -1,4 +1,5 @@
-//! Local, CPU-only Llama-compatible and Qwen3 inference for Rig, backed by Candle.
+//! Local, CPU-only Llama-compatible, Qwen3, Mistral, Phi-3, and Gemma 3
+//! inference for Rig, backed by Candle.
 //!
 //! Models are loaded entirely from caller-provided owned or borrowed byte
 //! buffers. This crate performs no filesystem or network access. On
@@ -30,18 +31,26 @@
 //!
 //! The validated profiles are unsharded Llama 3 safetensors,
 //! SmolLM2-360M-Instruct Q4_K_M GGUF, and (on native targets) the official
-//! Qwen3-4B Q4_K_M GGUF. Conversation rendering is explicit; tokenizer-provided
+//! Qwen3-4B, Mistral-7B-Instruct-v0.3, Phi-3-mini-4k-instruct, and Gemma 3 1B
+//! instruct Q4_K_M GGUFs. Conversation rendering is explicit; tokenizer-provided
 //! templates are validated where necessary but never executed.
 //!
 //! Qwen3 supports Rig function definitions, all portable `ToolChoice` modes,
 //! assistant tool-call history, correlated text/JSON tool results, buffered
 //! agent runs, and streaming agent runs. Qwen control markup is buffered for
 //! one model turn before complete tool calls are emitted, so partial XML never
-//! leaks as assistant text. Tool arguments are checked for JSON object syntax;
-//! the registered Rig tool remains responsible for typed/schema validation.
-//! Direct `CompletionRequest::output_schema` is rejected because decoding is
-//! not grammar constrained. Agent `OutputMode::Tool` is supported through Rig's
-//! synthetic final-result tool.
+//! leaks as assistant text. Mistral supports the same through its native
+//! `[TOOL_CALLS]` protocol; Phi-3 and Gemma 3 are text-only. Tool-call
+//! arguments are decoded under a token mask built from the called tool's
+//! parameter schema.
+//!
+//! `CompletionRequest::output_schema` is enforced the same way: every sampling
+//! step masks the tokens that cannot continue a schema-valid document, and the
+//! schema is shown to the model in the system prompt. This backs extractors and
+//! agent `OutputMode::Native` as well as `OutputMode::Tool`. Schemas using
+//! keywords the mask cannot follow (`pattern`, `not`, `if`, ...) are rejected
+//! with `CandleError::UnsupportedFeature`; numeric bounds other than
+//! non-negativity, `multipleOf`, `uniqueItems`, and `format` are not masked.
 //!
 //! Request `max_tokens` and `temperature` override builder defaults. The
 //! Candle-specific `additional_params` keys are `top_k`, `top_p`, `seed`,
@@ -58,6 +67,11 @@
 //! boundary. WASM does not use native synchronization or threads and collects its
 //! synchronously generated events before exposing them as a compatible stream.
 //!
+//! Each model instance retains the KV state of recent prompts and replies, up to
+//! [`CandleModelBuilder::prefix_cache_tokens`], and a request whose prompt starts
+//! with a retained token sequence prefills only the remainder. Reused tokens are
+//! reported as `Usage::cached_input_tokens`.
+//!
 //! Multimodal content, accelerators, shards, arbitrary tokenizer chat templates,
 //! provider-hosted tools, and in-crate downloads are unsupported.
 
@@ -101,6 +115,7 @@ use crate::types::*;
 use crate::validation::*;
 
 const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 1;
+pub(crate) const DEFAULT_PREFIX_CACHE_TOKENS: usize = 4096;
 #[cfg(not(target_family = "wasm"))]
 const STREAM_CHANNEL_CAPACITY: usize = 8;
 
@@ -119,6 +134,7 @@ pub struct CandleModelBuilder<'a> {
     family: Option<ModelFamily>,
     generation: GenerationConfig,
     max_concurrent_requests: usize,
+    prefix_cache_tokens: usize,
 }
 
 enum ModelSource<'a> {
@@ -163,6 +179,7 @@ impl CandleModel {
             family: None,
             generation: GenerationConfig::default(),
             max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            prefix_cache_tokens: DEFAULT_PREFIX_CACHE_TOKENS,
         }
     }
 
@@ -177,6 +194,7 @@ impl CandleModel {
             family: None,
             generation: GenerationConfig::default(),
             max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            prefix_cache_tokens: DEFAULT_PREFIX_CACHE_TOKENS,
         }
     }
 
@@ -260,6 +278,20 @@ impl<'a> CandleModelBuilder<'a> {
         self
     }
 
+    /// Sets how many tokens of KV state are kept for reuse across requests.
+    ///
+    /// A request whose prompt starts with the tokens of an earlier prompt, or
+    /// of an earlier prompt plus its generated reply, resumes from that state
+    /// and prefills only the rest; the reused count is reported as
+    /// `Usage::cached_input_tokens`. Each retained token costs
+    /// `2 * layers * kv_heads * head_dim * 4` bytes (about 80 KiB for
+    /// SmolLM2-360M, 288 KiB for Qwen3-4B). The default is 4096 tokens; zero
+    /// disables reuse so every request starts from an empty cache.
+    pub fn prefix_cache_tokens(mut self, prefix_cache_tokens: usize) -> Self {
+        self.prefix_cache_tokens = prefix_cache_tokens;
+        self
+    }
+
     /// Validates all artifacts and loads model tensors onto the CPU.
     pub fn build(self) -> Result<CandleModel, CandleError> {
         validate_generation(&self.generation, None)?;
@@ -272,12 +304,14 @@ impl<'a> CandleModelBuilder<'a> {
                 self.family,
                 self.generation,
                 self.max_concurrent_requests,
+                self.prefix_cache_tokens,
             )?,
             ModelSource::BorrowedGguf(data) => load_gguf_model(
                 data,
                 self.family,
                 self.generation,
                 self.max_concurrent_requests,
+                self.prefix_cache_tokens,
             )?,
         };
         Ok(CandleModel {
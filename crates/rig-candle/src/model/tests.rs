@@ -10,6 +10,7 @@ use safetensors::tensor::{Dtype, View, serialize};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use tokenizers::decoders::byte_fallback::ByteFallback;
+use tokenizers::decoders::fuse::Fuse;
 use tokenizers::models::bpe::{BPE, Vocab};
 use tokenizers::models::wordlevel::WordLevel;
 use tokenizers::normalizers::unicode::NFC;
@@ -835,9 +836,12 @@ fn loaded_model_works_with_agent_builder() -> Result<(), Box<dyn std::error::Err
 #[tokio::test(flavor = "current_thread")]
 async fn buffered_and_streaming_generation_are_equivalent()
 -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Without prefix reuse both paths start from an empty cache, so their
+    // usage matches exactly.
     let model = LlamaModel::builder(model_data()?)
         .temperature(0.0)
         .max_tokens(3)
+        .prefix_cache_tokens(0)
         .build()?;
     let completion_request = request(vec![Message::user("hello")]);
     let buffered = model.raw_completion(completion_request.clone()).await?;
@@ -859,6 +863,101 @@ async fn buffered_and_streaming_generation_are_equivalent()
     Ok(())
 }
 
+#[cfg(not(target_family = "wasm"))]
+#[tokio::test(flavor = "current_thread")]
+async fn later_requests_reuse_the_kv_cache_of_a_shared_prompt_prefix()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let model = LlamaModel::builder(model_data()?)
+        .temperature(0.0)
+        .max_tokens(3)
+        .build()?;
+    let uncached = LlamaModel::builder(model_data()?)
+        .temperature(0.0)
+        .max_tokens(3)
+        .prefix_cache_tokens(0)
+        .build()?;
+    let first_turn = request(vec![Message::user("hello")]);
+
+    let first = model.raw_completion(first_turn.clone()).await?;
+    assert_eq!(first.cached_prompt_tokens, 0);
+    // The prompt and prompt-plus-reply states are both retained.
+    assert_eq!(
+        model.state.prefix_cache.retained_tokens(),
+        [
+            first.prompt_tokens,
+            first.prompt_tokens + first.generated_tokens - 1
+        ]
+        .map(|tokens| tokens as usize)
+    );
+
+    // An identical prompt needs no prefill at all.
+    let repeated = model.completion(first_turn.clone()).await?;
+    assert_eq!(repeated.usage.cached_input_tokens, first.prompt_tokens);
+    assert_eq!(repeated.usage.input_tokens, first.prompt_tokens);
+
+    // A follow-up turn prefills only what follows the shared prefix and
+    // generates what a cold cache would.
+    let follow_up = request(vec![
+        Message::user("hello"),
+        Message::assistant(first.text.clone()),
+        Message::user("hello"),
+    ]);
+    let warm = model.raw_completion(follow_up.clone()).await?;
+    let cold = uncached.raw_completion(follow_up).await?;
+    assert!(warm.cached_prompt_tokens >= first.prompt_tokens);
+    assert!(warm.cached_prompt_tokens < warm.prompt_tokens);
+    assert_eq!(cold.cached_prompt_tokens, 0);
+    assert_eq!(warm.text, cold.text);
+    assert_eq!(warm.prompt_tokens, cold.prompt_tokens);
+    assert!(uncached.state.prefix_cache.retained_tokens().is_empty());
+    Ok(())
+}
+
+#[test]
+fn prefix_cache_evicts_least_recently_used_snapshots_beyond_its_budget()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use crate::prefix_cache::{KvState, PrefixCache};
+
+    let loaded = load_model(model_data()?, GenerationConfig::default(), 1)?;
+    let LoadedWeights::Safetensors { config, .. } = &loaded.model else {
+        return Err("the tiny checkpoint loads as safetensors".into());
+    };
+    let state = KvState::Safetensors(candle_transformers::models::llama::Cache::new(
+        true,
+        loaded.runtime.cache_dtype(),
+        config,
+        loaded.runtime.device(),
+    )?);
+    let logits = candle_core::Tensor::zeros(8, candle_core::DType::F32, loaded.runtime.device())?;
+    let cache = PrefixCache::new(5);
+
+    cache.insert(&[1, 2], state.clone(), logits.clone());
+    cache.insert(&[1, 2, 3], state.clone(), logits.clone());
+    assert_eq!(cache.retained_tokens(), [2, 3]);
+    assert_eq!(
+        cache.lookup(&[1, 2, 3, 4]).map(|hit| hit.cached_tokens),
+        Some(3)
+    );
+    assert_eq!(
+        cache.lookup(&[1, 2, 9]).map(|hit| hit.cached_tokens),
+        Some(2)
+    );
+    assert!(cache.lookup(&[2, 1]).is_none());
+
+    // `[1, 2, 3]` is now the least recently used and makes room.
+    cache.insert(&[7, 8, 9], state.clone(), logits.clone());
+    assert_eq!(cache.retained_tokens(), [2, 3]);
+    assert_eq!(
+        cache.lookup(&[1, 2, 3]).map(|hit| hit.cached_tokens),
+        Some(2)
+    );
+    // Snapshots longer than the whole budget are never retained.
+    cache.insert(&[1, 2, 3, 4, 5, 6], state.clone(), logits.clone());
+    assert_eq!(cache.retained_tokens(), [2, 3]);
+    assert!(PrefixCache::new(0).lookup(&[1, 2]).is_none());
+    Ok(())
+}
+
 #[cfg(not(target_family = "wasm"))]
 #[tokio::test(flavor = "current_thread")]
 async fn streaming_reports_eos_and_excludes_the_stop_token()
@@ -878,6 +977,89 @@ async fn streaming_reports_eos_and_excludes_the_stop_token()
     Ok(())
 }
 
+/// The tiny Llama 3 vocabulary with `[TOOL_CALLS]` as token zero, which
+/// zero-weight greedy decoding picks first, and one token spelling a whole
+/// Mistral call batch.
+#[cfg(not(target_family = "wasm"))]
+fn tiny_mistral_tool_tokenizer() -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let vocab = [
+        (crate::profile::TOOL_CALLS.to_string(), 0),
+        (END_OF_TURN.to_string(), 1),
+        (BEGIN_OF_TEXT.to_string(), 2),
+        ("<eos>".to_string(), 3),
+        (START_HEADER.to_string(), 4),
+        (END_HEADER.to_string(), 5),
+        ("<unk>".to_string(), 6),
+        (r#" [{"name": "ping", "arguments": {}}]"#.to_string(), 7),
+    ]
+    .into_iter()
+    .collect();
+    let model = WordLevel::builder()
+        .vocab(vocab)
+        .unk_token("<unk>".to_string())
+        .build()?;
+    let mut tokenizer = Tokenizer::new(model);
+    tokenizer.with_decoder(Some(Fuse::new()));
+    tokenizer.add_special_tokens(&[
+        AddedToken::from(crate::profile::TOOL_CALLS, true),
+        AddedToken::from(END_OF_TURN, true),
+        AddedToken::from(BEGIN_OF_TEXT, true),
+        AddedToken::from(START_HEADER, true),
+        AddedToken::from(END_HEADER, true),
+    ]);
+    Ok(tokenizer.to_string(false)?.into_bytes())
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[tokio::test(flavor = "current_thread")]
+async fn mistral_streaming_emits_parsed_tool_calls_instead_of_control_text()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = ModelData {
+        tokenizer: tiny_mistral_tool_tokenizer()?,
+        ..model_data()?
+    };
+    let generation = GenerationConfig {
+        temperature: 0.0,
+        max_tokens: 2,
+        ..GenerationConfig::default()
+    };
+    let mut loaded = load_model(data, generation, 1)?;
+    loaded.profile = crate::profile::ValidatedProfile::new(
+        definition_for(ModelFamily::Mistral, ArtifactFormat::Gguf)?,
+        8,
+        128,
+        std::collections::HashSet::from([3]),
+    )?;
+    let model = LlamaModel {
+        state: Arc::new(loaded),
+    };
+
+    let mut tool_request = request(vec![Message::user("ping")]);
+    tool_request.tools = vec![ToolDefinition {
+        name: "ping".to_string(),
+        description: "Ping.".to_string(),
+        parameters: serde_json::json!({"type": "object", "properties": {}}),
+    }];
+    let mut stream = model.raw_stream(tool_request).await?;
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    let mut final_response = None;
+    while let Some(item) = stream.next().await {
+        match item? {
+            RawStreamingChoice::Message(fragment) => text.push_str(&fragment),
+            RawStreamingChoice::ToolCall(call) => calls.push((call.name, call.arguments)),
+            RawStreamingChoice::FinalResponse(raw) => final_response = Some(raw),
+            _ => {}
+        }
+    }
+    // The `[TOOL_CALLS]` marker and its JSON never reach the text stream.
+    assert!(text.is_empty(), "{text:?}");
+    assert_eq!(calls, [("ping".to_string(), serde_json::json!({}))]);
+    let raw = final_response.ok_or("stream did not emit a final response")?;
+    assert_eq!(raw.generated_tokens, 2);
+    Ok(())
+}
+
 #[cfg(not(target_family = "wasm"))]
 #[tokio::test(flavor = "current_thread")]
 async fn streaming_clamps_context_and_rejects_bad_request_options()
@@ -931,7 +1113,7 @@ fn incremental_decoder_preserves_token_boundaries() -> Result<(), CandleError> {
         .map_err(|error| CandleError::TokenizerDecoding(error.to_string()))?;
     assert_ne!(independently_decoded, complete);
 
-    let mut decoder = IncrementalTextDecoder::new(&tokenizer);
+    let mut decoder = IncrementalTextDecoder::new(&tokenizer, true);
     let mut streamed = String::new();
     for id in ids {
         if let Some(fragment) = decoder.push(id)? {
@@ -969,7 +1151,7 @@ fn incremental_decoder_waits_for_complete_unicode_bytes()
         .with_post_processor(Some(ByteLevel::default()))
         .build()?
         .into();
-    let mut decoder = IncrementalTextDecoder::new(&tokenizer);
+    let mut decoder = IncrementalTextDecoder::new(&tokenizer, true);
     assert!(decoder.push(1)?.is_none());
     assert_eq!(decoder.push(2)?.as_deref(), Some("é"));
     assert!(decoder.finish()?.is_none());
@@ -1019,6 +1201,87 @@ fn eos_is_counted_but_excluded_from_decoded_text()
     Ok(())
 }
 
+/// The tiny Llama 3 vocabulary with its two content tokens replaced by JSON
+/// punctuation, fused on decode so token bytes concatenate verbatim.
+fn tiny_json_tokenizer() -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let vocab = [
+        ("<unk>".to_string(), 0),
+        (END_OF_TURN.to_string(), 1),
+        (BEGIN_OF_TEXT.to_string(), 2),
+        ("<eos>".to_string(), 3),
+        (START_HEADER.to_string(), 4),
+        (END_HEADER.to_string(), 5),
+        ("{".to_string(), 6),
+        ("}".to_string(), 7),
+    ]
+    .into_iter()
+    .collect();
+    let model = WordLevel::builder()
+        .vocab(vocab)
+        .unk_token("<unk>".to_string())
+        .build()?;
+    let mut tokenizer = Tokenizer::new(model);
+    tokenizer.with_decoder(Some(Fuse::new()));
+    tokenizer.add_special_tokens(&[
+        AddedToken::from(END_OF_TURN, true),
+        AddedToken::from(BEGIN_OF_TEXT, true),
+        AddedToken::from(START_HEADER, true),
+        AddedToken::from(END_HEADER, true),
+    ]);
+    Ok(tokenizer.to_string(false)?.into_bytes())
+}
+
+#[test]
+fn output_schema_masks_sampling_to_schema_valid_json()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = ModelData {
+        tokenizer: tiny_json_tokenizer()?,
+        ..model_data()?
+    };
+    let generation = GenerationConfig {
+        temperature: 0.0,
+        max_tokens: 8,
+        ..GenerationConfig::default()
+    };
+    let loaded = load_model(data, generation, 1)?;
+
+    // Zero weights give every token the same logit, so greedy decoding picks
+    // the lowest allowed ID: `<unk>` when free, the schema's path when masked.
+    let free = infer(
+        &loaded,
+        &request(Vec::new()),
+        &CancellationSignal::default(),
+    )?;
+    assert_eq!(free.response.finish_reason, FinishReason::MaxTokens);
+
+    let mut constrained = request(Vec::new());
+    constrained.output_schema = Some(serde_json::from_value(
+        serde_json::json!({"type": "object"}),
+    )?);
+    let response = infer(&loaded, &constrained, &CancellationSignal::default())?.response;
+    assert_eq!(response.text, "{}");
+    assert_eq!(response.finish_reason, FinishReason::Eos);
+    assert_eq!(response.generated_tokens, 3);
+
+    constrained.output_schema = Some(serde_json::from_value(
+        serde_json::json!({"type": "string", "pattern": "^a+$"}),
+    )?);
+    assert!(matches!(
+        infer(&loaded, &constrained, &CancellationSignal::default()),
+        Err(CandleError::UnsupportedFeature(reason)) if reason.contains("pattern")
+    ));
+
+    // A schema the vocabulary cannot spell fails instead of sampling garbage.
+    constrained.output_schema = Some(serde_json::from_value(
+        serde_json::json!({"type": "string"}),
+    )?);
+    assert!(matches!(
+        infer(&loaded, &constrained, &CancellationSignal::default()),
+        Err(CandleError::Inference(reason)) if reason.contains("constrained decoding")
+    ));
+    Ok(())
+}
+
 #[test]
 fn sampling_modes_and_repeat_window_are_exact() {
     let mut config = GenerationConfig {
@@ -1090,6 +1353,101 @@ fn qwen3_4b_configuration_is_exactly_scoped() -> Result<(), CandleError> {
     Ok(())
 }
 
+#[test]
+fn phi3_and_gemma3_configurations_are_exactly_scoped() -> Result<(), CandleError> {
+    let parse = |json: &str| {
+        serde_json::from_str::<serde_json::Value>(json)
+            .map_err(|error| CandleError::Configuration(error.to_string()))
+    };
+    let phi3_json = parse(
+        r#"{
+            "architectures":["Phi3ForCausalLM"],
+            "model_type":"phi3",
+            "hidden_size":3072,
+            "intermediate_size":8192,
+            "num_hidden_layers":32,
+            "num_attention_heads":32,
+            "num_key_value_heads":32,
+            "max_position_embeddings":4096,
+            "sliding_window":2047,
+            "vocab_size":32064,
+            "rms_norm_eps":0.00001,
+            "rope_theta":10000.0,
+            "rope_scaling":null,
+            "tie_word_embeddings":false,
+            "bos_token_id":1,
+            "eos_token_id":32000,
+            "hidden_act":"silu"
+        }"#,
+    )?;
+    let phi3_definition = definition_for(ModelFamily::Phi3, ArtifactFormat::Gguf)?;
+    let mut phi3: Phi3Config = serde_json::from_value(phi3_json.clone())
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    validate_phi3_config(&phi3, phi3_definition)?;
+    let identity: ModelIdentity = serde_json::from_value(phi3_json)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    assert_eq!(declared_model_family(&identity), Some(ModelFamily::Phi3));
+
+    // The 128k variants need LongRoPE, which candle's Phi-3 ignores.
+    phi3.rope_scaling = Some(serde_json::json!({"type": "longrope"}));
+    assert!(matches!(
+        validate_phi3_config(&phi3, phi3_definition),
+        Err(CandleError::ArtifactMismatch {
+            artifact: "config.json",
+            ..
+        })
+    ));
+
+    let gemma3_json = parse(
+        r#"{
+            "architectures":["Gemma3ForCausalLM"],
+            "model_type":"gemma3_text",
+            "hidden_size":1152,
+            "intermediate_size":6912,
+            "num_hidden_layers":26,
+            "num_attention_heads":4,
+            "num_key_value_heads":1,
+            "head_dim":256,
+            "query_pre_attn_scalar":256,
+            "sliding_window":512,
+            "sliding_window_pattern":6,
+            "max_position_embeddings":32768,
+            "vocab_size":262144,
+            "rms_norm_eps":0.000001,
+            "rope_theta":1000000.0,
+            "rope_local_base_freq":10000.0,
+            "rope_scaling":null,
+            "attn_logit_softcapping":null,
+            "final_logit_softcapping":null,
+            "bos_token_id":2,
+            "eos_token_id":[1,106],
+            "hidden_activation":"gelu_pytorch_tanh"
+        }"#,
+    )?;
+    let gemma3_definition = definition_for(ModelFamily::Gemma3, ArtifactFormat::Gguf)?;
+    let mut gemma3: Gemma3Config = serde_json::from_value(gemma3_json)
+        .map_err(|error| CandleError::Configuration(error.to_string()))?;
+    assert!(gemma3.tie_word_embeddings);
+    assert_eq!(gemma3.eos_token_ids(), [1, 106]);
+    validate_gemma3_config(&gemma3, gemma3_definition)?;
+
+    gemma3.final_logit_softcapping = Some(30.0);
+    assert!(matches!(
+        validate_gemma3_config(&gemma3, gemma3_definition),
+        Err(CandleError::ArtifactMismatch {
+            artifact: "config.json",
+            ..
+        })
+    ));
+    gemma3.final_logit_softcapping = None;
+    gemma3.model_type = "gemma3".to_string();
+    assert!(matches!(
+        validate_gemma3_config(&gemma3, gemma3_definition),
+        Err(CandleError::UnsupportedModelFamily(_))
+    ));
+    Ok(())
+}
+
 #[cfg(not(target_family = "wasm"))]
 #[test]
 fn concurrency_limit_and_cancellation_are_deterministic()
@@ -1417,12 +1775,6 @@ fn rejects_unsupported_request_features() -> Result<(), Box<dyn std::error::Erro
     choice.tool_choice = Some(ToolChoice::Auto);
     assert!(render_prompt(&choice).is_err());
 
-    let mut schema = request(vec![Message::user("hello")]);
-    schema.output_schema = Some(serde_json::from_value(
-        serde_json::json!({"type": "string"}),
-    )?);
-    assert!(render_prompt(&schema).is_err());
-
     let mut override_request = request(vec![Message::user("hello")]);
     override_request.model = Some("other".to_string());
     assert!(render_prompt(&override_request).is_err());
@@ -1499,6 +1851,7 @@ fn converts_finish_reason_and_usage() -> Result<(), CandleError> {
     let response = CandleCompletionResponse {
         text: "done".to_string(),
         prompt_tokens: 5,
+        cached_prompt_tokens: 3,
         generated_tokens: 2,
         requested_max_tokens: 4,
         effective_max_tokens: 3,
@@ -1512,6 +1865,7 @@ fn converts_finish_reason_and_usage() -> Result<(), CandleError> {
     assert_eq!(usage.input_tokens, 5);
     assert_eq!(usage.output_tokens, 2);
     assert_eq!(usage.total_tokens, 7);
+    assert_eq!(usage.cached_input_tokens, 3);
     assert_eq!(response.finish_reason, FinishReason::Eos);
     assert_eq!(response.text, "done");
     assert_eq!(response.requested_max_tokens, 4);
@@ -1537,6 +1891,7 @@ fn candle_completion_response_round_trips_through_serde_json_value()
     let raw = CandleCompletionResponse {
         text: "done".to_string(),
         prompt_tokens: 5,
+        cached_prompt_tokens: 0,
         generated_tokens: 2,
         requested_max_tokens: 4,
         effective_max_tokens: 3,
@@ -1577,6 +1932,7 @@ async fn stream_from_events_terminal_carries_raw()
     let terminal_record = CandleCompletionResponse {
         text: "hi".to_string(),
         prompt_tokens: 3,
+        cached_prompt_tokens: 0,
         generated_tokens: 1,
         requested_max_tokens: 4,
         effective_max_tokens: 4,
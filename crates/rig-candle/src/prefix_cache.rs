@@ -0,0 +1,143 @@
+//! KV-cache reuse across requests that share a prompt prefix.
+//!
+//! Candle's caches can be extended from an offset but not truncated, so this
+//! keeps whole snapshots: the cache state and last-position logits after a
+//! prompt was prefilled, and again after generation. A new request resumes
+//! from the longest snapshot whose tokens prefix its prompt and prefills only
+//! the remainder. Snapshots are clones of reference-counted tensors, so taking
+//! one copies no cache data; a backend that writes its cache in place takes no
+//! snapshots. Retained snapshots are bounded by a total token budget and
+//! evicted least-recently-used first.
+
+use std::sync::{Mutex, PoisonError};
+
+use candle_core::Tensor;
+use candle_transformers::models::llama::Cache;
+use candle_transformers::models::quantized_gemma3::ModelWeights as QuantizedGemma3;
+use candle_transformers::models::quantized_llama::ModelWeights as QuantizedLlama;
+use candle_transformers::models::quantized_qwen3::ModelWeights as QuantizedQwen3;
+
+/// Owned KV state of one generation session, detached from the weights it
+/// belongs to.
+#[derive(Clone)]
+pub(crate) enum KvState {
+    Safetensors(Cache),
+    QuantizedLlama(QuantizedLlama),
+    QuantizedQwen3(QuantizedQwen3),
+    QuantizedGemma3(QuantizedGemma3),
+}
+
+/// A reusable prefix: the state after forwarding `tokens`, and the logits
+/// that forward produced for the next position.
+pub(crate) struct PrefixHit {
+    pub(crate) cached_tokens: usize,
+    pub(crate) state: KvState,
+    pub(crate) logits: Tensor,
+}
+
+pub(crate) struct PrefixCache {
+    capacity_tokens: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    snapshots: Vec<Snapshot>,
+    clock: u64,
+}
+
+struct Snapshot {
+    tokens: Vec<u32>,
+    state: KvState,
+    logits: Tensor,
+    last_used: u64,
+}
+
+impl PrefixCache {
+    /// A cache retaining at most `capacity_tokens` tokens of KV state. Zero
+    /// disables reuse.
+    pub(crate) fn new(capacity_tokens: usize) -> Self {
+        Self {
+            capacity_tokens,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// The longest retained snapshot whose tokens are a prefix of `prompt`.
+    pub(crate) fn lookup(&self, prompt: &[u32]) -> Option<PrefixHit> {
+        if self.capacity_tokens == 0 {
+            return None;
+        }
+        // Every mutation leaves the entry list consistent, so a panic in
+        // another request does not make it unusable.
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.clock = entries.clock.wrapping_add(1);
+        let clock = entries.clock;
+        let snapshot = entries
+            .snapshots
+            .iter_mut()
+            .filter(|snapshot| prompt.starts_with(&snapshot.tokens))
+            .max_by_key(|snapshot| snapshot.tokens.len())?;
+        snapshot.last_used = clock;
+        Some(PrefixHit {
+            cached_tokens: snapshot.tokens.len(),
+            state: snapshot.state.clone(),
+            logits: snapshot.logits.clone(),
+        })
+    }
+
+    /// Retain the state reached after forwarding `tokens`, evicting the
+    /// least-recently-used snapshots beyond the token budget.
+    pub(crate) fn insert(&self, tokens: &[u32], state: KvState, logits: Tensor) {
+        if tokens.is_empty() || tokens.len() > self.capacity_tokens {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.clock = entries.clock.wrapping_add(1);
+        let clock = entries.clock;
+        if let Some(existing) = entries
+            .snapshots
+            .iter_mut()
+            .find(|snapshot| snapshot.tokens == tokens)
+        {
+            existing.last_used = clock;
+            return;
+        }
+        entries.snapshots.push(Snapshot {
+            tokens: tokens.to_vec(),
+            state,
+            logits,
+            last_used: clock,
+        });
+        while entries
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.tokens.len())
+            .sum::<usize>()
+            > self.capacity_tokens
+        {
+            let Some(oldest) = entries
+                .snapshots
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, snapshot)| snapshot.last_used)
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            entries.snapshots.swap_remove(oldest);
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn retained_tokens(&self) -> Vec<usize> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut lengths: Vec<_> = entries
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.tokens.len())
+            .collect();
+        lengths.sort_unstable();
+        lengths
+    }
+}
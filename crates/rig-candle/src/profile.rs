@@ -16,6 +16,27 @@ pub const IM_END: &str = "<|im_end|>";
 pub(crate) const END_OF_TEXT: &str = "<|endoftext|>";
 pub const SMOLLM2_DEFAULT_SYSTEM_PROMPT: &str =
     "You are a helpful AI assistant named SmolLM, trained by Hugging Face";
+/// SentencePiece sequence markers shared by Mistral and Phi-3.
+pub(crate) const BOS: &str = "<s>";
+pub(crate) const EOS: &str = "</s>";
+pub(crate) const INST_START: &str = "[INST]";
+pub(crate) const INST_END: &str = "[/INST]";
+pub(crate) const TOOL_CALLS: &str = "[TOOL_CALLS]";
+pub(crate) const AVAILABLE_TOOLS_START: &str = "[AVAILABLE_TOOLS]";
+pub(crate) const AVAILABLE_TOOLS_END: &str = "[/AVAILABLE_TOOLS]";
+pub(crate) const TOOL_RESULTS_START: &str = "[TOOL_RESULTS]";
+pub(crate) const TOOL_RESULTS_END: &str = "[/TOOL_RESULTS]";
+pub(crate) const PHI3_SYSTEM: &str = "<|system|>";
+pub(crate) const PHI3_USER: &str = "<|user|>";
+pub(crate) const PHI3_ASSISTANT: &str = "<|assistant|>";
+pub(crate) const PHI3_END: &str = "<|end|>";
+pub(crate) const GEMMA_BOS: &str = "<bos>";
+pub(crate) const GEMMA_EOS: &str = "<eos>";
+pub(crate) const START_OF_TURN: &str = "<start_of_turn>";
+pub(crate) const END_OF_GEMMA_TURN: &str = "<end_of_turn>";
+/// Gemma 3's image placeholder, defined by the tokenizer past the 1B model's
+/// embedding matrix.
+pub(crate) const GEMMA_IMAGE_TOKEN: &str = "<image_soft_token>";
 
 /// Explicit conversation and generated-output protocol selected from validated artifacts.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -27,6 +48,12 @@ pub enum ConversationProtocol {
     SmolLm2,
     /// Qwen3 ChatML/Hermes tool-calling format.
     Qwen3,
+    /// Mistral instruct v0.3 format with its native `[TOOL_CALLS]` protocol.
+    Mistral,
+    /// Microsoft Phi-3 instruct format.
+    Phi3,
+    /// Google Gemma 3 instruct format.
+    Gemma3,
 }
 
 /// Backwards-compatible name for [`ConversationProtocol`].
@@ -36,10 +63,15 @@ pub type ModelFamily = ConversationProtocol;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ModelArchitecture {
-    /// Candle's Llama implementation, including compatible SmolLM2 checkpoints.
+    /// Candle's Llama implementation, including compatible SmolLM2 and Mistral
+    /// checkpoints.
     Llama,
     /// Candle's Qwen3 implementation with per-head query/key normalization.
     Qwen3,
+    /// Candle's Phi-3 implementation with fused QKV and gate/up projections.
+    Phi3,
+    /// Candle's Gemma 3 implementation with interleaved sliding-window layers.
+    Gemma3,
 }
 
 /// Quantized tensor encoding detected in a GGUF checkpoint.
@@ -61,6 +93,8 @@ pub(crate) enum LoaderBackend {
     LlamaSafetensors,
     LlamaGguf,
     Qwen3Gguf,
+    Phi3Gguf,
+    Gemma3Gguf,
 }
 
 #[derive(Debug)]
@@ -93,6 +127,9 @@ pub(crate) struct ConfigRequirements {
 pub(crate) enum TokenizerVocabulary {
     ModelCapacity,
     Exact(usize),
+    /// The model capacity plus at most this many tokenizer-only IDs past the
+    /// embedding matrix, which prompts may never contain.
+    ModelCapacityPlus(usize),
 }
 
 #[derive(Debug)]
@@ -103,10 +140,16 @@ pub(crate) struct MetadataStringRequirement {
 
 #[derive(Debug)]
 pub(crate) struct GgufRequirements {
+    /// Expected `general.architecture`, which need not match config.json's
+    /// `model_type` (Mistral checkpoints are exported as `llama`).
+    pub(crate) architecture: &'static str,
     pub(crate) file_type: u32,
     pub(crate) quantization_version: usize,
     pub(crate) metadata_strings: &'static [MetadataStringRequirement],
     pub(crate) chat_template_markers: &'static [&'static str],
+    /// Tokens `tokenizer.ggml.eos_token_id` may name; converters disagree on
+    /// whether it is the sequence end or the turn end.
+    pub(crate) eos_tokens: &'static [&'static str],
     pub(crate) allowed_tensor_dtypes: &'static [GgmlDType],
     pub(crate) token_embedding_dtypes: &'static [GgmlDType],
     pub(crate) norm_dtypes: &'static [GgmlDType],
@@ -131,6 +174,9 @@ pub(crate) struct ProfileDefinition {
     pub(crate) start_token: &'static str,
     pub(crate) end_token: &'static str,
     pub(crate) context_limit_cap: Option<usize>,
+    /// Rejected on wasm32, whose linear memory cannot hold the checkpoint.
+    #[cfg_attr(not(target_family = "wasm"), allow(dead_code))]
+    pub(crate) native_only: bool,
     pub(crate) gguf: Option<GgufRequirements>,
 }
 
@@ -168,6 +214,7 @@ const LLAMA3_PROFILE: ProfileDefinition = ProfileDefinition {
     start_token: BEGIN_OF_TEXT,
     end_token: END_OF_TURN,
     context_limit_cap: None,
+    native_only: false,
     gguf: None,
 };
 
@@ -229,7 +276,9 @@ const SMOLLM2_PROFILE: ProfileDefinition = ProfileDefinition {
     start_token: IM_START,
     end_token: IM_END,
     context_limit_cap: Some(4096),
+    native_only: false,
     gguf: Some(GgufRequirements {
+        architecture: "llama",
         file_type: 15,
         quantization_version: 2,
         metadata_strings: &[
@@ -247,6 +296,7 @@ const SMOLLM2_PROFILE: ProfileDefinition = ProfileDefinition {
             },
         ],
         chat_template_markers: &[],
+        eos_tokens: &[IM_END],
         allowed_tensor_dtypes: &[
             GgmlDType::F32,
             GgmlDType::Q4K,
@@ -324,7 +374,9 @@ const QWEN3_PROFILE: ProfileDefinition = ProfileDefinition {
     start_token: END_OF_TEXT,
     end_token: IM_END,
     context_limit_cap: Some(4096),
+    native_only: true,
     gguf: Some(GgufRequirements {
+        architecture: "qwen3",
         file_type: 15,
         quantization_version: 2,
         metadata_strings: &[
@@ -356,6 +408,7 @@ const QWEN3_PROFILE: ProfileDefinition = ProfileDefinition {
             "<tool_response>",
             "enable_thinking",
         ],
+        eos_tokens: &[IM_END],
         allowed_tensor_dtypes: &[GgmlDType::F32, GgmlDType::Q4K, GgmlDType::Q6K],
         token_embedding_dtypes: &[GgmlDType::Q6K],
         norm_dtypes: &[GgmlDType::F32],
@@ -365,6 +418,295 @@ const QWEN3_PROFILE: ProfileDefinition = ProfileDefinition {
     }),
 };
 
+const MISTRAL_PROFILE: ProfileDefinition = ProfileDefinition {
+    name: "Mistral-7B-Instruct-v0.3 Q4_K_M GGUF",
+    architecture: ModelArchitecture::Llama,
+    protocol: ConversationProtocol::Mistral,
+    artifact_format: ArtifactFormat::Gguf,
+    loader: LoaderBackend::LlamaGguf,
+    quantization: Some(Quantization::Q4K),
+    config_identity: ConfigIdentity {
+        model_type: "mistral",
+        architecture: "MistralForCausalLM",
+        required: true,
+    },
+    config_dimensions: &[
+        DimensionRequirement {
+            field: "hidden_size",
+            value: 4096,
+        },
+        DimensionRequirement {
+            field: "intermediate_size",
+            value: 14_336,
+        },
+        DimensionRequirement {
+            field: "vocab_size",
+            value: 32_768,
+        },
+        DimensionRequirement {
+            field: "num_hidden_layers",
+            value: 32,
+        },
+        DimensionRequirement {
+            field: "num_attention_heads",
+            value: 32,
+        },
+        DimensionRequirement {
+            field: "num_key_value_heads",
+            value: 8,
+        },
+        DimensionRequirement {
+            field: "max_position_embeddings",
+            value: 32_768,
+        },
+    ],
+    config_requirements: ConfigRequirements {
+        hidden_act: Some("silu"),
+        attention_bias: None,
+        mlp_bias: None,
+        rope_interleaved: None,
+        tie_word_embeddings: Some(false),
+        rms_norm_eps: Some(1e-5),
+        rope_theta: Some(1_000_000.0),
+        bos_token_id: Some(1),
+        eos_token_id: None,
+    },
+    tokenizer_tokens: &[
+        "<s>",
+        "</s>",
+        "[INST]",
+        "[/INST]",
+        "[TOOL_CALLS]",
+        "[AVAILABLE_TOOLS]",
+        "[/AVAILABLE_TOOLS]",
+        "[TOOL_RESULTS]",
+        "[/TOOL_RESULTS]",
+    ],
+    tokenizer_vocabulary: TokenizerVocabulary::ModelCapacity,
+    start_token: BOS,
+    end_token: EOS,
+    context_limit_cap: Some(4096),
+    native_only: true,
+    gguf: Some(GgufRequirements {
+        architecture: "llama",
+        file_type: 15,
+        quantization_version: 2,
+        metadata_strings: &[MetadataStringRequirement {
+            key: "tokenizer.ggml.model",
+            value: "llama",
+        }],
+        chat_template_markers: &[],
+        eos_tokens: &[EOS],
+        allowed_tensor_dtypes: &[GgmlDType::F32, GgmlDType::Q4K, GgmlDType::Q6K],
+        token_embedding_dtypes: &[],
+        norm_dtypes: &[],
+        matrix_dtypes: &[],
+        mixed_matrix_dtypes: &[],
+        tensors_per_layer: None,
+    }),
+};
+
+const PHI3_PROFILE: ProfileDefinition = ProfileDefinition {
+    name: "Phi-3-mini-4k-instruct Q4_K_M GGUF",
+    architecture: ModelArchitecture::Phi3,
+    protocol: ConversationProtocol::Phi3,
+    artifact_format: ArtifactFormat::Gguf,
+    loader: LoaderBackend::Phi3Gguf,
+    quantization: Some(Quantization::Q4K),
+    config_identity: ConfigIdentity {
+        model_type: "phi3",
+        architecture: "Phi3ForCausalLM",
+        required: true,
+    },
+    config_dimensions: &[
+        DimensionRequirement {
+            field: "hidden_size",
+            value: 3072,
+        },
+        DimensionRequirement {
+            field: "intermediate_size",
+            value: 8192,
+        },
+        DimensionRequirement {
+            field: "vocab_size",
+            value: 32_064,
+        },
+        DimensionRequirement {
+            field: "num_hidden_layers",
+            value: 32,
+        },
+        DimensionRequirement {
+            field: "num_attention_heads",
+            value: 32,
+        },
+        DimensionRequirement {
+            field: "num_key_value_heads",
+            value: 32,
+        },
+        DimensionRequirement {
+            field: "max_position_embeddings",
+            value: 4096,
+        },
+        DimensionRequirement {
+            field: "sliding_window",
+            value: 2047,
+        },
+    ],
+    config_requirements: ConfigRequirements {
+        hidden_act: Some("silu"),
+        attention_bias: Some(false),
+        mlp_bias: None,
+        rope_interleaved: None,
+        tie_word_embeddings: Some(false),
+        rms_norm_eps: Some(1e-5),
+        // Candle's quantized Phi-3 hard-codes this RoPE base.
+        rope_theta: Some(10_000.0),
+        bos_token_id: Some(1),
+        eos_token_id: Some(32_000),
+    },
+    tokenizer_tokens: &[
+        "<s>",
+        "<|endoftext|>",
+        "<|system|>",
+        "<|user|>",
+        "<|assistant|>",
+        "<|end|>",
+    ],
+    tokenizer_vocabulary: TokenizerVocabulary::Exact(32_011),
+    start_token: BOS,
+    end_token: PHI3_END,
+    // Candle's Phi-3 attends to the whole cache; staying inside the
+    // checkpoint's sliding window keeps it equivalent to the reference model.
+    context_limit_cap: Some(2047),
+    native_only: true,
+    gguf: Some(GgufRequirements {
+        architecture: "phi3",
+        file_type: 15,
+        quantization_version: 2,
+        metadata_strings: &[MetadataStringRequirement {
+            key: "tokenizer.ggml.model",
+            value: "llama",
+        }],
+        chat_template_markers: &[],
+        eos_tokens: &[END_OF_TEXT, PHI3_END],
+        allowed_tensor_dtypes: &[
+            GgmlDType::F32,
+            GgmlDType::Q4K,
+            GgmlDType::Q5K,
+            GgmlDType::Q6K,
+        ],
+        token_embedding_dtypes: &[],
+        norm_dtypes: &[],
+        matrix_dtypes: &[],
+        mixed_matrix_dtypes: &[],
+        tensors_per_layer: None,
+    }),
+};
+
+const GEMMA3_PROFILE: ProfileDefinition = ProfileDefinition {
+    name: "Gemma 3 1B instruct Q4_K_M GGUF",
+    architecture: ModelArchitecture::Gemma3,
+    protocol: ConversationProtocol::Gemma3,
+    artifact_format: ArtifactFormat::Gguf,
+    loader: LoaderBackend::Gemma3Gguf,
+    quantization: Some(Quantization::Q4K),
+    config_identity: ConfigIdentity {
+        model_type: "gemma3_text",
+        architecture: "Gemma3ForCausalLM",
+        required: true,
+    },
+    config_dimensions: &[
+        DimensionRequirement {
+            field: "hidden_size",
+            value: 1152,
+        },
+        DimensionRequirement {
+            field: "intermediate_size",
+            value: 6912,
+        },
+        DimensionRequirement {
+            field: "num_hidden_layers",
+            value: 26,
+        },
+        DimensionRequirement {
+            field: "num_attention_heads",
+            value: 4,
+        },
+        DimensionRequirement {
+            field: "num_key_value_heads",
+            value: 1,
+        },
+        DimensionRequirement {
+            field: "head_dim",
+            value: 256,
+        },
+        // Candle scales attention by 1/sqrt(head_dim), which is only the
+        // checkpoint's scaling when this scalar equals head_dim.
+        DimensionRequirement {
+            field: "query_pre_attn_scalar",
+            value: 256,
+        },
+        DimensionRequirement {
+            field: "sliding_window",
+            value: 512,
+        },
+        DimensionRequirement {
+            field: "sliding_window_pattern",
+            value: 6,
+        },
+        DimensionRequirement {
+            field: "max_position_embeddings",
+            value: 32_768,
+        },
+        DimensionRequirement {
+            field: "vocab_size",
+            value: 262_144,
+        },
+    ],
+    config_requirements: ConfigRequirements {
+        hidden_act: Some("gelu_pytorch_tanh"),
+        attention_bias: Some(false),
+        mlp_bias: None,
+        rope_interleaved: None,
+        tie_word_embeddings: Some(true),
+        rms_norm_eps: Some(1e-6),
+        rope_theta: Some(1_000_000.0),
+        bos_token_id: Some(2),
+        eos_token_id: None,
+    },
+    tokenizer_tokens: &["<bos>", "<eos>", "<start_of_turn>", "<end_of_turn>"],
+    tokenizer_vocabulary: TokenizerVocabulary::ModelCapacityPlus(1),
+    start_token: GEMMA_BOS,
+    end_token: END_OF_GEMMA_TURN,
+    // Candle applies the local layers' sliding window only within a single
+    // forward pass, so contexts are kept inside the 512-token window.
+    context_limit_cap: Some(512),
+    native_only: true,
+    gguf: Some(GgufRequirements {
+        architecture: "gemma3",
+        file_type: 15,
+        quantization_version: 2,
+        metadata_strings: &[MetadataStringRequirement {
+            key: "tokenizer.ggml.model",
+            value: "llama",
+        }],
+        chat_template_markers: &[],
+        eos_tokens: &[GEMMA_EOS, END_OF_GEMMA_TURN],
+        allowed_tensor_dtypes: &[
+            GgmlDType::F32,
+            GgmlDType::Q4K,
+            GgmlDType::Q5_0,
+            GgmlDType::Q6K,
+            GgmlDType::Q8_0,
+        ],
+        token_embedding_dtypes: &[],
+        norm_dtypes: &[],
+        matrix_dtypes: &[],
+        mixed_matrix_dtypes: &[],
+        tensors_per_layer: None,
+    }),
+};
+
 #[derive(Debug, Clone)]
 pub(crate) struct ValidatedProfile {
     pub(crate) definition: &'static ProfileDefinition,
@@ -405,6 +747,9 @@ pub(crate) fn definition_for(
         ConversationProtocol::Llama3 => &LLAMA3_PROFILE,
         ConversationProtocol::SmolLm2 => &SMOLLM2_PROFILE,
         ConversationProtocol::Qwen3 => &QWEN3_PROFILE,
+        ConversationProtocol::Mistral => &MISTRAL_PROFILE,
+        ConversationProtocol::Phi3 => &PHI3_PROFILE,
+        ConversationProtocol::Gemma3 => &GEMMA3_PROFILE,
     };
     if definition.artifact_format != artifact_format {
         return Err(CandleError::UnsupportedModelFamily(format!(
@@ -536,6 +881,15 @@ pub(crate) fn validate_tokenizer_requirements(
                 actual: actual_vocabulary,
             });
         }
+        TokenizerVocabulary::ModelCapacityPlus(extra)
+            if actual_vocabulary < vocab_size || actual_vocabulary > vocab_size + extra =>
+        {
+            return Err(CandleError::TokenizerVocabularyMismatch {
+                expected: vocab_size,
+                actual: actual_vocabulary,
+            });
+        }
+        TokenizerVocabulary::ModelCapacityPlus(_) => {}
         TokenizerVocabulary::Exact(expected) if actual_vocabulary != expected => {
             return Err(CandleError::ArtifactMismatch {
                 artifact: "tokenizer.json",
@@ -602,7 +956,7 @@ pub(crate) fn validate_tokenizer_requirements(
 }
 
 #[cfg(test)]
-#[allow(clippy::panic_in_result_fn)]
+#[allow(clippy::panic_in_result_fn, clippy::expect_used)]
 mod tests {
     use std::collections::HashSet;
 
@@ -663,6 +1017,91 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mistral_phi3_and_gemma3_profiles_select_their_own_backends() -> Result<(), CandleError> {
+        let mistral = definition_for(ConversationProtocol::Mistral, ArtifactFormat::Gguf)?;
+        assert_eq!(mistral.loader, LoaderBackend::LlamaGguf);
+        assert_eq!(mistral.architecture, ModelArchitecture::Llama);
+        assert_eq!(mistral.config_identity.model_type, "mistral");
+        assert!(mistral.tokenizer_tokens.contains(&TOOL_CALLS));
+        assert!(mistral.gguf.as_ref().is_some_and(|requirements| {
+            requirements.architecture == "llama" && requirements.eos_tokens == [EOS]
+        }));
+
+        let phi3 = definition_for(ConversationProtocol::Phi3, ArtifactFormat::Gguf)?;
+        assert_eq!(phi3.loader, LoaderBackend::Phi3Gguf);
+        assert_eq!(phi3.architecture, ModelArchitecture::Phi3);
+        assert_eq!(phi3.end_token, PHI3_END);
+        assert_eq!(
+            phi3.tokenizer_vocabulary,
+            TokenizerVocabulary::Exact(32_011)
+        );
+        assert!(phi3.gguf.as_ref().is_some_and(|requirements| {
+            requirements.eos_tokens.contains(&END_OF_TEXT)
+                && requirements.eos_tokens.contains(&PHI3_END)
+        }));
+
+        let gemma = definition_for(ConversationProtocol::Gemma3, ArtifactFormat::Gguf)?;
+        assert_eq!(gemma.loader, LoaderBackend::Gemma3Gguf);
+        assert_eq!(gemma.architecture, ModelArchitecture::Gemma3);
+        assert_eq!(gemma.start_token, GEMMA_BOS);
+        assert_eq!(gemma.end_token, END_OF_GEMMA_TURN);
+        let gemma = ValidatedProfile::new(gemma, 262_144, 32_768, HashSet::from([1, 106]))?;
+        assert_eq!(gemma.context_limit, 512);
+
+        for definition in [&MISTRAL_PROFILE, &PHI3_PROFILE, &GEMMA3_PROFILE] {
+            assert!(definition.native_only);
+            assert!(matches!(
+                definition_for(definition.protocol, ArtifactFormat::Safetensors),
+                Err(CandleError::UnsupportedModelFamily(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gemma_vocabulary_admits_only_the_declared_tokenizer_overhang() {
+        let vocab_size = 8;
+        let tokenizer = |extra: usize| {
+            let tokens = ["<bos>", "<eos>", "<start_of_turn>", "<end_of_turn>"];
+            let mut vocab: Vec<_> = tokens
+                .iter()
+                .map(|token| ((*token).to_string(), 0_u32))
+                .collect();
+            vocab.extend((tokens.len()..vocab_size + extra).map(|id| (format!("t{id}"), 0)));
+            let vocab = vocab
+                .into_iter()
+                .enumerate()
+                .map(|(id, (token, _))| (token, id as u32))
+                .collect();
+            let model = tokenizers::models::wordlevel::WordLevel::builder()
+                .vocab(vocab)
+                .unk_token("t4".to_string())
+                .build()
+                .expect("word-level tokenizer");
+            let mut tokenizer = tokenizers::Tokenizer::new(model);
+            tokenizer.add_special_tokens(
+                &tokens
+                    .iter()
+                    .map(|token| tokenizers::AddedToken::from(*token, true))
+                    .collect::<Vec<_>>(),
+            );
+            tokenizer
+        };
+        assert!(
+            validate_tokenizer_requirements(&GEMMA3_PROFILE, &tokenizer(0), vocab_size, None, &[])
+                .is_ok()
+        );
+        assert!(
+            validate_tokenizer_requirements(&GEMMA3_PROFILE, &tokenizer(1), vocab_size, None, &[])
+                .is_ok()
+        );
+        assert!(matches!(
+            validate_tokenizer_requirements(&GEMMA3_PROFILE, &tokenizer(2), vocab_size, None, &[]),
+            Err(CandleError::TokenizerVocabularyMismatch { .. })
+        ));
+    }
+
     #[test]
     fn profiles_reject_unsupported_artifact_combinations_and_empty_stops() {
         assert!(matches!(
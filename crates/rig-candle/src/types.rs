@@ -1,6 +1,7 @@
 //! Public errors and response metadata.
 
 use rig_core::completion::{CompletionError, Usage};
+use rig_core::embeddings::EmbeddingError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -214,6 +215,12 @@ impl From<CandleError> for CompletionError {
     }
 }
 
+impl From<CandleError> for EmbeddingError {
+    fn from(error: CandleError) -> Self {
+        EmbeddingError::ProviderError(error.to_string())
+    }
+}
+
 /// The reason local generation ended.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -231,6 +238,10 @@ pub struct CandleCompletionResponse {
     pub text: String,
     /// Number of encoded prompt tokens.
     pub prompt_tokens: u64,
+    /// Prompt tokens whose KV state was reused from an earlier request on the
+    /// same model instead of being prefilled; included in `prompt_tokens`.
+    #[serde(default)]
+    pub cached_prompt_tokens: u64,
     /// Number of sampled output tokens, including an EOS token when sampled.
     pub generated_tokens: u64,
     /// Maximum output tokens selected by request/default precedence before context clamping.
@@ -257,6 +268,7 @@ impl From<&CandleCompletionResponse> for Usage {
         Usage {
             input_tokens: response.prompt_tokens,
             output_tokens: response.generated_tokens,
+            cached_input_tokens: response.cached_prompt_tokens,
             total_tokens: response
                 .prompt_tokens
                 .saturating_add(response.generated_tokens),
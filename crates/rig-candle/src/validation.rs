@@ -12,7 +12,7 @@ use crate::CandleError;
 use crate::profile::{
     BEGIN_OF_TEXT, ConfigValues, END_HEADER, END_OF_TURN, IM_END, IM_START, ModelFamily,
     ProfileDefinition, START_HEADER, validate_config_requirements, validate_dimensions,
-    validate_tokenizer_requirements,
+    validate_identity, validate_tokenizer_requirements,
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -37,6 +37,79 @@ pub(crate) struct Qwen3Config {
     pub(crate) attention_bias: bool,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Phi3Config {
+    #[serde(default)]
+    pub(crate) architectures: Vec<String>,
+    pub(crate) model_type: String,
+    pub(crate) hidden_size: usize,
+    pub(crate) intermediate_size: usize,
+    pub(crate) num_hidden_layers: usize,
+    pub(crate) num_attention_heads: usize,
+    pub(crate) num_key_value_heads: usize,
+    pub(crate) max_position_embeddings: usize,
+    pub(crate) sliding_window: usize,
+    pub(crate) vocab_size: usize,
+    pub(crate) rms_norm_eps: f64,
+    pub(crate) rope_theta: f64,
+    #[serde(default)]
+    pub(crate) rope_scaling: Option<serde_json::Value>,
+    pub(crate) tie_word_embeddings: bool,
+    pub(crate) bos_token_id: u32,
+    pub(crate) eos_token_id: u32,
+    pub(crate) hidden_act: String,
+    #[serde(default)]
+    pub(crate) attention_bias: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Gemma3Config {
+    #[serde(default)]
+    pub(crate) architectures: Vec<String>,
+    pub(crate) model_type: String,
+    pub(crate) hidden_size: usize,
+    pub(crate) intermediate_size: usize,
+    pub(crate) num_hidden_layers: usize,
+    pub(crate) num_attention_heads: usize,
+    pub(crate) num_key_value_heads: usize,
+    pub(crate) head_dim: usize,
+    pub(crate) query_pre_attn_scalar: usize,
+    pub(crate) sliding_window: usize,
+    pub(crate) sliding_window_pattern: usize,
+    pub(crate) max_position_embeddings: usize,
+    pub(crate) vocab_size: usize,
+    pub(crate) rms_norm_eps: f64,
+    pub(crate) rope_theta: f64,
+    pub(crate) rope_local_base_freq: f64,
+    #[serde(default)]
+    pub(crate) rope_scaling: Option<serde_json::Value>,
+    #[serde(default)]
+    pub(crate) attn_logit_softcapping: Option<f64>,
+    #[serde(default)]
+    pub(crate) final_logit_softcapping: Option<f64>,
+    /// Gemma 3 ties its output projection unless a config says otherwise.
+    #[serde(default = "tied_by_default")]
+    pub(crate) tie_word_embeddings: bool,
+    pub(crate) bos_token_id: u32,
+    pub(crate) eos_token_id: LlamaEosToks,
+    pub(crate) hidden_activation: String,
+    #[serde(default)]
+    pub(crate) attention_bias: bool,
+}
+
+fn tied_by_default() -> bool {
+    true
+}
+
+impl Gemma3Config {
+    pub(crate) fn eos_token_ids(&self) -> Vec<u32> {
+        match &self.eos_token_id {
+            LlamaEosToks::Single(token) => vec![*token],
+            LlamaEosToks::Multiple(tokens) => tokens.clone(),
+        }
+    }
+}
+
 pub(crate) fn validate_model_config(config: &Config) -> Result<(), CandleError> {
     for (field, value) in [
         ("hidden_size", config.hidden_size),
@@ -242,6 +315,119 @@ pub(crate) fn validate_qwen3_config(
     Ok(())
 }
 
+pub(crate) fn validate_phi3_config(
+    config: &Phi3Config,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    validate_identity(
+        definition,
+        Some(config.model_type.as_str()),
+        &config.architectures,
+    )?;
+    let actual = [
+        ("hidden_size", config.hidden_size),
+        ("intermediate_size", config.intermediate_size),
+        ("vocab_size", config.vocab_size),
+        ("num_hidden_layers", config.num_hidden_layers),
+        ("num_attention_heads", config.num_attention_heads),
+        ("num_key_value_heads", config.num_key_value_heads),
+        ("max_position_embeddings", config.max_position_embeddings),
+        ("sliding_window", config.sliding_window),
+    ];
+    validate_dimensions(definition, &actual)?;
+    // Candle's quantized Phi-3 has no LongRoPE support, so the 128k variants'
+    // scaled rotary embeddings would be silently ignored.
+    if config.rope_scaling.is_some() {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "config.json",
+            reason: format!("{} requires rope_scaling to be null", definition.name),
+        });
+    }
+    validate_config_requirements(
+        definition,
+        &ConfigValues {
+            hidden_act: Some(config.hidden_act.as_str()),
+            attention_bias: Some(config.attention_bias),
+            mlp_bias: None,
+            rope_interleaved: None,
+            tie_word_embeddings: config.tie_word_embeddings,
+            rms_norm_eps: config.rms_norm_eps,
+            rope_theta: config.rope_theta,
+            bos_token_id: Some(config.bos_token_id),
+            eos_token_id: Some(config.eos_token_id),
+        },
+    )?;
+    validate_token_id("bos_token_id", config.bos_token_id, config.vocab_size)?;
+    validate_token_id("eos_token_id", config.eos_token_id, config.vocab_size)?;
+    Ok(())
+}
+
+pub(crate) fn validate_gemma3_config(
+    config: &Gemma3Config,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    validate_identity(
+        definition,
+        Some(config.model_type.as_str()),
+        &config.architectures,
+    )?;
+    let actual = [
+        ("hidden_size", config.hidden_size),
+        ("intermediate_size", config.intermediate_size),
+        ("num_hidden_layers", config.num_hidden_layers),
+        ("num_attention_heads", config.num_attention_heads),
+        ("num_key_value_heads", config.num_key_value_heads),
+        ("head_dim", config.head_dim),
+        ("query_pre_attn_scalar", config.query_pre_attn_scalar),
+        ("sliding_window", config.sliding_window),
+        ("sliding_window_pattern", config.sliding_window_pattern),
+        ("max_position_embeddings", config.max_position_embeddings),
+        ("vocab_size", config.vocab_size),
+    ];
+    validate_dimensions(definition, &actual)?;
+    // Candle implements neither logit soft-capping nor scaled RoPE for
+    // Gemma 3, and uses a fixed local RoPE base when the GGUF omits it.
+    if config.rope_scaling.is_some()
+        || config.attn_logit_softcapping.is_some()
+        || config.final_logit_softcapping.is_some()
+        || (config.rope_local_base_freq - 10_000.0).abs() > f64::EPSILON
+    {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "config.json",
+            reason: format!(
+                "{} requires rope_local_base_freq=10000 without rope scaling or logit soft-capping",
+                definition.name
+            ),
+        });
+    }
+    let eos_tokens = config.eos_token_ids();
+    if eos_tokens.is_empty() {
+        return Err(CandleError::InvalidConfigurationValue {
+            field: "eos_token_id",
+            reason: "must contain at least one token ID".to_string(),
+        });
+    }
+    validate_config_requirements(
+        definition,
+        &ConfigValues {
+            hidden_act: Some(config.hidden_activation.as_str()),
+            attention_bias: Some(config.attention_bias),
+            mlp_bias: None,
+            rope_interleaved: None,
+            tie_word_embeddings: config.tie_word_embeddings,
+            rms_norm_eps: config.rms_norm_eps,
+            rope_theta: config.rope_theta,
+            bos_token_id: Some(config.bos_token_id),
+            eos_token_id: None,
+        },
+    )?;
+    validate_token_id("bos_token_id", config.bos_token_id, config.vocab_size)?;
+    for token in eos_tokens {
+        validate_token_id("eos_token_id", token, config.vocab_size)?;
+    }
+    Ok(())
+}
+
 pub(crate) fn validate_positive_finite(field: &'static str, value: f32) -> Result<(), CandleError> {
     if !value.is_finite() || value <= 0.0 {
         return Err(CandleError::InvalidConfigurationValue {
@@ -252,6 +438,26 @@ pub(crate) fn validate_positive_finite(field: &'static str, value: f32) -> Resul
     Ok(())
 }
 
+/// The family a config.json names outright. Llama-architecture exports
+/// without a distinguishing `model_type` are told apart by their tokenizer.
+pub(crate) fn declared_model_family(identity: &ModelIdentity) -> Option<ModelFamily> {
+    [
+        (ModelFamily::Qwen3, "qwen3", "Qwen3ForCausalLM"),
+        (ModelFamily::Mistral, "mistral", "MistralForCausalLM"),
+        (ModelFamily::Phi3, "phi3", "Phi3ForCausalLM"),
+        (ModelFamily::Gemma3, "gemma3_text", "Gemma3ForCausalLM"),
+    ]
+    .into_iter()
+    .find(|(_, model_type, architecture)| {
+        identity.model_type.as_deref() == Some(*model_type)
+            || identity
+                .architectures
+                .iter()
+                .any(|declared| declared == architecture)
+    })
+    .map(|(family, ..)| family)
+}
+
 pub(crate) fn detect_model_family(tokenizer: &Tokenizer) -> Result<ModelFamily, CandleError> {
     let llama3 = [BEGIN_OF_TEXT, START_HEADER, END_HEADER, END_OF_TURN]
         .iter()
@@ -403,33 +609,42 @@ fn validate_gguf_common(
         }
     }
     for (key, expected) in floats {
-        let actual = metadata_f64(content, key)?;
-        if (actual - expected).abs() > 1e-5 * expected.abs().max(f64::MIN_POSITIVE) {
-            return Err(CandleError::ArtifactMismatch {
-                artifact: "model.gguf",
-                reason: format!("metadata `{key}` is {actual}, but config requires {expected}"),
-            });
-        }
+        validate_gguf_float(content, key, *expected)?;
     }
     let requirements = gguf_requirements(definition)?;
     for requirement in requirements.metadata_strings {
         require_metadata_string(content, requirement.key, requirement.value)?;
     }
     validate_gguf_tokenizer_vocabulary(content, vocab_size, tokenizer, allow_padding)?;
-    for (key, token) in [
-        ("tokenizer.ggml.bos_token_id", definition.start_token),
-        ("tokenizer.ggml.eos_token_id", definition.end_token),
-    ] {
-        let actual = metadata_usize(content, key)?;
-        let expected = tokenizer
+    let token_id = |token: &'static str| {
+        tokenizer
             .token_to_id(token)
-            .ok_or(CandleError::MissingSpecialToken { token })? as usize;
-        if actual != expected {
-            return Err(CandleError::ArtifactMismatch {
-                artifact: "model.gguf",
-                reason: format!("metadata `{key}` is {actual}, but tokenizer requires {expected}"),
-            });
-        }
+            .map(|id| id as usize)
+            .ok_or(CandleError::MissingSpecialToken { token })
+    };
+    let key = "tokenizer.ggml.bos_token_id";
+    let actual = metadata_usize(content, key)?;
+    let expected = token_id(definition.start_token)?;
+    if actual != expected {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "model.gguf",
+            reason: format!("metadata `{key}` is {actual}, but tokenizer requires {expected}"),
+        });
+    }
+    let key = "tokenizer.ggml.eos_token_id";
+    let actual = metadata_usize(content, key)?;
+    let expected = requirements
+        .eos_tokens
+        .iter()
+        .map(|token| token_id(token))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !expected.contains(&actual) {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "model.gguf",
+            reason: format!(
+                "metadata `{key}` is {actual}, but tokenizer requires one of {expected:?}"
+            ),
+        });
     }
     Ok(())
 }
@@ -488,6 +703,114 @@ pub(crate) fn validate_qwen3_gguf_metadata(
     Ok(())
 }
 
+pub(crate) fn validate_phi3_gguf_metadata(
+    content: &gguf_file::Content,
+    config: &Phi3Config,
+    tokenizer: &Tokenizer,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    let dims = [
+        ("phi3.embedding_length", config.hidden_size),
+        ("phi3.feed_forward_length", config.intermediate_size),
+        ("phi3.block_count", config.num_hidden_layers),
+        ("phi3.attention.head_count", config.num_attention_heads),
+        ("phi3.attention.head_count_kv", config.num_key_value_heads),
+        ("phi3.context_length", config.max_position_embeddings),
+        (
+            "phi3.rope.dimension_count",
+            config.hidden_size / config.num_attention_heads,
+        ),
+    ];
+    let floats = [("phi3.attention.layer_norm_rms_epsilon", config.rms_norm_eps)];
+    validate_gguf_common(
+        content,
+        &dims,
+        &floats,
+        definition,
+        tokenizer,
+        config.vocab_size,
+        true,
+    )?;
+    // Candle ignores this key and always uses the 4k checkpoints' base.
+    if content.metadata.contains_key("phi3.rope.freq_base") {
+        validate_gguf_float(content, "phi3.rope.freq_base", config.rope_theta)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_gemma3_gguf_metadata(
+    content: &gguf_file::Content,
+    config: &Gemma3Config,
+    tokenizer: &Tokenizer,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    let dims = [
+        ("gemma3.embedding_length", config.hidden_size),
+        ("gemma3.feed_forward_length", config.intermediate_size),
+        ("gemma3.block_count", config.num_hidden_layers),
+        ("gemma3.attention.head_count", config.num_attention_heads),
+        ("gemma3.attention.head_count_kv", config.num_key_value_heads),
+        ("gemma3.attention.key_length", config.head_dim),
+        ("gemma3.attention.value_length", config.head_dim),
+        ("gemma3.attention.sliding_window", config.sliding_window),
+        ("gemma3.context_length", config.max_position_embeddings),
+    ];
+    let floats = [
+        ("gemma3.rope.freq_base", config.rope_theta),
+        (
+            "gemma3.attention.layer_norm_rms_epsilon",
+            config.rms_norm_eps,
+        ),
+    ];
+    validate_gguf_common(
+        content,
+        &dims,
+        &floats,
+        definition,
+        tokenizer,
+        config.vocab_size,
+        false,
+    )?;
+    // Candle falls back to the Gemma 3 defaults when these are absent, so
+    // only a present value can disagree with the configuration.
+    let key = "gemma3.attention.sliding_window_type";
+    if content.metadata.contains_key(key) {
+        let actual = metadata_usize(content, key)?;
+        if actual != config.sliding_window_pattern {
+            return Err(CandleError::ArtifactMismatch {
+                artifact: "model.gguf",
+                reason: format!(
+                    "metadata `{key}` is {actual}, but config requires {}",
+                    config.sliding_window_pattern
+                ),
+            });
+        }
+    }
+    if content.metadata.contains_key("gemma3.rope.local_freq_base") {
+        validate_gguf_float(
+            content,
+            "gemma3.rope.local_freq_base",
+            config.rope_local_base_freq,
+        )?;
+    }
+    Ok(())
+}
+
+fn validate_gguf_float(
+    content: &gguf_file::Content,
+    key: &str,
+    expected: f64,
+) -> Result<(), CandleError> {
+    let actual = metadata_f64(content, key)?;
+    if (actual - expected).abs() > 1e-5 * expected.abs().max(f64::MIN_POSITIVE) {
+        return Err(CandleError::ArtifactMismatch {
+            artifact: "model.gguf",
+            reason: format!("metadata `{key}` is {actual}, but config requires {expected}"),
+        });
+    }
+    Ok(())
+}
+
 fn gguf_requirements(
     definition: &ProfileDefinition,
 ) -> Result<&crate::profile::GgufRequirements, CandleError> {
@@ -802,6 +1125,81 @@ pub(crate) fn validate_qwen3_gguf_tensors(
     Ok(())
 }
 
+pub(crate) fn validate_phi3_gguf_tensors(
+    content: &gguf_file::Content,
+    config: &Phi3Config,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    let requirements = gguf_requirements(definition)?;
+    reject_disallowed_dtypes(content, requirements, "Phi-3 ", " in a Q4_K_M checkpoint")?;
+    // LongRoPE factors belong to the 128k variants, which Candle cannot run.
+    if let Some(name) = content
+        .tensor_infos
+        .keys()
+        .find(|name| name.starts_with("rope_factors"))
+    {
+        return Err(CandleError::InvalidQuantizedCheckpoint(format!(
+            "Phi-3 tensor `{name}` requires LongRoPE, which the 4k profile does not support"
+        )));
+    }
+    let (hidden, intermediate) = (config.hidden_size, config.intermediate_size);
+    let kv_size = (hidden / config.num_attention_heads) * config.num_key_value_heads;
+    validate_gguf_tensor(content, "token_embd.weight", &[config.vocab_size, hidden])?;
+    validate_gguf_tensor(content, "output_norm.weight", &[hidden])?;
+    validate_gguf_tensor(content, "output.weight", &[config.vocab_size, hidden])?;
+    for layer in 0..config.num_hidden_layers {
+        for (suffix, shape) in [
+            ("attn_qkv.weight", vec![hidden + 2 * kv_size, hidden]),
+            ("attn_output.weight", vec![hidden, hidden]),
+            // Gate and up projections are fused into one matrix.
+            ("ffn_up.weight", vec![2 * intermediate, hidden]),
+            ("ffn_down.weight", vec![hidden, intermediate]),
+            ("attn_norm.weight", vec![hidden]),
+            ("ffn_norm.weight", vec![hidden]),
+        ] {
+            validate_gguf_tensor(content, &format!("blk.{layer}.{suffix}"), &shape)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_gemma3_gguf_tensors(
+    content: &gguf_file::Content,
+    config: &Gemma3Config,
+    definition: &ProfileDefinition,
+) -> Result<(), CandleError> {
+    let requirements = gguf_requirements(definition)?;
+    reject_disallowed_dtypes(content, requirements, "Gemma 3 ", " in a Q4_K_M checkpoint")?;
+    let (hidden, intermediate) = (config.hidden_size, config.intermediate_size);
+    let query_size = config.num_attention_heads * config.head_dim;
+    let kv_size = config.num_key_value_heads * config.head_dim;
+    validate_gguf_tensor(content, "token_embd.weight", &[config.vocab_size, hidden])?;
+    validate_gguf_tensor(content, "output_norm.weight", &[hidden])?;
+    if content.tensor_infos.contains_key("output.weight") {
+        validate_gguf_tensor(content, "output.weight", &[config.vocab_size, hidden])?;
+    }
+    for layer in 0..config.num_hidden_layers {
+        for (suffix, shape) in [
+            ("attn_q.weight", vec![query_size, hidden]),
+            ("attn_k.weight", vec![kv_size, hidden]),
+            ("attn_v.weight", vec![kv_size, hidden]),
+            ("attn_output.weight", vec![hidden, query_size]),
+            ("attn_q_norm.weight", vec![config.head_dim]),
+            ("attn_k_norm.weight", vec![config.head_dim]),
+            ("attn_norm.weight", vec![hidden]),
+            ("post_attention_norm.weight", vec![hidden]),
+            ("ffn_norm.weight", vec![hidden]),
+            ("post_ffw_norm.weight", vec![hidden]),
+            ("ffn_gate.weight", vec![intermediate, hidden]),
+            ("ffn_up.weight", vec![intermediate, hidden]),
+            ("ffn_down.weight", vec![hidden, intermediate]),
+        ] {
+            validate_gguf_tensor(content, &format!("blk.{layer}.{suffix}"), &shape)?;
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn validate_gguf_tensor_dtype(
     content: &gguf_file::Content,
     name: &str,
@@ -12,12 +12,23 @@ use rig_candle::{CandleModel, ModelArtifacts, ModelData};
 use rig_core::completion::CompletionModel;
 
 static MODEL: OnceLock<Result<CandleModel, String>> = OnceLock::new();
+static MISTRAL_MODEL: OnceLock<Result<CandleModel, String>> = OnceLock::new();
+static PHI3_MODEL: OnceLock<Result<CandleModel, String>> = OnceLock::new();
+static GEMMA3_MODEL: OnceLock<Result<CandleModel, String>> = OnceLock::new();
 
 fn model() -> Result<CandleModel, Box<dyn std::error::Error + Send + Sync>> {
-    let result = MODEL.get_or_init(|| -> Result<CandleModel, String> {
+    model_from(&MODEL, "RIG_CANDLE_TEST_MODEL_DIR")
+}
+
+/// Load the GGUF profile whose `config.json`, `tokenizer.json` and
+/// `model.gguf` live in the directory named by `variable`.
+fn model_from(
+    slot: &'static OnceLock<Result<CandleModel, String>>,
+    variable: &str,
+) -> Result<CandleModel, Box<dyn std::error::Error + Send + Sync>> {
+    let result = slot.get_or_init(|| -> Result<CandleModel, String> {
         let directory = PathBuf::from(
-            std::env::var_os("RIG_CANDLE_TEST_MODEL_DIR")
-                .ok_or_else(|| "RIG_CANDLE_TEST_MODEL_DIR is not set".to_string())?,
+            std::env::var_os(variable).ok_or_else(|| format!("{variable} is not set"))?,
         );
         let data = ModelData {
             config: std::fs::read(directory.join("config.json"))
@@ -32,6 +43,9 @@ fn model() -> Result<CandleModel, Box<dyn std::error::Error + Send + Sync>> {
             .seed(42)
             .max_tokens(384)
             .max_concurrent_requests(1)
+            // Keep the recorded reports reproducible: every request starts
+            // from an empty KV cache.
+            .prefix_cache_tokens(0)
             .build()
             .map_err(|error| error.to_string())
     });
@@ -62,41 +76,7 @@ fn print_report(report: &rig_agent::test_utils::ScenarioReport) {
 #[ignore = "downloads are opt-in; run tests/download_qwen3.sh and set RIG_CANDLE_TEST_MODEL_DIR"]
 async fn pinned_qwen3_model_contract() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let loaded_model = model()?;
-
-    let simple = tokio::time::timeout(Duration::from_secs(300), async {
-        loaded_model
-            .raw_completion(
-                loaded_model
-                    .completion_request("Answer with only the capital of France.")
-                    .temperature(0.0)
-                    .max_tokens(32)
-                    .build(),
-            )
-            .await
-    })
-    .await??;
-    if !simple.text.contains("Paris") {
-        return Err(format!(
-            "model-quality failure in simple completion: {:?}",
-            simple.text
-        )
-        .into());
-    }
-    println!(
-        "PASS simple_buffered prompt_tokens={} generated_tokens={} tool_calls=0 duration={}ms throughput={:?} output={:?}",
-        simple.prompt_tokens,
-        simple.generated_tokens,
-        simple.generation_duration_ms,
-        simple.tokens_per_second,
-        simple.text,
-    );
-
-    let text_parity = tokio::time::timeout(
-        Duration::from_secs(600),
-        buffered_streaming_text_parity(loaded_model.clone()),
-    )
-    .await??;
-    print_report(&text_parity);
+    plain_chat_contract(loaded_model.clone()).await?;
 
     let optional = tokio::time::timeout(
         Duration::from_secs(900),
@@ -235,3 +215,110 @@ async fn pinned_qwen3_model_contract() -> Result<(), Box<dyn std::error::Error +
 
     Ok(())
 }
+
+/// The capital-of-France completion every profile must answer, buffered and
+/// streamed with identical text.
+async fn plain_chat_contract(
+    loaded_model: CandleModel,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let simple = tokio::time::timeout(Duration::from_secs(300), async {
+        loaded_model
+            .raw_completion(
+                loaded_model
+                    .completion_request("Answer with only the capital of France.")
+                    .temperature(0.0)
+                    .max_tokens(32)
+                    .build(),
+            )
+            .await
+    })
+    .await??;
+    if !simple.text.contains("Paris") {
+        return Err(format!(
+            "model-quality failure in simple completion: {:?}",
+            simple.text
+        )
+        .into());
+    }
+    println!(
+        "PASS simple_buffered prompt_tokens={} generated_tokens={} tool_calls=0 duration={}ms throughput={:?} output={:?}",
+        simple.prompt_tokens,
+        simple.generated_tokens,
+        simple.generation_duration_ms,
+        simple.tokens_per_second,
+        simple.text,
+    );
+
+    let text_parity = tokio::time::timeout(
+        Duration::from_secs(600),
+        buffered_streaming_text_parity(loaded_model),
+    )
+    .await??;
+    print_report(&text_parity);
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[ignore = "set RIG_CANDLE_TEST_MISTRAL_DIR to a Mistral-7B-Instruct-v0.3 Q4_K_M GGUF directory"]
+async fn mistral_gguf_model_contract() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let loaded_model = model_from(&MISTRAL_MODEL, "RIG_CANDLE_TEST_MISTRAL_DIR")?;
+    plain_chat_contract(loaded_model.clone()).await?;
+
+    let optional = tokio::time::timeout(
+        Duration::from_secs(900),
+        optional_argument(loaded_model.clone(), |builder| {
+            builder.temperature(0.0).max_tokens(384)
+        }),
+    )
+    .await??;
+    print_report(&optional);
+
+    let parallel = tokio::time::timeout(
+        Duration::from_secs(900),
+        parallel_tools(
+            loaded_model.clone(),
+            |builder| builder.temperature(0.0).max_tokens(384),
+            None,
+        ),
+    )
+    .await??;
+    print_report(&parallel);
+
+    let streaming = tokio::time::timeout(
+        Duration::from_secs(900),
+        streaming_tool(loaded_model.clone(), |builder| {
+            builder.temperature(0.0).max_tokens(384)
+        }),
+    )
+    .await??;
+    print_report(&streaming);
+
+    let streaming_structured = tokio::time::timeout(
+        Duration::from_secs(900),
+        streaming_structured_after_tool(loaded_model.clone(), |builder| {
+            builder.temperature(0.0).max_tokens(384)
+        }),
+    )
+    .await??;
+    print_report(&streaming_structured);
+
+    let choices = tokio::time::timeout(
+        Duration::from_secs(900),
+        rig_agent::test_utils::tool_choice_modes(loaded_model),
+    )
+    .await??;
+    print_report(&choices);
+    Ok(())
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[ignore = "set RIG_CANDLE_TEST_PHI3_DIR to a Phi-3-mini-4k-instruct Q4_K_M GGUF directory"]
+async fn phi3_gguf_model_contract() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    plain_chat_contract(model_from(&PHI3_MODEL, "RIG_CANDLE_TEST_PHI3_DIR")?).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[ignore = "set RIG_CANDLE_TEST_GEMMA3_DIR to a Gemma 3 1B instruct Q4_K_M GGUF directory"]
+async fn gemma3_gguf_model_contract() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    plain_chat_contract(model_from(&GEMMA3_MODEL, "RIG_CANDLE_TEST_GEMMA3_DIR")?).await
+}
@@ -42,6 +42,7 @@ fn terminal_response() -> CandleCompletionResponse {
     CandleCompletionResponse {
         text: "hi".to_string(),
         prompt_tokens: 10,
+        cached_prompt_tokens: 0,
         generated_tokens: 5,
         requested_max_tokens: 256,
         effective_max_tokens: 256,
@@ -0,0 +1,407 @@
+//! Provider-neutral access to asynchronous batch completion APIs.
+//!
+//! Anthropic's Message Batches and OpenAI's Batch API both trade latency for
+//! price: a batch of up to tens of thousands of requests is accepted at once,
+//! processed within 24 hours, and billed at half the synchronous rate. The two
+//! wires differ — Anthropic takes the requests inline, OpenAI takes a JSONL
+//! file upload — but the lifecycle is the same, so rig models it once:
+//!
+//! 1. [`BatchCompletionClient::submit_batch`] turns [`BatchRequest`]s (an
+//!    ordinary [`CompletionRequest`] plus a caller-chosen `custom_id`) into a
+//!    provider batch and returns its [`Batch`] handle.
+//! 2. [`BatchCompletionClient::batch`] polls it; [`BatchStatus::is_terminal`]
+//!    says when to stop.
+//! 3. [`BatchCompletionClient::batch_results`] fetches one [`BatchResult`] per
+//!    request, each carrying the same normalized [`CompletionResponse`] the
+//!    synchronous path produces.
+//!
+//! Results are **not** returned in submission order by either provider; match
+//! them back to their inputs by `custom_id`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rig_core::client::ProviderClient;
+//! use rig_core::client::batch::{BatchCompletionClient, BatchOutcome, BatchRequest};
+//! use rig_core::providers::anthropic;
+//!
+//! let client = anthropic::Client::from_env()?;
+//! let requests = prompts
+//!     .iter()
+//!     .enumerate()
+//!     .map(|(i, prompt)| BatchRequest::new(format!("prompt-{i}"), build_request(prompt)))
+//!     .collect();
+//!
+//! let batch = client
+//!     .submit_batch(anthropic::completion::CLAUDE_HAIKU_4_5, requests)
+//!     .await?;
+//!
+//! // ...later, e.g. from the next scheduled run...
+//! let batch = client.batch(&batch.id).await?;
+//! if batch.status.is_terminal() {
+//!     for result in client.batch_results(&batch.id).await? {
+//!         match result.outcome {
+//!             BatchOutcome::Succeeded(response) => store(&result.custom_id, *response),
+//!             other => eprintln!("{} did not complete: {other:?}", result.custom_id),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    completion::{CompletionError, CompletionRequest, CompletionResponse},
+    http_client, provider_response,
+    wasm_compat::WasmCompatSend,
+};
+
+/// Errors from batch submission, polling, cancellation and result retrieval.
+///
+/// A request that *failed inside* an otherwise healthy batch is not an error
+/// here: it is reported per request as [`BatchOutcome::Errored`].
+#[derive(Debug, Error)]
+pub enum BatchError {
+    /// The batch was rejected before anything was sent: it was empty, two
+    /// requests shared a `custom_id`, or an id was not one the provider
+    /// accepts.
+    #[error("invalid batch: {0}")]
+    InvalidBatch(String),
+    /// A [`CompletionRequest`] in the batch could not be converted to the
+    /// provider's wire format.
+    #[error("batch request `{custom_id}` is invalid: {source}")]
+    InvalidRequest {
+        custom_id: String,
+        #[source]
+        source: CompletionError,
+    },
+    /// Results were requested for a batch that has not finished yet.
+    #[error("batch `{id}` has no results yet (status: {status})")]
+    NotReady { id: String, status: BatchStatus },
+    /// The provider answered with something that is not a batch payload.
+    #[error("provider error: {0}")]
+    ProviderError(String),
+    /// Raw error response preserved from the provider
+    #[error("provider response error: {0}")]
+    ProviderResponse(provider_response::ProviderResponseError),
+    #[error("http error: {0}")]
+    HttpError(
+        #[from]
+        #[source]
+        http_client::Error,
+    ),
+    #[error("json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+crate::provider_response::impl_provider_response_helpers!(BatchError);
+
+impl From<http::Error> for BatchError {
+    fn from(error: http::Error) -> Self {
+        Self::HttpError(error.into())
+    }
+}
+
+/// One request in a batch.
+#[derive(Clone, Debug)]
+pub struct BatchRequest {
+    /// Caller-chosen identifier, unique within the batch, that the matching
+    /// [`BatchResult`] carries back.
+    ///
+    /// Anthropic restricts it to 1–64 ASCII letters, digits, `-` and `_`;
+    /// keeping to that alphabet keeps a batch portable between providers.
+    pub custom_id: String,
+    /// The request, exactly as it would be sent to the synchronous endpoint.
+    pub request: CompletionRequest,
+}
+
+impl BatchRequest {
+    pub fn new(custom_id: impl Into<String>, request: CompletionRequest) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            request,
+        }
+    }
+}
+
+/// Where a batch is in its lifecycle.
+///
+/// The union of both providers' states. Anthropic only ever reports
+/// [`Self::InProgress`], [`Self::Cancelling`] and [`Self::Completed`] (its
+/// `ended`); the others are OpenAI's.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// The provider is checking the uploaded input.
+    Validating,
+    /// Requests are being processed.
+    InProgress,
+    /// Processing is done and the provider is writing results.
+    Finalizing,
+    /// A cancel was requested; in-flight requests are being wound down.
+    Cancelling,
+    /// Processing ended. Individual requests may still have errored, been
+    /// cancelled or expired — see [`BatchOutcome`].
+    Completed,
+    /// The batch was cancelled. Requests that finished before the cancel
+    /// still have results.
+    Cancelled,
+    /// The batch did not finish within the provider's window. Requests that
+    /// finished in time still have results.
+    Expired,
+    /// The batch as a whole was rejected, usually because its input failed
+    /// validation. See [`Batch::errors`].
+    Failed,
+}
+
+impl BatchStatus {
+    /// Whether the batch will change no further, so polling can stop.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::Cancelled | Self::Expired | Self::Failed
+        )
+    }
+}
+
+impl std::fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Validating => "validating",
+            Self::InProgress => "in_progress",
+            Self::Finalizing => "finalizing",
+            Self::Cancelling => "cancelling",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+            Self::Expired => "expired",
+            Self::Failed => "failed",
+        })
+    }
+}
+
+/// How many of a batch's requests are in each state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRequestCounts {
+    /// Not finished yet.
+    pub processing: u64,
+    pub succeeded: u64,
+    pub errored: u64,
+    /// Always zero for OpenAI, which counts cancelled requests as errored.
+    pub cancelled: u64,
+    /// Always zero for OpenAI, which counts expired requests as errored.
+    pub expired: u64,
+}
+
+impl BatchRequestCounts {
+    /// Every request in the batch, whatever its state.
+    pub fn total(&self) -> u64 {
+        self.processing + self.succeeded + self.errored + self.cancelled + self.expired
+    }
+}
+
+/// A submitted batch, as last reported by the provider.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Batch {
+    /// Provider-assigned id (`msgbatch_…`, `batch_…`). Persist this to pick
+    /// the batch up again from another process.
+    pub id: String,
+    pub status: BatchStatus,
+    pub request_counts: BatchRequestCounts,
+    /// Creation time as the provider reported it: an RFC 3339 string for
+    /// Anthropic, Unix seconds for OpenAI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Batch-level errors, e.g. input validation failures behind
+    /// [`BatchStatus::Failed`]. Per-request failures are in the results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    /// The provider's batch object, verbatim.
+    #[serde(default)]
+    pub raw: serde_json::Value,
+}
+
+/// The result of one [`BatchRequest`].
+#[derive(Clone, Debug)]
+pub struct BatchResult {
+    /// The [`BatchRequest::custom_id`] this result answers.
+    pub custom_id: String,
+    pub outcome: BatchOutcome,
+}
+
+/// What happened to one request in a batch.
+#[derive(Clone, Debug)]
+pub enum BatchOutcome {
+    /// The request completed. The response is normalized exactly as the
+    /// synchronous [`crate::completion::CompletionModel::completion`] would
+    /// have returned it, with the provider's payload in
+    /// [`CompletionResponse::raw`].
+    Succeeded(Box<CompletionResponse>),
+    /// The provider rejected or failed the request.
+    Errored {
+        message: String,
+        /// The provider's error object, verbatim.
+        raw: serde_json::Value,
+    },
+    /// The batch was cancelled before this request ran.
+    Cancelled,
+    /// The batch expired before this request ran.
+    Expired,
+}
+
+impl BatchOutcome {
+    /// The response, if the request succeeded.
+    pub fn response(&self) -> Option<&CompletionResponse> {
+        match self {
+            Self::Succeeded(response) => Some(response),
+            _ => None,
+        }
+    }
+}
+
+/// A provider client that can run completions through a batch API.
+///
+/// Implemented for [`crate::providers::anthropic::Client`] (Message Batches)
+/// and [`crate::providers::openai::Client`] (`/v1/batches` over the Responses
+/// endpoint).
+pub trait BatchCompletionClient {
+    /// Submit `requests` as one batch.
+    ///
+    /// `model` is used for every request that does not name its own through
+    /// [`CompletionRequest::model`], and provider defaults (such as
+    /// Anthropic's `max_tokens`) are applied exactly as
+    /// [`crate::client::CompletionClient::completion_model`] would apply them.
+    /// Every request is converted before anything is sent, so one malformed
+    /// request fails the submission with [`BatchError::InvalidRequest`]
+    /// rather than leaving a partial batch behind.
+    fn submit_batch(
+        &self,
+        model: &str,
+        requests: Vec<BatchRequest>,
+    ) -> impl Future<Output = Result<Batch, BatchError>> + WasmCompatSend;
+
+    /// Fetch the current state of a batch.
+    fn batch(&self, id: &str) -> impl Future<Output = Result<Batch, BatchError>> + WasmCompatSend;
+
+    /// Ask the provider to cancel a batch and return its updated state.
+    ///
+    /// Cancellation is asynchronous: the batch passes through
+    /// [`BatchStatus::Cancelling`], and requests already running still
+    /// finish and are billed.
+    fn cancel_batch(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<Batch, BatchError>> + WasmCompatSend;
+
+    /// Fetch one [`BatchResult`] per request of a finished batch.
+    ///
+    /// Fails with [`BatchError::NotReady`] while the batch is still running.
+    fn batch_results(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<Vec<BatchResult>, BatchError>> + WasmCompatSend;
+}
+
+/// Reject an empty batch and duplicate `custom_id`s before anything is sent.
+///
+/// A duplicate would make two results indistinguishable, and both providers
+/// only report that after the upload — OpenAI by failing the whole batch
+/// asynchronously.
+pub(crate) fn validate_batch_requests(requests: &[BatchRequest]) -> Result<(), BatchError> {
+    if requests.is_empty() {
+        return Err(BatchError::InvalidBatch(
+            "a batch needs at least one request".to_owned(),
+        ));
+    }
+    let mut seen = HashSet::with_capacity(requests.len());
+    for request in requests {
+        if request.custom_id.is_empty() {
+            return Err(BatchError::InvalidBatch(
+                "a batch request's `custom_id` must not be empty".to_owned(),
+            ));
+        }
+        if !seen.insert(request.custom_id.as_str()) {
+            return Err(BatchError::InvalidBatch(format!(
+                "`custom_id` `{}` is used by more than one request",
+                request.custom_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that a provider batch or file id can be spliced into a path.
+///
+/// Provider ids are ASCII alphanumerics, `-` and `_`; anything else (a `/`,
+/// `?` or `..`) would address a different resource than the caller meant.
+pub(crate) fn validate_resource_id<'a>(kind: &str, id: &'a str) -> Result<&'a str, BatchError> {
+    if !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+    {
+        Ok(id)
+    } else {
+        Err(BatchError::InvalidBatch(format!(
+            "`{id}` is not a valid {kind} id"
+        )))
+    }
+}
+
+/// Split a JSONL body into its non-blank lines, decoding each as `T`.
+pub(crate) fn parse_jsonl<T>(body: &[u8]) -> Result<Vec<T>, BatchError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    body.split(|byte| *byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| serde_json::from_slice(line).map_err(BatchError::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::CompletionRequestBuilder;
+    use crate::test_utils::MockCompletionModel;
+
+    fn request(custom_id: &str) -> BatchRequest {
+        BatchRequest::new(
+            custom_id,
+            CompletionRequestBuilder::new(MockCompletionModel::default(), "hi").build(),
+        )
+    }
+
+    #[test]
+    fn rejects_empty_batches_and_duplicate_ids() {
+        assert!(matches!(
+            validate_batch_requests(&[]),
+            Err(BatchError::InvalidBatch(_))
+        ));
+        assert!(matches!(
+            validate_batch_requests(&[request("a"), request("b"), request("a")]),
+            Err(BatchError::InvalidBatch(message)) if message.contains("`a`")
+        ));
+        assert!(validate_batch_requests(&[request("a"), request("b")]).is_ok());
+    }
+
+    #[test]
+    fn resource_ids_cannot_escape_their_path() {
+        assert!(validate_resource_id("batch", "msgbatch_01Hk-9").is_ok());
+        for id in ["", "../files", "batch_1?limit=1", "a/b"] {
+            assert!(
+                validate_resource_id("batch", id).is_err(),
+                "`{id}` should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn jsonl_skips_blank_lines() {
+        let rows: Vec<serde_json::Value> =
+            parse_jsonl(b"{\"a\":1}\n\n{\"a\":2}\r\n").expect("valid jsonl");
+        assert_eq!(rows.len(), 2);
+    }
+}
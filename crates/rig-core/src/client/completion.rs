@@ -166,6 +166,7 @@ mod tests {
             #[cfg(feature = "audio")]
             type AudioGeneration = Nothing;
             type Rerank = Nothing;
+            type Moderation = Nothing;
         }
 
         impl DebugExt for ExternalExt {}
@@ -0,0 +1,275 @@
+//! Provider-neutral access to provider file storage.
+//!
+//! Inline [`DocumentSourceKind::Base64`] bodies are re-sent, and re-billed for
+//! bandwidth, on every request that carries them — which for a 30 MB PDF or a
+//! video quickly dominates a conversation. OpenAI, Anthropic and Gemini all
+//! let a file be uploaded once and referenced by id afterwards:
+//!
+//! 1. [`FilesClient::upload_file`] stores a [`FileUpload`] and returns its
+//!    [`ProviderFile`].
+//! 2. [`ProviderFile::source`] is a [`DocumentSourceKind::FileId`] that goes
+//!    anywhere a document (or, where the provider allows it, an image) body
+//!    does. Each provider's message conversion serializes it in its own wire
+//!    form, and the providers that cannot dereference a file id reject it
+//!    with a [`crate::message::MessageError::ConversionError`].
+//! 3. [`FilesClient::delete_file`] removes it when it is no longer needed.
+//!
+//! A file id is only meaningful to the provider — and the account — that
+//! issued it.
+//!
+//! Provider notes:
+//!
+//! - **OpenAI** uploads with `purpose=user_data`, the purpose for model inputs.
+//! - **Anthropic**'s Files API is in beta. The files endpoints are sent with
+//!   the `files-api-2025-04-14` beta header automatically, but a Messages
+//!   request that references a file needs it too: build the client with
+//!   `.anthropic_beta(anthropic::files::FILES_API_BETA)`.
+//! - **Gemini** deletes files after 48 hours, and video and audio are only
+//!   usable once the file's `state` (in [`ProviderFile::raw`]) is `ACTIVE`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rig_core::client::ProviderClient;
+//! use rig_core::client::files::{FileUpload, FilesClient};
+//! use rig_core::message::{Document, DocumentMediaType, UserContent};
+//! use rig_core::providers::openai;
+//!
+//! let client = openai::Client::from_env()?;
+//! let file = client
+//!     .upload_file(FileUpload::new("report.pdf", "application/pdf", std::fs::read("report.pdf")?))
+//!     .await?;
+//!
+//! let document = UserContent::Document(Document {
+//!     data: file.source(),
+//!     media_type: Some(DocumentMediaType::PDF),
+//!     additional_params: None,
+//! });
+//! ```
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    http_client, message::DocumentSourceKind, provider_response, wasm_compat::WasmCompatSend,
+};
+
+/// Errors from uploading, listing, fetching and deleting provider files.
+#[derive(Debug, Error)]
+pub enum FilesError {
+    /// The upload or file id was rejected before anything was sent.
+    #[error("invalid file: {0}")]
+    InvalidFile(String),
+    /// The provider answered with something that is not a file payload.
+    #[error("provider error: {0}")]
+    ProviderError(String),
+    /// Raw error response preserved from the provider
+    #[error("provider response error: {0}")]
+    ProviderResponse(provider_response::ProviderResponseError),
+    #[error("http error: {0}")]
+    HttpError(
+        #[from]
+        #[source]
+        http_client::Error,
+    ),
+    #[error("json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+crate::provider_response::impl_provider_response_helpers!(FilesError);
+
+impl From<http::Error> for FilesError {
+    fn from(error: http::Error) -> Self {
+        Self::HttpError(error.into())
+    }
+}
+
+/// A file to upload.
+#[derive(Clone, Debug)]
+pub struct FileUpload {
+    /// The name the provider stores the file under. Providers use it for
+    /// display only; it need not be unique.
+    pub filename: String,
+    /// The file's MIME type, e.g. `application/pdf` or `video/mp4`.
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl FileUpload {
+    pub fn new(
+        filename: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            filename: filename.into(),
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Reject an upload no provider would accept, before its bytes are sent.
+    pub(crate) fn validate(&self) -> Result<mime::Mime, FilesError> {
+        if self.data.is_empty() {
+            return Err(FilesError::InvalidFile(format!(
+                "`{}` is empty",
+                self.filename
+            )));
+        }
+        // The filename ends up in a `Content-Disposition` parameter; a quote
+        // or a line break there would corrupt the multipart framing.
+        if self.filename.is_empty()
+            || self
+                .filename
+                .chars()
+                .any(|ch| ch.is_control() || matches!(ch, '"' | '\\'))
+        {
+            return Err(FilesError::InvalidFile(format!(
+                "`{}` is not a usable filename",
+                self.filename.escape_debug()
+            )));
+        }
+        self.mime_type.parse().map_err(|error| {
+            FilesError::InvalidFile(format!("`{}` is not a MIME type: {error}", self.mime_type))
+        })
+    }
+}
+
+/// A file stored with a provider, as last reported by it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProviderFile {
+    /// The id to reference the file by (`file-…` for OpenAI, `file_…` for
+    /// Anthropic, `files/…` for Gemini).
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Not reported by OpenAI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Creation time as the provider reported it: Unix seconds for OpenAI, an
+    /// RFC 3339 string for Anthropic and Gemini.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// When the provider will delete the file on its own, if it will.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// The provider's file object, verbatim.
+    #[serde(default)]
+    pub raw: serde_json::Value,
+}
+
+impl ProviderFile {
+    /// A document or image body that references this file.
+    pub fn source(&self) -> DocumentSourceKind {
+        DocumentSourceKind::FileId(self.id.clone())
+    }
+}
+
+/// A provider client that can store files for later reference.
+///
+/// Implemented for [`crate::providers::openai::Client`],
+/// [`crate::providers::anthropic::Client`] and
+/// [`crate::providers::gemini::Client`].
+pub trait FilesClient {
+    /// Upload a file and return its stored metadata.
+    fn upload_file(
+        &self,
+        upload: FileUpload,
+    ) -> impl Future<Output = Result<ProviderFile, FilesError>> + WasmCompatSend;
+
+    /// Every file this credential can see, following pagination.
+    fn list_files(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ProviderFile>, FilesError>> + WasmCompatSend;
+
+    /// Fetch one file's metadata.
+    fn file(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<ProviderFile, FilesError>> + WasmCompatSend;
+
+    /// Delete a file. Requests already sent that reference it are unaffected;
+    /// later ones fail at the provider.
+    fn delete_file(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<(), FilesError>> + WasmCompatSend;
+}
+
+/// The most pages a [`FilesClient::list_files`] implementation follows before
+/// returning what it has. Every provider's page holds at least a hundred
+/// files, so this is a guard against a cursor that never ends rather than a
+/// limit anyone should reach.
+pub(crate) const MAX_LISTING_PAGES: usize = 1_000;
+
+/// Check that a provider file id can be spliced into a path.
+///
+/// OpenAI and Anthropic ids are ASCII alphanumerics, `-` and `_`; anything
+/// else (a `/`, `?` or `..`) would address a different resource than the
+/// caller meant — and one of the paths this guards is the one that deletes.
+pub(crate) fn validate_file_id(id: &str) -> Result<&str, FilesError> {
+    if !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+    {
+        Ok(id)
+    } else {
+        Err(FilesError::InvalidFile(format!(
+            "`{id}` is not a valid file id"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uploads_are_checked_before_sending() {
+        let valid = FileUpload::new("report.pdf", "application/pdf", b"%PDF".to_vec());
+        assert_eq!(
+            valid.validate().expect("valid upload"),
+            mime::APPLICATION_PDF
+        );
+
+        for upload in [
+            FileUpload::new("report.pdf", "application/pdf", Vec::new()),
+            FileUpload::new("", "application/pdf", b"%PDF".to_vec()),
+            FileUpload::new("a\"b.pdf", "application/pdf", b"%PDF".to_vec()),
+            FileUpload::new("a\r\nb.pdf", "application/pdf", b"%PDF".to_vec()),
+            FileUpload::new("report.pdf", "not a mime type", b"%PDF".to_vec()),
+        ] {
+            assert!(
+                matches!(upload.validate(), Err(FilesError::InvalidFile(_))),
+                "{upload:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn file_ids_that_would_retarget_the_path_are_rejected() {
+        assert_eq!(validate_file_id("file-abc_123").ok(), Some("file-abc_123"));
+        for id in ["", "file/../x", "file?purpose=x", "file#x", "file x"] {
+            assert!(validate_file_id(id).is_err(), "`{id}` should be rejected");
+        }
+    }
+
+    #[test]
+    fn a_provider_file_is_a_file_id_source() {
+        let file = ProviderFile {
+            id: "file_011".to_owned(),
+            filename: None,
+            mime_type: None,
+            size_bytes: None,
+            created_at: None,
+            expires_at: None,
+            raw: serde_json::Value::Null,
+        };
+        assert_eq!(file.source(), DocumentSourceKind::FileId("file_011".into()));
+    }
+}
@@ -2,19 +2,25 @@
 //! Clients are used to create models for completion, embeddings, etc.
 
 pub mod audio_generation;
+pub mod batch;
 pub mod completion;
 pub mod embeddings;
+pub mod files;
 pub mod image_generation;
 pub mod model_listing;
+pub mod moderation;
 pub mod rerank;
 pub mod transcription;
 pub mod verify;
 
+pub use batch::{BatchCompletionClient, BatchError};
 use bytes::Bytes;
 pub use completion::{CompletionClient, ConstructCompletionModel};
 pub use embeddings::{ConstructEmbeddingModel, EmbeddingsClient};
+pub use files::{FilesClient, FilesError};
 use http::{HeaderMap, HeaderName, HeaderValue};
 pub use model_listing::{ConstructModelLister, ModelLister, ModelListingClient};
+pub use moderation::{ConstructModerationModel, ModerationClient};
 pub use rerank::{ConstructRerankModel, RerankingClient};
 use std::{env::VarError, fmt::Debug, marker::PhantomData, sync::Arc};
 use thiserror::Error;
@@ -37,8 +43,10 @@ use crate::{
     http_client::BoxedHttpClient,
     http_client::{
         self, Builder, HttpClientExt, LazyBody, MultipartForm, Request, Response, make_auth_header,
+        retry::RequestRetry,
     },
     markers::Missing,
+    moderation::ModerationModel,
     prelude::TranscriptionClient,
     rerank::RerankModel,
     transcription::TranscriptionModel,
@@ -221,7 +229,10 @@ impl ApiKey for Nothing {}
 pub struct Client<Ext = Nothing, H = BoxedHttpClient> {
     base_url: Arc<str>,
     headers: Arc<HeaderMap>,
-    http_client: H,
+    // Shared so a retried request can be resent from the `'static` future
+    // `HttpClientExt::send` returns without requiring `H: Clone`.
+    http_client: Arc<H>,
+    retry: Option<Arc<RequestRetry>>,
     ext: Ext,
 }
 
@@ -257,7 +268,8 @@ where
                     })
                     .collect::<Vec<(&HeaderName, &HeaderValue)>>(),
             )
-            .field("http_client", &self.http_client);
+            .field("http_client", &self.http_client)
+            .field("retry", &self.retry);
 
         self.ext
             .fields()
@@ -328,6 +340,8 @@ pub trait Capabilities<H> {
     type Embeddings: Capability;
     /// Rerank model capability marker.
     type Rerank: Capability;
+    /// Moderation model capability marker.
+    type Moderation: Capability;
     /// Audio transcription model capability marker.
     type Transcription: Capability;
     /// Model listing capability marker.
@@ -430,6 +444,7 @@ macro_rules! impl_capabilities {
         $(, image_generation = $image_generation:ty)?
         $(, audio_generation = $audio_generation:ty)?
         $(, rerank = $rerank:ty)?
+        $(, moderation = $moderation:ty)?
         $(,)?
     ) => {
         impl<H> $crate::client::Capabilities<H> for $ext {
@@ -442,6 +457,7 @@ macro_rules! impl_capabilities {
             #[cfg(feature = "audio")]
             type AudioGeneration = $crate::client::impl_capabilities!(@slot $($audio_generation)?);
             type Rerank = $crate::client::impl_capabilities!(@slot $($rerank)?);
+            type Moderation = $crate::client::impl_capabilities!(@slot $($moderation)?);
         }
     };
     (@slot $model:ty) => { $crate::client::Capable<$model> };
@@ -614,7 +630,8 @@ where
         Client {
             base_url: self.base_url,
             headers: self.headers,
-            http_client: BoxedHttpClient::new(self.http_client),
+            http_client: Arc::new(BoxedHttpClient::from_arc(self.http_client)),
+            retry: self.retry,
             ext: self.ext,
         }
     }
@@ -643,12 +660,19 @@ impl<Ext, H> Client<Ext, H> {
         &self.http_client
     }
 
+    /// Returns the retry policy applied to unary requests, if one was
+    /// configured with [`ClientBuilder::retry`].
+    pub fn retry(&self) -> Option<&RequestRetry> {
+        self.retry.as_deref()
+    }
+
     /// Reuse this client's base URL, headers, and HTTP backend with a different extension.
     pub fn with_ext<NewExt>(self, new_ext: NewExt) -> Client<NewExt, H> {
         Client {
             base_url: self.base_url,
             headers: self.headers,
             http_client: self.http_client,
+            retry: self.retry,
             ext: new_ext,
         }
     }
@@ -668,12 +692,21 @@ where
         U: From<Bytes>,
         U: WasmCompatSend + 'static,
     {
-        req.headers_mut().insert(
-            http::header::CONTENT_TYPE,
-            http::HeaderValue::from_static("application/json"),
-        );
-
-        self.http_client.send(req)
+        // Provider bodies are JSON unless the request says otherwise (e.g. a
+        // `multipart/related` file upload).
+        req.headers_mut()
+            .entry(http::header::CONTENT_TYPE)
+            .or_insert(http::HeaderValue::from_static("application/json"));
+
+        let req = req.map(Into::<Bytes>::into);
+        let http_client = Arc::clone(&self.http_client);
+        let retry = self.retry.clone();
+        async move {
+            match retry {
+                Some(retry) => retry.send(&*http_client, req).await,
+                None => http_client.send(req).await,
+            }
+        }
     }
 
     fn send_multipart<U>(
@@ -694,10 +727,9 @@ where
     where
         T: Into<Bytes> + WasmCompatSend,
     {
-        req.headers_mut().insert(
-            http::header::CONTENT_TYPE,
-            http::HeaderValue::from_static("application/json"),
-        );
+        req.headers_mut()
+            .entry(http::header::CONTENT_TYPE)
+            .or_insert(http::HeaderValue::from_static("application/json"));
 
         self.http_client.send_streaming(req)
     }
@@ -901,6 +933,7 @@ pub struct ClientBuilder<Ext, ApiKey = Missing, H = Missing> {
     api_key: ApiKey,
     headers: HeaderMap,
     http_client: H,
+    retry: Option<RequestRetry>,
     ext: Ext,
 }
 
@@ -914,6 +947,7 @@ where
             headers: Default::default(),
             base_url: ExtBuilder::BASE_URL.into(),
             http_client: Missing,
+            retry: None,
             ext: Default::default(),
         }
     }
@@ -928,6 +962,7 @@ impl<Ext, H> ClientBuilder<Ext, Missing, H> {
             base_url: self.base_url,
             headers: self.headers,
             http_client: self.http_client,
+            retry: self.retry,
             ext: self.ext,
         }
     }
@@ -947,6 +982,7 @@ where
             api_key,
             headers,
             http_client,
+            retry,
             ext,
         } = self;
 
@@ -957,6 +993,7 @@ where
             api_key,
             headers,
             http_client,
+            retry,
             ext: new_ext,
         }
     }
@@ -982,6 +1019,7 @@ where
             base_url: self.base_url,
             api_key: self.api_key,
             headers: self.headers,
+            retry: self.retry,
             ext: self.ext,
         }
     }
@@ -991,6 +1029,24 @@ where
         Self { headers, ..self }
     }
 
+    /// Retry unary requests (completions, embeddings, reranking and other
+    /// non-streaming calls) that fail with a transient status, as described
+    /// on [`RequestRetry`]. Streaming and multipart requests are never
+    /// retried.
+    ///
+    /// ```rust,ignore
+    /// let client = openai::Client::builder()
+    ///     .api_key(key)
+    ///     .retry(RequestRetry::default().max_elapsed(Some(Duration::from_secs(20))))
+    ///     .build()?;
+    /// ```
+    pub fn retry(self, retry: RequestRetry) -> Self {
+        Self {
+            retry: Some(retry),
+            ..self
+        }
+    }
+
     pub(crate) fn headers_mut(&mut self) -> &mut HeaderMap {
         &mut self.headers
     }
@@ -1038,6 +1094,7 @@ where
             base_url,
             mut headers,
             api_key,
+            retry,
             ..
         } = self;
 
@@ -1048,9 +1105,10 @@ where
         }
 
         Ok(Client {
-            http_client,
+            http_client: Arc::new(http_client),
             base_url: Arc::from(base_url.as_str()),
             headers: Arc::new(headers),
+            retry: retry.map(Arc::new),
             ext,
         })
     }
@@ -1119,6 +1177,14 @@ impl_capability_client!(RerankingClient {
     ConstructRerankModel
 });
 
+impl_capability_client!(ModerationClient {
+    Moderation,
+    ModerationModel,
+    moderation_model,
+    ModerationModel,
+    ConstructModerationModel
+});
+
 impl_capability_client!(TranscriptionClient {
     Transcription,
     TranscriptionModel,
@@ -1296,6 +1362,7 @@ mod tests {
 mod external_modality_extension_probe {
     use super::*;
     use crate::embeddings::{EmbeddingError, EmbeddingModel, EmbeddingResponse};
+    use crate::moderation::{ModerationError, ModerationModel, ModerationResponse};
     use crate::rerank::{RerankError, RerankModel, RerankResponse};
     use crate::transcription::{
         TranscriptionError, TranscriptionModel, TranscriptionRequest, TranscriptionResponse,
@@ -1340,6 +1407,7 @@ mod external_modality_extension_probe {
         #[cfg(feature = "audio")]
         type AudioGeneration = Capable<ExternalModel<H>>;
         type Rerank = Capable<ExternalModel<H>>;
+        type Moderation = Capable<ExternalModel<H>>;
     }
 
     impl DebugExt for ExternalExt {}
@@ -1440,6 +1508,31 @@ mod external_modality_extension_probe {
         }
     }
 
+    impl<H> ModerationModel for ExternalModel<H>
+    where
+        H: Send + Sync + 'static,
+    {
+        async fn moderate(
+            &self,
+            _inputs: Vec<String>,
+        ) -> Result<ModerationResponse, ModerationError> {
+            Err(ModerationError::ResponseError(self.model.clone()))
+        }
+    }
+
+    impl<H> ConstructModerationModel<Client<ExternalExt, H>> for ExternalModel<H>
+    where
+        H: Clone,
+    {
+        fn construct(client: &Client<ExternalExt, H>, model: String) -> Self {
+            Self {
+                _client: client.clone(),
+                model,
+                ndims: None,
+            }
+        }
+    }
+
     #[cfg(feature = "image")]
     impl<H> ImageGenerationModel for ExternalModel<H>
     where
@@ -1528,6 +1621,7 @@ mod external_modality_extension_probe {
         fn assert_transcription<C: TranscriptionClient>() {}
         fn assert_embeddings<C: EmbeddingsClient>() {}
         fn assert_rerank<C: RerankingClient>() {}
+        fn assert_moderation<C: ModerationClient>() {}
         fn assert_listing<C: ModelListingClient>() {}
         #[cfg(feature = "image")]
         fn assert_image<C: ImageGenerationClient>() {}
@@ -1538,6 +1632,7 @@ mod external_modality_extension_probe {
         assert_transcription::<ExternalClient>();
         assert_embeddings::<ExternalClient>();
         assert_rerank::<ExternalClient>();
+        assert_moderation::<ExternalClient>();
         assert_listing::<ExternalClient>();
         #[cfg(feature = "image")]
         assert_image::<ExternalClient>();
@@ -0,0 +1,18 @@
+use crate::moderation::ModerationModel;
+
+/// A provider client with content moderation capabilities.
+pub trait ModerationClient {
+    /// The type of [`ModerationModel`] used by the Client.
+    type ModerationModel: ModerationModel;
+
+    /// Create a moderation model with the given model identifier.
+    fn moderation_model(&self, model: impl Into<String>) -> Self::ModerationModel;
+}
+
+/// Construction hook for the blanket [`ModerationClient`] implementation over
+/// [`crate::client::Client`] — the moderation twin of
+/// [`crate::client::ConstructRerankModel`], public for the same reason.
+pub trait ConstructModerationModel<C>: Sized {
+    /// Build this model from its provider client and a model identifier.
+    fn construct(client: &C, model: String) -> Self;
+}
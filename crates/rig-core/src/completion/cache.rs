@@ -0,0 +1,1066 @@
+//! Response caching for completion models.
+//!
+//! [`CachedCompletionModel`] wraps any [`CompletionModel`] and answers
+//! repeated requests from a [`ResponseCache`] instead of calling the
+//! provider again — useful for evaluation and regression pipelines that send
+//! the same prompts over and over.
+//!
+//! Requests are keyed by a SHA-256 hash of their canonical JSON form: chat
+//! history, preamble, documents, tools, tool choice, output schema, sampling
+//! parameters and `additional_params`, with object keys sorted so that
+//! logically equal requests hash equally. The local
+//! [`record_telemetry_content`](super::CompletionRequest::record_telemetry_content)
+//! policy is not part of the key.
+//!
+//! ```
+//! use rig_core::completion::{
+//!     CompletionModel,
+//!     cache::{CachedCompletionModel, InMemoryResponseCache},
+//! };
+//! use rig_core::test_utils::MockCompletionModel;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let model = CachedCompletionModel::new(
+//!     MockCompletionModel::text("Paris"),
+//!     InMemoryResponseCache::new(1_000),
+//! );
+//!
+//! let first = model.completion_request("Capital of France?").send().await?;
+//! // Served from the cache: the mock has no second turn scripted.
+//! let second = model.completion_request("Capital of France?").send().await?;
+//! assert_eq!(first.choice, second.choice);
+//! assert_eq!(model.hits(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Backends: [`InMemoryResponseCache`] (bounded LRU), [`FileResponseCache`]
+//! (one JSON file per entry) and, in the `rig-sqlite` companion crate,
+//! `SqliteResponseCache`.
+//!
+//! # Near-duplicate lookup
+//!
+//! With [`CachedCompletionModel::semantic_lookup`], an exact-key miss falls
+//! back to comparing the *prompt* — the text of the last user message —
+//! against the prompts of cached entries by embedding similarity. Only
+//! entries whose request matched in everything but the prompt are compared,
+//! so a near-duplicate hit never crosses a different history, tool set or
+//! sampling configuration.
+//!
+//! # Streaming
+//!
+//! A streamed response is cached once its stream completes without an error
+//! and with a terminal record; a stream that is dropped or cancelled early is
+//! not cached. A hit on [`CompletionModel::stream`] is replayed as a
+//! [`StreamingCompletionResponse`] whose events and aggregated choice match
+//! the cached response. Unary and streamed calls share entries.
+//!
+//! Cache failures never fail a completion: they are logged and the request
+//! goes to the wrapped model.
+
+use std::{
+    fmt::Write as _,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::StreamExt;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{
+    AssistantContent, CompletionError, CompletionModel, CompletionRequest, CompletionResponse,
+    Message, ProviderCapabilities,
+    message::{Text, UserContent},
+};
+use crate::{
+    embeddings::{EmbeddingModel, EmbeddingModelHandle},
+    streaming::{
+        MintKind, RawStreamingChoice, RawStreamingToolCall, StreamFinal, StreamPartId,
+        StreamingCompletionResponse, StreamingResult, WireId,
+    },
+    wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync},
+};
+
+/// Boxed error source for response cache backend failures.
+#[cfg(not(target_family = "wasm"))]
+pub type ResponseCacheBackendError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Boxed error source for response cache backend failures.
+#[cfg(target_family = "wasm")]
+pub type ResponseCacheBackendError = Box<dyn std::error::Error + 'static>;
+
+/// Errors produced by a [`ResponseCache`].
+#[derive(Debug, thiserror::Error)]
+pub enum ResponseCacheError {
+    /// The backing store failed to read or write an entry.
+    #[error("Response cache backend error: {0}")]
+    Backend(ResponseCacheBackendError),
+
+    /// An entry could not be encoded or decoded.
+    #[error("Response cache serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// The key cannot be used by this cache.
+    #[error("Invalid response cache key `{0}`")]
+    InvalidKey(String),
+}
+
+impl ResponseCacheError {
+    /// Wrap an arbitrary error from a backend implementation.
+    pub fn backend<E>(source: E) -> Self
+    where
+        E: Into<ResponseCacheBackendError>,
+    {
+        Self::Backend(source.into())
+    }
+}
+
+/// A cached completion response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The response the wrapped model returned.
+    pub response: CompletionResponse,
+    /// Set when the entry was stored with near-duplicate lookup enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<SemanticKey>,
+}
+
+/// What near-duplicate lookup compares a cached entry by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticKey {
+    /// Hash of the request without its prompt. Only entries in the same
+    /// partition are compared.
+    pub partition: String,
+    /// Embedding of the prompt text.
+    pub embedding: Vec<f64>,
+}
+
+/// A storage backend for [`CachedCompletionModel`].
+///
+/// Keys are lowercase hex SHA-256 digests. `put` replaces any entry already
+/// stored under the same key; a backend is free to evict entries at any time.
+pub trait ResponseCache: WasmCompatSend + WasmCompatSync {
+    /// Load the entry stored under `key`, or `None` if there is none.
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<CachedResponse>, ResponseCacheError>>;
+
+    /// Store `entry` under `key`.
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+    ) -> WasmBoxedFuture<'a, Result<(), ResponseCacheError>>;
+
+    /// Every entry whose [`SemanticKey::partition`] is `partition`, for
+    /// near-duplicate lookup. Backends that do not support it keep the
+    /// default, which finds nothing.
+    fn semantic_candidates<'a>(
+        &'a self,
+        partition: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<CachedResponse>, ResponseCacheError>> {
+        let _ = partition;
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+macro_rules! forward_response_cache {
+    ($($ptr:ident)+) => {$(
+        impl<C> ResponseCache for $ptr<C>
+        where
+            C: ResponseCache + ?Sized,
+        {
+            fn get<'a>(
+                &'a self,
+                key: &'a str,
+            ) -> WasmBoxedFuture<'a, Result<Option<CachedResponse>, ResponseCacheError>> {
+                (**self).get(key)
+            }
+
+            fn put<'a>(
+                &'a self,
+                key: &'a str,
+                entry: CachedResponse,
+            ) -> WasmBoxedFuture<'a, Result<(), ResponseCacheError>> {
+                (**self).put(key, entry)
+            }
+
+            fn semantic_candidates<'a>(
+                &'a self,
+                partition: &'a str,
+            ) -> WasmBoxedFuture<'a, Result<Vec<CachedResponse>, ResponseCacheError>> {
+                (**self).semantic_candidates(partition)
+            }
+        }
+    )+};
+}
+
+forward_response_cache!(Arc Box);
+
+/// A process-local [`ResponseCache`] that keeps at most `capacity` entries,
+/// evicting the least recently used one.
+#[derive(Debug, Clone)]
+pub struct InMemoryResponseCache {
+    capacity: usize,
+    entries: Arc<Mutex<IndexMap<String, CachedResponse>>>,
+}
+
+impl InMemoryResponseCache {
+    /// Create an empty cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Arc::default(),
+        }
+    }
+
+    /// The number of cached entries.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, IndexMap<String, CachedResponse>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ResponseCache for InMemoryResponseCache {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            let mut entries = self.lock();
+            // Re-inserting moves the entry to the most recently used end.
+            let entry = entries.shift_remove(key);
+            if let Some(entry) = &entry {
+                entries.insert(key.to_string(), entry.clone());
+            }
+            Ok(entry)
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+    ) -> WasmBoxedFuture<'a, Result<(), ResponseCacheError>> {
+        Box::pin(async move {
+            let mut entries = self.lock();
+            entries.shift_remove(key);
+            entries.insert(key.to_string(), entry);
+            while entries.len() > self.capacity {
+                entries.shift_remove_index(0);
+            }
+            Ok(())
+        })
+    }
+
+    fn semantic_candidates<'a>(
+        &'a self,
+        partition: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            Ok(self
+                .lock()
+                .values()
+                .filter(|entry| in_partition(entry, partition))
+                .cloned()
+                .collect())
+        })
+    }
+}
+
+/// A [`ResponseCache`] that keeps one JSON file per entry in a directory.
+///
+/// Files are named `<key>.json` and written atomically (write to a temporary
+/// file, then rename). Entries are never evicted; delete the directory to
+/// reset the cache. Near-duplicate lookup reads every entry in the directory.
+/// File IO is blocking and runs inline on the calling task.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug, Clone)]
+pub struct FileResponseCache {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl FileResponseCache {
+    /// Store entries in `dir`, created on the first write.
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory entries are stored in.
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> Result<std::path::PathBuf, ResponseCacheError> {
+        let valid = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric());
+        if !valid {
+            return Err(ResponseCacheError::InvalidKey(key.to_string()));
+        }
+        Ok(self.dir.join(format!("{key}.json")))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl ResponseCache for FileResponseCache {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            let path = self.path(key)?;
+            match std::fs::read(&path) {
+                Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(ResponseCacheError::backend(error)),
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+    ) -> WasmBoxedFuture<'a, Result<(), ResponseCacheError>> {
+        Box::pin(async move {
+            let path = self.path(key)?;
+            let contents = serde_json::to_vec(&entry)?;
+            let temp = path.with_extension("json.tmp");
+            std::fs::create_dir_all(&self.dir).map_err(ResponseCacheError::backend)?;
+            std::fs::write(&temp, contents).map_err(ResponseCacheError::backend)?;
+            std::fs::rename(&temp, &path).map_err(ResponseCacheError::backend)
+        })
+    }
+
+    fn semantic_candidates<'a>(
+        &'a self,
+        partition: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            let dir = match std::fs::read_dir(&self.dir) {
+                Ok(dir) => dir,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(Vec::new());
+                }
+                Err(error) => return Err(ResponseCacheError::backend(error)),
+            };
+            let mut candidates = Vec::new();
+            for file in dir {
+                let path = file.map_err(ResponseCacheError::backend)?.path();
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+                let contents = std::fs::read(&path).map_err(ResponseCacheError::backend)?;
+                let entry: CachedResponse = serde_json::from_slice(&contents)?;
+                if in_partition(&entry, partition) {
+                    candidates.push(entry);
+                }
+            }
+            Ok(candidates)
+        })
+    }
+}
+
+fn in_partition(entry: &CachedResponse, partition: &str) -> bool {
+    entry
+        .semantic
+        .as_ref()
+        .is_some_and(|semantic| semantic.partition == partition)
+}
+
+/// Near-duplicate lookup settings.
+#[derive(Clone)]
+struct SemanticLookup {
+    model: EmbeddingModelHandle,
+    threshold: f64,
+}
+
+/// A [`CompletionModel`] that serves repeated requests from a
+/// [`ResponseCache`]. See the [module docs](self).
+#[derive(Clone)]
+pub struct CachedCompletionModel<M> {
+    model: M,
+    cache: Arc<dyn ResponseCache>,
+    namespace: String,
+    semantic: Option<SemanticLookup>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl<M> CachedCompletionModel<M> {
+    /// Wrap `model`, caching its responses in `cache`.
+    pub fn new(model: M, cache: impl ResponseCache + 'static) -> Self {
+        Self {
+            model,
+            cache: Arc::new(cache),
+            namespace: String::new(),
+            semantic: None,
+            hits: Arc::default(),
+            misses: Arc::default(),
+        }
+    }
+
+    /// Mix `namespace` into every key. Give each model its own namespace when
+    /// several share one cache; requests rarely name the model themselves.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// On an exact-key miss, serve the cached entry whose prompt embedding
+    /// has the highest cosine similarity to this prompt's, if that similarity
+    /// is at least `threshold`. New entries are stored with their prompt
+    /// embedding.
+    pub fn semantic_lookup<E>(mut self, embedding_model: E, threshold: f64) -> Self
+    where
+        E: EmbeddingModel + 'static,
+    {
+        self.semantic = Some(SemanticLookup {
+            model: EmbeddingModelHandle::new(embedding_model),
+            threshold,
+        });
+        self
+    }
+
+    /// The wrapped model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// How many requests were served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// How many requests went to the wrapped model.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// The key `request` is cached under.
+    pub fn cache_key(&self, request: &CompletionRequest) -> Result<String, serde_json::Error> {
+        Ok(hash_key(&self.namespace, &serde_json::to_value(request)?))
+    }
+
+    /// Look `request` up, returning a cached response or what the response
+    /// should be stored under.
+    async fn lookup(&self, request: &CompletionRequest) -> Lookup {
+        let key = match self.cache_key(request) {
+            Ok(key) => key,
+            Err(error) => {
+                tracing::warn!(target: "rig", %error, "request cannot be cache-keyed; not caching it");
+                return Lookup::Uncacheable;
+            }
+        };
+        match self.cache.get(&key).await {
+            Ok(Some(entry)) => return self.hit(entry.response, "exact"),
+            Ok(None) => {}
+            Err(error) => tracing::warn!(target: "rig", %error, "response cache read failed"),
+        }
+
+        let semantic = match &self.semantic {
+            Some(lookup) => self.semantic_key(lookup, request).await,
+            None => None,
+        };
+        if let (Some(lookup), Some(semantic)) = (&self.semantic, &semantic) {
+            match self.cache.semantic_candidates(&semantic.partition).await {
+                Ok(candidates) => {
+                    let best = candidates
+                        .into_iter()
+                        .filter_map(|entry| {
+                            let similarity = cosine_similarity(
+                                &semantic.embedding,
+                                &entry.semantic.as_ref()?.embedding,
+                            );
+                            (similarity >= lookup.threshold).then_some((similarity, entry))
+                        })
+                        .max_by(|(left, _), (right, _)| left.total_cmp(right));
+                    if let Some((_, entry)) = best {
+                        return self.hit(entry.response, "semantic");
+                    }
+                }
+                Err(error) => {
+                    tracing::warn!(target: "rig", %error, "response cache semantic lookup failed")
+                }
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        Lookup::Miss { key, semantic }
+    }
+
+    fn hit(&self, response: CompletionResponse, kind: &'static str) -> Lookup {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(target: "rig", lookup = kind, "serving completion from response cache");
+        Lookup::Hit(Box::new(response))
+    }
+
+    /// The partition and prompt embedding of `request`, when it has a text
+    /// prompt and the prompt could be embedded.
+    async fn semantic_key(
+        &self,
+        lookup: &SemanticLookup,
+        request: &CompletionRequest,
+    ) -> Option<SemanticKey> {
+        let (prompt, rest) = request.chat_history.split_last()?;
+        let prompt = prompt_text(prompt)?;
+        let mut context = request.clone();
+        context.chat_history = rest.to_vec();
+        let partition = hash_key(&self.namespace, &serde_json::to_value(&context).ok()?);
+
+        match lookup.model.embed_text(&prompt).await {
+            Ok(embedding) => Some(SemanticKey {
+                partition,
+                embedding: embedding.vec,
+            }),
+            Err(error) => {
+                tracing::warn!(target: "rig", %error, "prompt embedding failed; skipping semantic cache lookup");
+                None
+            }
+        }
+    }
+}
+
+enum Lookup {
+    Hit(Box<CompletionResponse>),
+    Miss {
+        key: String,
+        semantic: Option<SemanticKey>,
+    },
+    Uncacheable,
+}
+
+impl<M> CompletionModel for CachedCompletionModel<M>
+where
+    M: CompletionModel,
+{
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse, CompletionError> {
+        let (key, semantic) = match self.lookup(&request).await {
+            Lookup::Hit(response) => return Ok(*response),
+            Lookup::Miss { key, semantic } => (key, semantic),
+            Lookup::Uncacheable => return self.model.completion(request).await,
+        };
+
+        let response = self.model.completion(request).await?;
+        store(
+            self.cache.as_ref(),
+            &key,
+            CachedResponse {
+                response: response.clone(),
+                semantic,
+            },
+        )
+        .await;
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse, CompletionError> {
+        let (key, semantic) = match self.lookup(&request).await {
+            Lookup::Hit(response) => match replay(&response) {
+                Some(stream) => return Ok(stream),
+                None => {
+                    tracing::debug!(
+                        target: "rig",
+                        "cached response cannot be replayed as a stream; calling the model"
+                    );
+                    match self.cache_key(&request) {
+                        Ok(key) => (key, None),
+                        Err(_) => return self.model.stream(request).await,
+                    }
+                }
+            },
+            Lookup::Miss { key, semantic } => (key, semantic),
+            Lookup::Uncacheable => return self.model.stream(request).await,
+        };
+
+        let mut response = self.model.stream(request).await?;
+        let provider = response.provider().to_string();
+        let cache = self.cache.clone();
+        response.map_inner(|inner| record(inner, provider, cache, key, semantic));
+        Ok(response)
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.model.capabilities()
+    }
+}
+
+async fn store(cache: &dyn ResponseCache, key: &str, entry: CachedResponse) {
+    if let Err(error) = cache.put(key, entry).await {
+        tracing::warn!(target: "rig", %error, "response cache write failed");
+    }
+}
+
+/// Pass `inner` through unchanged, storing the aggregated response once it
+/// completes without an error and with a terminal record.
+fn record(
+    mut inner: StreamingResult,
+    provider: String,
+    cache: Arc<dyn ResponseCache>,
+    key: String,
+    semantic: Option<SemanticKey>,
+) -> StreamingResult {
+    Box::pin(async_stream::stream! {
+        let mut events = Vec::new();
+        let mut failed = false;
+        let mut terminated = false;
+        while let Some(item) = inner.next().await {
+            match &item {
+                Ok(event) => {
+                    terminated |= matches!(event, RawStreamingChoice::FinalResponse(_));
+                    events.push(event.clone());
+                }
+                Err(_) => failed = true,
+            }
+            yield item;
+        }
+        if failed || !terminated {
+            return;
+        }
+
+        // Aggregate a copy of the events exactly as the caller's stream does.
+        let mut copy = StreamingCompletionResponse::stream(
+            provider,
+            Box::pin(futures::stream::iter(events.into_iter().map(Ok))),
+        );
+        while copy.next().await.is_some() {}
+        let entry = CachedResponse {
+            response: CompletionResponse::from(copy),
+            semantic,
+        };
+        store(cache.as_ref(), &key, entry).await;
+    })
+}
+
+/// A stream that yields `response` again, or `None` when its choice holds
+/// content no stream event can carry (images).
+fn replay(response: &CompletionResponse) -> Option<StreamingCompletionResponse> {
+    let mut events = Vec::new();
+    if let Some(message_id) = &response.message_id {
+        events.push(RawStreamingChoice::MessageId(message_id.clone()));
+    }
+    for (index, content) in (0u64..).zip(&response.choice) {
+        match content {
+            AssistantContent::Text(Text {
+                text,
+                additional_params,
+            }) => {
+                let id = StreamPartId::minted(MintKind::Text, index);
+                events.push(RawStreamingChoice::TextStart {
+                    id: id.clone(),
+                    additional_params: additional_params.clone(),
+                });
+                events.push(RawStreamingChoice::Message(text.clone()));
+                events.push(RawStreamingChoice::TextEnd { id });
+            }
+            AssistantContent::ToolCall(tool_call) => {
+                let mut raw = RawStreamingToolCall::new(
+                    StreamPartId::minted(MintKind::Tool, index),
+                    tool_call.function.name.clone(),
+                    tool_call.function.arguments.clone(),
+                )
+                .with_signature(tool_call.signature.clone())
+                .with_additional_params(tool_call.additional_params.clone());
+                if let Some(provider) = &tool_call.provider {
+                    raw.call_id = Some(provider.call_id.clone());
+                    raw.tool_id = provider.item_id.clone().and_then(WireId::new);
+                }
+                events.push(RawStreamingChoice::ToolCall(raw));
+            }
+            AssistantContent::Reasoning(reasoning) => {
+                events.push(RawStreamingChoice::ReasoningEnd {
+                    id: StreamPartId::minted(MintKind::Reasoning, index),
+                    reasoning: Some(reasoning.clone()),
+                    signature: None,
+                    wire_sent: true,
+                });
+            }
+            AssistantContent::Image(_) => return None,
+        }
+    }
+
+    let mut terminal = StreamFinal::new(response.provider.clone(), response.usage)
+        .with_optional_finish_reason(response.finish_reason())
+        .with_optional_message_id(response.message_id.clone())
+        .with_optional_response_id(response.response_id.clone())
+        .with_optional_provider_request_id(response.provider_request_id.clone())
+        .with_optional_model(response.model.clone());
+    terminal.raw = response.raw.clone();
+    events.push(RawStreamingChoice::FinalResponse(terminal));
+
+    let stream = StreamingCompletionResponse::stream(
+        response.provider.clone(),
+        Box::pin(futures::stream::iter(events.into_iter().map(Ok))),
+    );
+    Some(match &response.served_by {
+        Some(served_by) => stream.with_served_by(served_by.clone()),
+        None => stream,
+    })
+}
+
+/// The text of a user prompt made only of text parts.
+fn prompt_text(message: &Message) -> Option<String> {
+    let Message::User { content } = message else {
+        return None;
+    };
+    let parts = content
+        .iter()
+        .map(|part| match part {
+            UserContent::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!parts.is_empty()).then(|| parts.join("\n"))
+}
+
+fn hash_key(namespace: &str, value: &serde_json::Value) -> String {
+    let mut canonical = String::new();
+    write_canonical(value, &mut canonical);
+    let digest = Sha256::new()
+        .chain_update(namespace.as_bytes())
+        .chain_update([0])
+        .chain_update(canonical.as_bytes())
+        .finalize();
+    digest.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// JSON with object keys sorted, so equal values render equally whatever
+/// order their maps were built in.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+fn cosine_similarity(left: &[f64], right: &[f64]) -> f64 {
+    if left.len() != right.len() {
+        return f64::NEG_INFINITY;
+    }
+    let dot = left.iter().zip(right).map(|(l, r)| l * r).sum::<f64>();
+    let norm = |vector: &[f64]| vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let denominator = norm(left) * norm(right);
+    if denominator == 0.0 {
+        f64::NEG_INFINITY
+    } else {
+        dot / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        embeddings::{Embedding, EmbeddingError, EmbeddingResponse},
+        streaming::StreamedAssistantContent,
+        test_utils::{MockCompletionModel, MockStreamEvent, MockTurn},
+    };
+
+    /// Embeds text as letter counts, so prompts that differ only in case
+    /// and punctuation are identical and unrelated prompts are far apart.
+    #[derive(Clone)]
+    struct LetterEmbedding;
+
+    impl EmbeddingModel for LetterEmbedding {
+        fn max_documents(&self) -> usize {
+            16
+        }
+
+        fn ndims(&self) -> usize {
+            26
+        }
+
+        async fn embed_texts_response(
+            &self,
+            texts: impl IntoIterator<Item = String> + WasmCompatSend,
+        ) -> Result<EmbeddingResponse, EmbeddingError> {
+            let embeddings = texts
+                .into_iter()
+                .map(|document| {
+                    let mut vec = vec![0.0; 26];
+                    for letter in document.to_ascii_lowercase().bytes() {
+                        if let Some(slot) = letter
+                            .checked_sub(b'a')
+                            .and_then(|offset| vec.get_mut(usize::from(offset)))
+                        {
+                            *slot += 1.0;
+                        }
+                    }
+                    Embedding { document, vec }
+                })
+                .collect();
+            Ok(EmbeddingResponse::new(embeddings, "letters"))
+        }
+    }
+
+    fn request(prompt: &str) -> CompletionRequest {
+        CompletionRequest {
+            model: None,
+            preamble: None,
+            chat_history: vec![Message::user(prompt)],
+            documents: Vec::new(),
+            tools: Vec::new(),
+            temperature: Some(0.0),
+            max_tokens: None,
+            tool_choice: None,
+            additional_params: None,
+            output_schema: None,
+            record_telemetry_content: false,
+        }
+    }
+
+    async fn collect_text(mut stream: StreamingCompletionResponse) -> String {
+        let mut text = String::new();
+        while let Some(item) = stream.next().await {
+            if let Ok(StreamedAssistantContent::Text(delta)) = item {
+                text.push_str(&delta.text);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn keys_ignore_map_order_and_telemetry_policy() {
+        let model = CachedCompletionModel::new(
+            MockCompletionModel::default(),
+            InMemoryResponseCache::new(4),
+        );
+        let mut first = request("hi");
+        first.additional_params = Some(json!({"a": 1, "b": {"c": 2, "d": 3}}));
+        let mut second = first.clone();
+        second.additional_params = Some(json!({"b": {"d": 3, "c": 2}, "a": 1}));
+        second.record_telemetry_content = true;
+
+        let key = model.cache_key(&first).expect("key");
+        assert_eq!(key, model.cache_key(&second).expect("key"));
+        assert_eq!(key.len(), 64);
+
+        second.temperature = Some(0.7);
+        assert_ne!(key, model.cache_key(&second).expect("key"));
+        let namespaced = CachedCompletionModel::new(
+            MockCompletionModel::default(),
+            InMemoryResponseCache::new(4),
+        )
+        .namespace("other-model");
+        assert_ne!(key, namespaced.cache_key(&first).expect("key"));
+    }
+
+    #[tokio::test]
+    async fn repeated_requests_are_served_from_the_cache() {
+        let model = CachedCompletionModel::new(
+            MockCompletionModel::new([MockTurn::text("Paris"), MockTurn::text("Lyon")]),
+            InMemoryResponseCache::new(4),
+        );
+
+        let first = model
+            .completion(request("Capital of France?"))
+            .await
+            .expect("miss");
+        let second = model
+            .completion(request("Capital of France?"))
+            .await
+            .expect("hit");
+        assert_eq!(first.choice, second.choice);
+        assert_eq!(model.model().request_count(), 1);
+
+        let other = model
+            .completion(request("Second city?"))
+            .await
+            .expect("miss");
+        assert_eq!(other.choice, vec![AssistantContent::text("Lyon")]);
+        assert_eq!((model.hits(), model.misses()), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn the_least_recently_used_entry_is_evicted() {
+        let cache = InMemoryResponseCache::new(2);
+        let entry = |text: &str| CachedResponse {
+            response: CompletionResponse::new(
+                vec![AssistantContent::text(text)],
+                Default::default(),
+                "mock",
+            ),
+            semantic: None,
+        };
+        cache.put("a", entry("a")).await.expect("put");
+        cache.put("b", entry("b")).await.expect("put");
+        cache.get("a").await.expect("get");
+        cache.put("c", entry("c")).await.expect("put");
+
+        assert!(cache.get("a").await.expect("get").is_some());
+        assert!(cache.get("b").await.expect("get").is_none());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn near_duplicate_prompts_hit_within_the_same_context() {
+        let model = CachedCompletionModel::new(
+            MockCompletionModel::new([MockTurn::text("Paris"), MockTurn::text("Berlin")]),
+            InMemoryResponseCache::new(8),
+        )
+        .semantic_lookup(LetterEmbedding, 0.99);
+
+        model
+            .completion(request("Capital of France?"))
+            .await
+            .expect("miss");
+        let near = model
+            .completion(request("capital of france"))
+            .await
+            .expect("semantic hit");
+        assert_eq!(near.choice, vec![AssistantContent::text("Paris")]);
+
+        // A different sampling configuration is a different partition.
+        let mut hotter = request("capital of france");
+        hotter.temperature = Some(1.0);
+        let fresh = model.completion(hotter).await.expect("miss");
+        assert_eq!(fresh.choice, vec![AssistantContent::text("Berlin")]);
+        assert_eq!(model.model().request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn completed_streams_are_cached_and_replayed() {
+        let model = CachedCompletionModel::new(
+            MockCompletionModel::from_stream_turns([vec![
+                MockStreamEvent::text("Hello, "),
+                MockStreamEvent::text("world"),
+                MockStreamEvent::tool_call("call-1", "lookup", json!({"q": "rig"}))
+                    .with_call_id("call-1"),
+                MockStreamEvent::final_response_with_total_tokens(7),
+            ]]),
+            InMemoryResponseCache::new(4),
+        );
+
+        let mut live = model.stream(request("Greet me")).await.expect("stream");
+        while live.next().await.is_some() {}
+
+        let mut replayed = model.stream(request("Greet me")).await.expect("replay");
+        let mut events = Vec::new();
+        while let Some(item) = replayed.next().await {
+            events.push(item.expect("replayed event"));
+        }
+        assert_eq!(model.model().request_count(), 1);
+        assert_eq!(replayed.choice, live.choice);
+        assert_eq!(replayed.usage().total_tokens, 7);
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, StreamedAssistantContent::ToolCall { .. }))
+        );
+
+        // The unary path shares the entry.
+        let unary = model.completion(request("Greet me")).await.expect("hit");
+        assert_eq!(unary.choice, live.choice);
+    }
+
+    #[tokio::test]
+    async fn failed_streams_are_not_cached() {
+        let model = CachedCompletionModel::new(
+            MockCompletionModel::from_stream_turns([
+                vec![
+                    MockStreamEvent::text("partial"),
+                    MockStreamEvent::error("connection reset"),
+                ],
+                vec![
+                    MockStreamEvent::text("complete"),
+                    MockStreamEvent::final_response_with_total_tokens(3),
+                ],
+            ]),
+            InMemoryResponseCache::new(4),
+        );
+
+        let failed = model.stream(request("Hi")).await.expect("stream");
+        collect_text(failed).await;
+        let retried = model.stream(request("Hi")).await.expect("stream");
+        assert_eq!(collect_text(retried).await, "complete");
+        assert_eq!(model.model().request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn file_cache_round_trips_entries() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        let cache = FileResponseCache::new(temp.path().join("cache"));
+        let entry = CachedResponse {
+            response: CompletionResponse::new(
+                vec![AssistantContent::text("cached")],
+                Default::default(),
+                "mock",
+            ),
+            semantic: Some(SemanticKey {
+                partition: "p".to_string(),
+                embedding: vec![1.0, 0.0],
+            }),
+        };
+
+        assert!(cache.get("abc123").await.expect("get").is_none());
+        cache.put("abc123", entry.clone()).await.expect("put");
+        let loaded = cache.get("abc123").await.expect("get").expect("stored");
+        assert_eq!(loaded.response.choice, entry.response.choice);
+        assert_eq!(cache.semantic_candidates("p").await.expect("scan").len(), 1);
+        assert!(
+            cache
+                .semantic_candidates("q")
+                .await
+                .expect("scan")
+                .is_empty()
+        );
+        assert!(matches!(
+            cache.get("../escape").await,
+            Err(ResponseCacheError::InvalidKey(_))
+        ));
+    }
+}
@@ -27,8 +27,12 @@
 //! # }
 //! ```
 
+pub mod cache;
 pub mod message;
 pub mod request;
+pub mod token_count;
 
+pub use cache::CachedCompletionModel;
 pub use message::{AssistantContent, Message, MessageError};
 pub use request::*;
+pub use token_count::TokenCountingModel;
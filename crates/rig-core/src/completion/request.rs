@@ -374,6 +374,11 @@ pub struct CompletionResponse {
     /// forward.
     #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
     pub raw: serde_json::Value,
+    /// Label of the model that served this response, set by routing models
+    /// that choose among several underlying models (for example rig-agent's
+    /// `FallbackModel`). `None` for a response straight from a provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
 }
 
 /// Response identity metadata for one completed model call: which provider
@@ -397,6 +402,11 @@ pub struct ResponseIdentity {
     /// provider support asks for. Never the body's message/response id.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub provider_request_id: Option<String>,
+    /// Label of the model that served the call when a routing model chose
+    /// among several (see [`CompletionResponse::served_by`]); `None` when the
+    /// call went straight to a provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
 }
 
 impl CompletionResponse {
@@ -413,6 +423,7 @@ impl CompletionResponse {
             provider: provider.into(),
             model: None,
             raw: serde_json::Value::Null,
+            served_by: None,
         }
     }
 
@@ -427,9 +438,16 @@ impl CompletionResponse {
             message_id: self.message_id.clone(),
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: self.served_by.clone(),
         }
     }
 
+    /// Attach the label of the model that served this response.
+    pub fn with_served_by(mut self, served_by: impl Into<String>) -> Self {
+        self.served_by = Some(served_by.into());
+        self
+    }
+
     /// Attach the normalized finish reason, reconciled against the choice via
     /// [`FinishReason::reconcile_with_output`].
     pub fn with_finish_reason(self, finish_reason: FinishReason) -> Self {
@@ -485,6 +503,8 @@ struct CompletionResponseRepr {
     // value" means.
     #[serde(default)]
     raw: serde_json::Value,
+    #[serde(default)]
+    served_by: Option<String>,
 }
 
 impl From<CompletionResponseRepr> for CompletionResponse {
@@ -499,14 +519,17 @@ impl From<CompletionResponseRepr> for CompletionResponse {
             provider,
             model,
             raw,
+            served_by,
         } = repr;
-        Self::new(choice, usage, provider)
+        let mut response = Self::new(choice, usage, provider)
             .with_optional_message_id(message_id)
             .with_optional_response_id(response_id)
             .with_optional_provider_request_id(provider_request_id)
             .with_optional_finish_reason(finish_reason)
             .with_optional_model(model)
-            .with_raw(raw)
+            .with_raw(raw);
+        response.served_by = served_by;
+        response
     }
 }
 
@@ -2210,6 +2233,7 @@ mod response_identity_tests {
                 message_id: Some("msg_1".into()),
                 response_id: Some("resp_1".into()),
                 provider_request_id: Some("req_1".into()),
+                served_by: None,
             }
         );
     }
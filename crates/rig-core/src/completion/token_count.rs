@@ -0,0 +1,83 @@
+//! Exact input-token counts from a provider's own tokenizer.
+//!
+//! Character-length heuristics are fine for trimming chat history, but not for
+//! deciding whether a long document still fits a context window: tokenizers
+//! disagree by tens of percent on code, non-English text and JSON, and tool
+//! schemas and document wrappers add tokens the caller never sees. A
+//! [`TokenCountingModel`] asks the provider instead, for the exact
+//! [`CompletionRequest`] that would be sent — preamble, history, documents and
+//! tool definitions included.
+//!
+//! Implemented for:
+//!
+//! - [`crate::providers::anthropic::completion::CompletionModel`] — the free
+//!   `POST /v1/messages/count_tokens` endpoint.
+//! - [`crate::providers::gemini::completion::CompletionModel`] —
+//!   `models/{model}:countTokens` over the full `generateContent` request.
+//! - [`crate::providers::llamacpp::completion::CompletionModel`] —
+//!   `llama-server`'s `/apply-template` followed by `/tokenize`, so the count
+//!   includes the loaded model's chat template.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rig_core::client::{CompletionClient, ProviderClient};
+//! use rig_core::completion::{CompletionModel, TokenCountingModel};
+//! use rig_core::providers::anthropic;
+//!
+//! let model = anthropic::Client::from_env()?.completion_model(anthropic::completion::CLAUDE_SONNET_4_6);
+//! let request = model.completion_request(long_document).build();
+//! if model.count_tokens(request.clone()).await? > 180_000 {
+//!     return Err("document too long".into());
+//! }
+//! let response = model.completion(request).await?;
+//! ```
+
+use std::future::Future;
+
+use crate::completion::{CompletionError, CompletionRequest};
+use crate::http_client::{self, HttpClientExt};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+/// A model that can count the input tokens of a [`CompletionRequest`] without
+/// running it.
+pub trait TokenCountingModel: WasmCompatSend + WasmCompatSync {
+    /// Count the input tokens `request` would consume if sent to
+    /// [`crate::completion::CompletionModel::completion`] on this model.
+    ///
+    /// The request is converted exactly as a completion would convert it, so a
+    /// request the provider would reject fails here the same way. Output-only
+    /// settings such as `max_tokens` and `temperature` do not affect the count.
+    fn count_tokens(
+        &self,
+        request: CompletionRequest,
+    ) -> impl Future<Output = Result<u64, CompletionError>> + WasmCompatSend;
+}
+
+/// Send a counting request and decode its JSON body, routing a non-success
+/// status through [`CompletionError::from_http_response`] so the status and
+/// body stay readable through the `provider_response_*` helpers.
+pub(crate) async fn send_count_request<C, T>(
+    client: &C,
+    request: http_client::Request<Vec<u8>>,
+) -> Result<T, CompletionError>
+where
+    C: HttpClientExt,
+    T: serde::de::DeserializeOwned,
+{
+    let response = client.send::<_, Vec<u8>>(request).await?;
+    let status = response.status();
+    let body = response.into_body().await?;
+    if !status.is_success() {
+        return Err(CompletionError::from_http_response(
+            status,
+            String::from_utf8_lossy(&body),
+        ));
+    }
+    serde_json::from_slice(&body).map_err(|error| {
+        CompletionError::ResponseError(format!(
+            "could not decode the token count response: {error}: {}",
+            String::from_utf8_lossy(&body)
+        ))
+    })
+}
@@ -230,6 +230,7 @@ impl EmbeddingResponse {
             message_id: None,
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
         }
     }
 }
@@ -314,6 +315,7 @@ impl ImageEmbeddingResponse {
             message_id: None,
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
         }
     }
 }
@@ -109,6 +109,18 @@ impl BoxedHttpClient {
         Self(Arc::new(http))
     }
 
+    /// Erase a shared `http` without another allocation. If `http` is already
+    /// a `BoxedHttpClient`, this is a clone of it.
+    pub(crate) fn from_arc<H>(http: Arc<H>) -> Self
+    where
+        H: HttpClientExt + 'static,
+    {
+        if let Some(already) = (&*http as &dyn Any).downcast_ref::<BoxedHttpClient>() {
+            return already.clone();
+        }
+        Self(http)
+    }
+
     /// Whether two handles share the same underlying transport.
     pub fn ptr_eq(&self, other: &Self) -> bool {
         Arc::ptr_eq(&self.0, &other.0)
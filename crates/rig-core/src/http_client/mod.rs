@@ -151,6 +151,47 @@ pub async fn text(response: Response<LazyBody<Vec<u8>>>) -> Result<String> {
     Ok(String::from(String::from_utf8_lossy(&text)))
 }
 
+/// Send `request` and return the body of a successful response.
+///
+/// A non-success status the transport hands back as a response (rather than
+/// as an error) becomes [`Error::InvalidStatusCodeWithMessage`] with the body
+/// kept verbatim — the same shape a capability error's `from_http_response`
+/// builds, so `?` into any of them keeps the `provider_response_*` helpers
+/// working.
+pub(crate) async fn send_for_bytes<C, B>(client: &C, request: Request<B>) -> Result<Vec<u8>>
+where
+    C: HttpClientExt,
+    B: Into<Bytes> + WasmCompatSend,
+{
+    let response = client.send::<_, Vec<u8>>(request).await?;
+    read_success_body(response).await
+}
+
+/// [`send_for_bytes`] for a multipart upload.
+pub(crate) async fn send_multipart_for_bytes<C>(
+    client: &C,
+    request: Request<MultipartForm>,
+) -> Result<Vec<u8>>
+where
+    C: HttpClientExt,
+{
+    let response = client.send_multipart::<Vec<u8>>(request).await?;
+    read_success_body(response).await
+}
+
+async fn read_success_body(response: Response<LazyBody<Vec<u8>>>) -> Result<Vec<u8>> {
+    let status = response.status();
+    let body = response.into_body().await?;
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Err(Error::InvalidStatusCodeWithMessage(
+            status,
+            String::from_utf8_lossy(&body).into_owned(),
+        ))
+    }
+}
+
 pub fn make_auth_header(key: impl AsRef<str>) -> Result<(HeaderName, HeaderValue)> {
     Ok((
         http::header::AUTHORIZATION,
@@ -1,6 +1,9 @@
 //! Helpers to handle connection delays when receiving errors
 
-use super::Error;
+use super::{Error, HttpClientExt, LazyBody, Result};
+use crate::wasm_compat::WasmCompatSend;
+use bytes::Bytes;
+use http::{HeaderMap, Request, Response, StatusCode};
 use std::time::Duration;
 
 pub trait RetryPolicy {
@@ -77,3 +80,300 @@ pub const DEFAULT_RETRY: ExponentialBackoff = ExponentialBackoff::new(
     Some(Duration::from_secs(5)),
     None,
 );
+
+/// The default backoff for [`RequestRetry`]: three retries starting at 500ms,
+/// doubling, with no single delay above 30s.
+pub const DEFAULT_REQUEST_BACKOFF: ExponentialBackoff = ExponentialBackoff::new(
+    Duration::from_millis(500),
+    2.,
+    Some(Duration::from_secs(30)),
+    Some(3),
+);
+
+/// Statuses [`RequestRetry`] treats as transient by default: request timeout,
+/// rate limiting, gateway and availability errors, and Anthropic's 529
+/// "overloaded".
+pub const DEFAULT_RETRY_STATUSES: [u16; 7] = [408, 429, 500, 502, 503, 504, 529];
+
+/// Retry policy for unary (non-streaming, non-multipart) requests sent through
+/// a provider [`Client`](crate::client::Client), configured with
+/// [`ClientBuilder::retry`](crate::client::ClientBuilder::retry).
+///
+/// A request that fails with one of the configured statuses is sent again
+/// with the same method, URI, headers and body after a delay. The delay is
+/// the response's `retry-after-ms` or `Retry-After` (in seconds) header when
+/// present, and the [`ExponentialBackoff`] delay otherwise. Retrying stops
+/// when the backoff runs out of retries, or when waiting again would exceed
+/// the elapsed-time cap; the last failure is then returned unchanged, with
+/// its headers, exactly as without a retry policy.
+///
+/// Completion, embedding and rerank calls are stateless, so sending them again
+/// is safe. Each retry emits an event on the current span (the provider's
+/// telemetry span when called through a model) carrying `resend_count`, the
+/// `status` that triggered it and the `delay_ms` waited.
+#[derive(Debug, Clone)]
+pub struct RequestRetry {
+    backoff: ExponentialBackoff,
+    max_elapsed: Option<Duration>,
+    statuses: Vec<StatusCode>,
+}
+
+impl Default for RequestRetry {
+    fn default() -> Self {
+        Self::new(DEFAULT_REQUEST_BACKOFF)
+    }
+}
+
+impl RequestRetry {
+    /// Retry with `backoff` on [`DEFAULT_RETRY_STATUSES`], for at most 60s
+    /// in total.
+    pub fn new(backoff: ExponentialBackoff) -> Self {
+        Self {
+            backoff,
+            max_elapsed: Some(Duration::from_secs(60)),
+            statuses: DEFAULT_RETRY_STATUSES
+                .iter()
+                .filter_map(|status| StatusCode::from_u16(*status).ok())
+                .collect(),
+        }
+    }
+
+    /// Cap the total time spent on one request, measured from the first
+    /// attempt. `None` leaves only the backoff's retry limit.
+    pub fn max_elapsed(mut self, max_elapsed: Option<Duration>) -> Self {
+        self.max_elapsed = max_elapsed;
+        self
+    }
+
+    /// Replace the statuses that trigger a retry.
+    pub fn statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Send `req` through `client`, retrying transient failures.
+    pub(crate) async fn send<H, U>(
+        &self,
+        client: &H,
+        req: Request<Bytes>,
+    ) -> Result<Response<LazyBody<U>>>
+    where
+        H: HttpClientExt,
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        let (parts, body) = req.into_parts();
+        let started = web_time::Instant::now();
+        let mut last_retry = None;
+
+        loop {
+            let result = client
+                .send::<Bytes, U>(Request::from_parts(parts.clone(), body.clone()))
+                .await;
+
+            let (status, retry_after) = match &result {
+                Ok(response) => (response.status(), retry_after(response.headers())),
+                Err(error) => match error.non_success_status() {
+                    Some(status) => (status, error.non_success_headers().and_then(retry_after)),
+                    None => return result,
+                },
+            };
+            if !self.statuses.contains(&status) {
+                return result;
+            }
+
+            let Some(backoff) = self
+                .backoff
+                .retry(&Error::InvalidStatusCode(status), last_retry)
+            else {
+                return result;
+            };
+            let delay = retry_after.unwrap_or(backoff);
+            if self
+                .max_elapsed
+                .is_some_and(|max_elapsed| started.elapsed() + delay > max_elapsed)
+            {
+                return result;
+            }
+
+            let resend_count = last_retry.map_or(1, |(count, _)| count + 1);
+            tracing::info!(
+                target: "rig::http",
+                resend_count,
+                status = status.as_u16(),
+                delay_ms = delay.as_millis() as u64,
+                "retrying request after transient status"
+            );
+            crate::wasm_compat::sleep(delay).await;
+            last_retry = Some((resend_count, backoff));
+        }
+    }
+}
+
+/// The server-requested delay, from `retry-after-ms` (sent by OpenAI and
+/// Azure) or a delta-seconds `Retry-After`. HTTP-date values are ignored.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+
+    if let Some(millis) = header("retry-after-ms").and_then(|value| value.parse::<f64>().ok())
+        && millis.is_finite()
+        && millis >= 0.0
+    {
+        return Some(Duration::from_secs_f64(millis / 1000.0));
+    }
+
+    header(http::header::RETRY_AFTER.as_str())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockHttpResponse, SequencedHttpClient};
+
+    fn request() -> Request<Bytes> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("https://example.test/v1/embeddings")
+            .header("x-test", "kept")
+            .body(Bytes::from_static(b"{\"input\":\"hello\"}"))
+            .expect("valid request")
+    }
+
+    fn immediate(max_retries: usize) -> RequestRetry {
+        RequestRetry::new(ExponentialBackoff::new(
+            Duration::ZERO,
+            1.,
+            None,
+            Some(max_retries),
+        ))
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    http::HeaderName::from_static(name),
+                    http::HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn transient_statuses_are_resent_until_success() {
+        let client = SequencedHttpClient::new([
+            MockHttpResponse::error_with_headers(
+                StatusCode::TOO_MANY_REQUESTS,
+                "slow down",
+                headers(&[("retry-after", "0")]),
+            ),
+            MockHttpResponse::ErrorResponse(StatusCode::SERVICE_UNAVAILABLE, Bytes::new()),
+            MockHttpResponse::success("ok"),
+        ]);
+
+        let response = immediate(3)
+            .send::<_, Bytes>(&client, request())
+            .await
+            .expect("third attempt succeeds");
+
+        assert_eq!(response.into_body().await.expect("body"), "ok");
+        let requests = client.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.windows(2).all(|pair| pair[0] == pair[1]));
+        assert_eq!(requests[2].headers["x-test"], "kept");
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_return_the_last_failure_with_its_headers() {
+        let rate_limited = || {
+            MockHttpResponse::error_with_headers(
+                StatusCode::TOO_MANY_REQUESTS,
+                "slow down",
+                headers(&[("retry-after-ms", "0")]),
+            )
+        };
+        let client = SequencedHttpClient::new([rate_limited(), rate_limited(), rate_limited()]);
+
+        let Err(error) = immediate(1).send::<_, Bytes>(&client, request()).await else {
+            panic!("retries run out");
+        };
+
+        assert_eq!(client.requests().len(), 2);
+        assert_eq!(
+            error.non_success_status(),
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        );
+        assert!(
+            error
+                .non_success_headers()
+                .is_some_and(|headers| headers.contains_key("retry-after-ms"))
+        );
+    }
+
+    #[tokio::test]
+    async fn other_failures_are_not_retried() {
+        let client = SequencedHttpClient::new([
+            MockHttpResponse::error(StatusCode::BAD_REQUEST, "bad"),
+            MockHttpResponse::success("ok"),
+        ]);
+
+        let Err(error) = immediate(3).send::<_, Bytes>(&client, request()).await else {
+            panic!("a 400 is final");
+        };
+
+        assert_eq!(error.non_success_status(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_after_beyond_the_elapsed_cap_is_not_waited_for() {
+        let client = SequencedHttpClient::new([
+            MockHttpResponse::error_with_headers(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "maintenance",
+                headers(&[("retry-after", "120")]),
+            ),
+            MockHttpResponse::success("ok"),
+        ]);
+
+        let Err(error) = immediate(3)
+            .max_elapsed(Some(Duration::from_secs(1)))
+            .send::<_, Bytes>(&client, request())
+            .await
+        else {
+            panic!("the requested delay exceeds the cap");
+        };
+
+        assert_eq!(
+            error.non_success_status(),
+            Some(StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[test]
+    fn retry_after_prefers_milliseconds_and_ignores_dates() {
+        assert_eq!(
+            retry_after(&headers(&[("retry-after-ms", "250"), ("retry-after", "3")])),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", " 3 ")])),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                "retry-after",
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            )])),
+            None
+        );
+    }
+}
@@ -79,6 +79,7 @@ impl ImageGenerationResponse {
             message_id: None,
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
         }
     }
 }
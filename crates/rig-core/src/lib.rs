@@ -163,7 +163,9 @@ pub mod loaders;
 pub mod markers;
 pub mod memory;
 pub mod model;
+pub mod moderation;
 pub mod prelude;
+pub mod pricing;
 pub(crate) mod provider_response;
 pub mod providers;
 pub mod rerank;
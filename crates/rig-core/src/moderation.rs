@@ -0,0 +1,203 @@
+//! Provider-agnostic content moderation abstractions.
+//!
+//! Moderation models classify text against a provider's harm taxonomy. The
+//! [`ModerationModel`] trait defines the interface, and [`ModerationResponse`]
+//! carries one [`ModerationResult`] per input — an overall verdict plus the
+//! per-category flags and scores behind it.
+//!
+//! Category names are the provider's own (`"harassment/threatening"` for
+//! OpenAI, `"hate_and_discrimination"` for Mistral): the taxonomies do not
+//! line up one-to-one, and a lossy mapping onto a shared list would hide
+//! exactly the distinctions a policy is written against.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    completion::{ResponseIdentity, Usage},
+    wasm_compat::{WasmCompatSend, WasmCompatSync},
+};
+use serde::{Deserialize, Serialize};
+
+crate::provider_response::provider_error_enum!(
+    ModerationError, "moderation" {
+        /// The request was rejected before anything was sent.
+        #[error("RequestError: {0}")]
+        RequestError(String),
+    }
+);
+
+/// Trait for models that classify text for harmful content.
+pub trait ModerationModel: WasmCompatSend + WasmCompatSync {
+    /// Classify each input. The response holds one result per input, in
+    /// input order.
+    fn moderate(
+        &self,
+        inputs: Vec<String>,
+    ) -> impl std::future::Future<Output = Result<ModerationResponse, ModerationError>> + WasmCompatSend;
+}
+
+/// One category's verdict for one input.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModerationCategory {
+    /// Whether the provider flagged the input for this category.
+    pub flagged: bool,
+    /// The provider's confidence that the input belongs to this category,
+    /// from 0 to 1. Providers flag against their own per-category
+    /// thresholds, so compare scores within a category, not across them.
+    pub score: f64,
+}
+
+/// The moderation verdict for one input.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModerationResult {
+    /// Whether any category was flagged.
+    pub flagged: bool,
+    /// Every category the provider reported, keyed by its own name.
+    pub categories: BTreeMap<String, ModerationCategory>,
+}
+
+impl ModerationResult {
+    /// Build a result from per-category verdicts; the input counts as flagged
+    /// when any category is.
+    pub fn from_categories(categories: BTreeMap<String, ModerationCategory>) -> Self {
+        Self {
+            flagged: categories.values().any(|category| category.flagged),
+            categories,
+        }
+    }
+
+    /// The names of the flagged categories, in name order.
+    pub fn flagged_categories(&self) -> impl Iterator<Item = &str> {
+        self.categories
+            .iter()
+            .filter(|(_, category)| category.flagged)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The names of the categories scoring at least `threshold`, in name
+    /// order, whether or not the provider flagged them.
+    pub fn categories_scoring_at_least(&self, threshold: f64) -> impl Iterator<Item = &str> {
+        self.categories
+            .iter()
+            .filter(move |(_, category)| category.score >= threshold)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// The normalized moderation response: one result per input plus the
+/// metadata every provider can report, attributed to the provider that
+/// produced it.
+///
+/// Concrete and provider-neutral. The provider's own payload stays reachable
+/// through a model's inherent `raw_moderation` method, which performs the
+/// same request and returns the provider's native type, and through
+/// [`Self::raw`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResponse {
+    /// One result per input, in input order.
+    pub results: Vec<ModerationResult>,
+    /// Provider-reported model identifier, when the wire response named one.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Token usage for this request. Zero-valued when the provider reported
+    /// none — which OpenAI and Mistral both do — the sentinel [`Usage`]
+    /// documents.
+    #[serde(default)]
+    pub usage: Usage,
+    /// Stable descriptor name of the provider that produced this response,
+    /// for example `"openai"`. Always populated.
+    pub provider: String,
+    /// Provider-assigned response-scoped identifier, when reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_id: Option<String>,
+    /// The provider's transport-level request identifier, taken from the HTTP
+    /// response headers. `None` means the provider reported none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_request_id: Option<String>,
+    /// The provider's own response for this call: the value the model's
+    /// inherent `raw_moderation` would have returned, serialized.
+    /// `Value::Null` means the value was built without a provider behind it
+    /// (a test double), never that the provider sent nothing.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub raw: serde_json::Value,
+}
+
+impl ModerationResponse {
+    /// Create a response from its required parts; optional metadata starts
+    /// unset and is filled in with the `with_*` helpers.
+    pub fn new(results: Vec<ModerationResult>, provider: impl Into<String>) -> Self {
+        Self {
+            results,
+            model: None,
+            usage: Usage::new(),
+            provider: provider.into(),
+            response_id: None,
+            provider_request_id: None,
+            raw: serde_json::Value::Null,
+        }
+    }
+
+    /// Whether any input was flagged.
+    pub fn flagged(&self) -> bool {
+        self.results.iter().any(|result| result.flagged)
+    }
+
+    /// This response's identity metadata as one [`ResponseIdentity`] carrier.
+    /// `message_id` is always `None`: nothing here is replayed as an
+    /// assistant message.
+    pub fn identity(&self) -> ResponseIdentity {
+        ResponseIdentity {
+            message_id: None,
+            response_id: self.response_id.clone(),
+            provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
+        }
+    }
+}
+
+crate::provider_response::modality_response_metadata_setters!(ModerationResponse);
+
+/// Convert a provider's own moderation payload into the normalized
+/// [`ModerationResponse`].
+///
+/// The provider descriptor name is an input, for the same reason as
+/// [`crate::rerank::NormalizeRerankResponse`]: a wire shape shared by several
+/// providers must not hardcode one of their names.
+pub trait NormalizeModerationResponse {
+    /// Normalize this payload, attributing it to `provider`.
+    fn normalize(self, provider: &str) -> Result<ModerationResponse, ModerationError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(flagged: bool, score: f64) -> ModerationCategory {
+        ModerationCategory { flagged, score }
+    }
+
+    #[test]
+    fn a_result_is_flagged_when_any_category_is() {
+        let result = ModerationResult::from_categories(BTreeMap::from([
+            ("hate".to_owned(), category(false, 0.4)),
+            ("violence".to_owned(), category(true, 0.9)),
+            ("sexual".to_owned(), category(false, 0.01)),
+        ]));
+        assert!(result.flagged);
+        assert_eq!(
+            result.flagged_categories().collect::<Vec<_>>(),
+            ["violence"]
+        );
+        assert_eq!(
+            result.categories_scoring_at_least(0.3).collect::<Vec<_>>(),
+            ["hate", "violence"]
+        );
+
+        let clean = ModerationResult::from_categories(BTreeMap::from([(
+            "hate".to_owned(),
+            category(false, 0.0),
+        )]));
+        assert!(!clean.flagged);
+        assert!(!ModerationResponse::new(vec![clean], "probe").flagged());
+    }
+}
@@ -0,0 +1,404 @@
+//! Token cost accounting.
+//!
+//! A [`PricingTable`] maps model ids to [`ModelPricing`] rates and turns a
+//! completion's [`Usage`] into a [`Cost`]. Tables are plain serde data, so
+//! they can live in configuration and be reloaded when providers change their
+//! prices:
+//!
+//! ```rust
+//! use rig_core::{completion::Usage, pricing::PricingTable};
+//!
+//! let table = PricingTable::from_json(
+//!     r#"{
+//!         "currency": "USD",
+//!         "models": {
+//!             "gpt-4o": { "input": 2.5, "cached_input": 1.25, "output": 10.0 }
+//!         }
+//!     }"#,
+//! )?;
+//!
+//! let mut usage = Usage::new();
+//! usage.input_tokens = 1_000_000;
+//! usage.cached_input_tokens = 400_000;
+//! usage.output_tokens = 100_000;
+//!
+//! // 600k uncached input, 400k cached input and 100k output tokens.
+//! let cost = table.cost("gpt-4o-2024-08-06", &usage).unwrap();
+//! assert_eq!(cost.total(), 1.5 + 0.5 + 1.0);
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    ops::{Add, AddAssign},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::completion::Usage;
+
+/// Rates for one model, in currency units per million tokens.
+///
+/// The cached-read, cache-write and reasoning rates are optional and fall back
+/// to the input rate (cache reads and writes) or the output rate (reasoning)
+/// when unset.
+///
+/// Providers normalize [`Usage`] differently, so two flags say how this
+/// model's usage is reported; both default to `true`, which matches OpenAI:
+///
+/// - [`input_includes_cache`](Self::input_includes_cache): whether
+///   `input_tokens` already counts the cached-read and cache-write tokens
+///   (OpenAI, Gemini) or reports them separately (Anthropic).
+/// - [`output_includes_reasoning`](Self::output_includes_reasoning): whether
+///   `output_tokens` already counts the reasoning tokens (OpenAI, Anthropic)
+///   or reports them separately (Gemini).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Rate for uncached input tokens, including tool-use prompt tokens.
+    pub input: f64,
+    /// Rate for output tokens.
+    pub output: f64,
+    /// Rate for input tokens read from a provider-managed cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input: Option<f64>,
+    /// Rate for input tokens written to a provider-managed cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation: Option<f64>,
+    /// Rate for reasoning tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<f64>,
+    /// Whether `input_tokens` includes cached-read and cache-write tokens.
+    #[serde(default = "default_true")]
+    pub input_includes_cache: bool,
+    /// Whether `output_tokens` includes reasoning tokens.
+    #[serde(default = "default_true")]
+    pub output_includes_reasoning: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ModelPricing {
+    /// Pricing with the given input and output rates per million tokens.
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cached_input: None,
+            cache_creation: None,
+            reasoning: None,
+            input_includes_cache: true,
+            output_includes_reasoning: true,
+        }
+    }
+
+    /// Rate for tokens read from a provider-managed cache.
+    pub fn cached_input(mut self, rate: f64) -> Self {
+        self.cached_input = Some(rate);
+        self
+    }
+
+    /// Rate for tokens written to a provider-managed cache.
+    pub fn cache_creation(mut self, rate: f64) -> Self {
+        self.cache_creation = Some(rate);
+        self
+    }
+
+    /// Rate for reasoning tokens.
+    pub fn reasoning(mut self, rate: f64) -> Self {
+        self.reasoning = Some(rate);
+        self
+    }
+
+    /// Whether `input_tokens` includes cached-read and cache-write tokens.
+    pub fn input_includes_cache(mut self, included: bool) -> Self {
+        self.input_includes_cache = included;
+        self
+    }
+
+    /// Whether `output_tokens` includes reasoning tokens.
+    pub fn output_includes_reasoning(mut self, included: bool) -> Self {
+        self.output_includes_reasoning = included;
+        self
+    }
+
+    /// The cost of `usage` at these rates.
+    pub fn cost(&self, usage: &Usage) -> Cost {
+        let cached = usage.cached_input_tokens;
+        let cache_creation = usage.cache_creation_input_tokens;
+        let input = if self.input_includes_cache {
+            usage.input_tokens.saturating_sub(cached + cache_creation)
+        } else {
+            usage.input_tokens
+        } + usage.tool_use_prompt_tokens;
+
+        let reasoning = if self.output_includes_reasoning {
+            usage.reasoning_tokens.min(usage.output_tokens)
+        } else {
+            usage.reasoning_tokens
+        };
+        let output = if self.output_includes_reasoning {
+            usage.output_tokens - reasoning
+        } else {
+            usage.output_tokens
+        };
+
+        Cost {
+            input: priced(input, self.input),
+            cached_input: priced(cached, self.cached_input.unwrap_or(self.input)),
+            cache_creation: priced(cache_creation, self.cache_creation.unwrap_or(self.input)),
+            output: priced(output, self.output),
+            reasoning: priced(reasoning, self.reasoning.unwrap_or(self.output)),
+        }
+    }
+}
+
+fn priced(tokens: u64, rate_per_million: f64) -> f64 {
+    tokens as f64 * rate_per_million / 1_000_000.0
+}
+
+/// A cost breakdown, in the currency of the rates it was computed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cost {
+    /// Cost of uncached input tokens.
+    pub input: f64,
+    /// Cost of input tokens read from a provider-managed cache.
+    pub cached_input: f64,
+    /// Cost of input tokens written to a provider-managed cache.
+    pub cache_creation: f64,
+    /// Cost of output tokens other than reasoning tokens.
+    pub output: f64,
+    /// Cost of reasoning tokens.
+    pub reasoning: f64,
+}
+
+impl Cost {
+    /// The total cost.
+    pub fn total(&self) -> f64 {
+        self.input + self.cached_input + self.cache_creation + self.output + self.reasoning
+    }
+}
+
+impl Add for Cost {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self::Output {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for Cost {
+    fn add_assign(&mut self, other: Self) {
+        self.input += other.input;
+        self.cached_input += other.cached_input;
+        self.cache_creation += other.cache_creation;
+        self.output += other.output;
+        self.reasoning += other.reasoning;
+    }
+}
+
+/// Per-model rates, keyed by model id.
+///
+/// Lookups try the exact model id first, then the longest key that prefixes
+/// it at a `-`, `@`, `:` or `.` boundary, so an entry for `gpt-4o` prices
+/// dated snapshots such as `gpt-4o-2024-08-06`. Note that it also prices
+/// `gpt-4o-mini` unless that model has an entry of its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingTable {
+    /// Currency the rates are expressed in, for reporting only.
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    models: BTreeMap<String, ModelPricing>,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PricingTable {
+    /// An empty table with rates in US dollars.
+    pub fn new() -> Self {
+        Self {
+            currency: default_currency(),
+            models: BTreeMap::new(),
+        }
+    }
+
+    /// Set the currency the rates are expressed in.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = currency.into();
+        self
+    }
+
+    /// Add rates for `model`, replacing any existing entry.
+    pub fn model(mut self, model: impl Into<String>, pricing: ModelPricing) -> Self {
+        self.insert(model, pricing);
+        self
+    }
+
+    /// Add rates for `model`, returning the entry it replaced.
+    pub fn insert(
+        &mut self,
+        model: impl Into<String>,
+        pricing: ModelPricing,
+    ) -> Option<ModelPricing> {
+        self.models.insert(model.into(), pricing)
+    }
+
+    /// Parse a table from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Parse a table from TOML, with one `[models."<id>"]` table per model.
+    #[cfg(feature = "toml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "toml")))]
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    /// The rates for `model`, if the table prices it.
+    pub fn get(&self, model: &str) -> Option<&ModelPricing> {
+        if let Some(pricing) = self.models.get(model) {
+            return Some(pricing);
+        }
+
+        self.models
+            .iter()
+            .filter(|(key, _)| {
+                model
+                    .strip_prefix(key.as_str())
+                    .is_some_and(|rest| rest.starts_with(['-', '@', ':', '.']))
+            })
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, pricing)| pricing)
+    }
+
+    /// The cost of `usage` on `model`, or `None` when the table does not
+    /// price it.
+    pub fn cost(&self, model: &str, usage: &Usage) -> Option<Cost> {
+        self.get(model).map(|pricing| pricing.cost(usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cost, ModelPricing, PricingTable};
+    use crate::completion::Usage;
+
+    fn usage(input: u64, cached: u64, cache_creation: u64, output: u64, reasoning: u64) -> Usage {
+        let mut usage = Usage::new();
+        usage.input_tokens = input;
+        usage.cached_input_tokens = cached;
+        usage.cache_creation_input_tokens = cache_creation;
+        usage.output_tokens = output;
+        usage.reasoning_tokens = reasoning;
+        usage
+    }
+
+    #[test]
+    fn inclusive_usage_is_split_across_rates() {
+        let pricing = ModelPricing::new(2.0, 8.0)
+            .cached_input(0.5)
+            .cache_creation(2.5)
+            .reasoning(16.0);
+
+        let cost = pricing.cost(&usage(1_000_000, 200_000, 100_000, 500_000, 100_000));
+
+        assert_eq!(
+            cost,
+            Cost {
+                input: 1.4,
+                cached_input: 0.1,
+                cache_creation: 0.25,
+                output: 3.2,
+                reasoning: 1.6,
+            }
+        );
+    }
+
+    #[test]
+    fn separately_reported_tokens_are_not_subtracted() {
+        // Anthropic-style input and Gemini-style output.
+        let pricing = ModelPricing::new(3.0, 15.0)
+            .cached_input(0.3)
+            .input_includes_cache(false)
+            .output_includes_reasoning(false);
+
+        let cost = pricing.cost(&usage(1_000_000, 1_000_000, 0, 1_000_000, 1_000_000));
+
+        assert_eq!(cost.input, 3.0);
+        assert_eq!(cost.cached_input, 0.3);
+        assert_eq!(cost.output, 15.0);
+        // Reasoning falls back to the output rate.
+        assert_eq!(cost.reasoning, 15.0);
+    }
+
+    #[test]
+    fn lookup_prefers_exact_then_longest_prefix_at_a_boundary() {
+        let table = PricingTable::new()
+            .model("gpt-4o", ModelPricing::new(2.5, 10.0))
+            .model("gpt-4o-mini", ModelPricing::new(0.15, 0.6));
+
+        assert_eq!(table.get("gpt-4o").unwrap().input, 2.5);
+        assert_eq!(table.get("gpt-4o-2024-08-06").unwrap().input, 2.5);
+        assert_eq!(table.get("gpt-4o-mini-2024-07-18").unwrap().input, 0.15);
+        assert!(table.get("gpt-4").is_none());
+        assert!(table.get("gpt-4oo").is_none());
+        assert!(table.cost("claude-sonnet-4-5", &Usage::new()).is_none());
+    }
+
+    #[test]
+    fn json_tables_default_currency_and_usage_flags() {
+        let table = PricingTable::from_json(
+            r#"{"models": {"claude-sonnet-4-5": {
+                "input": 3.0, "output": 15.0, "cache_creation": 3.75,
+                "input_includes_cache": false
+            }}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(table.currency, "USD");
+        let pricing = table.get("claude-sonnet-4-5").unwrap();
+        assert!(!pricing.input_includes_cache);
+        assert!(pricing.output_includes_reasoning);
+        assert_eq!(pricing.cache_creation, Some(3.75));
+        assert_eq!(
+            PricingTable::from_json(&serde_json::to_string(&table).unwrap()).unwrap(),
+            table
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_tables_parse() {
+        let table = PricingTable::from_toml(
+            r#"
+            currency = "EUR"
+
+            [models."gemini-2.5-pro"]
+            input = 1.25
+            output = 10.0
+            output_includes_reasoning = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(table.currency, "EUR");
+        assert!(
+            !table
+                .get("gemini-2.5-pro")
+                .unwrap()
+                .output_includes_reasoning
+        );
+    }
+}
@@ -680,6 +680,7 @@ mod tests {
         assert_funnel!(crate::transcription::TranscriptionError);
         assert_funnel!(crate::client::verify::VerifyError);
         assert_funnel!(crate::rerank::RerankError);
+        assert_funnel!(crate::moderation::ModerationError);
         #[cfg(feature = "image")]
         assert_funnel!(crate::image_generation::ImageGenerationError);
         #[cfg(feature = "audio")]
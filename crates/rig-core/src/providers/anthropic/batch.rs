@@ -0,0 +1,401 @@
+//! Anthropic Message Batches — [`BatchCompletionClient`] for
+//! [`super::Client`].
+//!
+//! Requests are sent inline to `POST /v1/messages/batches`, each as the exact
+//! body the synchronous Messages endpoint would receive, and results come back
+//! as JSONL from `/v1/messages/batches/{id}/results`. The results path is
+//! built from the client's base URL rather than taken from the batch's
+//! `results_url`, so a proxied or mocked client keeps talking to the host it
+//! was configured with.
+
+use serde::Deserialize;
+
+use super::client::Client;
+use super::completion::{CompletionModel, CompletionResponse};
+use crate::client::batch::{
+    Batch, BatchCompletionClient, BatchError, BatchOutcome, BatchRequest, BatchRequestCounts,
+    BatchResult, BatchStatus, parse_jsonl, validate_batch_requests, validate_resource_id,
+};
+use crate::completion::NormalizeCompletionResponse;
+use crate::http_client::{HttpClientExt, send_for_bytes};
+use crate::providers::anthropic::completion::AnthropicCompatibleProvider;
+use crate::telemetry::CompletionOperation;
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+const BATCHES_PATH: &str = "/v1/messages/batches";
+
+/// Anthropic's documented `custom_id` limit.
+const MAX_CUSTOM_ID_LEN: usize = 64;
+
+#[derive(Debug, Deserialize)]
+struct MessageBatch {
+    id: String,
+    processing_status: String,
+    #[serde(default)]
+    request_counts: MessageBatchRequestCounts,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MessageBatchRequestCounts {
+    #[serde(default)]
+    processing: u64,
+    #[serde(default)]
+    succeeded: u64,
+    #[serde(default)]
+    errored: u64,
+    #[serde(default)]
+    canceled: u64,
+    #[serde(default)]
+    expired: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultLine {
+    custom_id: String,
+    result: serde_json::Value,
+}
+
+impl MessageBatch {
+    fn into_batch(self, raw: serde_json::Value) -> Result<Batch, BatchError> {
+        let status = match self.processing_status.as_str() {
+            "in_progress" => BatchStatus::InProgress,
+            "canceling" => BatchStatus::Cancelling,
+            "ended" => BatchStatus::Completed,
+            other => {
+                return Err(BatchError::ProviderError(format!(
+                    "unknown Anthropic batch processing_status `{other}`"
+                )));
+            }
+        };
+        let counts = self.request_counts;
+        Ok(Batch {
+            id: self.id,
+            status,
+            request_counts: BatchRequestCounts {
+                processing: counts.processing,
+                succeeded: counts.succeeded,
+                errored: counts.errored,
+                cancelled: counts.canceled,
+                expired: counts.expired,
+            },
+            created_at: self.created_at,
+            errors: Vec::new(),
+            raw,
+        })
+    }
+}
+
+fn decode_batch(body: &[u8]) -> Result<Batch, BatchError> {
+    let raw: serde_json::Value = serde_json::from_slice(body)?;
+    let batch: MessageBatch = serde_json::from_value(raw.clone())?;
+    batch.into_batch(raw)
+}
+
+fn validate_custom_id(custom_id: &str) -> Result<(), BatchError> {
+    let valid = custom_id.len() <= MAX_CUSTOM_ID_LEN
+        && custom_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(BatchError::InvalidBatch(format!(
+            "Anthropic `custom_id`s are 1-{MAX_CUSTOM_ID_LEN} ASCII letters, digits, `-` or `_`; \
+             got `{custom_id}`"
+        )))
+    }
+}
+
+fn normalize_message(
+    raw: serde_json::Value,
+) -> Result<crate::completion::CompletionResponse, String> {
+    let message: CompletionResponse = serde_json::from_value(raw)
+        .map_err(|error| format!("could not decode the batch result message: {error}"))?;
+    message
+        .normalize(<super::client::AnthropicExt as AnthropicCompatibleProvider>::PROVIDER_NAME)
+        .map_err(|error| format!("could not normalize the batch result message: {error}"))
+}
+
+/// Turn one line of the results file into a [`BatchResult`].
+fn decode_result(line: ResultLine) -> Result<BatchResult, BatchError> {
+    let kind = line
+        .result
+        .get("type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let outcome = match kind {
+        "succeeded" => {
+            let raw = line
+                .result
+                .get("message")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            // A result rig cannot normalize is reported against its own
+            // request rather than failing the retrieval of every other one.
+            match normalize_message(raw.clone()) {
+                Ok(response) => BatchOutcome::Succeeded(Box::new(response.with_raw(raw))),
+                Err(message) => BatchOutcome::Errored { message, raw },
+            }
+        }
+        "errored" => {
+            let raw = line
+                .result
+                .get("error")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            // `{"type": "error", "error": {"type": "...", "message": "..."}}`
+            let message = raw
+                .pointer("/error/message")
+                .or_else(|| raw.get("message"))
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("request errored")
+                .to_owned();
+            BatchOutcome::Errored { message, raw }
+        }
+        "canceled" => BatchOutcome::Cancelled,
+        "expired" => BatchOutcome::Expired,
+        other => {
+            return Err(BatchError::ProviderError(format!(
+                "unknown Anthropic batch result type `{other}` for `{}`",
+                line.custom_id
+            )));
+        }
+    };
+    Ok(BatchResult {
+        custom_id: line.custom_id,
+        outcome,
+    })
+}
+
+impl<H> BatchCompletionClient for Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn submit_batch(
+        &self,
+        model: &str,
+        requests: Vec<BatchRequest>,
+    ) -> Result<Batch, BatchError> {
+        validate_batch_requests(&requests)?;
+        let completion_model = CompletionModel::new(self.clone(), model);
+
+        let mut params = Vec::with_capacity(requests.len());
+        for BatchRequest { custom_id, request } in requests {
+            validate_custom_id(&custom_id)?;
+            // The span `prepare_request` opens is for a live call; a batch
+            // entry is only serialized here.
+            let (_span, body) = completion_model
+                .prepare_request(request, CompletionOperation::Chat)
+                .map_err(|source| BatchError::InvalidRequest {
+                    custom_id: custom_id.clone(),
+                    source,
+                })?;
+            params.push(serde_json::json!({
+                "custom_id": custom_id,
+                "params": body,
+            }));
+        }
+
+        let body = serde_json::to_vec(&serde_json::json!({ "requests": params }))?;
+        let request = self.post(BATCHES_PATH)?.body(body)?;
+        decode_batch(&send_for_bytes(self, request).await?)
+    }
+
+    async fn batch(&self, id: &str) -> Result<Batch, BatchError> {
+        let id = validate_resource_id("batch", id)?;
+        let request = self.get(format!("{BATCHES_PATH}/{id}"))?.body(Vec::new())?;
+        decode_batch(&send_for_bytes(self, request).await?)
+    }
+
+    async fn cancel_batch(&self, id: &str) -> Result<Batch, BatchError> {
+        let id = validate_resource_id("batch", id)?;
+        let request = self
+            .post(format!("{BATCHES_PATH}/{id}/cancel"))?
+            .body(Vec::new())?;
+        decode_batch(&send_for_bytes(self, request).await?)
+    }
+
+    async fn batch_results(&self, id: &str) -> Result<Vec<BatchResult>, BatchError> {
+        let batch = self.batch(id).await?;
+        if !batch.status.is_terminal() {
+            return Err(BatchError::NotReady {
+                id: batch.id,
+                status: batch.status,
+            });
+        }
+
+        let request = self
+            .get(format!("{BATCHES_PATH}/{}/results", batch.id))?
+            .body(Vec::new())?;
+        let body = send_for_bytes(self, request).await?;
+        parse_jsonl::<ResultLine>(&body)?
+            .into_iter()
+            .map(decode_result)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{AssistantContent, CompletionRequestBuilder};
+    use crate::test_utils::{MockCompletionModel, MockHttpResponse, SequencedHttpClient};
+
+    fn client(http_client: SequencedHttpClient) -> Client<SequencedHttpClient> {
+        Client::builder()
+            .api_key("test-key")
+            .http_client(http_client)
+            .build()
+            .expect("build client")
+    }
+
+    fn batch_json(status: &str) -> String {
+        serde_json::json!({
+            "id": "msgbatch_01",
+            "type": "message_batch",
+            "processing_status": status,
+            "request_counts": {
+                "processing": 0, "succeeded": 1, "errored": 1, "canceled": 0, "expired": 1
+            },
+            "created_at": "2026-10-17T00:00:00Z",
+        })
+        .to_string()
+    }
+
+    fn request(prompt: &str) -> crate::completion::CompletionRequest {
+        CompletionRequestBuilder::new(MockCompletionModel::default(), prompt)
+            .max_tokens(64)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn submit_sends_each_request_as_messages_params() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(batch_json("in_progress"))]);
+        let batch = client(http.clone())
+            .submit_batch(
+                "claude-haiku-4-5",
+                vec![
+                    BatchRequest::new("a", request("first")),
+                    BatchRequest::new("b", request("second")),
+                ],
+            )
+            .await
+            .expect("submit");
+
+        assert_eq!(batch.id, "msgbatch_01");
+        assert_eq!(batch.status, BatchStatus::InProgress);
+        assert_eq!(batch.request_counts.total(), 3);
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.ends_with("/v1/messages/batches"));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json body");
+        assert_eq!(body["requests"][0]["custom_id"], "a");
+        assert_eq!(body["requests"][0]["params"]["model"], "claude-haiku-4-5");
+        assert_eq!(body["requests"][0]["params"]["max_tokens"], 64);
+        assert_eq!(
+            body["requests"][1]["params"]["messages"][0]["content"][0]["text"],
+            "second"
+        );
+        assert!(body["requests"][0]["params"].get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn submit_rejects_ids_anthropic_would_refuse_without_sending() {
+        let http = SequencedHttpClient::new([]);
+        let error = client(http.clone())
+            .submit_batch(
+                "claude-haiku-4-5",
+                vec![BatchRequest::new("has space", request("hi"))],
+            )
+            .await
+            .expect_err("invalid custom id");
+
+        assert!(matches!(error, BatchError::InvalidBatch(_)), "{error:?}");
+        assert!(http.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn results_are_normalized_per_outcome() {
+        let results = [
+            serde_json::json!({"custom_id": "a", "result": {"type": "succeeded", "message": {
+                "id": "msg_1", "type": "message", "role": "assistant", "model": "claude-haiku-4-5",
+                "content": [{"type": "text", "text": "Paris"}],
+                "stop_reason": "end_turn", "stop_sequence": null,
+                "usage": {"input_tokens": 10, "output_tokens": 2}
+            }}}),
+            serde_json::json!({"custom_id": "b", "result": {"type": "errored", "error": {
+                "type": "error", "error": {"type": "invalid_request_error", "message": "too long"}
+            }}}),
+            serde_json::json!({"custom_id": "c", "result": {"type": "expired"}}),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(batch_json("ended")),
+            MockHttpResponse::success(results),
+        ]);
+
+        let results = client(http.clone())
+            .batch_results("msgbatch_01")
+            .await
+            .expect("results");
+
+        assert_eq!(results.len(), 3);
+        let response = results[0].outcome.response().expect("first succeeded");
+        assert_eq!(response.choice, vec![AssistantContent::text("Paris")]);
+        assert_eq!(response.usage.input_tokens, 10);
+        assert_eq!(response.message_id.as_deref(), Some("msg_1"));
+        assert_eq!(response.raw["id"], "msg_1");
+        assert!(matches!(
+            &results[1].outcome,
+            BatchOutcome::Errored { message, .. } if message == "too long"
+        ));
+        assert!(matches!(results[2].outcome, BatchOutcome::Expired));
+        assert!(
+            http.requests()[1]
+                .uri
+                .ends_with("/v1/messages/batches/msgbatch_01/results")
+        );
+    }
+
+    #[tokio::test]
+    async fn results_of_a_running_batch_are_not_ready() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(batch_json("in_progress"))]);
+        let error = client(http)
+            .batch_results("msgbatch_01")
+            .await
+            .expect_err("still running");
+
+        assert!(
+            matches!(
+                error,
+                BatchError::NotReady {
+                    status: BatchStatus::InProgress,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_posts_to_the_cancel_endpoint() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(batch_json("canceling"))]);
+        let batch = client(http.clone())
+            .cancel_batch("msgbatch_01")
+            .await
+            .expect("cancel");
+
+        assert_eq!(batch.status, BatchStatus::Cancelling);
+        assert!(
+            http.requests()[0]
+                .uri
+                .ends_with("/v1/messages/batches/msgbatch_01/cancel")
+        );
+    }
+}
@@ -912,9 +912,10 @@ impl FromStr for ToolResultContent {
 
 /// The source of an image content block.
 ///
-/// Anthropic supports two source types for images:
+/// Anthropic supports three source types for images:
 /// - `Base64`: Base64-encoded image data with media type
 /// - `Url`: URL reference to an image
+/// - `File`: Provider-side uploaded file reference from the Files API
 ///
 /// See: <https://docs.anthropic.com/en/api/messages>
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -927,6 +928,8 @@ pub enum ImageSource {
     },
     #[serde(rename = "url")]
     Url { url: String },
+    #[serde(rename = "file")]
+    File { file_id: String },
 }
 
 /// The source of a document content block.
@@ -1187,11 +1190,20 @@ impl TryFrom<message::Message> for Message {
                                 })
                             }
                             message::ToolResultContent::Image(image) => {
-                                let DocumentSourceKind::Base64(data) = image.data else {
-                                    return Err(MessageError::ConversionError(
-                                        "Only base64 strings can be used with the Anthropic API"
-                                            .to_string(),
-                                    ));
+                                let data = match image.data {
+                                    DocumentSourceKind::Base64(data) => data,
+                                    DocumentSourceKind::FileId(file_id) => {
+                                        return Ok(ToolResultContent::Image {
+                                            source: ImageSource::File { file_id },
+                                        });
+                                    }
+                                    _ => {
+                                        return Err(MessageError::ConversionError(
+                                            "Only base64 strings and file ids can be used for \
+                                             Anthropic tool result images"
+                                                .to_string(),
+                                        ));
+                                    }
                                 };
                                 let media_type =
                                     image.media_type.ok_or(MessageError::ConversionError(
@@ -1223,6 +1235,7 @@ impl TryFrom<message::Message> for Message {
                                 }
                             }
                             DocumentSourceKind::Url(url) => ImageSource::Url { url },
+                            DocumentSourceKind::FileId(file_id) => ImageSource::File { file_id },
                             DocumentSourceKind::Unknown => {
                                 return Err(MessageError::ConversionError(
                                     "Image content has no body".into(),
@@ -1416,6 +1429,12 @@ impl From<ToolResultContent> for message::ToolResultContent {
                     message::ToolResultContent::image_base64(data, Some(media_type.into()), None)
                 }
                 ImageSource::Url { url } => message::ToolResultContent::image_url(url, None, None),
+                ImageSource::File { file_id } => {
+                    message::ToolResultContent::Image(message::Image {
+                        data: DocumentSourceKind::FileId(file_id),
+                        ..Default::default()
+                    })
+                }
             },
         }
     }
@@ -1462,6 +1481,14 @@ impl TryFrom<Message> for message::Message {
                                         additional_params: None,
                                     })
                                 }
+                                ImageSource::File { file_id } => {
+                                    message::UserContent::Image(message::Image {
+                                        data: DocumentSourceKind::FileId(file_id),
+                                        media_type: None,
+                                        detail: None,
+                                        additional_params: None,
+                                    })
+                                }
                             },
                             Content::Document {
                                 source,
@@ -3072,6 +3099,56 @@ where
     }
 }
 
+/// Request fields `count_tokens` accepts. The Messages body also carries
+/// output-only settings (`max_tokens`, `temperature`, `stop_sequences`, …)
+/// that the counting endpoint rejects as unknown, and none of them change
+/// the input count.
+const COUNT_TOKENS_FIELDS: &[&str] = &[
+    "model",
+    "messages",
+    "system",
+    "tools",
+    "tool_choice",
+    "thinking",
+    "output_config",
+    "mcp_servers",
+];
+
+#[derive(Debug, Deserialize)]
+struct CountTokensResponse {
+    input_tokens: u64,
+}
+
+impl<T> completion::TokenCountingModel for CompletionModel<T>
+where
+    T: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn count_tokens(
+        &self,
+        mut completion_request: completion::CompletionRequest,
+    ) -> Result<u64, CompletionError> {
+        // `prepare_request` insists on an output cap that counting then drops.
+        completion_request.max_tokens.get_or_insert(1);
+        let (_span, request) =
+            self.prepare_request(completion_request, CompletionOperation::Chat)?;
+        let serde_json::Value::Object(mut body) = serde_json::to_value(&request)? else {
+            return Err(CompletionError::RequestError(
+                "Anthropic request did not serialize to an object".into(),
+            ));
+        };
+        body.retain(|key, _| COUNT_TOKENS_FIELDS.contains(&key.as_str()));
+
+        let req = self
+            .client
+            .post("/v1/messages/count_tokens")?
+            .body(serde_json::to_vec(&body)?)
+            .map_err(|e| CompletionError::HttpError(e.into()))?;
+        let response: CountTokensResponse =
+            completion::token_count::send_count_request(&self.client, req).await?;
+        Ok(response.input_tokens)
+    }
+}
+
 use crate::providers::internal::envelope::ApiErrorResponse;
 
 #[derive(Debug, Deserialize)]
@@ -5631,6 +5708,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_file_id_image_round_trip() {
+        use crate::completion::message as msg;
+
+        let rig_message = msg::Message::User {
+            content: vec![msg::UserContent::Image(msg::Image {
+                data: DocumentSourceKind::FileId("file_abc".to_string()),
+                ..Default::default()
+            })],
+        };
+
+        let anthropic_message: Message = rig_message.try_into().unwrap();
+        let json = serde_json::to_value(&anthropic_message.content[0]).unwrap();
+        assert_eq!(
+            json["source"],
+            json!({ "type": "file", "file_id": "file_abc" })
+        );
+
+        let rig_message: msg::Message = anthropic_message.try_into().unwrap();
+        let msg::Message::User { content } = rig_message else {
+            panic!("Expected User message");
+        };
+        match content.first() {
+            Some(msg::UserContent::Image(image)) => {
+                assert_eq!(
+                    image.data,
+                    DocumentSourceKind::FileId("file_abc".to_string())
+                );
+            }
+            other => panic!("Expected Image content, got: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_plaintext_rig_to_anthropic_conversion() {
         use crate::completion::message as msg;
@@ -7259,4 +7369,70 @@ mod tests {
             assert_eq!(normalized.provider_request_id.as_deref(), Some(REQUEST_ID));
         }
     }
+
+    mod token_count {
+        use super::*;
+        use crate::client::CompletionClient;
+        use crate::completion::{CompletionModel as _, TokenCountingModel as _};
+        use crate::providers::anthropic::Client;
+        use crate::test_utils::RecordingHttpClient;
+
+        #[tokio::test]
+        async fn count_tokens_sends_only_the_fields_the_endpoint_accepts() {
+            let http_client = RecordingHttpClient::new(r#"{"input_tokens": 1234}"#);
+            let client = Client::builder()
+                .api_key("test-key")
+                .http_client(http_client.clone())
+                .build()
+                .expect("build client");
+            let model = client.completion_model(CLAUDE_SONNET_4_6);
+            let request = model
+                .completion_request("count me")
+                .preamble("be brief".to_owned())
+                .temperature(0.2)
+                .tool(completion::ToolDefinition {
+                    name: "lookup".to_owned(),
+                    description: "Look something up".to_owned(),
+                    parameters: serde_json::json!({"type": "object", "properties": {}}),
+                })
+                .build();
+
+            let count = model.count_tokens(request).await.expect("count");
+
+            assert_eq!(count, 1234);
+            let requests = http_client.requests();
+            assert!(requests[0].uri.ends_with("/v1/messages/count_tokens"));
+            let body: serde_json::Value =
+                serde_json::from_slice(&requests[0].body).expect("json body");
+            assert_eq!(body["model"], CLAUDE_SONNET_4_6);
+            assert_eq!(body["tools"][0]["name"], "lookup");
+            assert!(body["system"].is_array());
+            assert!(body.get("max_tokens").is_none(), "{body}");
+            assert!(body.get("temperature").is_none(), "{body}");
+        }
+
+        #[tokio::test]
+        async fn count_tokens_preserves_provider_errors() {
+            let http_client = RecordingHttpClient::with_error(
+                http::StatusCode::BAD_REQUEST,
+                r#"{"type":"error","error":{"type":"invalid_request_error","message":"bad"}}"#,
+            );
+            let client = Client::builder()
+                .api_key("test-key")
+                .http_client(http_client)
+                .build()
+                .expect("build client");
+            let model = client.completion_model(CLAUDE_SONNET_4_6);
+
+            let error = model
+                .count_tokens(model.completion_request("hi").build())
+                .await
+                .expect_err("400");
+
+            assert_eq!(
+                error.provider_response_status(),
+                Some(http::StatusCode::BAD_REQUEST)
+            );
+        }
+    }
 }
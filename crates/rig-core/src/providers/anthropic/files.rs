@@ -0,0 +1,268 @@
+//! Anthropic Files API — [`FilesClient`] for [`super::Client`].
+//!
+//! The Files API is in beta: every request here carries [`FILES_API_BETA`]
+//! alongside whatever betas the client was built with. A Messages request that
+//! references an uploaded file needs the same beta, which the client has to be
+//! built with (`.anthropic_beta(FILES_API_BETA)`); the completion path does not
+//! add it per request. A [`DocumentSourceKind::FileId`] is sent as a `file`
+//! source on `document` and `image` blocks.
+//!
+//! [`DocumentSourceKind::FileId`]: crate::message::DocumentSourceKind::FileId
+
+use http::HeaderValue;
+use serde::Deserialize;
+
+use super::client::Client;
+use crate::client::files::{
+    FileUpload, FilesClient, FilesError, MAX_LISTING_PAGES, ProviderFile, validate_file_id,
+};
+use crate::http_client::{
+    self, HttpClientExt, MultipartForm, multipart::Part, send_for_bytes, send_multipart_for_bytes,
+};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+/// The `anthropic-beta` value that enables the Files API, both for the files
+/// endpoints and for Messages requests that reference a file.
+pub const FILES_API_BETA: &str = "files-api-2025-04-14";
+
+const FILES_PATH: &str = "/v1/files";
+
+/// The largest page `GET /v1/files` serves.
+const PAGE_LIMIT: &str = "1000";
+
+#[derive(Debug, Deserialize)]
+struct FileMetadata {
+    id: String,
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    size_bytes: Option<u64>,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileList {
+    data: Vec<serde_json::Value>,
+    #[serde(default)]
+    has_more: bool,
+    #[serde(default)]
+    last_id: Option<String>,
+}
+
+fn decode_file(raw: serde_json::Value) -> Result<ProviderFile, FilesError> {
+    let file: FileMetadata = serde_json::from_value(raw.clone())?;
+    Ok(ProviderFile {
+        id: file.id,
+        filename: file.filename,
+        mime_type: file.mime_type,
+        size_bytes: file.size_bytes,
+        created_at: file.created_at,
+        expires_at: None,
+        raw,
+    })
+}
+
+/// Add [`FILES_API_BETA`] to the request's `anthropic-beta` header, keeping
+/// the betas the client already sends: a second header line would be read as
+/// a replacement by some proxies.
+fn with_files_beta(mut builder: http_client::Builder) -> Result<http_client::Builder, FilesError> {
+    if let Some(headers) = builder.headers_mut() {
+        let betas = match headers
+            .get("anthropic-beta")
+            .and_then(|value| value.to_str().ok())
+        {
+            Some(existing)
+                if existing
+                    .split(',')
+                    .any(|beta| beta.trim() == FILES_API_BETA) =>
+            {
+                return Ok(builder);
+            }
+            Some(existing) if !existing.trim().is_empty() => format!("{existing},{FILES_API_BETA}"),
+            _ => FILES_API_BETA.to_owned(),
+        };
+        headers.insert(
+            "anthropic-beta",
+            HeaderValue::from_str(&betas).map_err(http::Error::from)?,
+        );
+    }
+    Ok(builder)
+}
+
+impl<H> FilesClient for Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn upload_file(&self, upload: FileUpload) -> Result<ProviderFile, FilesError> {
+        let mime_type = upload.validate()?;
+        let FileUpload { filename, data, .. } = upload;
+        let form = MultipartForm::new().part(
+            Part::bytes("file", data)
+                .filename(filename)
+                .content_type(mime_type),
+        );
+        let request = with_files_beta(self.post(FILES_PATH)?)?.body(form)?;
+        decode_file(serde_json::from_slice(
+            &send_multipart_for_bytes(self, request).await?,
+        )?)
+    }
+
+    async fn list_files(&self) -> Result<Vec<ProviderFile>, FilesError> {
+        let mut files = Vec::new();
+        let mut after_id: Option<String> = None;
+        for _ in 0..MAX_LISTING_PAGES {
+            let mut pairs = vec![("limit", PAGE_LIMIT)];
+            if let Some(after_id) = &after_id {
+                pairs.push(("after_id", after_id.as_str()));
+            }
+            let path =
+                crate::providers::internal::model_listing::with_query_pairs(FILES_PATH, &pairs);
+            let request = with_files_beta(self.get(path)?)?.body(Vec::new())?;
+            let page: FileList = serde_json::from_slice(&send_for_bytes(self, request).await?)?;
+            for raw in page.data {
+                files.push(decode_file(raw)?);
+            }
+            // A cursor that does not advance would fetch the same page forever.
+            match page.last_id {
+                Some(last_id) if page.has_more && after_id.as_ref() != Some(&last_id) => {
+                    after_id = Some(last_id);
+                }
+                _ => return Ok(files),
+            }
+        }
+        tracing::warn!(
+            provider = "Anthropic",
+            files = files.len(),
+            pages = MAX_LISTING_PAGES,
+            "file listing hit its page ceiling; returning the pages fetched so far"
+        );
+        Ok(files)
+    }
+
+    async fn file(&self, id: &str) -> Result<ProviderFile, FilesError> {
+        let id = validate_file_id(id)?;
+        let request = with_files_beta(self.get(format!("{FILES_PATH}/{id}"))?)?.body(Vec::new())?;
+        decode_file(serde_json::from_slice(
+            &send_for_bytes(self, request).await?,
+        )?)
+    }
+
+    async fn delete_file(&self, id: &str) -> Result<(), FilesError> {
+        let id = validate_file_id(id)?;
+        let request =
+            with_files_beta(self.delete(format!("{FILES_PATH}/{id}"))?)?.body(Vec::new())?;
+        send_for_bytes(self, request).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockHttpResponse, SequencedHttpClient};
+
+    const FILE: &str = r#"{
+        "id": "file_011CNha8iCJcU1wXNR6q4V8w",
+        "type": "file",
+        "filename": "report.pdf",
+        "mime_type": "application/pdf",
+        "size_bytes": 4,
+        "created_at": "2025-04-14T12:00:00Z",
+        "downloadable": false
+    }"#;
+
+    fn client(http: SequencedHttpClient, betas: &[&str]) -> Client<SequencedHttpClient> {
+        Client::builder()
+            .api_key("test-key")
+            .anthropic_betas(betas)
+            .http_client(http)
+            .build()
+            .expect("build client")
+    }
+
+    #[tokio::test]
+    async fn upload_adds_the_files_beta_to_the_clients_own() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(FILE)]);
+        let client = client(http.clone(), &["prompt-caching-2024-07-31"]);
+
+        let file = client
+            .upload_file(FileUpload::new(
+                "report.pdf",
+                "application/pdf",
+                b"%PDF".to_vec(),
+            ))
+            .await
+            .expect("upload");
+        assert_eq!(file.id, "file_011CNha8iCJcU1wXNR6q4V8w");
+        assert_eq!(file.mime_type.as_deref(), Some("application/pdf"));
+
+        let requests = http.requests();
+        assert!(requests[0].uri.ends_with("/v1/files"));
+        assert_eq!(
+            requests[0]
+                .headers
+                .get("anthropic-beta")
+                .and_then(|value| value.to_str().ok()),
+            Some("prompt-caching-2024-07-31,files-api-2025-04-14")
+        );
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(
+            body.contains("name=\"file\"; filename=\"report.pdf\""),
+            "{body}"
+        );
+    }
+
+    #[tokio::test]
+    async fn the_files_beta_is_not_repeated() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(FILE)]);
+        let client = client(http.clone(), &[FILES_API_BETA]);
+
+        client
+            .file("file_011CNha8iCJcU1wXNR6q4V8w")
+            .await
+            .expect("get");
+        assert_eq!(
+            http.requests()[0]
+                .headers
+                .get("anthropic-beta")
+                .and_then(|value| value.to_str().ok()),
+            Some(FILES_API_BETA)
+        );
+    }
+
+    #[tokio::test]
+    async fn list_follows_the_after_id_cursor_and_delete_addresses_the_file() {
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(
+                r#"{"data": [{"id": "file_1"}], "has_more": true, "first_id": "file_1", "last_id": "file_1"}"#,
+            ),
+            MockHttpResponse::success(
+                r#"{"data": [{"id": "file_2"}], "has_more": false, "first_id": "file_2", "last_id": "file_2"}"#,
+            ),
+            MockHttpResponse::success(r#"{"id": "file_2", "type": "file_deleted"}"#),
+        ]);
+        let client = client(http.clone(), &[]);
+
+        let files = client.list_files().await.expect("list");
+        assert_eq!(files.len(), 2);
+        client.delete_file("file_2").await.expect("delete");
+
+        let requests = http.requests();
+        assert!(
+            requests[1].uri.contains("after_id=file_1"),
+            "{}",
+            requests[1].uri
+        );
+        assert!(requests[2].uri.ends_with("/v1/files/file_2"));
+        assert_eq!(
+            requests[2]
+                .headers
+                .get("anthropic-beta")
+                .and_then(|value| value.to_str().ok()),
+            Some(FILES_API_BETA)
+        );
+    }
+}
@@ -12,8 +12,10 @@
 //! # }
 //! ```
 
+pub mod batch;
 pub mod client;
 pub mod completion;
+pub mod files;
 pub mod model_listing;
 pub mod streaming;
 
@@ -37,10 +37,12 @@ use crate::providers::gemini::completion::gemini_api_types::{
 use crate::providers::internal::completion_send::send_completion;
 use crate::providers::internal::envelope::DirectPayload;
 use crate::telemetry::{CompletionOperation, CompletionSpanBuilder, SpanCombinator};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
 use gemini_api_types::{
     Content, FinishReason, FunctionDeclaration, GenerateContentRequest, GenerateContentResponse,
     GenerationConfig, Part, PartKind, Role, Tool, map_finish_reason,
 };
+use serde::Deserialize;
 use serde_json::{Map, Value};
 use std::convert::TryFrom;
 use tracing_futures::Instrument;
@@ -251,6 +253,52 @@ where
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CountTokensResponse {
+    total_tokens: u64,
+}
+
+impl<T> completion::TokenCountingModel for CompletionModel<T>
+where
+    T: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    /// Counts through `countTokens` with the whole `generateContent` request
+    /// (the `generateContentRequest` form), not just `contents`, so the system
+    /// instruction, tools and a cached-content handle are all counted.
+    async fn count_tokens(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<u64, CompletionError> {
+        let request_model = resolve_request_model(&self.model, &completion_request);
+        let mut request = create_request_body(completion_request)?;
+        if let Some(name) = self.cached_content.as_deref() {
+            request.with_cached_content(name)?;
+        }
+        let mut generate_content_request = serde_json::to_value(&request)?;
+        if let Some(object) = generate_content_request.as_object_mut() {
+            // The nested request must name its model; the URL's does not
+            // carry over.
+            object.insert(
+                "model".to_owned(),
+                Value::String(format!("models/{request_model}")),
+            );
+        }
+        let body = serde_json::to_vec(&serde_json::json!({
+            "generateContentRequest": generate_content_request,
+        }))?;
+
+        let request = self
+            .client
+            .post(count_tokens_endpoint(&request_model).as_str())?
+            .body(body)
+            .map_err(|e| CompletionError::HttpError(e.into()))?;
+        let response: CountTokensResponse =
+            completion::token_count::send_count_request(&self.client, request).await?;
+        Ok(response.total_tokens)
+    }
+}
+
 pub(crate) fn create_request_body(
     completion_request: CompletionRequest,
 ) -> Result<GenerateContentRequest, CompletionError> {
@@ -548,6 +596,10 @@ pub(crate) fn streaming_endpoint(model: &str) -> String {
     format!("/v1beta/models/{model}:streamGenerateContent")
 }
 
+pub(crate) fn count_tokens_endpoint(model: &str) -> String {
+    format!("/v1beta/models/{model}:countTokens")
+}
+
 impl TryFrom<completion::ToolDefinition> for Tool {
     type Error = CompletionError;
 
@@ -1079,8 +1131,8 @@ pub mod gemini_api_types {
     /// Map a media body onto the Gemini part kind that carries it.
     ///
     /// Gemini takes every non-text body one of exactly two ways — a URI
-    /// reference (`fileData`) or a base64 payload (`inlineData`) — and rejects
-    /// the rest. `kind` names the medium in the rejection messages.
+    /// reference (`fileData`, which is also how an uploaded file is named) or
+    /// a base64 payload (`inlineData`) — and rejects the rest. `kind` names the medium in the rejection messages.
     /// `string_is_data` says whether an untagged [`DocumentSourceKind::String`]
     /// counts as a payload for this medium: it does for images and documents,
     /// whose bodies routinely arrive as an unlabelled base64 string, but a bare
@@ -1106,9 +1158,10 @@ pub mod gemini_api_types {
             DocumentSourceKind::Raw(_) => Err(message::MessageError::ConversionError(
                 "Raw files not supported, encode as base64 first".to_string(),
             )),
-            DocumentSourceKind::FileId(_) => Err(message::MessageError::ConversionError(format!(
-                "Provider file IDs are not supported for Gemini {kind} inputs"
-            ))),
+            DocumentSourceKind::FileId(file_id) => Ok(PartKind::FileData(FileData {
+                mime_type: Some(mime_type),
+                file_uri: crate::providers::gemini::files::file_uri(&file_id),
+            })),
             DocumentSourceKind::Unknown => Err(message::MessageError::ConversionError(format!(
                 "Gemini {kind} input has no body"
             ))),
@@ -1322,12 +1375,10 @@ pub mod gemini_api_types {
                                     "Raw files not supported, encode as base64 first".to_string(),
                                 ));
                             }
-                            DocumentSourceKind::FileId(_) => {
-                                return Err(MessageError::ConversionError(
-                                    "Provider file IDs are not supported for Gemini documents"
-                                        .to_string(),
-                                ));
-                            }
+                            DocumentSourceKind::FileId(file_id) => PartKind::FileData(FileData {
+                                mime_type: Some(media_type.to_mime_type().to_string()),
+                                file_uri: crate::providers::gemini::files::file_uri(&file_id),
+                            }),
                             DocumentSourceKind::Unknown => {
                                 return Err(MessageError::ConversionError(
                                     "Document has no body".to_string(),
@@ -4163,6 +4214,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_uploaded_file_document_conversion_to_file_data_part() {
+        use crate::message::{DocumentMediaType, DocumentSourceKind, UserContent};
+
+        let doc = UserContent::Document(message::Document {
+            data: DocumentSourceKind::FileId("files/abc-123".to_string()),
+            media_type: Some(DocumentMediaType::PDF),
+            additional_params: None,
+        });
+
+        let content: Content = message::Message::User { content: vec![doc] }
+            .try_into()
+            .unwrap();
+
+        let Part {
+            part: PartKind::FileData(file_data),
+            ..
+        } = &content.parts[0]
+        else {
+            panic!("Expected file_data part, got: {:?}", content.parts[0]);
+        };
+        assert_eq!(
+            file_data.file_uri,
+            "https://generativelanguage.googleapis.com/v1beta/files/abc-123"
+        );
+        assert_eq!(file_data.mime_type.as_deref(), Some("application/pdf"));
+    }
+
     #[test]
     fn test_tool_result_with_url_image_is_rejected() {
         use crate::message::{
@@ -4358,6 +4437,48 @@ mod tests {
         );
         assert_eq!(error.provider_response_body(), Some(body));
     }
+
+    #[tokio::test]
+    async fn count_tokens_wraps_the_full_generate_content_request() {
+        use crate::client::completion::CompletionClient;
+        use crate::completion::{CompletionModel as _, TokenCountingModel as _};
+        use crate::providers::gemini::Client;
+        use crate::test_utils::RecordingHttpClient;
+
+        let http_client = RecordingHttpClient::new(r#"{"totalTokens": 42}"#);
+        let client = Client::builder()
+            .api_key("test-key")
+            .http_client(http_client.clone())
+            .build()
+            .expect("build client");
+        let model = client.completion_model(super::GEMINI_3_FLASH_PREVIEW);
+        let request = model
+            .completion_request("hello")
+            .preamble("be terse".to_owned())
+            .build();
+
+        let count = model.count_tokens(request).await.expect("count tokens");
+        assert_eq!(count, 42);
+
+        let requests = http_client.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0].uri.contains(&format!(
+                "/v1beta/models/{}:countTokens",
+                super::GEMINI_3_FLASH_PREVIEW
+            )),
+            "unexpected uri {}",
+            requests[0].uri
+        );
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json body");
+        let inner = &body["generateContentRequest"];
+        assert_eq!(
+            inner["model"],
+            format!("models/{}", super::GEMINI_3_FLASH_PREVIEW)
+        );
+        assert!(inner.get("systemInstruction").is_some());
+        assert!(inner.get("contents").is_some());
+    }
 }
 
 #[cfg(test)]
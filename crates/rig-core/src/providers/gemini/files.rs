@@ -0,0 +1,311 @@
+//! Gemini Files API — [`FilesClient`] for [`super::Client`].
+//!
+//! Files are uploaded in one `multipart/related` request (metadata, then the
+//! bytes) to the `/upload/v1beta/files` endpoint and live for 48 hours. Their
+//! id is the resource name, `files/<id>`. A [`DocumentSourceKind::FileId`]
+//! holding that name (or the file's full `uri`) is sent as a `fileData` part,
+//! which — unlike `inlineData` — also needs the part's MIME type, so documents
+//! and media referencing a file must carry one.
+//!
+//! Video and audio files are processed after upload; until the file's `state`
+//! (in [`ProviderFile::raw`]) is `ACTIVE`, requests that reference it fail.
+//!
+//! [`DocumentSourceKind::FileId`]: crate::message::DocumentSourceKind::FileId
+
+use serde::Deserialize;
+
+use super::Client;
+use crate::client::files::{FileUpload, FilesClient, FilesError, MAX_LISTING_PAGES, ProviderFile};
+use crate::http_client::{HttpClientExt, send_for_bytes};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+const FILES_PATH: &str = "/v1beta/files";
+
+const UPLOAD_PATH: &str = "/upload/v1beta/files?uploadType=multipart";
+
+/// The prefix of every file's `uri`. Gemini resolves the URI itself, so it is
+/// the public host's even when the client talks to a proxy.
+const FILE_URI_PREFIX: &str = "https://generativelanguage.googleapis.com/v1beta/files/";
+
+/// The largest page `files.list` serves.
+const PAGE_SIZE: &str = "100";
+
+const MULTIPART_BOUNDARY: &str = "rig-gemini-file-upload";
+
+const MULTIPART_CONTENT_TYPE: &str = "multipart/related; boundary=rig-gemini-file-upload";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiFile {
+    name: String,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default)]
+    mime_type: Option<String>,
+    /// An `int64`, which proto3 JSON writes as a string.
+    #[serde(default)]
+    size_bytes: Option<String>,
+    #[serde(default)]
+    create_time: Option<String>,
+    #[serde(default)]
+    expiration_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListFilesResponse {
+    #[serde(default)]
+    files: Vec<serde_json::Value>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+fn decode_file(raw: serde_json::Value) -> Result<ProviderFile, FilesError> {
+    let file: GeminiFile = serde_json::from_value(raw.clone())?;
+    Ok(ProviderFile {
+        id: file.name,
+        filename: file.display_name,
+        mime_type: file.mime_type,
+        size_bytes: file.size_bytes.and_then(|size| size.parse().ok()),
+        created_at: file.create_time,
+        expires_at: file.expiration_time,
+        raw,
+    })
+}
+
+/// The `fileData.fileUri` for a file id: the name `files/<id>`, a bare `<id>`,
+/// or a full URI, which is passed through.
+pub(crate) fn file_uri(file_id: &str) -> String {
+    if file_id.starts_with("https://") || file_id.starts_with("http://") {
+        return file_id.to_owned();
+    }
+    let id = file_id.strip_prefix("files/").unwrap_or(file_id);
+    format!("{FILE_URI_PREFIX}{id}")
+}
+
+/// `/v1beta/files/<id>` from either a bare id or a full `files/<id>` name.
+///
+/// The id is spliced into the path, where a `?`, `#` or `/` would retarget
+/// the call — and one of the paths is the one that deletes.
+fn resource_path(name: &str) -> Result<String, FilesError> {
+    let id = name.strip_prefix("files/").unwrap_or(name);
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+    {
+        return Err(FilesError::InvalidFile(format!(
+            "`{name}` is not a Gemini file name; expected `files/<id>` or a bare `<id>` of \
+             letters, digits, `-` and `_`"
+        )));
+    }
+    Ok(format!("{FILES_PATH}/{id}"))
+}
+
+/// The `multipart/related` body of a one-request upload: the file's metadata
+/// as JSON, then its bytes.
+fn multipart_related_body(
+    upload: &FileUpload,
+    mime_type: &mime::Mime,
+) -> Result<Vec<u8>, FilesError> {
+    let metadata = serde_json::to_vec(&serde_json::json!({
+        "file": { "displayName": upload.filename },
+    }))?;
+    if upload
+        .data
+        .windows(MULTIPART_BOUNDARY.len())
+        .any(|window| window == MULTIPART_BOUNDARY.as_bytes())
+    {
+        return Err(FilesError::InvalidFile(format!(
+            "`{}` contains the multipart boundary",
+            upload.filename
+        )));
+    }
+
+    let mut body = Vec::with_capacity(upload.data.len() + metadata.len() + 256);
+    body.extend_from_slice(
+        format!("--{MULTIPART_BOUNDARY}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n")
+            .as_bytes(),
+    );
+    body.extend_from_slice(&metadata);
+    body.extend_from_slice(
+        format!("\r\n--{MULTIPART_BOUNDARY}\r\nContent-Type: {mime_type}\r\n\r\n").as_bytes(),
+    );
+    body.extend_from_slice(&upload.data);
+    body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+    Ok(body)
+}
+
+impl<H> FilesClient for Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn upload_file(&self, upload: FileUpload) -> Result<ProviderFile, FilesError> {
+        let mime_type = upload.validate()?;
+        let body = multipart_related_body(&upload, &mime_type)?;
+        let request = self
+            .post(UPLOAD_PATH)?
+            .header("X-Goog-Upload-Protocol", "multipart")
+            .header(http::header::CONTENT_TYPE, MULTIPART_CONTENT_TYPE)
+            .body(body)?;
+        let mut response: serde_json::Value =
+            serde_json::from_slice(&send_for_bytes(self, request).await?)?;
+        match response.get_mut("file").map(serde_json::Value::take) {
+            Some(file) => decode_file(file),
+            None => Err(FilesError::ProviderError(format!(
+                "Gemini upload response has no `file`: {response}"
+            ))),
+        }
+    }
+
+    async fn list_files(&self) -> Result<Vec<ProviderFile>, FilesError> {
+        let mut files = Vec::new();
+        let mut page_token: Option<String> = None;
+        for _ in 0..MAX_LISTING_PAGES {
+            let mut pairs = vec![("pageSize", PAGE_SIZE)];
+            if let Some(token) = &page_token {
+                pairs.push(("pageToken", token.as_str()));
+            }
+            let path =
+                crate::providers::internal::model_listing::with_query_pairs(FILES_PATH, &pairs);
+            let request = self.get(path)?.body(Vec::new())?;
+            let page: ListFilesResponse =
+                serde_json::from_slice(&send_for_bytes(self, request).await?)?;
+            for raw in page.files {
+                files.push(decode_file(raw)?);
+            }
+            // An empty or repeated cursor would fetch the same page forever.
+            match page.next_page_token.filter(|token| !token.is_empty()) {
+                Some(token) if page_token.as_ref() != Some(&token) => page_token = Some(token),
+                _ => return Ok(files),
+            }
+        }
+        tracing::warn!(
+            provider = "Gemini",
+            files = files.len(),
+            pages = MAX_LISTING_PAGES,
+            "file listing hit its page ceiling; returning the pages fetched so far"
+        );
+        Ok(files)
+    }
+
+    async fn file(&self, id: &str) -> Result<ProviderFile, FilesError> {
+        let request = self.get(resource_path(id)?)?.body(Vec::new())?;
+        decode_file(serde_json::from_slice(
+            &send_for_bytes(self, request).await?,
+        )?)
+    }
+
+    async fn delete_file(&self, id: &str) -> Result<(), FilesError> {
+        let request = self.delete(resource_path(id)?)?.body(Vec::new())?;
+        send_for_bytes(self, request).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockHttpResponse, SequencedHttpClient};
+
+    const FILE: &str = r#"{
+        "name": "files/abc-123",
+        "displayName": "clip.mp4",
+        "mimeType": "video/mp4",
+        "sizeBytes": "1048576",
+        "createTime": "2025-01-01T00:00:00.000000Z",
+        "expirationTime": "2025-01-03T00:00:00.000000Z",
+        "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+        "state": "PROCESSING"
+    }"#;
+
+    fn client(http: SequencedHttpClient) -> Client<SequencedHttpClient> {
+        Client::builder()
+            .api_key("test-key")
+            .http_client(http)
+            .build()
+            .expect("build client")
+    }
+
+    #[test]
+    fn file_ids_become_file_uris() {
+        let uri = "https://generativelanguage.googleapis.com/v1beta/files/abc-123";
+        assert_eq!(file_uri("files/abc-123"), uri);
+        assert_eq!(file_uri("abc-123"), uri);
+        assert_eq!(file_uri(uri), uri);
+    }
+
+    #[tokio::test]
+    async fn upload_sends_metadata_then_bytes_as_multipart_related() {
+        let http =
+            SequencedHttpClient::new([MockHttpResponse::success(format!(r#"{{"file": {FILE}}}"#))]);
+        let client = client(http.clone());
+
+        let file = client
+            .upload_file(FileUpload::new(
+                "clip.mp4",
+                "video/mp4",
+                b"\x00\x01".to_vec(),
+            ))
+            .await
+            .expect("upload");
+        assert_eq!(file.id, "files/abc-123");
+        assert_eq!(file.size_bytes, Some(1_048_576));
+        assert_eq!(file.filename.as_deref(), Some("clip.mp4"));
+
+        let requests = http.requests();
+        assert!(
+            requests[0]
+                .uri
+                .contains("/upload/v1beta/files?uploadType=multipart&key=test-key"),
+            "{}",
+            requests[0].uri
+        );
+        assert_eq!(
+            requests[0]
+                .headers
+                .get(http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok()),
+            Some("multipart/related; boundary=rig-gemini-file-upload")
+        );
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(
+            body.contains(r#"{"file":{"displayName":"clip.mp4"}}"#),
+            "{body}"
+        );
+        assert!(
+            body.contains("Content-Type: video/mp4\r\n\r\n\u{0}\u{1}\r\n"),
+            "{body}"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_follows_page_tokens_and_delete_addresses_the_file() {
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(format!(r#"{{"files": [{FILE}], "nextPageToken": "next"}}"#)),
+            MockHttpResponse::success(r#"{"files": [{"name": "files/def"}]}"#),
+            MockHttpResponse::success("{}"),
+        ]);
+        let client = client(http.clone());
+
+        let files = client.list_files().await.expect("list");
+        assert_eq!(files.len(), 2);
+        client.delete_file("files/def").await.expect("delete");
+
+        let requests = http.requests();
+        assert!(
+            requests[1].uri.contains("pageToken=next"),
+            "{}",
+            requests[1].uri
+        );
+        assert!(
+            requests[2].uri.contains("/v1beta/files/def?key="),
+            "{}",
+            requests[2].uri
+        );
+        assert!(matches!(
+            client.delete_file("files/def?x=1").await,
+            Err(FilesError::InvalidFile(_))
+        ));
+    }
+}
@@ -16,6 +16,7 @@ pub mod cached_content;
 pub mod client;
 pub mod completion;
 pub mod embedding;
+pub mod files;
 #[cfg(feature = "image")]
 #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
 pub mod image_generation;
@@ -21,6 +21,7 @@ pub(crate) mod envelope;
 #[cfg(feature = "image")]
 pub(crate) mod image_generation;
 pub(crate) mod model_listing;
+pub(crate) mod moderation;
 pub(crate) mod openai_chat_completions_compatible;
 pub(crate) mod rerank;
 pub(crate) mod schema;
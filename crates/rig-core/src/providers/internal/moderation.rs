@@ -0,0 +1,252 @@
+//! Shared request plumbing for OpenAI-shaped `/moderations` endpoints.
+//!
+//! OpenAI and Mistral accept the same body — `{model, input}` with `input` a
+//! list of strings — and answer with the same envelope: `{id, model,
+//! results}`, one result per input carrying a `categories` map of booleans
+//! and a `category_scores` map of probabilities. The differences are the
+//! category names, which stay the provider's own, and the overall `flagged`
+//! boolean, which Mistral omits and which is derived from the categories
+//! when absent.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::http_client::HttpClientExt;
+use crate::moderation::{
+    ModerationCategory, ModerationError, ModerationResponse, ModerationResult,
+    NormalizeModerationResponse,
+};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+/// Contract for provider extensions that speak the OpenAI-shaped moderation
+/// wire through [`GenericModerationModel`].
+#[doc(hidden)]
+pub trait OpenAiCompatibleModeration: crate::client::Provider {
+    /// Provider name stamped on every normalized response.
+    const PROVIDER_NAME: &'static str;
+
+    /// The provider's transport request-id response header, when it has one.
+    const REQUEST_ID_HEADER: Option<&'static str> = None;
+
+    /// The request path for moderation, resolved against the client base URL.
+    fn moderation_path(&self) -> String {
+        "/moderations".to_string()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ModerationRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+/// One input's verdict on the OpenAI-shaped wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationApiResult {
+    /// Absent on Mistral, which reports only the per-category booleans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flagged: Option<bool>,
+    /// Category booleans. OpenAI has sent `null` for a category its model
+    /// does not score, so a missing verdict reads as not flagged.
+    #[serde(default)]
+    pub categories: BTreeMap<String, Option<bool>>,
+    #[serde(default)]
+    pub category_scores: BTreeMap<String, Option<f64>>,
+    /// Provider-specific extras, such as OpenAI's
+    /// `category_applied_input_types`.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The OpenAI-shaped moderation wire response: what
+/// [`GenericModerationModel::raw_moderation`] returns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationApiResponse {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub results: Vec<ModerationApiResult>,
+}
+
+impl From<ModerationApiResult> for ModerationResult {
+    fn from(result: ModerationApiResult) -> Self {
+        let names: BTreeSet<_> = result
+            .categories
+            .keys()
+            .chain(result.category_scores.keys())
+            .cloned()
+            .collect();
+        let categories = names
+            .into_iter()
+            .map(|name| {
+                let category = ModerationCategory {
+                    flagged: result.categories.get(&name).copied().flatten() == Some(true),
+                    score: result
+                        .category_scores
+                        .get(&name)
+                        .copied()
+                        .flatten()
+                        .unwrap_or(0.0),
+                };
+                (name, category)
+            })
+            .collect();
+        let mut normalized = ModerationResult::from_categories(categories);
+        // The provider's overall verdict wins where it gave one: OpenAI can
+        // flag an input on a category it does not break out.
+        if let Some(flagged) = result.flagged {
+            normalized.flagged = flagged;
+        }
+        normalized
+    }
+}
+
+impl NormalizeModerationResponse for ModerationApiResponse {
+    fn normalize(self, provider: &str) -> Result<ModerationResponse, ModerationError> {
+        Ok(ModerationResponse::new(
+            self.results
+                .into_iter()
+                .map(ModerationResult::from)
+                .collect(),
+            provider,
+        )
+        .with_optional_model(self.model)
+        .with_optional_response_id(self.id))
+    }
+}
+
+/// A moderation model on an OpenAI-shaped `/moderations` endpoint.
+#[derive(Clone)]
+pub struct GenericModerationModel<Ext, H> {
+    client: Client<Ext, H>,
+    /// Identifier the request carries in its `model` field.
+    pub model: String,
+}
+
+impl<Ext, H> GenericModerationModel<Ext, H> {
+    /// Create a moderation model handle.
+    pub fn new(client: Client<Ext, H>, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+        }
+    }
+}
+
+impl<Ext, H> GenericModerationModel<Ext, H>
+where
+    Client<Ext, H>: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    Ext: OpenAiCompatibleModeration + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    H: WasmCompatSend + WasmCompatSync,
+{
+    /// Perform the request and return the provider's native response instead
+    /// of the normalized [`ModerationResponse`]. Same request, transport,
+    /// parser, and error path as
+    /// [`crate::moderation::ModerationModel::moderate`].
+    pub async fn raw_moderation(
+        &self,
+        inputs: Vec<String>,
+    ) -> Result<ModerationApiResponse, ModerationError> {
+        self.raw_moderation_with_request_id(inputs)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// [`Self::raw_moderation`] plus the transport request id from the
+    /// provider's request-id response header, when it carries one.
+    pub async fn raw_moderation_with_request_id(
+        &self,
+        inputs: Vec<String>,
+    ) -> Result<(ModerationApiResponse, Option<String>), ModerationError> {
+        if inputs.is_empty() {
+            return Err(ModerationError::RequestError(
+                "moderation needs at least one input".to_owned(),
+            ));
+        }
+
+        let body = serde_json::to_vec(&ModerationRequest {
+            model: &self.model,
+            input: &inputs,
+        })?;
+
+        let req = self
+            .client
+            .post(self.client.ext().moderation_path())?
+            .body(body)
+            .map_err(|error| ModerationError::HttpError(error.into()))?;
+
+        let response = self.client.send(req).await?;
+        let (parts, body) = response.into_parts();
+        let status = parts.status;
+        let provider_request_id =
+            super::transcription::request_id_from_headers(&parts.headers, Ext::REQUEST_ID_HEADER);
+        let response_body: Vec<u8> = body.await?;
+        if !status.is_success() {
+            return Err(ModerationError::from_http_response(
+                status,
+                String::from_utf8_lossy(&response_body).into_owned(),
+            )
+            .with_response_headers(Some(Box::new(parts.headers))));
+        }
+
+        let parsed: ModerationApiResponse =
+            serde_json::from_slice(&response_body).map_err(|error| {
+                ModerationError::ResponseError(format!(
+                    "{}: moderation response was not an OpenAI-shaped payload: {error}",
+                    Ext::PROVIDER_NAME
+                ))
+            })?;
+        // A short result list would silently pair verdicts with the wrong
+        // inputs — or leave the last inputs unchecked.
+        if parsed.results.len() != inputs.len() {
+            return Err(ModerationError::ResponseError(format!(
+                "{}: moderation returned {} results for {} inputs",
+                Ext::PROVIDER_NAME,
+                parsed.results.len(),
+                inputs.len()
+            )));
+        }
+
+        Ok((parsed, provider_request_id))
+    }
+}
+
+impl<Ext, H> crate::moderation::ModerationModel for GenericModerationModel<Ext, H>
+where
+    Client<Ext, H>: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    Ext: OpenAiCompatibleModeration + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    H: WasmCompatSend + WasmCompatSync,
+{
+    async fn moderate(&self, inputs: Vec<String>) -> Result<ModerationResponse, ModerationError> {
+        crate::telemetry::instrument_modality(
+            Ext::PROVIDER_NAME,
+            &self.model,
+            crate::telemetry::ModalityOperation::Moderation,
+            async {
+                let (response, provider_request_id) =
+                    self.raw_moderation_with_request_id(inputs).await?;
+                let captured = serde_json::to_value(&response)?;
+                Ok(response
+                    .normalize(Ext::PROVIDER_NAME)?
+                    .with_optional_provider_request_id(provider_request_id)
+                    .with_raw(captured))
+            },
+        )
+        .await
+    }
+}
+
+impl<Ext, H> crate::client::ConstructModerationModel<Client<Ext, H>>
+    for GenericModerationModel<Ext, H>
+where
+    Client<Ext, H>: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    Ext: OpenAiCompatibleModeration + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    H: WasmCompatSend + WasmCompatSync,
+{
+    fn construct(client: &Client<Ext, H>, model: String) -> Self {
+        Self::new(client.clone(), model)
+    }
+}
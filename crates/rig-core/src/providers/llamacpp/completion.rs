@@ -5,7 +5,11 @@
 //! dialect is declared by the `OpenAICompatibleProvider` impl on
 //! [`LlamacppExt`](super::client::LlamacppExt) in `client.rs`.
 
+use crate::completion::{self, CompletionError};
+use crate::http_client::HttpClientExt;
 use crate::providers::openai;
+use crate::providers::openai::completion::{CompletionModelOptions, OpenAICompatibleProvider};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
 use serde::{Deserialize, Serialize};
 
 // ================================================================
@@ -142,6 +146,70 @@ impl crate::telemetry::ProviderResponseExt for CompletionResponse {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct ApplyTemplateResponse {
+    prompt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenizeResponse {
+    tokens: Vec<serde_json::Value>,
+}
+
+impl<H> completion::TokenCountingModel for CompletionModel<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    /// Renders the request through the loaded model's chat template with
+    /// `/apply-template`, then tokenizes the rendered prompt with `/tokenize`.
+    ///
+    /// Two round trips, both local and neither touching the KV cache. The body
+    /// sent to `/apply-template` is the one a completion would send, so tool
+    /// definitions are rendered into the prompt when the server runs with
+    /// `--jinja`; without it `llama-server` ignores them, and so does the count.
+    async fn count_tokens(
+        &self,
+        completion_request: completion::CompletionRequest,
+    ) -> Result<u64, CompletionError> {
+        let options = CompletionModelOptions {
+            strict_tools: self.strict_tools,
+            tool_result_array_content: self.tool_result_array_content,
+            prompt_caching: self.prompt_caching,
+        };
+        let ext = self.client.ext();
+        let mut request =
+            ext.build_completion_request(self.model.clone(), completion_request, options)?;
+        ext.prepare_request(&mut request)?;
+        let mut body = openai::completion::request_body(&request, false)?;
+        ext.finalize_request_body_with_options(&mut body, options)?;
+
+        let request = self
+            .client
+            .post("/apply-template")?
+            .body(serde_json::to_vec(&body)?)
+            .map_err(|e| CompletionError::HttpError(e.into()))?;
+        let ApplyTemplateResponse { prompt } =
+            completion::token_count::send_count_request(&self.client, request).await?;
+
+        // `add_special` adds the BOS token a generation would start with;
+        // `parse_special` reads the template's control tokens as single tokens
+        // rather than as their spelled-out text.
+        let body = serde_json::to_vec(&serde_json::json!({
+            "content": prompt,
+            "add_special": true,
+            "parse_special": true,
+        }))?;
+        let request = self
+            .client
+            .post("/tokenize")?
+            .body(body)
+            .map_err(|e| CompletionError::HttpError(e.into()))?;
+        let TokenizeResponse { tokens } =
+            completion::token_count::send_count_request(&self.client, request).await?;
+        Ok(tokens.len() as u64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,4 +296,42 @@ mod tests {
         let again: CompletionResponse = serde_json::from_value(value).expect("should round-trip");
         assert_eq!(again.timings, response.timings);
     }
+
+    #[tokio::test]
+    async fn count_tokens_renders_the_template_then_tokenizes_it() {
+        use crate::client::CompletionClient as _;
+        use crate::completion::{CompletionModel as _, TokenCountingModel as _};
+        use crate::providers::llamacpp::Client;
+        use crate::test_utils::{MockHttpResponse, SequencedHttpClient};
+
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(r#"{"prompt": "<|im_start|>user\nhello<|im_end|>\n"}"#),
+            MockHttpResponse::success(r#"{"tokens": [151644, 872, 198, 14990, 151645, 198]}"#),
+        ]);
+        let client =
+            Client::from_url_with("http://localhost:8080/v1", http.clone()).expect("client");
+        let model = client.completion_model(LLAMA_CPP);
+        let request = model.completion_request("hello").max_tokens(64).build();
+
+        assert_eq!(model.count_tokens(request).await.expect("count"), 6);
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].uri, "http://localhost:8080/apply-template");
+        let rendered: serde_json::Value =
+            serde_json::from_slice(&requests[0].body).expect("json body");
+        assert_eq!(rendered["messages"][0]["content"], "hello");
+
+        assert_eq!(requests[1].uri, "http://localhost:8080/tokenize");
+        let tokenize: serde_json::Value =
+            serde_json::from_slice(&requests[1].body).expect("json body");
+        assert_eq!(
+            tokenize,
+            serde_json::json!({
+                "content": "<|im_start|>user\nhello<|im_end|>\n",
+                "add_special": true,
+                "parse_special": true,
+            })
+        );
+    }
 }
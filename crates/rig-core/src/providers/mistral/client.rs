@@ -151,6 +151,7 @@ client::impl_capabilities!(
     embeddings = super::EmbeddingModel<H>,
     transcription = super::TranscriptionModel<H>,
     model_listing = MistralModelLister<H>,
+    moderation = super::moderation::ModerationModel<H>,
 );
 
 impl DebugExt for MistralExt {}
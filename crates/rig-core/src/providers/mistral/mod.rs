@@ -2,6 +2,7 @@ pub mod client;
 pub mod completion;
 pub mod embedding;
 pub mod model_listing;
+pub mod moderation;
 pub mod transcription;
 
 pub use client::*;
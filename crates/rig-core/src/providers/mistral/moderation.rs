@@ -0,0 +1,68 @@
+//! Mistral moderation (`POST /v1/moderations`).
+//!
+//! `mistral-moderation-latest` scores text against nine categories
+//! (`sexual`, `hate_and_discrimination`, `violence_and_threats`,
+//! `dangerous_and_criminal_content`, `selfharm`, `health`, `financial`,
+//! `law`, `pii`). Mistral reports no overall `flagged` boolean; an input is
+//! flagged when any of its categories is.
+
+use crate::providers::internal::moderation::{GenericModerationModel, OpenAiCompatibleModeration};
+
+use super::client::MistralExt;
+
+/// `mistral-moderation-latest` moderation model
+pub const MISTRAL_MODERATION_LATEST: &str = "mistral-moderation-latest";
+
+impl OpenAiCompatibleModeration for MistralExt {
+    const PROVIDER_NAME: &'static str = "mistral";
+    const REQUEST_ID_HEADER: Option<&'static str> = Some("mistral-correlation-id");
+
+    fn moderation_path(&self) -> String {
+        "/v1/moderations".to_string()
+    }
+}
+
+/// Mistral moderation model.
+pub type ModerationModel<H> = GenericModerationModel<MistralExt, H>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ModerationClient;
+    use crate::moderation::ModerationModel as _;
+    use crate::test_utils::RecordingHttpClient;
+
+    #[tokio::test]
+    async fn flagged_is_derived_from_the_categories() {
+        let http = RecordingHttpClient::new(
+            r#"{
+                "id": "4d2a7c3e9f0b4c1d8e6f5a2b3c4d5e6f",
+                "model": "mistral-moderation-latest",
+                "results": [{
+                    "categories": {"sexual": false, "violence_and_threats": true, "pii": false},
+                    "category_scores": {"sexual": 0.0001, "violence_and_threats": 0.93, "pii": 0.002}
+                }]
+            }"#,
+        );
+        let client = crate::providers::mistral::Client::builder()
+            .api_key("test-key")
+            .http_client(http.clone())
+            .build()
+            .expect("build client");
+
+        let response = client
+            .moderation_model(MISTRAL_MODERATION_LATEST)
+            .moderate(vec!["I will hurt you".to_owned()])
+            .await
+            .expect("moderate");
+
+        assert_eq!(response.provider, "mistral");
+        let result = &response.results[0];
+        assert!(result.flagged);
+        assert_eq!(
+            result.flagged_categories().collect::<Vec<_>>(),
+            ["violence_and_threats"]
+        );
+        assert!(http.requests()[0].uri.ends_with("/v1/moderations"));
+    }
+}
@@ -0,0 +1,497 @@
+//! OpenAI Batch API — [`BatchCompletionClient`] for [`super::Client`].
+//!
+//! The batch runs against the Responses endpoint, the same one
+//! [`super::Client`]'s completion models use. Submission is two calls: the
+//! requests are written as JSONL (one `{"custom_id", "method", "url", "body"}`
+//! line each) and uploaded to `/files` with `purpose=batch`, then
+//! `/batches` is pointed at the uploaded file. Results are read back from the
+//! batch's output and error files.
+
+use serde::Deserialize;
+
+use super::client::Client;
+use super::responses_api::{CompletionResponse, ResponsesCompletionModel};
+use crate::client::batch::{
+    Batch, BatchCompletionClient, BatchError, BatchOutcome, BatchRequest, BatchRequestCounts,
+    BatchResult, BatchStatus, parse_jsonl, validate_batch_requests, validate_resource_id,
+};
+use crate::completion::NormalizeCompletionResponse;
+use crate::http_client::{
+    HttpClientExt, MultipartForm, multipart::Part, send_for_bytes, send_multipart_for_bytes,
+};
+use crate::providers::openai::responses_api::ResponsesProviderExt;
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+/// The endpoint every line of the input file targets. Relative to the API
+/// host, not to the client's `/v1` base URL.
+const BATCH_ENDPOINT: &str = "/v1/responses";
+
+/// The only window OpenAI accepts.
+const COMPLETION_WINDOW: &str = "24h";
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatch {
+    id: String,
+    status: String,
+    #[serde(default)]
+    request_counts: OpenAIBatchRequestCounts,
+    #[serde(default)]
+    created_at: Option<u64>,
+    #[serde(default)]
+    output_file_id: Option<String>,
+    #[serde(default)]
+    error_file_id: Option<String>,
+    #[serde(default)]
+    errors: Option<OpenAIBatchErrors>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIBatchRequestCounts {
+    #[serde(default)]
+    total: u64,
+    #[serde(default)]
+    completed: u64,
+    #[serde(default)]
+    failed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchErrors {
+    #[serde(default)]
+    data: Vec<OpenAIBatchErrorEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchErrorEntry {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    line: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadedFile {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputLine {
+    custom_id: String,
+    #[serde(default)]
+    response: Option<OutputResponse>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputResponse {
+    status_code: u16,
+    #[serde(default)]
+    request_id: Option<String>,
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+impl OpenAIBatch {
+    fn status(&self) -> Result<BatchStatus, BatchError> {
+        Ok(match self.status.as_str() {
+            "validating" => BatchStatus::Validating,
+            "in_progress" => BatchStatus::InProgress,
+            "finalizing" => BatchStatus::Finalizing,
+            "cancelling" => BatchStatus::Cancelling,
+            "completed" => BatchStatus::Completed,
+            "cancelled" => BatchStatus::Cancelled,
+            "expired" => BatchStatus::Expired,
+            "failed" => BatchStatus::Failed,
+            other => {
+                return Err(BatchError::ProviderError(format!(
+                    "unknown OpenAI batch status `{other}`"
+                )));
+            }
+        })
+    }
+
+    fn to_batch(&self, raw: serde_json::Value) -> Result<Batch, BatchError> {
+        let counts = &self.request_counts;
+        let errors = self
+            .errors
+            .iter()
+            .flat_map(|errors| &errors.data)
+            .map(|entry| {
+                let mut text = entry
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| "batch error".to_owned());
+                if let Some(code) = &entry.code {
+                    text = format!("{code}: {text}");
+                }
+                if let Some(line) = entry.line {
+                    text = format!("line {line}: {text}");
+                }
+                text
+            })
+            .collect();
+        Ok(Batch {
+            id: self.id.clone(),
+            status: self.status()?,
+            request_counts: BatchRequestCounts {
+                processing: counts
+                    .total
+                    .saturating_sub(counts.completed)
+                    .saturating_sub(counts.failed),
+                succeeded: counts.completed,
+                errored: counts.failed,
+                cancelled: 0,
+                expired: 0,
+            },
+            created_at: self.created_at.map(|at| at.to_string()),
+            errors,
+            raw,
+        })
+    }
+}
+
+fn decode_batch(body: &[u8]) -> Result<(OpenAIBatch, Batch), BatchError> {
+    let raw: serde_json::Value = serde_json::from_slice(body)?;
+    let openai: OpenAIBatch = serde_json::from_value(raw.clone())?;
+    let batch = openai.to_batch(raw)?;
+    Ok((openai, batch))
+}
+
+/// The message of an OpenAI error object, wherever it nests it.
+fn error_message(error: &serde_json::Value) -> Option<&str> {
+    error
+        .pointer("/error/message")
+        .or_else(|| error.get("message"))
+        .and_then(serde_json::Value::as_str)
+}
+
+/// Turn one line of an output or error file into a [`BatchResult`].
+fn decode_result(line: OutputLine) -> BatchResult {
+    let outcome = match (line.response, line.error) {
+        (Some(response), None) if (200..300).contains(&response.status_code) => {
+            let raw = response.body;
+            // A result rig cannot normalize is reported against its own
+            // request rather than failing the retrieval of every other one.
+            match normalize_response(raw.clone(), response.request_id) {
+                Ok(normalized) => BatchOutcome::Succeeded(Box::new(normalized.with_raw(raw))),
+                Err(message) => BatchOutcome::Errored { message, raw },
+            }
+        }
+        (_, Some(error)) => {
+            // Requests the batch never ran are reported through the error
+            // file with these codes rather than with a status of their own.
+            match error.get("code").and_then(serde_json::Value::as_str) {
+                Some("batch_cancelled") => BatchOutcome::Cancelled,
+                Some("batch_expired") => BatchOutcome::Expired,
+                _ => BatchOutcome::Errored {
+                    message: error_message(&error)
+                        .unwrap_or("request errored")
+                        .to_owned(),
+                    raw: error,
+                },
+            }
+        }
+        (Some(response), None) => BatchOutcome::Errored {
+            message: error_message(&response.body)
+                .map(str::to_owned)
+                .unwrap_or_else(|| format!("request failed with status {}", response.status_code)),
+            raw: response.body,
+        },
+        (None, None) => BatchOutcome::Errored {
+            message: "batch result carries neither a response nor an error".to_owned(),
+            raw: serde_json::Value::Null,
+        },
+    };
+    BatchResult {
+        custom_id: line.custom_id,
+        outcome,
+    }
+}
+
+fn normalize_response(
+    raw: serde_json::Value,
+    request_id: Option<String>,
+) -> Result<crate::completion::CompletionResponse, String> {
+    let mut response: CompletionResponse = serde_json::from_value(raw)
+        .map_err(|error| format!("could not decode the batch response body: {error}"))?;
+    response.provider_request_id = request_id;
+    response
+        .normalize(<super::OpenAIResponsesExt as ResponsesProviderExt>::PROVIDER_NAME)
+        .map_err(|error| format!("could not normalize the batch response body: {error}"))
+}
+
+impl<H> Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn fetch_batch(&self, id: &str) -> Result<(OpenAIBatch, Batch), BatchError> {
+        let id = validate_resource_id("batch", id)?;
+        let request = self.get(format!("/batches/{id}"))?.body(Vec::new())?;
+        decode_batch(&send_for_bytes(self, request).await?)
+    }
+
+    async fn file_content(&self, file_id: &str) -> Result<Vec<u8>, BatchError> {
+        let file_id = validate_resource_id("file", file_id)?;
+        let request = self
+            .get(format!("/files/{file_id}/content"))?
+            .body(Vec::new())?;
+        Ok(send_for_bytes(self, request).await?)
+    }
+}
+
+impl<H> BatchCompletionClient for Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn submit_batch(
+        &self,
+        model: &str,
+        requests: Vec<BatchRequest>,
+    ) -> Result<Batch, BatchError> {
+        validate_batch_requests(&requests)?;
+        let completion_model = ResponsesCompletionModel::new(self.clone(), model);
+
+        let mut jsonl = Vec::new();
+        for BatchRequest { custom_id, request } in requests {
+            let body = completion_model
+                .create_completion_request(request)
+                .map_err(|source| BatchError::InvalidRequest {
+                    custom_id: custom_id.clone(),
+                    source,
+                })?;
+            serde_json::to_writer(
+                &mut jsonl,
+                &serde_json::json!({
+                    "custom_id": custom_id,
+                    "method": "POST",
+                    "url": BATCH_ENDPOINT,
+                    "body": body,
+                }),
+            )?;
+            jsonl.push(b'\n');
+        }
+
+        let form = MultipartForm::new()
+            .text("purpose", "batch")
+            .part(Part::bytes("file", jsonl).filename("batch.jsonl"));
+        let upload = self.post("/files")?.body(form)?;
+        let file: UploadedFile =
+            serde_json::from_slice(&send_multipart_for_bytes(self, upload).await?)?;
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "input_file_id": file.id,
+            "endpoint": BATCH_ENDPOINT,
+            "completion_window": COMPLETION_WINDOW,
+        }))?;
+        let request = self.post("/batches")?.body(body)?;
+        decode_batch(&send_for_bytes(self, request).await?).map(|(_, batch)| batch)
+    }
+
+    async fn batch(&self, id: &str) -> Result<Batch, BatchError> {
+        self.fetch_batch(id).await.map(|(_, batch)| batch)
+    }
+
+    async fn cancel_batch(&self, id: &str) -> Result<Batch, BatchError> {
+        let id = validate_resource_id("batch", id)?;
+        let request = self
+            .post(format!("/batches/{id}/cancel"))?
+            .body(Vec::new())?;
+        decode_batch(&send_for_bytes(self, request).await?).map(|(_, batch)| batch)
+    }
+
+    async fn batch_results(&self, id: &str) -> Result<Vec<BatchResult>, BatchError> {
+        let (openai, batch) = self.fetch_batch(id).await?;
+        if !batch.status.is_terminal() {
+            return Err(BatchError::NotReady {
+                id: batch.id,
+                status: batch.status,
+            });
+        }
+
+        // Successes land in the output file and failures in the error file;
+        // either is absent when it would be empty.
+        let mut results = Vec::with_capacity(batch.request_counts.total() as usize);
+        for file_id in [&openai.output_file_id, &openai.error_file_id]
+            .into_iter()
+            .flatten()
+        {
+            let body = self.file_content(file_id).await?;
+            results.extend(
+                parse_jsonl::<OutputLine>(&body)?
+                    .into_iter()
+                    .map(decode_result),
+            );
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{AssistantContent, CompletionRequestBuilder};
+    use crate::test_utils::{MockCompletionModel, MockHttpResponse, SequencedHttpClient};
+
+    fn client(http_client: SequencedHttpClient) -> Client<SequencedHttpClient> {
+        Client::builder()
+            .api_key("test-key")
+            .http_client(http_client)
+            .build()
+            .expect("build client")
+    }
+
+    fn batch_json(status: &str) -> String {
+        serde_json::json!({
+            "id": "batch_1",
+            "object": "batch",
+            "endpoint": "/v1/responses",
+            "input_file_id": "file-in",
+            "completion_window": "24h",
+            "status": status,
+            "output_file_id": "file-out",
+            "error_file_id": "file-err",
+            "created_at": 1_760_000_000,
+            "errors": null,
+            "request_counts": {"total": 3, "completed": 1, "failed": 1},
+        })
+        .to_string()
+    }
+
+    fn request(prompt: &str) -> crate::completion::CompletionRequest {
+        CompletionRequestBuilder::new(MockCompletionModel::default(), prompt).build()
+    }
+
+    #[tokio::test]
+    async fn submit_uploads_jsonl_then_creates_the_batch() {
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(r#"{"id": "file-in", "object": "file"}"#),
+            MockHttpResponse::success(batch_json("validating")),
+        ]);
+        let batch = client(http.clone())
+            .submit_batch(
+                "gpt-5-mini",
+                vec![
+                    BatchRequest::new("a", request("first")),
+                    BatchRequest::new("b", request("second")),
+                ],
+            )
+            .await
+            .expect("submit");
+
+        assert_eq!(batch.id, "batch_1");
+        assert_eq!(batch.status, BatchStatus::Validating);
+        assert_eq!(batch.request_counts.processing, 1);
+        assert_eq!(batch.created_at.as_deref(), Some("1760000000"));
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].uri.ends_with("/v1/files"));
+        let upload = String::from_utf8_lossy(&requests[0].body);
+        assert!(upload.contains("name=\"purpose\""), "{upload}");
+        let lines: Vec<serde_json::Value> = upload
+            .lines()
+            .filter(|line| line.starts_with('{'))
+            .map(|line| serde_json::from_str(line).expect("jsonl line"))
+            .collect();
+        assert_eq!(lines.len(), 2, "{upload}");
+        assert_eq!(lines[0]["custom_id"], "a");
+        assert_eq!(lines[0]["url"], "/v1/responses");
+        assert_eq!(lines[0]["body"]["model"], "gpt-5-mini");
+        assert_eq!(lines[1]["custom_id"], "b");
+
+        assert!(requests[1].uri.ends_with("/v1/batches"));
+        let create: serde_json::Value =
+            serde_json::from_slice(&requests[1].body).expect("json body");
+        assert_eq!(create["input_file_id"], "file-in");
+        assert_eq!(create["endpoint"], "/v1/responses");
+        assert_eq!(create["completion_window"], "24h");
+    }
+
+    #[tokio::test]
+    async fn results_merge_output_and_error_files() {
+        let output = serde_json::json!({
+            "id": "batch_req_1", "custom_id": "a", "error": null,
+            "response": {"status_code": 200, "request_id": "req_1", "body": {
+                "id": "resp_1", "object": "response", "created_at": 1_760_000_000,
+                "status": "completed", "model": "gpt-5-mini",
+                "output": [{"type": "message", "id": "msg_1", "role": "assistant",
+                    "status": "completed",
+                    "content": [{"type": "output_text", "text": "Paris", "annotations": []}]}],
+                "usage": {"input_tokens": 10, "output_tokens": 2, "total_tokens": 12,
+                    "input_tokens_details": {"cached_tokens": 0},
+                    "output_tokens_details": {"reasoning_tokens": 0}}
+            }}
+        });
+        let errors = [
+            serde_json::json!({"id": "batch_req_2", "custom_id": "b", "error": null,
+                "response": {"status_code": 400, "request_id": "req_2",
+                    "body": {"error": {"message": "bad input", "type": "invalid_request_error"}}}}),
+            serde_json::json!({"id": "batch_req_3", "custom_id": "c", "response": null,
+                "error": {"code": "batch_expired", "message": "expired"}}),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(batch_json("completed")),
+            MockHttpResponse::success(format!("{output}\n")),
+            MockHttpResponse::success(errors),
+        ]);
+
+        let results = client(http.clone())
+            .batch_results("batch_1")
+            .await
+            .expect("results");
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].custom_id, "a");
+        let response = results[0].outcome.response().expect("first succeeded");
+        assert_eq!(response.choice, vec![AssistantContent::text("Paris")]);
+        assert_eq!(response.usage.output_tokens, 2);
+        assert_eq!(response.provider_request_id.as_deref(), Some("req_1"));
+        assert!(matches!(
+            &results[1].outcome,
+            BatchOutcome::Errored { message, .. } if message == "bad input"
+        ));
+        assert!(matches!(results[2].outcome, BatchOutcome::Expired));
+
+        let requests = http.requests();
+        assert!(requests[1].uri.ends_with("/v1/files/file-out/content"));
+        assert!(requests[2].uri.ends_with("/v1/files/file-err/content"));
+    }
+
+    #[tokio::test]
+    async fn failed_batches_surface_their_validation_errors() {
+        let body = serde_json::json!({
+            "id": "batch_1", "object": "batch", "status": "failed",
+            "errors": {"object": "list", "data": [
+                {"code": "invalid_json_line", "message": "not json", "line": 2}
+            ]},
+            "request_counts": {"total": 0, "completed": 0, "failed": 0},
+        })
+        .to_string();
+        let http = SequencedHttpClient::new([MockHttpResponse::success(body)]);
+        let batch = client(http).batch("batch_1").await.expect("batch");
+
+        assert_eq!(batch.status, BatchStatus::Failed);
+        assert_eq!(batch.errors, vec!["line 2: invalid_json_line: not json"]);
+    }
+
+    #[tokio::test]
+    async fn provider_errors_keep_status_and_body() {
+        let http = SequencedHttpClient::new([MockHttpResponse::error(
+            http::StatusCode::NOT_FOUND,
+            r#"{"error": {"message": "No batch found"}}"#,
+        )]);
+        let error = client(http).cancel_batch("batch_1").await.expect_err("404");
+
+        assert_eq!(
+            error.provider_response_status(),
+            Some(http::StatusCode::NOT_FOUND)
+        );
+    }
+}
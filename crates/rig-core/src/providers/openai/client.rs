@@ -77,6 +77,7 @@ client::impl_capabilities!(
     model_listing = super::OpenAIModelLister<H>,
     image_generation = super::ImageGenerationModel<H>,
     audio_generation = super::audio_generation::AudioGenerationModel<H>,
+    moderation = super::moderation::ModerationModel<H>,
 );
 
 client::impl_capabilities!(
@@ -87,6 +88,7 @@ client::impl_capabilities!(
     model_listing = super::OpenAICompletionsModelLister<H>,
     image_generation = super::CompletionsImageGenerationModel<H>,
     audio_generation = super::audio_generation::CompletionsAudioGenerationModel<H>,
+    moderation = super::moderation::CompletionsModerationModel<H>,
 );
 
 impl DebugExt for OpenAIResponsesExt {}
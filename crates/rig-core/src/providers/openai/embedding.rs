@@ -609,6 +609,36 @@ mod tests {
         assert_eq!(body["user"], serde_json::json!("user-123"));
     }
 
+    #[tokio::test]
+    async fn openai_embeddings_retry_rate_limits_when_the_client_retries() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::RETRY_AFTER, "0".parse().expect("header"));
+        let http_client = crate::test_utils::SequencedHttpClient::new([
+            crate::test_utils::MockHttpResponse::error_with_headers(
+                http::StatusCode::TOO_MANY_REQUESTS,
+                "rate limited",
+                headers,
+            ),
+            crate::test_utils::MockHttpResponse::success(RESPONSE_BODY),
+        ]);
+        let client = CompletionsClient::builder()
+            .api_key("test-key")
+            .http_client(http_client.clone())
+            .retry(crate::http_client::retry::RequestRetry::default())
+            .build()
+            .expect("build client");
+
+        let response = client
+            .embedding_model(TEXT_EMBEDDING_3_SMALL)
+            .embed_texts_response(["hello".to_string()])
+            .await
+            .expect("the retried request should succeed");
+
+        assert_eq!(response.usage.input_tokens, 4);
+        assert_eq!(http_client.requests().len(), 2);
+        assert_eq!(http_client.remaining_responses(), 0);
+    }
+
     /// The normalized response carries the provider name, the wire's model,
     /// the `x-request-id` transport id, and the raw payload — which
     /// round-trips back to the provider type.
@@ -0,0 +1,257 @@
+//! OpenAI Files API — [`FilesClient`] for [`super::Client`].
+//!
+//! Uploads use `purpose=user_data`, the purpose OpenAI documents for files
+//! passed to a model as input. A [`DocumentSourceKind::FileId`] is sent as an
+//! `input_file` (Responses) or `file` (Chat Completions) part.
+//!
+//! [`DocumentSourceKind::FileId`]: crate::message::DocumentSourceKind::FileId
+
+use serde::Deserialize;
+
+use super::client::Client;
+use crate::client::files::{
+    FileUpload, FilesClient, FilesError, MAX_LISTING_PAGES, ProviderFile, validate_file_id,
+};
+use crate::http_client::{
+    HttpClientExt, MultipartForm, multipart::Part, send_for_bytes, send_multipart_for_bytes,
+};
+use crate::wasm_compat::{WasmCompatSend, WasmCompatSync};
+
+const FILES_PATH: &str = "/files";
+
+/// The purpose OpenAI uses for files meant as model inputs.
+const USER_DATA_PURPOSE: &str = "user_data";
+
+/// The largest page `GET /files` serves.
+const PAGE_LIMIT: &str = "10000";
+
+#[derive(Debug, Deserialize)]
+struct OpenAIFile {
+    id: String,
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    bytes: Option<u64>,
+    #[serde(default)]
+    created_at: Option<u64>,
+    #[serde(default)]
+    expires_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIFileList {
+    data: Vec<serde_json::Value>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+fn decode_file(raw: serde_json::Value) -> Result<ProviderFile, FilesError> {
+    let file: OpenAIFile = serde_json::from_value(raw.clone())?;
+    Ok(ProviderFile {
+        id: file.id,
+        filename: file.filename,
+        mime_type: None,
+        size_bytes: file.bytes,
+        created_at: file.created_at.map(|at| at.to_string()),
+        expires_at: file.expires_at.map(|at| at.to_string()),
+        raw,
+    })
+}
+
+impl<H> FilesClient for Client<H>
+where
+    H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+{
+    async fn upload_file(&self, upload: FileUpload) -> Result<ProviderFile, FilesError> {
+        let mime_type = upload.validate()?;
+        let FileUpload { filename, data, .. } = upload;
+        let form = MultipartForm::new()
+            .text("purpose", USER_DATA_PURPOSE)
+            .part(
+                Part::bytes("file", data)
+                    .filename(filename)
+                    .content_type(mime_type),
+            );
+        let request = self.post(FILES_PATH)?.body(form)?;
+        decode_file(serde_json::from_slice(
+            &send_multipart_for_bytes(self, request).await?,
+        )?)
+    }
+
+    async fn list_files(&self) -> Result<Vec<ProviderFile>, FilesError> {
+        let mut files = Vec::new();
+        let mut after: Option<String> = None;
+        for _ in 0..MAX_LISTING_PAGES {
+            let mut pairs = vec![("limit", PAGE_LIMIT)];
+            if let Some(after) = &after {
+                pairs.push(("after", after.as_str()));
+            }
+            let path =
+                crate::providers::internal::model_listing::with_query_pairs(FILES_PATH, &pairs);
+            let request = self.get(path)?.body(Vec::new())?;
+            let page: OpenAIFileList =
+                serde_json::from_slice(&send_for_bytes(self, request).await?)?;
+            let has_more = page.has_more;
+            for raw in page.data {
+                files.push(decode_file(raw)?);
+            }
+            let last = files.last().map(|file| file.id.clone());
+            // A cursor that does not advance would fetch the same page forever.
+            if !has_more || last.is_none() || last == after {
+                return Ok(files);
+            }
+            after = last;
+        }
+        tracing::warn!(
+            provider = "OpenAI",
+            files = files.len(),
+            pages = MAX_LISTING_PAGES,
+            "file listing hit its page ceiling; returning the pages fetched so far"
+        );
+        Ok(files)
+    }
+
+    async fn file(&self, id: &str) -> Result<ProviderFile, FilesError> {
+        let id = validate_file_id(id)?;
+        let request = self.get(format!("{FILES_PATH}/{id}"))?.body(Vec::new())?;
+        decode_file(serde_json::from_slice(
+            &send_for_bytes(self, request).await?,
+        )?)
+    }
+
+    async fn delete_file(&self, id: &str) -> Result<(), FilesError> {
+        let id = validate_file_id(id)?;
+        let request = self
+            .delete(format!("{FILES_PATH}/{id}"))?
+            .body(Vec::new())?;
+        let body: serde_json::Value =
+            serde_json::from_slice(&send_for_bytes(self, request).await?)?;
+        if body.get("deleted").and_then(serde_json::Value::as_bool) == Some(false) {
+            return Err(FilesError::ProviderError(format!(
+                "OpenAI did not delete file `{id}`"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockHttpResponse, RecordingHttpClient, SequencedHttpClient};
+
+    fn client<H>(http: H) -> Client<H>
+    where
+        H: HttpClientExt + Clone + WasmCompatSend + WasmCompatSync + 'static,
+    {
+        Client::builder()
+            .api_key("test-key")
+            .http_client(http)
+            .build()
+            .expect("build client")
+    }
+
+    const FILE: &str = r#"{
+        "id": "file-abc123",
+        "object": "file",
+        "bytes": 4,
+        "created_at": 1700000000,
+        "expires_at": null,
+        "filename": "report.pdf",
+        "purpose": "user_data",
+        "status": "processed"
+    }"#;
+
+    #[tokio::test]
+    async fn upload_sends_a_user_data_multipart_form() {
+        let http = SequencedHttpClient::new([MockHttpResponse::success(FILE)]);
+        let client = client(http.clone());
+
+        let file = client
+            .upload_file(FileUpload::new(
+                "report.pdf",
+                "application/pdf",
+                b"%PDF".to_vec(),
+            ))
+            .await
+            .expect("upload");
+        assert_eq!(file.id, "file-abc123");
+        assert_eq!(file.size_bytes, Some(4));
+        assert_eq!(file.created_at.as_deref(), Some("1700000000"));
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.ends_with("/v1/files"));
+        let body = String::from_utf8_lossy(&requests[0].body);
+        assert!(body.contains("name=\"purpose\"\r\n\r\nuser_data"), "{body}");
+        assert!(body.contains("filename=\"report.pdf\""), "{body}");
+        assert!(body.contains("Content-Type: application/pdf"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn list_follows_the_after_cursor() {
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(
+                r#"{"object": "list", "data": [{"id": "file-1"}, {"id": "file-2"}], "has_more": true}"#,
+            ),
+            MockHttpResponse::success(
+                r#"{"object": "list", "data": [{"id": "file-3"}], "has_more": false}"#,
+            ),
+        ]);
+        let client = client(http.clone());
+
+        let ids: Vec<_> = client
+            .list_files()
+            .await
+            .expect("list")
+            .into_iter()
+            .map(|file| file.id)
+            .collect();
+        assert_eq!(ids, ["file-1", "file-2", "file-3"]);
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].uri.contains("after="));
+        assert!(
+            requests[1].uri.contains("after=file-2"),
+            "{}",
+            requests[1].uri
+        );
+    }
+
+    #[tokio::test]
+    async fn get_and_delete_address_the_file() {
+        let http = SequencedHttpClient::new([
+            MockHttpResponse::success(FILE),
+            MockHttpResponse::success(
+                r#"{"id": "file-abc123", "object": "file", "deleted": true}"#,
+            ),
+        ]);
+        let client = client(http.clone());
+
+        client.file("file-abc123").await.expect("get");
+        client.delete_file("file-abc123").await.expect("delete");
+
+        let requests = http.requests();
+        assert!(requests[0].uri.ends_with("/v1/files/file-abc123"));
+        assert!(requests[1].uri.ends_with("/v1/files/file-abc123"));
+        assert!(matches!(
+            client.delete_file("file-abc123/../x").await,
+            Err(FilesError::InvalidFile(_))
+        ));
+        assert_eq!(http.requests().len(), 2, "an invalid id is never sent");
+    }
+
+    #[tokio::test]
+    async fn provider_errors_keep_their_status() {
+        let client = client(RecordingHttpClient::with_error_response(
+            http::StatusCode::NOT_FOUND,
+            r#"{"error": {"message": "No such File object: file-x"}}"#,
+        ));
+        let error = client.file("file-x").await.expect_err("404");
+        assert_eq!(
+            error.provider_response_status(),
+            Some(http::StatusCode::NOT_FOUND)
+        );
+    }
+}
@@ -11,10 +11,13 @@
 //! # Ok(())
 //! # }
 //! ```
+pub mod batch;
 pub mod client;
 pub mod completion;
 pub mod embedding;
+pub mod files;
 pub mod model_listing;
+pub mod moderation;
 pub mod responses_api;
 
 #[cfg(feature = "audio")]
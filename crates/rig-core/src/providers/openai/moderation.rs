@@ -0,0 +1,127 @@
+//! OpenAI moderation (`POST /v1/moderations`).
+//!
+//! The `omni-moderation` models score text against thirteen categories
+//! (`harassment`, `harassment/threatening`, `hate`, `hate/threatening`,
+//! `illicit`, `illicit/violent`, `self-harm`, `self-harm/intent`,
+//! `self-harm/instructions`, `sexual`, `sexual/minors`, `violence`,
+//! `violence/graphic`) and are free to call. The endpoint is the same for the
+//! Responses and Chat Completions clients.
+
+use crate::providers::internal::moderation::{GenericModerationModel, OpenAiCompatibleModeration};
+
+use super::client::{OpenAICompletionsExt, OpenAIResponsesExt};
+
+/// `omni-moderation-latest` moderation model
+pub const OMNI_MODERATION_LATEST: &str = "omni-moderation-latest";
+/// `text-moderation-latest` moderation model (legacy, text-only)
+pub const TEXT_MODERATION_LATEST: &str = "text-moderation-latest";
+
+impl OpenAiCompatibleModeration for OpenAIResponsesExt {
+    const PROVIDER_NAME: &'static str = "openai";
+    const REQUEST_ID_HEADER: Option<&'static str> = Some("x-request-id");
+}
+
+impl OpenAiCompatibleModeration for OpenAICompletionsExt {
+    const PROVIDER_NAME: &'static str = "openai";
+    const REQUEST_ID_HEADER: Option<&'static str> = Some("x-request-id");
+}
+
+/// OpenAI moderation model.
+pub type ModerationModel<H> = GenericModerationModel<OpenAIResponsesExt, H>;
+
+/// OpenAI moderation model for a client using Chat Completions.
+pub type CompletionsModerationModel<H> = GenericModerationModel<OpenAICompletionsExt, H>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ModerationClient;
+    use crate::moderation::{ModerationError, ModerationModel as _};
+    use crate::test_utils::RecordingHttpClient;
+
+    const RESPONSE: &str = r#"{
+        "id": "modr-970d409ef3bef3b70c73d8232df86e7d",
+        "model": "omni-moderation-latest",
+        "results": [
+            {
+                "flagged": true,
+                "categories": {"harassment": false, "violence": true, "illicit": null},
+                "category_scores": {"harassment": 0.0011, "violence": 0.8599, "illicit": null},
+                "category_applied_input_types": {"harassment": ["text"], "violence": ["text"]}
+            },
+            {
+                "flagged": false,
+                "categories": {"harassment": false, "violence": false},
+                "category_scores": {"harassment": 0.0001, "violence": 0.0002}
+            }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn moderation_posts_every_input_and_normalizes_each_result() {
+        let http = RecordingHttpClient::new(RESPONSE);
+        let client = crate::providers::openai::Client::builder()
+            .api_key("test-key")
+            .http_client(http.clone())
+            .build()
+            .expect("build client");
+
+        let response = client
+            .moderation_model(OMNI_MODERATION_LATEST)
+            .moderate(vec!["I will hurt you".to_owned(), "hello".to_owned()])
+            .await
+            .expect("moderate");
+
+        assert_eq!(response.provider, "openai");
+        assert_eq!(response.model.as_deref(), Some(OMNI_MODERATION_LATEST));
+        assert_eq!(
+            response.response_id.as_deref(),
+            Some("modr-970d409ef3bef3b70c73d8232df86e7d")
+        );
+        assert!(response.flagged());
+        let [flagged, clean] = response.results.as_slice() else {
+            panic!("expected two results, got {:?}", response.results);
+        };
+        assert_eq!(
+            flagged.flagged_categories().collect::<Vec<_>>(),
+            ["violence"]
+        );
+        assert_eq!(flagged.categories["violence"].score, 0.8599);
+        assert!(!flagged.categories["illicit"].flagged);
+        assert!(!clean.flagged);
+        assert_eq!(
+            response.raw["results"][0]["category_applied_input_types"]["violence"][0],
+            "text"
+        );
+
+        let requests = http.requests();
+        assert!(requests[0].uri.ends_with("/v1/moderations"));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json body");
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "model": "omni-moderation-latest",
+                "input": ["I will hurt you", "hello"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn a_result_count_that_does_not_match_the_inputs_is_an_error() {
+        let client = crate::providers::openai::Client::builder()
+            .api_key("test-key")
+            .http_client(RecordingHttpClient::new(RESPONSE))
+            .build()
+            .expect("build client");
+
+        let error = client
+            .moderation_model(OMNI_MODERATION_LATEST)
+            .moderate(vec!["one".to_owned()])
+            .await
+            .expect_err("two results for one input");
+        assert!(
+            matches!(error, ModerationError::ResponseError(_)),
+            "{error}"
+        );
+    }
+}
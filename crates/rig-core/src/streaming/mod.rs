@@ -313,6 +313,7 @@ impl StreamFinal {
             message_id: self.message_id.clone(),
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
         }
     }
 }
@@ -942,6 +943,8 @@ pub struct StreamingCompletionResponse {
     pub final_response_yielded: AtomicBool,
     /// Provider-assigned message ID (e.g. OpenAI Responses API `msg_` ID).
     pub message_id: Option<String>,
+    /// Label of the model that served this stream, set by routing models.
+    served_by: Option<String>,
 }
 
 impl StreamingCompletionResponse {
@@ -971,6 +974,7 @@ impl StreamingCompletionResponse {
             response: None,
             final_response_yielded: AtomicBool::new(false),
             message_id: None,
+            served_by: None,
         }
     }
 
@@ -979,6 +983,58 @@ impl StreamingCompletionResponse {
         &self.provider
     }
 
+    /// Label of the model that served this stream, when a routing model
+    /// chose among several. Reported through [`Self::identity`].
+    pub fn served_by(&self) -> Option<&str> {
+        self.served_by.as_deref()
+    }
+
+    /// Attach the label of the model that served this stream.
+    pub fn with_served_by(mut self, served_by: impl Into<String>) -> Self {
+        self.served_by = Some(served_by.into());
+        self
+    }
+
+    /// Wait until the provider yields its first event, without consuming it.
+    ///
+    /// When the first event is an error it is returned here instead of being
+    /// yielded by the stream; otherwise the event (or the end of an empty
+    /// stream) is kept and yielded first. Routing models call this before
+    /// handing a stream out, so a provider that fails before streaming
+    /// anything can be replaced while nothing has reached the caller yet.
+    pub async fn wait_for_first_event(&mut self) -> Result<(), CompletionError> {
+        let first = match self.inner.next().await {
+            Some(Err(error)) => return Err(error),
+            first => first,
+        };
+        let (_, placeholder_registration) = AbortHandle::new_pair();
+        let empty: StreamingResult = Box::pin(futures::stream::empty());
+        let rest = std::mem::replace(
+            &mut self.inner,
+            Abortable::new(empty, placeholder_registration),
+        );
+        let replayed: StreamingResult = Box::pin(futures::stream::iter(first).chain(rest));
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.inner = Abortable::new(replayed, abort_registration);
+        self.abort_handle = abort_handle;
+        Ok(())
+    }
+
+    /// Replace the provider stream with `wrap` applied to it, before anything
+    /// has been polled through this response. Aggregation, pausing and
+    /// cancellation keep working on the wrapped stream.
+    pub(crate) fn map_inner(&mut self, wrap: impl FnOnce(StreamingResult) -> StreamingResult) {
+        let (_, placeholder_registration) = AbortHandle::new_pair();
+        let empty: StreamingResult = Box::pin(futures::stream::empty());
+        let inner = std::mem::replace(
+            &mut self.inner,
+            Abortable::new(empty, placeholder_registration),
+        );
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.inner = Abortable::new(wrap(Box::pin(inner)), abort_registration);
+        self.abort_handle = abort_handle;
+    }
+
     /// Resolve the public correlator for a reasoning part that just ended,
     /// keeping the identity available for the part's afterlife.
     ///
@@ -1065,6 +1121,7 @@ impl StreamingCompletionResponse {
     pub fn identity(&self) -> crate::completion::ResponseIdentity {
         crate::completion::ResponseIdentity {
             message_id: self.message_id.clone(),
+            served_by: self.served_by.clone(),
             ..self
                 .response
                 .as_ref()
@@ -1489,6 +1546,7 @@ mod tests {
                 message_id: Some("msg_terminal".to_string()),
                 response_id: Some("resp_1".to_string()),
                 provider_request_id: Some("req_1".to_string()),
+                served_by: None,
             }
         );
     }
@@ -1515,10 +1573,43 @@ mod tests {
                 message_id: Some("msg_event".to_string()),
                 response_id: Some("resp_1".to_string()),
                 provider_request_id: None,
+                served_by: None,
             }
         );
     }
 
+    /// Waiting for the first event keeps it in the stream, and a first-event
+    /// error is returned instead of yielded.
+    #[tokio::test]
+    async fn wait_for_first_event_replays_the_event_or_returns_the_error() {
+        let raw = stream! {
+            yield Ok(RawStreamingChoice::Message("hello".to_string()));
+            yield Ok(RawStreamingChoice::FinalResponse(mock_final_with_total_tokens(1)));
+        };
+        let mut stream = StreamingCompletionResponse::stream(TEST_PROVIDER, to_stream_result(raw))
+            .with_served_by("primary");
+        stream
+            .wait_for_first_event()
+            .await
+            .expect("the first event is not an error");
+        while stream.next().await.is_some() {}
+        assert!(matches!(
+            stream.choice.as_slice(),
+            [AssistantContent::Text(text)] if text.text == "hello"
+        ));
+        assert_eq!(stream.identity().served_by.as_deref(), Some("primary"));
+
+        let raw = stream! {
+            yield Err(CompletionError::ProviderError("overloaded".to_string()));
+            yield Ok(RawStreamingChoice::Message("late".to_string()));
+        };
+        let mut stream = StreamingCompletionResponse::stream(TEST_PROVIDER, to_stream_result(raw));
+        assert!(matches!(
+            stream.wait_for_first_event().await,
+            Err(CompletionError::ProviderError(message)) if message == "overloaded"
+        ));
+    }
+
     fn create_reasoning_stream() -> StreamingCompletionResponse {
         let stream = stream! {
             yield Ok(RawStreamingChoice::Reasoning {                id: StreamPartId::wire("rs_1"),
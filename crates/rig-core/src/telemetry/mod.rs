@@ -393,6 +393,8 @@ pub enum ModalityOperation {
     Embeddings,
     /// A reranking request.
     Rerank,
+    /// A content moderation request.
+    Moderation,
     /// An audio transcription request.
     Transcription,
     /// An image generation request.
@@ -406,6 +408,7 @@ impl ModalityOperation {
         match self {
             Self::Embeddings => "embeddings",
             Self::Rerank => "rerank",
+            Self::Moderation => "moderation",
             Self::Transcription => "transcription",
             Self::ImageGeneration => "image_generation",
             Self::AudioGeneration => "audio_generation",
@@ -434,7 +437,8 @@ macro_rules! new_modality_span {
 }
 
 /// Builder for a canonical GenAI span on a non-completion modality
-/// (embeddings, rerank, transcription, image generation, audio generation).
+/// (embeddings, rerank, moderation, transcription, image generation, audio
+/// generation).
 ///
 /// Unlike [`CompletionSpanBuilder`], this never adopts an ambient span: the
 /// adoption contract exists so one *model turn* has exactly one completion
@@ -468,6 +472,9 @@ impl<'a> ModalitySpanBuilder<'a> {
             ModalityOperation::Rerank => {
                 new_modality_span!("rerank", self.provider, self.request_model, operation)
             }
+            ModalityOperation::Moderation => {
+                new_modality_span!("moderation", self.provider, self.request_model, operation)
+            }
             ModalityOperation::Transcription => {
                 new_modality_span!(
                     "transcription",
@@ -494,7 +501,7 @@ impl<'a> ModalitySpanBuilder<'a> {
 
 /// The telemetry a normalized modality response can put on its span: the
 /// usage and identity fields every normalized response carries since the
-/// type-erasure sweep. Implemented for all six normalized response types.
+/// type-erasure sweep. Implemented for all seven normalized response types.
 pub trait ModalityResponseTelemetry {
     /// Rig-normalized token usage for the call.
     fn telemetry_usage(&self) -> &Usage;
@@ -524,6 +531,7 @@ impl_modality_response_telemetry!(
     crate::embeddings::EmbeddingResponse,
     crate::embeddings::ImageEmbeddingResponse,
     crate::rerank::RerankResponse,
+    crate::moderation::ModerationResponse,
     crate::transcription::TranscriptionResponse,
 );
 #[cfg(feature = "image")]
@@ -0,0 +1,1064 @@
+//! Record/replay HTTP cassettes for offline provider tests.
+//!
+//! [`CassetteClient`] wraps a real [`HttpClientExt`] transport. In
+//! [`CassetteMode::Record`] it forwards every request to that transport and
+//! appends the exchange — unary bodies and full SSE streams alike — to a
+//! cassette file, with credentials redacted. In [`CassetteMode::Replay`] it
+//! never touches the network: requests are matched in order against the
+//! recorded interactions and answered from the file.
+//!
+//! Cassettes use the `when`/`then` schema of the workspace's own provider
+//! cassettes, so existing recordings replay unchanged. A `.json` path is read
+//! and written as a JSON array of interactions; any other extension is
+//! multi-document YAML.
+//!
+//! ```no_run
+//! use rig_core::http_client::HttpClientExt;
+//! use rig_core::test_utils::{CassetteClient, CassetteError};
+//!
+//! // Records when `RIG_PROVIDER_TEST_MODE=record`, replays otherwise.
+//! fn cassette_transport<H>(live: H) -> Result<CassetteClient<H>, CassetteError>
+//! where
+//!     H: HttpClientExt + Clone,
+//! {
+//!     CassetteClient::new("tests/cassettes/openai/chat.yaml", live)
+//! }
+//! ```
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bytes::Bytes;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    http_client::{
+        self, HeaderMap, HttpClientExt, LazyBody, MultipartForm, Request, Response,
+        StreamingResponse,
+    },
+    wasm_compat::WasmCompatSend,
+};
+
+/// Environment variable read by [`CassetteMode::from_env`].
+pub const CASSETTE_MODE_ENV: &str = "RIG_PROVIDER_TEST_MODE";
+
+/// Placeholder written in place of redacted header and query values.
+const REDACTED: &str = "[REDACTED]";
+
+/// Boundary used to encode multipart bodies, so uploads record and match
+/// byte-for-byte regardless of the boundary the caller generated.
+const MULTIPART_BOUNDARY: &str = "rig-cassette-boundary";
+
+const DEFAULT_REDACTED_HEADERS: &[&str] = &[
+    "authorization",
+    "x-api-key",
+    "api-key",
+    "x-goog-api-key",
+    "ocp-apim-subscription-key",
+    "cookie",
+    "set-cookie",
+    "openai-organization",
+    "openai-project",
+    "anthropic-organization-id",
+    "x-amz-security-token",
+    "x-amz-content-sha256",
+    "x-amz-date",
+];
+
+const DEFAULT_REDACTED_QUERY_PARAMS: &[&str] = &[
+    "key",
+    "api_key",
+    "apikey",
+    "access_token",
+    "x-amz-credential",
+    "x-amz-signature",
+    "x-amz-security-token",
+];
+
+/// Whether a [`CassetteClient`] talks to the network or to its cassette.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Answer every request from the cassette; never call the inner client.
+    #[default]
+    Replay,
+    /// Forward every request to the inner client and overwrite the cassette
+    /// with the exchanges.
+    Record,
+}
+
+impl CassetteMode {
+    /// [`Self::Record`] when [`CASSETTE_MODE_ENV`] is `record`
+    /// (case-insensitive), [`Self::Replay`] otherwise.
+    pub fn from_env() -> Self {
+        match std::env::var(CASSETTE_MODE_ENV) {
+            Ok(mode) if mode.trim().eq_ignore_ascii_case("record") => Self::Record,
+            _ => Self::Replay,
+        }
+    }
+}
+
+/// Errors raised while loading, saving or replaying a cassette.
+///
+/// Replay and recording failures reach the caller as
+/// [`http_client::Error::Instance`] wrapping this type.
+#[derive(Debug, thiserror::Error)]
+pub enum CassetteError {
+    #[error("cassette {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("cassette {path} is not valid YAML: {source}")]
+    Yaml {
+        path: PathBuf,
+        #[source]
+        source: serde_yaml::Error,
+    },
+    #[error("cassette {path} is not valid JSON: {source}")]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("cassette {path} has an undecodable base64 body: {source}")]
+    Body {
+        path: PathBuf,
+        #[source]
+        source: base64::DecodeError,
+    },
+    /// Every recorded interaction has already been replayed.
+    #[error("cassette {path} has no interaction left for {method} {uri}")]
+    Exhausted {
+        path: PathBuf,
+        method: String,
+        uri: String,
+    },
+    /// The request differs from the next recorded interaction.
+    #[error(
+        "cassette {path} interaction {index} expects {expected}, got {request} \
+         (mismatched {mismatch})"
+    )]
+    Mismatch {
+        path: PathBuf,
+        index: usize,
+        /// The request's method and URI.
+        request: String,
+        /// The recorded method and path.
+        expected: String,
+        /// Which part of the request failed to match.
+        mismatch: &'static str,
+    },
+}
+
+/// An [`HttpClientExt`] that records real exchanges to a cassette file and
+/// replays them deterministically.
+///
+/// Replay is strictly ordered: each request must match the next unreplayed
+/// interaction on method, path, query and body. JSON bodies compare
+/// structurally, and redacted query values match anything. Recorded request
+/// headers are kept for reference but not matched on.
+///
+/// Non-success responses replay as
+/// [`http_client::Error::InvalidStatusCodeWithDetails`], the way the bundled
+/// reqwest transport reports them.
+#[derive(Clone, Debug)]
+pub struct CassetteClient<H> {
+    inner: H,
+    mode: CassetteMode,
+    state: Arc<Mutex<CassetteState>>,
+    redaction: Arc<Redaction>,
+}
+
+impl<H> CassetteClient<H> {
+    /// Create a client in the mode selected by [`CassetteMode::from_env`].
+    pub fn new(path: impl Into<PathBuf>, inner: H) -> Result<Self, CassetteError> {
+        Self::with_mode(path, CassetteMode::from_env(), inner)
+    }
+
+    /// Create a client in an explicit mode.
+    ///
+    /// Replay loads the cassette up front and fails if it cannot be read.
+    /// Record starts from an empty cassette and rewrites the file after every
+    /// completed exchange.
+    pub fn with_mode(
+        path: impl Into<PathBuf>,
+        mode: CassetteMode,
+        inner: H,
+    ) -> Result<Self, CassetteError> {
+        let path = path.into();
+        let interactions = match mode {
+            CassetteMode::Replay => load_interactions(&path)?,
+            CassetteMode::Record => Vec::new(),
+        };
+
+        Ok(Self {
+            inner,
+            mode,
+            state: Arc::new(Mutex::new(CassetteState {
+                path,
+                interactions,
+                cursor: 0,
+            })),
+            redaction: Arc::new(Redaction::default()),
+        })
+    }
+
+    /// Create a replay-only client. The inner client is never called.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, CassetteError>
+    where
+        H: Default,
+    {
+        Self::with_mode(path, CassetteMode::Replay, H::default())
+    }
+
+    /// Also redact this request or response header when recording.
+    pub fn redact_header(mut self, name: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.redaction)
+            .headers
+            .push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Also redact this query parameter when recording.
+    pub fn redact_query_param(mut self, name: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.redaction)
+            .query_params
+            .push(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// The mode this client runs in.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// The cassette file this client reads or writes.
+    pub fn path(&self) -> PathBuf {
+        self.state_guard().path.clone()
+    }
+
+    /// Recorded interactions not yet replayed. Always zero while recording.
+    ///
+    /// Assert this is zero at the end of a test to catch requests the code
+    /// under test stopped making.
+    pub fn remaining_interactions(&self) -> usize {
+        match self.mode {
+            CassetteMode::Replay => {
+                let state = self.state_guard();
+                state.interactions.len().saturating_sub(state.cursor)
+            }
+            CassetteMode::Record => 0,
+        }
+    }
+
+    fn state_guard(&self) -> MutexGuard<'_, CassetteState> {
+        lock(&self.state)
+    }
+
+    fn record_request(&self, parts: &http::request::Parts, body: &[u8]) -> Recorder {
+        let when = CassetteRequest {
+            path: parts.uri.path().to_string(),
+            method: parts.method.as_str().to_ascii_uppercase(),
+            query_param: query_pairs(parts.uri.query())
+                .into_iter()
+                .map(|(name, value)| NameValue {
+                    value: self.redaction.query_value(&name, value),
+                    name,
+                })
+                .collect(),
+            header: self.redaction.headers(&parts.headers),
+            body: None,
+            body_encoding: BodyEncoding::Utf8,
+        }
+        .with_body(body);
+
+        let slot = {
+            let mut state = self.state_guard();
+            state
+                .interactions
+                .push(CassetteInteraction { when, then: None });
+            state.interactions.len() - 1
+        };
+
+        Recorder {
+            state: self.state.clone(),
+            redaction: self.redaction.clone(),
+            slot,
+        }
+    }
+
+    fn replay_request(
+        &self,
+        parts: &http::request::Parts,
+        body: &[u8],
+    ) -> Result<CassetteResponse, CassetteError> {
+        let mut state = self.state_guard();
+        let index = state.cursor;
+        let Some(interaction) = state.interactions.get(index) else {
+            return Err(CassetteError::Exhausted {
+                path: state.path.clone(),
+                method: parts.method.to_string(),
+                uri: parts.uri.to_string(),
+            });
+        };
+
+        let when = &interaction.when;
+        let mismatch = if !parts.method.as_str().eq_ignore_ascii_case(&when.method) {
+            Some("method")
+        } else if parts.uri.path() != when.path {
+            Some("path")
+        } else if !query_matches(parts.uri.query(), &when.query_param) {
+            Some("query")
+        } else if !when
+            .body_matches(body)
+            .map_err(|source| CassetteError::Body {
+                path: state.path.clone(),
+                source,
+            })?
+        {
+            Some("body")
+        } else {
+            None
+        };
+        if let Some(mismatch) = mismatch {
+            return Err(CassetteError::Mismatch {
+                path: state.path.clone(),
+                index,
+                request: format!("{} {}", parts.method, parts.uri),
+                expected: format!("{} {}", when.method, when.path),
+                mismatch,
+            });
+        }
+
+        let response = interaction.then.clone().unwrap_or_default();
+        state.cursor += 1;
+        let path = state.path.clone();
+        drop(state);
+
+        response
+            .decoded_body()
+            .map(|_| response)
+            .map_err(|source| CassetteError::Body { path, source })
+    }
+}
+
+impl<H> HttpClientExt for CassetteClient<H>
+where
+    H: HttpClientExt + Clone + 'static,
+{
+    fn send<T, U>(
+        &self,
+        req: Request<T>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        T: Into<Bytes> + WasmCompatSend,
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let route = match self.mode {
+            CassetteMode::Replay => Route::Replay(self.replay_request(&parts, &body)),
+            CassetteMode::Record => Route::Record(
+                self.record_request(&parts, &body),
+                self.inner
+                    .send::<Bytes, Bytes>(Request::from_parts(parts, body)),
+            ),
+        };
+
+        async move {
+            match route {
+                Route::Replay(replayed) => replayed.map_err(instance_error)?.into_unary(),
+                Route::Record(recorder, pending) => recorder.finish_unary(pending.await).await,
+            }
+        }
+    }
+
+    fn send_multipart<U>(
+        &self,
+        req: Request<MultipartForm>,
+    ) -> impl Future<Output = http_client::Result<Response<LazyBody<U>>>> + WasmCompatSend + 'static
+    where
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        let (parts, form) = req.into_parts();
+        let (_, body) = form.clone().boundary(MULTIPART_BOUNDARY).encode();
+        let route = match self.mode {
+            CassetteMode::Replay => Route::Replay(self.replay_request(&parts, &body)),
+            CassetteMode::Record => Route::Record(
+                self.record_request(&parts, &body),
+                self.inner
+                    .send_multipart::<Bytes>(Request::from_parts(parts, form)),
+            ),
+        };
+
+        async move {
+            match route {
+                Route::Replay(replayed) => replayed.map_err(instance_error)?.into_unary(),
+                Route::Record(recorder, pending) => recorder.finish_unary(pending.await).await,
+            }
+        }
+    }
+
+    fn send_streaming<T>(
+        &self,
+        req: Request<T>,
+    ) -> impl Future<Output = http_client::Result<StreamingResponse>> + WasmCompatSend
+    where
+        T: Into<Bytes> + WasmCompatSend,
+    {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = body.into();
+        let route = match self.mode {
+            CassetteMode::Replay => Route::Replay(self.replay_request(&parts, &body)),
+            CassetteMode::Record => Route::Record(
+                self.record_request(&parts, &body),
+                Request::from_parts(parts, body),
+            ),
+        };
+
+        async move {
+            match route {
+                Route::Replay(replayed) => replayed.map_err(instance_error)?.into_streaming(),
+                Route::Record(recorder, request) => {
+                    recorder.finish_streaming(self.inner.send_streaming(request).await)
+                }
+            }
+        }
+    }
+}
+
+/// Where a request goes: answered from the cassette, or sent through the
+/// inner client with `P` — its pending response or request — in hand.
+enum Route<P> {
+    Replay(Result<CassetteResponse, CassetteError>),
+    Record(Recorder, P),
+}
+
+/// Completes one reserved interaction while recording.
+///
+/// Slots are reserved when the request is sent, so the cassette keeps request
+/// order even when responses complete out of order. A request that fails
+/// without an HTTP status is never written.
+struct Recorder {
+    state: Arc<Mutex<CassetteState>>,
+    redaction: Arc<Redaction>,
+    slot: usize,
+}
+
+impl Recorder {
+    async fn finish_unary<U>(
+        self,
+        result: http_client::Result<Response<LazyBody<Bytes>>>,
+    ) -> http_client::Result<Response<LazyBody<U>>>
+    where
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        let response = match result {
+            Ok(response) => response,
+            Err(error) => return Err(self.finish_error(error)),
+        };
+
+        let (parts, body) = response.into_parts();
+        let body = body.await?;
+        self.finish(parts.status, &parts.headers, &body)
+            .map_err(instance_error)?;
+
+        let body: LazyBody<U> = Box::pin(async move { Ok(U::from(body)) });
+        Ok(Response::from_parts(parts, body))
+    }
+
+    fn finish_streaming(
+        self,
+        result: http_client::Result<StreamingResponse>,
+    ) -> http_client::Result<StreamingResponse> {
+        let response = match result {
+            Ok(response) => response,
+            Err(error) => return Err(self.finish_error(error)),
+        };
+
+        let (parts, mut stream) = response.into_parts();
+        let status = parts.status;
+        let headers = parts.headers.clone();
+        let recorded: http_client::sse::BoxedStream = Box::pin(async_stream::stream! {
+            let mut body = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                if let Ok(bytes) = &chunk {
+                    body.extend_from_slice(bytes);
+                }
+                yield chunk;
+            }
+            if let Err(error) = self.finish(status, &headers, &body) {
+                yield Err(instance_error(error));
+            }
+        });
+
+        Ok(Response::from_parts(parts, recorded))
+    }
+
+    /// Record a non-success status carried by a transport error, then hand
+    /// the error back unchanged.
+    fn finish_error(self, error: http_client::Error) -> http_client::Error {
+        let Some(status) = error.non_success_status() else {
+            return error;
+        };
+        let headers = error.non_success_headers().cloned().unwrap_or_default();
+        let body = error.non_success_body().unwrap_or_default().to_string();
+
+        match self.finish(status, &headers, body.as_bytes()) {
+            Ok(()) => error,
+            Err(cassette_error) => instance_error(cassette_error),
+        }
+    }
+
+    fn finish(
+        &self,
+        status: http::StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(), CassetteError> {
+        let then = CassetteResponse {
+            status: status.as_u16(),
+            header: self.redaction.headers(headers),
+            body: None,
+            body_encoding: BodyEncoding::Utf8,
+        }
+        .with_body(body);
+
+        let mut state = lock(&self.state);
+        if let Some(interaction) = state.interactions.get_mut(self.slot) {
+            interaction.then = Some(then);
+        }
+        state.save()
+    }
+}
+
+#[derive(Debug)]
+struct CassetteState {
+    path: PathBuf,
+    interactions: Vec<CassetteInteraction>,
+    /// Index of the next interaction to replay.
+    cursor: usize,
+}
+
+impl CassetteState {
+    fn save(&self) -> Result<(), CassetteError> {
+        let completed: Vec<&CassetteInteraction> = self
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.then.is_some())
+            .collect();
+
+        let contents = if is_json(&self.path) {
+            serde_json::to_string_pretty(&completed).map_err(|source| CassetteError::Json {
+                path: self.path.clone(),
+                source,
+            })?
+        } else {
+            let mut output = String::new();
+            for (index, interaction) in completed.iter().enumerate() {
+                if index > 0 {
+                    output.push_str("---\n");
+                }
+                let document =
+                    serde_yaml::to_string(interaction).map_err(|source| CassetteError::Yaml {
+                        path: self.path.clone(),
+                        source,
+                    })?;
+                output.push_str(&document);
+            }
+            output
+        };
+
+        let io_error = |source| CassetteError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        std::fs::write(&self.path, contents).map_err(io_error)
+    }
+}
+
+fn load_interactions(path: &Path) -> Result<Vec<CassetteInteraction>, CassetteError> {
+    let contents = std::fs::read_to_string(path).map_err(|source| CassetteError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    if is_json(path) {
+        return serde_json::from_str(&contents).map_err(|source| CassetteError::Json {
+            path: path.to_path_buf(),
+            source,
+        });
+    }
+
+    serde_yaml::Deserializer::from_str(&contents)
+        .map(|document| {
+            CassetteInteraction::deserialize(document).map_err(|source| CassetteError::Yaml {
+                path: path.to_path_buf(),
+                source,
+            })
+        })
+        .collect()
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+fn lock(state: &Mutex<CassetteState>) -> MutexGuard<'_, CassetteState> {
+    match state.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+fn instance_error(error: CassetteError) -> http_client::Error {
+    http_client::Error::Instance(Box::new(error))
+}
+
+#[derive(Clone, Debug)]
+struct Redaction {
+    headers: Vec<String>,
+    query_params: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            headers: DEFAULT_REDACTED_HEADERS
+                .iter()
+                .map(|name| (*name).to_string())
+                .collect(),
+            query_params: DEFAULT_REDACTED_QUERY_PARAMS
+                .iter()
+                .map(|name| (*name).to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Redaction {
+    fn headers(&self, headers: &HeaderMap) -> Vec<NameValue> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str().to_ascii_lowercase();
+                let value = if self.headers.contains(&name) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                NameValue { name, value }
+            })
+            .collect()
+    }
+
+    fn query_value(&self, name: &str, value: String) -> String {
+        if self.query_params.contains(&name.to_ascii_lowercase()) {
+            REDACTED.to_string()
+        } else {
+            value
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CassetteInteraction {
+    when: CassetteRequest,
+    /// `None` only while a recorded request is still in flight.
+    then: Option<CassetteResponse>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CassetteRequest {
+    path: String,
+    method: String,
+    #[serde(default)]
+    query_param: Vec<NameValue>,
+    #[serde(default)]
+    header: Vec<NameValue>,
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "BodyEncoding::is_utf8")]
+    body_encoding: BodyEncoding,
+}
+
+impl CassetteRequest {
+    fn with_body(mut self, body: &[u8]) -> Self {
+        (self.body, self.body_encoding) = encode_body(body);
+        self
+    }
+
+    fn body_matches(&self, actual: &[u8]) -> Result<bool, base64::DecodeError> {
+        let Some(expected) = &self.body else {
+            return Ok(actual.is_empty());
+        };
+        let expected = decode_body(expected, self.body_encoding)?;
+
+        if let (Ok(actual), Ok(expected)) = (
+            serde_json::from_slice::<serde_json::Value>(actual),
+            serde_json::from_slice::<serde_json::Value>(&expected),
+        ) {
+            return Ok(actual == expected);
+        }
+
+        Ok(actual == expected.as_slice())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct CassetteResponse {
+    status: u16,
+    #[serde(default)]
+    header: Vec<NameValue>,
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "BodyEncoding::is_utf8")]
+    body_encoding: BodyEncoding,
+}
+
+impl Default for CassetteResponse {
+    fn default() -> Self {
+        Self {
+            status: http::StatusCode::OK.as_u16(),
+            header: Vec::new(),
+            body: None,
+            body_encoding: BodyEncoding::Utf8,
+        }
+    }
+}
+
+impl CassetteResponse {
+    fn with_body(mut self, body: &[u8]) -> Self {
+        (self.body, self.body_encoding) = encode_body(body);
+        self
+    }
+
+    fn decoded_body(&self) -> Result<Bytes, base64::DecodeError> {
+        match &self.body {
+            Some(body) => decode_body(body, self.body_encoding).map(Bytes::from),
+            None => Ok(Bytes::new()),
+        }
+    }
+
+    fn header_map(&self) -> HeaderMap {
+        self.header
+            .iter()
+            .filter_map(|header| {
+                Some((
+                    http::HeaderName::from_bytes(header.name.as_bytes()).ok()?,
+                    http::HeaderValue::from_str(&header.value).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    fn is_event_stream(&self) -> bool {
+        self.header.iter().any(|header| {
+            header.name.eq_ignore_ascii_case("content-type")
+                && header
+                    .value
+                    .to_ascii_lowercase()
+                    .starts_with("text/event-stream")
+        })
+    }
+
+    /// Split the parts every replay path needs, surfacing a non-success
+    /// status as the error the bundled transport raises.
+    fn into_replayed(self) -> http_client::Result<(http::StatusCode, HeaderMap, Bytes, bool)> {
+        let status = http::StatusCode::from_u16(self.status)
+            .map_err(|error| http_client::Error::Protocol(error.into()))?;
+        let headers = self.header_map();
+        let body = self
+            .decoded_body()
+            .map_err(|error| http_client::Error::Instance(Box::new(error)))?;
+
+        if !status.is_success() {
+            return Err(http_client::Error::non_success_with_details(
+                status,
+                headers,
+                String::from_utf8_lossy(&body).into_owned(),
+            ));
+        }
+
+        let event_stream = self.is_event_stream();
+        Ok((status, headers, body, event_stream))
+    }
+
+    fn into_unary<U>(self) -> http_client::Result<Response<LazyBody<U>>>
+    where
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        let (status, headers, body, _) = self.into_replayed()?;
+        let body: LazyBody<U> = Box::pin(async move { Ok(U::from(body)) });
+        build_response(status, headers, body)
+    }
+
+    /// Event streams replay one SSE event per chunk so consumers see the
+    /// same incremental delivery they saw live; other bodies arrive whole.
+    fn into_streaming(self) -> http_client::Result<StreamingResponse> {
+        let (status, headers, body, event_stream) = self.into_replayed()?;
+        let chunks = if event_stream {
+            split_events(&body)
+        } else {
+            vec![body]
+        };
+        let stream: http_client::sse::BoxedStream = Box::pin(futures::stream::iter(
+            chunks.into_iter().map(Ok::<Bytes, http_client::Error>),
+        ));
+        build_response(status, headers, stream)
+    }
+}
+
+fn build_response<B>(
+    status: http::StatusCode,
+    headers: HeaderMap,
+    body: B,
+) -> http_client::Result<Response<B>> {
+    let mut builder = Response::builder().status(status);
+    if let Some(slot) = builder.headers_mut() {
+        *slot = headers;
+    }
+    builder.body(body).map_err(http_client::Error::Protocol)
+}
+
+/// Split an SSE body after each blank-line event terminator.
+fn split_events(body: &Bytes) -> Vec<Bytes> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut index = 0;
+    while let Some(window) = body.get(index..index + 2) {
+        if window == b"\n\n" {
+            chunks.push(body.slice(start..index + 2));
+            start = index + 2;
+            index = start;
+        } else {
+            index += 1;
+        }
+    }
+    if start < body.len() {
+        chunks.push(body.slice(start..));
+    }
+    chunks
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BodyEncoding {
+    #[default]
+    Utf8,
+    Base64,
+}
+
+impl BodyEncoding {
+    fn is_utf8(&self) -> bool {
+        matches!(self, Self::Utf8)
+    }
+}
+
+fn encode_body(body: &[u8]) -> (Option<String>, BodyEncoding) {
+    if body.is_empty() {
+        return (None, BodyEncoding::Utf8);
+    }
+    match std::str::from_utf8(body) {
+        Ok(body) => (Some(body.to_string()), BodyEncoding::Utf8),
+        Err(_) => (Some(BASE64_STANDARD.encode(body)), BodyEncoding::Base64),
+    }
+}
+
+fn decode_body(body: &str, encoding: BodyEncoding) -> Result<Vec<u8>, base64::DecodeError> {
+    match encoding {
+        BodyEncoding::Utf8 => Ok(body.as_bytes().to_vec()),
+        BodyEncoding::Base64 => BASE64_STANDARD.decode(body),
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+fn query_pairs(query: Option<&str>) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Query parameters match as a multiset; a redacted recorded value matches
+/// any actual value for that name.
+fn query_matches(query: Option<&str>, expected: &[NameValue]) -> bool {
+    let mut actual = query_pairs(query);
+    if actual.len() != expected.len() {
+        return false;
+    }
+
+    expected.iter().all(|expected| {
+        let position = actual.iter().position(|(name, value)| {
+            *name == expected.name && (expected.value == REDACTED || *value == expected.value)
+        });
+        match position {
+            Some(position) => {
+                actual.swap_remove(position);
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{MockHttpResponse, MockStreamingClient, SequencedHttpClient};
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("rig-cassette-{}", std::process::id()))
+            .join(name)
+    }
+
+    fn chat_request(prompt: &str) -> Request<Vec<u8>> {
+        Request::post("https://api.example.com/v1/chat?key=sk-live&stream=false")
+            .header("authorization", "Bearer sk-live")
+            .header("content-type", "application/json")
+            .body(format!(r#"{{"model":"m","prompt":"{prompt}"}}"#).into_bytes())
+            .unwrap()
+    }
+
+    async fn send_error(
+        client: &impl HttpClientExt,
+        request: Request<Vec<u8>>,
+    ) -> http_client::Error {
+        match client.send::<_, Bytes>(request).await {
+            Ok(_) => panic!("expected the request to fail"),
+            Err(error) => error,
+        }
+    }
+
+    async fn body_text(response: Response<LazyBody<Bytes>>) -> String {
+        let body = response.into_body().await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_redacted_exchanges_and_replays_them_in_order() {
+        let path = cassette_path("unary.yaml");
+        let live = SequencedHttpClient::new([
+            MockHttpResponse::success(r#"{"text":"first"}"#),
+            MockHttpResponse::success(r#"{"text":"second"}"#),
+        ]);
+        let recorder = CassetteClient::with_mode(&path, CassetteMode::Record, live).unwrap();
+        let first = recorder.send(chat_request("one")).await.unwrap();
+        assert_eq!(body_text(first).await, r#"{"text":"first"}"#);
+        let second = recorder.send(chat_request("two")).await.unwrap();
+        assert_eq!(body_text(second).await, r#"{"text":"second"}"#);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("sk-live"), "{contents}");
+        assert!(contents.contains(REDACTED), "{contents}");
+
+        let replay = CassetteClient::<SequencedHttpClient>::replay(&path).unwrap();
+        assert_eq!(replay.remaining_interactions(), 2);
+        // Key order and the redacted credential don't affect matching.
+        let request = Request::post("https://api.example.com/v1/chat?stream=false&key=other")
+            .body(br#"{"prompt":"one","model":"m"}"#.to_vec())
+            .unwrap();
+        let first = replay.send(request).await.unwrap();
+        assert_eq!(body_text(first).await, r#"{"text":"first"}"#);
+
+        let error = send_error(&replay, chat_request("one")).await;
+        assert!(error.to_string().contains("mismatched body"), "{error}");
+        let second = replay.send(chat_request("two")).await.unwrap();
+        assert_eq!(body_text(second).await, r#"{"text":"second"}"#);
+        assert_eq!(replay.remaining_interactions(), 0);
+
+        let error = send_error(&replay, chat_request("three")).await;
+        assert!(error.to_string().contains("no interaction left"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn records_and_replays_event_streams_event_by_event() {
+        let path = cassette_path("stream.json");
+        let sse = "data: {\"delta\":\"Hel\"}\n\ndata: {\"delta\":\"lo\"}\n\ndata: [DONE]\n\n";
+        let live = MockStreamingClient {
+            sse_bytes: Bytes::from(sse),
+        };
+        let recorder = CassetteClient::with_mode(&path, CassetteMode::Record, live).unwrap();
+        let response = recorder.send_streaming(chat_request("hi")).await.unwrap();
+        let recorded: Vec<Bytes> = response
+            .into_body()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(recorded.concat(), sse.as_bytes());
+
+        let replay = CassetteClient::<MockStreamingClient>::replay(&path).unwrap();
+        let response = replay.send_streaming(chat_request("hi")).await.unwrap();
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let replayed: Vec<Bytes> = response
+            .into_body()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(replayed.len(), 3);
+        assert_eq!(replayed.concat(), sse.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn non_success_statuses_replay_as_transport_errors() {
+        let path = cassette_path("error.yaml");
+        let live = SequencedHttpClient::new([MockHttpResponse::error(
+            http::StatusCode::TOO_MANY_REQUESTS,
+            "slow down",
+        )]);
+        let recorder = CassetteClient::with_mode(&path, CassetteMode::Record, live).unwrap();
+        let recorded = send_error(&recorder, chat_request("hi")).await;
+        assert_eq!(
+            recorded.non_success_status(),
+            Some(http::StatusCode::TOO_MANY_REQUESTS)
+        );
+
+        let replay = CassetteClient::<SequencedHttpClient>::replay(&path).unwrap();
+        let replayed = send_error(&replay, chat_request("hi")).await;
+        assert_eq!(
+            replayed.non_success_status(),
+            Some(http::StatusCode::TOO_MANY_REQUESTS)
+        );
+        assert_eq!(replayed.non_success_body(), Some("slow down"));
+    }
+
+    #[test]
+    fn replays_workspace_cassettes() {
+        let path = cassette_path("workspace.yaml");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "when:\n  path: /v1/models\n  method: GET\n  query_param: []\n  header: []\n  body: null\n\
+             then:\n  status: 200\n  header: []\n  body: '{\"data\":[]}'\n",
+        )
+        .unwrap();
+
+        let replay = CassetteClient::<SequencedHttpClient>::replay(&path).unwrap();
+        let request = Request::get("https://api.example.com/v1/models")
+            .body(Vec::new())
+            .unwrap();
+        let response = futures::executor::block_on(replay.send(request)).unwrap();
+        assert_eq!(
+            futures::executor::block_on(body_text(response)),
+            r#"{"data":[]}"#
+        );
+    }
+}
@@ -338,8 +338,9 @@ impl HttpClientExt for SequencedHttpClient {
         U: From<Bytes> + WasmCompatSend + 'static,
     {
         let response = self.next_response();
-        let (parts, _body) = req.into_parts();
-        self.record_request(parts.uri.to_string(), parts.headers, Bytes::new());
+        let (parts, body) = req.into_parts();
+        let (_, body) = body.boundary("sequenced-http-client").encode();
+        self.record_request(parts.uri.to_string(), parts.headers, body);
 
         async move {
             match response {
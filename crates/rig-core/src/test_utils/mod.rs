@@ -1,5 +1,6 @@
 //! Test utilities for deterministic completion-model tests.
 
+mod cassette;
 mod completion;
 mod embeddings;
 mod http;
@@ -14,6 +15,7 @@ pub mod streaming_conformance;
 mod streaming_conformance_suite;
 mod tracing_isolation;
 
+pub use cassette::{CASSETTE_MODE_ENV, CassetteClient, CassetteError, CassetteMode};
 pub use completion::{MockCompletionModel, MockError, MockTurn};
 pub use embeddings::{MockEmbeddingModel, MockMultiTextDocument, MockTextDocument};
 pub use http::{
@@ -91,6 +91,7 @@ impl TranscriptionResponse {
             message_id: None,
             response_id: self.response_id.clone(),
             provider_request_id: self.provider_request_id.clone(),
+            served_by: None,
         }
     }
 }
@@ -0,0 +1,425 @@
+//! In-memory BM25 keyword index.
+//!
+//! [`Bm25Index`] ranks documents with Okapi BM25 over their text. Exact tokens
+//! such as SKUs, error codes or function names are matched literally, which
+//! embedding similarity tends to blur. The index implements
+//! [`VectorStoreIndex`], so it can be queried on its own or fused with an
+//! embedding index through [`HybridIndex`](super::hybrid::HybridIndex).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    Embed,
+    embeddings::{EmbedError, to_texts},
+    vector_store::{
+        VectorSearchRequest, VectorStoreError, VectorStoreIndex,
+        in_memory_store::InMemoryVectorStore, request::Filter,
+    },
+};
+
+/// Default term-frequency saturation parameter.
+pub const DEFAULT_K1: f64 = 1.2;
+
+/// Default document-length normalization parameter.
+pub const DEFAULT_B: f64 = 0.75;
+
+/// An in-memory BM25 keyword index over documents of type `D`.
+///
+/// Text is lowercased and split on characters that are neither alphanumeric
+/// nor `-`/`_`. A compound token such as `SKU-4711` is indexed both whole and
+/// as its parts (`sku`, `4711`), so either form of the query matches.
+///
+/// Scores are raw BM25 scores, not similarities: they are unbounded and only
+/// comparable within one query. A request threshold is applied to them as a
+/// minimum score.
+#[derive(Clone, Debug)]
+pub struct Bm25Index<D> {
+    documents: HashMap<String, IndexedDocument<D>>,
+    /// Term -> ids of the documents containing it.
+    postings: HashMap<String, HashSet<String>>,
+    total_length: usize,
+    k1: f64,
+    b: f64,
+}
+
+#[derive(Clone, Debug)]
+struct IndexedDocument<D> {
+    document: D,
+    term_frequencies: HashMap<String, usize>,
+    length: usize,
+}
+
+impl<D> Default for Bm25Index<D> {
+    fn default() -> Self {
+        Self {
+            documents: HashMap::new(),
+            postings: HashMap::new(),
+            total_length: 0,
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+        }
+    }
+}
+
+impl<D> Bm25Index<D> {
+    /// Create an empty index with the default `k1` and `b` parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the BM25 `k1` (term-frequency saturation) and `b` (length
+    /// normalization) parameters.
+    pub fn with_params(mut self, k1: f64, b: f64) -> Self {
+        self.k1 = k1;
+        self.b = b;
+        self
+    }
+
+    /// Index a document under `id` with the given text, replacing any document
+    /// already stored under that id.
+    pub fn insert(&mut self, id: impl ToString, document: D, text: &str) {
+        let id = id.to_string();
+        self.remove(&id);
+
+        let mut term_frequencies = HashMap::<String, usize>::new();
+        let mut length = 0;
+        for token in tokenize(text) {
+            *term_frequencies.entry(token).or_default() += 1;
+            length += 1;
+        }
+
+        for term in term_frequencies.keys() {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id.clone());
+        }
+        self.total_length += length;
+        self.documents.insert(
+            id,
+            IndexedDocument {
+                document,
+                term_frequencies,
+                length,
+            },
+        );
+    }
+
+    /// Add documents under explicit ids, indexing the same text that
+    /// [`Embed`] would hand to an embedding model.
+    pub fn add_documents_with_ids(
+        &mut self,
+        documents: impl IntoIterator<Item = (impl ToString, D)>,
+    ) -> Result<(), EmbedError>
+    where
+        D: Embed + Clone,
+    {
+        for (id, document) in documents {
+            let text = to_texts(document.clone())?.join("\n");
+            self.insert(id, document, &text);
+        }
+        Ok(())
+    }
+
+    /// Delete documents by id, returning how many were removed.
+    pub fn delete_documents(&mut self, ids: impl IntoIterator<Item = impl AsRef<str>>) -> usize {
+        ids.into_iter()
+            .filter(|id| self.remove(id.as_ref()))
+            .count()
+    }
+
+    /// Get a document by its id.
+    pub fn get_document(&self, id: &str) -> Option<&D> {
+        self.documents.get(id).map(|indexed| &indexed.document)
+    }
+
+    /// Number of indexed documents.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether the index holds no documents.
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    fn remove(&mut self, id: &str) -> bool {
+        let Some(indexed) = self.documents.remove(id) else {
+            return false;
+        };
+
+        for term in indexed.term_frequencies.keys() {
+            if let Some(ids) = self.postings.get_mut(term) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_length -= indexed.length;
+        true
+    }
+
+    /// Rank documents against `query`, best first.
+    fn search(
+        &self,
+        query: &str,
+        n: usize,
+        filter: Option<&Filter<serde_json::Value>>,
+        threshold: Option<f64>,
+    ) -> Result<Vec<(f64, &String, &D)>, VectorStoreError>
+    where
+        D: Serialize,
+    {
+        if self.documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let document_count = self.documents.len() as f64;
+        let average_length = (self.total_length as f64 / document_count).max(1.0);
+        let terms = tokenize(query).collect::<HashSet<_>>();
+
+        let mut scores = HashMap::<&String, f64>::new();
+        for term in &terms {
+            let Some(ids) = self.postings.get(term) else {
+                continue;
+            };
+            let frequency = ids.len() as f64;
+            let idf = (1.0 + (document_count - frequency + 0.5) / (frequency + 0.5)).ln();
+
+            for id in ids {
+                let Some(indexed) = self.documents.get(id) else {
+                    continue;
+                };
+                let tf = indexed.term_frequencies.get(term).copied().unwrap_or(0) as f64;
+                let norm = 1.0 - self.b + self.b * indexed.length as f64 / average_length;
+                *scores.entry(id).or_default() +=
+                    idf * tf * (self.k1 + 1.0) / (tf + self.k1 * norm);
+            }
+        }
+
+        let mut ranked = Vec::with_capacity(scores.len());
+        for (id, score) in scores {
+            if threshold.is_some_and(|threshold| score < threshold) {
+                continue;
+            }
+            let Some(indexed) = self.documents.get(id) else {
+                continue;
+            };
+            if let Some(filter) = filter
+                && !filter.satisfies(&serde_json::to_value(&indexed.document)?)
+            {
+                continue;
+            }
+            ranked.push((score, id, &indexed.document));
+        }
+
+        ranked.sort_by(|(a_score, a_id, _), (b_score, b_id, _)| {
+            b_score.total_cmp(a_score).then_with(|| a_id.cmp(b_id))
+        });
+        ranked.truncate(n);
+        Ok(ranked)
+    }
+}
+
+impl<D: Serialize + Clone> Bm25Index<D> {
+    /// Build an index over every document in an [`InMemoryVectorStore`],
+    /// keyed by the same ids and indexing the text each embedding was computed
+    /// from.
+    pub fn from_store(store: &InMemoryVectorStore<D>) -> Self {
+        let mut index = Self::new();
+        for (id, (document, embeddings)) in store.iter() {
+            let text = embeddings
+                .iter()
+                .map(|embedding| embedding.document.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            index.insert(id, document.clone(), &text);
+        }
+        index
+    }
+}
+
+impl<D: Serialize + Send + Sync> VectorStoreIndex for Bm25Index<D> {
+    type Filter = Filter<serde_json::Value>;
+
+    async fn top_n<T: DeserializeOwned>(
+        &self,
+        req: VectorSearchRequest,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.search(
+            req.query(),
+            req.samples() as usize,
+            req.filter().as_ref(),
+            req.threshold(),
+        )?
+        .into_iter()
+        .map(|(score, id, document)| {
+            Ok((
+                score,
+                id.clone(),
+                serde_json::from_value(serde_json::to_value(document)?)?,
+            ))
+        })
+        .collect()
+    }
+
+    async fn top_n_ids(
+        &self,
+        req: VectorSearchRequest,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        Ok(self
+            .search(
+                req.query(),
+                req.samples() as usize,
+                req.filter().as_ref(),
+                req.threshold(),
+            )?
+            .into_iter()
+            .map(|(score, id, _)| (score, id.clone()))
+            .collect())
+    }
+}
+
+/// Lowercase `text` and split it into terms. Compound tokens joined by `-` or
+/// `_` are yielded whole, followed by their parts.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .map(|token| token.trim_matches(|c| c == '-' || c == '_'))
+        .filter(|token| !token.is_empty())
+        .flat_map(|token| {
+            let token = token.to_lowercase();
+            let parts = if token.contains(['-', '_']) {
+                token
+                    .split(['-', '_'])
+                    .filter(|part| !part.is_empty())
+                    .map(str::to_owned)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            std::iter::once(token).chain(parts)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{Bm25Index, tokenize};
+    use crate::vector_store::{
+        VectorSearchRequest, VectorStoreIndex,
+        request::{Filter, SearchFilter},
+    };
+
+    fn catalog() -> Bm25Index<Value> {
+        let mut index = Bm25Index::new();
+        index.insert(
+            "manual",
+            json!({"kind": "manual"}),
+            "Replacement filter for the espresso machine, part SKU-4711.",
+        );
+        index.insert(
+            "blog",
+            json!({"kind": "blog"}),
+            "Ten tips for a better espresso: grind, tamp, and clean the machine filter often.",
+        );
+        index.insert(
+            "faq",
+            json!({"kind": "faq"}),
+            "Shipping takes three days. Returns are accepted within thirty days.",
+        );
+        index
+    }
+
+    #[test]
+    fn tokenizer_keeps_compound_identifiers_and_their_parts() {
+        assert_eq!(
+            tokenize("Order SKU-4711, not snake_case!").collect::<Vec<_>>(),
+            vec![
+                "order",
+                "sku-4711",
+                "sku",
+                "4711",
+                "not",
+                "snake_case",
+                "snake",
+                "case"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn exact_identifier_ranks_its_document_first() {
+        let index = catalog();
+        let req = VectorSearchRequest::builder()
+            .query("sku-4711")
+            .samples(3)
+            .build();
+
+        let results = index.top_n_ids(req).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1, "manual");
+    }
+
+    #[tokio::test]
+    async fn rare_terms_outweigh_common_ones() {
+        let index = catalog();
+        let req = VectorSearchRequest::builder()
+            .query("espresso machine tips")
+            .samples(3)
+            .build();
+
+        let results = index.top_n_ids(req).await.unwrap();
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|(_, id)| id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["blog", "manual"]
+        );
+        assert!(results[0].0 > results[1].0);
+    }
+
+    #[tokio::test]
+    async fn filter_and_threshold_prune_results() {
+        let index = catalog();
+        let req = VectorSearchRequest::builder()
+            .query("espresso filter")
+            .samples(3)
+            .filter(Filter::eq("kind", json!("blog")))
+            .build();
+
+        let results = index.top_n::<Value>(req).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1, "blog");
+        assert_eq!(results[0].2, json!({"kind": "blog"}));
+
+        let req = VectorSearchRequest::builder()
+            .query("espresso filter")
+            .samples(3)
+            .threshold(f64::MAX)
+            .build();
+        assert!(index.top_n_ids(req).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replaced_and_deleted_documents_leave_no_postings() {
+        let mut index = catalog();
+        index.insert("manual", json!({"kind": "manual"}), "Descaling guide.");
+        assert_eq!(index.delete_documents(["faq", "missing"]), 1);
+        assert_eq!(index.len(), 2);
+
+        let req = VectorSearchRequest::builder()
+            .query("sku-4711 shipping")
+            .samples(3)
+            .build();
+        assert!(index.top_n_ids(req).await.unwrap().is_empty());
+        assert!(!index.postings.contains_key("sku-4711"));
+        assert!(!index.postings.contains_key("shipping"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Hybrid retrieval by reciprocal rank fusion.
+//!
+//! [`HybridIndex`] queries a lexical index (such as [`Bm25Index`](super::bm25::Bm25Index))
+//! and an embedding index with the same request and merges the two rankings
+//! with weighted reciprocal rank fusion (RRF). Each document scores
+//! `Σ weight / (k + rank)` over the rankings it appears in, with ranks starting
+//! at 1. Only ranks are used, so the raw BM25 and similarity scores never have
+//! to be put on a common scale.
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::vector_store::{
+    VectorSearchRequest, VectorStoreError, VectorStoreIndex, VectorStoreIndexDyn, request::Filter,
+};
+
+/// Default RRF rank constant, as proposed by Cormack et al.
+pub const DEFAULT_RANK_CONSTANT: f64 = 60.0;
+
+/// A [`VectorStoreIndex`] fusing a lexical and a vector ranking with
+/// reciprocal rank fusion.
+///
+/// Both inner indexes receive the request's query and filter. The request
+/// threshold applies to the fused score rather than to either inner score,
+/// and the returned scores are fused scores.
+///
+/// # Example
+/// ```rust,ignore
+/// let keywords = Bm25Index::from_store(&store);
+/// let vectors = store.index(embedding_model);
+///
+/// let hybrid = HybridIndex::new(keywords, vectors)
+///     .lexical_weight(0.5)
+///     .candidates(20);
+///
+/// let agent = client.agent(model).dynamic_context(3, hybrid).build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct HybridIndex<L, V> {
+    lexical: L,
+    vector: V,
+    lexical_weight: f64,
+    vector_weight: f64,
+    rank_constant: f64,
+    candidates: u64,
+}
+
+impl<L, V> HybridIndex<L, V>
+where
+    L: VectorStoreIndexDyn,
+    V: VectorStoreIndexDyn,
+{
+    /// Fuse `lexical` and `vector` with equal weights and the default rank
+    /// constant.
+    pub fn new(lexical: L, vector: V) -> Self {
+        Self {
+            lexical,
+            vector,
+            lexical_weight: 1.0,
+            vector_weight: 1.0,
+            rank_constant: DEFAULT_RANK_CONSTANT,
+            candidates: 0,
+        }
+    }
+
+    /// Weight of the lexical ranking. Defaults to `1.0`.
+    pub fn lexical_weight(mut self, weight: f64) -> Self {
+        self.lexical_weight = weight;
+        self
+    }
+
+    /// Weight of the vector ranking. Defaults to `1.0`.
+    pub fn vector_weight(mut self, weight: f64) -> Self {
+        self.vector_weight = weight;
+        self
+    }
+
+    /// RRF rank constant `k`. Larger values flatten the difference between
+    /// top and lower ranks. Defaults to [`DEFAULT_RANK_CONSTANT`].
+    pub fn rank_constant(mut self, k: f64) -> Self {
+        self.rank_constant = k;
+        self
+    }
+
+    /// Number of results fetched from each inner index before fusion. Values
+    /// below the request's `samples` are raised to it. A deeper pool lets a
+    /// document ranked moderately by both sides outrank one ranked highly by
+    /// only one of them.
+    pub fn candidates(mut self, candidates: u64) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    fn inner_request(
+        &self,
+        req: &VectorSearchRequest<Filter<Value>>,
+    ) -> VectorSearchRequest<Filter<Value>> {
+        let builder = VectorSearchRequest::builder()
+            .query(req.query())
+            .samples(self.candidates.max(req.samples()));
+
+        match req.filter() {
+            Some(filter) => builder.filter(filter.clone()).build(),
+            None => builder.build(),
+        }
+    }
+
+    fn fuse<'a>(
+        &self,
+        req: &VectorSearchRequest<Filter<Value>>,
+        lexical: impl IntoIterator<Item = &'a String>,
+        vector: impl IntoIterator<Item = &'a String>,
+    ) -> Vec<(f64, String)> {
+        let mut scores = HashMap::<&String, f64>::new();
+        for (weight, ranking) in [
+            (self.lexical_weight, lexical.into_iter().collect::<Vec<_>>()),
+            (self.vector_weight, vector.into_iter().collect()),
+        ] {
+            for (rank, id) in ranking.into_iter().enumerate() {
+                *scores.entry(id).or_default() += weight / (self.rank_constant + rank as f64 + 1.0);
+            }
+        }
+
+        let mut fused = scores
+            .into_iter()
+            .filter(|(_, score)| req.threshold().is_none_or(|threshold| *score >= threshold))
+            .map(|(id, score)| (score, id.clone()))
+            .collect::<Vec<_>>();
+        fused.sort_by(|(a_score, a_id), (b_score, b_id)| {
+            b_score.total_cmp(a_score).then_with(|| a_id.cmp(b_id))
+        });
+        fused.truncate(req.samples() as usize);
+        fused
+    }
+}
+
+impl<L, V> VectorStoreIndex for HybridIndex<L, V>
+where
+    L: VectorStoreIndexDyn,
+    V: VectorStoreIndexDyn,
+{
+    type Filter = Filter<Value>;
+
+    async fn top_n<T: DeserializeOwned>(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        let (lexical, vector) = futures::future::try_join(
+            VectorStoreIndexDyn::top_n(&self.lexical, self.inner_request(&req)),
+            VectorStoreIndexDyn::top_n(&self.vector, self.inner_request(&req)),
+        )
+        .await?;
+
+        let mut documents = HashMap::new();
+        for (_, id, document) in lexical.iter().chain(&vector) {
+            documents.entry(id).or_insert(document);
+        }
+
+        self.fuse(
+            &req,
+            lexical.iter().map(|(_, id, _)| id),
+            vector.iter().map(|(_, id, _)| id),
+        )
+        .into_iter()
+        .filter_map(|(score, id)| {
+            let document = documents.get(&id)?;
+            Some(serde_json::from_value((*document).clone()).map(|document| (score, id, document)))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(VectorStoreError::JsonError)
+    }
+
+    async fn top_n_ids(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        let (lexical, vector) = futures::future::try_join(
+            VectorStoreIndexDyn::top_n_ids(&self.lexical, self.inner_request(&req)),
+            VectorStoreIndexDyn::top_n_ids(&self.vector, self.inner_request(&req)),
+        )
+        .await?;
+
+        Ok(self.fuse(
+            &req,
+            lexical.iter().map(|(_, id)| id),
+            vector.iter().map(|(_, id)| id),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::HybridIndex;
+    use crate::vector_store::{
+        VectorSearchRequest, VectorStoreError, VectorStoreIndex,
+        bm25::Bm25Index,
+        request::{Filter, SearchFilter},
+    };
+
+    /// A vector index returning a fixed ranking, ignoring the query.
+    struct FixedRanking(Vec<&'static str>);
+
+    impl VectorStoreIndex for FixedRanking {
+        type Filter = Filter<Value>;
+
+        async fn top_n<T: serde::de::DeserializeOwned>(
+            &self,
+            req: VectorSearchRequest<Self::Filter>,
+        ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+            self.top_n_ids(req)
+                .await?
+                .into_iter()
+                .map(|(score, id)| {
+                    let document = serde_json::from_value(json!({ "id": id }))?;
+                    Ok((score, id, document))
+                })
+                .collect()
+        }
+
+        async fn top_n_ids(
+            &self,
+            req: VectorSearchRequest<Self::Filter>,
+        ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+            Ok(self
+                .0
+                .iter()
+                .take(req.samples() as usize)
+                .map(|id| (1.0, id.to_string()))
+                .collect())
+        }
+    }
+
+    fn keywords() -> Bm25Index<Value> {
+        let mut index = Bm25Index::new();
+        index.insert("a", json!({"id": "a"}), "error E1042 when syncing");
+        index.insert("b", json!({"id": "b"}), "sync fails with a timeout");
+        index.insert("c", json!({"id": "c"}), "general troubleshooting guide");
+        index
+    }
+
+    fn request(samples: u64) -> VectorSearchRequest {
+        VectorSearchRequest::builder()
+            .query("E1042")
+            .samples(samples)
+            .build()
+    }
+
+    fn ids(results: &[(f64, String)]) -> Vec<&str> {
+        results.iter().map(|(_, id)| id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn documents_found_by_both_rankings_rise_to_the_top() {
+        let hybrid = HybridIndex::new(keywords(), FixedRanking(vec!["b", "a", "c"]));
+
+        let results = hybrid.top_n_ids(request(3)).await.unwrap();
+
+        // "a" is first lexically and second by vector.
+        assert_eq!(ids(&results), vec!["a", "b", "c"]);
+        let expected = 1.0 / 61.0 + 1.0 / 62.0;
+        assert!((results[0].0 - expected).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn weights_shift_the_fused_order() {
+        let hybrid = HybridIndex::new(keywords(), FixedRanking(vec!["b", "c", "a"]))
+            .lexical_weight(0.1)
+            .rank_constant(1.0);
+
+        let results = hybrid.top_n_ids(request(2)).await.unwrap();
+
+        assert_eq!(ids(&results), vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn candidates_widen_the_pool_beyond_samples() {
+        // "b" is second in both rankings, which only counts once both lists
+        // are deeper than the single requested sample.
+        let req = || {
+            VectorSearchRequest::builder()
+                .query("error sync")
+                .samples(1)
+                .build()
+        };
+
+        let shallow = HybridIndex::new(keywords(), FixedRanking(vec!["c", "b"]));
+        let results = shallow.top_n_ids(req()).await.unwrap();
+        assert_eq!(ids(&results), vec!["a"]);
+
+        let deep = HybridIndex::new(keywords(), FixedRanking(vec!["c", "b"])).candidates(2);
+        let results = deep.top_n_ids(req()).await.unwrap();
+        assert_eq!(ids(&results), vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn filter_and_threshold_apply_to_fused_results() {
+        let hybrid = HybridIndex::new(keywords(), FixedRanking(vec!["a"])).vector_weight(0.5);
+        let req = VectorSearchRequest::builder()
+            .query("sync")
+            .samples(3)
+            .filter(Filter::eq("id", json!("b")))
+            .build();
+
+        let results = hybrid.top_n::<Value>(req).await.unwrap();
+
+        // The lexical side honours the filter; the fixed ranking does not.
+        assert_eq!(
+            results
+                .iter()
+                .map(|(_, id, doc)| (id.as_str(), doc.clone()))
+                .collect::<Vec<_>>(),
+            vec![("b", json!({"id": "b"})), ("a", json!({"id": "a"}))]
+        );
+
+        let req = VectorSearchRequest::builder()
+            .query("sync")
+            .samples(3)
+            .threshold(1.5 / 61.0)
+            .build();
+        assert!(hybrid.top_n_ids(req).await.unwrap().is_empty());
+    }
+}
@@ -111,13 +111,43 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
     }
 
     /// Insert a single document, keeping the LSH index (when enabled) in sync.
+    ///
+    /// A document already stored under `id` is replaced, and its embeddings
+    /// are dropped from the LSH index so they can no longer surface as
+    /// candidates.
     fn insert_document(&mut self, id: String, doc: D, embeddings: Vec<Embedding>) {
+        self.remove_document(&id);
         if let Some(ref mut lsh_index) = self.lsh_index {
             for embedding in embeddings.iter() {
                 lsh_index.insert(&id, &embedding.vec);
             }
         }
         self.embeddings.insert(id, (doc, embeddings));
+
+        // A store built empty has no dimension to size the LSH index with, so
+        // the index is created once the first document arrives.
+        if let IndexStrategy::LSH {
+            num_tables,
+            num_hyperplanes,
+        } = self.index_strategy
+            && self.lsh_index.is_none()
+        {
+            self.initialize_lsh_index(num_tables, num_hyperplanes);
+        }
+    }
+
+    /// Remove a single document and its LSH index entries, returning whether
+    /// it was stored.
+    fn remove_document(&mut self, id: &str) -> bool {
+        let Some((_, embeddings)) = self.embeddings.remove(id) else {
+            return false;
+        };
+        if let Some(ref mut lsh_index) = self.lsh_index {
+            for embedding in embeddings.iter() {
+                lsh_index.remove(id, &embedding.vec);
+            }
+        }
+        true
     }
 
     /// Tests whether a document satisfies the (optional) metadata filter.
@@ -314,14 +344,24 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
     /// Add documents and their corresponding embeddings to the store.
     /// Ids are automatically generated have will have the form `"doc{n}"` where `n`
     /// is the index of the document.
+    ///
+    /// Ids already in use, for example after earlier documents were deleted,
+    /// are skipped rather than overwritten.
     pub fn add_documents(&mut self, documents: impl IntoIterator<Item = (D, Vec<Embedding>)>) {
-        let current_index = self.embeddings.len();
-        for (index, (doc, embeddings)) in documents.into_iter().enumerate() {
-            self.insert_document(format!("doc{}", index + current_index), doc, embeddings);
+        let mut next_index = self.embeddings.len();
+        for (doc, embeddings) in documents {
+            let mut id = format!("doc{next_index}");
+            while self.embeddings.contains_key(&id) {
+                next_index += 1;
+                id = format!("doc{next_index}");
+            }
+            next_index += 1;
+            self.insert_document(id, doc, embeddings);
         }
     }
 
     /// Add documents and their corresponding embeddings to the store with ids.
+    /// A document already stored under one of the ids is replaced.
     pub fn add_documents_with_ids(
         &mut self,
         documents: impl IntoIterator<Item = (impl ToString, D, Vec<Embedding>)>,
@@ -342,6 +382,48 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
             self.insert_document(f(&doc), doc, embeddings);
         }
     }
+
+    /// Insert or replace documents under the given ids. A replaced document
+    /// loses all of its previous embeddings, including in the LSH index.
+    ///
+    /// This is the in-memory counterpart of
+    /// [`UpsertDocuments`](super::UpsertDocuments); the store is owned rather
+    /// than shared, so it is mutated through `&mut self` like
+    /// [`InMemoryVectorStore::add_documents`].
+    pub fn upsert_documents(
+        &mut self,
+        documents: impl IntoIterator<Item = (impl ToString, D, Vec<Embedding>)>,
+    ) {
+        self.add_documents_with_ids(documents);
+    }
+
+    /// Delete the documents with the given ids, returning how many were
+    /// stored. Unknown ids are ignored.
+    ///
+    /// This is the in-memory counterpart of
+    /// [`DeleteDocuments::delete_documents`](super::DeleteDocuments::delete_documents).
+    pub fn delete_documents(&mut self, ids: impl IntoIterator<Item = impl AsRef<str>>) -> usize {
+        ids.into_iter()
+            .filter(|id| self.remove_document(id.as_ref()))
+            .count()
+    }
+
+    /// Delete every document matching `filter`, returning how many were
+    /// removed. Documents are matched exactly as [`VectorStoreIndex::top_n`]
+    /// matches them, via [`Filter::satisfies`].
+    pub fn delete_documents_where(
+        &mut self,
+        filter: &Filter<serde_json::Value>,
+    ) -> Result<usize, VectorStoreError> {
+        let mut matching = Vec::new();
+        for (id, (doc, _)) in self.embeddings.iter() {
+            if Self::satisfies_filter(doc, Some(filter))? {
+                matching.push(id.clone());
+            }
+        }
+
+        Ok(self.delete_documents(matching))
+    }
 }
 
 /// RankingItem(distance, document_id, serializable document, embeddings document)
@@ -477,6 +559,7 @@ mod tests {
     use crate::{embeddings::embedding::Embedding, vector_store::IndexStrategy};
 
     use super::{InMemoryVectorStore, RankingItem};
+    use crate::vector_store::request::{Filter, SearchFilter};
 
     #[test]
     fn test_auto_ids() {
@@ -911,4 +994,120 @@ mod tests {
         assert_eq!(results[0].1, "mixed");
         assert!(results[0].0.is_finite());
     }
+
+    fn lsh_store() -> InMemoryVectorStore<serde_json::Value> {
+        InMemoryVectorStore::builder()
+            .index_strategy(IndexStrategy::LSH {
+                num_tables: 5,
+                num_hyperplanes: 10,
+            })
+            .documents_with_ids(vec![
+                (
+                    "doc1",
+                    serde_json::json!({"kind": "draft"}),
+                    vec![Embedding {
+                        document: "glarb-garb".to_string(),
+                        vec: vec![0.1, 0.1, 0.5],
+                    }],
+                ),
+                (
+                    "doc2",
+                    serde_json::json!({"kind": "final"}),
+                    vec![Embedding {
+                        document: "marble-marble".to_string(),
+                        vec: vec![0.7, -0.3, 0.0],
+                    }],
+                ),
+            ])
+            .build()
+    }
+
+    fn lsh_candidates(store: &InMemoryVectorStore<serde_json::Value>, vec: &[f64]) -> Vec<String> {
+        let mut candidates = store.lsh_index.as_ref().unwrap().query(vec);
+        candidates.sort();
+        candidates
+    }
+
+    #[test]
+    fn upsert_replaces_every_embedding_in_the_lsh_index() {
+        let mut store = lsh_store();
+        assert_eq!(lsh_candidates(&store, &[0.1, 0.1, 0.5]), vec!["doc1"]);
+
+        // The opposite vector lands on the other side of every hyperplane, so
+        // it can only be a candidate for the old one if the old entry leaked.
+        store.upsert_documents(vec![(
+            "doc1",
+            serde_json::json!({"kind": "final"}),
+            vec![Embedding {
+                document: "glarb-garb".to_string(),
+                vec: vec![-0.1, -0.1, -0.5],
+            }],
+        )]);
+
+        assert_eq!(store.len(), 2);
+        assert!(lsh_candidates(&store, &[0.1, 0.1, 0.5]).is_empty());
+        assert_eq!(lsh_candidates(&store, &[-0.1, -0.1, -0.5]), vec!["doc1"]);
+    }
+
+    #[test]
+    fn delete_by_id_and_filter_drops_lsh_entries() {
+        let mut store = lsh_store();
+
+        assert_eq!(store.delete_documents(["doc1", "missing"]), 1);
+        assert!(lsh_candidates(&store, &[0.1, 0.1, 0.5]).is_empty());
+
+        let filter = Filter::eq("kind", serde_json::json!("final"));
+        assert_eq!(store.delete_documents_where(&filter).unwrap(), 1);
+        assert!(store.is_empty());
+        assert!(lsh_candidates(&store, &[0.7, -0.3, 0.0]).is_empty());
+    }
+
+    #[test]
+    fn add_documents_skips_ids_still_in_use_after_delete() {
+        let mut store = lsh_store();
+        store.add_documents(vec![(
+            serde_json::json!({"kind": "draft"}),
+            vec![Embedding {
+                document: "flumb-flumb".to_string(),
+                vec: vec![0.3, 0.7, 0.1],
+            }],
+        )]);
+        store.delete_documents(["doc1"]);
+
+        // Two documents remain, so the next automatic id would be `doc2`,
+        // which is still taken.
+        store.add_documents(vec![(
+            serde_json::json!({"kind": "draft"}),
+            vec![Embedding {
+                document: "brotato".to_string(),
+                vec: vec![0.3, 0.7, 0.1],
+            }],
+        )]);
+
+        let mut ids = store.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec!["doc2", "doc3", "doc4"]);
+    }
+
+    #[test]
+    fn lsh_index_is_created_once_an_empty_store_gets_documents() {
+        let mut store = InMemoryVectorStore::<serde_json::Value>::builder()
+            .index_strategy(IndexStrategy::LSH {
+                num_tables: 5,
+                num_hyperplanes: 10,
+            })
+            .build();
+        assert!(store.lsh_index.is_none());
+
+        store.upsert_documents(vec![(
+            "doc1",
+            serde_json::json!({"kind": "draft"}),
+            vec![Embedding {
+                document: "glarb-garb".to_string(),
+                vec: vec![0.1, 0.1, 0.5],
+            }],
+        )]);
+
+        assert_eq!(lsh_candidates(&store, &[0.1, 0.1, 0.5]), vec!["doc1"]);
+    }
 }
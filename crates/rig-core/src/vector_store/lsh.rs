@@ -115,6 +115,24 @@ impl LSHIndex {
         }
     }
 
+    /// Remove a document ID from the buckets its embedding hashes to.
+    ///
+    /// Call once per embedding that was inserted for the ID; removing an
+    /// embedding that was never inserted is a no-op.
+    pub fn remove(&mut self, id: &str, embedding: &[f64]) {
+        for table_idx in 0..self.lsh.num_tables {
+            let hash = self.lsh.hash(embedding, table_idx);
+            if let Some(table) = self.tables.get_mut(table_idx)
+                && let Some(ids) = table.get_mut(&hash)
+            {
+                ids.retain(|existing| existing != id);
+                if ids.is_empty() {
+                    table.remove(&hash);
+                }
+            }
+        }
+    }
+
     /// Query for candidate document IDs
     pub fn query(&self, embedding: &[f64]) -> Vec<String> {
         use std::collections::HashSet;
@@ -4,10 +4,17 @@
 //!
 //! - [`VectorStoreIndex`]: Query a vector store for similar documents.
 //! - [`InsertDocuments`]: Insert documents and their embeddings.
+//! - [`UpsertDocuments`]: Insert or replace documents under explicit ids.
+//! - [`DeleteDocuments`]: Delete documents by id or by filter.
 //! - [`VectorStoreIndexDyn`]: Type-erased vector queries for runtime-defined retrieval policies.
 //!
 //! Use [`VectorSearchRequest`] to build queries. See [`request`] for filtering.
 //!
+//! [`bm25::Bm25Index`] provides in-memory keyword search, and
+//! [`hybrid::HybridIndex`] fuses a keyword ranking with any vector index.
+//! [`reranking::RerankingIndex`] reorders any index's results with a rerank
+//! model.
+//!
 //! Types implementing [`VectorStoreIndex`] automatically implement [`PortableTool`].
 
 use http::StatusCode;
@@ -18,15 +25,19 @@ use serde_json::{Value, json};
 use crate::{
     Embed,
     embeddings::{Embedding, EmbeddingError},
+    rerank::RerankError,
     tool::PortableTool,
     vector_store::request::{DynamicSearchFilter, Filter, FilterError, SearchFilter},
     wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync},
 };
 
+pub mod bm25;
 pub mod builder;
+pub mod hybrid;
 pub mod in_memory_store;
 pub mod lsh;
 pub mod request;
+pub mod reranking;
 
 /// Errors from vector store operations.
 #[derive(Debug, thiserror::Error)]
@@ -35,6 +46,10 @@ pub enum VectorStoreError {
     #[error("Embedding error: {0}")]
     EmbeddingError(#[from] EmbeddingError),
 
+    /// Reranking retrieved candidates failed.
+    #[error("Rerank error: {0}")]
+    RerankError(#[from] RerankError),
+
     /// JSON serialization or deserialization failed.
     #[error("Json error: {0}")]
     JsonError(#[from] serde_json::Error),
@@ -130,6 +145,45 @@ pub trait InsertDocuments: WasmCompatSend + WasmCompatSync {
     ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + WasmCompatSend;
 }
 
+/// Trait for removing documents, together with all of their embeddings, from
+/// a vector store.
+pub trait DeleteDocuments: WasmCompatSend + WasmCompatSync {
+    /// The filter type accepted by [`DeleteDocuments::delete_documents_where`].
+    /// Implementors that also implement [`VectorStoreIndex`] use the same type,
+    /// so a filter that selects documents for search selects the same
+    /// documents for deletion.
+    type Filter: SearchFilter + WasmCompatSend + WasmCompatSync;
+
+    /// Delete the documents with the given ids. Ids that are not stored are
+    /// ignored.
+    fn delete_documents(
+        &self,
+        ids: Vec<String>,
+    ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + WasmCompatSend;
+
+    /// Delete every document matching `filter`.
+    fn delete_documents_where(
+        &self,
+        filter: Self::Filter,
+    ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + WasmCompatSend;
+}
+
+/// Trait for writing documents under caller-chosen ids, replacing any
+/// document already stored under the same id.
+pub trait UpsertDocuments: WasmCompatSend + WasmCompatSync {
+    /// Store each `(id, document, embeddings)` triple. A document already
+    /// stored under `id` is replaced together with *all* of its embeddings,
+    /// so re-embedding a document with fewer chunks leaves no stale vectors
+    /// behind.
+    ///
+    /// As with [`InsertDocuments::insert_documents`], every document must
+    /// carry at least one embedding.
+    fn upsert_documents<Doc: Serialize + Embed + WasmCompatSend>(
+        &self,
+        documents: Vec<(String, Doc, Vec<Embedding>)>,
+    ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + WasmCompatSend;
+}
+
 /// Trait for querying a vector store by similarity.
 pub trait VectorStoreIndex: WasmCompatSend + WasmCompatSync {
     /// The filter type for this backend.
@@ -303,10 +357,34 @@ mod tests {
             Self
         }
 
+        fn ne(_key: impl AsRef<str>, _value: Self::Value) -> Self {
+            Self
+        }
+
+        fn gte(_key: impl AsRef<str>, _value: Self::Value) -> Self {
+            Self
+        }
+
         fn lt(_key: impl AsRef<str>, _value: Self::Value) -> Self {
             Self
         }
 
+        fn lte(_key: impl AsRef<str>, _value: Self::Value) -> Self {
+            Self
+        }
+
+        fn in_values(_key: impl AsRef<str>, _values: Vec<Self::Value>) -> Self {
+            Self
+        }
+
+        fn exists(_key: impl AsRef<str>) -> Self {
+            Self
+        }
+
+        fn contains(_key: impl AsRef<str>, _value: Self::Value) -> Self {
+            Self
+        }
+
         fn and(self, _rhs: Self) -> Self {
             self
         }
@@ -314,6 +392,10 @@ mod tests {
         fn or(self, _rhs: Self) -> Self {
             self
         }
+
+        fn not(self) -> Self {
+            self
+        }
     }
 
     impl DynamicSearchFilter for NativeFilter {
@@ -118,10 +118,65 @@ pub trait SearchFilter {
     type Value;
 
     fn eq(key: impl AsRef<str>, value: Self::Value) -> Self;
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self;
     fn gt(key: impl AsRef<str>, value: Self::Value) -> Self;
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self;
     fn lt(key: impl AsRef<str>, value: Self::Value) -> Self;
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self;
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self;
+    fn exists(key: impl AsRef<str>) -> Self;
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self;
     fn and(self, rhs: Self) -> Self;
     fn or(self, rhs: Self) -> Self;
+    fn not(self) -> Self;
+
+    /// Whether this backend can express `op` natively.
+    ///
+    /// Backends whose query language lacks an operator return `false` for it,
+    /// so that translating a dynamic [`Filter`] using that operator fails with
+    /// [`FilterError::TypeError`] instead of producing a query the backend
+    /// rejects or silently misinterprets.
+    fn supports(_op: FilterOperator) -> bool {
+        true
+    }
+}
+
+/// The operators of the [`Filter`] algebra, used by [`SearchFilter::supports`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Exists,
+    Contains,
+    And,
+    Or,
+    Not,
+}
+
+impl std::fmt::Display for FilterOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Eq => "eq",
+            Self::Ne => "ne",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+            Self::In => "in",
+            Self::Exists => "exists",
+            Self::Contains => "contains",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Not => "not",
+        };
+
+        f.write_str(name)
+    }
 }
 
 /// A rendered SQL-style condition together with its positional bind parameters.
@@ -247,6 +302,7 @@ where
     F: SearchFilter<Value = serde_json::Value>,
 {
     fn from_dynamic_filter(filter: Filter<serde_json::Value>) -> Result<Self, FilterError> {
+        filter.ensure_supported::<F>()?;
         Ok(filter.interpret())
     }
 
@@ -286,10 +342,17 @@ where
     V: std::fmt::Debug + Clone,
 {
     Eq(String, V),
+    Ne(String, V),
     Gt(String, V),
+    Gte(String, V),
     Lt(String, V),
+    Lte(String, V),
+    In(String, Vec<V>),
+    Exists(String),
+    Contains(String, V),
     And(Box<Self>, Box<Self>),
     Or(Box<Self>, Box<Self>),
+    Not(Box<Self>),
 }
 
 impl<V> SearchFilter for Filter<V>
@@ -303,16 +366,46 @@ where
         Self::Eq(key.as_ref().to_owned(), value)
     }
 
+    /// Select values where the entry at `key` is present and not equal to `value`
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::Ne(key.as_ref().to_owned(), value)
+    }
+
     /// Select values where the entry at `key` is greater than `value`
     fn gt(key: impl AsRef<str>, value: Self::Value) -> Self {
         Self::Gt(key.as_ref().to_owned(), value)
     }
 
+    /// Select values where the entry at `key` is greater than or equal to `value`
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::Gte(key.as_ref().to_owned(), value)
+    }
+
     /// Select values where the entry at `key` is less than `value`
     fn lt(key: impl AsRef<str>, value: Self::Value) -> Self {
         Self::Lt(key.as_ref().to_owned(), value)
     }
 
+    /// Select values where the entry at `key` is less than or equal to `value`
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::Lte(key.as_ref().to_owned(), value)
+    }
+
+    /// Select values where the entry at `key` is equal to one of `values`
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::In(key.as_ref().to_owned(), values)
+    }
+
+    /// Select values where the entry at `key` is present and not null
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self::Exists(key.as_ref().to_owned())
+    }
+
+    /// Select values where the entry at `key` is an array containing `value`
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::Contains(key.as_ref().to_owned(), value)
+    }
+
     /// Select values where the entry satisfies `self` *and* `rhs`
     fn and(self, rhs: Self) -> Self {
         Self::And(self.into(), rhs.into())
@@ -322,6 +415,11 @@ where
     fn or(self, rhs: Self) -> Self {
         Self::Or(self.into(), rhs.into())
     }
+
+    /// Select values where the entry does *not* satisfy `self`
+    fn not(self) -> Self {
+        Self::Not(self.into())
+    }
 }
 
 impl<V> Filter<V>
@@ -356,21 +454,72 @@ where
     {
         Ok(match self {
             Self::Eq(key, val) => F::eq(key, conv(val)?),
+            Self::Ne(key, val) => F::ne(key, conv(val)?),
             Self::Gt(key, val) => F::gt(key, conv(val)?),
+            Self::Gte(key, val) => F::gte(key, conv(val)?),
             Self::Lt(key, val) => F::lt(key, conv(val)?),
+            Self::Lte(key, val) => F::lte(key, conv(val)?),
+            Self::In(key, vals) => F::in_values(
+                key,
+                vals.into_iter().map(conv).collect::<Result<Vec<_>, _>>()?,
+            ),
+            Self::Exists(key) => F::exists(key),
+            Self::Contains(key, val) => F::contains(key, conv(val)?),
             Self::And(lhs, rhs) => F::and(lhs.try_interpret(conv)?, rhs.try_interpret(conv)?),
             Self::Or(lhs, rhs) => F::or(lhs.try_interpret(conv)?, rhs.try_interpret(conv)?),
+            Self::Not(expr) => F::not(expr.try_interpret(conv)?),
         })
     }
+
+    /// The operator at the root of this filter.
+    pub fn operator(&self) -> FilterOperator {
+        match self {
+            Self::Eq(..) => FilterOperator::Eq,
+            Self::Ne(..) => FilterOperator::Ne,
+            Self::Gt(..) => FilterOperator::Gt,
+            Self::Gte(..) => FilterOperator::Gte,
+            Self::Lt(..) => FilterOperator::Lt,
+            Self::Lte(..) => FilterOperator::Lte,
+            Self::In(..) => FilterOperator::In,
+            Self::Exists(..) => FilterOperator::Exists,
+            Self::Contains(..) => FilterOperator::Contains,
+            Self::And(..) => FilterOperator::And,
+            Self::Or(..) => FilterOperator::Or,
+            Self::Not(..) => FilterOperator::Not,
+        }
+    }
+
+    /// Checks that every operator in this filter is supported by `F`,
+    /// returning [`FilterError::TypeError`] naming the first one that is not.
+    pub fn ensure_supported<F>(&self) -> Result<(), FilterError>
+    where
+        F: SearchFilter,
+    {
+        let op = self.operator();
+        if !F::supports(op) {
+            return Err(FilterError::TypeError(op.to_string()));
+        }
+
+        match self {
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) => {
+                lhs.ensure_supported::<F>()?;
+                rhs.ensure_supported::<F>()
+            }
+            Self::Not(expr) => expr.ensure_supported::<F>(),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Filter<serde_json::Value> {
     /// Tests whether a JSON document satisfies this filter.
     ///
-    /// Leaf filters (`Eq`/`Gt`/`Lt`) look their key up in `value` (expected to be
-    /// a JSON object) and compare the resulting field against the filter operand.
-    /// A missing field, or an operand that is not order-comparable with the field,
-    /// never satisfies the leaf. `And`/`Or` combine leaf results.
+    /// Leaf filters look their key up in `value` (expected to be a JSON object)
+    /// and compare the resulting field against the filter operand. A missing
+    /// field, or an operand that is not order-comparable with the field, never
+    /// satisfies a comparison leaf, including `Ne`. `Exists` requires the field
+    /// to be present and non-null, and `Contains` requires it to be an array with
+    /// an element equal to the operand. `And`/`Or`/`Not` combine leaf results.
     pub fn satisfies(&self, value: &serde_json::Value) -> bool {
         use Filter::*;
         use serde_json::{Value, Value::*};
@@ -399,23 +548,38 @@ impl Filter<serde_json::Value> {
             }
         }
 
-        match self {
-            // Numbers compare numerically so `5` matches `5.0`, consistent with
-            // `Gt`/`Lt`; other JSON types fall back to structural equality so
-            // strings/bools/arrays/objects still match exactly.
-            Eq(k, v) => value
-                .get(k)
-                .is_some_and(|field| compare_pair(field, v) == Some(Ordering::Equal) || field == v),
-            Gt(k, v) => value
+        // Numbers compare numerically so `5` matches `5.0`, consistent with
+        // `Gt`/`Lt`; other JSON types fall back to structural equality so
+        // strings/bools/arrays/objects still match exactly.
+        fn equals(l: &Value, r: &Value) -> bool {
+            compare_pair(l, r) == Some(Ordering::Equal) || l == r
+        }
+
+        let ordering = |k: &str, v: &Value, accept: fn(Ordering) -> bool| {
+            value
                 .get(k)
                 .and_then(|field| compare_pair(field, v))
-                .is_some_and(|ord| ord == Ordering::Greater),
-            Lt(k, v) => value
+                .is_some_and(accept)
+        };
+
+        match self {
+            Eq(k, v) => value.get(k).is_some_and(|field| equals(field, v)),
+            Ne(k, v) => value.get(k).is_some_and(|field| !equals(field, v)),
+            Gt(k, v) => ordering(k, v, Ordering::is_gt),
+            Gte(k, v) => ordering(k, v, Ordering::is_ge),
+            Lt(k, v) => ordering(k, v, Ordering::is_lt),
+            Lte(k, v) => ordering(k, v, Ordering::is_le),
+            In(k, vs) => value
                 .get(k)
-                .and_then(|field| compare_pair(field, v))
-                .is_some_and(|ord| ord == Ordering::Less),
+                .is_some_and(|field| vs.iter().any(|v| equals(field, v))),
+            Exists(k) => value.get(k).is_some_and(|field| !field.is_null()),
+            Contains(k, v) => value
+                .get(k)
+                .and_then(Value::as_array)
+                .is_some_and(|items| items.iter().any(|item| equals(item, v))),
             And(l, r) => l.satisfies(value) && r.satisfies(value),
             Or(l, r) => l.satisfies(value) || r.satisfies(value),
+            Not(expr) => !expr.satisfies(value),
         }
     }
 }
@@ -570,6 +734,121 @@ mod tests {
         assert!(either.satisfies(&doc));
     }
 
+    #[test]
+    fn inclusive_ordering_and_ne_compare_the_named_field() {
+        let doc = json!({ "price": 10, "text": "banana" });
+        assert!(F::gte("price", json!(10)).satisfies(&doc));
+        assert!(F::gte("price", json!(9.5)).satisfies(&doc));
+        assert!(!F::gte("price", json!(11)).satisfies(&doc));
+        assert!(F::lte("price", json!(10.0)).satisfies(&doc));
+        assert!(!F::lte("price", json!(9)).satisfies(&doc));
+
+        assert!(F::ne("text", json!("apple")).satisfies(&doc));
+        assert!(!F::ne("price", json!(10.0)).satisfies(&doc));
+        // Like the other comparisons, a missing field never satisfies `Ne`.
+        assert!(!F::ne("missing", json!("apple")).satisfies(&doc));
+    }
+
+    #[test]
+    fn in_exists_and_contains_inspect_the_named_field() {
+        let doc = json!({ "tag": "a", "tags": ["x", 2], "deleted_at": null });
+        assert!(F::in_values("tag", vec![json!("b"), json!("a")]).satisfies(&doc));
+        assert!(!F::in_values("tag", vec![json!("b")]).satisfies(&doc));
+        assert!(!F::in_values("tag", vec![]).satisfies(&doc));
+
+        assert!(F::exists("tag").satisfies(&doc));
+        assert!(!F::exists("deleted_at").satisfies(&doc));
+        assert!(!F::exists("missing").satisfies(&doc));
+
+        assert!(F::contains("tags", json!("x")).satisfies(&doc));
+        assert!(F::contains("tags", json!(2.0)).satisfies(&doc));
+        assert!(!F::contains("tags", json!("y")).satisfies(&doc));
+        // Only arrays contain values; a scalar field is not searched.
+        assert!(!F::contains("tag", json!("a")).satisfies(&doc));
+    }
+
+    #[test]
+    fn not_negates_the_wrapped_filter() {
+        let doc = json!({ "tag": "a", "archived": false });
+        let filter = F::in_values("tag", vec![json!("a"), json!("b")])
+            .and(F::eq("archived", json!(true)).not());
+        assert!(filter.satisfies(&doc));
+        assert!(!filter.satisfies(&json!({ "tag": "a", "archived": true })));
+        // `Not` of a leaf on a missing field matches.
+        assert!(F::eq("missing", json!(1)).not().satisfies(&doc));
+    }
+
+    #[test]
+    fn new_variants_round_trip_through_serde() {
+        let filter = F::ne("a", json!(1))
+            .and(F::in_values("b", vec![json!("x")]).or(F::exists("c").not()))
+            .and(F::contains("d", json!(true)).and(F::gte("e", json!(1))));
+        let encoded = serde_json::to_value(&filter).unwrap();
+        assert_eq!(encoded["and"][0]["and"][0]["ne"], json!(["a", 1]));
+        let decoded: F = serde_json::from_value(encoded.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), encoded);
+    }
+
+    #[test]
+    fn ensure_supported_rejects_unsupported_operators() {
+        use super::{FilterError, FilterOperator};
+
+        #[derive(Debug)]
+        struct NoNegation;
+
+        impl SearchFilter for NoNegation {
+            type Value = serde_json::Value;
+
+            fn eq(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn ne(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn gt(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn gte(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn lt(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn lte(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn in_values(_: impl AsRef<str>, _: Vec<Self::Value>) -> Self {
+                Self
+            }
+            fn exists(_: impl AsRef<str>) -> Self {
+                Self
+            }
+            fn contains(_: impl AsRef<str>, _: Self::Value) -> Self {
+                Self
+            }
+            fn and(self, _: Self) -> Self {
+                self
+            }
+            fn or(self, _: Self) -> Self {
+                self
+            }
+            fn not(self) -> Self {
+                self
+            }
+            fn supports(op: FilterOperator) -> bool {
+                op != FilterOperator::Not
+            }
+        }
+
+        let supported = F::eq("a", json!(1)).or(F::exists("b"));
+        assert!(supported.ensure_supported::<NoNegation>().is_ok());
+
+        let nested = F::eq("a", json!(1)).and(F::exists("b").not());
+        let err =
+            <NoNegation as super::DynamicSearchFilter>::from_dynamic_filter(nested).unwrap_err();
+        assert!(matches!(err, FilterError::TypeError(ref op) if op == "not"));
+    }
+
     #[test]
     fn try_interpret_converts_nested_leaf_values() {
         let f: Filter<i64> =
@@ -0,0 +1,295 @@
+//! Rerank stage for vector store retrieval.
+//!
+//! [`RerankingIndex`] over-fetches candidates from any [`VectorStoreIndexDyn`],
+//! scores them against the query with a [`RerankModelHandle`], and returns
+//! the best of them ordered by rerank score. Since it is itself a
+//! [`VectorStoreIndex`], it drops into `AgentBuilder::dynamic_context` like any
+//! other index.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    rerank::{RerankModel, RerankModelHandle},
+    vector_store::{
+        VectorSearchRequest, VectorStoreError, VectorStoreIndex, VectorStoreIndexDyn,
+        request::Filter,
+    },
+};
+
+/// Default number of candidates fetched from the inner index.
+pub const DEFAULT_CANDIDATES: u64 = 20;
+
+/// A [`VectorStoreIndex`] that reranks the results of an inner index.
+///
+/// Each query fetches `max(candidates, samples)` results from the inner
+/// index, capped at the model's [`RerankModel::max_documents`] so the whole
+/// pool is scored in one rerank call, and returns the top `samples` of them.
+/// The request's filter and threshold are forwarded to the inner index; the
+/// returned scores are the reranker's relevance scores, which are only
+/// meaningful for ordering (see [`RerankResult::relevance_score`]).
+///
+/// Documents are sent to the reranker as text: a JSON string document is sent
+/// as is, anything else as its JSON serialization, unless
+/// [`text_field`](Self::text_field) selects a field to send instead.
+///
+/// [`RerankResult::relevance_score`]: crate::rerank::RerankResult::relevance_score
+///
+/// # Example
+/// ```rust,ignore
+/// let index = RerankingIndex::new(store.index(embedding_model), reranker)
+///     .candidates(50)
+///     .text_field("content");
+///
+/// let agent = client.agent(model).dynamic_context(5, index).build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RerankingIndex<I> {
+    index: I,
+    model: RerankModelHandle,
+    candidates: u64,
+    text_field: Option<String>,
+}
+
+impl<I> RerankingIndex<I>
+where
+    I: VectorStoreIndexDyn,
+{
+    /// Rerank results of `index` with `model`, fetching
+    /// [`DEFAULT_CANDIDATES`] candidates per query.
+    pub fn new(index: I, model: RerankModelHandle) -> Self {
+        Self {
+            index,
+            model,
+            candidates: DEFAULT_CANDIDATES,
+            text_field: None,
+        }
+    }
+
+    /// Number of candidates fetched from the inner index per query.
+    pub fn candidates(mut self, candidates: u64) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    /// Send this top-level field of each document to the reranker instead of
+    /// the whole document. Documents without the field are sent whole.
+    pub fn text_field(mut self, field: impl Into<String>) -> Self {
+        self.text_field = Some(field.into());
+        self
+    }
+
+    fn document_text(&self, document: &Value) -> String {
+        let value = self
+            .text_field
+            .as_deref()
+            .and_then(|field| document.get(field))
+            .unwrap_or(document);
+
+        match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    async fn rerank(
+        &self,
+        req: &VectorSearchRequest<Filter<Value>>,
+    ) -> Result<Vec<(f64, String, Value)>, VectorStoreError> {
+        let samples = self
+            .candidates
+            .max(req.samples())
+            .min(self.model.max_documents() as u64);
+
+        let mut builder = VectorSearchRequest::builder()
+            .query(req.query())
+            .samples(samples);
+        if let Some(threshold) = req.threshold() {
+            builder = builder.threshold(threshold);
+        }
+        if let Some(filter) = req.filter() {
+            builder = builder.filter(filter.clone());
+        }
+
+        let mut candidates = VectorStoreIndexDyn::top_n(&self.index, builder.build())
+            .await?
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let texts = candidates
+            .iter()
+            .flatten()
+            .map(|(_, _, document)| self.document_text(document))
+            .collect();
+        let mut results = RerankModel::rerank(&self.model, req.query(), texts)
+            .await?
+            .results;
+        results.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+
+        // A provider may return fewer results than documents; indexes it
+        // repeats or invents are skipped.
+        Ok(results
+            .into_iter()
+            .filter_map(|result| {
+                let (_, id, document) = candidates.get_mut(result.index)?.take()?;
+                Some((result.relevance_score, id, document))
+            })
+            .take(req.samples() as usize)
+            .collect())
+    }
+}
+
+impl<I> VectorStoreIndex for RerankingIndex<I>
+where
+    I: VectorStoreIndexDyn,
+{
+    type Filter = Filter<Value>;
+
+    async fn top_n<T: DeserializeOwned>(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
+        self.rerank(&req)
+            .await?
+            .into_iter()
+            .map(|(score, id, document)| Ok((score, id, serde_json::from_value(document)?)))
+            .collect()
+    }
+
+    async fn top_n_ids(
+        &self,
+        req: VectorSearchRequest<Self::Filter>,
+    ) -> Result<Vec<(f64, String)>, VectorStoreError> {
+        Ok(self
+            .rerank(&req)
+            .await?
+            .into_iter()
+            .map(|(score, id, _)| (score, id))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{Value, json};
+
+    use super::RerankingIndex;
+    use crate::{
+        rerank::{RerankError, RerankModel, RerankModelHandle, RerankResponse, RerankResult},
+        vector_store::{VectorSearchRequest, VectorStoreIndex, bm25::Bm25Index},
+    };
+
+    /// Scores shorter documents higher and records every batch it receives.
+    #[derive(Clone, Default)]
+    struct ShortestFirst {
+        batches: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    impl RerankModel for ShortestFirst {
+        fn max_documents(&self) -> usize {
+            2
+        }
+
+        async fn rerank(
+            &self,
+            _query: &str,
+            documents: Vec<String>,
+        ) -> Result<RerankResponse, RerankError> {
+            self.batches.lock().unwrap().push(documents.clone());
+            Ok(RerankResponse::new(
+                documents
+                    .iter()
+                    .enumerate()
+                    .map(|(index, document)| RerankResult {
+                        index,
+                        document: None,
+                        relevance_score: -(document.len() as f64),
+                    })
+                    .collect(),
+                "probe",
+            ))
+        }
+    }
+
+    fn articles() -> Bm25Index<Value> {
+        let mut index = Bm25Index::new();
+        index.insert(
+            "long",
+            json!({"content": "pandas pandas pandas eat bamboo all day long, every day"}),
+            "pandas pandas pandas",
+        );
+        index.insert(
+            "short",
+            json!({"content": "pandas sleep"}),
+            "pandas and other bears",
+        );
+        index.insert(
+            "other",
+            json!({"content": "koalas"}),
+            "koalas and other marsupials",
+        );
+        index
+    }
+
+    #[tokio::test]
+    async fn results_are_reordered_by_rerank_score() {
+        let model = ShortestFirst::default();
+        let index = RerankingIndex::new(articles(), RerankModelHandle::new(model.clone()))
+            .text_field("content");
+        let req = VectorSearchRequest::builder()
+            .query("pandas")
+            .samples(1)
+            .build();
+
+        let results = index.top_n::<Value>(req).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1, "short");
+        assert_eq!(results[0].0, -12.0);
+        assert_eq!(results[0].2, json!({"content": "pandas sleep"}));
+        assert_eq!(
+            *model.batches.lock().unwrap(),
+            vec![vec![
+                "pandas pandas pandas eat bamboo all day long, every day".to_owned(),
+                "pandas sleep".to_owned(),
+            ]]
+        );
+    }
+
+    #[tokio::test]
+    async fn candidate_pool_is_capped_at_the_model_batch_size() {
+        let model = ShortestFirst::default();
+        let index = RerankingIndex::new(articles(), RerankModelHandle::new(model.clone()));
+        let req = VectorSearchRequest::builder()
+            .query("pandas other")
+            .samples(3)
+            .build();
+
+        let results = index.top_n_ids(req).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+        let batches = model.batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 2);
+        assert!(batches[0][0].starts_with('{'));
+    }
+
+    #[tokio::test]
+    async fn empty_retrieval_skips_the_reranker() {
+        let model = ShortestFirst::default();
+        let index = RerankingIndex::new(articles(), RerankModelHandle::new(model.clone()));
+        let req = VectorSearchRequest::builder()
+            .query("zebras")
+            .samples(3)
+            .build();
+
+        assert!(index.top_n_ids(req).await.unwrap().is_empty());
+        assert!(model.batches.lock().unwrap().is_empty());
+    }
+}
@@ -13,7 +13,8 @@
 //! This crate provides [`LanceDbVectorIndex`], a Rig vector store index backed
 //! by LanceDB tables. It supports exact and approximate vector search through
 //! [`SearchType`] and accepts LanceDB SQL filter expressions through
-//! [`LanceDBFilter`].
+//! [`LanceDBFilter`]. Documents can be replaced or removed through the
+//! [`UpsertDocuments`] and [`DeleteDocuments`] traits.
 //!
 //! The root `rig` facade re-exports this crate as `rig::lancedb` when the
 //! `lancedb` feature is enabled.
@@ -22,16 +23,18 @@ use std::ops::Range;
 
 use lancedb::{
     DistanceType,
+    arrow::arrow_schema::{DataType, Schema},
     query::{QueryBase, VectorQuery},
 };
 use rig_core::{
-    embeddings::{EmbeddingModelHandle, embedding::EmbeddingModel},
+    Embed,
+    embeddings::{Embedding, EmbeddingModelHandle, embedding::EmbeddingModel},
     vector_store::{
-        VectorStoreError, VectorStoreIndex,
+        DeleteDocuments, UpsertDocuments, VectorStoreError, VectorStoreIndex,
         request::{FilterError, SearchFilter, VectorSearchRequest},
     },
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use utils::{FilterTableColumns, QueryToJson};
 
@@ -182,6 +185,31 @@ impl SearchFilter for LanceDBFilter {
         Self(escape_value(value).map(|s| format!("{} < {s}", key.as_ref())))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(escape_value(value).map(|s| format!("{} != {s}", key.as_ref())))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(escape_value(value).map(|s| format!("{} >= {s}", key.as_ref())))
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(escape_value(value).map(|s| format!("{} <= {s}", key.as_ref())))
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::in_values(key.as_ref(), values)
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self::is_not_null(key.as_ref())
+    }
+
+    /// Array contains (for LIST columns)
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(escape_value(value).map(|s| format!("array_has({}, {s})", key.as_ref())))
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(zip_result(self.0, rhs.0).map(|(l, r)| format!("({l}) AND ({r})")))
     }
@@ -189,6 +217,10 @@ impl SearchFilter for LanceDBFilter {
     fn or(self, rhs: Self) -> Self {
         Self(zip_result(self.0, rhs.0).map(|(l, r)| format!("({l}) OR ({r})")))
     }
+
+    fn not(self) -> Self {
+        Self(self.0.map(|s| format!("NOT ({s})")))
+    }
 }
 
 fn escape_value(value: serde_json::Value) -> Result<String, FilterError> {
@@ -369,6 +401,129 @@ impl SearchParams {
     }
 }
 
+impl LanceDbVectorIndex {
+    /// The column new embeddings are written to: the configured
+    /// [`SearchParams::column`], or else the table's only fixed-size list of
+    /// floats, which is also the column LanceDB searches by default.
+    fn vector_column(&self, schema: &Schema) -> Result<String, VectorStoreError> {
+        if let Some(column) = &self.search_params.column {
+            return Ok(column.clone());
+        }
+
+        let mut candidates = schema.fields().iter().filter(|field| {
+            matches!(
+                field.data_type(),
+                DataType::FixedSizeList(inner, _) if inner.data_type().is_floating()
+            )
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(field), None) => Ok(field.name().clone()),
+            _ => Err(VectorStoreError::DatastoreError(
+                "the vector column is ambiguous or missing; set it with `SearchParams::column`"
+                    .into(),
+            )),
+        }
+    }
+
+    /// Render an `IN` predicate over the id field.
+    fn ids_predicate(&self, ids: Vec<String>) -> Result<String, VectorStoreError> {
+        Ok(
+            LanceDBFilter::in_values(&self.id_field, ids.into_iter().map(Value::String).collect())
+                .into_inner()?,
+        )
+    }
+}
+
+/// Rows are matched on the index's id field. A document stored as several
+/// rows, one per embedding, is removed in full.
+impl DeleteDocuments for LanceDbVectorIndex {
+    type Filter = LanceDBFilter;
+
+    async fn delete_documents(&self, ids: Vec<String>) -> Result<(), VectorStoreError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.table
+            .delete(&self.ids_predicate(ids)?)
+            .await
+            .map_err(VectorStoreError::datastore)?;
+
+        Ok(())
+    }
+
+    async fn delete_documents_where(&self, filter: Self::Filter) -> Result<(), VectorStoreError> {
+        self.table
+            .delete(&filter.into_inner()?)
+            .await
+            .map_err(VectorStoreError::datastore)?;
+
+        Ok(())
+    }
+}
+
+/// Each embedding becomes one row: the document's fields, decoded against the
+/// table schema, plus the id field and the vector column (see
+/// [`SearchParams::column`]). Document fields that are not table columns are
+/// ignored.
+///
+/// LanceDB has no multi-statement transactions, so the previous rows are
+/// deleted before the new ones are added; if the add fails, the upserted
+/// documents are left deleted rather than stale.
+impl UpsertDocuments for LanceDbVectorIndex {
+    async fn upsert_documents<Doc: Serialize + Embed + Send>(
+        &self,
+        documents: Vec<(String, Doc, Vec<Embedding>)>,
+    ) -> Result<(), VectorStoreError> {
+        if documents.is_empty() {
+            return Ok(());
+        }
+
+        let schema = self
+            .table
+            .schema()
+            .await
+            .map_err(VectorStoreError::datastore)?;
+        let vector_column = self.vector_column(&schema)?;
+
+        let mut ids = Vec::with_capacity(documents.len());
+        let mut rows = Vec::new();
+        for (id, document, embeddings) in documents {
+            let Value::Object(fields) = serde_json::to_value(document)? else {
+                return Err(VectorStoreError::DatastoreError(
+                    format!("document `{id}` must serialize to a JSON object").into(),
+                ));
+            };
+            for embedding in embeddings {
+                let mut row = fields.clone();
+                row.insert(self.id_field.clone(), Value::String(id.clone()));
+                row.insert(vector_column.clone(), embedding.vec.into());
+                rows.push(Value::Object(row));
+            }
+            ids.push(id);
+        }
+
+        let mut decoder = arrow_json::ReaderBuilder::new(schema)
+            .build_decoder()
+            .map_err(VectorStoreError::datastore)?;
+        decoder
+            .serialize(&rows)
+            .map_err(VectorStoreError::datastore)?;
+        let batch = decoder.flush().map_err(VectorStoreError::datastore)?;
+
+        self.delete_documents(ids).await?;
+        if let Some(batch) = batch {
+            self.table
+                .add(batch)
+                .execute()
+                .await
+                .map_err(VectorStoreError::datastore)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl VectorStoreIndex for LanceDbVectorIndex {
     type Filter = LanceDBFilter;
 
@@ -491,3 +646,93 @@ impl VectorStoreIndex for LanceDbVectorIndex {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lancedb::arrow::arrow_schema::{DataType, Field, Schema};
+    use rig_core::{
+        embeddings::Embedding,
+        test_utils::MockEmbeddingModel,
+        vector_store::{DeleteDocuments, UpsertDocuments, request::SearchFilter},
+    };
+    use serde_json::json;
+
+    use super::{LanceDBFilter, LanceDbVectorIndex, SearchParams};
+
+    #[tokio::test]
+    async fn upsert_and_delete_rewrite_rows_by_id() {
+        let db = lancedb::connect("memory://").execute().await.unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("kind", DataType::Utf8, true),
+            Field::new(
+                "embedding",
+                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float64, true)), 10),
+                false,
+            ),
+        ]));
+        let table = db
+            .create_empty_table("documents", schema)
+            .execute()
+            .await
+            .unwrap();
+        let index = LanceDbVectorIndex::new(
+            table.clone(),
+            MockEmbeddingModel,
+            "id",
+            SearchParams::default(),
+        )
+        .await
+        .unwrap();
+        let embedding = |x: f64| Embedding {
+            document: "chunk".to_string(),
+            vec: vec![x; 10],
+        };
+
+        index
+            .upsert_documents(vec![
+                (
+                    "a".to_string(),
+                    json!({ "kind": "draft", "unmapped": true }),
+                    vec![embedding(0.1), embedding(0.2)],
+                ),
+                (
+                    "b".to_string(),
+                    json!({ "kind": "final" }),
+                    vec![embedding(0.3)],
+                ),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 3);
+
+        // Re-upserting `a` with one chunk replaces both of its old rows.
+        index
+            .upsert_documents(vec![(
+                "a".to_string(),
+                json!({ "kind": "final" }),
+                vec![embedding(0.1)],
+            )])
+            .await
+            .unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 2);
+        assert_eq!(
+            table
+                .count_rows(Some("kind = 'final'".to_string()))
+                .await
+                .unwrap(),
+            2
+        );
+
+        index.delete_documents(vec!["b".to_string()]).await.unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 1);
+
+        index
+            .delete_documents_where(LanceDBFilter::eq("kind", json!("final")))
+            .await
+            .unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 0);
+    }
+}
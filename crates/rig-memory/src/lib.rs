@@ -19,6 +19,8 @@
 //! - [`TokenWindowMemory`] — retains messages that fit within a token budget.
 //! - [`HeuristicTokenCounter`] — provider-agnostic, zero-dependency
 //!   [`TokenCounter`] that approximates token cost from character lengths.
+//! - [`ModelTokenCounter`] — exact [`TokenCounter`] that asks the provider's
+//!   tokenizer through [`TokenCountingModel`].
 //! - [`DemotionHook`] + [`DemotingPolicyMemory`] — bridge truncated turns
 //!   from a [`MemoryPolicy`] into a long-tail store.
 //! - [`Compactor`] + [`CompactingMemory`] — replace truncated turns with a
@@ -41,7 +43,8 @@
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, VecDeque, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
     sync::{Arc, Mutex as StdMutex},
 };
 
@@ -52,7 +55,7 @@ pub use rig_core::memory::{
     NoopDemotionHook,
 };
 
-use rig_core::completion::Message;
+use rig_core::completion::{CompletionRequest, Message, TokenCountingModel};
 use rig_core::message::UserContent;
 use rig_core::wasm_compat::{WasmBoxedFuture, WasmCompatSend, WasmCompatSync};
 
@@ -85,6 +88,23 @@ pub trait MemoryPolicy: WasmCompatSend + WasmCompatSync {
     ) -> Result<(Vec<Message>, Vec<Message>), MemoryError> {
         Ok((self.apply(messages)?, Vec::new()))
     }
+
+    /// Do any asynchronous work [`apply`](Self::apply) needs for `messages`
+    /// before it runs — for example, fetching exact token counts from a
+    /// provider (see [`ModelTokenCounter`]).
+    ///
+    /// The memory adapters in this crate ([`PolicyMemory`],
+    /// [`DemotingPolicyMemory`], [`CompactingMemory`]) await this on every
+    /// load, immediately before applying the policy to the same history. A
+    /// filter built with [`IntoFilter::into_filter`] is synchronous and never
+    /// calls it. The default does nothing.
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        let _ = messages;
+        Box::pin(async { Ok(()) })
+    }
 }
 
 impl<P> MemoryPolicy for Arc<P>
@@ -101,6 +121,13 @@ where
     ) -> Result<(Vec<Message>, Vec<Message>), MemoryError> {
         (**self).apply_with_demoted(messages)
     }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        (**self).prepare(messages)
+    }
 }
 
 impl<P> MemoryPolicy for Box<P>
@@ -117,6 +144,13 @@ where
     ) -> Result<(Vec<Message>, Vec<Message>), MemoryError> {
         (**self).apply_with_demoted(messages)
     }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        (**self).prepare(messages)
+    }
 }
 
 /// Adapt a [`MemoryPolicy`] into a closure suitable for
@@ -234,6 +268,19 @@ fn split_window(messages: Vec<Message>, keep_from: usize) -> (Vec<Message>, Vec<
 pub trait TokenCounter: WasmCompatSend + WasmCompatSync {
     /// Approximate the number of tokens contributed by `message`.
     fn count(&self, message: &Message) -> usize;
+
+    /// Do any asynchronous work needed before [`count`](Self::count) is
+    /// called for each of `messages`. [`TokenWindowMemory`] forwards its
+    /// [`MemoryPolicy::prepare`] here, so a counter that has to ask a provider
+    /// (see [`ModelTokenCounter`]) can fetch its counts once per load and
+    /// answer `count` from them. The default does nothing.
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        let _ = messages;
+        Box::pin(async { Ok(()) })
+    }
 }
 
 impl<F> TokenCounter for F
@@ -252,12 +299,26 @@ where
     fn count(&self, message: &Message) -> usize {
         (**self).count(message)
     }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        (**self).prepare(messages)
+    }
 }
 
 impl TokenCounter for Box<dyn TokenCounter> {
     fn count(&self, message: &Message) -> usize {
         (**self).count(message)
     }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        (**self).prepare(messages)
+    }
 }
 
 /// A provider-agnostic [`TokenCounter`] that approximates token counts from
@@ -419,6 +480,273 @@ impl TokenCounter for HeuristicTokenCounter {
     }
 }
 
+/// A [`TokenCounter`] backed by a provider's own tokenizer through
+/// [`TokenCountingModel`].
+///
+/// Counting is asynchronous and costs a request, so it happens in
+/// [`prepare`](TokenCounter::prepare), which [`TokenWindowMemory`] receives
+/// from [`PolicyMemory`], [`DemotingPolicyMemory`] and [`CompactingMemory`] on
+/// every load. `count` then answers from what `prepare` fetched.
+///
+/// # Strategy
+///
+/// A message's cost is the growth of the exact count when it is appended to
+/// the history before it: `count(messages[..=i]) - count(messages[..i])`. The
+/// first message therefore also carries the request's fixed overhead, and
+/// every cost includes the role and separator tokens the provider adds. Both
+/// per-message costs (keyed by message content) and prefix totals (keyed by
+/// the history up to that point) are cached, so a conversation that grows by
+/// one turn costs one counting request per load rather than one per message.
+/// The caches are bounded and evict oldest-first.
+///
+/// A message whose cost is unknown — because `prepare` was never called for
+/// it, or because the provider refused to count a prefix ending at it (some
+/// reject a history that ends in an unanswered tool call) — is counted by the
+/// fallback counter, [`HeuristicTokenCounter::default`] unless replaced with
+/// [`ModelTokenCounter::with_fallback`]. That includes every count made
+/// through [`IntoFilter::into_filter`], which has no asynchronous step.
+///
+/// # Example
+///
+/// ```
+/// use rig_core::completion::TokenCountingModel;
+/// use rig_memory::{
+///     ConversationMemory, InMemoryConversationMemory, ModelTokenCounter, PolicyMemory,
+///     TokenWindowMemory,
+/// };
+///
+/// /// Keep as much history as fits in 100k of `model`'s own tokens.
+/// fn memory<M>(model: M) -> impl ConversationMemory
+/// where
+///     M: TokenCountingModel + 'static,
+/// {
+///     PolicyMemory::new(
+///         InMemoryConversationMemory::new(),
+///         TokenWindowMemory::new(100_000, ModelTokenCounter::new(model)),
+///     )
+/// }
+/// ```
+pub struct ModelTokenCounter<M> {
+    model: M,
+    fallback: Arc<dyn TokenCounter>,
+    cache: StdMutex<CountCache>,
+}
+
+/// Default number of entries each of [`ModelTokenCounter`]'s caches holds.
+const DEFAULT_COUNT_CACHE_CAPACITY: usize = 4_096;
+
+impl<M> ModelTokenCounter<M>
+where
+    M: TokenCountingModel,
+{
+    /// Count with `model`, falling back to [`HeuristicTokenCounter::default`]
+    /// for messages it has not counted.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            fallback: Arc::new(HeuristicTokenCounter::default()),
+            cache: StdMutex::new(CountCache::new(DEFAULT_COUNT_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Replace the counter used for messages the model has not counted.
+    pub fn with_fallback<C>(mut self, fallback: C) -> Self
+    where
+        C: TokenCounter + 'static,
+    {
+        self.fallback = Arc::new(fallback);
+        self
+    }
+
+    /// Bound each cache (per-message costs and prefix totals) to `capacity`
+    /// entries. Defaults to 4096; `0` disables caching, so `count` only ever
+    /// sees the fallback.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self {
+            cache: StdMutex::new(CountCache::new(capacity)),
+            ..self
+        }
+    }
+
+    /// The exact count of `prefix`, from the cache or the model. `None` when
+    /// the provider refused to count it; refusals are cached too, so a prefix
+    /// the provider rejects is not retried on every load.
+    async fn prefix_total(&self, prefix: &[Message], key: u64) -> Result<Option<u64>, MemoryError> {
+        if let Some(total) = self.cache.lock().map_err(poisoned)?.totals.get(key) {
+            return Ok(total);
+        }
+        let request = CompletionRequest {
+            model: None,
+            preamble: None,
+            chat_history: prefix.to_vec(),
+            documents: Vec::new(),
+            tools: Vec::new(),
+            temperature: None,
+            max_tokens: None,
+            tool_choice: None,
+            additional_params: None,
+            output_schema: None,
+            record_telemetry_content: false,
+        };
+        let total = match self.model.count_tokens(request).await {
+            Ok(total) => Some(total),
+            Err(error) => {
+                tracing::warn!(
+                    %error,
+                    messages = prefix.len(),
+                    "token counting failed; falling back for the last message"
+                );
+                None
+            }
+        };
+        self.cache
+            .lock()
+            .map_err(poisoned)?
+            .totals
+            .insert(key, total);
+        Ok(total)
+    }
+}
+
+impl<M> std::fmt::Debug for ModelTokenCounter<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelTokenCounter")
+            .field("model", &std::any::type_name::<M>())
+            .field("fallback", &"<counter>")
+            .finish()
+    }
+}
+
+impl<M> TokenCounter for ModelTokenCounter<M>
+where
+    M: TokenCountingModel,
+{
+    fn count(&self, message: &Message) -> usize {
+        let cached = message_key(message).and_then(|key| {
+            self.cache
+                .lock()
+                .ok()
+                .and_then(|cache| cache.costs.get(key))
+        });
+        cached.unwrap_or_else(|| self.fallback.count(message))
+    }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            let mut prefix_key = 0u64;
+            for (idx, message) in messages.iter().enumerate() {
+                let Some(message_key) = message_key(message) else {
+                    // Unserializable, so uncacheable: `count` falls back.
+                    return Ok(());
+                };
+                let before_key = prefix_key;
+                prefix_key = chain_key(prefix_key, message_key);
+                let known = self
+                    .cache
+                    .lock()
+                    .map_err(poisoned)?
+                    .costs
+                    .get(message_key)
+                    .is_some();
+                if known {
+                    continue;
+                }
+
+                let (Some(prefix), Some(extended)) = (messages.get(..idx), messages.get(..=idx))
+                else {
+                    continue;
+                };
+                let before = if prefix.is_empty() {
+                    Some(0)
+                } else {
+                    self.prefix_total(prefix, before_key).await?
+                };
+                let after = self.prefix_total(extended, prefix_key).await?;
+                if let (Some(before), Some(after)) = (before, after) {
+                    let cost = usize::try_from(after.saturating_sub(before)).unwrap_or(usize::MAX);
+                    self.cache
+                        .lock()
+                        .map_err(poisoned)?
+                        .costs
+                        .insert(message_key, cost);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The bounded caches behind a [`ModelTokenCounter`].
+struct CountCache {
+    /// Per-message cost, keyed by [`message_key`].
+    costs: FifoMap<usize>,
+    /// Exact count of a history prefix, keyed by [`chain_key`] over its
+    /// messages. `None` records a prefix the provider refused to count.
+    totals: FifoMap<Option<u64>>,
+}
+
+impl CountCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            costs: FifoMap::new(capacity),
+            totals: FifoMap::new(capacity),
+        }
+    }
+}
+
+/// A map that evicts its oldest entry once it holds `capacity` of them.
+struct FifoMap<V> {
+    capacity: usize,
+    entries: HashMap<u64, V>,
+    order: VecDeque<u64>,
+}
+
+impl<V: Copy> FifoMap<V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<V> {
+        self.entries.get(&key).copied()
+    }
+
+    fn insert(&mut self, key: u64, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key, value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A content hash of `message`, or `None` if it cannot be serialized.
+fn message_key(message: &Message) -> Option<u64> {
+    let encoded = serde_json::to_vec(message).ok()?;
+    let mut hasher = DefaultHasher::new();
+    encoded.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// The key of a prefix extended by one message.
+fn chain_key(prefix: u64, message: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (prefix, message).hash(&mut hasher);
+    hasher.finish()
+}
+
 /// A [`MemoryPolicy`] that retains the most recent messages up to a token budget.
 ///
 /// Messages are walked from newest to oldest, accumulating token counts
@@ -477,6 +805,13 @@ impl MemoryPolicy for TokenWindowMemory {
 
         Ok(split_window(messages, keep_from))
     }
+
+    fn prepare<'a>(
+        &'a self,
+        messages: &'a [Message],
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        self.counter.prepare(messages)
+    }
 }
 
 /// Wrap a [`ConversationMemory`] backend with a [`MemoryPolicy`], propagating
@@ -536,6 +871,7 @@ where
     ) -> WasmBoxedFuture<'a, Result<Vec<Message>, MemoryError>> {
         Box::pin(async move {
             let messages = self.inner.load(conversation_id).await?;
+            self.policy.prepare(&messages).await?;
             self.policy.apply(messages)
         })
     }
@@ -747,6 +1083,7 @@ where
     ) -> WasmBoxedFuture<'a, Result<Vec<Message>, MemoryError>> {
         Box::pin(async move {
             let messages = self.inner.load(conversation_id).await?;
+            self.policy.prepare(&messages).await?;
             let (kept, mut demoted) = self.policy.apply_with_demoted(messages)?;
             let demoted_count = demoted.len();
 
@@ -1043,6 +1380,7 @@ where
     ) -> WasmBoxedFuture<'a, Result<Vec<Message>, MemoryError>> {
         Box::pin(async move {
             let messages = self.inner.load(conversation_id).await?;
+            self.policy.prepare(&messages).await?;
             let (kept, demoted) = self.policy.apply_with_demoted(messages)?;
             let demoted_count = demoted.len();
 
@@ -1675,6 +2013,101 @@ mod tests {
         assert!(matches!(result, Err(MemoryError::Policy(_))));
     }
 
+    /// Charges 5 tokens of request overhead plus 10 per message, or 100 for a
+    /// message whose text is `"long"`, and refuses any history ending in a
+    /// message whose text is `"refused"`.
+    #[derive(Default)]
+    struct FixedCountModel {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl TokenCountingModel for FixedCountModel {
+        async fn count_tokens(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<u64, rig_core::completion::CompletionError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if request.chat_history.last() == Some(&user("refused")) {
+                return Err(rig_core::completion::CompletionError::RequestError(
+                    "refused".into(),
+                ));
+            }
+            Ok(request
+                .chat_history
+                .iter()
+                .map(|message| if *message == user("long") { 100 } else { 10 })
+                .sum::<u64>()
+                + 5)
+        }
+    }
+
+    #[tokio::test]
+    async fn model_token_counter_counts_only_new_messages() {
+        let counter = ModelTokenCounter::new(FixedCountModel::default());
+        let history = vec![user("a"), assistant("b"), user("long")];
+
+        counter.prepare(&history).await.unwrap();
+        assert_eq!(
+            counter.count(&history[0]),
+            15,
+            "first message carries the overhead"
+        );
+        assert_eq!(counter.count(&history[1]), 10);
+        assert_eq!(counter.count(&history[2]), 100);
+        assert_eq!(
+            counter
+                .model
+                .calls
+                .load(std::sync::atomic::Ordering::SeqCst),
+            3
+        );
+
+        let mut grown = history.clone();
+        grown.push(assistant("c"));
+        counter.prepare(&grown).await.unwrap();
+        assert_eq!(counter.count(&grown[3]), 10);
+        assert_eq!(
+            counter
+                .model
+                .calls
+                .load(std::sync::atomic::Ordering::SeqCst),
+            4,
+            "a grown history reuses the cached prefix and costs one request"
+        );
+    }
+
+    #[tokio::test]
+    async fn model_token_counter_falls_back_when_the_provider_refuses() {
+        let counter =
+            ModelTokenCounter::new(FixedCountModel::default()).with_fallback(|_: &Message| 7);
+        let history = vec![user("a"), user("refused"), assistant("b")];
+
+        counter.prepare(&history).await.unwrap();
+        assert_eq!(counter.count(&history[0]), 15);
+        assert_eq!(counter.count(&history[1]), 7);
+        assert_eq!(
+            counter.count(&history[2]),
+            7,
+            "no baseline to subtract from"
+        );
+        assert_eq!(counter.count(&assistant("never prepared")), 7);
+    }
+
+    #[tokio::test]
+    async fn policy_memory_drives_token_window_with_exact_counts() {
+        // The heuristic would charge "long" a couple of tokens and keep it.
+        let mem = PolicyMemory::new(
+            InMemoryConversationMemory::new(),
+            TokenWindowMemory::new(50, ModelTokenCounter::new(FixedCountModel::default())),
+        );
+        mem.append("c", vec![user("long"), assistant("b"), user("c")])
+            .await
+            .unwrap();
+
+        let loaded = mem.load("c").await.unwrap();
+        assert_eq!(loaded, vec![assistant("b"), user("c")]);
+    }
+
     #[tokio::test]
     async fn policy_memory_append_and_clear_delegate_to_inner() {
         let mem = PolicyMemory::new(InMemoryConversationMemory::new(), NoopMemoryPolicy);
@@ -131,6 +131,30 @@ impl SearchFilter for Filter {
         Self(format!("{} < {}", key.as_ref(), value.escaped()))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!("{} != {}", key.as_ref(), value.escaped()))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key.as_ref(), value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key.as_ref(), value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::in_values(key.as_ref(), values)
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self(format!("{} IS NOT NULL", key.as_ref()))
+    }
+
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::array_contains(key.as_ref(), value)
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(format!("({}) AND ({})", self.0, rhs.0))
     }
@@ -138,6 +162,10 @@ impl SearchFilter for Filter {
     fn or(self, rhs: Self) -> Self {
         Self(format!("({}) OR ({})", self.0, rhs.0))
     }
+
+    fn not(self) -> Self {
+        Self(format!("NOT ({})", self.0))
+    }
 }
 
 impl Filter {
@@ -334,6 +334,38 @@ impl SearchFilter for MongoDbSearchFilter {
         Self(doc! { key: { "$lt": value } })
     }
 
+    /// `$ne` alone also matches documents missing `key`, so the field is
+    /// additionally required to exist.
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        let key = key.as_ref().to_owned();
+        Self(doc! { key: { "$exists": true, "$ne": value } })
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key.as_ref(), value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key.as_ref(), value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        let key = key.as_ref().to_owned();
+        Self(doc! { key: { "$in": values } })
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        let key = key.as_ref().to_owned();
+        Self(doc! { key: { "$exists": true, "$ne": Bson::Null } })
+    }
+
+    /// An equality match on an array field matches if any element is equal,
+    /// and `$vectorSearch` pre-filters do not accept `$elemMatch`, so this
+    /// renders like [`SearchFilter::eq`].
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        <Self as SearchFilter>::eq(key, value)
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(doc! { "$and": [ self.0, rhs.0 ]})
     }
@@ -341,6 +373,10 @@ impl SearchFilter for MongoDbSearchFilter {
     fn or(self, rhs: Self) -> Self {
         Self(doc! { "$or": [ self.0, rhs.0 ]})
     }
+
+    fn not(self) -> Self {
+        Self(doc! { "$nor": [self.0] })
+    }
 }
 
 impl MongoDbSearchFilter {
@@ -360,7 +396,7 @@ impl MongoDbSearchFilter {
 
     #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Self {
-        Self(doc! { "$nor": [self.0] })
+        <Self as SearchFilter>::not(self)
     }
 
     /// Tests whether the value at `key` is the BSON type `typ`
@@ -119,6 +119,33 @@ impl SearchFilter for Neo4jSearchFilter {
         Self(format!("n.{} < {}", key.as_ref(), serialize_cypher(value)))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!("n.{} <> {}", key.as_ref(), serialize_cypher(value)))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key.as_ref(), value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key.as_ref(), value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::member(key.as_ref(), values)
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self(format!("n.{} IS NOT NULL", key.as_ref()))
+    }
+
+    /// Tests whether the list at `key` contains `value`. Unlike the inherent
+    /// [`Neo4jSearchFilter::contains`], this is list membership rather than a
+    /// substring match.
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!("{} IN n.{}", serialize_cypher(value), key.as_ref()))
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(format!("({}) AND ({})", self.0, rhs.0))
     }
@@ -126,6 +153,10 @@ impl SearchFilter for Neo4jSearchFilter {
     fn or(self, rhs: Self) -> Self {
         Self(format!("({}) OR ({})", self.0, rhs.0))
     }
+
+    fn not(self) -> Self {
+        Self(format!("NOT ({})", self.0))
+    }
 }
 
 impl Neo4jSearchFilter {
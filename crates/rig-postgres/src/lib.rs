@@ -5,6 +5,10 @@
 //! functions represented by [`PgVectorDistanceFunction`] and query filters via
 //! [`PgSearchFilter`].
 //!
+//! [`PostgresConversationMemory`] persists agent conversation history, and
+//! [`PostgresDemotionStore`] archives messages that a memory policy truncates
+//! out of the active window.
+//!
 //! The root `rig` facade re-exports this crate as `rig::postgres` when the
 //! `postgres` feature is enabled.
 
@@ -14,7 +18,7 @@ use rig_core::{
     Embed,
     embeddings::{Embedding, EmbeddingModel, EmbeddingModelHandle},
     vector_store::{
-        InsertDocuments, VectorStoreError, VectorStoreIndex,
+        DeleteDocuments, InsertDocuments, UpsertDocuments, VectorStoreError, VectorStoreIndex,
         request::{SearchFilter, SqlCondition, VectorSearchRequest},
     },
 };
@@ -23,6 +27,10 @@ use serde_json::Value;
 use sqlx::{PgPool, Postgres, postgres::PgArguments, query::QueryAs};
 use uuid::Uuid;
 
+mod memory;
+
+pub use memory::{PostgresConversationMemory, PostgresDemotionStore};
+
 /// The embedding model's concrete type is erased at construction into an
 /// [`EmbeddingModelHandle`]; the handle is fixed for the store's lifetime (an
 /// index populated under one model is only meaningful under that model).
@@ -88,6 +96,39 @@ impl SearchFilter for PgSearchFilter {
         Self(SqlCondition::binary(key, "<", PLACEHOLDER, value))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(SqlCondition::binary(key, "<>", PLACEHOLDER, value))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key.as_ref(), value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key.as_ref(), value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self(SqlCondition::list(key, "IN", PLACEHOLDER, values))
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self::is_not_null(key.as_ref())
+    }
+
+    /// Tests whether the array at `key` contains `value`. `key` must be a
+    /// Postgres array expression.
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        // Rendered as `$ = ANY(key)`: the operand sits on the left, so the
+        // array expression takes the place of the right-hand placeholder.
+        Self(SqlCondition::binary(
+            PLACEHOLDER,
+            "=",
+            &format!("ANY({})", key.as_ref()),
+            value,
+        ))
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(self.0.and(rhs.0))
     }
@@ -95,6 +136,10 @@ impl SearchFilter for PgSearchFilter {
     fn or(self, rhs: Self) -> Self {
         Self(self.0.or(rhs.0))
     }
+
+    fn not(self) -> Self {
+        Self(self.0.not())
+    }
 }
 
 impl PgSearchFilter {
@@ -195,6 +240,31 @@ fn bind_value<S>(
     }
 }
 
+/// Numbers the bare `$` placeholders emitted by [`PgSearchFilter`], starting
+/// at `$first`.
+fn number_placeholders(clause: &str, first: usize) -> String {
+    let mut counter = first;
+    let mut buf = String::with_capacity(clause.len() * 2);
+
+    for c in clause.chars() {
+        buf.push(c);
+
+        if c == '$' {
+            buf.push_str(counter.to_string().as_str());
+            counter += 1;
+        }
+    }
+
+    buf
+}
+
+/// Parses document ids, which this store keys as `uuid`s.
+fn parse_ids(ids: &[String]) -> Result<Vec<Uuid>, VectorStoreError> {
+    ids.iter()
+        .map(|id| Uuid::parse_str(id).map_err(VectorStoreError::datastore))
+        .collect()
+}
+
 #[derive(Debug, Deserialize, sqlx::FromRow)]
 pub struct SearchResult {
     id: Uuid,
@@ -298,25 +368,11 @@ impl PostgresVectorStore {
         let (where_clause, params) = match filter {
             Some(f) => {
                 let (expr, params) = f.into_clause();
-                (String::from("WHERE") + &expr, params)
+                (format!("WHERE {}", number_placeholders(&expr, 3)), params)
             }
             None => (Default::default(), Default::default()),
         };
 
-        let mut counter = 3;
-        let mut buf = String::with_capacity(where_clause.len() * 2);
-
-        for c in where_clause.chars() {
-            buf.push(c);
-
-            if c == '$' {
-                buf.push_str(counter.to_string().as_str());
-                counter += 1;
-            }
-        }
-
-        let where_clause = buf;
-
         let query = format!(
             "
             SELECT id{}, distance FROM ( \
@@ -365,6 +421,102 @@ impl InsertDocuments for PostgresVectorStore {
     }
 }
 
+/// Documents are stored as one row per embedding sharing the document's `id`,
+/// so deleting an id removes every embedding of that document.
+impl DeleteDocuments for PostgresVectorStore {
+    type Filter = PgSearchFilter;
+
+    async fn delete_documents(&self, ids: Vec<String>) -> Result<(), VectorStoreError> {
+        let ids = parse_ids(&ids)?;
+
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            "DELETE FROM {} WHERE id = ANY($1)",
+            self.documents_table
+        )))
+        .bind(ids)
+        .execute(&self.pg_pool)
+        .await
+        .map_err(VectorStoreError::datastore)?;
+
+        Ok(())
+    }
+
+    /// Deletes every row matching `filter`. Filter keys name columns of the
+    /// documents table, as in [`VectorStoreIndex::top_n`], so a filter on
+    /// `document` fields removes all embeddings of each matching document.
+    async fn delete_documents_where(&self, filter: Self::Filter) -> Result<(), VectorStoreError> {
+        let (expr, params) = filter.into_clause();
+        let query = format!(
+            "DELETE FROM {} WHERE {} RETURNING id",
+            self.documents_table,
+            number_placeholders(&expr, 1)
+        );
+
+        let builder = sqlx::query_as::<_, (Uuid,)>(sqlx::AssertSqlSafe(query));
+        params
+            .into_iter()
+            .fold(builder, bind_value)
+            .fetch_all(&self.pg_pool)
+            .await
+            .map_err(VectorStoreError::datastore)?;
+
+        Ok(())
+    }
+}
+
+/// Ids must be UUIDs, the type of the documents table's `id` column. Each
+/// upsert deletes the existing rows for its ids and inserts the new ones in a
+/// single transaction.
+impl UpsertDocuments for PostgresVectorStore {
+    async fn upsert_documents<Doc: Serialize + Embed + Send>(
+        &self,
+        documents: Vec<(String, Doc, Vec<Embedding>)>,
+    ) -> Result<(), VectorStoreError> {
+        let ids = parse_ids(
+            &documents
+                .iter()
+                .map(|(id, _, _)| id.clone())
+                .collect::<Vec<_>>(),
+        )?;
+
+        let mut tx = self
+            .pg_pool
+            .begin()
+            .await
+            .map_err(VectorStoreError::datastore)?;
+
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            "DELETE FROM {} WHERE id = ANY($1)",
+            self.documents_table
+        )))
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(VectorStoreError::datastore)?;
+
+        let insert = format!(
+            "INSERT INTO {} (id, document, embedded_text, embedding) VALUES ($1, $2, $3, $4)",
+            self.documents_table
+        );
+        for (id, (_, document, embeddings)) in ids.into_iter().zip(documents) {
+            let json_document = serde_json::to_value(&document)?;
+
+            for embedding in embeddings {
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(id)
+                    .bind(&json_document)
+                    .bind(&embedding.document)
+                    .bind(&embedding.vec)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(VectorStoreError::datastore)?;
+            }
+        }
+
+        tx.commit().await.map_err(VectorStoreError::datastore)
+    }
+}
+
 impl VectorStoreIndex for PostgresVectorStore {
     type Filter = PgSearchFilter;
 
@@ -402,7 +554,7 @@ impl VectorStoreIndex for PostgresVectorStore {
 
 #[cfg(test)]
 mod tests {
-    use super::{PgSearchFilter, SearchFilter};
+    use super::{PgSearchFilter, SearchFilter, number_placeholders};
     use serde_json::json;
 
     /// `gte`/`lte`/`member` previously emitted `?` placeholders while
@@ -426,4 +578,46 @@ mod tests {
         assert!(!cond.contains('?'));
         assert_eq!(cond.matches('$').count(), values.len());
     }
+
+    #[test]
+    fn dynamic_operators_render_postgres_conditions() {
+        let filter = PgSearchFilter::in_values("kind", vec![json!("fruit"), json!("veg")])
+            .and(<PgSearchFilter as SearchFilter>::ne(
+                "status",
+                json!("archived"),
+            ))
+            .and(<PgSearchFilter as SearchFilter>::contains("tags", json!("red")).not())
+            .and(PgSearchFilter::exists("price"));
+
+        let (cond, values) = filter.into_clause();
+        assert_eq!(
+            cond,
+            "(((kind IN ($, $)) AND (status <> $)) AND (NOT ($ = ANY(tags)))) AND (price is not null)"
+        );
+        assert_eq!(
+            values,
+            vec![
+                json!("fruit"),
+                json!("veg"),
+                json!("archived"),
+                json!("red")
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders_are_numbered_from_the_first_free_parameter() {
+        let (cond, _) = PgSearchFilter::eq("document->>'kind'", json!("fruit"))
+            .and(PgSearchFilter::in_values("id", vec![json!(1), json!(2)]))
+            .into_clause();
+
+        assert_eq!(
+            number_placeholders(&cond, 1),
+            "(document->>'kind' = $1) AND (id IN ($2, $3))"
+        );
+        assert_eq!(
+            number_placeholders(&cond, 3),
+            "(document->>'kind' = $3) AND (id IN ($4, $5))"
+        );
+    }
 }
@@ -0,0 +1,298 @@
+//! PostgreSQL-backed [`ConversationMemory`] and [`DemotionHook`].
+
+use rig_core::{
+    completion::Message,
+    memory::{ConversationMemory, DemotionHook, MemoryError},
+    wasm_compat::WasmBoxedFuture,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// A durable [`ConversationMemory`] backed by a PostgreSQL table.
+///
+/// Each message is stored as a `jsonb` row keyed by `(conversation_id,
+/// position)`, where `position` is the zero-based index of the message within
+/// its conversation. Appends run in a transaction holding a transaction-scoped
+/// advisory lock on the conversation id, so two workers appending to the same
+/// conversation are serialized and their turns never interleave.
+///
+/// The table can be created with [`PostgresConversationMemory::create_table`]
+/// or by a migration with the same schema:
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS conversation_messages (
+///   conversation_id text NOT NULL,
+///   position bigint NOT NULL,
+///   message jsonb NOT NULL,
+///   created_at timestamptz NOT NULL DEFAULT now(),
+///   PRIMARY KEY (conversation_id, position)
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct PostgresConversationMemory {
+    pg_pool: PgPool,
+    table: String,
+}
+
+impl PostgresConversationMemory {
+    /// Create a memory store over `table`, defaulting to
+    /// `conversation_messages`. The table name is interpolated into SQL and
+    /// must come from trusted configuration.
+    pub fn new(pg_pool: PgPool, table: Option<String>) -> Self {
+        Self {
+            pg_pool,
+            table: table.unwrap_or(String::from("conversation_messages")),
+        }
+    }
+
+    /// Create a memory store over the default `conversation_messages` table.
+    pub fn with_defaults(pg_pool: PgPool) -> Self {
+        Self::new(pg_pool, None)
+    }
+
+    /// Create the messages table if it does not exist.
+    pub async fn create_table(&self) -> Result<(), MemoryError> {
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                conversation_id text NOT NULL,
+                position bigint NOT NULL,
+                message jsonb NOT NULL,
+                created_at timestamptz NOT NULL DEFAULT now(),
+                PRIMARY KEY (conversation_id, position)
+            )",
+            self.table
+        )))
+        .execute(&self.pg_pool)
+        .await
+        .map_err(MemoryError::backend)?;
+
+        Ok(())
+    }
+}
+
+impl ConversationMemory for PostgresConversationMemory {
+    fn load<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<Message>, MemoryError>> {
+        Box::pin(async move {
+            let rows: Vec<Value> = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+                "SELECT message FROM {} WHERE conversation_id = $1 ORDER BY position",
+                self.table
+            )))
+            .bind(conversation_id)
+            .fetch_all(&self.pg_pool)
+            .await
+            .map_err(MemoryError::backend)?;
+
+            rows.into_iter()
+                .map(|row| serde_json::from_value(row).map_err(MemoryError::backend))
+                .collect()
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: Vec<Message>,
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            if messages.is_empty() {
+                return Ok(());
+            }
+
+            let rows = messages
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(MemoryError::backend)?;
+
+            let mut tx = self.pg_pool.begin().await.map_err(MemoryError::backend)?;
+
+            // Serialize appends per (table, conversation). The row primary key
+            // would reject a racing writer anyway, but the lock makes the second
+            // writer wait and append after the first instead of failing.
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1), hashtext($2))")
+                .bind(&self.table)
+                .bind(conversation_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(MemoryError::backend)?;
+
+            let next_position: i64 = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+                "SELECT COALESCE(MAX(position) + 1, 0) FROM {} WHERE conversation_id = $1",
+                self.table
+            )))
+            .bind(conversation_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(MemoryError::backend)?;
+
+            let insert = format!(
+                "INSERT INTO {} (conversation_id, position, message) VALUES ($1, $2, $3)",
+                self.table
+            );
+            for (position, message) in (next_position..).zip(rows) {
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(conversation_id)
+                    .bind(position)
+                    .bind(message)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(MemoryError::backend)?;
+            }
+
+            tx.commit().await.map_err(MemoryError::backend)
+        })
+    }
+
+    fn clear<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            sqlx::query(sqlx::AssertSqlSafe(format!(
+                "DELETE FROM {} WHERE conversation_id = $1",
+                self.table
+            )))
+            .bind(conversation_id)
+            .execute(&self.pg_pool)
+            .await
+            .map_err(MemoryError::backend)?;
+
+            Ok(())
+        })
+    }
+}
+
+/// A [`DemotionHook`] that archives demoted messages into a PostgreSQL
+/// long-tail table, so history truncated by a policy (for example rig-memory's
+/// `DemotingPolicyMemory`) is kept instead of lost.
+///
+/// Rows are keyed by `(conversation_id, message_hash)`, where `message_hash`
+/// is the `md5` of the message's canonical `jsonb` text. Redelivered
+/// demotions, which the [`DemotionHook`] contract allows after a restart, are
+/// therefore ignored rather than archived twice. A consequence is that two
+/// byte-identical messages in the same conversation are archived once.
+///
+/// ```sql
+/// CREATE TABLE IF NOT EXISTS conversation_archive (
+///   conversation_id text NOT NULL,
+///   message_hash text NOT NULL,
+///   message jsonb NOT NULL,
+///   demoted_at timestamptz NOT NULL DEFAULT now(),
+///   sequence bigserial,
+///   PRIMARY KEY (conversation_id, message_hash)
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct PostgresDemotionStore {
+    pg_pool: PgPool,
+    table: String,
+}
+
+impl PostgresDemotionStore {
+    /// Create an archive over `table`, defaulting to `conversation_archive`.
+    /// The table name is interpolated into SQL and must come from trusted
+    /// configuration.
+    pub fn new(pg_pool: PgPool, table: Option<String>) -> Self {
+        Self {
+            pg_pool,
+            table: table.unwrap_or(String::from("conversation_archive")),
+        }
+    }
+
+    /// Create an archive over the default `conversation_archive` table.
+    pub fn with_defaults(pg_pool: PgPool) -> Self {
+        Self::new(pg_pool, None)
+    }
+
+    /// Create the archive table if it does not exist.
+    pub async fn create_table(&self) -> Result<(), MemoryError> {
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                conversation_id text NOT NULL,
+                message_hash text NOT NULL,
+                message jsonb NOT NULL,
+                demoted_at timestamptz NOT NULL DEFAULT now(),
+                sequence bigserial,
+                PRIMARY KEY (conversation_id, message_hash)
+            )",
+            self.table
+        )))
+        .execute(&self.pg_pool)
+        .await
+        .map_err(MemoryError::backend)?;
+
+        Ok(())
+    }
+
+    /// Load every archived message for `conversation_id`, in the order it was
+    /// demoted.
+    pub async fn load_archived(&self, conversation_id: &str) -> Result<Vec<Message>, MemoryError> {
+        let rows: Vec<Value> = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+            "SELECT message FROM {} WHERE conversation_id = $1 ORDER BY sequence",
+            self.table
+        )))
+        .bind(conversation_id)
+        .fetch_all(&self.pg_pool)
+        .await
+        .map_err(MemoryError::backend)?;
+
+        rows.into_iter()
+            .map(|row| serde_json::from_value(row).map_err(MemoryError::backend))
+            .collect()
+    }
+
+    /// Remove every archived message for `conversation_id`.
+    pub async fn clear_archived(&self, conversation_id: &str) -> Result<(), MemoryError> {
+        sqlx::query(sqlx::AssertSqlSafe(format!(
+            "DELETE FROM {} WHERE conversation_id = $1",
+            self.table
+        )))
+        .bind(conversation_id)
+        .execute(&self.pg_pool)
+        .await
+        .map_err(MemoryError::backend)?;
+
+        Ok(())
+    }
+}
+
+impl DemotionHook for PostgresDemotionStore {
+    fn on_demote<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: Vec<Message>,
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            if messages.is_empty() {
+                return Ok(());
+            }
+
+            let rows = messages
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(MemoryError::backend)?;
+
+            let insert = format!(
+                "INSERT INTO {} (conversation_id, message_hash, message) \
+                 VALUES ($1, md5($2::jsonb::text), $2) \
+                 ON CONFLICT (conversation_id, message_hash) DO NOTHING",
+                self.table
+            );
+            let mut tx = self.pg_pool.begin().await.map_err(MemoryError::backend)?;
+            for message in rows {
+                sqlx::query(sqlx::AssertSqlSafe(insert.as_str()))
+                    .bind(conversation_id)
+                    .bind(message)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(MemoryError::backend)?;
+            }
+
+            tx.commit().await.map_err(MemoryError::backend)
+        })
+    }
+}
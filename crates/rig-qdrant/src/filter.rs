@@ -1,6 +1,6 @@
 use qdrant_client::qdrant::{
     Condition, FieldCondition, Filter, IsEmptyCondition, IsNullCondition, Match, Range,
-    condition::ConditionOneOf, r#match::MatchValue,
+    RepeatedIntegers, RepeatedStrings, condition::ConditionOneOf, r#match::MatchValue,
 };
 use rig_core::vector_store::request::{FilterError, SearchFilter};
 use serde::{Deserialize, Serialize};
@@ -49,6 +49,60 @@ impl SearchFilter for QdrantFilter {
         }))
     }
 
+    /// Qdrant's `must_not` also matches points missing `key`, so the field is
+    /// additionally required to exist.
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        let key = key.as_ref();
+
+        Self(json!({
+            "must": [ Self::exists(key).0 ],
+            "must_not": [ <Self as SearchFilter>::eq(key, value).0 ]
+        }))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        let key = key.as_ref().to_owned();
+
+        Self(json!({
+            "key": key,
+            "range": {
+                "gte": value
+            }
+        }))
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        let key = key.as_ref().to_owned();
+
+        Self(json!({
+            "key": key,
+            "range": {
+                "lte": value
+            }
+        }))
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        let key = key.as_ref().to_owned();
+
+        Self(json!({
+            "key": key,
+            "match": {
+                "any": values
+            }
+        }))
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self::exists(key.as_ref())
+    }
+
+    /// A Qdrant `match` on an array payload matches if any element matches, so
+    /// this renders like [`SearchFilter::eq`].
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        <Self as SearchFilter>::eq(key, value)
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(json!({ "must": [ self.0, rhs.0 ]}))
     }
@@ -56,12 +110,16 @@ impl SearchFilter for QdrantFilter {
     fn or(self, rhs: Self) -> Self {
         Self(json!({ "should": [ self.0, rhs.0 ]}))
     }
+
+    fn not(self) -> Self {
+        Self(json!({ "must_not": [ self.0 ]}))
+    }
 }
 
 impl QdrantFilter {
     #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Self {
-        Self(json!({ "must_not": [ self.0 ]}))
+        <Self as SearchFilter>::not(self)
     }
     pub fn into_inner(self) -> serde_json::Value {
         self.0
@@ -159,6 +217,37 @@ impl QdrantFilter {
                 }
             }
 
+            fn to_match_any(values: serde_json::Value) -> Result<MatchValue, FilterError> {
+                let values = match values {
+                    Array(values) => values,
+                    other => {
+                        return Err(FilterError::Expected {
+                            expected: "Array".into(),
+                            got: other.to_string(),
+                        });
+                    }
+                };
+
+                if values.iter().all(serde_json::Value::is_string) {
+                    let strings = values
+                        .into_iter()
+                        .filter_map(|v| v.as_str().map(str::to_owned))
+                        .collect();
+                    Ok(MatchValue::Keywords(RepeatedStrings { strings }))
+                } else {
+                    let integers = values
+                        .into_iter()
+                        .map(|v| {
+                            v.as_i64().ok_or_else(|| FilterError::Expected {
+                                expected: "Integer or String".into(),
+                                got: v.to_string(),
+                            })
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok(MatchValue::Integers(RepeatedIntegers { integers }))
+                }
+            }
+
             fn to_condition(value: serde_json::Value) -> Result<Condition, FilterError> {
                 // Handle is_empty condition
                 if let Some(is_empty) = value.get("is_empty") {
@@ -240,12 +329,16 @@ impl QdrantFilter {
                     };
 
                     // Handle match condition
-                    if let Some(match_obj) = value.get("match")
-                        && let Some(val) = match_obj.get("value")
-                    {
-                        field_condition.r#match = Some(Match {
-                            match_value: Some(to_match(val.clone())?),
-                        });
+                    if let Some(match_obj) = value.get("match") {
+                        if let Some(val) = match_obj.get("value") {
+                            field_condition.r#match = Some(Match {
+                                match_value: Some(to_match(val.clone())?),
+                            });
+                        } else if let Some(vals) = match_obj.get("any") {
+                            field_condition.r#match = Some(Match {
+                                match_value: Some(to_match_any(vals.clone())?),
+                            });
+                        }
                     }
 
                     // Handle range condition
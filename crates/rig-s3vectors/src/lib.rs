@@ -20,7 +20,10 @@ use rig_core::{
     embeddings::{EmbeddingModel, EmbeddingModelHandle},
     vector_store::{
         InsertDocuments, VectorStoreError, VectorStoreIndex,
-        request::{DynamicSearchFilter, Filter, FilterError, SearchFilter, VectorSearchRequest},
+        request::{
+            DynamicSearchFilter, Filter, FilterError, FilterOperator, SearchFilter,
+            VectorSearchRequest,
+        },
     },
 };
 use serde::{Deserialize, Serialize};
@@ -53,6 +56,34 @@ impl SearchFilter for S3SearchFilter {
         Self(document_comparison(key, "$lt", value))
     }
 
+    /// `$ne` alone also matches vectors missing `key`, so the field is
+    /// additionally required to exist.
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        let key = key.as_ref();
+        Self::exists(key).and(Self(document_comparison(key, "$ne", value)))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(document_comparison(key, "$gte", value))
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(document_comparison(key, "$lte", value))
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self(document_comparison(key, "$in", Document::Array(values)))
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self(document_comparison(key, "$exists", Document::Bool(true)))
+    }
+
+    /// S3Vectors' `$eq` matches an array field if any element is equal.
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(document_comparison(key, "$eq", value))
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(document_object([(
             "$and",
@@ -66,6 +97,15 @@ impl SearchFilter for S3SearchFilter {
             Document::Array(vec![self.0, rhs.0]),
         )]))
     }
+
+    fn not(self) -> Self {
+        Self(document_object([("$not", self.0)]))
+    }
+
+    /// S3Vectors has no general negation operator.
+    fn supports(op: FilterOperator) -> bool {
+        op != FilterOperator::Not
+    }
 }
 
 /// Builds a `Document::Object` from the given entries.
@@ -89,6 +129,7 @@ fn document_comparison(key: impl AsRef<str>, op: &str, value: Document) -> Docum
 
 impl DynamicSearchFilter for S3SearchFilter {
     fn from_dynamic_filter(filter: Filter<serde_json::Value>) -> Result<Self, FilterError> {
+        filter.ensure_supported::<Self>()?;
         Ok(filter.interpret_with(|value| json_value_to_document(&value)))
     }
 }
@@ -103,26 +144,20 @@ impl S3SearchFilter {
     }
 
     pub fn gte(key: impl Into<String>, value: <Self as SearchFilter>::Value) -> Self {
-        let key = key.into();
-        Self(document_comparison(key, "$gte", value))
+        <Self as SearchFilter>::gte(key.into(), value)
     }
 
     pub fn lte(key: impl Into<String>, value: <Self as SearchFilter>::Value) -> Self {
-        let key = key.into();
-        Self(document_comparison(key, "$lte", value))
+        <Self as SearchFilter>::lte(key.into(), value)
     }
 
     pub fn exists(key: impl Into<String>) -> Self {
-        let key = key.into();
-        Self(document_object([(
-            "$exists",
-            document_object([(key, Document::Bool(true))]),
-        )]))
+        <Self as SearchFilter>::exists(key.into())
     }
 
     #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Self {
-        Self(document_object([("$not", self.0)]))
+        <Self as SearchFilter>::not(self)
     }
 }
 
@@ -407,10 +442,47 @@ mod tests {
                             { "score": { "$gte": 5 } },
                             { "score": { "$lte": 1 } }
                         ]},
-                        { "$exists": { "status": true } }
+                        { "status": { "$exists": true } }
                     ]
                 }
             })
         );
     }
+
+    #[test]
+    fn dynamic_filter_compiles_extended_operators() {
+        let filter = Filter::in_values("status", vec![serde_json::json!("ready")])
+            .and(Filter::contains("tags", serde_json::json!("rust")))
+            .and(Filter::ne("kind", serde_json::json!("draft")));
+
+        let compiled = S3SearchFilter::from_dynamic_filter(filter)
+            .expect("extended operators should compile to AWS documents");
+
+        assert_eq!(
+            document_to_json_value(compiled.inner()),
+            serde_json::json!({
+                "$and": [{
+                    "$and": [
+                        { "status": { "$in": ["ready"] } },
+                        { "tags": { "$eq": "rust" } }
+                    ]
+                }, {
+                    "$and": [
+                        { "kind": { "$exists": true } },
+                        { "kind": { "$ne": "draft" } }
+                    ]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn dynamic_filter_rejects_negation() {
+        let filter = Filter::eq("status", serde_json::json!("ready")).not();
+
+        assert!(matches!(
+            S3SearchFilter::from_dynamic_filter(filter),
+            Err(FilterError::TypeError(op)) if op == "not"
+        ));
+    }
 }
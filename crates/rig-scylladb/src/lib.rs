@@ -13,7 +13,7 @@ use rig_core::{
     vector_store::{
         InsertDocuments, VectorStoreError, VectorStoreIndex,
         request::{
-            DynamicSearchFilter, Filter, FilterError, SearchFilter, SqlCondition,
+            DynamicSearchFilter, Filter, FilterError, FilterOperator, SearchFilter, SqlCondition,
             VectorSearchRequest,
         },
     },
@@ -129,6 +129,30 @@ impl SearchFilter for ScyllaSearchFilter {
         Self(SqlCondition::binary(key, "<", PLACEHOLDER, value))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::ne(key.as_ref(), value)
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key.as_ref(), value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key.as_ref(), value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::member(key.as_ref(), values)
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self(SqlCondition::raw(format!("{} IS NOT NULL", key.as_ref())))
+    }
+
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(SqlCondition::binary(key, "CONTAINS", PLACEHOLDER, value))
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(self.0.and(rhs.0))
     }
@@ -136,6 +160,16 @@ impl SearchFilter for ScyllaSearchFilter {
     fn or(self, rhs: Self) -> Self {
         Self(self.0.or(rhs.0))
     }
+
+    fn not(self) -> Self {
+        Self(self.0.not())
+    }
+
+    /// CQL has no `NOT`, and only accepts `IS NOT NULL` in materialized view
+    /// definitions.
+    fn supports(op: FilterOperator) -> bool {
+        !matches!(op, FilterOperator::Not | FilterOperator::Exists)
+    }
 }
 
 impl ScyllaSearchFilter {
@@ -177,6 +211,7 @@ impl TryFrom<Filter<serde_json::Value>> for ScyllaSearchFilter {
     type Error = FilterError;
 
     fn try_from(value: Filter<serde_json::Value>) -> Result<Self, Self::Error> {
+        value.ensure_supported::<Self>()?;
         value.try_interpret(cql_value_from_json)
     }
 }
@@ -512,7 +547,8 @@ pub async fn create_session(uri: &str) -> Result<Session, VectorStoreError> {
 
 #[cfg(test)]
 mod tests {
-    use super::{CqlValue, ScyllaSearchFilter, SearchFilter};
+    use super::{CqlValue, Filter, FilterError, ScyllaSearchFilter, SearchFilter};
+    use serde_json::json;
 
     /// CQL binds positionally, so the rendered condition must carry exactly one
     /// `?` per parameter — including `IN`, which renders one per value.
@@ -532,4 +568,22 @@ mod tests {
         assert_eq!(filter.condition().matches('?').count(), 4);
         assert_eq!(filter.params().len(), 4);
     }
+
+    #[test]
+    fn dynamic_filters_compile_supported_operators_only() {
+        let filter = Filter::in_values("kind", vec![json!("fruit"), json!("veg")])
+            .and(Filter::contains("tags", json!("red")));
+        let compiled = ScyllaSearchFilter::try_from(filter);
+
+        assert!(matches!(
+            compiled,
+            Ok(ref filter) if filter.condition() == "(kind IN (?, ?)) AND (tags CONTAINS ?)"
+        ));
+
+        let negated = Filter::exists("kind").not();
+        assert!(matches!(
+            ScyllaSearchFilter::try_from(negated),
+            Err(FilterError::TypeError(op)) if op == "not"
+        ));
+    }
 }
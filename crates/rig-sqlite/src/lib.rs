@@ -4,13 +4,22 @@
 //! storing embedded documents in SQLite with the `sqlite-vec` extension. Define
 //! document table schemas by implementing [`SqliteVectorStoreTable`].
 //!
+//! [`SqliteConversationMemory`] persists agent conversation history in the same
+//! database, implementing rig-core's
+//! [`ConversationMemory`](rig_core::memory::ConversationMemory). With the
+//! `agent` feature, [`SqliteRunStore`] checkpoints resumable agent runs there
+//! too. [`SqliteResponseCache`] stores responses for rig-core's
+//! [`CachedCompletionModel`](rig_core::completion::CachedCompletionModel).
+//!
 //! The root `rig` facade re-exports this crate as `rig::sqlite` when the
 //! `sqlite` feature is enabled.
 
 use rig_core::Embed;
 use rig_core::embeddings::{Embedding, EmbeddingModel, EmbeddingModelHandle};
 use rig_core::vector_store::request::{FilterError, SearchFilter, VectorSearchRequest};
-use rig_core::vector_store::{InsertDocuments, VectorStoreError, VectorStoreIndex};
+use rig_core::vector_store::{
+    DeleteDocuments, InsertDocuments, UpsertDocuments, VectorStoreError, VectorStoreIndex,
+};
 use rig_core::wasm_compat::{WasmCompatSend, WasmCompatSync};
 use rusqlite::OptionalExtension;
 use rusqlite::types::{Type, Value, ValueRef};
@@ -20,6 +29,16 @@ use std::ops::RangeInclusive;
 use tokio_rusqlite::Connection;
 use tracing::{debug, info};
 
+mod memory;
+mod response_cache;
+#[cfg(feature = "agent")]
+mod run_store;
+
+pub use memory::{ConversationSummary, DEFAULT_CONVERSATION_TABLE, SqliteConversationMemory};
+pub use response_cache::{DEFAULT_RESPONSE_CACHE_TABLE, SqliteResponseCache};
+#[cfg(feature = "agent")]
+pub use run_store::{DEFAULT_RUN_TABLE, SqliteRunStore};
+
 /// Maximum `k` accepted by a `sqlite-vec` `vec0` KNN query (`embedding MATCH ?
 /// AND k = ?`). `sqlite-vec` enforces this as a hard `#define
 /// SQLITE_VEC_VEC0_K_MAX 4096` and rejects larger values with
@@ -197,6 +216,12 @@ enum SqliteInternalError {
         column_type: &'static str,
         message: String,
     },
+    #[error("`{0}` is not a valid SQLite table name")]
+    InvalidTableName(String),
+    #[error("stored timestamp `{0}` is out of range")]
+    InvalidTimestamp(i64),
+    #[error("document upserted under id `{expected}` has id `{found}`")]
+    UpsertIdMismatch { expected: String, found: String },
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -588,14 +613,8 @@ where
             embedding_placeholders.join(", ")
         );
         let existing_rowid_sql = format!("SELECT rowid FROM {table_name} WHERE id = ?1");
-        let existing_embedding_rowids_sql = format!(
-            "SELECT embedding_rowid FROM {embedding_map_table_name} WHERE document_rowid = ?1"
-        );
         let insert_embedding_map_sql =
             format!("INSERT INTO {embedding_map_table_name}(document_rowid) VALUES (?1)");
-        let delete_embedding_map_sql =
-            format!("DELETE FROM {embedding_map_table_name} WHERE document_rowid = ?1");
-        let delete_embeddings_sql = format!("DELETE FROM {embeddings_table_name} WHERE rowid = ?1");
 
         for (doc, embeddings) in documents {
             debug!("Storing document with id {}", doc.id());
@@ -612,14 +631,7 @@ where
                 })
                 .optional()?
             {
-                let existing_embedding_rowids = txn
-                    .prepare(&existing_embedding_rowids_sql)?
-                    .query_map([existing_rowid], |row| row.get::<_, i64>(0))?
-                    .collect::<rusqlite::Result<Vec<_>>>()?;
-                for embedding_rowid in existing_embedding_rowids {
-                    txn.execute(&delete_embeddings_sql, [embedding_rowid])?;
-                }
-                txn.execute(&delete_embedding_map_sql, [existing_rowid])?;
+                Self::delete_embeddings_with_txn(txn, existing_rowid)?;
             }
 
             let columns = values.iter().map(|(col, _)| *col).collect::<Vec<_>>();
@@ -686,6 +698,67 @@ where
             .await
             .map_err(VectorStoreError::datastore)
     }
+
+    /// Deletes the embeddings and embedding-map rows owned by the document at
+    /// `document_rowid`, leaving the document row itself in place.
+    fn delete_embeddings_with_txn(
+        txn: &rusqlite::Transaction<'_>,
+        document_rowid: i64,
+    ) -> Result<(), tokio_rusqlite::Error> {
+        let table_name = T::name();
+        let embedding_rowids = txn
+            .prepare(&format!(
+                "SELECT embedding_rowid FROM {table_name}_embedding_map WHERE document_rowid = ?1"
+            ))?
+            .query_map([document_rowid], |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for embedding_rowid in embedding_rowids {
+            txn.execute(
+                &format!("DELETE FROM {table_name}_embeddings WHERE rowid = ?1"),
+                [embedding_rowid],
+            )?;
+        }
+        txn.execute(
+            &format!("DELETE FROM {table_name}_embedding_map WHERE document_rowid = ?1"),
+            [document_rowid],
+        )?;
+
+        Ok(())
+    }
+
+    /// Deletes every document row selected by `select_rowids_sql` (a query
+    /// returning document-table rowids) together with its embeddings, in one
+    /// transaction.
+    async fn delete_rows_where(
+        &self,
+        select_rowids_sql: String,
+        params: Vec<Value>,
+    ) -> Result<(), VectorStoreError> {
+        let table_name = T::name();
+
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                let rowids = tx
+                    .prepare(&select_rowids_sql)?
+                    .query_map(rusqlite::params_from_iter(params), |row| {
+                        row.get::<_, i64>(0)
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                for rowid in rowids {
+                    Self::delete_embeddings_with_txn(&tx, rowid)?;
+                    tx.execute(
+                        &format!("DELETE FROM {table_name} WHERE rowid = ?1"),
+                        [rowid],
+                    )?;
+                }
+                tx.commit()?;
+
+                Ok(())
+            })
+            .await
+            .map_err(VectorStoreError::datastore)
+    }
 }
 
 impl<T> InsertDocuments for SqliteVectorStore<T>
@@ -720,12 +793,98 @@ where
     }
 }
 
+/// Rows are matched on the table's `id` column, the same column
+/// [`SqliteVectorStore::add_rows`] replaces on.
+impl<T> DeleteDocuments for SqliteVectorStore<T>
+where
+    T: SqliteVectorStoreTable + 'static,
+{
+    type Filter = SqliteSearchFilter;
+
+    async fn delete_documents(&self, ids: Vec<String>) -> Result<(), VectorStoreError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        self.delete_rows_where(
+            format!(
+                "SELECT rowid FROM {} WHERE id IN ({placeholders})",
+                T::name()
+            ),
+            ids.into_iter().map(Value::Text).collect(),
+        )
+        .await
+    }
+
+    /// Filter keys resolve against the document table exactly as the
+    /// post-search filters of [`SqliteVectorIndex`] do.
+    async fn delete_documents_where(&self, filter: Self::Filter) -> Result<(), VectorStoreError> {
+        let rendered = filter.expr.render_document(&self.metadata_columns)?;
+        self.delete_rows_where(
+            format!(
+                "SELECT d.rowid FROM {} d WHERE {}",
+                T::name(),
+                rendered.condition
+            ),
+            rendered.params,
+        )
+        .await
+    }
+}
+
+/// Each document is converted into a `T` row as in
+/// [`InsertDocuments::insert_documents`]; the row's [`SqliteVectorStoreTable::id`]
+/// must equal the id it is upserted under.
+impl<T> UpsertDocuments for SqliteVectorStore<T>
+where
+    T: SqliteVectorStoreTable
+        + serde::de::DeserializeOwned
+        + WasmCompatSend
+        + WasmCompatSync
+        + 'static,
+{
+    async fn upsert_documents<Doc: Serialize + Embed + WasmCompatSend>(
+        &self,
+        documents: Vec<(String, Doc, Vec<Embedding>)>,
+    ) -> Result<(), VectorStoreError> {
+        if documents.is_empty() {
+            return Ok(());
+        }
+
+        let rows = documents
+            .into_iter()
+            .map(|(id, document, embeddings)| {
+                let document = serde_json::to_value(document)?;
+                let row = serde_json::from_value::<T>(document)?;
+                if row.id() != id {
+                    return Err(VectorStoreError::datastore(
+                        SqliteInternalError::UpsertIdMismatch {
+                            expected: id,
+                            found: row.id(),
+                        },
+                    ));
+                }
+
+                Ok((row, embeddings))
+            })
+            .collect::<Result<Vec<_>, VectorStoreError>>()?;
+
+        // `add_rows` already replaces a stored row with the same id, dropping
+        // all of its previous embeddings.
+        self.add_rows(rows).await?;
+
+        Ok(())
+    }
+}
+
 /// Search filter for SQLite vector searches.
 ///
 /// SQLite vector search applies simple indexed metadata comparisons and ranges
 /// during sqlite-vec KNN candidate search when possible. Other supported
-/// document-table expressions, including JSON expressions, `OR`, null checks,
-/// `LIKE`, and `GLOB`, are applied after candidate search with an exhaustive
+/// document-table expressions, including JSON expressions, `OR`, `IN`, null
+/// checks, array containment, `LIKE`, and `GLOB`, are applied after candidate
+/// search with an exhaustive
 /// candidate limit so custom document columns can still be filtered correctly.
 ///
 /// For hot scalar filters, prefer marking columns with [`Column::indexed`] so
@@ -763,6 +922,15 @@ enum SqliteSearchFilterExpr {
         key: String,
         negated: bool,
     },
+    In {
+        key: String,
+        values: Vec<serde_json::Value>,
+    },
+    /// Matches when the JSON array at `key` has an element equal to `value`.
+    Contains {
+        key: String,
+        value: serde_json::Value,
+    },
     Pattern {
         key: String,
         op: SqlitePatternOp,
@@ -916,6 +1084,45 @@ impl SearchFilter for SqliteSearchFilter {
         Self::cmp(key, SqliteComparisonOp::Lt, value)
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::cmp(key, SqliteComparisonOp::Ne, value)
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::cmp(key, SqliteComparisonOp::Gte, value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::cmp(key, SqliteComparisonOp::Lte, value)
+    }
+
+    /// sqlite-vec cannot enforce `IN` during candidate search, so this is
+    /// applied as a document-table post-filter.
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self {
+            expr: SqliteSearchFilterExpr::In {
+                key: key.as_ref().to_string(),
+                values,
+            },
+        }
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self::null_check(key.as_ref().to_string(), true)
+    }
+
+    /// Tests whether the JSON array at `key` has an element equal to `value`.
+    ///
+    /// This is applied as a document-table post-filter using `json_each`.
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self {
+            expr: SqliteSearchFilterExpr::Contains {
+                key: key.as_ref().to_string(),
+                value,
+            },
+        }
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self {
             expr: SqliteSearchFilterExpr::And(Box::new(self.expr), Box::new(rhs.expr)),
@@ -927,6 +1134,10 @@ impl SearchFilter for SqliteSearchFilter {
             expr: SqliteSearchFilterExpr::Or(Box::new(self.expr), Box::new(rhs.expr)),
         }
     }
+
+    fn not(self) -> Self {
+        Self::not(self)
+    }
 }
 
 impl SqliteSearchFilter {
@@ -1083,9 +1294,13 @@ impl SqliteSearchFilterExpr {
                 })
             }
             Self::Noop => Ok(SqliteRenderedFilters::default()),
-            Self::Or(_, _) | Self::NullCheck { .. } | Self::Pattern { .. } => Ok(
-                SqliteRenderedFilters::post_only(self.render_document(metadata_columns)?),
-            ),
+            Self::Or(_, _)
+            | Self::NullCheck { .. }
+            | Self::In { .. }
+            | Self::Contains { .. }
+            | Self::Pattern { .. } => Ok(SqliteRenderedFilters::post_only(
+                self.render_document(metadata_columns)?,
+            )),
             Self::Not(expr) => expr.render_negated_split(metadata_columns),
         }
     }
@@ -1152,6 +1367,35 @@ impl SqliteSearchFilterExpr {
                     params: Vec::new(),
                 })
             }
+            Self::In { key, values } => {
+                let key = sqlite_qualify_document_key(key)?;
+                let params = values
+                    .iter()
+                    .map(|value| {
+                        sqlite_document_filter_param(&key, metadata_columns, value.clone())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(SqliteRenderedFilter {
+                    condition: format!(
+                        "{} in ({})",
+                        key.expression,
+                        vec!["?"; params.len()].join(", ")
+                    ),
+                    params,
+                })
+            }
+            Self::Contains { key, value } => {
+                let key = sqlite_qualify_document_key(key)?;
+                // `json_each` yields SQL values, so the operand is bound as one
+                // regardless of how `key` itself is compared.
+                Ok(SqliteRenderedFilter {
+                    condition: format!(
+                        "exists (select 1 from json_each({}) where value = ?)",
+                        key.expression
+                    ),
+                    params: vec![sqlite_filter_param(value.clone())?],
+                })
+            }
             Self::Pattern { key, op, pattern } => {
                 let key = sqlite_qualify_document_key(key)?;
                 Ok(SqliteRenderedFilter {
@@ -2072,6 +2316,7 @@ mod tests {
     }
     use super::*;
     use rig_core::embeddings::{EmbeddingError, EmbeddingResponse};
+    use rig_core::vector_store::request::Filter;
     use rusqlite::ffi::{sqlite3, sqlite3_api_routines, sqlite3_auto_extension};
     use sqlite_vec::sqlite3_vec_init;
     use std::cmp::Ordering;
@@ -2859,6 +3104,60 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn in_and_contains_filters_use_document_filter() -> anyhow::Result<()> {
+        let filter = SqliteSearchFilter::in_values(
+            "category",
+            vec![serde_json::json!("docs"), serde_json::json!("blog")],
+        )
+        .and(SqliteSearchFilter::contains(
+            "metadata->'$.tags'",
+            serde_json::json!("rust"),
+        ))
+        .and(SqliteSearchFilter::exists("title"));
+        let req = VectorSearchRequest::<SqliteSearchFilter>::builder()
+            .query("needle")
+            .samples(5)
+            .filter(filter)
+            .build();
+
+        let filters =
+            render_search_filters(&req, SqliteDistanceMetric::Cosine, &test_metadata_columns())?;
+        anyhow::ensure!(
+            filters.has_post_filters(),
+            "IN and array containment filters should be applied after vector candidate search"
+        );
+        let query = build_search_query(query_blob(&[1.0, 0.0]), filters, 5)?;
+
+        anyhow::ensure!(
+            query.vector_where_clause == "WHERE e.embedding MATCH ? AND k = ?",
+            "unexpected vector where clause: {}",
+            query.vector_where_clause
+        );
+        anyhow::ensure!(
+            query.document_filter_clause
+                == "AND (d.category in (?, ?)) \
+                    AND (exists (select 1 from json_each(d.metadata->'$.tags') where value = ?)) \
+                    AND (d.title is not null)",
+            "unexpected document filter clause: {}",
+            query.document_filter_clause
+        );
+        anyhow::ensure!(
+            query.params.get(3..6)
+                == Some(
+                    &[
+                        Value::Text("docs".to_string()),
+                        Value::Text("blog".to_string()),
+                        Value::Text("rust".to_string()),
+                    ][..]
+                ),
+            "unexpected IN and containment filter params: {:?}",
+            query.params
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn json_metadata_arrow_expression_binds_rhs_as_json_text() -> anyhow::Result<()> {
         let req = VectorSearchRequest::<SqliteSearchFilter>::builder()
@@ -3693,6 +3992,153 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn live_dynamic_in_not_and_contains_filters() -> anyhow::Result<()> {
+        let tagged = |id: &str, category: &str, tags: serde_json::Value, vec: Vec<f64>| {
+            let document = JsonMetadataDocument {
+                id: id.to_string(),
+                category: category.to_string(),
+                metadata: serde_json::json!({ "tags": tags }).to_string(),
+                title: format!("{id} title"),
+            };
+            let embedding = Embedding {
+                document: document.title.clone(),
+                vec,
+            };
+            (document, vec![embedding])
+        };
+        let index = live_json_metadata_test_index(
+            "live_dynamic_in_not_and_contains_filters",
+            vec![
+                tagged(
+                    "nearest",
+                    "docs",
+                    serde_json::json!(["rust"]),
+                    vec![1.0, 0.0],
+                ),
+                tagged(
+                    "archived",
+                    "blog",
+                    serde_json::json!(["rust", "archived"]),
+                    vec![0.9, 0.1],
+                ),
+                tagged("untagged", "blog", serde_json::json!([]), vec![0.8, 0.2]),
+                tagged(
+                    "wanted",
+                    "blog",
+                    serde_json::json!(["rust"]),
+                    vec![0.0, 1.0],
+                ),
+            ],
+        )
+        .await?;
+
+        let filter = Filter::in_values("category", vec![serde_json::json!("blog")])
+            .and(Filter::contains(
+                "metadata->'$.tags'",
+                serde_json::json!("rust"),
+            ))
+            .and(Filter::contains("metadata->'$.tags'", serde_json::json!("archived")).not());
+        let req = VectorSearchRequest::builder()
+            .query("needle")
+            .samples(4)
+            .filter(filter)
+            .build();
+
+        let results = rig_core::vector_store::VectorStoreIndexDyn::top_n_ids(&index, req).await?;
+        let ids = results
+            .iter()
+            .map(|(_, id)| id.as_str())
+            .collect::<Vec<_>>();
+        anyhow::ensure!(
+            ids.as_slice() == ["wanted"],
+            "dynamic IN/NOT/contains filters should select only the wanted row: {results:?}"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn live_delete_and_upsert_remove_every_stale_embedding() -> anyhow::Result<()> {
+        register_sqlite_vec_extension();
+
+        let conn = Connection::open("file:live_delete_and_upsert?mode=memory").await?;
+        let model = TestEmbeddingModel;
+        let store: SqliteVectorStore<JsonMetadataDocument> =
+            SqliteVectorStore::new(conn, &model).await?;
+        store
+            .add_rows(vec![
+                json_metadata_row("kept", "blog", "x", "kept", vec![0.0, 1.0]),
+                json_metadata_row("by-id", "blog", "x", "by id", vec![0.9, 0.1]),
+                json_metadata_row("by-filter", "docs", "x", "by filter", vec![1.0, 0.0]),
+            ])
+            .await?;
+
+        let (kept, _) = json_metadata_row("kept", "blog", "y", "kept", vec![0.0, 1.0]);
+        let replacement = vec![
+            Embedding {
+                document: "first chunk".to_string(),
+                vec: vec![0.0, 1.0],
+            },
+            Embedding {
+                document: "second chunk".to_string(),
+                vec: vec![0.1, 0.9],
+            },
+        ];
+        store
+            .upsert_documents(vec![("kept".to_string(), kept.clone(), replacement)])
+            .await?;
+        store.delete_documents(vec!["by-id".to_string()]).await?;
+        store
+            .delete_documents_where(SqliteSearchFilter::eq(
+                "category",
+                serde_json::json!("docs"),
+            ))
+            .await?;
+
+        let (documents, embeddings, mapped) = store
+            .conn
+            .call(|conn| {
+                let count = |table: &str| {
+                    conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                };
+                Ok((
+                    count("live_json_metadata_test_documents")?,
+                    count("live_json_metadata_test_documents_embeddings")?,
+                    count("live_json_metadata_test_documents_embedding_map")?,
+                ))
+            })
+            .await?;
+        anyhow::ensure!(
+            (documents, embeddings, mapped) == (1, 2, 2),
+            "only the upserted document and its two new embeddings should remain: \
+             {documents} documents, {embeddings} embeddings, {mapped} mapped"
+        );
+
+        let index = store.clone().index(model);
+        let req = VectorSearchRequest::<SqliteSearchFilter>::builder()
+            .query("needle")
+            .samples(3)
+            .build();
+        let results = index.top_n::<JsonMetadataDocument>(req).await?;
+        anyhow::ensure!(
+            results.len() == 1 && results.first().map(|(_, _, doc)| doc) == Some(&kept),
+            "search should only return the upserted document: {results:?}"
+        );
+
+        let mismatch = store
+            .upsert_documents(vec![("other".to_string(), kept, Vec::new())])
+            .await;
+        anyhow::ensure!(
+            mismatch.is_err(),
+            "upserting a row under a different id should fail"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn live_json_arrow_filter_compares_against_json_text() -> anyhow::Result<()> {
         let index = live_json_metadata_test_index(
@@ -4894,7 +5340,7 @@ mod tests {
         }
     }
 
-    #[derive(Clone, Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     struct JsonMetadataDocument {
         id: String,
         category: String,
@@ -4902,6 +5348,16 @@ mod tests {
         title: String,
     }
 
+    impl Embed for JsonMetadataDocument {
+        fn embed(
+            &self,
+            embedder: &mut rig_core::embeddings::TextEmbedder,
+        ) -> Result<(), rig_core::embeddings::EmbedError> {
+            embedder.embed(self.title.clone());
+            Ok(())
+        }
+    }
+
     impl SqliteVectorStoreTable for JsonMetadataDocument {
         fn name() -> &'static str {
             "live_json_metadata_test_documents"
@@ -0,0 +1,424 @@
+//! SQLite-backed [`ConversationMemory`].
+
+use chrono::{DateTime, Utc};
+use rig_core::completion::Message;
+use rig_core::memory::{ConversationMemory, MemoryError};
+use rig_core::wasm_compat::WasmBoxedFuture;
+use rusqlite::TransactionBehavior;
+use tokio_rusqlite::Connection;
+
+use crate::SqliteInternalError;
+
+/// Default table used by [`SqliteConversationMemory::new`].
+pub const DEFAULT_CONVERSATION_TABLE: &str = "rig_conversation_messages";
+
+/// A durable [`ConversationMemory`] that stores each message as a row in a
+/// SQLite table.
+///
+/// Rows are keyed by `(conversation_id, position)`, where `position` is the
+/// zero-based index of the message within its conversation, and carry the
+/// serialized [`Message`] as JSON plus a `created_at` timestamp (Unix
+/// milliseconds). Appends run inside an immediate transaction, so two writers
+/// sharing the same database file cannot interleave the messages of a turn.
+///
+/// The table does not need the `sqlite-vec` extension; the same
+/// [`Connection`] used for a [`crate::SqliteVectorStore`] can be shared.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use rig_core::{completion::Message, memory::ConversationMemory};
+/// use rig_sqlite::SqliteConversationMemory;
+/// use tokio_rusqlite::Connection;
+///
+/// let conn = Connection::open("agent.db").await?;
+/// let memory = SqliteConversationMemory::new(conn).await?;
+///
+/// memory
+///     .append("thread-1", vec![Message::user("hello"), Message::assistant("hi")])
+///     .await?;
+/// assert_eq!(memory.load("thread-1").await?.len(), 2);
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct SqliteConversationMemory {
+    conn: Connection,
+    table_name: String,
+}
+
+/// Summary of one stored conversation, returned by
+/// [`SqliteConversationMemory::list_conversations`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversationSummary {
+    /// The conversation id passed to [`ConversationMemory::append`].
+    pub conversation_id: String,
+    /// Number of stored messages.
+    pub message_count: u64,
+    /// When the first stored message was appended.
+    pub first_message_at: DateTime<Utc>,
+    /// When the most recent message was appended.
+    pub last_message_at: DateTime<Utc>,
+}
+
+impl SqliteConversationMemory {
+    /// Opens conversation memory in [`DEFAULT_CONVERSATION_TABLE`], creating
+    /// the table and its indexes if they do not exist.
+    pub async fn new(conn: Connection) -> Result<Self, MemoryError> {
+        Self::with_table_name(conn, DEFAULT_CONVERSATION_TABLE).await
+    }
+
+    /// Opens conversation memory in `table_name`, creating the table and its
+    /// indexes if they do not exist.
+    ///
+    /// `table_name` is interpolated into SQL, so it must be a plain
+    /// identifier (ASCII letters, digits and `_`, not starting with a digit).
+    pub async fn with_table_name(
+        conn: Connection,
+        table_name: impl Into<String>,
+    ) -> Result<Self, MemoryError> {
+        let table_name = table_name.into();
+        if !is_sql_identifier(&table_name) {
+            return Err(MemoryError::backend(SqliteInternalError::InvalidTableName(
+                table_name,
+            )));
+        }
+
+        let create_sql = format!(
+            "CREATE TABLE IF NOT EXISTS {table_name} (
+                conversation_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                message JSON NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (conversation_id, position)
+            );
+            CREATE INDEX IF NOT EXISTS idx_{table_name}_created_at ON {table_name}(created_at);"
+        );
+        conn.call(move |conn| Ok(conn.execute_batch(&create_sql)?))
+            .await
+            .map_err(MemoryError::backend)?;
+
+        Ok(Self { conn, table_name })
+    }
+
+    /// Returns the table messages are stored in.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Lists every stored conversation, most recently active first.
+    pub async fn list_conversations(&self) -> Result<Vec<ConversationSummary>, MemoryError> {
+        let sql = format!(
+            "SELECT conversation_id, COUNT(*), MIN(created_at), MAX(created_at)
+             FROM {}
+             GROUP BY conversation_id
+             ORDER BY MAX(created_at) DESC, conversation_id",
+            self.table_name
+        );
+        let rows = self
+            .conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, i64>(2)?,
+                            row.get::<_, i64>(3)?,
+                        ))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(MemoryError::backend)?;
+
+        rows.into_iter()
+            .map(|(conversation_id, count, first, last)| {
+                Ok(ConversationSummary {
+                    conversation_id,
+                    message_count: u64::try_from(count).unwrap_or(0),
+                    first_message_at: timestamp_from_millis(first)?,
+                    last_message_at: timestamp_from_millis(last)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Deletes every conversation whose most recent message was appended
+    /// before `cutoff`, returning the number of conversations removed.
+    ///
+    /// Conversations are removed whole: a thread that is still active keeps
+    /// its older messages.
+    pub async fn delete_conversations_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, MemoryError> {
+        let table_name = self.table_name.clone();
+        let cutoff = cutoff.timestamp_millis();
+        self.conn
+            .call(move |conn| {
+                let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+                let stale = {
+                    let mut stmt = tx.prepare(&format!(
+                        "SELECT conversation_id FROM {table_name}
+                         GROUP BY conversation_id
+                         HAVING MAX(created_at) < ?1"
+                    ))?;
+                    stmt.query_map([cutoff], |row| row.get::<_, String>(0))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?
+                };
+                let delete_sql = format!("DELETE FROM {table_name} WHERE conversation_id = ?1");
+                for conversation_id in &stale {
+                    tx.execute(&delete_sql, [conversation_id])?;
+                }
+                tx.commit()?;
+                Ok(stale.len() as u64)
+            })
+            .await
+            .map_err(MemoryError::backend)
+    }
+}
+
+impl std::fmt::Debug for SqliteConversationMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteConversationMemory")
+            .field("table_name", &self.table_name)
+            .finish()
+    }
+}
+
+impl ConversationMemory for SqliteConversationMemory {
+    fn load<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<Message>, MemoryError>> {
+        Box::pin(async move {
+            let sql = format!(
+                "SELECT message FROM {} WHERE conversation_id = ?1 ORDER BY position",
+                self.table_name
+            );
+            let conversation_id = conversation_id.to_owned();
+            let rows = self
+                .conn
+                .call(move |conn| {
+                    let mut stmt = conn.prepare(&sql)?;
+                    let rows = stmt
+                        .query_map([conversation_id], |row| row.get::<_, String>(0))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    Ok(rows)
+                })
+                .await
+                .map_err(MemoryError::backend)?;
+
+            rows.iter()
+                .map(|row| serde_json::from_str(row).map_err(MemoryError::backend))
+                .collect()
+        })
+    }
+
+    fn append<'a>(
+        &'a self,
+        conversation_id: &'a str,
+        messages: Vec<Message>,
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            if messages.is_empty() {
+                return Ok(());
+            }
+
+            let rows = messages
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(MemoryError::backend)?;
+            let table_name = self.table_name.clone();
+            let conversation_id = conversation_id.to_owned();
+            let created_at = Utc::now().timestamp_millis();
+
+            self.conn
+                .call(move |conn| {
+                    // `IMMEDIATE` takes the write lock before reading the next
+                    // position, so concurrent writers on the same file queue up
+                    // instead of both claiming the same positions.
+                    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+                    let next_position = tx.query_row(
+                        &format!(
+                            "SELECT COALESCE(MAX(position) + 1, 0) FROM {table_name} WHERE conversation_id = ?1"
+                        ),
+                        [&conversation_id],
+                        |row| row.get::<_, i64>(0),
+                    )?;
+                    {
+                        let mut stmt = tx.prepare(&format!(
+                            "INSERT INTO {table_name} (conversation_id, position, message, created_at)
+                             VALUES (?1, ?2, ?3, ?4)"
+                        ))?;
+                        for (position, message) in (next_position..).zip(rows) {
+                            stmt.execute(rusqlite::params![
+                                conversation_id,
+                                position,
+                                message,
+                                created_at
+                            ])?;
+                        }
+                    }
+                    tx.commit()?;
+                    Ok(())
+                })
+                .await
+                .map_err(MemoryError::backend)
+        })
+    }
+
+    fn clear<'a>(
+        &'a self,
+        conversation_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<(), MemoryError>> {
+        Box::pin(async move {
+            let sql = format!("DELETE FROM {} WHERE conversation_id = ?1", self.table_name);
+            let conversation_id = conversation_id.to_owned();
+            self.conn
+                .call(move |conn| {
+                    conn.execute(&sql, [conversation_id])?;
+                    Ok(())
+                })
+                .await
+                .map_err(MemoryError::backend)
+        })
+    }
+}
+
+pub(crate) fn is_sql_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn timestamp_from_millis(millis: i64) -> Result<DateTime<Utc>, MemoryError> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| MemoryError::backend(SqliteInternalError::InvalidTimestamp(millis)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn memory() -> anyhow::Result<SqliteConversationMemory> {
+        let conn = Connection::open_in_memory().await?;
+        Ok(SqliteConversationMemory::new(conn).await?)
+    }
+
+    #[tokio::test]
+    async fn round_trip_preserves_order_across_appends() -> anyhow::Result<()> {
+        let memory = memory().await?;
+        anyhow::ensure!(memory.load("c1").await?.is_empty());
+
+        memory
+            .append("c1", vec![Message::user("1"), Message::assistant("2")])
+            .await?;
+        memory
+            .append("c1", vec![Message::user("3"), Message::assistant("4")])
+            .await?;
+        memory.append("c2", vec![Message::user("other")]).await?;
+
+        let loaded = memory.load("c1").await?;
+        anyhow::ensure!(
+            loaded
+                == vec![
+                    Message::user("1"),
+                    Message::assistant("2"),
+                    Message::user("3"),
+                    Message::assistant("4"),
+                ],
+            "messages should load in append order, got {loaded:?}"
+        );
+        anyhow::ensure!(memory.load("c2").await?.len() == 1);
+
+        memory.clear("c1").await?;
+        anyhow::ensure!(memory.load("c1").await?.is_empty());
+        anyhow::ensure!(memory.load("c2").await?.len() == 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn concurrent_appends_do_not_interleave_turns() -> anyhow::Result<()> {
+        let memory = memory().await?;
+
+        let appends = (0..8).map(|turn| {
+            let memory = memory.clone();
+            tokio::spawn(async move {
+                memory
+                    .append(
+                        "shared",
+                        vec![
+                            Message::user(format!("q{turn}")),
+                            Message::assistant(format!("a{turn}")),
+                        ],
+                    )
+                    .await
+            })
+        });
+        for append in appends.collect::<Vec<_>>() {
+            append.await??;
+        }
+
+        let loaded = memory.load("shared").await?;
+        anyhow::ensure!(loaded.len() == 16, "expected 16 messages, got {loaded:?}");
+        for pair in loaded.chunks(2) {
+            let [Message::User { .. }, Message::Assistant { .. }] = pair else {
+                anyhow::bail!("turn was interleaved: {pair:?}");
+            };
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_and_delete_conversations_by_age() -> anyhow::Result<()> {
+        let memory = memory().await?;
+        memory.append("old", vec![Message::user("stale")]).await?;
+        memory.append("new", vec![Message::user("fresh")]).await?;
+
+        // Age the `old` thread by rewriting its timestamp directly.
+        let table_name = memory.table_name().to_owned();
+        memory
+            .conn
+            .call(move |conn| {
+                conn.execute(
+                    &format!(
+                        "UPDATE {table_name} SET created_at = 0 WHERE conversation_id = 'old'"
+                    ),
+                    [],
+                )?;
+                Ok(())
+            })
+            .await?;
+
+        let listed = memory.list_conversations().await?;
+        let ids = listed
+            .iter()
+            .map(|summary| summary.conversation_id.as_str())
+            .collect::<Vec<_>>();
+        anyhow::ensure!(ids == ["new", "old"], "unexpected listing {listed:?}");
+
+        let cutoff = Utc::now() - chrono::Duration::hours(1);
+        anyhow::ensure!(memory.delete_conversations_older_than(cutoff).await? == 1);
+        anyhow::ensure!(memory.load("old").await?.is_empty());
+        anyhow::ensure!(memory.load("new").await?.len() == 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_non_identifier_table_names() -> anyhow::Result<()> {
+        let conn = Connection::open_in_memory().await?;
+        let result = SqliteConversationMemory::with_table_name(conn, "messages; DROP").await;
+        anyhow::ensure!(
+            matches!(result, Err(MemoryError::Backend(_))),
+            "table name should be rejected, got {result:?}"
+        );
+
+        Ok(())
+    }
+}
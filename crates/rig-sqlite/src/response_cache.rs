@@ -0,0 +1,245 @@
+//! SQLite-backed [`ResponseCache`].
+
+use chrono::Utc;
+use rig_core::completion::cache::{CachedResponse, ResponseCache, ResponseCacheError};
+use rig_core::wasm_compat::WasmBoxedFuture;
+use tokio_rusqlite::Connection;
+
+use crate::SqliteInternalError;
+use crate::memory::is_sql_identifier;
+
+/// Default table used by [`SqliteResponseCache::new`].
+pub const DEFAULT_RESPONSE_CACHE_TABLE: &str = "rig_response_cache";
+
+/// A durable [`ResponseCache`] for
+/// [`CachedCompletionModel`](rig_core::completion::CachedCompletionModel)
+/// that keeps one row per cached response.
+///
+/// Rows are keyed by the request hash and carry the serialized
+/// [`CachedResponse`] as JSON, the near-duplicate partition (indexed, so
+/// semantic lookups only read matching rows) and a `created_at` timestamp
+/// (Unix milliseconds). Entries are never evicted; use
+/// [`SqliteResponseCache::clear`] to reset the cache.
+///
+/// Like [`crate::SqliteConversationMemory`], the table does not need the
+/// `sqlite-vec` extension and can share a [`Connection`] with the other
+/// stores in this crate.
+///
+/// ```no_run
+/// # async fn run<M: rig_core::completion::CompletionModel>(model: M) -> Result<(), Box<dyn std::error::Error>> {
+/// use rig_core::completion::CachedCompletionModel;
+/// use rig_sqlite::SqliteResponseCache;
+/// use tokio_rusqlite::Connection;
+///
+/// let conn = Connection::open("eval-cache.db").await?;
+/// let model = CachedCompletionModel::new(model, SqliteResponseCache::new(conn).await?);
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct SqliteResponseCache {
+    conn: Connection,
+    table_name: String,
+}
+
+impl SqliteResponseCache {
+    /// Opens a response cache in [`DEFAULT_RESPONSE_CACHE_TABLE`], creating
+    /// the table and its index if they do not exist.
+    pub async fn new(conn: Connection) -> Result<Self, ResponseCacheError> {
+        Self::with_table_name(conn, DEFAULT_RESPONSE_CACHE_TABLE).await
+    }
+
+    /// Opens a response cache in `table_name`, creating the table and its
+    /// index if they do not exist.
+    ///
+    /// `table_name` is interpolated into SQL, so it must be a plain
+    /// identifier (ASCII letters, digits and `_`, not starting with a digit).
+    pub async fn with_table_name(
+        conn: Connection,
+        table_name: impl Into<String>,
+    ) -> Result<Self, ResponseCacheError> {
+        let table_name = table_name.into();
+        if !is_sql_identifier(&table_name) {
+            return Err(ResponseCacheError::backend(
+                SqliteInternalError::InvalidTableName(table_name),
+            ));
+        }
+
+        let create_sql = format!(
+            "CREATE TABLE IF NOT EXISTS {table_name} (
+                cache_key TEXT PRIMARY KEY,
+                partition TEXT,
+                entry JSON NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_{table_name}_partition ON {table_name}(partition);"
+        );
+        conn.call(move |conn| Ok(conn.execute_batch(&create_sql)?))
+            .await
+            .map_err(ResponseCacheError::backend)?;
+
+        Ok(Self { conn, table_name })
+    }
+
+    /// Returns the table responses are stored in.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Removes every cached response.
+    pub async fn clear(&self) -> Result<(), ResponseCacheError> {
+        let sql = format!("DELETE FROM {}", self.table_name);
+        self.conn
+            .call(move |conn| {
+                conn.execute(&sql, [])?;
+                Ok(())
+            })
+            .await
+            .map_err(ResponseCacheError::backend)
+    }
+}
+
+impl std::fmt::Debug for SqliteResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteResponseCache")
+            .field("table_name", &self.table_name)
+            .finish()
+    }
+}
+
+impl ResponseCache for SqliteResponseCache {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            let sql = format!("SELECT entry FROM {} WHERE cache_key = ?1", self.table_name);
+            let key = key.to_owned();
+            let row = self
+                .conn
+                .call(move |conn| {
+                    let mut stmt = conn.prepare(&sql)?;
+                    let mut rows = stmt.query_map([key], |row| row.get::<_, String>(0))?;
+                    Ok(rows.next().transpose()?)
+                })
+                .await
+                .map_err(ResponseCacheError::backend)?;
+
+            row.map(|row| serde_json::from_str(&row).map_err(ResponseCacheError::from))
+                .transpose()
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+    ) -> WasmBoxedFuture<'a, Result<(), ResponseCacheError>> {
+        Box::pin(async move {
+            let row = serde_json::to_string(&entry)?;
+            let partition = entry.semantic.map(|semantic| semantic.partition);
+            let sql = format!(
+                "INSERT INTO {} (cache_key, partition, entry, created_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(cache_key) DO UPDATE SET
+                     partition = excluded.partition,
+                     entry = excluded.entry,
+                     created_at = excluded.created_at",
+                self.table_name
+            );
+            let key = key.to_owned();
+            let created_at = Utc::now().timestamp_millis();
+            self.conn
+                .call(move |conn| {
+                    conn.execute(&sql, rusqlite::params![key, partition, row, created_at])?;
+                    Ok(())
+                })
+                .await
+                .map_err(ResponseCacheError::backend)
+        })
+    }
+
+    fn semantic_candidates<'a>(
+        &'a self,
+        partition: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Vec<CachedResponse>, ResponseCacheError>> {
+        Box::pin(async move {
+            let sql = format!("SELECT entry FROM {} WHERE partition = ?1", self.table_name);
+            let partition = partition.to_owned();
+            let rows = self
+                .conn
+                .call(move |conn| {
+                    let mut stmt = conn.prepare(&sql)?;
+                    let rows = stmt
+                        .query_map([partition], |row| row.get::<_, String>(0))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    Ok(rows)
+                })
+                .await
+                .map_err(ResponseCacheError::backend)?;
+
+            rows.iter()
+                .map(|row| serde_json::from_str(row).map_err(ResponseCacheError::from))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rig_core::completion::{
+        AssistantContent, CompletionResponse, Usage,
+        cache::{CachedResponse, SemanticKey},
+    };
+
+    use super::*;
+
+    fn entry(text: &str, partition: Option<&str>) -> CachedResponse {
+        CachedResponse {
+            response: CompletionResponse::new(
+                vec![AssistantContent::text(text)],
+                Usage::new(),
+                "mock",
+            ),
+            semantic: partition.map(|partition| SemanticKey {
+                partition: partition.to_owned(),
+                embedding: vec![1.0, 0.0],
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn stores_replaces_and_scans_entries() -> anyhow::Result<()> {
+        let conn = Connection::open_in_memory().await?;
+        let cache = SqliteResponseCache::new(conn).await?;
+        anyhow::ensure!(cache.get("k1").await?.is_none());
+
+        cache.put("k1", entry("first", Some("p"))).await?;
+        cache.put("k1", entry("second", Some("p"))).await?;
+        cache.put("k2", entry("other", None)).await?;
+
+        let loaded = cache
+            .get("k1")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("k1 should be stored"))?;
+        anyhow::ensure!(
+            loaded.response.choice == vec![AssistantContent::text("second")],
+            "a put should replace the previous entry, got {loaded:?}"
+        );
+        anyhow::ensure!(cache.semantic_candidates("p").await?.len() == 1);
+        anyhow::ensure!(cache.semantic_candidates("q").await?.is_empty());
+
+        cache.clear().await?;
+        anyhow::ensure!(cache.get("k2").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_non_identifier_table_names() -> anyhow::Result<()> {
+        let conn = Connection::open_in_memory().await?;
+        let result = SqliteResponseCache::with_table_name(conn, "cache; DROP").await;
+        anyhow::ensure!(
+            matches!(result, Err(ResponseCacheError::Backend(_))),
+            "table name should be rejected, got {result:?}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+//! SQLite-backed [`RunStore`] for resumable agent runs.
+
+use chrono::Utc;
+use rig_agent::agent::run::store::{RunCheckpoint, RunStore, RunStoreError};
+use rig_core::wasm_compat::WasmBoxedFuture;
+use tokio_rusqlite::Connection;
+
+use crate::SqliteInternalError;
+use crate::memory::is_sql_identifier;
+
+/// Default table used by [`SqliteRunStore::new`].
+pub const DEFAULT_RUN_TABLE: &str = "rig_agent_runs";
+
+/// A durable [`RunStore`] that keeps one row per checkpointed agent run.
+///
+/// Rows are keyed by `run_id` and carry the serialized [`RunCheckpoint`] as
+/// JSON plus an `updated_at` timestamp (Unix milliseconds). Saves are a
+/// single upsert, so a crash mid-save leaves the previous checkpoint intact.
+///
+/// Like [`crate::SqliteConversationMemory`], the table does not need the
+/// `sqlite-vec` extension and can share a [`Connection`] with the other
+/// stores in this crate.
+///
+/// ```no_run
+/// # async fn run(agent: rig_agent::agent::Agent) -> Result<(), Box<dyn std::error::Error>> {
+/// use rig_agent::agent::AgentRunner;
+/// use rig_sqlite::SqliteRunStore;
+/// use tokio_rusqlite::Connection;
+///
+/// let conn = Connection::open("agent.db").await?;
+/// let store = SqliteRunStore::new(conn).await?;
+///
+/// let response = AgentRunner::resume(&agent, store, "job-42").run().await?;
+/// println!("{}", response.output);
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct SqliteRunStore {
+    conn: Connection,
+    table_name: String,
+}
+
+impl SqliteRunStore {
+    /// Opens a run store in [`DEFAULT_RUN_TABLE`], creating the table if it
+    /// does not exist.
+    pub async fn new(conn: Connection) -> Result<Self, RunStoreError> {
+        Self::with_table_name(conn, DEFAULT_RUN_TABLE).await
+    }
+
+    /// Opens a run store in `table_name`, creating the table if it does not
+    /// exist.
+    ///
+    /// `table_name` is interpolated into SQL, so it must be a plain
+    /// identifier (ASCII letters, digits and `_`, not starting with a digit).
+    pub async fn with_table_name(
+        conn: Connection,
+        table_name: impl Into<String>,
+    ) -> Result<Self, RunStoreError> {
+        let table_name = table_name.into();
+        if !is_sql_identifier(&table_name) {
+            return Err(RunStoreError::backend(
+                SqliteInternalError::InvalidTableName(table_name),
+            ));
+        }
+
+        let create_sql = format!(
+            "CREATE TABLE IF NOT EXISTS {table_name} (
+                run_id TEXT PRIMARY KEY,
+                checkpoint JSON NOT NULL,
+                updated_at INTEGER NOT NULL
+            );"
+        );
+        conn.call(move |conn| Ok(conn.execute_batch(&create_sql)?))
+            .await
+            .map_err(RunStoreError::backend)?;
+
+        Ok(Self { conn, table_name })
+    }
+
+    /// Returns the table checkpoints are stored in.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Lists the ids of every checkpointed run, most recently saved first.
+    pub async fn list_runs(&self) -> Result<Vec<String>, RunStoreError> {
+        let sql = format!(
+            "SELECT run_id FROM {} ORDER BY updated_at DESC, run_id",
+            self.table_name
+        );
+        self.conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&sql)?;
+                let rows = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(RunStoreError::backend)
+    }
+}
+
+impl std::fmt::Debug for SqliteRunStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteRunStore")
+            .field("table_name", &self.table_name)
+            .finish()
+    }
+}
+
+impl RunStore for SqliteRunStore {
+    fn save<'a>(
+        &'a self,
+        checkpoint: &'a RunCheckpoint,
+    ) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            let row = serde_json::to_string(checkpoint)?;
+            let sql = format!(
+                "INSERT INTO {} (run_id, checkpoint, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(run_id) DO UPDATE SET
+                     checkpoint = excluded.checkpoint,
+                     updated_at = excluded.updated_at",
+                self.table_name
+            );
+            let run_id = checkpoint.run_id.clone();
+            let updated_at = Utc::now().timestamp_millis();
+            self.conn
+                .call(move |conn| {
+                    conn.execute(&sql, rusqlite::params![run_id, row, updated_at])?;
+                    Ok(())
+                })
+                .await
+                .map_err(RunStoreError::backend)
+        })
+    }
+
+    fn load<'a>(
+        &'a self,
+        run_id: &'a str,
+    ) -> WasmBoxedFuture<'a, Result<Option<RunCheckpoint>, RunStoreError>> {
+        Box::pin(async move {
+            let sql = format!(
+                "SELECT checkpoint FROM {} WHERE run_id = ?1",
+                self.table_name
+            );
+            let run_id = run_id.to_owned();
+            let row = self
+                .conn
+                .call(move |conn| {
+                    let mut stmt = conn.prepare(&sql)?;
+                    let mut rows = stmt.query_map([run_id], |row| row.get::<_, String>(0))?;
+                    Ok(rows.next().transpose()?)
+                })
+                .await
+                .map_err(RunStoreError::backend)?;
+
+            row.map(|row| serde_json::from_str(&row).map_err(RunStoreError::from))
+                .transpose()
+        })
+    }
+
+    fn delete<'a>(&'a self, run_id: &'a str) -> WasmBoxedFuture<'a, Result<(), RunStoreError>> {
+        Box::pin(async move {
+            let sql = format!("DELETE FROM {} WHERE run_id = ?1", self.table_name);
+            let run_id = run_id.to_owned();
+            self.conn
+                .call(move |conn| {
+                    conn.execute(&sql, [run_id])?;
+                    Ok(())
+                })
+                .await
+                .map_err(RunStoreError::backend)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rig_agent::agent::AgentRun;
+    use rig_core::completion::Message;
+
+    use super::*;
+
+    fn checkpoint(run_id: &str, prompt: &str) -> RunCheckpoint {
+        RunCheckpoint {
+            run_id: run_id.to_owned(),
+            prompt: Message::user(prompt),
+            conversation_id: None,
+            appends_to_memory: false,
+            run: AgentRun::new(prompt),
+        }
+    }
+
+    #[tokio::test]
+    async fn saves_replace_and_delete_checkpoints() -> anyhow::Result<()> {
+        let conn = Connection::open_in_memory().await?;
+        let store = SqliteRunStore::new(conn).await?;
+        anyhow::ensure!(store.load("job-1").await?.is_none());
+
+        store.save(&checkpoint("job-1", "first")).await?;
+        store.save(&checkpoint("job-1", "second")).await?;
+        store.save(&checkpoint("job-2", "other")).await?;
+
+        let loaded = store
+            .load("job-1")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("job-1 should be stored"))?;
+        anyhow::ensure!(
+            loaded.prompt == Message::user("second"),
+            "a save should replace the previous checkpoint, got {loaded:?}"
+        );
+        anyhow::ensure!(store.list_runs().await?.len() == 2);
+
+        store.delete("job-1").await?;
+        store.delete("job-1").await?;
+        anyhow::ensure!(store.load("job-1").await?.is_none());
+        anyhow::ensure!(store.list_runs().await? == ["job-2"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_non_identifier_table_names() -> anyhow::Result<()> {
+        let conn = Connection::open_in_memory().await?;
+        let result = SqliteRunStore::with_table_name(conn, "runs; DROP").await;
+        anyhow::ensure!(
+            matches!(result, Err(RunStoreError::Backend(_))),
+            "table name should be rejected, got {result:?}"
+        );
+        Ok(())
+    }
+}
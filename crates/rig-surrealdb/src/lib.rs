@@ -173,6 +173,37 @@ impl SearchFilter for SurrealSearchFilter {
         Self(format!("{} < {}", key.as_ref(), value.to_sql()))
     }
 
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!(
+            "({key} != NONE) AND ({key} != {})",
+            value.to_sql(),
+            key = key.as_ref()
+        ))
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!("{} >= {}", key.as_ref(), value.to_sql()))
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self(format!("{} <= {}", key.as_ref(), value.to_sql()))
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::member(key.as_ref(), &Value::from_t(values))
+    }
+
+    fn exists(key: impl AsRef<str>) -> Self {
+        Self(format!(
+            "({key} != NONE) AND ({key} != NULL)",
+            key = key.as_ref()
+        ))
+    }
+
+    fn contains(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::contains(key.as_ref(), &value)
+    }
+
     fn and(self, rhs: Self) -> Self {
         Self(format!("({self}) AND ({rhs})"))
     }
@@ -180,6 +211,10 @@ impl SearchFilter for SurrealSearchFilter {
     fn or(self, rhs: Self) -> Self {
         Self(format!("({self}) OR ({rhs})"))
     }
+
+    fn not(self) -> Self {
+        Self(format!("NOT ({self})"))
+    }
 }
 
 impl SurrealSearchFilter {
@@ -374,7 +409,10 @@ mod tests {
     use rig_core::{
         client::Nothing,
         embeddings::{Embedding, EmbeddingError, EmbeddingModel, EmbeddingResponse},
-        vector_store::{VectorStoreIndexDyn, request::Filter},
+        vector_store::{
+            VectorStoreIndexDyn,
+            request::{Filter, SearchFilter},
+        },
     };
     use serde_json::json;
     use surrealdb::Surreal;
@@ -437,6 +475,26 @@ mod tests {
         assert!(sql.contains("tags: ['surreal', 'json']"));
     }
 
+    #[allow(clippy::panic)]
+    #[test]
+    fn filter_from_json_translates_extended_operators() {
+        let dynamic = Filter::In("kind".to_string(), vec![json!("a"), json!("b")])
+            .and(Filter::Contains("tags".to_string(), json!("rig")))
+            .and(Filter::Not(Box::new(Filter::Exists(
+                "archived_at".to_string(),
+            ))));
+        let filter = match SurrealSearchFilter::try_from(dynamic) {
+            Ok(filter) => filter,
+            Err(err) => panic!("unexpected surreal filter conversion failure: {err}"),
+        };
+
+        assert_eq!(
+            filter.to_string(),
+            "((kind IN ['a', 'b']) AND (tags CONTAINS 'rig')) \
+             AND (NOT ((archived_at != NONE) AND (archived_at != NULL)))"
+        );
+    }
+
     #[allow(clippy::panic)]
     #[tokio::test]
     async fn surreal_vector_store_supports_type_erased_queries() {
@@ -1,6 +1,6 @@
 //! Filter implementation for Cloudflare Vectorize.
 
-use rig_core::vector_store::request::SearchFilter;
+use rig_core::vector_store::request::{FilterOperator, SearchFilter};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
@@ -75,6 +75,44 @@ impl SearchFilter for VectorizeFilter {
         tracing::error!("Vectorize does not support OR filters. This filter will fail.");
         Self(json!({ "$unsupported_or": "Vectorize does not support OR filters" }))
     }
+
+    fn ne(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::ne(key, &value)
+    }
+
+    fn gte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::gte(key, &value)
+    }
+
+    fn lte(key: impl AsRef<str>, value: Self::Value) -> Self {
+        Self::lte(key, &value)
+    }
+
+    fn in_values(key: impl AsRef<str>, values: Vec<Self::Value>) -> Self {
+        Self::in_values(key, &values)
+    }
+
+    fn exists(_key: impl AsRef<str>) -> Self {
+        Self::unsupported("exists")
+    }
+
+    fn contains(_key: impl AsRef<str>, _value: Self::Value) -> Self {
+        Self::unsupported("contains")
+    }
+
+    fn not(self) -> Self {
+        Self::unsupported("not")
+    }
+
+    fn supports(op: FilterOperator) -> bool {
+        !matches!(
+            op,
+            FilterOperator::Or
+                | FilterOperator::Not
+                | FilterOperator::Exists
+                | FilterOperator::Contains
+        )
+    }
 }
 
 impl VectorizeFilter {
@@ -103,15 +141,28 @@ impl VectorizeFilter {
         Self(json!({ key.as_ref(): { "$nin": values } }))
     }
 
+    /// Marks the filter as using an operator Vectorize cannot express, in the
+    /// same way as [`SearchFilter::or`].
+    fn unsupported(op: &str) -> Self {
+        let op = op.to_uppercase();
+        tracing::error!("Vectorize does not support {op} filters. This filter will fail.");
+        Self(json!({
+            format!("$unsupported_{}", op.to_lowercase()):
+                format!("Vectorize does not support {op} filters")
+        }))
+    }
+
     /// Validates that the filter doesn't contain unsupported operations.
-    /// Returns an error if the filter contains OR operations.
+    /// Returns an error if the filter contains OR, NOT, EXISTS or CONTAINS
+    /// operations.
     pub fn validate(&self) -> Result<(), VectorizeError> {
         if let Some(obj) = self.0.as_object()
-            && obj.contains_key("$unsupported_or")
+            && let Some(op) = obj.keys().find_map(|key| key.strip_prefix("$unsupported_"))
         {
-            return Err(VectorizeError::UnsupportedFilterOperation(
-                "OR filters are not supported by Vectorize".to_string(),
-            ));
+            return Err(VectorizeError::UnsupportedFilterOperation(format!(
+                "{} filters are not supported by Vectorize",
+                op.to_uppercase()
+            )));
         }
         Ok(())
     }
@@ -233,6 +284,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unsupported_operators_fail_validation() {
+        let filters = [
+            (<VectorizeFilter as SearchFilter>::exists("a"), "EXISTS"),
+            (VectorizeFilter::contains("tags", json!("a")), "CONTAINS"),
+            (VectorizeFilter::eq("a", json!(1)).not(), "NOT"),
+        ];
+
+        for (filter, op) in filters {
+            let filter = filter.and(VectorizeFilter::eq("b", json!(2)));
+            assert!(
+                matches!(
+                    filter.validate(),
+                    Err(VectorizeError::UnsupportedFilterOperation(ref msg)) if msg.contains(op)
+                ),
+                "{op} filters should fail validation"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dynamic_filter_rejects_unsupported_operators() {
+        use rig_core::vector_store::request::{DynamicSearchFilter, Filter, FilterError};
+
+        let supported = Filter::in_values("category", vec![json!("a"), json!("b")])
+            .and(Filter::gte("score", json!(0.5)));
+        assert!(VectorizeFilter::from_dynamic_filter(supported).is_ok());
+
+        let negated = Filter::eq("category", json!("a")).not();
+        assert!(matches!(
+            VectorizeFilter::from_dynamic_filter(negated),
+            Err(FilterError::TypeError(op)) if op == "not"
+        ));
+    }
+
     #[test]
     fn test_empty_filter() {
         let filter = VectorizeFilter::new();
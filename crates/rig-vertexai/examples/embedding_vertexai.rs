@@ -0,0 +1,22 @@
+use rig_core::client::EmbeddingsClient;
+use rig_core::embeddings::EmbeddingModel;
+use rig_vertexai::{Client, embedding::TEXT_EMBEDDING_005};
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt().with_target(false).init();
+
+    // Uses ADC credentials and expects GOOGLE_CLOUD_PROJECT to be set.
+    let client = Client::from_env()?;
+    let model = client.embedding_model(TEXT_EMBEDDING_005);
+
+    let embeddings = model
+        .embed_texts(["Hello, world!".to_string(), "Goodbye, world!".to_string()])
+        .await?;
+
+    for embedding in embeddings {
+        println!("{}: {} dimensions", embedding.document, embedding.vec.len());
+    }
+
+    Ok(())
+}
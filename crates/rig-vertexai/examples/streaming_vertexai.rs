@@ -0,0 +1,22 @@
+use rig_agent::{agent::stream_to_stdout, prelude::*, streaming::StreamingPrompt};
+use rig_vertexai::{Client, completion::GEMINI_2_5_FLASH};
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    tracing_subscriber::fmt().with_target(false).init();
+
+    // Uses ADC credentials and expects GOOGLE_CLOUD_PROJECT to be set.
+    let agent = Client::from_env()?
+        .agent(GEMINI_2_5_FLASH)
+        .preamble("Be precise and concise.")
+        .build();
+
+    // Stream the response and print chunks as they arrive
+    let mut stream = agent
+        .stream_prompt("When and where and what type is the next solar eclipse?")
+        .await;
+
+    let _ = stream_to_stdout(&mut stream).await?;
+
+    Ok(())
+}
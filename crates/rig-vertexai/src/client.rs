@@ -1,8 +1,9 @@
 use crate::completion::CompletionModel;
+use crate::embedding::EmbeddingModel;
 use google_cloud_aiplatform_v1 as vertexai;
 use google_cloud_auth::credentials;
 use google_cloud_auth::credentials::Credentials;
-use rig_core::client::{CompletionClient, Nothing};
+use rig_core::client::{CompletionClient, EmbeddingsClient, Nothing};
 use rig_core::prelude::*;
 use std::sync::Arc;
 use thiserror::Error;
@@ -123,6 +124,7 @@ impl ClientBuilder {
             project,
             location,
             credentials,
+            http_client: rig_reqwest::ReqwestClient::default(),
             vertex_client: Arc::new(OnceCell::new()),
         })
     }
@@ -139,6 +141,9 @@ pub struct Client {
     project: String,
     location: String,
     credentials: Credentials,
+    /// Transport for `streamGenerateContent`, which the Vertex AI SDK does
+    /// not expose as an RPC.
+    pub(crate) http_client: rig_reqwest::ReqwestClient,
     pub(crate) vertex_client:
         Arc<OnceCell<Result<vertexai::client::PredictionService, VertexAiClientError>>>,
 }
@@ -203,6 +208,22 @@ impl Client {
         &self.location
     }
 
+    pub(crate) fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// The REST base URL for this client's location.
+    ///
+    /// The `global` location is served from `aiplatform.googleapis.com`;
+    /// every regional location has its own `{location}-aiplatform` host.
+    pub(crate) fn rest_base_url(&self) -> String {
+        if self.location == DEFAULT_LOCATION {
+            "https://aiplatform.googleapis.com".to_string()
+        } else {
+            format!("https://{}-aiplatform.googleapis.com", self.location)
+        }
+    }
+
     pub async fn get_inner(
         &self,
     ) -> Result<&vertexai::client::PredictionService, VertexAiClientError> {
@@ -249,9 +270,52 @@ impl CompletionClient for Client {
     }
 }
 
+impl EmbeddingsClient for Client {
+    type EmbeddingModel = EmbeddingModel;
+
+    fn embedding_model(&self, model: impl Into<String>) -> Self::EmbeddingModel {
+        EmbeddingModel::new(self.clone(), model, None)
+    }
+
+    fn embedding_model_with_ndims(
+        &self,
+        model: impl Into<String>,
+        ndims: usize,
+    ) -> Self::EmbeddingModel {
+        EmbeddingModel::new(self.clone(), model, Some(ndims))
+    }
+}
+
 impl VerifyClient for Client {
     async fn verify(&self) -> Result<(), VerifyError> {
         // No API endpoint to verify credentials - they're validated on first use
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use google_cloud_auth::credentials::anonymous;
+
+    fn client(location: &str) -> Client {
+        Client::builder()
+            .with_project("my-project")
+            .with_location(location)
+            .with_credentials(anonymous::Builder::new().build())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn rest_base_url_uses_the_regional_host_outside_global() {
+        assert_eq!(
+            client(DEFAULT_LOCATION).rest_base_url(),
+            "https://aiplatform.googleapis.com"
+        );
+        assert_eq!(
+            client("us-central1").rest_base_url(),
+            "https://us-central1-aiplatform.googleapis.com"
+        );
+    }
+}
@@ -4,6 +4,7 @@ use super::Client;
 use crate::types::{
     completion_request::VertexCompletionRequest, completion_response::VertexGenerateContentOutput,
 };
+use google_cloud_aiplatform_v1 as vertexai;
 use rig_core::completion::{
     CompletionError, CompletionModel as CompletionModelTrait, CompletionRequest, CompletionResponse,
 };
@@ -72,41 +73,18 @@ impl CompletionModel {
             "Vertex AI completion request: {request:?}"
         );
 
-        let vertex_request = VertexCompletionRequest(request);
+        let generate_content_request = self.generate_content_request(request)?;
 
-        let generation_config = vertex_request.generation_config()?;
-        let system_instruction = vertex_request.system_instruction();
-        let tools = vertex_request.tools();
-        let tool_config = vertex_request.tool_config();
-        let contents = vertex_request.contents()?;
-        let model_path = self.model_path();
-
-        let mut request_builder = self
+        let response = self
             .client
             .get_inner()
             .await
             .map_err(|error| CompletionError::ProviderError(error.to_string()))?
             .generate_content()
-            .set_model(&model_path)
-            .set_contents(contents);
-
-        if let Some(config) = generation_config {
-            request_builder = request_builder.set_generation_config(config);
-        }
-
-        if let Some(system_instruction) = system_instruction {
-            request_builder = request_builder.set_system_instruction(system_instruction);
-        }
-
-        if let Some(tools) = tools {
-            request_builder = request_builder.set_tools([tools]);
-        }
-
-        if let Some(tool_config) = tool_config {
-            request_builder = request_builder.set_tool_config(tool_config);
-        }
-
-        let response = request_builder.send().await.map_err(rpc_error)?;
+            .with_request(generate_content_request)
+            .send()
+            .await
+            .map_err(rpc_error)?;
 
         tracing::debug!(
             target: "rig_core::vertexai",
@@ -116,23 +94,46 @@ impl CompletionModel {
         Ok(VertexGenerateContentOutput(response))
     }
 
-    /// Vertex AI streaming is not implemented in this integration.
+    /// Open a `streamGenerateContent` stream whose terminal record stays
+    /// Vertex AI's own wire response.
     ///
-    /// Present for parity with the other providers' escape hatches so callers
-    /// get the same error from the raw and normalized paths.
+    /// [`CompletionModelTrait::stream`] calls it and normalizes the terminal,
+    /// so there is exactly one request either way.
     pub async fn raw_stream(
         &self,
-        _request: CompletionRequest,
+        request: CompletionRequest,
     ) -> Result<rig_core::streaming::RawStreamingResult<VertexGenerateContentOutput>, CompletionError>
     {
-        Err(streaming_unsupported())
+        tracing::debug!(
+            target: "rig_core::vertexai",
+            "Vertex AI streaming request: {request:?}"
+        );
+
+        let generate_content_request = self.generate_content_request(request)?;
+        crate::streaming::raw_stream(&self.client, generate_content_request).await
     }
-}
 
-fn streaming_unsupported() -> CompletionError {
-    CompletionError::ProviderError(
-        "Streaming is not supported for Vertex AI in this integration".to_string(),
-    )
+    /// Build the SDK request shared by the unary and streaming paths.
+    fn generate_content_request(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<vertexai::model::GenerateContentRequest, CompletionError> {
+        let vertex_request = VertexCompletionRequest(request);
+
+        let generation_config = vertex_request.generation_config()?;
+        let system_instruction = vertex_request.system_instruction();
+        let tools = vertex_request.tools();
+        let tool_config = vertex_request.tool_config();
+        let contents = vertex_request.contents()?;
+
+        Ok(vertexai::model::GenerateContentRequest::new()
+            .set_model(self.model_path())
+            .set_contents(contents)
+            .set_or_clear_generation_config(generation_config)
+            .set_or_clear_system_instruction(system_instruction)
+            .set_tools(tools)
+            .set_or_clear_tool_config(tool_config))
+    }
 }
 
 impl CompletionModelTrait for CompletionModel {
@@ -149,9 +150,10 @@ impl CompletionModelTrait for CompletionModel {
 
     async fn stream(
         &self,
-        _request: CompletionRequest,
+        request: CompletionRequest,
     ) -> Result<StreamingCompletionResponse, CompletionError> {
-        Err(streaming_unsupported())
+        let raw = self.raw_stream(request).await?;
+        Ok(crate::streaming::normalize(raw))
     }
 }
 
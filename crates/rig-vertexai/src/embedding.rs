@@ -0,0 +1,272 @@
+//! Vertex AI text embeddings.
+//!
+//! All supported models: <https://cloud.google.com/vertex-ai/generative-ai/docs/model-reference/text-embeddings-api>
+
+use google_cloud_aiplatform_v1 as vertexai;
+use rig_core::completion::Usage;
+use rig_core::embeddings::{self, EmbeddingError};
+use rig_core::wasm_compat::WasmCompatSend;
+use serde::Deserialize;
+
+use super::Client;
+use crate::types::completion_response::PROVIDER_NAME;
+
+/// `gemini-embedding-001` (3072 dimensions by default)
+pub const GEMINI_EMBEDDING_001: &str = "gemini-embedding-001";
+/// `text-embedding-005` (768 dimensions by default)
+pub const TEXT_EMBEDDING_005: &str = "text-embedding-005";
+/// `text-embedding-004` (768 dimensions by default)
+pub const TEXT_EMBEDDING_004: &str = "text-embedding-004";
+/// `text-multilingual-embedding-002` (768 dimensions by default)
+pub const TEXT_MULTILINGUAL_EMBEDDING_002: &str = "text-multilingual-embedding-002";
+
+/// Dimensions assumed for a model this crate does not know.
+const FALLBACK_NDIMS: usize = 768;
+
+/// Returns the default output dimensionality for known Vertex AI embedding
+/// models.
+fn model_default_ndims(model: &str) -> Option<usize> {
+    match model {
+        GEMINI_EMBEDDING_001 => Some(3072),
+        TEXT_EMBEDDING_005 | TEXT_EMBEDDING_004 | TEXT_MULTILINGUAL_EMBEDDING_002 => Some(768),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EmbeddingModel {
+    client: Client,
+    model: String,
+    ndims: usize,
+    /// Sent as `outputDimensionality` only when the caller asked for a size;
+    /// otherwise the model answers at its native size.
+    output_dimensionality: Option<usize>,
+}
+
+impl EmbeddingModel {
+    pub fn new(client: Client, model: impl Into<String>, dims: Option<usize>) -> Self {
+        let model = model.into();
+        Self {
+            ndims: dims
+                .or_else(|| model_default_ndims(&model))
+                .unwrap_or(FALLBACK_NDIMS),
+            output_dimensionality: dims,
+            client,
+            model,
+        }
+    }
+
+    fn model_path(&self) -> String {
+        format!(
+            "projects/{}/locations/{}/publishers/google/models/{}",
+            self.client.project(),
+            self.client.location(),
+            self.model
+        )
+    }
+
+    fn parameters(&self) -> Option<serde_json::Value> {
+        self.output_dimensionality
+            .map(|dims| serde_json::json!({ "outputDimensionality": dims }))
+    }
+}
+
+impl EmbeddingModel {
+    /// Perform the requests and return Vertex AI's native `predict` answers —
+    /// one `PredictResponse` per batch of at most
+    /// [`max_documents`](embeddings::EmbeddingModel::max_documents) texts, in
+    /// input order — instead of the normalized
+    /// [`embeddings::EmbeddingResponse`]. Same requests, transport, and error
+    /// path as [`embeddings::EmbeddingModel::embed_texts_response`].
+    pub async fn raw_embed_texts(
+        &self,
+        documents: impl IntoIterator<Item = String> + WasmCompatSend,
+    ) -> Result<Vec<vertexai::model::PredictResponse>, EmbeddingError> {
+        let documents: Vec<String> = documents.into_iter().collect();
+        let batch_size = embeddings::EmbeddingModel::max_documents(self);
+        let model_path = self.model_path();
+
+        let prediction_service = self
+            .client
+            .get_inner()
+            .await
+            .map_err(|error| EmbeddingError::ProviderError(error.to_string()))?;
+
+        let mut responses = Vec::with_capacity(documents.len().div_ceil(batch_size));
+        for batch in documents.chunks(batch_size) {
+            let instances = batch
+                .iter()
+                .map(|document| serde_json::json!({ "content": document }));
+
+            let response = prediction_service
+                .predict()
+                .set_endpoint(&model_path)
+                .set_instances(instances)
+                .set_or_clear_parameters(self.parameters())
+                .send()
+                .await
+                .map_err(rpc_error)?;
+
+            responses.push(response);
+        }
+
+        Ok(responses)
+    }
+}
+
+impl embeddings::EmbeddingModel for EmbeddingModel {
+    fn max_documents(&self) -> usize {
+        // Gemini embedding models take a single instance per `predict`; the
+        // `text-embedding` family takes up to 250.
+        if self.model.starts_with("gemini-embedding") {
+            1
+        } else {
+            250
+        }
+    }
+
+    fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    async fn embed_texts_response(
+        &self,
+        documents: impl IntoIterator<Item = String> + WasmCompatSend,
+    ) -> Result<embeddings::EmbeddingResponse, EmbeddingError> {
+        rig_core::telemetry::instrument_modality(
+            PROVIDER_NAME,
+            &self.model,
+            rig_core::telemetry::ModalityOperation::Embeddings,
+            async {
+                let documents: Vec<String> = documents.into_iter().collect();
+                let responses = self.raw_embed_texts(documents.clone()).await?;
+                let raw = serde_json::to_value(&responses)?;
+
+                let mut predictions = Vec::with_capacity(documents.len());
+                for response in responses {
+                    for prediction in response.predictions {
+                        predictions.push(serde_json::from_value::<Prediction>(prediction)?);
+                    }
+                }
+
+                if predictions.len() != documents.len() {
+                    return Err(EmbeddingError::ResponseError(
+                        "Number of returned embeddings does not match input".into(),
+                    ));
+                }
+
+                let input_tokens = predictions
+                    .iter()
+                    .filter_map(|prediction| prediction.embeddings.statistics.as_ref())
+                    .map(|statistics| statistics.token_count)
+                    .sum();
+
+                let embeddings = documents
+                    .into_iter()
+                    .zip(predictions)
+                    .map(|(document, prediction)| embeddings::Embedding {
+                        document,
+                        vec: prediction.embeddings.values,
+                    })
+                    .collect();
+
+                let mut usage = Usage::new();
+                usage.input_tokens = input_tokens;
+                usage.total_tokens = input_tokens;
+
+                let mut response =
+                    embeddings::EmbeddingResponse::new(embeddings, PROVIDER_NAME).with_usage(usage);
+                response.raw = raw;
+                Ok(response)
+            },
+        )
+        .await
+    }
+}
+
+impl rig_core::client::ConstructEmbeddingModel<Client> for EmbeddingModel {
+    fn construct(client: &Client, model: String, dims: Option<usize>) -> Self {
+        Self::new(client.clone(), model, dims)
+    }
+}
+
+/// One entry of a text-embedding `predict` response's `predictions`.
+#[derive(Debug, Deserialize)]
+struct Prediction {
+    embeddings: PredictionEmbeddings,
+}
+
+#[derive(Debug, Deserialize)]
+struct PredictionEmbeddings {
+    #[serde(default)]
+    values: Vec<f64>,
+    #[serde(default)]
+    statistics: Option<PredictionStatistics>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PredictionStatistics {
+    /// Vertex reports this as a JSON number that is not always integral in
+    /// form (`7.0`), so it is read as a float and truncated.
+    #[serde(default, deserialize_with = "deserialize_token_count")]
+    token_count: u64,
+}
+
+fn deserialize_token_count<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let count = f64::deserialize(deserializer)?;
+    Ok(count.max(0.0) as u64)
+}
+
+/// Map a failed `predict` call into an [`EmbeddingError`] that preserves the
+/// provider's error text verbatim, exactly as the completion path's
+/// `rpc_error` does: the SDK transport exposes no [`http::StatusCode`], so
+/// the body is preserved with `status: None`.
+fn rpc_error(error: impl std::fmt::Display) -> EmbeddingError {
+    EmbeddingError::from_provider_body(error.to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_default_ndims_lookup() {
+        assert_eq!(model_default_ndims(GEMINI_EMBEDDING_001), Some(3072));
+        assert_eq!(model_default_ndims(TEXT_EMBEDDING_005), Some(768));
+        assert_eq!(model_default_ndims("unknown-model"), None);
+    }
+
+    #[test]
+    fn prediction_parses_values_and_fractional_token_count() {
+        let prediction: Prediction = serde_json::from_value(serde_json::json!({
+            "embeddings": {
+                "values": [0.25, -0.5],
+                "statistics": { "truncated": false, "token_count": 7.0 }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(prediction.embeddings.values, vec![0.25, -0.5]);
+        assert_eq!(
+            prediction
+                .embeddings
+                .statistics
+                .map(|statistics| statistics.token_count),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn rpc_error_preserves_raw_text_without_http_status() {
+        let raw = "status: InvalidArgument, message: \"bad instance\"";
+
+        let err = rpc_error(raw);
+
+        assert_eq!(err.provider_response_body(), Some(raw));
+        assert_eq!(err.provider_response_status(), None);
+    }
+}
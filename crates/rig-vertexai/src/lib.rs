@@ -10,8 +10,9 @@
 )]
 //! Google Cloud Vertex AI provider integration for Rig.
 //!
-//! This crate exposes Vertex AI hosted model completions through Rig's
-//! completion traits. Configure Google Cloud Application Default Credentials or
+//! This crate exposes Vertex AI hosted model completions, streaming
+//! completions (`streamGenerateContent`), and text embeddings through Rig's
+//! completion and embedding traits. Configure Google Cloud Application Default Credentials or
 //! provide credentials through Google Cloud's standard environment before
 //! constructing a client.
 //!
@@ -20,6 +21,8 @@
 
 pub mod client;
 pub mod completion;
+pub mod embedding;
+pub mod streaming;
 pub(crate) mod types;
 
 pub use client::{Client, ClientBuilder};
@@ -0,0 +1,564 @@
+//! Vertex AI `streamGenerateContent` streaming.
+//!
+//! The Vertex AI SDK exposes no streaming RPC, so the stream is opened over
+//! the REST surface (`:streamGenerateContent?alt=sse`) with the same request
+//! body the unary path sends, authenticated with the client's credentials.
+//! Chunks decode into the SDK's own `GenerateContentResponse`, so the
+//! terminal record is the same type [`CompletionModel::raw_completion`]
+//! returns.
+//!
+//! [`CompletionModel::raw_completion`]: crate::completion::CompletionModel::raw_completion
+
+use async_stream::stream;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures::StreamExt;
+use google_cloud_aiplatform_v1 as vertexai;
+use google_cloud_auth::credentials::CacheableResource;
+use rig_core::completion::CompletionError;
+use rig_core::http_client::sse::{Event, GenericEventSource};
+use rig_core::providers::internal::adapter::{
+    AdapterOutput, WireAdapter, WireFrame, run_wire_stream,
+};
+use rig_core::providers::internal::chunk_lifecycle::{ChunkParts, MintedReasoningLifecycle};
+use rig_core::providers::internal::wire::{self, WireEvent};
+use rig_core::streaming;
+use rig_core::wasm_compat::WasmCompatSend;
+
+use crate::client::Client;
+use crate::types::completion_response::{
+    PROVIDER_NAME, VertexGenerateContentOutput, map_finish_reason, map_usage,
+    tool_protocol_finish_reason_error,
+};
+
+/// The recognizability markers of a `streamGenerateContent` chunk: every
+/// genuine frame carries `candidates` and/or `usageMetadata`. A frame with
+/// either must fully decode (else `Corrupt`); other JSON is `Unknown`.
+const RECOGNIZABLE_CHUNK_KEYS: &[&str] = &["candidates", "usageMetadata"];
+
+/// Vertex thought parts carry no id or block boundaries; a per-stream constant
+/// minted identity keeps every thought delta merging into one item. Minted, so
+/// it can never reach a request.
+const REASONING_ID: streaming::StreamPartId =
+    streaming::StreamPartId::minted(streaming::MintKind::Reasoning, 0);
+
+/// The Vertex AI SSE wire as a [`WireAdapter`].
+///
+/// Vertex serves the same chunk stream as the Gemini API's
+/// `streamGenerateContent`, so the policy is that provider's: a `finishReason`
+/// can arrive on an intermediate chunk when a built-in tool runs a round, and
+/// the terminal record is deferred to EOF, carrying the last reason, usage,
+/// and metadata the stream reported.
+struct VertexAdapter {
+    /// Owns the constant-key thought lifecycle, so the ends this wire never
+    /// announces are derived by the shared helper rather than hand-rolled.
+    reasoning: MintedReasoningLifecycle,
+    /// Per-stream minter for tool-call keys: Vertex function calls carry no
+    /// id, and a fresh key per call keeps two same-tool calls distinct.
+    tool_ids: streaming::SyntheticIds,
+    usage: Option<vertexai::model::generate_content_response::UsageMetadata>,
+    model_version: String,
+    response_id: String,
+    finish_reason: Option<vertexai::model::candidate::FinishReason>,
+    finish_message: Option<String>,
+    /// A tool-protocol finish reason ended the turn; later frames are dead,
+    /// and no terminal may dress the failure up as a completed turn.
+    failed: bool,
+}
+
+impl Default for VertexAdapter {
+    fn default() -> Self {
+        Self {
+            reasoning: MintedReasoningLifecycle::new(REASONING_ID),
+            tool_ids: streaming::SyntheticIds::tool(),
+            usage: None,
+            model_version: String::new(),
+            response_id: String::new(),
+            finish_reason: None,
+            finish_message: None,
+            failed: false,
+        }
+    }
+}
+
+impl WireAdapter for VertexAdapter {
+    type Frame = WireFrame;
+    type Event = vertexai::model::GenerateContentResponse;
+    type Response = VertexGenerateContentOutput;
+
+    fn classify(&self, frame: WireFrame) -> WireEvent<Self::Event> {
+        wire::classify_marker_keyed_frame(&frame.as_str(), RECOGNIZABLE_CHUNK_KEYS)
+    }
+
+    fn interpret(&mut self, chunk: Self::Event, out: &mut AdapterOutput<Self::Response>) {
+        if self.failed {
+            return;
+        }
+
+        if !chunk.response_id.is_empty() {
+            self.response_id = chunk.response_id;
+        }
+        if !chunk.model_version.is_empty() {
+            self.model_version = chunk.model_version;
+        }
+        if chunk.usage_metadata.is_some() {
+            self.usage = chunk.usage_metadata;
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            return;
+        };
+
+        if candidate.finish_reason != vertexai::model::candidate::FinishReason::Unspecified {
+            // Last one wins: an intermediate reason is superseded by the one
+            // the turn actually ended on.
+            self.finish_reason = Some(candidate.finish_reason.clone());
+        }
+        if candidate.finish_message.is_some() {
+            self.finish_message = candidate.finish_message.clone();
+        }
+
+        if let Some(err) = tool_protocol_finish_reason_error(&candidate) {
+            self.failed = true;
+            out.push(Err(err));
+            return;
+        }
+
+        if let Some(content) = candidate.content {
+            for part in &content.parts {
+                let parts = self.interpret_part(part);
+                self.reasoning.emit_chunk(parts, out);
+            }
+        }
+    }
+
+    fn finish(&mut self, out: &mut AdapterOutput<Self::Response>) {
+        // EOF without a `finishReason` is truncation: synthesizing a terminal
+        // would report a successful completion the provider never sent.
+        let Some(finish_reason) = self.finish_reason.take() else {
+            return;
+        };
+
+        let mut candidate = vertexai::model::Candidate::new().set_finish_reason(finish_reason);
+        candidate.finish_message = self.finish_message.take();
+
+        let mut response = vertexai::model::GenerateContentResponse::new()
+            .set_candidates([candidate])
+            .set_model_version(std::mem::take(&mut self.model_version))
+            .set_response_id(std::mem::take(&mut self.response_id));
+        response.usage_metadata = self.usage.take();
+
+        out.push(Ok(streaming::RawStreamingChoice::FinalResponse(
+            VertexGenerateContentOutput(response),
+        )));
+    }
+
+    fn is_finished(&self) -> bool {
+        // A tool-protocol failure is the wire's in-band terminal: `interpret`
+        // already pushed the `Err`, so the driver stops reading.
+        self.failed
+    }
+}
+
+impl VertexAdapter {
+    /// Declare what one part carried; the shared lifecycle derives the event
+    /// sequence, exactly as the Gemini API provider does.
+    fn interpret_part(
+        &mut self,
+        part: &vertexai::model::Part,
+    ) -> ChunkParts<VertexGenerateContentOutput> {
+        // Same base64 encoding of the opaque signature bytes as the unary
+        // conversion, so a streamed turn replays identically.
+        let signature =
+            (!part.thought_signature.is_empty()).then(|| BASE64.encode(&part.thought_signature));
+
+        if let Some(function_call) = part.function_call() {
+            let args = function_call
+                .args
+                .clone()
+                .map(serde_json::Value::Object)
+                .unwrap_or_else(|| serde_json::json!({}));
+
+            let tool_call = streaming::RawStreamingToolCall::new(
+                self.tool_ids.mint(),
+                function_call.name.clone(),
+                args,
+            )
+            // A signature on a function-call part belongs to the call, not
+            // to the thought block.
+            .with_signature(signature);
+
+            ChunkParts {
+                reasoning: None,
+                reasoning_signature: None,
+                text: None,
+                tool_events: vec![streaming::RawStreamingChoice::ToolCall(tool_call)],
+            }
+        } else if let Some(text) = part.text() {
+            if part.thought {
+                ChunkParts {
+                    reasoning: Some(text.clone()),
+                    reasoning_signature: signature,
+                    text: None,
+                    tool_events: Vec::new(),
+                }
+            } else {
+                // A trailing non-thought part can carry the signature of the
+                // already-closed thought block.
+                ChunkParts {
+                    reasoning: None,
+                    reasoning_signature: signature,
+                    text: Some(text.clone()),
+                    tool_events: Vec::new(),
+                }
+            }
+        } else {
+            // Other part kinds (inline media, executable code) have no
+            // streamed carrier.
+            ChunkParts {
+                reasoning: None,
+                reasoning_signature: None,
+                text: None,
+                tool_events: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Drive `streamGenerateContent` SSE `data:` payloads through the full shared
+/// pipeline — frame triage, canonical grammar, terminal normalization.
+///
+/// The conformance seam: recorded or hand-written chunks feed the adapter
+/// directly, with no Google Cloud transport or credentials.
+pub fn stream_from_events(
+    frames: impl futures::Stream<Item = Result<WireFrame, CompletionError>> + WasmCompatSend + 'static,
+) -> streaming::StreamingCompletionResponse {
+    normalize(run_wire_stream(frames, VertexAdapter::default()))
+}
+
+/// Open a stream whose terminal record stays Vertex AI's own response.
+pub(crate) async fn raw_stream(
+    client: &Client,
+    request: vertexai::model::GenerateContentRequest,
+) -> Result<streaming::RawStreamingResult<VertexGenerateContentOutput>, CompletionError> {
+    let url = format!(
+        "{}/v1/{}:streamGenerateContent?alt=sse",
+        client.rest_base_url(),
+        request.model
+    );
+    let body = serde_json::to_vec(&request)?;
+
+    let mut builder =
+        http::Request::post(url).header(http::header::CONTENT_TYPE, "application/json");
+    if let Some(headers) = builder.headers_mut() {
+        headers.extend(auth_headers(client).await?);
+    }
+    let request = builder
+        .body(body)
+        .map_err(|error| CompletionError::HttpError(error.into()))?;
+
+    let mut event_source = GenericEventSource::new(client.http_client.clone(), request);
+
+    // Transport layer: SSE payloads only — classification and policy live in
+    // the shared driver.
+    let frames = stream! {
+        while let Some(event) = event_source.next().await {
+            match event {
+                Ok(Event::Open) => tracing::trace!("SSE connection opened"),
+                Ok(Event::Message(message)) => {
+                    if !message.data.trim().is_empty() {
+                        yield Ok(WireFrame::Text(message.data));
+                    }
+                }
+                Err(rig_core::http_client::Error::StreamEnded) => break,
+                Err(error) => {
+                    tracing::error!(?error, "SSE error");
+                    yield Err(transport_error(error));
+                    break;
+                }
+            }
+        }
+        event_source.close();
+    };
+
+    Ok(run_wire_stream(frames, VertexAdapter::default()))
+}
+
+/// Map an SSE transport error the way rig-core's own SSE wires do: a
+/// non-success HTTP response stays an [`CompletionError::HttpError`] so its
+/// status and body remain readable, and any other failure is a
+/// `ProviderError`.
+fn transport_error(error: rig_core::http_client::Error) -> CompletionError {
+    use rig_core::http_client::Error;
+
+    match error {
+        Error::InvalidStatusCode(_)
+        | Error::InvalidStatusCodeWithMessage(..)
+        | Error::InvalidStatusCodeWithDetails { .. } => CompletionError::HttpError(error),
+        error => CompletionError::ProviderError(error.to_string()),
+    }
+}
+
+/// The client's credentials as request headers.
+///
+/// A credentials failure is a Rig-side setup failure, not a provider
+/// response, so it stays a `ProviderError` like the SDK client-init failure.
+async fn auth_headers(client: &Client) -> Result<http::HeaderMap, CompletionError> {
+    let headers = client
+        .credentials()
+        .headers(http::Extensions::new())
+        .await
+        .map_err(|error| {
+            CompletionError::ProviderError(format!("failed to obtain credentials: {error}"))
+        })?;
+
+    match headers {
+        CacheableResource::New { data, .. } => Ok(data),
+        // Only returned when the request carries a cached entity tag, which
+        // this one never does.
+        CacheableResource::NotModified => Err(CompletionError::ProviderError(
+            "credentials returned no headers".to_string(),
+        )),
+    }
+}
+
+/// Normalize the provider-native terminal record into rig's
+/// [`streaming::StreamFinal`].
+pub(crate) fn normalize(
+    raw: streaming::RawStreamingResult<VertexGenerateContentOutput>,
+) -> streaming::StreamingCompletionResponse {
+    let normalized = streaming::normalize_stream(raw, |response| {
+        let response = response.0;
+        let usage = map_usage(response.usage_metadata.as_ref());
+        let finish_reason = response
+            .candidates
+            .first()
+            .and_then(|candidate| map_finish_reason(&candidate.finish_reason));
+
+        Ok(streaming::StreamFinal::new(PROVIDER_NAME, usage)
+            .with_optional_finish_reason(finish_reason)
+            .with_optional_response_id(Some(response.response_id).filter(|id| !id.is_empty()))
+            .with_optional_model(Some(response.model_version).filter(|model| !model.is_empty())))
+    });
+
+    streaming::StreamingCompletionResponse::stream(PROVIDER_NAME, normalized)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use rig_core::completion::FinishReason;
+    use rig_core::message::{Reasoning, ReasoningContent};
+    use rig_core::streaming::StreamedAssistantContent;
+    use serde_json::json;
+
+    fn frames(chunks: Vec<serde_json::Value>) -> streaming::StreamingCompletionResponse {
+        stream_from_events(futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(WireFrame::Text(chunk.to_string()))),
+        ))
+    }
+
+    fn chunk(parts: serde_json::Value) -> serde_json::Value {
+        json!({ "candidates": [{ "content": { "role": "model", "parts": parts } }] })
+    }
+
+    async fn collect(
+        mut stream: streaming::StreamingCompletionResponse,
+    ) -> Vec<StreamedAssistantContent> {
+        let mut items = Vec::new();
+        while let Some(item) = stream.next().await {
+            items.push(item.expect("stream item should be ok"));
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn text_deltas_and_deferred_terminal_carry_usage_and_metadata() {
+        let stream = frames(vec![
+            chunk(json!([{ "text": "Hello" }])),
+            json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": ", world" }] },
+                    "finishReason": "STOP"
+                }],
+                "modelVersion": "gemini-2.5-flash",
+                "responseId": "resp-1",
+                "usageMetadata": {
+                    "promptTokenCount": 4,
+                    "candidatesTokenCount": 3,
+                    "thoughtsTokenCount": 2,
+                    "totalTokenCount": 9
+                }
+            }),
+        ]);
+
+        let items = collect(stream).await;
+        let text: String = items
+            .iter()
+            .filter_map(|item| match item {
+                StreamedAssistantContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello, world");
+
+        let Some(StreamedAssistantContent::Final(terminal)) = items.last() else {
+            panic!("the stream must end with its terminal record: {items:?}");
+        };
+        assert_eq!(terminal.provider, PROVIDER_NAME);
+        assert_eq!(terminal.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(terminal.response_id.as_deref(), Some("resp-1"));
+        assert_eq!(terminal.model.as_deref(), Some("gemini-2.5-flash"));
+        let usage = terminal.usage;
+        assert_eq!(usage.input_tokens, 4);
+        assert_eq!(usage.output_tokens, 3);
+        assert_eq!(usage.reasoning_tokens, 2);
+        assert_eq!(usage.total_tokens, 9);
+    }
+
+    // An intermediate `finishReason` (a built-in tool round) must not end the
+    // stream: the answer after it still reaches the consumer.
+    #[tokio::test]
+    async fn intermediate_finish_reason_does_not_drop_later_output() {
+        let stream = frames(vec![
+            json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": "first " }] },
+                    "finishReason": "STOP"
+                }]
+            }),
+            json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": "second" }] },
+                    "finishReason": "MAX_TOKENS"
+                }]
+            }),
+        ]);
+
+        let items = collect(stream).await;
+        let text: String = items
+            .iter()
+            .filter_map(|item| match item {
+                StreamedAssistantContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "first second");
+
+        let Some(StreamedAssistantContent::Final(terminal)) = items.last() else {
+            panic!("the stream must end with its terminal record: {items:?}");
+        };
+        assert_eq!(terminal.finish_reason, Some(FinishReason::Length));
+    }
+
+    #[tokio::test]
+    async fn signed_thought_parts_stream_as_one_signed_reasoning_block() {
+        let signature = BASE64.encode(b"opaque-signature");
+        let stream = frames(vec![
+            chunk(json!([{ "text": "think1 ", "thought": true }])),
+            json!({
+                "candidates": [{
+                    "content": {
+                        "role": "model",
+                        "parts": [{ "text": "think2", "thought": true, "thoughtSignature": signature }]
+                    },
+                    "finishReason": "STOP"
+                }]
+            }),
+        ]);
+
+        let blocks: Vec<Reasoning> = collect(stream)
+            .await
+            .into_iter()
+            .filter_map(|item| match item {
+                StreamedAssistantContent::Reasoning { reasoning, .. } => Some(reasoning),
+                _ => None,
+            })
+            .collect();
+        let signed = blocks
+            .last()
+            .expect("the signed part must yield a Reasoning block");
+        assert_eq!(
+            signed.content,
+            vec![ReasoningContent::Text {
+                text: "think1 think2".to_string(),
+                signature: Some(signature),
+            }]
+        );
+    }
+
+    // Vertex function calls carry no id: two calls to the same tool in one
+    // turn must still surface as two distinct calls with their arguments.
+    #[tokio::test]
+    async fn id_less_function_calls_stream_as_distinct_tool_calls() {
+        let stream = frames(vec![json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "functionCall": { "name": "add", "args": { "x": 1 } } },
+                        { "functionCall": { "name": "add", "args": { "x": 2 } } }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        })]);
+
+        let calls: Vec<_> = collect(stream)
+            .await
+            .into_iter()
+            .filter_map(|item| match item {
+                StreamedAssistantContent::ToolCall {
+                    tool_call,
+                    internal_call_id,
+                } => Some((tool_call, internal_call_id)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0.function.name, "add");
+        assert_eq!(calls[0].0.function.arguments, json!({ "x": 1 }));
+        assert_eq!(calls[1].0.function.arguments, json!({ "x": 2 }));
+        assert_ne!(calls[0].1, calls[1].1);
+    }
+
+    #[tokio::test]
+    async fn malformed_function_call_fails_the_stream_without_a_terminal() {
+        let mut stream = frames(vec![json!({
+            "candidates": [{
+                "finishReason": "MALFORMED_FUNCTION_CALL",
+                "finishMessage": "bad call"
+            }]
+        })]);
+
+        let mut saw_error = false;
+        while let Some(item) = stream.next().await {
+            match item {
+                Err(err) => {
+                    assert!(err.to_string().contains("MALFORMED_FUNCTION_CALL"));
+                    saw_error = true;
+                }
+                Ok(StreamedAssistantContent::Final(_)) => {
+                    panic!("an aborted turn must not report a terminal record")
+                }
+                Ok(_) => {}
+            }
+        }
+        assert!(saw_error);
+    }
+
+    #[tokio::test]
+    async fn eof_without_finish_reason_reports_no_terminal() {
+        let items = collect(frames(vec![chunk(json!([{ "text": "cut off" }]))])).await;
+
+        assert!(
+            !items
+                .iter()
+                .any(|item| matches!(item, StreamedAssistantContent::Final(_)))
+        );
+    }
+}
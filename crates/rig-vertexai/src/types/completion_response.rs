@@ -34,6 +34,59 @@ pub fn map_finish_reason(
     map_google_finish_reason(&wire_name)
 }
 
+/// Map Vertex AI's `usageMetadata` onto rig's [`Usage`]; absent metadata is
+/// the zero sentinel.
+pub fn map_usage(
+    usage: Option<&vertexai::model::generate_content_response::UsageMetadata>,
+) -> Usage {
+    usage
+        .map(|usage| Usage {
+            input_tokens: usage.prompt_token_count as u64,
+            output_tokens: usage.candidates_token_count as u64,
+            total_tokens: usage.total_token_count as u64,
+            // `prompt_token_count` is documented as "still the total
+            // effective prompt size... including the number of tokens in the
+            // cached content", so the cached count is a *subset* of the
+            // input count, matching the Gemini surface.
+            cached_input_tokens: usage.cached_content_token_count as u64,
+            // Vertex reports no cache-write counter.
+            cache_creation_input_tokens: 0,
+            tool_use_prompt_tokens: 0,
+            // Vertex reports `thoughts_token_count`, and rig has a field for
+            // it. Hardcoding zero here silently discarded the thinking spend
+            // on every Vertex response — on the sibling Gemini surface it is
+            // routinely the largest component of the bill.
+            reasoning_tokens: usage.thoughts_token_count as u64,
+        })
+        .unwrap_or_default()
+}
+
+/// Turn a tool-protocol terminal `finishReason` into an error, mirroring the
+/// Gemini API provider.
+///
+/// `MALFORMED_FUNCTION_CALL` means the turn aborted inside the tool protocol:
+/// the candidate carries no usable call, so reporting it as merely finished
+/// would let an agent loop read an aborted turn as a complete one.
+pub(crate) fn tool_protocol_finish_reason_error(
+    candidate: &vertexai::model::Candidate,
+) -> Option<CompletionError> {
+    use vertexai::model::candidate::FinishReason;
+
+    match &candidate.finish_reason {
+        reason @ FinishReason::MalformedFunctionCall => {
+            let message = candidate
+                .finish_message
+                .as_deref()
+                .unwrap_or("no finish message provided");
+            let wire_name = reason.name().unwrap_or("MALFORMED_FUNCTION_CALL");
+            Some(CompletionError::ResponseError(format!(
+                "Vertex AI stopped with finish_reason={wire_name}: {message}"
+            )))
+        }
+        _ => None,
+    }
+}
+
 impl TryFrom<VertexGenerateContentOutput> for CompletionResponse {
     type Error = CompletionError;
 
@@ -140,28 +193,7 @@ impl TryFrom<VertexGenerateContentOutput> for CompletionResponse {
 
         let choice = rig_core::message::require_non_empty_response(assistant_contents)?;
 
-        let usage = response
-            .usage_metadata
-            .as_ref()
-            .map(|usage| Usage {
-                input_tokens: usage.prompt_token_count as u64,
-                output_tokens: usage.candidates_token_count as u64,
-                total_tokens: usage.total_token_count as u64,
-                // `prompt_token_count` is documented as "still the total
-                // effective prompt size... including the number of tokens in the
-                // cached content", so the cached count is a *subset* of the
-                // input count, matching the Gemini surface.
-                cached_input_tokens: usage.cached_content_token_count as u64,
-                // Vertex reports no cache-write counter.
-                cache_creation_input_tokens: 0,
-                tool_use_prompt_tokens: 0,
-                // Vertex reports `thoughts_token_count`, and rig has a field for
-                // it. Hardcoding zero here silently discarded the thinking spend
-                // on every Vertex response — on the sibling Gemini surface it is
-                // routinely the largest component of the bill.
-                reasoning_tokens: usage.thoughts_token_count as u64,
-            })
-            .unwrap_or_default();
+        let usage = map_usage(response.usage_metadata.as_ref());
 
         let finish_reason = map_finish_reason(&candidate.finish_reason);
         let model = Some(response.model_version.clone()).filter(|model| !model.is_empty());
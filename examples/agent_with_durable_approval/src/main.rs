@@ -20,6 +20,11 @@
 //! - **edit**    → execute with human-supplied JSON arguments instead
 //! - **abort**   → stop the run
 //!
+//! To keep `AgentRunner` driving the loop instead, have a hook return
+//! `ToolCallAction::defer(..)`: the run ends with
+//! `PromptError::ApprovalRequired`, whose serializable `PendingApprovals` is
+//! resumed with `AgentRunner::resume_approvals` (see `rig::agent::approval`).
+//!
 //! The gate is **fail-closed**: empty/unknown input denies, and closed stdin
 //! (e.g. piped `< /dev/null`) aborts — a destructive tool never runs on ambiguous
 //! input. The prompt is a UX affordance, not a security boundary; enforce real
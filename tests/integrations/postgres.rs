@@ -8,13 +8,19 @@
 
 use rig::client::DefaultTransportBuilder as _;
 use rig::client::EmbeddingsClient;
-use rig::postgres::PostgresVectorStore;
+use rig::completion::Message;
+use rig::memory::{ConversationMemory, DemotionHook};
+use rig::postgres::{
+    PgSearchFilter, PostgresConversationMemory, PostgresDemotionStore, PostgresVectorStore,
+};
 use rig::providers::openai;
 use rig::vector_store::request::VectorSearchRequest;
 use rig::{
     Embed,
-    embeddings::EmbeddingsBuilder,
-    vector_store::{InsertDocuments, VectorStoreIndex},
+    embeddings::{Embedding, EmbeddingsBuilder},
+    vector_store::{
+        DeleteDocuments, InsertDocuments, UpsertDocuments, VectorStoreIndex, request::SearchFilter,
+    },
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -166,6 +172,167 @@ async fn vector_search_test() {
     assert_eq!(id, full_query_id);
 }
 
+#[tokio::test]
+async fn delete_and_upsert_test() {
+    if skip_if_docker_unavailable("delete_and_upsert_test") {
+        return;
+    }
+
+    let container = start_container().await;
+    let host = container.get_host().await.unwrap().to_string();
+    let port = container
+        .get_host_port_ipv4(POSTGRES_PORT)
+        .await
+        .expect("Error getting docker port");
+    let pg_pool = connect_to_postgres(host, port).await;
+    sqlx::migrate!("./tests/migrations")
+        .run(&pg_pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let openai_mock = create_openai_mock_service().await;
+    let openai_client = openai::Client::builder()
+        .api_key("TEST")
+        .base_url(openai_mock.base_url())
+        .build()
+        .unwrap();
+    let model = openai_client.embedding_model(openai::TEXT_EMBEDDING_3_SMALL);
+    let vector_store = PostgresVectorStore::with_defaults(model, pg_pool.clone());
+
+    let id = |n: u8| format!("00000000-0000-0000-0000-00000000000{n}");
+    let word = |n: u8, name: &str| Word {
+        id: id(n),
+        name: name.to_string(),
+        definition: format!("Definition of a *{name}*"),
+    };
+    let embedding = |x: f64| Embedding {
+        document: "chunk".to_string(),
+        vec: vec![x; 1536],
+    };
+    let rows = || async {
+        sqlx::query_scalar::<_, i64>("SELECT count(*) FROM documents")
+            .fetch_one(&pg_pool)
+            .await
+            .expect("Failed to fetch documents count")
+    };
+
+    vector_store
+        .upsert_documents(vec![
+            (
+                id(1),
+                word(1, "flurbo"),
+                vec![embedding(0.1), embedding(0.2)],
+            ),
+            (id(2), word(2, "glarb-glarb"), vec![embedding(0.3)]),
+            (id(3), word(3, "linglingdong"), vec![embedding(0.4)]),
+        ])
+        .await
+        .expect("Failed to upsert documents");
+    assert_eq!(rows().await, 4);
+
+    // Re-upserting with fewer chunks drops the stale embedding.
+    vector_store
+        .upsert_documents(vec![(id(1), word(1, "flurbo-v2"), vec![embedding(0.1)])])
+        .await
+        .expect("Failed to re-upsert document");
+    assert_eq!(rows().await, 3);
+    let name: String =
+        sqlx::query_scalar("SELECT document->>'name' FROM documents WHERE id = $1::uuid")
+            .bind(id(1))
+            .fetch_one(&pg_pool)
+            .await
+            .unwrap();
+    assert_eq!(name, "flurbo-v2");
+
+    vector_store
+        .delete_documents(vec![id(2)])
+        .await
+        .expect("Failed to delete by id");
+    assert_eq!(rows().await, 2);
+
+    vector_store
+        .delete_documents_where(PgSearchFilter::eq(
+            "document->>'name'",
+            json!("linglingdong"),
+        ))
+        .await
+        .expect("Failed to delete by filter");
+    assert_eq!(rows().await, 1);
+
+    assert!(
+        vector_store
+            .delete_documents(vec!["not-a-uuid".to_string()])
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn conversation_memory_test() {
+    if skip_if_docker_unavailable("conversation_memory_test") {
+        return;
+    }
+
+    let container = start_container().await;
+    let host = container.get_host().await.unwrap().to_string();
+    let port = container
+        .get_host_port_ipv4(POSTGRES_PORT)
+        .await
+        .expect("Error getting docker port");
+    let pg_pool = connect_to_postgres(host, port).await;
+
+    let memory = PostgresConversationMemory::with_defaults(pg_pool.clone());
+    memory.create_table().await.expect("Failed to create table");
+
+    // Two workers appending to the same conversation must not interleave turns.
+    let appends = (0..16).map(|turn| {
+        let memory = memory.clone();
+        tokio::spawn(async move {
+            memory
+                .append(
+                    "shared",
+                    vec![
+                        Message::user(format!("q{turn}")),
+                        Message::assistant(format!("a{turn}")),
+                    ],
+                )
+                .await
+        })
+    });
+    for append in appends.collect::<Vec<_>>() {
+        append.await.unwrap().expect("append failed");
+    }
+
+    let history = memory.load("shared").await.expect("load failed");
+    assert_eq!(history.len(), 32);
+    for pair in history.chunks(2) {
+        assert!(
+            matches!(pair, [Message::User { .. }, Message::Assistant { .. }]),
+            "turn was interleaved: {pair:?}"
+        );
+    }
+
+    memory.clear("shared").await.expect("clear failed");
+    assert!(memory.load("shared").await.unwrap().is_empty());
+
+    // Redelivered demotions are archived once.
+    let archive = PostgresDemotionStore::with_defaults(pg_pool);
+    archive
+        .create_table()
+        .await
+        .expect("Failed to create table");
+    let demoted = vec![Message::user("first"), Message::assistant("second")];
+    archive
+        .on_demote("archived", demoted.clone())
+        .await
+        .expect("demotion failed");
+    archive
+        .on_demote("archived", demoted.clone())
+        .await
+        .expect("redelivered demotion failed");
+    assert_eq!(archive.load_archived("archived").await.unwrap(), demoted);
+}
+
 async fn start_container() -> ContainerAsync<GenericImage> {
     // Setup a local postgres container for testing. NOTE: docker service must be running.
     GenericImage::new("pgvector/pgvector", "pg17")